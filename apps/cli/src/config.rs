@@ -0,0 +1,181 @@
+//! Config commands - check, read and change settings without editing
+//! echovault.toml by hand.
+//!
+//! Keys are dotted (`sync.remote_name`, `embedding.chunk_size`); values are
+//! typed after the current one, as for `ECHOVAULT_*` overrides (lists also
+//! accept `a,b,c`). A change that would add a configuration error is not
+//! saved. `config set vault_path <path>` moves the existing vault (session
+//! files and databases) to the new location before pointing the config at it.
+
+use crate::output::{self, say};
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::storage::relocate::relocate_vault;
+use echovault_core::Config;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Check the config for problems (like `doctor`, but unknown keys fail)
+    Check,
+    /// Print a config value
+    Get {
+        /// Dotted config key (e.g. `sync.remote_name`)
+        key: String,
+    },
+    /// Set a config value
+    Set {
+        /// Dotted config key (e.g. `sync.remote_name`, `vault_path`)
+        key: String,
+
+        /// New value
+        value: String,
+    },
+    /// Reset a config value to its default
+    Unset {
+        /// Dotted config key
+        key: String,
+    },
+}
+
+pub fn cmd_config(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Check => crate::cmd_doctor(true),
+        ConfigAction::Get { key } => get_value(&key),
+        ConfigAction::Set { key, value } if key == "vault_path" => {
+            set_vault_path(PathBuf::from(value))
+        }
+        ConfigAction::Unset { key } if key == "vault_path" => {
+            bail!("vault_path cannot be unset; `config set vault_path <path>` moves the vault")
+        }
+        ConfigAction::Set { key, value } => set_value(&key, Some(&value)),
+        ConfigAction::Unset { key } => set_value(&key, None),
+    }
+}
+
+/// Print a value: strings as-is, anything else as TOML.
+fn get_value(key: &str) -> Result<()> {
+    let config = Config::load_default()?;
+    let value = config.get(key)?;
+    match &value {
+        Some(toml::Value::String(text)) => say!("{}", text),
+        Some(toml::Value::Table(table)) => say!("{}", toml::to_string_pretty(table)?.trim_end()),
+        Some(value) => say!("{}", value),
+        None => say!("{}", format!("{} is not set", key).dimmed()),
+    }
+    output::emit(&serde_json::json!({ "key": key, "value": value }))
+}
+
+/// Set (or with `None`, reset) a value and save the config, unless that
+/// adds a configuration error.
+fn set_value(key: &str, value: Option<&str>) -> Result<()> {
+    let mut config = Config::load_default()?;
+    let errors = |config: &Config| -> HashSet<String> {
+        config
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .map(|issue| issue.to_string())
+            .collect()
+    };
+    let before = errors(&config);
+    config.set(key, value)?;
+    let added: Vec<String> = errors(&config)
+        .into_iter()
+        .filter(|error| !before.contains(error))
+        .collect();
+    if !added.is_empty() {
+        bail!(
+            "Not saved, the change is invalid:\n  {}",
+            added.join("\n  ")
+        );
+    }
+
+    let path = config.save_default()?;
+    match config.get(key)? {
+        Some(new) => println!("{}", format!("✓ {} = {}", key, new).green()),
+        None => println!("{}", format!("✓ {} reset to its default", key).green()),
+    }
+    println!("{}", format!("Saved to {}", path.display()).dimmed());
+    Ok(())
+}
+
+/// Move the vault to a new path and save it in the config.
+fn set_vault_path(path: PathBuf) -> Result<()> {
+    let mut config = Config::load_default()?;
+    if let Some(env) = config.env_overrides.iter().find(|o| o.key == "vault_path") {
+        bail!("vault_path is set by {}; unset it first", env.var);
+    }
+    if crate::watch::is_running() {
+        bail!("`echovault-cli watch` is running; stop it before moving the vault");
+    }
+
+    let new_path = std::path::absolute(&path)?;
+    let old_path = config.vault_path.clone();
+
+    if old_path.is_dir() {
+        println!(
+            "Moving vault {} -> {}",
+            old_path.display(),
+            new_path.display()
+        );
+        let pb = ProgressBar::new(0);
+        pb.set_style(
+            ProgressStyle::with_template(
+                "  [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({percent}%)",
+            )
+            .unwrap()
+            .progress_chars("█▓░"),
+        );
+        let report = relocate_vault(&old_path, &new_path, |progress| {
+            pb.set_length(progress.total_bytes);
+            pb.set_position(progress.bytes_done);
+        })?;
+        pb.finish_and_clear();
+
+        println!(
+            "  Moved {} files ({} bytes{})",
+            report.files,
+            report.bytes,
+            if report.copied { ", copied" } else { "" }
+        );
+        if report.rewritten_paths > 0 {
+            println!(
+                "  Updated {} session paths in vault.db",
+                report.rewritten_paths
+            );
+        }
+        if let Some(leftover) = &report.leftover {
+            println!(
+                "  {}",
+                format!(
+                    "Warning: old vault could not be removed: {}",
+                    leftover.display()
+                )
+                .yellow()
+            );
+        }
+    } else {
+        println!(
+            "No vault at {}; only updating the config",
+            old_path.display()
+        );
+    }
+
+    config.vault_path = new_path.clone();
+    config.save_default().with_context(|| {
+        format!(
+            "Vault is now at {} but the config was not updated; set vault_path by hand",
+            new_path.display()
+        )
+    })?;
+    println!(
+        "{}",
+        format!("✓ vault_path = {}", new_path.display()).green()
+    );
+    println!("{}", "Restart the desktop app if it is running.".dimmed());
+    Ok(())
+}
@@ -0,0 +1,516 @@
+//! Encryption commands - set up and verify end-to-end encryption.
+//!
+//! The passphrase is optionally remembered in the OS keyring. Commands that
+//! need the key (e.g. `sync`) read it from `ECHOVAULT_PASSPHRASE`, then the
+//! keyring, and prompt as a last resort. age vaults are unlocked with the
+//! identity file from `[encryption] identity_file` instead.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use dialoguer::{Confirm, Input, Password};
+use echovault_core::crypto::manifest::{self, Manifest};
+use echovault_core::crypto::recovery::{self, RecoveryCode};
+use echovault_core::crypto::{self, age_backend, rotate, staging, EncryptionMeta, VaultKey};
+use echovault_core::secrets::{self, ExposeSecret, SecretString};
+use echovault_core::sync::{create_provider, SyncOptions};
+use echovault_core::{Config, VaultMetadata};
+
+#[derive(Subcommand)]
+pub enum EncryptionAction {
+    /// Enable encryption with a new passphrase (or join the remote's existing key)
+    Setup {
+        /// Encrypt to age recipients instead of a passphrase
+        /// (generates an identity file if none exists)
+        #[arg(long)]
+        age: bool,
+
+        /// Extra age recipient (repeatable; e.g. another machine or a hardware key)
+        #[arg(long = "recipient", value_name = "RECIPIENT")]
+        recipients: Vec<String>,
+    },
+    /// Check the passphrase (or age identity) and the integrity of encrypted files
+    Verify,
+    /// Show whether synced data is encrypted
+    Status,
+    /// Re-encrypt synced data under a new passphrase (resumes if interrupted)
+    RotateKey,
+    /// Generate a new recovery code (replaces the previous one)
+    RecoveryCode,
+    /// Unlock with the recovery code and set a new passphrase
+    Recover,
+}
+
+pub fn cmd_encryption(action: EncryptionAction) -> Result<()> {
+    match action {
+        EncryptionAction::Setup { age, recipients } => cmd_setup(age, recipients),
+        EncryptionAction::Verify => cmd_verify(),
+        EncryptionAction::Status => cmd_status(),
+        EncryptionAction::RotateKey => cmd_rotate_key(),
+        EncryptionAction::RecoveryCode => cmd_recovery_code(),
+        EncryptionAction::Recover => cmd_recover(),
+    }
+}
+
+fn cmd_setup(use_age: bool, mut extra_recipients: Vec<String>) -> Result<()> {
+    println!("{}", "🔒 EchoVault Encryption Setup".bold().cyan());
+    println!();
+
+    let config = crate::ensure_config()?;
+    // Recipients from [encryption] recipients come first, then --recipient
+    extra_recipients.splice(0..0, config.encryption.recipients.iter().cloned());
+    let use_age = use_age || !extra_recipients.is_empty();
+    let vault_dir = &config.vault_path;
+    let mut metadata = VaultMetadata::load_or_create(vault_dir)?;
+
+    // Another machine may already have encrypted the remote (or rotated its
+    // key): join that key instead of creating a new one
+    let provider = create_provider(&config);
+    if provider.is_authenticated() {
+        println!("Checking remote for an existing encrypted vault...");
+        if let Err(e) = provider.pull(
+            &staging::staging_dir(vault_dir),
+            &SyncOptions::for_sync(&config.sync),
+        ) {
+            println!("  {}", format!("Warning: {}", e).yellow());
+        }
+    }
+    let remote = staging::remote_meta(vault_dir)
+        .filter(|remote| metadata.encryption.as_ref() != Some(remote));
+
+    if metadata.is_encrypted() && remote.is_none() {
+        println!("{}", "✓ Encryption is already enabled".green());
+        println!(
+            "Run {} to check your passphrase.",
+            "echovault-cli encryption verify".cyan()
+        );
+        return Ok(());
+    }
+
+    let (key, passphrase) = if let Some(remote) = remote {
+        if remote.is_age() {
+            println!("Remote vault is encrypted with age. Unlocking with the identity file...");
+            let identity = age_backend::load_identity(&config.encryption.identity_path())?;
+            let key = remote.unlock_with_identity(identity.expose_secret())?;
+            metadata.encryption = Some(remote);
+            (key, None)
+        } else {
+            println!(
+                "{}",
+                "Remote vault is encrypted with another key. Enter its passphrase to join."
+                    .yellow()
+            );
+            let passphrase = prompt_passphrase("Passphrase", None)?;
+            let key = remote.unlock(passphrase.expose_secret())?;
+            metadata.encryption = Some(remote);
+            (key, Some(passphrase))
+        }
+    } else if use_age {
+        let (meta, key) = setup_age(&config, extra_recipients)?;
+        metadata.encryption = Some(meta);
+        (key, None)
+    } else {
+        let passphrase = prompt_passphrase("New passphrase", Some("Confirm passphrase"))?;
+        println!("Deriving key (Argon2id)...");
+        let (mut meta, key) = EncryptionMeta::create(passphrase.expose_secret())?;
+        let code = meta.add_recovery(&key)?;
+        show_recovery_code(&code)?;
+        metadata.encryption = Some(meta);
+        (key, Some(passphrase))
+    };
+
+    metadata.save(vault_dir)?;
+
+    let remember = match &passphrase {
+        Some(_) => Confirm::new()
+            .with_prompt("Remember passphrase in the OS keyring?")
+            .default(true)
+            .interact()?,
+        None => false,
+    };
+    if let Some(passphrase) = passphrase.filter(|_| remember) {
+        match secrets::set(&secrets::vault_passphrase_key(), passphrase.expose_secret()) {
+            Ok(()) => println!("  {}", "Passphrase stored in keyring".dimmed()),
+            Err(e) => println!("  {}", format!("Warning: {:#}", e).yellow()),
+        }
+    }
+
+    let opened = staging::open(vault_dir, &key, config.encryption.allow_missing_manifest)?;
+    if opened.decrypted > 0 {
+        println!(
+            "  Decrypted {} files from remote",
+            opened.decrypted.to_string().green()
+        );
+    }
+
+    println!();
+    println!("{}", "✓ Encryption enabled".green().bold());
+    println!("Session files, parsed output and the databases are encrypted before every push.");
+    if metadata
+        .encryption
+        .as_ref()
+        .is_some_and(|meta| meta.is_age())
+    {
+        println!(
+            "{}",
+            format!(
+                "Back up {:?}: without it (or another recipient's identity) the vault cannot be decrypted.",
+                config.encryption.identity_path()
+            )
+            .yellow()
+        );
+    } else {
+        println!(
+            "{}",
+            "Keep your passphrase and recovery code safe.".yellow()
+        );
+    }
+    println!(
+        "{}",
+        "Plaintext files already on the remote are not removed.".dimmed()
+    );
+
+    Ok(())
+}
+
+/// Print a recovery code and ask for a few of its words until they match.
+fn show_recovery_code(code: &RecoveryCode) -> Result<()> {
+    loop {
+        println!();
+        println!("{}", "Recovery code".bold());
+        println!(
+            "{}",
+            "Write these words down and keep them offline. They unlock the vault if you forget the passphrase."
+                .yellow()
+        );
+        for (row, words) in code.words().chunks(4).enumerate() {
+            let line: Vec<String> = words
+                .iter()
+                .enumerate()
+                .map(|(col, word)| format!("{:>2}. {:<10}", row * 4 + col + 1, word))
+                .collect();
+            println!("  {}", line.join("  "));
+        }
+        println!();
+
+        if !Confirm::new()
+            .with_prompt("I have written down the recovery code")
+            .default(true)
+            .interact()?
+        {
+            continue;
+        }
+        // Push the words off screen before the check
+        print!("\x1b[2J\x1b[H");
+
+        let mut answers = Vec::new();
+        for position in RecoveryCode::challenge(3) {
+            let word: String = Input::new()
+                .with_prompt(format!("Word #{}", position))
+                .interact_text()?;
+            answers.push((position, word));
+        }
+        if code.check_words(&answers) {
+            println!("{}", "✓ Recovery code confirmed".green());
+            return Ok(());
+        }
+        println!("{}", "Those words do not match. Please check again.".red());
+    }
+}
+
+/// Load (or generate) this machine's identity and create age encryption metadata.
+fn setup_age(config: &Config, extra_recipients: Vec<String>) -> Result<(EncryptionMeta, VaultKey)> {
+    let identity_path = config.encryption.identity_path();
+    let identity = if identity_path.exists() {
+        println!("Using age identity {:?}", identity_path);
+        age_backend::load_identity(&identity_path)?
+    } else {
+        let (identity, recipient) = age_backend::generate_identity();
+        age_backend::save_identity(&identity_path, identity.expose_secret())?;
+        println!("Generated age identity {:?}", identity_path);
+        println!("  Public key: {}", recipient.cyan());
+        identity
+    };
+
+    let mut recipients = age_backend::recipients_of(identity.expose_secret());
+    for recipient in extra_recipients {
+        if !recipients.contains(&recipient) {
+            recipients.push(recipient);
+        }
+    }
+    if recipients.is_empty() {
+        bail!("The identity file has no native identity; pass its recipient with --recipient");
+    }
+
+    let meta = EncryptionMeta::create_age(recipients)?;
+    let key = meta.unlock_with_identity(identity.expose_secret())?;
+    println!("  Encrypting to {} recipient(s)", meta.recipients.len());
+    Ok((meta, key))
+}
+
+fn cmd_verify() -> Result<()> {
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let Some(meta) = VaultMetadata::load_or_create(vault_dir)?.encryption else {
+        bail!("Encryption is not enabled. Run 'echovault-cli encryption setup' first.");
+    };
+
+    let key = if meta.is_age() {
+        let identity = age_backend::load_identity(&config.encryption.identity_path())?;
+        let key = meta.unlock_with_identity(identity.expose_secret())?;
+        println!("{}", "✓ Identity file can decrypt the vault".green());
+        key
+    } else {
+        let passphrase = prompt_passphrase("Passphrase", None)?;
+        let Ok(key) = meta.unlock(passphrase.expose_secret()) else {
+            bail!("Incorrect passphrase");
+        };
+        println!("{}", "✓ Passphrase is correct".green());
+        key
+    };
+
+    // Check staged files against the signed manifest
+    let staging = staging::staging_dir(vault_dir);
+    if Manifest::load(&staging)?.is_none() {
+        println!(
+            "{}",
+            "No integrity manifest yet (created on the next sync).".dimmed()
+        );
+        return Ok(());
+    }
+    println!("Checking encrypted files against the manifest...");
+    let report = manifest::verify_staging(&staging, &key)?;
+    println!("  {} files verified", report.verified.to_string().green());
+    for (label, names) in [
+        ("Corrupted", &report.corrupted),
+        ("Missing", &report.missing),
+        ("Not in manifest", &report.unlisted),
+    ] {
+        for name in names {
+            println!("  {} {}", format!("{}:", label).red(), name);
+        }
+    }
+    if !report.corrupted.is_empty() {
+        bail!(
+            "{} files do not match the manifest. Re-run 'echovault-cli sync' from a trusted machine.",
+            report.corrupted.len()
+        );
+    }
+    if report.is_clean() {
+        println!("{}", "✓ All encrypted files match the manifest".green());
+    }
+    Ok(())
+}
+
+fn cmd_status() -> Result<()> {
+    let config = crate::ensure_config()?;
+    match VaultMetadata::load_or_create(&config.vault_path)?.encryption {
+        Some(meta) => {
+            println!("Encryption: {}", "enabled".green());
+            println!("  Cipher:  {}", meta.algorithm);
+            println!("  KDF:     {}", meta.kdf);
+            if !meta.is_age() {
+                let recovery = match &meta.recovery {
+                    Some(recovery) => format!("set ({})", recovery.created_at).normal(),
+                    None => "none".dimmed(),
+                };
+                println!("  Recovery: {}", recovery);
+            }
+            for recipient in &meta.recipients {
+                println!("  Recipient: {}", recipient);
+            }
+            if meta.is_age() {
+                println!(
+                    "  Identity: {}",
+                    config.encryption.identity_path().display()
+                );
+            }
+            println!("  Since:   {}", meta.created_at.dimmed());
+        }
+        None => println!("Encryption: {}", "disabled".dimmed()),
+    }
+    Ok(())
+}
+
+/// Also `vault rekey`.
+pub(crate) fn cmd_rotate_key() -> Result<()> {
+    println!("{}", "🔑 EchoVault Key Rotation".bold().cyan());
+    println!();
+
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+    match VaultMetadata::load_or_create(vault_dir)?.encryption {
+        None => bail!("Encryption is not enabled. Run 'echovault-cli encryption setup' first."),
+        Some(meta) if meta.is_age() => {
+            bail!("rotate-key changes the passphrase; this vault is encrypted with age recipients")
+        }
+        Some(_) => {}
+    }
+
+    let resuming = rotate::pending_rotation(vault_dir).is_some();
+    if resuming {
+        println!("{}", "Resuming interrupted key rotation.".yellow());
+    }
+
+    let old_passphrase = prompt_passphrase("Current passphrase", None)?;
+    let new_passphrase = if resuming {
+        prompt_passphrase("New passphrase (as entered before)", None)?
+    } else {
+        prompt_passphrase("New passphrase", Some("Confirm new passphrase"))?
+    };
+
+    println!("Re-encrypting vault...");
+    let (_, stats) = rotate::rotate_key(
+        vault_dir,
+        old_passphrase.expose_secret(),
+        new_passphrase.expose_secret(),
+    )?;
+
+    // Keep a remembered passphrase in sync with the new key
+    if secrets::get_or_warn(&secrets::vault_passphrase_key()).is_some() {
+        if let Err(e) = secrets::set(
+            &secrets::vault_passphrase_key(),
+            new_passphrase.expose_secret(),
+        ) {
+            println!("  {}", format!("Warning: {:#}", e).yellow());
+        }
+    }
+
+    println!(
+        "  {} files re-encrypted, {} already rotated",
+        stats.rotated.to_string().green(),
+        stats.skipped
+    );
+    println!();
+    println!("{}", "✓ Key rotated".green().bold());
+    println!(
+        "Run {} to upload the re-encrypted vault. Other machines must re-enter the new passphrase.",
+        "echovault-cli sync".cyan()
+    );
+
+    Ok(())
+}
+
+fn cmd_recovery_code() -> Result<()> {
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let mut metadata = VaultMetadata::load_or_create(vault_dir)?;
+    let Some(key) = unlock_key(&config)? else {
+        bail!("Encryption is not enabled. Run 'echovault-cli encryption setup' first.");
+    };
+    let Some(meta) = metadata.encryption.as_mut() else {
+        bail!("Encryption is not enabled");
+    };
+    if meta.recovery.is_some()
+        && !Confirm::new()
+            .with_prompt("Replace the existing recovery code? The old one will stop working.")
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    let code = meta.add_recovery(&key)?;
+    show_recovery_code(&code)?;
+    metadata.save(vault_dir)?;
+    println!(
+        "Run {} to share the new recovery code with other machines.",
+        "echovault-cli sync".cyan()
+    );
+    Ok(())
+}
+
+fn cmd_recover() -> Result<()> {
+    println!("{}", "🛟 EchoVault Vault Recovery".bold().cyan());
+    println!();
+
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let Some(meta) = VaultMetadata::load_or_create(vault_dir)?.encryption else {
+        bail!("Encryption is not enabled");
+    };
+    if meta.recovery.is_none() {
+        bail!("This vault has no recovery code");
+    }
+
+    let phrase: SecretString = Input::<String>::new()
+        .with_prompt(format!(
+            "Recovery code ({} words)",
+            recovery::RECOVERY_WORDS
+        ))
+        .interact_text()?
+        .into();
+    let code = RecoveryCode::parse(phrase.expose_secret())?;
+    let key = meta.unlock_with_recovery(&code)?;
+    println!("{}", "✓ Recovery code accepted".green());
+
+    let new_passphrase = prompt_passphrase("New passphrase", Some("Confirm new passphrase"))?;
+
+    println!("Re-encrypting vault...");
+    let (_, stats) = rotate::rotate_with_key(vault_dir, &key, new_passphrase.expose_secret())?;
+    if secrets::get_or_warn(&secrets::vault_passphrase_key()).is_some() {
+        if let Err(e) = secrets::set(
+            &secrets::vault_passphrase_key(),
+            new_passphrase.expose_secret(),
+        ) {
+            println!("  {}", format!("Warning: {:#}", e).yellow());
+        }
+    }
+
+    println!(
+        "  {} files re-encrypted",
+        (stats.rotated + stats.skipped).to_string().green()
+    );
+    println!();
+    println!(
+        "{}",
+        "✓ New passphrase set. The recovery code still works."
+            .green()
+            .bold()
+    );
+    println!(
+        "Run {} to upload the re-encrypted vault. Other machines must re-enter the new passphrase.",
+        "echovault-cli sync".cyan()
+    );
+    Ok(())
+}
+
+/// Unlock the vault key for sync, if encryption is enabled.
+///
+/// age vaults use the configured identity file. Otherwise uses
+/// `ECHOVAULT_PASSPHRASE` when set, then the keyring, otherwise prompts.
+pub fn unlock_key(config: &Config) -> Result<Option<VaultKey>> {
+    let Some(meta) = VaultMetadata::load_or_create(&config.vault_path)?.encryption else {
+        return Ok(None);
+    };
+
+    if meta.is_age() {
+        let identity = age_backend::load_identity(&config.encryption.identity_path())?;
+        return Ok(Some(meta.unlock_with_identity(identity.expose_secret())?));
+    }
+
+    if let Some(passphrase) = crypto::passphrase_from_env() {
+        return Ok(Some(meta.unlock(passphrase.expose_secret())?));
+    }
+    if let Some(passphrase) = secrets::get_or_warn(&secrets::vault_passphrase_key()) {
+        match meta.unlock(passphrase.expose_secret()) {
+            Ok(key) => return Ok(Some(key)),
+            Err(_) => println!(
+                "{}",
+                "Passphrase in keyring is outdated, please re-enter it.".yellow()
+            ),
+        }
+    }
+
+    let passphrase = prompt_passphrase("Vault passphrase", None)?;
+    Ok(Some(meta.unlock(passphrase.expose_secret())?))
+}
+
+/// Prompt for a passphrase (optionally twice). The answer is cleared from memory on drop.
+fn prompt_passphrase(prompt: &str, confirmation: Option<&str>) -> Result<SecretString> {
+    let mut password = Password::new().with_prompt(prompt);
+    if let Some(confirmation) = confirmation {
+        password = password.with_confirmation(confirmation, "Passphrases do not match");
+    }
+    Ok(password.interact()?.into())
+}
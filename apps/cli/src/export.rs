@@ -0,0 +1,439 @@
+//! Export command - write parsed conversations out of the vault.
+//!
+//! Without a subcommand, conversations are exported as Markdown files
+//! (standard or Logseq pages). `export obsidian` writes them into an Obsidian
+//! vault instead, with an index note per workspace; re-exporting only rewrites
+//! the notes that changed. `export jsonl` writes a fine-tuning dataset, one
+//! conversation per line, and `export pdf <id>` renders one conversation as a
+//! PDF to share.
+//!
+//! With `export` in `[redaction] destinations`, every export masks the
+//! secrets of the conversations it writes out (see `echovault_core::redact`).
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::config::EXPORT_DESTINATION;
+use echovault_core::parsers::{
+    all_parsers,
+    dataset::{self, DatasetFormat, DatasetOptions},
+    find_conversation, markdown_writer,
+    markdown_writer::MarkdownFlavor,
+    obsidian::ObsidianWriter,
+    parse_vault_source, ParsedConversation,
+};
+use echovault_core::storage::atomic;
+use echovault_core::{redact, render, Config, SessionIndex};
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum ExportTarget {
+    /// Write conversations as notes into an Obsidian vault
+    Obsidian {
+        /// Obsidian vault (default: obsidian.vault_path in config)
+        #[arg(long)]
+        vault: Option<PathBuf>,
+
+        /// Only export sessions of this source (e.g., "cursor")
+        #[arg(long)]
+        source: Option<String>,
+    },
+
+    /// Write a fine-tuning dataset: one JSON conversation per line
+    Jsonl {
+        /// Record layout: openai or sharegpt
+        #[arg(short, long, default_value_t = DatasetFormat::OpenAi)]
+        format: DatasetFormat,
+
+        /// Output file (default: dataset-<format>.jsonl in export_path)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Drop tool calls and results
+        #[arg(long)]
+        strip_tools: bool,
+
+        /// Mask API keys, tokens and passwords in messages
+        #[arg(long)]
+        redact_secrets: bool,
+
+        /// Only export sessions of this source (e.g., "cursor")
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Only sessions started on or after this day, YYYY-MM-DD
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+
+        /// Only sessions started on or before this day, YYYY-MM-DD
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+    },
+
+    /// Render one conversation as a PDF document
+    Pdf {
+        /// Session ID
+        session_id: String,
+
+        /// Output file (default: <session-id>.pdf in export_path)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+pub fn cmd_export(
+    target: Option<ExportTarget>,
+    format: Option<MarkdownFlavor>,
+    output: Option<PathBuf>,
+    source: Option<&str>,
+) -> Result<()> {
+    println!("{}", "📤 EchoVault Export".bold().cyan());
+    println!();
+
+    let config = crate::ensure_config()?;
+    let sessions_dir = config.vault_path.join("sessions");
+    if !sessions_dir.exists() {
+        println!(
+            "{}",
+            "No sessions found. Run 'echovault-cli extract' first.".yellow()
+        );
+        return Ok(());
+    }
+
+    match target {
+        Some(ExportTarget::Obsidian { vault, source }) => {
+            export_obsidian(&config, &sessions_dir, vault, source.as_deref())
+        }
+        Some(ExportTarget::Jsonl {
+            format,
+            output,
+            strip_tools,
+            redact_secrets,
+            source,
+            since,
+            until,
+        }) => {
+            let options = DatasetOptions {
+                format,
+                strip_tools,
+                redact_secrets: redact_secrets || config.redaction.applies_to(EXPORT_DESTINATION),
+            };
+            let output = output.unwrap_or_else(|| {
+                config
+                    .export_dir()
+                    .join(format!("dataset-{}.jsonl", format))
+            });
+            export_jsonl(
+                &sessions_dir,
+                &options,
+                &output,
+                source.as_deref(),
+                since,
+                until,
+            )
+        }
+        Some(ExportTarget::Pdf { session_id, output }) => {
+            let output =
+                output.unwrap_or_else(|| config.export_dir().join(format!("{}.pdf", session_id)));
+            export_pdf(&config, &sessions_dir, &session_id, &output)
+        }
+        None => export_markdown(&config, &sessions_dir, format, output, source),
+    }
+}
+
+fn export_markdown(
+    config: &Config,
+    sessions_dir: &Path,
+    format: Option<MarkdownFlavor>,
+    output: Option<PathBuf>,
+    source: Option<&str>,
+) -> Result<()> {
+    let flavor = format.unwrap_or(config.export_format);
+    let output = output.unwrap_or_else(|| config.export_dir());
+    println!("Format: {}", flavor);
+    println!("Output: {}", output.display().to_string().dimmed());
+    println!();
+
+    let mut total_exported = 0usize;
+    let mut total_errors = 0usize;
+
+    let redact = config.redaction.applies_to(EXPORT_DESTINATION);
+    let redacted = for_each_source(
+        sessions_dir,
+        source,
+        redact,
+        |name, conversations, errors| {
+            total_errors += errors;
+            let mut exported = 0usize;
+            for conv in conversations {
+                let path = output.join(flavor.export_path(conv));
+                match markdown_writer::write_markdown_as(conv, &path, flavor) {
+                    Ok(()) => exported += 1,
+                    Err(e) => {
+                        tracing::warn!("Error writing {:?}: {}", path, e);
+                        total_errors += 1;
+                    }
+                }
+            }
+            total_exported += exported;
+
+            println!(
+                "  {}: {} exported, {} errors",
+                name,
+                exported.to_string().green(),
+                errors.to_string().red()
+            );
+        },
+    );
+
+    println!();
+    if redact {
+        println!("Secrets redacted: {}", redacted);
+    }
+    println!(
+        "{}",
+        format!(
+            "Complete: {} exported, {} errors",
+            total_exported, total_errors
+        )
+        .green()
+        .bold()
+    );
+
+    Ok(())
+}
+
+fn export_obsidian(
+    config: &Config,
+    sessions_dir: &Path,
+    vault: Option<PathBuf>,
+    source: Option<&str>,
+) -> Result<()> {
+    let Some(vault) = vault.or_else(|| config.obsidian.vault_path.clone()) else {
+        bail!("No Obsidian vault: pass --vault or set obsidian.vault_path in the config");
+    };
+    let writer = ObsidianWriter::for_vault(config, &vault)?;
+    println!("Obsidian vault: {}", vault.display().to_string().dimmed());
+    println!();
+
+    let mut total_written = 0usize;
+    let mut total_unchanged = 0usize;
+    let mut total_errors = 0usize;
+    let mut workspaces = BTreeSet::new();
+
+    let redact = config.redaction.applies_to(EXPORT_DESTINATION);
+    let redacted = for_each_source(
+        sessions_dir,
+        source,
+        redact,
+        |name, conversations, errors| {
+            total_errors += errors;
+            let (mut written, mut unchanged) = (0usize, 0usize);
+            for conv in conversations {
+                match writer.write(conv) {
+                    Ok(true) => written += 1,
+                    Ok(false) => unchanged += 1,
+                    Err(e) => {
+                        tracing::warn!("Error writing note of {}: {}", conv.id, e);
+                        total_errors += 1;
+                        continue;
+                    }
+                }
+                if let Some(workspace) = &conv.workspace {
+                    workspaces.insert(workspace.clone());
+                }
+            }
+            total_written += written;
+            total_unchanged += unchanged;
+
+            println!(
+                "  {}: {} written, {} unchanged, {} errors",
+                name,
+                written.to_string().green(),
+                unchanged,
+                errors.to_string().red()
+            );
+        },
+    );
+
+    println!();
+    if redact {
+        println!("Secrets redacted: {}", redacted);
+    }
+    println!(
+        "{}",
+        format!(
+            "Complete: {} notes written, {} unchanged, {} workspace indexes, {} errors",
+            total_written,
+            total_unchanged,
+            workspaces.len(),
+            total_errors
+        )
+        .green()
+        .bold()
+    );
+
+    Ok(())
+}
+
+fn export_jsonl(
+    sessions_dir: &Path,
+    options: &DatasetOptions,
+    output: &Path,
+    source: Option<&str>,
+    since: Option<chrono::NaiveDate>,
+    until: Option<chrono::NaiveDate>,
+) -> Result<()> {
+    println!("Format: {}", options.format);
+    println!("Output: {}", output.display().to_string().dimmed());
+    println!();
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Streamed to a temporary file, renamed over the output once complete
+    let tmp = atomic::temp_path(output);
+    let file =
+        std::fs::File::create(&tmp).with_context(|| format!("Cannot create {}", tmp.display()))?;
+    let mut out = BufWriter::new(file);
+
+    let mut total_records = 0usize;
+    let mut total_skipped = 0usize;
+    let mut total_redacted = 0usize;
+    let mut total_errors = 0usize;
+    let mut write_error = None;
+
+    for_each_source(
+        sessions_dir,
+        source,
+        false,
+        |name, conversations, errors| {
+            total_errors += errors;
+            let (mut records, mut skipped) = (0usize, 0usize);
+            for conv in conversations {
+                let day = conv.created_at.map(|t| t.date_naive());
+                if since.is_some_and(|since| day.is_none_or(|day| day < since))
+                    || until.is_some_and(|until| day.is_none_or(|day| day > until))
+                {
+                    continue;
+                }
+                let Some((record, redacted)) = dataset::to_record(conv, options) else {
+                    skipped += 1;
+                    continue;
+                };
+                if write_error.is_some() {
+                    continue;
+                }
+                match writeln!(out, "{}", record) {
+                    Ok(()) => {
+                        records += 1;
+                        total_redacted += redacted;
+                    }
+                    Err(e) => write_error = Some(e),
+                }
+            }
+            total_records += records;
+            total_skipped += skipped;
+
+            println!(
+                "  {}: {} records, {} without an exchange, {} errors",
+                name,
+                records.to_string().green(),
+                skipped,
+                errors.to_string().red()
+            );
+        },
+    );
+    let written = match write_error {
+        Some(e) => Err(e),
+        None => out.flush(),
+    };
+    drop(out);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("Cannot write {}", output.display()));
+    }
+    atomic::persist(&tmp, output).with_context(|| format!("Cannot write {}", output.display()))?;
+
+    println!();
+    if options.redact_secrets {
+        println!("Secrets redacted: {}", total_redacted);
+    }
+    println!(
+        "{}",
+        format!(
+            "Complete: {} records, {} skipped, {} errors",
+            total_records, total_skipped, total_errors
+        )
+        .green()
+        .bold()
+    );
+
+    Ok(())
+}
+
+fn export_pdf(config: &Config, sessions_dir: &Path, session_id: &str, output: &Path) -> Result<()> {
+    // The index knows the source of a session, sparing a parse of every source
+    let source = SessionIndex::open(&config.vault_path)
+        .and_then(|index| index.get(session_id))
+        .ok()
+        .flatten()
+        .map(|session| session.source);
+    let Some(mut conv) = find_conversation(sessions_dir, session_id, source.as_deref()) else {
+        bail!("Session '{}' not found", session_id);
+    };
+    if config.redaction.applies_to(EXPORT_DESTINATION) {
+        let redacted = redact::redact_conversation(&mut conv);
+        println!("Secrets redacted: {}", redacted);
+    }
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let pdf = render::conversation_pdf(&conv);
+    atomic::write(output, &pdf).with_context(|| format!("Cannot write {}", output.display()))?;
+
+    println!(
+        "{} {} ({} messages, {} KB)",
+        "✓".green(),
+        output.display(),
+        conv.messages.len(),
+        pdf.len().div_ceil(1024)
+    );
+    Ok(())
+}
+
+/// Parse the sessions of every source (or only `source`) and hand each
+/// source's conversations and parse error count to `export`. With `redact`,
+/// their secrets are masked first; returns how many were.
+fn for_each_source(
+    sessions_dir: &Path,
+    source: Option<&str>,
+    redact: bool,
+    mut export: impl FnMut(&str, &[ParsedConversation], usize),
+) -> usize {
+    let mut redacted = 0;
+    for parser in all_parsers() {
+        if source.is_some_and(|s| s != parser.source_name()) {
+            continue;
+        }
+        if !sessions_dir.join(parser.source_name()).exists() {
+            continue;
+        }
+
+        let (mut conversations, errors) = parse_vault_source(parser.as_ref(), sessions_dir);
+        for (path, err) in &errors {
+            tracing::warn!("Error parsing {:?}: {}", path, err);
+        }
+        if redact {
+            redacted += conversations
+                .iter_mut()
+                .map(redact::redact_conversation)
+                .sum::<usize>();
+        }
+        export(parser.source_name(), &conversations, errors.len());
+    }
+    redacted
+}
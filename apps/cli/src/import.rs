@@ -0,0 +1,75 @@
+//! Import commands - bring in conversations from chat service data exports,
+//! or single chats saved by hand.
+//!
+//! Imported conversations are stored as their own source in the vault; run
+//! `parse` and `embed` afterwards to make them searchable.
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::importers::{chatgpt, manual, ImportSummary};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ImportAction {
+    /// Import a ChatGPT data export (the .zip, or its conversations.json)
+    ChatgptExport {
+        /// Path to the export
+        path: PathBuf,
+    },
+
+    /// Import one chat pasted from a web UI (Markdown or OpenAI-messages JSON)
+    File {
+        /// Path to the chat file
+        path: PathBuf,
+
+        /// Title of the conversation (default: the file's first heading)
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Where the chat came from, e.g. "claude.ai" (kept as an origin tag)
+        #[arg(long, default_value = "manual")]
+        source: String,
+    },
+}
+
+pub fn cmd_import(action: ImportAction) -> Result<()> {
+    let config = crate::ensure_config()?;
+
+    match action {
+        ImportAction::ChatgptExport { path } => {
+            println!("{}", "📥 EchoVault Import (ChatGPT)".bold().cyan());
+            println!();
+            let summary = chatgpt::import_export(&path, &config.vault_path)?;
+            print_summary(&summary);
+        }
+        ImportAction::File {
+            path,
+            title,
+            source,
+        } => {
+            println!("{}", "📥 EchoVault Import (file)".bold().cyan());
+            println!();
+            let summary =
+                manual::import_file(&path, &config.vault_path, title.as_deref(), Some(&source))?;
+            print_summary(&summary);
+        }
+    }
+    Ok(())
+}
+
+fn print_summary(summary: &ImportSummary) {
+    println!(
+        "{} {} conversations imported ({} unchanged)",
+        "✓".green(),
+        summary.imported.to_string().green(),
+        summary.unchanged
+    );
+    if summary.imported > 0 {
+        println!(
+            "Run {} and {} to make them searchable.",
+            "echovault-cli parse".cyan(),
+            "echovault-cli embed".cyan()
+        );
+    }
+}
@@ -5,32 +5,74 @@
 //! like Ubuntu 20.04.
 //!
 //! Usage:
-//!   echovault-cli auth     - Authenticate with Google Drive
+//!   echovault-cli auth     - Authenticate with the sync provider
 //!   echovault-cli sync     - Sync vault (pull → extract → push)
 //!   echovault-cli extract  - Extract sessions from IDE only
+//!   echovault-cli dedupe [--apply] - Link sessions captured by more than one source
+//!   echovault-cli compress - Compress the raw sessions already in the vault
+//!   echovault-cli redact [--apply] - Find secrets, or write redacted copies to push
+//!   echovault-cli import chatgpt-export <zip> - Import a ChatGPT data export
+//!   echovault-cli import file <path> - Import a chat pasted from a web UI
+//!   echovault-cli export [obsidian] - Export conversations as Markdown or into Obsidian
 //!   echovault-cli status   - Show auth and sync status
+//!   echovault-cli watch    - Capture sessions as soon as source files change
+//!   echovault-cli pause    - Pause automatic capture (file watchers)
+//!   echovault-cli resume   - Resume automatic capture
+//!   echovault-cli digest   - Write a daily/weekly digest of new conversations
+//!   echovault-cli serve    - Serve sessions, search and sync over a local REST API
+//!   echovault-cli schema   - Print the JSON Schema of exported conversations
+//!   echovault-cli doctor   - Check configuration for problems
+//!   echovault-cli config check|get|set|unset - Check and edit settings by key
+//!   echovault-cli notes set|show|remove|list|search - Annotate sessions
+//!   echovault-cli sources list|enable|disable - Choose which sources are extracted
+//!   echovault-cli profile list|add|use|remove - Manage profiles with their own config
+//!   echovault-cli encryption setup|verify|status|rotate-key|recover - End-to-end encryption
+//!   echovault-cli vault encrypt|decrypt|rekey - Encrypt, decrypt or re-key the vault
+//!
+//! Every command accepts `--profile <name>` to use a named config profile.
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use echovault_core::{
-    extractors::{
-        aider::AiderExtractor, antigravity::AntigravityExtractor, claude_code::ClaudeCodeExtractor,
-        cline::ClineExtractor, codex::CodexExtractor, continue_dev::ContinueDevExtractor,
-        cursor::CursorExtractor, gemini_cli::GeminiCliExtractor, jetbrains::JetBrainsExtractor,
-        opencode::OpenCodeExtractor, vscode_copilot::VSCodeCopilotExtractor, zed::ZedExtractor,
-        Extractor, SessionFile,
+    config::{EmbeddingBackend, DEFAULT_SYNC_TARGET},
+    crypto::{staging, VaultKey},
+    embedding::{SearchFilter, SearchOptions},
+    extractors::{enrich, ingest},
+    metrics, notifications,
+    parsers::{
+        markdown_writer::MarkdownFlavor,
+        vault_parse::{parse_vault, ParseResult},
+    },
+    pipeline::{self, PipelineOptions},
+    progress::{is_cancelled, Cancelled, Operation},
+    storage::{compressed, FtsFilter, FtsIndex, LocalDb, SessionEntry, VaultDb},
+    sync::{
+        create_provider, create_target_provider, delta, partition, AuthStatus, SyncOptions,
+        SyncProvider,
     },
-    storage::{SessionEntry, VaultDb},
-    sync::{AuthStatus, RcloneProvider, SyncOptions, SyncProvider},
     Config,
 };
-use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
+use output::say;
 use std::fs;
-use std::path::Path;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+
+mod config;
+mod encryption;
+mod export;
+mod import;
+mod notes;
+mod output;
+mod profile;
+mod progress;
+mod redact;
+mod script_filter;
+mod sessions;
+mod sources;
+mod tags;
+mod tui;
+mod vault;
+mod watch;
 
 /// EchoVault CLI - Black box for your AI conversations
 #[derive(Parser)]
@@ -43,22 +85,170 @@ struct Cli {
     /// Enable verbose output
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Use a named profile (listed in profiles.toml or [profiles.<name>] in
+    /// echovault.toml; also ECHOVAULT_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Print the result as JSON to stdout and human output to stderr
+    /// (extract, status, sync, parse, embed, search, doctor, stats, list, show)
+    #[arg(long, global = true)]
+    json: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Authenticate with Google Drive (required before first sync)
+    /// Authenticate with the sync provider (required before first sync)
     Auth,
 
     /// Sync vault with cloud (pull -> extract -> push)
-    Sync,
+    Sync {
+        /// Sync target: "default" ([sync]) or a [sync.targets.<name>] entry
+        #[arg(long, conflicts_with = "all")]
+        remote: Option<String>,
+
+        /// Sync with every configured target in turn
+        #[arg(long)]
+        all: bool,
+
+        /// Push every file instead of only those changed since the last push
+        #[arg(long)]
+        full: bool,
+
+        /// Only push the sessions of workspaces whose name contains this,
+        /// to the targets they sync with (see [[sync.workspaces]])
+        #[arg(long, conflicts_with_all = ["all", "full_pipeline"])]
+        workspace: Option<String>,
+
+        /// Also parse and embed: pull -> extract -> parse -> embed -> push
+        #[arg(long, conflicts_with = "all")]
+        full_pipeline: bool,
+
+        /// Skip pulling from the remote (with --full-pipeline)
+        #[arg(long, requires = "full_pipeline")]
+        skip_pull: bool,
+
+        /// Skip extracting from local IDEs (with --full-pipeline)
+        #[arg(long, requires = "full_pipeline")]
+        skip_extract: bool,
+
+        /// Skip parsing into Markdown (with --full-pipeline)
+        #[arg(long, requires = "full_pipeline")]
+        skip_parse: bool,
+
+        /// Skip embedding for semantic search (with --full-pipeline)
+        #[arg(long, requires = "full_pipeline")]
+        skip_embed: bool,
+
+        /// Skip pushing to the remote (with --full-pipeline)
+        #[arg(long, requires = "full_pipeline")]
+        skip_push: bool,
+    },
 
     /// Extract sessions from IDE into vault (without syncing to cloud)
     Extract,
 
+    /// Import conversations from a chat service data export
+    Import {
+        #[command(subcommand)]
+        action: import::ImportAction,
+    },
+
     /// Parse raw sessions into clean Markdown
     Parse,
 
+    /// List sessions recorded in the vault
+    List {
+        /// Only sessions of this source (e.g., "cursor")
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Only workspaces whose name contains this (case-insensitive)
+        #[arg(long)]
+        workspace: Option<String>,
+
+        /// Number of sessions listed
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: usize,
+
+        /// Order of the list
+        #[arg(long, value_enum, default_value = "updated")]
+        sort: sessions::SessionSort,
+
+        /// Only sessions with this tag (repeatable; all must match)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+
+    /// Show a conversation in the terminal
+    Show {
+        /// Session ID
+        session_id: String,
+
+        /// Print the vault copy of the raw session file instead
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Open a session's parsed Markdown (or raw file) in $EDITOR or the default app
+    Open {
+        /// Session ID
+        session_id: String,
+
+        /// Open the vault copy of the raw session file instead
+        #[arg(long)]
+        raw: bool,
+
+        /// Print the file's path instead of opening it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Find sessions holding the same conversation as another source's capture
+    /// (only lists them without `--apply`)
+    Dedupe {
+        /// Store the duplicate links and drop the duplicates' embeddings
+        #[arg(long)]
+        apply: bool,
+    },
+
+    /// Compress the raw sessions already in the vault with zstd (new copies
+    /// are compressed with `[extractors] compress = true`)
+    Compress,
+
+    /// Find secrets in the files pushed to sync targets, or write their
+    /// redacted copies (pushed to targets in `[redaction] destinations`)
+    Redact {
+        /// Only report the secrets found (the default)
+        #[arg(long, conflicts_with = "apply")]
+        scan: bool,
+
+        /// Write the redacted copies into <vault>/.redacted
+        #[arg(long)]
+        apply: bool,
+    },
+
+    /// Export conversations as Markdown files (standard or Logseq pages), or
+    /// into an Obsidian vault
+    #[command(args_conflicts_with_subcommands = true)]
+    Export {
+        #[command(subcommand)]
+        target: Option<export::ExportTarget>,
+
+        /// Markdown flavor: standard or logseq (default: export_format in config)
+        #[arg(short, long)]
+        format: Option<MarkdownFlavor>,
+
+        /// Output directory (default: export_path in config)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only export sessions of this source (e.g., "cursor")
+        #[arg(long)]
+        source: Option<String>,
+    },
+
     /// Start interceptor proxy for capturing API traffic
     Intercept {
         /// Port to listen on (default: 18080)
@@ -77,20 +267,251 @@ enum Commands {
         /// Maximum number of results
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Output format: text, or script-filter (Alfred/Raycast JSON)
+        #[arg(long, value_enum, default_value_t = SearchFormat::Text)]
+        format: SearchFormat,
+
+        /// Score every chunk instead of using the approximate index
+        #[arg(long)]
+        exact: bool,
+
+        /// Keep the hybrid ranking even if [embedding.rerank] is enabled
+        #[arg(long)]
+        no_rerank: bool,
+
+        /// Only sessions of this source (e.g., "cursor")
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Only sessions whose workspace name contains this
+        #[arg(long)]
+        workspace: Option<String>,
+
+        /// Only sessions started on or after this day, YYYY-MM-DD
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+
+        /// Only sessions whose model name contains this (e.g., "claude")
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Only sessions with this tag (repeatable; all must match)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+
+    /// Keyword search across parsed conversations (no embedding API needed)
+    Grep {
+        /// FTS5 query: words, "exact phrases", prefix*, AND/OR/NOT
+        query: String,
+
+        /// Only sessions of this source (e.g., "cursor")
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Only sessions whose workspace name contains this
+        #[arg(long)]
+        workspace: Option<String>,
+
+        /// Only sessions started on or after this day, YYYY-MM-DD
+        #[arg(long)]
+        after: Option<chrono::NaiveDate>,
+
+        /// Only sessions started before this day, YYYY-MM-DD
+        #[arg(long)]
+        before: Option<chrono::NaiveDate>,
+
+        /// Only sessions whose model name contains this (e.g., "claude")
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Only sessions with this tag (repeatable; all must match)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Rebuild the full-text index from the raw sessions first
+        #[arg(long)]
+        reindex: bool,
+    },
+
+    /// Start MCP (Model Context Protocol) server on stdio, or over HTTP
+    Mcp {
+        /// Serve over streamable HTTP (SSE) on this address instead of stdio,
+        /// e.g. 127.0.0.1:8765
+        #[arg(long, value_name = "ADDR")]
+        http: Option<std::net::SocketAddr>,
+
+        /// Bearer token HTTP clients must send (default: $ECHOVAULT_MCP_TOKEN,
+        /// or a random token printed at startup)
+        #[arg(long, requires = "http")]
+        token: Option<String>,
+    },
+
+    /// Serve sessions, search and sync as a JSON REST API on localhost
+    Serve {
+        /// Port to listen on (default: 18081)
+        #[arg(short, long, default_value_t = echovault_core::api::DEFAULT_PORT)]
+        port: u16,
+
+        /// Bearer token clients must send (default: $ECHOVAULT_API_TOKEN,
+        /// or a random token printed at startup)
+        #[arg(long)]
+        token: Option<String>,
     },
 
-    /// Start MCP (Model Context Protocol) server on stdio
-    Mcp,
+    /// Print the JSON Schema of the canonical conversation format
+    Schema,
 
     /// Show current status (auth, last sync, etc.)
     Status,
+
+    /// Show vault statistics (sessions, messages, models, busiest days) and local metrics
+    Stats {
+        /// Show token usage and estimated costs per tool and model instead
+        #[arg(long, alias = "cost")]
+        costs: bool,
+
+        /// Group costs by week instead of by day
+        #[arg(long, requires = "costs")]
+        weekly: bool,
+
+        /// Group costs by month instead of by day
+        #[arg(long, requires = "costs", conflicts_with = "weekly")]
+        monthly: bool,
+
+        /// Days of usage included in --json (vault statistics, metrics and
+        /// usage: sessions per day, messages per model, most active workspaces)
+        #[arg(long, default_value = "30", requires = "json")]
+        days: u32,
+    },
+
+    /// Write a Markdown digest of the conversations of a day or week into the vault
+    Digest {
+        /// Digest of a week (Monday to Sunday) instead of a day
+        #[arg(long)]
+        weekly: bool,
+
+        /// Any day of the period, YYYY-MM-DD (default: the last complete one)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+
+        /// Skip the summary from the [digest] chat endpoint
+        #[arg(long)]
+        no_summary: bool,
+
+        /// Also print the digest
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Watch IDE storage and extract new sessions as they appear
+    Watch {
+        /// Run as a service: sd_notify readiness, log output, exit on SIGTERM
+        #[arg(long)]
+        daemon: bool,
+
+        /// PID/lock file path (default: $XDG_RUNTIME_DIR/echovault/watch.pid)
+        #[arg(long)]
+        pid_file: Option<std::path::PathBuf>,
+
+        /// Also write Markdown for newly captured sessions
+        #[arg(long)]
+        parse: bool,
+
+        /// Also embed newly captured sessions for semantic search
+        #[arg(long)]
+        embed: bool,
+    },
+
+    /// Dashboard of sources, sync status and search in the terminal
+    Tui,
+
+    /// Pause automatic capture by file watchers (desktop app and `watch`)
+    Pause,
+
+    /// Resume automatic capture
+    Resume,
+
+    /// Manage end-to-end encryption of synced data
+    Encryption {
+        #[command(subcommand)]
+        action: encryption::EncryptionAction,
+    },
+
+    /// Encrypt or decrypt the vault without syncing, or re-encrypt it (rekey)
+    Vault {
+        #[command(subcommand)]
+        action: vault::VaultAction,
+    },
+
+    /// Check configuration for problems and how to fix them
+    Doctor,
+
+    /// Annotate sessions with notes, ratings and follow-up flags
+    Notes {
+        #[command(subcommand)]
+        action: notes::NotesAction,
+    },
+
+    /// Add, remove and list the tags of sessions
+    Tag {
+        #[command(subcommand)]
+        action: tags::TagAction,
+    },
+
+    /// List sources and enable or disable them
+    Sources {
+        #[command(subcommand)]
+        action: sources::SourcesAction,
+    },
+
+    /// Check, read and change settings (`config set vault_path <path>` moves the vault)
+    Config {
+        #[command(subcommand)]
+        action: config::ConfigAction,
+    },
+
+    /// List, create and pick profiles with their own config file
+    Profile {
+        #[command(subcommand)]
+        action: profile::ProfileAction,
+    },
+}
+
+/// Output format of `search`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SearchFormat {
+    /// Human-readable results
+    Text,
+    /// Alfred/Raycast Script Filter JSON
+    ScriptFilter,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    output::set_json(cli.json);
+
+    if cli.profile.is_some() {
+        echovault_core::config::set_active_profile(cli.profile.clone());
+    }
 
-    // Initialize logging
-    let log_level = if cli.verbose { "debug" } else { "info" };
+    // Initialize logging (--verbose, then log_level / ECHOVAULT_LOG_LEVEL)
+    let loaded = Config::load_default();
+    let log_level = if cli.verbose {
+        "debug".to_string()
+    } else {
+        loaded
+            .as_ref()
+            .ok()
+            .and_then(|c| c.log_level.clone())
+            .filter(|level| level.parse::<tracing::Level>().is_ok())
+            .unwrap_or_else(|| "info".to_string())
+    };
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::from_default_env()
@@ -98,18 +519,190 @@ fn main() -> Result<()> {
                 .add_directive(format!("echovault_core={}", log_level).parse().unwrap()),
         )
         .with_target(false)
+        // Keep stdout clean for JSON output (mcp, schema, search --format)
+        .with_writer(std::io::stderr)
         .init();
 
+    // Move plaintext secrets from echovault.toml into the OS keyring
+    if let Err(e) = echovault_core::secrets::migrate_default_config() {
+        tracing::warn!("Secrets migration failed: {:#}", e);
+    }
+
+    // Catch config mistakes up front instead of deep inside a command
+    // (doctor and status report problems themselves, config and profile are how
+    // they get fixed)
+    let diagnostic = matches!(
+        cli.command,
+        Commands::Doctor | Commands::Status | Commands::Config { .. } | Commands::Profile { .. }
+    );
+    if let (Ok(config), false) = (&loaded, diagnostic) {
+        let issues = config.validate();
+        for issue in &issues {
+            tracing::warn!("Config: {}", issue);
+        }
+        if issues.iter().any(|issue| issue.is_error()) {
+            anyhow::bail!("Invalid configuration; run `echovault-cli doctor` for details");
+        }
+    }
+
     match cli.command {
         Commands::Auth => cmd_auth(),
-        Commands::Sync => cmd_sync(),
+        Commands::Sync {
+            remote,
+            all,
+            full,
+            workspace,
+            full_pipeline,
+            skip_pull,
+            skip_extract,
+            skip_parse,
+            skip_embed,
+            skip_push,
+        } => {
+            if full_pipeline {
+                cmd_pipeline(PipelineOptions {
+                    target: remote.unwrap_or_else(|| DEFAULT_SYNC_TARGET.to_string()),
+                    key: None,
+                    full_push: full,
+                    skip_pull,
+                    skip_extract,
+                    skip_parse,
+                    skip_embed,
+                    skip_push,
+                })
+            } else {
+                cmd_sync(remote, all, full, workspace.as_deref())
+            }
+        }
         Commands::Extract => cmd_extract(),
+        Commands::Import { action } => import::cmd_import(action),
         Commands::Parse => cmd_parse(),
+        Commands::List {
+            source,
+            workspace,
+            limit,
+            sort,
+            tags,
+        } => sessions::cmd_list(source, workspace, limit, sort, &tags),
+        Commands::Show { session_id, raw } => sessions::cmd_show(&session_id, raw),
+        Commands::Open {
+            session_id,
+            raw,
+            print,
+        } => sessions::cmd_open(&session_id, raw, print),
+        Commands::Dedupe { apply } => cmd_dedupe(apply),
+        Commands::Compress => cmd_compress(),
+        Commands::Redact { scan, apply } => redact::cmd_redact(apply && !scan),
+        Commands::Export {
+            target,
+            format,
+            output,
+            source,
+        } => export::cmd_export(target, format, output, source.as_deref()),
         Commands::Intercept { port } => cmd_intercept(port),
         Commands::Embed => cmd_embed(),
-        Commands::Search { query, limit } => cmd_search(&query, limit),
-        Commands::Mcp => cmd_mcp(),
+        Commands::Search {
+            query,
+            limit,
+            format,
+            exact,
+            no_rerank,
+            source,
+            workspace,
+            since,
+            model,
+            tags,
+        } => cmd_search(
+            &query,
+            limit,
+            format,
+            &SearchOptions {
+                exact,
+                skip_rerank: no_rerank,
+                filter: SearchFilter {
+                    source,
+                    workspace,
+                    since,
+                    model,
+                    only: tags::tagged_sessions(&tags)?,
+                },
+            },
+        ),
+        Commands::Grep {
+            query,
+            source,
+            workspace,
+            after,
+            before,
+            model,
+            tags,
+            limit,
+            reindex,
+        } => cmd_grep(
+            &query,
+            FtsFilter {
+                source,
+                workspace,
+                after,
+                before,
+                model,
+                only: tags::tagged_sessions(&tags)?,
+                ..Default::default()
+            },
+            limit,
+            reindex,
+        ),
+        Commands::Mcp { http, token } => match http {
+            Some(addr) => cmd_mcp_http(addr, token),
+            None => cmd_mcp(),
+        },
+        Commands::Serve { port, token } => cmd_serve(port, token),
+        Commands::Schema => cmd_schema(),
         Commands::Status => cmd_status(),
+        Commands::Stats {
+            costs,
+            weekly,
+            monthly,
+            days,
+        } => {
+            if costs {
+                use echovault_core::analytics::Period;
+                cmd_costs(if monthly {
+                    Period::Month
+                } else if weekly {
+                    Period::Week
+                } else {
+                    Period::Day
+                })
+            } else if cli.json {
+                cmd_stats_json(days)
+            } else {
+                cmd_stats()
+            }
+        }
+        Commands::Digest {
+            weekly,
+            date,
+            no_summary,
+            print,
+        } => cmd_digest(weekly, date, !no_summary, print),
+        Commands::Watch {
+            daemon,
+            pid_file,
+            parse,
+            embed,
+        } => watch::cmd_watch(daemon, pid_file, watch::PostIngest { parse, embed }),
+        Commands::Tui => tui::cmd_tui(),
+        Commands::Pause => cmd_pause(true),
+        Commands::Resume => cmd_pause(false),
+        Commands::Encryption { action } => encryption::cmd_encryption(action),
+        Commands::Vault { action } => vault::cmd_vault(action),
+        Commands::Doctor => cmd_doctor(false),
+        Commands::Notes { action } => notes::cmd_notes(action),
+        Commands::Tag { action } => tags::cmd_tag(action),
+        Commands::Sources { action } => sources::cmd_sources(action),
+        Commands::Config { action } => config::cmd_config(action),
+        Commands::Profile { action } => profile::cmd_profile(action),
     }
 }
 
@@ -119,15 +712,20 @@ fn cmd_auth() -> Result<()> {
     println!("{}", "🔐 EchoVault Authentication".bold().cyan());
     println!();
 
-    let mut provider = RcloneProvider::new();
+    let config = Config::load_default().unwrap_or_default();
+    let label = config.sync.provider.label();
+    let mut provider = create_provider(&config);
 
     // Check if already authenticated
     if provider.is_authenticated() {
-        println!("{}", "✓ Already authenticated with Google Drive".green());
+        println!(
+            "{}",
+            format!("✓ Already authenticated with {}", label).green()
+        );
         return Ok(());
     }
 
-    println!("Starting Google Drive authentication...");
+    println!("Starting {} authentication...", label);
     println!();
 
     // Start auth - this will open browser
@@ -184,214 +782,696 @@ fn cmd_auth() -> Result<()> {
 
 // ============ SYNC COMMAND ============
 
-fn cmd_sync() -> Result<()> {
-    println!("{}", "🔄 EchoVault Sync".bold().cyan());
-    println!();
+fn cmd_sync(remote: Option<String>, all: bool, full: bool, workspace: Option<&str>) -> Result<()> {
+    say!("{}", "🔄 EchoVault Sync".bold().cyan());
+    say!();
+
+    // Ensure config exists
+    let config = ensure_config()?;
+    let targets: Vec<String> = if let Some(workspace) = workspace {
+        let sessions = partition::workspace_sessions(&config.vault_path, workspace)?;
+        if sessions.is_empty() {
+            anyhow::bail!("No sessions in a workspace matching '{}'", workspace);
+        }
+        let targets = partition::targets_of(&config, &sessions)?;
+        match remote {
+            Some(remote) if !targets.contains(&remote) => anyhow::bail!(
+                "Sessions of '{}' do not sync with '{}' (see [[sync.workspaces]])",
+                workspace,
+                remote
+            ),
+            Some(remote) => vec![remote],
+            None => targets,
+        }
+    } else if all {
+        config
+            .sync
+            .target_names()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    } else {
+        vec![remote.unwrap_or_else(|| DEFAULT_SYNC_TARGET.to_string())]
+    };
 
-    let provider = RcloneProvider::new();
+    // Outcome per target, for --json
+    let mut results = Vec::new();
+    let sync_result = |target: &str, outcome: Result<usize, String>| match outcome {
+        Ok(files) => serde_json::json!({ "target": target, "files_pushed": files }),
+        Err(error) => serde_json::json!({ "target": target, "error": error }),
+    };
 
     // Check auth
-    if !provider.is_authenticated() {
-        println!(
-            "{}",
-            "✗ Not authenticated. Please run 'echovault-cli auth' first.".red()
+    let mut providers = Vec::new();
+    for name in &targets {
+        let provider = create_target_provider(&config, name)?;
+        if provider.is_authenticated() {
+            providers.push((name.as_str(), provider));
+        } else if targets.len() == 1 {
+            say!(
+                "{}",
+                "✗ Not authenticated. Please run 'echovault-cli auth' first.".red()
+            );
+            results.push(sync_result(
+                name.as_str(),
+                Err("Not authenticated".to_string()),
+            ));
+            return output::emit(&results);
+        } else {
+            say!(
+                "{}",
+                format!("✗ {}: not authenticated, skipped", name).yellow()
+            );
+            record_sync(&config, name, 0, &Err(anyhow::anyhow!("Not authenticated")));
+            results.push(sync_result(
+                name.as_str(),
+                Err("Not authenticated".to_string()),
+            ));
+        }
+    }
+
+    let op = progress::interruptible()?;
+    let key = encryption::unlock_key(&config)?;
+    let mut failed = Vec::new();
+    for (name, provider) in &providers {
+        if targets.len() > 1 {
+            say!("{}", format!("== {} ==", name).bold());
+        }
+        let started = chrono::Utc::now().timestamp_millis();
+        let outcome = run_sync(
+            &config,
+            name,
+            provider.as_ref(),
+            key.as_ref(),
+            full,
+            workspace,
+            &op,
         );
-        return Ok(());
+        // A workspace sync pushes part of the changes only: recorded, it
+        // would become the base of the next delta push
+        if workspace.is_none() {
+            record_sync(&config, name, started, &outcome);
+        }
+        match outcome {
+            Ok(files) => results.push(sync_result(name, Ok(files))),
+            Err(e) if is_cancelled(&e) || targets.len() == 1 => return Err(e),
+            Err(e) => {
+                say!("{}", format!("✗ {}: {:#}", name, e).red());
+                results.push(sync_result(name, Err(format!("{:#}", e))));
+                failed.push(*name);
+            }
+        }
+        say!();
+    }
+    output::emit(&results)?;
+    if !failed.is_empty() {
+        anyhow::bail!("Sync failed for: {}", failed.join(", "));
     }
+    Ok(())
+}
 
-    // Ensure config exists
-    let config = ensure_config()?;
+/// Record the outcome of a sync started at `started` (unix milliseconds)
+/// for `status` and the next delta push, and send it to the
+/// webhooks of `[notifications]`. Cancelled syncs are not recorded.
+fn record_sync(config: &Config, target: &str, started: i64, outcome: &Result<usize>) {
+    let (recorded, event) = match outcome {
+        Err(e) if is_cancelled(e) => return,
+        Ok(files) => (
+            delta::record_sync(&config.vault_path, target, started, Ok(*files)),
+            notifications::Event::SyncCompleted {
+                target: target.to_string(),
+                files_pushed: *files,
+            },
+        ),
+        Err(e) => {
+            let error = format!("{:#}", e);
+            (
+                delta::record_sync(&config.vault_path, target, started, Err(&error)),
+                notifications::Event::SyncFailed {
+                    target: target.to_string(),
+                    error,
+                },
+            )
+        }
+    };
+    if let Err(e) = recorded {
+        tracing::warn!("Failed to record sync state of {}: {}", target, e);
+    }
+    notifications::notify(&config.notifications, &event);
+}
 
+/// Pull, extract and push with a sync target, printing each step. Only
+/// files changed since the last push are pushed, unless `full`, or with a
+/// `workspace`, only the files of its sessions. Sessions that workspace rules
+/// keep from the target go neither way. Returns the number of files pushed.
+/// Shared by `sync` and the `POST /sync` endpoint of `serve`.
+fn run_sync(
+    config: &Config,
+    target: &str,
+    provider: &dyn SyncProvider,
+    key: Option<&VaultKey>,
+    full: bool,
+    workspace: Option<&str>,
+    op: &Operation,
+) -> Result<usize> {
+    let timer = metrics::Timer::start(metrics::SYNC_DURATION_MS);
     let vault_dir = &config.vault_path;
-    println!("Vault: {}", vault_dir.display().to_string().dimmed());
-    println!();
+    say!("Vault: {}", vault_dir.display().to_string().dimmed());
+
+    // Encrypted vaults sync through the staging directory
+    let sync_root = match key {
+        Some(_) => {
+            say!("Encryption: {}", "enabled".green());
+            staging::staging_dir(vault_dir)
+        }
+        None => vault_dir.clone(),
+    };
+    say!();
 
     // Step 1: Pull from remote
-    println!("{}", "Step 1/3: Pulling from Google Drive...".bold());
-    let options = SyncOptions::default();
-    match provider.pull(vault_dir, &options) {
+    let label = match config.sync.target(target) {
+        Some(sync) if target != DEFAULT_SYNC_TARGET => {
+            format!("{} '{}'", sync.provider.label(), target)
+        }
+        _ => config.sync.provider.label().to_string(),
+    };
+    say!("{}", format!("Step 1/3: Pulling from {}...", label).bold());
+    let excluded = partition::excluded(config, target)?;
+    if excluded.sessions > 0 {
+        say!(
+            "  {}",
+            format!(
+                "{} sessions of other workspaces not synced with this target",
+                excluded.sessions
+            )
+            .dimmed()
+        );
+    }
+    let mut options = SyncOptions {
+        cancel: op.token().clone(),
+        exclude: excluded.paths,
+        skip_newer: key.is_some() || config.redaction.applies_to(target),
+        ..SyncOptions::for_sync(&config.sync)
+    };
+    match provider.pull(&sync_root, &options) {
         Ok(result) => {
             if result.has_changes {
-                println!(
+                say!(
                     "  {} new files, {} updated",
                     result.new_files.to_string().green(),
                     result.updated_files.to_string().yellow()
                 );
             } else {
-                println!("  {}", "No new changes from remote".dimmed());
+                say!("  {}", "No new changes from remote".dimmed());
             }
         }
+        Err(e) if is_cancelled(&e) => return Err(e),
         Err(e) => {
-            println!(
+            say!(
                 "  {} (continuing anyway)",
                 format!("Warning: {}", e).yellow()
             );
         }
     }
 
+    if let Some(key) = key {
+        let opened = staging::open(vault_dir, key, config.encryption.allow_missing_manifest)?;
+        if opened.decrypted > 0 {
+            say!("  Decrypted {} files", opened.decrypted.to_string().green());
+        }
+        if opened.unlisted > 0 {
+            say!(
+                "  {}",
+                format!(
+                    "{} files skipped (not in the remote manifest yet)",
+                    opened.unlisted
+                )
+                .yellow()
+            );
+        }
+    }
+
     // Step 1.5: Import pulled sessions into vault.db
-    let import_count = import_vault_sessions(vault_dir)?;
+    let import_count = ingest::import_vault_sessions(vault_dir)?;
     if import_count > 0 {
-        println!(
+        say!(
             "  Imported {} sessions from other machines",
             import_count.to_string().green()
         );
     }
 
-    println!();
+    say!();
 
     // Step 2: Extract from local IDEs
-    println!("{}", "Step 2/3: Extracting from local IDEs...".bold());
-    let extracted = ingest_sessions(vault_dir)?;
+    op.check()?;
+    say!("{}", "Step 2/3: Extracting from local IDEs...".bold());
+    let extracted = ingest_sessions(vault_dir, config, None, op)?.copied > 0;
     if extracted {
-        println!("  {}", "Sessions extracted successfully".green());
+        say!("  {}", "Sessions extracted successfully".green());
     } else {
-        println!("  {}", "All sessions already up-to-date".dimmed());
+        say!("  {}", "All sessions already up-to-date".dimmed());
     }
 
-    println!();
+    say!();
 
     // Step 3: Push to remote
-    println!("{}", "Step 3/3: Pushing to Google Drive...".bold());
-    match provider.push(vault_dir, &options) {
+    op.check()?;
+    say!("{}", format!("Step 3/3: Pushing to {}...", label).bold());
+    if let Some(key) = key {
+        let sealed = staging::seal(vault_dir, key)?;
+        say!(
+            "  Encrypted {} changed files",
+            sealed.encrypted.to_string().green()
+        );
+    }
+    // Targets listed in [redaction] get copies with their secrets masked
+    let push_root = match echovault_core::redact::vault::push_root(config, target, key.is_some())? {
+        Some((root, staged)) => {
+            say!(
+                "  Redacted {} secrets in {} changed files",
+                staged.secrets.to_string().green(),
+                staged.redacted
+            );
+            if staged.unredacted > 0 {
+                say!(
+                    "  {}",
+                    format!("{} binary files pushed unredacted", staged.unredacted).yellow()
+                );
+            }
+            root
+        }
+        None => sync_root.clone(),
+    };
+    // Extract may have added sessions kept from this target
+    options.exclude = partition::excluded(config, target)?.paths;
+    if let Some(workspace) = workspace {
+        let sessions = partition::workspace_sessions(vault_dir, workspace)?;
+        options.files = Some(partition::session_files(vault_dir, &push_root, &sessions));
+    } else if !full && config.sync.targets.is_empty() {
+        // With several targets, files pulled from one must reach the others,
+        // which only a full push (comparing the whole tree) guarantees
+        options.files = delta::push_files(vault_dir, &push_root, target);
+    }
+    match &options.files {
+        Some(files) => say!("  {} changed files", files.len().to_string().green()),
+        None => say!("  {}", "Full push".dimmed()),
+    }
+    let files_pushed = match provider.push(&push_root, &options) {
         Ok(result) => {
-            println!("  {} files pushed", result.files_pushed.to_string().green());
+            say!("  {} files pushed", result.files_pushed.to_string().green());
+            result.files_pushed
         }
         Err(e) => {
             return Err(anyhow::anyhow!("Push failed: {}", e));
         }
+    };
+
+    timer.finish(config);
+    say!();
+    say!("{}", "✓ Sync complete!".green().bold());
+
+    Ok(files_pushed)
+}
+
+// ============ FULL PIPELINE ============
+
+/// `sync --full-pipeline`: pull, extract, parse, embed and push in one run,
+/// then print what each stage did.
+fn cmd_pipeline(options: PipelineOptions) -> Result<()> {
+    say!("{}", "🔄 EchoVault Full Pipeline".bold().cyan());
+    say!();
+
+    let config = ensure_config()?;
+    say!(
+        "Vault: {}",
+        config.vault_path.display().to_string().dimmed()
+    );
+    say!();
+
+    let op = progress::interruptible()?;
+    let key = encryption::unlock_key(&config)?;
+    let options = PipelineOptions {
+        key: key.as_ref(),
+        ..options
+    };
+    let started = chrono::Utc::now().timestamp_millis();
+    let outcome = pipeline::run(&config, &options, &op);
+    if !(options.skip_pull && options.skip_push) {
+        let pushed = match &outcome {
+            Ok(summary) => Ok(summary.push.as_ref().map_or(0, |push| push.pushed)),
+            Err(e) if is_cancelled(e) => Err(Cancelled.into()),
+            Err(e) => Err(anyhow::anyhow!("{:#}", e)),
+        };
+        record_sync(&config, &options.target, started, &pushed);
+    }
+    let summary = outcome?;
+    output::emit(&summary)?;
+    if let Some(extract) = summary.extract.as_ref().filter(|e| e.copied > 0) {
+        notifications::notify(
+            &config.notifications,
+            &notifications::Event::SessionsExtracted {
+                count: extract.copied,
+            },
+        );
     }
 
-    println!();
-    println!("{}", "✓ Sync complete!".green().bold());
+    let skipped = || "skipped".dimmed().to_string();
+    say!("{}", "Summary".bold());
+    say!(
+        "  Pull:    {}",
+        summary.pull.as_ref().map_or_else(skipped, |pull| {
+            let mut line = format!(
+                "{} new, {} updated, {} sessions imported",
+                pull.new_files, pull.updated_files, pull.imported
+            );
+            if pull.decrypted > 0 {
+                line.push_str(&format!(", {} decrypted", pull.decrypted));
+            }
+            line
+        })
+    );
+    say!(
+        "  Extract: {}",
+        summary
+            .extract
+            .as_ref()
+            .map_or_else(skipped, |extract| format!(
+                "{} found, {} copied, {} errors",
+                extract.found, extract.copied, extract.errors
+            ))
+    );
+    say!(
+        "  Parse:   {}",
+        summary.parse.as_ref().map_or_else(skipped, |parse| format!(
+            "{} parsed, {} skipped, {} errors",
+            parse.parsed, parse.skipped, parse.errors
+        ))
+    );
+    say!(
+        "  Embed:   {}",
+        summary.embed.as_ref().map_or_else(skipped, |embed| format!(
+            "{} sessions, {} chunks, {} errors",
+            embed.processed, embed.chunks, embed.errors
+        ))
+    );
+    say!(
+        "  Push:    {}",
+        summary
+            .push
+            .as_ref()
+            .map_or_else(skipped, |push| format!("{} files pushed", push.pushed))
+    );
+    for warning in &summary.warnings {
+        say!("{}", format!("  Warning: {}", warning).yellow());
+    }
 
+    say!();
+    say!(
+        "{}",
+        format!(
+            "✓ Pipeline complete in {:.1}s",
+            summary.duration_ms as f64 / 1000.0
+        )
+        .green()
+        .bold()
+    );
     Ok(())
 }
 
 // ============ EXTRACT COMMAND ============
 
 fn cmd_extract() -> Result<()> {
-    println!("{}", "📁 EchoVault Extract".bold().cyan());
-    println!();
+    say!("{}", "📁 EchoVault Extract".bold().cyan());
+    say!();
 
     // Ensure config exists
     let config = ensure_config()?;
 
     let vault_dir = &config.vault_path;
-    println!("Vault: {}", vault_dir.display().to_string().dimmed());
-    println!();
-
-    let extracted = ingest_sessions(vault_dir)?;
-    if extracted {
-        println!();
-        println!("{}", "✓ Extraction complete!".green().bold());
+    say!("Vault: {}", vault_dir.display().to_string().dimmed());
+    say!();
+
+    let op = progress::interruptible()?;
+    let report = ingest_sessions(vault_dir, &config, None, &op)?;
+    if report.copied > 0 {
+        say!();
+        say!("{}", "✓ Extraction complete!".green().bold());
     } else {
-        println!("{}", "✓ All sessions already up-to-date".green());
+        say!("{}", "✓ All sessions already up-to-date".green());
     }
 
-    Ok(())
+    let sources: Vec<serde_json::Value> = report
+        .sources
+        .iter()
+        .map(|(source, found)| match found {
+            Ok(count) => serde_json::json!({ "source": source, "found": count }),
+            Err(error) => serde_json::json!({ "source": source, "error": error }),
+        })
+        .collect();
+    output::emit(&serde_json::json!({
+        "sources": sources,
+        "found": report.found,
+        "copied": report.copied,
+        "up_to_date": report.up_to_date,
+        "errors": report.errors,
+    }))
 }
 
 // ============ PARSE COMMAND ============
 
 fn cmd_parse() -> Result<()> {
-    println!("{}", "📝 EchoVault Parse".bold().cyan());
-    println!();
+    say!("{}", "📝 EchoVault Parse".bold().cyan());
+    say!();
 
     let config = ensure_config()?;
     let vault_dir = &config.vault_path;
-    let sessions_dir = vault_dir.join("sessions");
 
-    if !sessions_dir.exists() {
-        println!(
+    if !vault_dir.join("sessions").exists() {
+        say!(
             "{}",
             "No sessions found. Run 'echovault-cli extract' first.".yellow()
         );
-        return Ok(());
+        return output::emit(&ParseResult::default());
     }
 
-    println!("Vault: {}", vault_dir.display().to_string().dimmed());
-    println!();
-
-    use echovault_core::parsers::{all_parsers, markdown_writer, parse_vault_source};
-
-    let parsers = all_parsers();
-    let parsed_dir = vault_dir.join("parsed");
-
-    let mut total_parsed = 0usize;
-    let mut total_errors = 0usize;
-    let mut total_skipped = 0usize;
-
-    for parser in &parsers {
-        let source_dir = sessions_dir.join(parser.source_name());
-        if !source_dir.exists() {
-            continue;
-        }
-
-        print!("  Parsing {}...", parser.source_name());
-
-        let (conversations, errors) = parse_vault_source(parser.as_ref(), &sessions_dir);
-
-        let mut source_parsed = 0;
-        for (path, err) in &errors {
-            tracing::warn!("Error parsing {:?}: {}", path, err);
-        }
-
-        for conv in &conversations {
-            let output_path = parsed_dir
-                .join(&conv.source)
-                .join(format!("{}.md", conv.id));
-
-            // Skip if already parsed and source hasn't changed
-            if output_path.exists() {
-                total_skipped += 1;
-                continue;
-            }
-
-            match markdown_writer::write_markdown(conv, &output_path) {
-                Ok(()) => {
-                    source_parsed += 1;
-                    total_parsed += 1;
-                }
-                Err(e) => {
-                    tracing::warn!("Error writing {:?}: {}", output_path, e);
-                    total_errors += 1;
-                }
-            }
-        }
-
-        total_errors += errors.len();
-
-        println!(
-            " {} parsed, {} errors",
-            source_parsed.to_string().green(),
-            errors.len().to_string().red()
+    say!("Vault: {}", vault_dir.display().to_string().dimmed());
+    say!();
+
+    let op = progress::interruptible()?;
+    let summary = parse_vault(&config, &op)?;
+    for source in &summary.sources {
+        say!(
+            "  {}: {} parsed, {} errors {}",
+            source.source,
+            source.parsed.to_string().green(),
+            source.errors.to_string().red(),
+            format!("({:.1}s)", source.duration_ms as f64 / 1000.0).dimmed()
         );
     }
 
-    println!();
-    println!(
+    say!();
+    say!(
         "{}",
         format!(
-            "Complete: {} parsed, {} skipped, {} errors",
-            total_parsed, total_skipped, total_errors
+            "Complete: {} parsed, {} skipped, {} errors in {:.1}s",
+            summary.parsed,
+            summary.skipped,
+            summary.errors,
+            summary.duration_ms as f64 / 1000.0
         )
         .green()
         .bold()
     );
+    if let Some(slowest) = summary.slowest.first().filter(|f| f.duration_ms >= 1000) {
+        say!(
+            "{}",
+            format!(
+                "Slowest: {} ({:.1}s)",
+                slowest.path.display(),
+                slowest.duration_ms as f64 / 1000.0
+            )
+            .dimmed()
+        );
+    }
+    if summary.removed > 0 {
+        say!("Stale Markdown removed: {}", summary.removed);
+    }
+    if summary.duplicates > 0 {
+        say!(
+            "Duplicate sessions (hidden from lists and search): {}",
+            summary.duplicates
+        );
+    }
+    if let Some(notes) = summary.obsidian_notes {
+        say!("Obsidian notes updated: {}", notes);
+    }
 
-    Ok(())
+    output::emit(&summary)
 }
 
-// ============ INTERCEPT COMMAND ============
-
-fn cmd_intercept(port: u16) -> Result<()> {
-    use echovault_core::interceptor::{self, InterceptorConfig, InterceptorState};
+// ============ COMPRESS COMMAND ============
 
-    println!("{}", "Interceptor Proxy".bold().cyan());
+/// Compress the plain sessions of the vault in place.
+fn cmd_compress() -> Result<()> {
+    println!("{}", "🗜 EchoVault Compress".bold().cyan());
     println!();
 
-    let config = InterceptorConfig {
+    let config = ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let op = progress::interruptible()?;
+    let stats = compressed::compress_sessions(vault_dir, &op)?;
+    if stats.files == 0 {
+        println!("{}", "✓ No uncompressed sessions".green());
+    } else {
+        // Compressed copies replace the plain ones on the next push
+        LocalDb::open(vault_dir)?.mark_dirty_files(&stats.written)?;
+        println!(
+            "{} Compressed {} sessions: {:.1} MB -> {:.1} MB",
+            "✓".green(),
+            stats.files,
+            stats.bytes_before as f64 / 1_048_576.0,
+            stats.bytes_after as f64 / 1_048_576.0
+        );
+    }
+    if op.is_cancelled() {
+        println!(
+            "{}",
+            "Interrupted; run again to compress the rest.".yellow()
+        );
+    }
+    if !config.extractors.compress {
+        println!(
+            "{}",
+            "New sessions are stored uncompressed; set [extractors] compress = true to change that."
+                .dimmed()
+        );
+    }
+    Ok(())
+}
+
+// ============ DEDUPE COMMAND ============
+
+/// Show the duplicate links over all parsed sessions, and store them with `apply`.
+fn cmd_dedupe(apply: bool) -> Result<()> {
+    use echovault_core::dedupe;
+    use echovault_core::embedding::store::EmbeddingStore;
+    use std::collections::HashMap;
+
+    println!("{}", "Session Deduplication".bold().cyan());
+    println!();
+
+    let config = ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let mut db = VaultDb::open(vault_dir)?;
+    if db.signatures()?.is_empty() {
+        println!(
+            "{}",
+            "No conversation signatures yet. Run 'echovault-cli parse' first.".yellow()
+        );
+        return Ok(());
+    }
+
+    let links = dedupe::plan_duplicates(vault_dir)?;
+    let stored = db.duplicates()?;
+    let sessions: HashMap<String, SessionEntry> = db
+        .get_all_sessions()?
+        .into_iter()
+        .map(|s| (s.id.clone(), s))
+        .collect();
+    let describe = |id: &str| match sessions.get(id) {
+        Some(s) => format!(
+            "{} [{}]",
+            s.title.as_deref().unwrap_or("(untitled)"),
+            s.source
+        ),
+        None => id.to_string(),
+    };
+
+    if links.is_empty() {
+        println!("{}", "No duplicate sessions found.".green());
+    }
+    // Group duplicates under the capture that is kept
+    let mut groups: Vec<(&str, Vec<&echovault_core::storage::DuplicateLink>)> = Vec::new();
+    for link in &links {
+        match groups
+            .iter_mut()
+            .find(|(canonical, _)| *canonical == link.canonical_id)
+        {
+            Some((_, group)) => group.push(link),
+            None => groups.push((&link.canonical_id, vec![link])),
+        }
+    }
+    for (canonical, group) in &groups {
+        println!("{} {}", "keep".green(), describe(canonical));
+        for link in group {
+            let new = !stored
+                .iter()
+                .any(|s| s.session_id == link.session_id && s.canonical_id == link.canonical_id);
+            println!(
+                "  {} {} {}{}",
+                "dup".yellow(),
+                describe(&link.session_id),
+                format!("({:.0}% similar)", link.similarity * 100.0).dimmed(),
+                if new {
+                    " new".cyan().to_string()
+                } else {
+                    String::new()
+                }
+            );
+        }
+    }
+    let unlinked: Vec<_> = stored
+        .iter()
+        .filter(|s| !links.iter().any(|l| l.session_id == s.session_id))
+        .collect();
+    for link in &unlinked {
+        println!("{} {}", "unlink".cyan(), describe(&link.session_id));
+    }
+
+    println!();
+    println!(
+        "{} duplicates in {} groups ({} stored, {} no longer duplicates)",
+        links.len(),
+        groups.len(),
+        stored.len(),
+        unlinked.len()
+    );
+    if !apply {
+        println!("{}", "Dry run: use --apply to store these links.".dimmed());
+        return Ok(());
+    }
+
+    db.replace_duplicates(&links)?;
+    // Unlinked sessions are embedded again by the next `embed`
+    let mut dropped = 0;
+    if vault_dir.join("embeddings.db").exists() {
+        let store = EmbeddingStore::open(vault_dir)?;
+        for link in &links {
+            dropped += store.delete_session_chunks(&link.session_id)?;
+        }
+    }
+    println!(
+        "{}",
+        format!(
+            "Stored {} duplicate links, removed {} embedded chunks",
+            links.len(),
+            dropped
+        )
+        .green()
+        .bold()
+    );
+    Ok(())
+}
+
+// ============ INTERCEPT COMMAND ============
+
+fn cmd_intercept(port: u16) -> Result<()> {
+    use echovault_core::interceptor::{self, InterceptorConfig, InterceptorState};
+
+    println!("{}", "Interceptor Proxy".bold().cyan());
+    println!();
+
+    let config = InterceptorConfig {
         port,
         ..Default::default()
     };
@@ -451,34 +1531,35 @@ fn cmd_intercept(port: u16) -> Result<()> {
 // ============ EMBED COMMAND ============
 
 fn cmd_embed() -> Result<()> {
-    println!("{}", "Embedding Sessions".bold().cyan());
-    println!();
+    say!("{}", "Embedding Sessions".bold().cyan());
+    say!();
 
     let config = ensure_config()?;
     let vault_dir = &config.vault_path;
 
-    println!("Vault: {}", vault_dir.display().to_string().dimmed());
-    println!(
-        "API:   {} ({})",
-        config.embedding.api_base.dimmed(),
-        config.embedding.model.yellow()
-    );
-    println!();
+    say!("Vault: {}", vault_dir.display().to_string().dimmed());
+    match config.embedding.backend {
+        EmbeddingBackend::Api => say!(
+            "API:   {} ({})",
+            config.embedding.api_base.dimmed(),
+            config.embedding.model.yellow()
+        ),
+        EmbeddingBackend::Local => say!(
+            "Model: {} {}",
+            config.embedding.model.yellow(),
+            "(local, downloaded on first use)".dimmed()
+        ),
+    }
+    say!();
 
-    let embedding_config = echovault_core::embedding::EmbeddingConfig {
-        api_base: config.embedding.api_base,
-        api_key: config.embedding.api_key,
-        model: config.embedding.model,
-        chunk_size: config.embedding.chunk_size,
-        chunk_overlap: config.embedding.chunk_overlap,
-        batch_size: config.embedding.batch_size,
-    };
+    let embedding_config = embedding_config(&config);
 
-    println!("Processing conversations...");
-    match echovault_core::embedding::embed_vault(&embedding_config, vault_dir) {
+    say!("Processing conversations...");
+    let op = progress::interruptible()?;
+    match echovault_core::embedding::embed_vault(&embedding_config, vault_dir, &op) {
         Ok(result) => {
-            println!();
-            println!(
+            say!();
+            say!(
                 "{}",
                 format!(
                     "Complete: {} processed, {} chunks, {} skipped, {} errors",
@@ -492,15 +1573,18 @@ fn cmd_embed() -> Result<()> {
             );
 
             if !result.errors.is_empty() {
-                println!();
-                println!("{}", "Errors:".red());
+                say!();
+                say!("{}", "Errors:".red());
                 for (id, err) in &result.errors {
-                    println!("  {} - {}", id.dimmed(), err);
+                    say!("  {} - {}", id.dimmed(), err);
                 }
             }
+            output::emit(&result)?;
         }
+        // Scripts need a failing exit code
+        Err(e) if output::is_json() => return Err(e.context("Embedding failed")),
         Err(e) => {
-            println!("{}", format!("Embedding failed: {}", e).red());
+            say!("{}", format!("Embedding failed: {}", e).red());
         }
     }
 
@@ -509,38 +1593,56 @@ fn cmd_embed() -> Result<()> {
 
 // ============ SEARCH COMMAND ============
 
-fn cmd_search(query: &str, limit: usize) -> Result<()> {
-    println!("{}", "Semantic Search".bold().cyan());
-    println!();
+/// Embedding API settings from the `[embedding]` config section.
+fn embedding_config(config: &Config) -> echovault_core::embedding::EmbeddingConfig {
+    echovault_core::embedding::EmbeddingConfig::from_config(config)
+}
+
+fn cmd_search(
+    query: &str,
+    limit: usize,
+    format: SearchFormat,
+    options: &SearchOptions,
+) -> Result<()> {
+    if format == SearchFormat::ScriptFilter {
+        return cmd_search_script_filter(query, limit, options);
+    }
+
+    say!("{}", "Semantic Search".bold().cyan());
+    say!();
 
     let config = ensure_config()?;
     let vault_dir = &config.vault_path;
 
-    let embedding_config = echovault_core::embedding::EmbeddingConfig {
-        api_base: config.embedding.api_base,
-        api_key: config.embedding.api_key,
-        model: config.embedding.model,
-        chunk_size: config.embedding.chunk_size,
-        chunk_overlap: config.embedding.chunk_overlap,
-        batch_size: config.embedding.batch_size,
-    };
+    let embedding_config = embedding_config(&config);
 
-    println!("Query: {}", query.yellow());
-    println!();
+    say!("Query: {}", query.yellow());
+    say!();
 
-    match echovault_core::embedding::search_similar(&embedding_config, vault_dir, query, limit) {
+    let timer = metrics::Timer::start(metrics::SEARCH_LATENCY_MS);
+    let found = echovault_core::embedding::search_similar(
+        &embedding_config,
+        vault_dir,
+        query,
+        limit,
+        options,
+    );
+    if found.is_ok() {
+        timer.finish(&config);
+    }
+    match found {
         Ok(results) => {
             if results.is_empty() {
-                println!(
+                say!(
                     "{}",
                     "No results found. Run 'echovault-cli embed' first.".yellow()
                 );
-                return Ok(());
+                return output::emit(&results);
             }
 
             for (i, r) in results.iter().enumerate() {
                 let title = r.title.as_deref().unwrap_or("(untitled)");
-                println!(
+                say!(
                     "{}. {} [{}] (score: {:.3})",
                     (i + 1).to_string().bold(),
                     title.green(),
@@ -549,19 +1651,134 @@ fn cmd_search(query: &str, limit: usize) -> Result<()> {
                 );
                 // Show snippet (first 200 chars)
                 let snippet: String = r.chunk_content.chars().take(200).collect();
-                println!("   {}", snippet.dimmed());
-                println!("   ID: {}", r.session_id.dimmed());
-                println!();
+                say!("   {}", snippet.dimmed());
+                say!("   ID: {}", r.session_id.dimmed());
+                say!();
             }
+            output::emit(&results)?;
         }
+        Err(e) if output::is_json() => return Err(e.context("Search failed")),
         Err(e) => {
-            println!("{}", format!("Search failed: {}", e).red());
+            say!("{}", format!("Search failed: {}", e).red());
         }
     }
 
     Ok(())
 }
 
+/// Search for launchers (Alfred, Raycast): semantic search, falling back to
+/// keyword (FTS5) search when embeddings are unavailable, printed as Script
+/// Filter JSON only.
+fn cmd_search_script_filter(query: &str, limit: usize, options: &SearchOptions) -> Result<()> {
+    use echovault_core::storage::SessionIndex;
+    use script_filter::Hit;
+
+    let config = ensure_config()?;
+    let vault_dir = &config.vault_path;
+
+    let semantic = echovault_core::embedding::search_similar(
+        &embedding_config(&config),
+        vault_dir,
+        query,
+        limit,
+        options,
+    );
+    let output = match semantic {
+        Ok(results) if !results.is_empty() => {
+            // One item per session (its best-matching chunk)
+            let mut seen = std::collections::HashSet::new();
+            let hits: Vec<Hit> = results
+                .iter()
+                .filter(|r| seen.insert(r.session_id.as_str()))
+                .map(|r| Hit {
+                    id: &r.session_id,
+                    source: &r.source,
+                    title: r.title.as_deref(),
+                    detail: Some(&r.chunk_content),
+                })
+                .collect();
+            script_filter::render(vault_dir, &hits)?
+        }
+        semantic => {
+            if let Err(e) = semantic {
+                tracing::debug!("Semantic search unavailable, using keyword search: {}", e);
+            }
+            match SessionIndex::open(vault_dir).and_then(|index| index.search(query, limit)) {
+                Ok(sessions) => {
+                    let hits: Vec<Hit> = sessions
+                        .iter()
+                        .map(|s| Hit {
+                            id: &s.id,
+                            source: &s.source,
+                            title: s.title.as_deref(),
+                            detail: s.workspace_name.as_deref(),
+                        })
+                        .collect();
+                    script_filter::render(vault_dir, &hits)?
+                }
+                Err(e) => script_filter::render_error(&e)?,
+            }
+        }
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+// ============ GREP COMMAND ============
+
+fn cmd_grep(query: &str, mut filter: FtsFilter, limit: usize, reindex: bool) -> Result<()> {
+    use echovault_core::storage::fts::{HIGHLIGHT_END, HIGHLIGHT_START};
+
+    let config = ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let mut index = FtsIndex::open(vault_dir)?;
+
+    // Built on first use; parse keeps it up to date afterwards
+    if reindex || index.is_empty()? {
+        println!("Building the full-text index...");
+        let op = progress::interruptible()?;
+        let count = index.rebuild(vault_dir, &op)?;
+        println!("{}", format!("Indexed {} conversations", count).dimmed());
+        println!();
+    }
+
+    filter.exclude = echovault_core::dedupe::duplicate_ids(vault_dir);
+    let hits = index.search(query, &filter, limit)?;
+    if hits.is_empty() {
+        println!("{}", "No matches.".yellow());
+        return Ok(());
+    }
+
+    for (i, hit) in hits.iter().enumerate() {
+        let title = hit.title.as_deref().unwrap_or("(untitled)");
+        let mut context = vec![hit.source.clone()];
+        context.extend(hit.workspace.clone());
+        context.extend(hit.created_at.map(|d| d.format("%Y-%m-%d").to_string()));
+        println!(
+            "{}. {} [{}]",
+            (i + 1).to_string().bold(),
+            title.green(),
+            context.join(", ").dimmed()
+        );
+
+        // Matched terms in bold yellow, the rest dimmed
+        let mut snippet = String::new();
+        for (n, part) in hit.snippet.split(HIGHLIGHT_START).enumerate() {
+            match part.split_once(HIGHLIGHT_END) {
+                Some((term, rest)) if n > 0 => {
+                    snippet.push_str(&term.yellow().bold().to_string());
+                    snippet.push_str(&rest.dimmed().to_string());
+                }
+                _ => snippet.push_str(&part.dimmed().to_string()),
+            }
+        }
+        println!("   {}", snippet.replace('\n', " "));
+        println!("   ID: {}", hit.session_id.dimmed());
+        println!();
+    }
+    Ok(())
+}
+
 // ============ MCP COMMAND ============
 
 fn cmd_mcp() -> Result<()> {
@@ -572,30 +1789,288 @@ fn cmd_mcp() -> Result<()> {
     Ok(())
 }
 
+fn cmd_mcp_http(addr: std::net::SocketAddr, token: Option<String>) -> Result<()> {
+    use echovault_core::mcp;
+    use echovault_core::utils::http_auth;
+
+    println!("{}", "EchoVault MCP Server".bold().cyan());
+    println!();
+
+    ensure_config()?;
+    let token = token
+        .or_else(|| std::env::var("ECHOVAULT_MCP_TOKEN").ok())
+        .filter(|t| !t.is_empty());
+    let generated = token.is_none();
+    let token = token.unwrap_or_else(http_auth::generate_token);
+
+    println!(
+        "Listening on {}",
+        format!("http://{}{}", addr, mcp::HTTP_PATH).green()
+    );
+    if generated {
+        println!("Token: {}", token.yellow());
+    }
+    println!(
+        "Clients must send the header {}",
+        "Authorization: Bearer <token>".bold()
+    );
+    if !addr.ip().is_loopback() {
+        println!(
+            "{}",
+            "⚠ Listening beyond localhost; traffic is not encrypted (use a TLS proxy)".yellow()
+        );
+    }
+    println!("Press {} to stop.", "Ctrl+C".bold());
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(mcp::run_http_server(addr, token, async {
+        let _ = tokio::signal::ctrl_c().await;
+    }))
+}
+
+// ============ SERVE COMMAND ============
+
+fn cmd_serve(port: u16, token: Option<String>) -> Result<()> {
+    use echovault_core::api::{self, ApiState};
+    use echovault_core::utils::http_auth;
+
+    println!("{}", "EchoVault API".bold().cyan());
+    println!();
+
+    let config = ensure_config()?;
+    // Unlock once here: requests cannot prompt for the passphrase
+    let key = encryption::unlock_key(&config)?;
+    let token = token
+        .or_else(|| std::env::var(api::TOKEN_ENV).ok())
+        .filter(|t| !t.is_empty());
+    let generated = token.is_none();
+    let token = token.unwrap_or_else(http_auth::generate_token);
+    let sync_config = config.clone();
+    let state = ApiState::new(config, token.clone()).with_sync(Box::new(move |op| {
+        let provider = create_provider(&sync_config);
+        if !provider.is_authenticated() {
+            anyhow::bail!("Not authenticated. Please run 'echovault-cli auth' first.");
+        }
+        let started = chrono::Utc::now().timestamp_millis();
+        let outcome = run_sync(
+            &sync_config,
+            DEFAULT_SYNC_TARGET,
+            provider.as_ref(),
+            key.as_ref(),
+            false,
+            None,
+            op,
+        );
+        record_sync(&sync_config, DEFAULT_SYNC_TARGET, started, &outcome);
+        outcome.map(|_| ())
+    }));
+
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    println!("Listening on {}", format!("http://{}", addr).green());
+    println!("Endpoints: GET /sessions, GET /sessions/{{id}}, GET /search?q=, POST /sync");
+    if generated {
+        println!("Token: {}", token.yellow());
+    }
+    println!(
+        "Clients must send the header {}",
+        "Authorization: Bearer <token>".bold()
+    );
+    println!("Press {} to stop.", "Ctrl+C".bold());
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(api::serve(addr, state, async {
+        let _ = tokio::signal::ctrl_c().await;
+    }))
+}
+
+// ============ SCHEMA COMMAND ============
+
+fn cmd_schema() -> Result<()> {
+    // Raw JSON only, so the output can be redirected to a file
+    let schema = echovault_core::parsers::schema::conversation_schema();
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+// ============ DOCTOR COMMAND ============
+
+/// Report config problems; errors fail, and with `strict` unknown keys too.
+fn cmd_doctor(strict: bool) -> Result<()> {
+    say!("{}", "🩺 EchoVault Doctor".bold().cyan());
+    say!();
+
+    let path = echovault_core::config::default_config_path();
+    if path.exists() {
+        say!("Config:   {}", path.display());
+    } else {
+        say!(
+            "Config:   {} {}",
+            path.display(),
+            "(not found, using defaults)".dimmed()
+        );
+    }
+    let config = Config::load_default()?;
+    if let Some(profile) = config.profile_name() {
+        say!("Profile:  {}", profile.cyan());
+    }
+    say!();
+
+    let issues = config.validate();
+    output::emit(&serde_json::json!({
+        "config": path,
+        "profile": config.profile_name(),
+        "issues": issues,
+    }))?;
+    if issues.is_empty() {
+        say!("{}", "✓ No problems found".green());
+        return Ok(());
+    }
+
+    for issue in &issues {
+        let level = if issue.is_error() {
+            "error".red().bold()
+        } else {
+            "warning".yellow().bold()
+        };
+        say!("{} {}: {}", level, issue.key.bold(), issue.message);
+        say!("    {}", issue.hint.dimmed());
+    }
+
+    let mut errors = issues.iter().filter(|issue| issue.is_error()).count();
+    if strict {
+        errors += config.unknown_keys.len();
+    }
+    say!();
+    if errors > 0 {
+        anyhow::bail!("{} configuration error(s)", errors);
+    }
+    say!("{}", format!("{} warning(s)", issues.len()).yellow());
+    Ok(())
+}
+
 // ============ STATUS COMMAND ============
 
 fn cmd_status() -> Result<()> {
-    println!("{}", "📊 EchoVault Status".bold().cyan());
-    println!();
+    say!("{}", "📊 EchoVault Status".bold().cyan());
+    say!();
+
+    // Fields of --json, filled in along the human output
+    let mut status = serde_json::Map::new();
+    let mut set = |key: &str, value: serde_json::Value| {
+        status.insert(key.to_string(), value);
+    };
 
     // Auth status
-    let provider = RcloneProvider::new();
+    let provider = create_provider(&Config::load_default().unwrap_or_default());
+    set("authenticated", provider.is_authenticated().into());
     let auth_status = if provider.is_authenticated() {
         "Authenticated".green().to_string()
     } else {
         "Not authenticated".red().to_string()
     };
-    println!("Auth:     {}", auth_status);
+    say!("Auth:     {}", auth_status);
 
     // Config status
     match Config::load_default() {
         Ok(config) => {
-            println!("Vault:    {}", config.vault_path.display());
+            set("configured", true.into());
+            set("profile", config.profile_name().into());
+            set("vault", config.vault_path.display().to_string().into());
+            if let Some(profile) = config.profile_name() {
+                say!("Profile:  {}", profile.cyan());
+            }
+            say!("Vault:    {}", config.vault_path.display());
+            let issues = config.validate();
+            set("config_issues", issues.len().into());
+            if !issues.is_empty() {
+                say!(
+                    "Config:   {}",
+                    format!("{} problem(s), run `echovault-cli doctor`", issues.len()).yellow()
+                );
+            }
+            if !config.env_overrides.is_empty() {
+                let vars: Vec<&str> = config
+                    .env_overrides
+                    .iter()
+                    .map(|o| o.var.as_str())
+                    .collect();
+                set("env_overrides", vars.clone().into());
+                say!("Env:      {}", vars.join(", ").yellow());
+            }
+
+            let paused = echovault_core::watcher::is_capture_paused();
+            set("capture_paused", paused.into());
+            let capture = if paused {
+                "paused".yellow().to_string()
+            } else {
+                "active".green().to_string()
+            };
+            say!("Capture:  {}", capture);
+
+            let encrypted = echovault_core::VaultMetadata::load(&config.vault_path)
+                .map(|meta| meta.is_encrypted())
+                .unwrap_or(false);
+            set("encrypted", encrypted.into());
+            let encryption = if encrypted {
+                "enabled".green().to_string()
+            } else {
+                "disabled".dimmed().to_string()
+            };
+            say!("Encrypt:  {}", encryption);
+
+            // Background scheduling state (battery / metered network)
+            let deferred = echovault_core::utils::power::defer_reason(&config.schedule);
+            set(
+                "deferred",
+                deferred.as_ref().map(|reason| reason.to_string()).into(),
+            );
+            match deferred {
+                Some(reason) => say!(
+                    "Schedule: {}",
+                    format!("background work deferred ({})", reason).yellow()
+                ),
+                None => say!("Schedule: {}", "background work allowed".green()),
+            }
+
+            // Last sync with each target from this machine
+            let states = LocalDb::open(&config.vault_path)
+                .and_then(|db| db.remote_sync_states())
+                .unwrap_or_default();
+            set("sync", serde_json::to_value(&states)?);
+            let format_time = |secs: i64| {
+                chrono::DateTime::from_timestamp(secs, 0)
+                    .map(|d| {
+                        d.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_default()
+            };
+            for name in config.sync.target_names() {
+                let state = match states.iter().find(|state| state.remote == name) {
+                    None => "never synced".dimmed().to_string(),
+                    Some(state) => {
+                        let last = state
+                            .last_success
+                            .map(format_time)
+                            .unwrap_or_else(|| "never".to_string());
+                        match &state.error {
+                            Some(error) => format!("failed: {} (last success: {})", error, last)
+                                .red()
+                                .to_string(),
+                            None => format!("{} ({} files pushed)", last, state.files_pushed)
+                                .green()
+                                .to_string(),
+                        }
+                    }
+                };
+                say!("Sync:     {} - {}", name, state);
+            }
 
             // Count sessions in vault
             if let Ok(vault_db) = VaultDb::open(&config.vault_path) {
                 if let Ok(sessions) = vault_db.get_all_sessions() {
-                    println!("Sessions: {}", sessions.len().to_string().cyan());
+                    say!("Sessions: {}", sessions.len().to_string().cyan());
 
                     // Count by source
                     let mut by_source: std::collections::HashMap<String, usize> =
@@ -603,480 +2078,376 @@ fn cmd_status() -> Result<()> {
                     for session in &sessions {
                         *by_source.entry(session.source.clone()).or_insert(0) += 1;
                     }
+                    set("sessions", sessions.len().into());
+                    set("sessions_by_source", serde_json::to_value(&by_source)?);
                     for (source, count) in by_source {
-                        println!("  - {}: {}", source, count);
+                        say!("  - {}: {}", source, count);
                     }
                 }
             }
         }
         Err(_) => {
-            println!("Config:   {}", "Not configured".yellow());
-            println!();
-            println!("Run {} to set up.", "echovault-cli auth".cyan());
+            set("configured", false.into());
+            say!("Config:   {}", "Not configured".yellow());
+            say!();
+            say!("Run {} to set up.", "echovault-cli auth".cyan());
         }
     }
 
-    Ok(())
+    output::emit(&status)
 }
 
-// ============ HELPER FUNCTIONS ============
+// ============ STATS COMMAND ============
 
-/// Ensure config exists, create default if not
-fn ensure_config() -> Result<Config> {
-    match Config::load_default() {
-        Ok(c) if c.setup_complete => Ok(c),
-        _ => {
-            // Create default config
-            println!("Creating default configuration...");
-            let vault_path = dirs::data_local_dir()
-                .context("Cannot find local data directory")?
-                .join("echovault")
-                .join("vault");
-            fs::create_dir_all(&vault_path)?;
+fn cmd_stats() -> Result<()> {
+    println!("{}", "📈 EchoVault Stats".bold().cyan());
+    println!();
 
-            let config = Config {
-                vault_path,
-                setup_complete: true,
-                ..Config::default()
-            };
-            config.save_default()?;
-            Ok(config)
-        }
-    }
-}
+    let config = Config::load_default()?;
+    print_vault_stats(&echovault_core::analytics::vault_stats(&config.vault_path)?);
 
-/// Import sessions from vault/sessions folder into vault.db
-fn import_vault_sessions(vault_dir: &Path) -> Result<usize> {
-    let sessions_dir = vault_dir.join("sessions");
-    if !sessions_dir.exists() {
-        return Ok(0);
+    if !config.metrics.enabled {
+        println!(
+            "{}",
+            "Metrics are disabled ([metrics] enabled = false)".yellow()
+        );
     }
 
-    let mut vault_db = VaultDb::open(vault_dir)?;
-    let existing_sessions = vault_db.get_all_sessions()?;
-    let existing_mtimes: std::collections::HashMap<String, u64> = existing_sessions
-        .iter()
-        .map(|s| (s.id.clone(), s.mtime))
-        .collect();
-
-    let mut sessions_to_import: Vec<SessionEntry> = Vec::new();
-
-    // Scan all subdirectories
-    if let Ok(entries) = fs::read_dir(&sessions_dir) {
-        for entry in entries.filter_map(|e| e.ok()) {
-            let source_dir = entry.path();
-            if !source_dir.is_dir() {
-                continue;
-            }
-
-            let source_name = source_dir
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            if let Ok(files) = fs::read_dir(&source_dir) {
-                for file in files.filter_map(|f| f.ok()) {
-                    let file_path = file.path();
-
-                    let extension = file_path.extension().and_then(|e| e.to_str());
-                    if !matches!(
-                        extension,
-                        Some("json") | Some("jsonl") | Some("pb") | Some("md")
-                    ) {
-                        continue;
-                    }
-
-                    let session_id = file_path
-                        .file_stem()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("")
-                        .to_string();
-
-                    if session_id.is_empty() {
-                        continue;
-                    }
-
-                    let metadata = match fs::metadata(&file_path) {
-                        Ok(m) => m,
-                        Err(_) => continue,
-                    };
-
-                    let file_mtime = metadata
-                        .modified()
-                        .ok()
-                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                        .map(|d| d.as_secs())
-                        .unwrap_or(0);
-
-                    if let Some(&existing_mtime) = existing_mtimes.get(&session_id) {
-                        if existing_mtime >= file_mtime {
-                            continue;
-                        }
-                    }
-
-                    let (title, workspace_name, created_at) = if extension == Some("json")
-                        || extension == Some("jsonl")
-                    {
-                        match extension {
-                            Some("jsonl") => {
-                                // JSONL: read first line, parse v.customTitle/v.creationDate
-                                use std::io::BufRead;
-                                match std::fs::File::open(&file_path) {
-                                    Ok(file) => {
-                                        let reader = std::io::BufReader::new(file);
-                                        if let Some(Ok(first_line)) = reader.lines().next() {
-                                            if let Ok(obj) = serde_json::from_str::<serde_json::Value>(
-                                                &first_line,
-                                            ) {
-                                                let v = obj.get("v");
-                                                let title = v
-                                                    .and_then(|v| v.get("customTitle"))
-                                                    .and_then(|v| v.as_str())
-                                                    .map(|s| s.to_string());
-                                                let created = v
-                                                        .and_then(|v| v.get("creationDate"))
-                                                        .and_then(|v| v.as_i64())
-                                                        .map(|ts| {
-                                                            chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ts)
-                                                                .map(|d| d.to_rfc3339())
-                                                                .unwrap_or_default()
-                                                        });
-                                                (title, None, created)
-                                            } else {
-                                                (None, None, None)
-                                            }
-                                        } else {
-                                            (None, None, None)
-                                        }
-                                    }
-                                    Err(_) => (None, None, None),
-                                }
-                            }
-                            _ => match fs::read_to_string(&file_path) {
-                                Ok(content) => {
-                                    if let Ok(json) =
-                                        serde_json::from_str::<serde_json::Value>(&content)
-                                    {
-                                        let title = json
-                                            .get("title")
-                                            .or_else(|| json.get("name"))
-                                            .and_then(|v| v.as_str())
-                                            .map(|s| s.to_string());
-                                        let workspace = json
-                                            .get("workspace_name")
-                                            .or_else(|| json.get("workspaceName"))
-                                            .and_then(|v| v.as_str())
-                                            .map(|s| s.to_string());
-                                        let created = json
-                                            .get("created_at")
-                                            .or_else(|| json.get("createdAt"))
-                                            .and_then(|v| v.as_str())
-                                            .map(|s| s.to_string());
-                                        (title, workspace, created)
-                                    } else {
-                                        (None, None, None)
-                                    }
-                                }
-                                Err(_) => (None, None, None),
-                            },
-                        }
-                    } else {
-                        (None, None, None)
-                    };
-
-                    let ext = extension.unwrap_or("json");
-                    let vault_path = format!("sessions/{}/{}.{}", source_name, session_id, ext);
-
-                    sessions_to_import.push(SessionEntry {
-                        id: session_id,
-                        source: source_name.clone(),
-                        mtime: file_mtime,
-                        file_size: metadata.len(),
-                        title,
-                        workspace_name,
-                        created_at,
-                        vault_path,
-                        original_path: file_path.to_string_lossy().to_string(),
-                    });
-                }
-            }
+    let summaries = metrics::summaries(&config)?;
+    if summaries.is_empty() {
+        println!(
+            "{}",
+            "No metrics recorded yet. Run a sync or search first.".dimmed()
+        );
+    } else {
+        println!("{}", "This machine, last 90 days:".bold());
+        for summary in &summaries {
+            println!(
+                "  {:<20} {:>5} runs  avg {:>10.1}  min {:>10.1}  max {:>10.1}",
+                summary.name,
+                summary.count,
+                summary.average(),
+                summary.min,
+                summary.max
+            );
         }
     }
 
-    let import_count = sessions_to_import.len();
-
-    if import_count > 0 {
-        vault_db.upsert_batch(&sessions_to_import)?;
-    }
+    println!();
+    let telemetry = if metrics::export_allowed(&config) {
+        "opted in (nothing is exported yet)".yellow().to_string()
+    } else {
+        "off (metrics never leave this machine)".green().to_string()
+    };
+    println!("Telemetry: {}", telemetry);
 
-    Ok(import_count)
+    Ok(())
 }
 
-/// Ingest sessions from local extractors into vault
-fn ingest_sessions(vault_dir: &Path) -> Result<bool> {
-    let mut all_sessions: Vec<SessionFile> = Vec::new();
-
-    // Collect sessions from all extractors
-    println!("  Scanning VS Code Copilot...");
-    let vscode_extractor = VSCodeCopilotExtractor::new();
-    if let Ok(locations) = vscode_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = vscode_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
+/// Sessions, messages, characters and their breakdowns across the vault.
+fn print_vault_stats(stats: &echovault_core::analytics::VaultStats) {
+    if stats.sessions == 0 {
+        println!(
+            "{}",
+            "No sessions in the vault yet. Run 'echovault-cli extract' first.".dimmed()
+        );
+        println!();
+        return;
     }
 
-    println!("  Scanning Antigravity...");
-    let antigravity_extractor = AntigravityExtractor::new();
-    if let Ok(locations) = antigravity_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = antigravity_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
+    let m = &stats.messages;
+    println!("{}", "Vault:".bold());
+    println!("  Sessions:   {}", stats.sessions);
+    if stats.unparsed > 0 {
+        println!(
+            "  {}",
+            format!("({} could not be parsed)", stats.unparsed).dimmed()
+        );
     }
-
-    println!("  Scanning Cursor...");
-    let cursor_extractor = CursorExtractor::new();
-    if let Ok(locations) = cursor_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = cursor_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
+    println!(
+        "  Messages:   {} ({} user, {} assistant, {} system, {} tool, {} info)",
+        m.total(),
+        m.user,
+        m.assistant,
+        m.system,
+        m.tool,
+        m.info
+    );
+    println!("  Characters: {}", stats.characters);
+
+    let groups = [
+        ("By source:", &stats.sources),
+        ("Top workspaces:", &stats.workspaces),
+        ("By month:", &stats.months),
+        ("Busiest days:", &stats.busiest_days),
+    ];
+    for (heading, groups) in groups {
+        if groups.is_empty() {
+            continue;
         }
-    }
-
-    println!("  Scanning Cline...");
-    let cline_extractor = ClineExtractor::new();
-    if let Ok(locations) = cline_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = cline_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
+        println!();
+        println!("{}", heading.bold());
+        for group in groups {
+            println!(
+                "  {:<30} {:>6} sessions {:>8} messages",
+                group.key, group.sessions, group.messages
+            );
         }
     }
-
-    println!("  Scanning Gemini CLI...");
-    let gemini_extractor = GeminiCliExtractor::new();
-    if let Ok(locations) = gemini_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = gemini_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
+    if !stats.models.is_empty() {
+        println!();
+        println!("{}", "Top models (assistant messages):".bold());
+        for model in &stats.models {
+            println!(
+                "  {:<30} {:>6} sessions {:>8} messages",
+                model.model, model.sessions, model.messages
+            );
         }
     }
+    println!();
+}
 
-    println!("  Scanning Claude Code...");
-    let claude_extractor = ClaudeCodeExtractor::new();
-    if let Ok(locations) = claude_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = claude_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
-    }
+fn cmd_stats_json(days: u32) -> Result<()> {
+    let config = Config::load_default()?;
+    output::emit(&serde_json::json!({
+        "vault": echovault_core::analytics::vault_stats(&config.vault_path)?,
+        "metrics": metrics::summaries(&config)?,
+        "usage": echovault_core::analytics::usage_dashboard(&config.vault_path, days)?,
+    }))
+}
 
-    println!("  Scanning Aider...");
-    let aider_extractor = AiderExtractor::new();
-    if let Ok(locations) = aider_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = aider_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
-    }
+fn cmd_costs(period: echovault_core::analytics::Period) -> Result<()> {
+    use echovault_core::analytics::{self, Period};
+    use std::collections::BTreeMap;
 
-    println!("  Scanning Codex...");
-    let codex_extractor = CodexExtractor::new();
-    if let Ok(locations) = codex_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = codex_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
-    }
+    say!("{}", "💰 EchoVault Costs".bold().cyan());
+    say!();
 
-    println!("  Scanning Continue.dev...");
-    let continue_dev_extractor = ContinueDevExtractor::new();
-    if let Ok(locations) = continue_dev_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = continue_dev_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
+    let config = ensure_config()?;
+    let report = analytics::vault_cost_report(&config, period)?;
+    if output::is_json() {
+        return output::emit(&report);
     }
-
-    println!("  Scanning OpenCode...");
-    let opencode_extractor = OpenCodeExtractor::new();
-    if let Ok(locations) = opencode_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = opencode_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
+    if report.rows.is_empty() {
+        println!(
+            "{}",
+            "No token usage found. Run 'echovault-cli extract' first.".dimmed()
+        );
+        return Ok(());
     }
 
-    println!("  Scanning Zed...");
-    let zed_extractor = ZedExtractor::new();
-    if let Ok(locations) = zed_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = zed_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
+    let heading = match period {
+        Period::Day => "Day",
+        Period::Week => "Week of",
+        Period::Month => "Month of",
+    };
+    println!(
+        "{}",
+        format!(
+            "  {:<10}  {:<16} {:<28} {:>12} {:>12} {:>10}",
+            heading, "Tool", "Model", "Input", "Output", "Cost"
+        )
+        .bold()
+    );
+    for row in &report.rows {
+        let cost = row
+            .cost
+            .map(|c| format!("${:.2}", c))
+            .unwrap_or_else(|| "-".to_string());
+        let marker = if row.estimated { "~" } else { " " };
+        println!(
+            "  {:<10}  {:<16} {:<28} {:>12} {:>12} {:>9}{}",
+            row.period_start,
+            row.source,
+            row.model,
+            row.input_tokens,
+            row.output_tokens,
+            cost,
+            marker
+        );
     }
 
-    println!("  Scanning JetBrains AI...");
-    let jetbrains_extractor = JetBrainsExtractor::new();
-    if let Ok(locations) = jetbrains_extractor.find_storage_locations() {
-        for location in &locations {
-            if let Ok(files) = jetbrains_extractor.list_session_files(location) {
-                all_sessions.extend(files);
-            }
-        }
+    // Spend per tool and period, newest first
+    let mut per_tool: BTreeMap<_, f64> = BTreeMap::new();
+    for row in &report.rows {
+        *per_tool
+            .entry((std::cmp::Reverse(row.period_start), row.source.as_str()))
+            .or_default() += row.cost.unwrap_or(0.0);
+    }
+    println!();
+    println!("{}", "Per tool:".bold());
+    for ((std::cmp::Reverse(period_start), source), cost) in per_tool {
+        println!(
+            "  {:<10}  {:<16} {:>10}",
+            period_start,
+            source,
+            format!("${:.2}", cost)
+        );
     }
 
-    let total_sessions = all_sessions.len();
-    println!("  Found {} sessions total", total_sessions);
-
-    if total_sessions == 0 {
-        return Ok(false);
+    println!();
+    println!(
+        "Total: {}",
+        format!("${:.2}", report.total_cost).green().bold()
+    );
+    println!(
+        "{}",
+        "~ includes tokens estimated from text length (about 4 characters per token)".dimmed()
+    );
+    if !report.unpriced_models.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "No price for: {} (add them to [analytics.prices])",
+                report.unpriced_models.join(", ")
+            )
+            .yellow()
+        );
     }
+    Ok(())
+}
 
-    // Open vault database
-    let vault_db = VaultDb::open(vault_dir)?;
-    let sessions_dir = vault_dir.join("sessions");
-    fs::create_dir_all(&sessions_dir)?;
+// ============ DIGEST COMMAND ============
 
-    // Filter sessions that need processing
-    let sessions_to_process: Vec<_> = all_sessions
-        .into_iter()
-        .filter_map(|session| {
-            let source_path = &session.metadata.original_path;
-            let file_size = session.metadata.file_size;
+fn cmd_digest(
+    weekly: bool,
+    date: Option<chrono::NaiveDate>,
+    summarize: bool,
+    print: bool,
+) -> Result<()> {
+    use echovault_core::analytics::Period;
+    use echovault_core::digest;
 
-            let metadata = match fs::metadata(source_path) {
-                Ok(m) => m,
-                Err(_) => return None,
-            };
+    let config = ensure_config()?;
+    let period = if weekly { Period::Week } else { Period::Day };
+    let date =
+        date.unwrap_or_else(|| digest::last_complete(period, chrono::Utc::now().date_naive()));
 
-            let mtime = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
-
-            let should_process = match vault_db.get_session_mtime(&session.metadata.id) {
-                Ok(Some(cached_mtime)) => mtime > cached_mtime,
-                Ok(None) => true,
-                Err(_) => true,
-            };
+    let (digest, path) = digest::generate(&config, period, date, summarize)?;
+    if print {
+        println!("{}", digest.to_markdown());
+        return Ok(());
+    }
+    println!("{}", digest.title().bold().cyan());
+    println!();
+    println!("New conversations: {}", digest.total);
+    for (source, count) in &digest.per_source {
+        println!("  {:<16} {}", source, count);
+    }
+    let summary_expected = summarize && digest.total > 0 && config.digest.chat_api_base.is_some();
+    if summary_expected && digest.summary.is_none() {
+        println!(
+            "{}",
+            "Summary unavailable (see the log for the chat API error)".yellow()
+        );
+    }
+    println!();
+    println!("{} {}", "✓ Saved to".green(), path.display());
+    Ok(())
+}
 
-            if should_process {
-                Some((session, mtime, file_size))
-            } else {
-                None
-            }
-        })
-        .collect();
+// ============ PAUSE/RESUME COMMANDS ============
 
-    let to_process = sessions_to_process.len();
-    let skipped = total_sessions - to_process;
+fn cmd_pause(paused: bool) -> Result<()> {
+    echovault_core::watcher::set_capture_paused(paused)?;
 
-    if to_process == 0 {
-        return Ok(false);
+    if paused {
+        println!("{}", "⏸ Capture paused".yellow());
+        println!(
+            "File changes are ignored until you run {}.",
+            "echovault-cli resume".cyan()
+        );
+    } else {
+        println!("{}", "▶ Capture resumed".green());
     }
 
-    println!(
-        "  Processing {} sessions ({} up-to-date)",
-        to_process, skipped
-    );
-
-    // Progress bar
-    let pb = ProgressBar::new(to_process as u64);
-    pb.set_style(
-        ProgressStyle::with_template("  [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)")
-            .unwrap()
-            .progress_chars("█▓░"),
-    );
+    Ok(())
+}
 
-    let processed = AtomicUsize::new(0);
-    let errors = Mutex::new(Vec::<String>::new());
-    let new_entries = Mutex::new(Vec::<SessionEntry>::new());
-
-    // Process in parallel
-    sessions_to_process
-        .par_iter()
-        .for_each(|(session, mtime, file_size)| {
-            let source_path = &session.metadata.original_path;
-            let dest_dir = sessions_dir.join(&session.metadata.source);
-
-            if let Err(e) = fs::create_dir_all(&dest_dir) {
-                errors
-                    .lock()
-                    .unwrap()
-                    .push(format!("Failed to create dir {:?}: {}", dest_dir, e));
-                return;
-            }
+// ============ HELPER FUNCTIONS ============
 
-            let extension = source_path
-                .extension()
-                .and_then(|e| e.to_str())
-                .unwrap_or("json");
+/// Ensure config exists, create default if not
+fn ensure_config() -> Result<Config> {
+    match Config::load_default() {
+        Ok(c) if c.setup_complete => Ok(c),
+        // Never replace a config holding profiles with defaults
+        Ok(mut c) if c.profile_name().is_some() => {
+            fs::create_dir_all(&c.vault_path)?;
+            c.setup_complete = true;
+            c.save_default()?;
+            Ok(c)
+        }
+        Err(e) if echovault_core::config::active_profile().is_some() => Err(e),
+        _ => {
+            // Create default config
+            eprintln!("Creating default configuration...");
+            let vault_path = dirs::data_local_dir()
+                .context("Cannot find local data directory")?
+                .join("echovault")
+                .join("vault");
+            fs::create_dir_all(&vault_path)?;
 
-            let dest_path = if session.metadata.id.ends_with(extension) {
-                dest_dir.join(&session.metadata.id)
-            } else {
-                dest_dir.join(format!("{}.{}", session.metadata.id, extension))
+            let config = Config {
+                vault_path,
+                setup_complete: true,
+                ..Config::default()
             };
+            config.save_default()?;
+            Ok(config)
+        }
+    }
+}
 
-            if let Some(parent) = dest_path.parent() {
-                if !parent.exists() {
-                    let _ = fs::create_dir_all(parent);
-                }
-            }
-
-            if let Err(e) = fs::copy(source_path, &dest_path) {
-                errors
-                    .lock()
-                    .unwrap()
-                    .push(format!("Failed to copy {}: {}", session.metadata.id, e));
-                return;
-            }
+/// Ingest sessions from local extractors into vault, printing what was
+/// found per source. With `only`, the other sources are not scanned.
+///
+/// When `op` is cancelled, sessions already copied are still recorded, and
+/// the rest are picked up by the next ingest.
+fn ingest_sessions(
+    vault_dir: &Path,
+    config: &Config,
+    only: Option<&[&str]>,
+    op: &Operation,
+) -> Result<ingest::IngestReport> {
+    say!("  Scanning sources...");
+    let report = match only {
+        Some(sources) => ingest::ingest_sources(vault_dir, config, sources, op)?,
+        None => ingest::ingest_sessions(vault_dir, config, op)?,
+    };
 
-            new_entries.lock().unwrap().push(SessionEntry {
-                id: session.metadata.id.clone(),
-                source: session.metadata.source.clone(),
-                mtime: *mtime,
-                file_size: *file_size,
-                title: session.metadata.title.clone(),
-                workspace_name: session.metadata.workspace_name.clone(),
-                created_at: session.metadata.created_at.map(|d| d.to_rfc3339()),
-                vault_path: dest_path.to_string_lossy().to_string(),
-                original_path: source_path.to_string_lossy().to_string(),
-            });
-
-            processed.fetch_add(1, Ordering::Relaxed);
-            pb.inc(1);
-        });
-
-    pb.finish();
-
-    // Update vault.db
-    let entries = new_entries.into_inner().unwrap();
-    if !entries.is_empty() {
-        for entry in &entries {
-            if let Err(e) = vault_db.upsert_session(entry) {
-                tracing::warn!("Failed to upsert {}: {}", entry.id, e);
-            }
+    for (source, found) in &report.sources {
+        match found {
+            Ok(0) => {}
+            Ok(count) => say!("    {}: {}", source, count),
+            Err(e) => say!("    {}", format!("Warning: {}: {}", source, e).yellow()),
         }
-        if let Err(e) = vault_db.log_sync("ingest", Some(&format!("{} sessions", entries.len()))) {
-            tracing::warn!("Failed to log sync: {}", e);
+    }
+    say!("  Found {} sessions total", report.found);
+    if report.copied + report.errors.len() > 0 {
+        say!(
+            "  Processed {} sessions ({} up-to-date)",
+            report.copied + report.errors.len(),
+            report.up_to_date
+        );
+    }
+
+    // Titles and creation times were skipped while listing; fill them in from
+    // the vault copies now that the copy is done
+    if report.needs_enrichment() {
+        match enrich::enrich_metadata(vault_dir) {
+            Ok(0) => {}
+            Ok(count) => say!("  Enriched metadata of {} sessions", count),
+            Err(e) => tracing::warn!("Failed to enrich session metadata: {}", e),
         }
     }
 
-    Ok(true)
+    if report.copied > 0 {
+        notifications::notify(
+            &config.notifications,
+            &notifications::Event::SessionsExtracted {
+                count: report.copied,
+            },
+        );
+    }
+    Ok(report)
 }
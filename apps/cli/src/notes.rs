@@ -0,0 +1,177 @@
+//! Notes commands - annotate sessions with notes, tags, ratings and follow-up
+//! flags.
+//!
+//! Annotations live in vault.db and are copied into the frontmatter of the
+//! parsed Markdown. Notes are searchable here (FTS5) and, after `embed`,
+//! through semantic search.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::parsers::markdown_writer;
+use echovault_core::storage::{Annotation, AnnotationUpdate, VaultDb};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Subcommand)]
+pub enum NotesAction {
+    /// Set the note, tags, rating or follow-up flag of a session (others are kept)
+    Set {
+        /// Session ID
+        session_id: String,
+
+        /// Free-form note ("" removes it)
+        #[arg(short, long)]
+        note: Option<String>,
+
+        /// Tag (repeatable); replaces the existing tags
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+
+        /// Remove all tags
+        #[arg(long, conflicts_with = "tags")]
+        clear_tags: bool,
+
+        /// Rating from 1 to 5 (0 clears it)
+        #[arg(short, long)]
+        rating: Option<u8>,
+
+        /// Flag the session to come back to (true/false)
+        #[arg(long)]
+        follow_up: Option<bool>,
+    },
+    /// Show the annotation of a session
+    Show {
+        /// Session ID
+        session_id: String,
+    },
+    /// Remove the annotation of a session
+    Remove {
+        /// Session ID
+        session_id: String,
+    },
+    /// List annotated sessions, most recently changed first
+    List {
+        /// Only sessions flagged for follow-up
+        #[arg(long)]
+        follow_up: bool,
+    },
+    /// Full-text search in notes
+    Search {
+        /// FTS5 query
+        query: String,
+
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+}
+
+pub fn cmd_notes(action: NotesAction) -> Result<()> {
+    let config = crate::ensure_config()?;
+    let db = VaultDb::open(&config.vault_path)?;
+
+    match action {
+        NotesAction::Set {
+            session_id,
+            note,
+            tags,
+            clear_tags,
+            rating,
+            follow_up,
+        } => {
+            let tags = (clear_tags || !tags.is_empty()).then_some(tags);
+            if note.is_none() && tags.is_none() && rating.is_none() && follow_up.is_none() {
+                bail!("Nothing to set; pass --note, --tag, --clear-tags, --rating or --follow-up");
+            }
+            let update = AnnotationUpdate {
+                note,
+                tags,
+                rating,
+                follow_up,
+            };
+            let annotation = db.annotate(&session_id, &update)?;
+            write_markdown(&config.vault_path, &session_id, annotation.as_ref());
+            match annotation {
+                Some(annotation) => print_annotation(&annotation, None),
+                None => println!("{}", "Annotation removed (nothing left)".yellow()),
+            }
+        }
+        NotesAction::Show { session_id } => match db.annotation(&session_id)? {
+            Some(annotation) => print_annotation(&annotation, None),
+            None => println!("No annotation for {}", session_id),
+        },
+        NotesAction::Remove { session_id } => {
+            if db.delete_annotation(&session_id)? {
+                write_markdown(&config.vault_path, &session_id, None);
+                println!("{}", "✓ Annotation removed".green());
+            } else {
+                println!("No annotation for {}", session_id);
+            }
+        }
+        NotesAction::List { follow_up } => {
+            let annotations = db.annotations(follow_up)?;
+            if annotations.is_empty() {
+                println!("No annotated sessions.");
+            }
+            let titles = titles(&db)?;
+            for annotation in &annotations {
+                print_annotation(annotation, titles.get(&annotation.session_id));
+            }
+        }
+        NotesAction::Search { query, limit } => {
+            let annotations = db.search_annotations(&query, limit)?;
+            if annotations.is_empty() {
+                println!("No notes match '{}'.", query);
+            }
+            let titles = titles(&db)?;
+            for annotation in &annotations {
+                print_annotation(annotation, titles.get(&annotation.session_id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy an annotation into the parsed Markdown of its session.
+pub(crate) fn write_markdown(vault_dir: &Path, session_id: &str, annotation: Option<&Annotation>) {
+    if let Err(e) = markdown_writer::write_annotation(vault_dir, session_id, annotation) {
+        tracing::warn!(
+            "Failed to write annotation of {} to Markdown: {}",
+            session_id,
+            e
+        );
+    }
+}
+
+/// Session titles by ID.
+fn titles(db: &VaultDb) -> Result<HashMap<String, String>> {
+    Ok(db
+        .get_all_sessions()?
+        .into_iter()
+        .filter_map(|s| Some((s.id, s.title?)))
+        .collect())
+}
+
+fn print_annotation(annotation: &Annotation, title: Option<&String>) {
+    let mut header = title
+        .map(|t| t.green().to_string())
+        .unwrap_or_else(|| annotation.session_id.green().to_string());
+    if let Some(rating) = annotation.rating {
+        header.push_str(&format!(" {}", "★".repeat(rating as usize).yellow()));
+    }
+    if annotation.follow_up {
+        header.push_str(&format!(" {}", "[follow-up]".cyan()));
+    }
+    println!("{}", header);
+    if title.is_some() {
+        println!("   ID: {}", annotation.session_id.dimmed());
+    }
+    if !annotation.tags.is_empty() {
+        println!("   Tags: {}", annotation.tags.join(", ").cyan());
+    }
+    for line in annotation.note.lines() {
+        println!("   {}", line);
+    }
+    println!();
+}
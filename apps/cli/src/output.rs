@@ -0,0 +1,43 @@
+//! Machine-readable output (global `--json` flag).
+//!
+//! Commands that support it (extract, status, sync, parse, embed, search,
+//! doctor, list, show, open, tag, redact, config check/get) print one JSON document to stdout
+//! and their usual human-readable output to stderr, so scripts can pipe stdout
+//! straight into `jq`. They print human output with [`say!`] instead of `println!` and
+//! finish with [`emit`].
+
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Switch JSON output on (from `--json`).
+pub fn set_json(enabled: bool) {
+    JSON.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--json` was given.
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// `println!` for human output: stdout, or stderr with `--json`.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use say;
+
+/// Print the result of a command as JSON to stdout (only with `--json`).
+pub fn emit<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    if is_json() {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,178 @@
+//! Profile commands - manage profiles with their own config file.
+//!
+//! `add` lists a profile in `profiles.toml` and writes its config file (own
+//! vault, remote folder, sources, sync targets); `use` picks the profile
+//! commands run with when neither `--profile` nor `ECHOVAULT_PROFILE` is
+//! given. `list` also shows the `[profiles.<name>]` sections of
+//! echovault.toml.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::config::{active_profile, main_config_path, ProfilesIndex};
+use echovault_core::Config;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// List profiles, marking the active one
+    List,
+    /// Create a profile with its own config file
+    Add {
+        /// Profile name (letters, digits, '-' and '_')
+        name: String,
+
+        /// Vault directory (default: <data dir>/echovault/profiles/<name>/vault)
+        #[arg(long)]
+        vault: Option<PathBuf>,
+
+        /// Config file (default: <config dir>/echovault/profiles/<name>.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Use a profile by default (`--none` goes back to echovault.toml)
+    Use {
+        /// Profile name
+        #[arg(required_unless_present = "none")]
+        name: Option<String>,
+
+        /// Use no profile by default
+        #[arg(long, conflicts_with = "name")]
+        none: bool,
+    },
+    /// Remove a profile from the index (its config file and vault are kept)
+    Remove {
+        /// Profile name
+        name: String,
+    },
+}
+
+pub fn cmd_profile(action: ProfileAction) -> Result<()> {
+    let mut index = ProfilesIndex::load_default()?;
+    let sections = section_profiles()?;
+
+    match action {
+        ProfileAction::List => {
+            let active = active_profile();
+            if index.profiles.is_empty() && sections.is_empty() {
+                println!(
+                    "{}",
+                    "No profiles; create one with `echovault-cli profile add <name>`".dimmed()
+                );
+            }
+            for name in index.profiles.keys() {
+                let path = index.config_path(name).unwrap_or_default();
+                print_profile(name, active.as_deref(), &path.display().to_string());
+            }
+            for name in sections.iter().filter(|n| !index.profiles.contains_key(*n)) {
+                print_profile(
+                    name,
+                    active.as_deref(),
+                    &format!("[profiles.{}] in {}", name, main_config_path().display()),
+                );
+            }
+            if let Some(default) = &index.default {
+                println!();
+                println!("Default: {}", default.cyan());
+            }
+        }
+        ProfileAction::Add {
+            name,
+            vault,
+            config,
+        } => {
+            if sections.contains(&name) {
+                bail!(
+                    "Profile '{}' already has a [profiles.{}] section in {}",
+                    name,
+                    name,
+                    main_config_path().display()
+                );
+            }
+            let path = index.add(&name, config.map(std::path::absolute).transpose()?)?;
+            if path.exists() {
+                println!("Using existing config {}", path.display());
+            } else {
+                let mut profile = Config::for_profile(&name);
+                if let Some(vault) = vault {
+                    profile.vault_path = std::path::absolute(vault)?;
+                }
+                profile.save(&path)?;
+                println!("Vault:  {}", profile.vault_path.display());
+                println!("Remote: folder {}", profile.sync.folder_name);
+            }
+            index.save_default()?;
+            println!(
+                "{}",
+                format!("✓ Profile {} created ({})", name, path.display()).green()
+            );
+            println!(
+                "{}",
+                format!(
+                    "Run commands with `--profile {}`, or `echovault-cli profile use {}`",
+                    name, name
+                )
+                .dimmed()
+            );
+        }
+        ProfileAction::Use { none: true, .. } | ProfileAction::Use { name: None, .. } => {
+            index.default = None;
+            index.save_default()?;
+            println!("{}", "✓ No profile used by default".green());
+        }
+        ProfileAction::Use {
+            name: Some(name), ..
+        } => {
+            if !index.profiles.contains_key(&name) && !sections.contains(&name) {
+                bail!(
+                    "Unknown profile '{}'; create it with `echovault-cli profile add {}`",
+                    name,
+                    name
+                );
+            }
+            index.default = Some(name.clone());
+            index.save_default()?;
+            println!("{}", format!("✓ Using profile {} by default", name).green());
+            if let Ok(env) = std::env::var(echovault_core::config::PROFILE_ENV) {
+                println!(
+                    "{}",
+                    format!(
+                        "{} is set to '{}' and takes precedence",
+                        echovault_core::config::PROFILE_ENV,
+                        env
+                    )
+                    .yellow()
+                );
+            }
+        }
+        ProfileAction::Remove { name } => {
+            let path = index.config_path(&name);
+            index.remove(&name)?;
+            index.save_default()?;
+            println!("{}", format!("✓ Profile {} removed", name).green());
+            if let Some(path) = path.filter(|p| p.exists()) {
+                println!("{}", format!("Config kept at {}", path.display()).dimmed());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Names of the `[profiles.<name>]` sections of echovault.toml.
+fn section_profiles() -> Result<Vec<String>> {
+    let path = main_config_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(Config::load(&path)?.profiles.into_keys().collect())
+}
+
+fn print_profile(name: &str, active: Option<&str>, location: &str) {
+    let marker = if active == Some(name) { "*" } else { " " };
+    println!(
+        "{} {:<16} {}",
+        marker.green(),
+        name.bold(),
+        location.dimmed()
+    );
+}
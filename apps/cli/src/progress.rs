@@ -0,0 +1,65 @@
+//! Progress bars and Ctrl+C cancellation for long-running commands.
+
+use anyhow::Result;
+use echovault_core::progress::{CancellationToken, Operation, Progress};
+use indicatif::{ProgressBar, ProgressStyle};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::sync::{Arc, Mutex};
+
+/// Exit code of a second Ctrl+C (128 + SIGINT)
+const FORCED_EXIT_CODE: i32 = 130;
+
+/// Shows each stage of an operation as a progress bar.
+#[derive(Default)]
+pub struct BarProgress {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl Progress for BarProgress {
+    fn start(&self, stage: &str, total: u64) {
+        let bar = ProgressBar::new(total);
+        bar.set_style(
+            ProgressStyle::with_template(
+                "  {msg:14} [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)",
+            )
+            .unwrap()
+            .progress_chars("█▓░"),
+        );
+        bar.set_message(stage.to_string());
+        if let Some(previous) = self.bar.lock().unwrap().replace(bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    fn advance(&self, n: u64) {
+        if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+            bar.inc(n);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// Cancel `cancel` on Ctrl+C or SIGTERM.
+///
+/// The first signal lets the step in progress finish so the command stops
+/// cleanly; a second one exits immediately.
+pub fn cancel_on_signals(cancel: &CancellationToken) -> Result<()> {
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register_conditional_shutdown(signal, FORCED_EXIT_CODE, cancel.flag())?;
+        signal_hook::flag::register(signal, cancel.flag())?;
+    }
+    Ok(())
+}
+
+/// An operation for a foreground command: progress bars on the terminal,
+/// cancelled by Ctrl+C.
+pub fn interruptible() -> Result<Operation> {
+    let cancel = CancellationToken::new();
+    cancel_on_signals(&cancel)?;
+    Ok(Operation::new(cancel, Arc::new(BarProgress::default())))
+}
@@ -0,0 +1,105 @@
+//! Redact command - find the secrets in the vault files a push sends
+//! (`--scan`, the default) or write their redacted copies (`--apply`).
+//!
+//! Sync pushes stage the copies themselves for the targets listed in
+//! `[redaction] destinations`; `--apply` stages them ahead of a push, e.g.
+//! to review what a target will receive. The vault's own files are never
+//! modified.
+
+use crate::output::{self, say};
+use anyhow::Result;
+use colored::Colorize;
+use echovault_core::redact::vault;
+
+pub fn cmd_redact(apply: bool) -> Result<()> {
+    say!("{}", "🔒 EchoVault Redact".bold().cyan());
+    say!();
+
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+    if config.redaction.destinations.is_empty() {
+        say!(
+            "{}",
+            "No [redaction] destinations configured: pushes and exports are not redacted.".dimmed()
+        );
+    } else {
+        say!(
+            "Redacted destinations: {}",
+            config.redaction.destinations.join(", ").cyan()
+        );
+    }
+    say!();
+
+    if apply {
+        let stats = vault::stage(vault_dir)?;
+        say!(
+            "{} Redacted {} secrets in {} files, {} copied clean, {} removed",
+            "✓".green(),
+            stats.secrets.to_string().green(),
+            stats.redacted,
+            stats.copied,
+            stats.removed
+        );
+        if stats.unredacted > 0 {
+            say!(
+                "{}",
+                format!("{} binary files copied unredacted", stats.unredacted).yellow()
+            );
+        }
+        say!(
+            "Copies: {}",
+            vault::redacted_dir(vault_dir)
+                .display()
+                .to_string()
+                .dimmed()
+        );
+        return output::emit(&stats);
+    }
+
+    let report = vault::scan(vault_dir)?;
+    for file in &report.files {
+        let kinds: Vec<String> = file
+            .findings
+            .iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect();
+        say!("  {}  {}", file.path.display(), kinds.join(", ").yellow());
+    }
+    if !report.files.is_empty() {
+        say!();
+    }
+    let totals = report.totals();
+    if totals.is_empty() {
+        say!(
+            "{} No secrets found in {} files",
+            "✓".green(),
+            report.scanned
+        );
+    } else {
+        say!(
+            "{} secrets in {} of {} files",
+            totals.values().sum::<usize>().to_string().yellow().bold(),
+            report.files.len(),
+            report.scanned
+        );
+        for (kind, count) in &totals {
+            say!("  {:<20} {}", kind, count);
+        }
+        say!(
+            "{}",
+            "Run with --apply to write the redacted copies.".dimmed()
+        );
+    }
+    if report.unreadable > 0 {
+        say!(
+            "{}",
+            format!("{} binary files could not be scanned", report.unreadable).dimmed()
+        );
+    }
+    output::emit(&serde_json::json!({
+        "files": report.files,
+        "scanned": report.scanned,
+        "unreadable": report.unreadable,
+        "totals": totals,
+    }))
+}
@@ -0,0 +1,133 @@
+//! Script Filter output of `search --format script-filter`.
+//!
+//! Prints results as Alfred Script Filter JSON (also read by Raycast script
+//! filter extensions), so the vault can be searched from a launcher. Each item
+//! opens `echovault://session/<id>` in the desktop app; with ⌘ it opens the
+//! parsed Markdown instead, which is also shown by Quick Look.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Deep link of a session in the desktop app.
+pub fn session_url(id: &str) -> String {
+    format!("echovault://session/{}", id)
+}
+
+/// One search hit, from semantic or keyword search.
+pub struct Hit<'a> {
+    pub id: &'a str,
+    pub source: &'a str,
+    pub title: Option<&'a str>,
+    /// Matching text or workspace, shown under the title
+    pub detail: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Output {
+    items: Vec<Item>,
+}
+
+#[derive(Serialize)]
+struct Item {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uid: Option<String>,
+    title: String,
+    subtitle: String,
+    arg: String,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quicklookurl: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<Text>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    mods: BTreeMap<&'static str, Modifier>,
+}
+
+#[derive(Serialize)]
+struct Text {
+    copy: String,
+    largetype: String,
+}
+
+#[derive(Serialize)]
+struct Modifier {
+    arg: String,
+    subtitle: String,
+}
+
+/// Script Filter JSON of search results (a single "no results" item if empty).
+pub fn render(vault_dir: &Path, hits: &[Hit]) -> serde_json::Result<String> {
+    let mut items: Vec<Item> = hits.iter().map(|hit| item(vault_dir, hit)).collect();
+    if items.is_empty() {
+        items.push(message(
+            "No results",
+            "Run 'echovault-cli embed' to index new sessions",
+        ));
+    }
+    serde_json::to_string(&Output { items })
+}
+
+/// Script Filter JSON of an error, shown in the launcher instead of results.
+pub fn render_error(error: &anyhow::Error) -> serde_json::Result<String> {
+    let item = message("Search failed", &format!("{:#}", error));
+    serde_json::to_string(&Output { items: vec![item] })
+}
+
+fn item(vault_dir: &Path, hit: &Hit) -> Item {
+    let title = hit.title.unwrap_or("(untitled)").to_string();
+    let detail: String = hit
+        .detail
+        .map(|d| d.split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default();
+    let snippet: String = detail.chars().take(120).collect();
+    let subtitle = if snippet.is_empty() {
+        hit.source.to_string()
+    } else {
+        format!("[{}] {}", hit.source, snippet)
+    };
+
+    let markdown = vault_dir
+        .join("parsed")
+        .join(hit.source)
+        .join(format!("{}.md", hit.id));
+    let mut mods = BTreeMap::new();
+    let quicklookurl = markdown.exists().then(|| {
+        let path = markdown.to_string_lossy().to_string();
+        mods.insert(
+            "cmd",
+            Modifier {
+                arg: path.clone(),
+                subtitle: "Open parsed Markdown".to_string(),
+            },
+        );
+        path
+    });
+
+    Item {
+        uid: Some(hit.id.to_string()),
+        title: title.clone(),
+        subtitle,
+        arg: session_url(hit.id),
+        valid: true,
+        quicklookurl,
+        text: Some(Text {
+            copy: hit.id.to_string(),
+            largetype: title,
+        }),
+        mods,
+    }
+}
+
+fn message(title: &str, subtitle: &str) -> Item {
+    Item {
+        uid: None,
+        title: title.to_string(),
+        subtitle: subtitle.to_string(),
+        arg: String::new(),
+        valid: false,
+        quicklookurl: None,
+        text: None,
+        mods: BTreeMap::new(),
+    }
+}
@@ -0,0 +1,329 @@
+//! Session commands - list the sessions recorded in vault.db, show one
+//! conversation in the terminal or open its file in an editor.
+
+use crate::output::{self, say};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use echovault_core::parsers::{load_session, session_file, ParsedConversation, Role};
+use echovault_core::storage::{compressed, SessionEntry, VaultDb};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Characters of a title shown in the `list` table
+const TITLE_WIDTH: usize = 60;
+
+/// Order of `list`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SessionSort {
+    /// Most recently updated first
+    Updated,
+    /// Most recently started first
+    Created,
+    /// By title, A to Z
+    Title,
+    /// Largest raw file first
+    Size,
+}
+
+fn format_mtime(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|d| {
+            d.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+pub fn cmd_list(
+    source: Option<String>,
+    workspace: Option<String>,
+    limit: usize,
+    sort: SessionSort,
+    tags: &[String],
+) -> Result<()> {
+    let config = crate::ensure_config()?;
+    let workspace = workspace.map(|w| w.to_lowercase());
+    let tagged = crate::tags::tagged_sessions(tags)?;
+    let mut sessions: Vec<SessionEntry> = VaultDb::open(&config.vault_path)?
+        .get_all_sessions()?
+        .into_iter()
+        .filter(|s| source.as_ref().is_none_or(|source| &s.source == source))
+        .filter(|s| tagged.as_ref().is_none_or(|tagged| tagged.contains(&s.id)))
+        .filter(|s| {
+            workspace.as_ref().is_none_or(|w| {
+                s.workspace_name
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase().contains(w))
+            })
+        })
+        .collect();
+
+    match sort {
+        SessionSort::Updated => sessions.sort_by_key(|s| std::cmp::Reverse(s.mtime)),
+        // RFC 3339 timestamps sort chronologically as text
+        SessionSort::Created => sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        SessionSort::Title => sessions.sort_by_cached_key(|s| {
+            s.title
+                .as_ref()
+                .map(|t| t.to_lowercase())
+                .unwrap_or_default()
+        }),
+        SessionSort::Size => sessions.sort_by_key(|s| std::cmp::Reverse(s.file_size)),
+    }
+    let total = sessions.len();
+    sessions.truncate(limit);
+
+    if output::is_json() {
+        return output::emit(&sessions);
+    }
+    if sessions.is_empty() {
+        say!("{}", "No sessions found.".yellow());
+        return Ok(());
+    }
+
+    let id_width = sessions.iter().map(|s| s.id.len()).max().unwrap_or(2);
+    let source_width = sessions.iter().map(|s| s.source.len()).max().unwrap_or(6);
+    say!(
+        "{}",
+        format!(
+            "{:<16}  {:<source_width$}  {:<id_width$}  TITLE",
+            "UPDATED", "SOURCE", "ID"
+        )
+        .bold()
+    );
+    for session in &sessions {
+        say!(
+            "{:<16}  {}  {:<id_width$}  {}",
+            format_mtime(session.mtime),
+            format!("{:<source_width$}", session.source).cyan(),
+            session.id,
+            truncate(
+                session.title.as_deref().unwrap_or("(untitled)"),
+                TITLE_WIDTH
+            )
+        );
+    }
+    if total > sessions.len() {
+        say!();
+        say!(
+            "{}",
+            format!(
+                "{} of {} sessions (use --limit to see more)",
+                sessions.len(),
+                total
+            )
+            .dimmed()
+        );
+    }
+    Ok(())
+}
+
+pub fn cmd_show(session_id: &str, raw: bool) -> Result<()> {
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+
+    if raw {
+        let entry = VaultDb::open(vault_dir)?
+            .get_session(session_id)?
+            .with_context(|| format!("Session '{}' not found in vault.db", session_id))?;
+        let path = compressed::locate(&vault_dir.join(&entry.vault_path));
+        if output::is_json() {
+            return output::emit(&serde_json::json!({
+                "session": entry,
+                "path": path,
+            }));
+        }
+        say!("{} {}", "Vault copy:".bold(), path.display());
+        say!("{} {}", "Original:  ".bold(), entry.original_path);
+        say!();
+        let plain = compressed::Plain::open(&path, &vault_dir.join("sessions"))
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        let data = std::fs::read(plain.path())?;
+        match String::from_utf8(data) {
+            Ok(text) => say!("{}", text),
+            Err(e) => say!(
+                "{}",
+                format!("(binary file, {} bytes)", e.as_bytes().len()).dimmed()
+            ),
+        }
+        return Ok(());
+    }
+
+    let conv = load_session(vault_dir, session_id)?
+        .with_context(|| format!("Session '{}' not found", session_id))?;
+    if output::is_json() {
+        return output::emit(&conv);
+    }
+    print_conversation(&conv);
+    Ok(())
+}
+
+/// `$VISUAL`, or else `$EDITOR`, when set.
+fn configured_editor() -> Option<String> {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+}
+
+/// Open a file in `$VISUAL`, `$EDITOR` or the platform's basic editor, and
+/// wait for it to exit.
+pub(crate) fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = configured_editor().unwrap_or_else(|| {
+        if cfg!(windows) {
+            "notepad".to_string()
+        } else {
+            "vi".to_string()
+        }
+    });
+    // $EDITOR may carry arguments (e.g. "code --wait")
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("$EDITOR is empty")?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Cannot run {}", program))?;
+    if !status.success() {
+        bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+/// Hand a file to the application the OS associates with it, without
+/// waiting for it.
+fn open_with_default_app(path: &Path) -> Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is the window title `start` expects first
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(path)
+        .spawn()
+        .with_context(|| format!("Cannot open {}", path.display()))?;
+    Ok(())
+}
+
+/// Vault copy of a session's raw file, which may be compressed.
+fn raw_file(vault_dir: &Path, session_id: &str) -> Result<PathBuf> {
+    let entry = VaultDb::open(vault_dir)?
+        .get_session(session_id)?
+        .with_context(|| format!("Session '{}' not found in vault.db", session_id))?;
+    Ok(compressed::locate(&vault_dir.join(&entry.vault_path)))
+}
+
+pub fn cmd_open(session_id: &str, raw: bool, print: bool) -> Result<()> {
+    let config = crate::ensure_config()?;
+    let vault_dir = &config.vault_path;
+    let path = if raw {
+        raw_file(vault_dir, session_id)?
+    } else {
+        session_file(vault_dir, session_id)?
+            .with_context(|| format!("Session '{}' not found", session_id))?
+    };
+
+    if print || output::is_json() {
+        output::emit(&serde_json::json!({
+            "session_id": session_id,
+            "path": path,
+            "compressed": compressed::is_compressed(&path),
+        }))?;
+        if !output::is_json() {
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
+
+    if compressed::is_compressed(&path) {
+        // Only an editor we wait for can read the temporary decompressed copy
+        if configured_editor().is_none() {
+            bail!(
+                "Session is stored compressed; set $EDITOR or run `echovault-cli parse` to open it"
+            );
+        }
+        let plain = compressed::Plain::open(&path, &vault_dir.join("sessions"))
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        say!(
+            "{}",
+            "Opening a decompressed copy; changes to it are not saved.".yellow()
+        );
+        return open_in_editor(plain.path());
+    }
+
+    say!("{} {}", "Opening".bold(), path.display());
+    if configured_editor().is_some() {
+        open_in_editor(&path)
+    } else {
+        open_with_default_app(&path)
+    }
+}
+
+/// Render a conversation for the terminal: a header, then each message
+/// under a rule naming its role.
+fn print_conversation(conv: &ParsedConversation) {
+    say!(
+        "{}",
+        conv.title.as_deref().unwrap_or("(untitled)").bold().green()
+    );
+    let mut meta = vec![conv.source.clone()];
+    meta.extend(conv.workspace.clone());
+    meta.extend(conv.created_at.map(|d| {
+        d.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }));
+    meta.extend(conv.model.clone());
+    meta.push(format!("{} messages", conv.messages.len()));
+    say!("{}", meta.join(" · ").dimmed());
+    if !conv.tags.is_empty() {
+        say!("{}", format!("tags: {}", conv.tags.join(", ")).dimmed());
+    }
+
+    for message in &conv.messages {
+        let mut label = match (&message.role, &message.tool_name) {
+            (Role::User, _) => "User".to_string(),
+            (Role::Assistant, _) => "Assistant".to_string(),
+            (Role::System, _) => "System".to_string(),
+            (Role::Tool, Some(name)) => format!("Tool ({})", name),
+            (Role::Tool, None) => "Tool".to_string(),
+            (Role::Info, _) => "Info".to_string(),
+        };
+        if let Some(model) = message.model.as_ref().filter(|_| conv.model.is_none()) {
+            label.push_str(&format!(" · {}", model));
+        }
+        let rule = format!("── {} ──", label);
+        let rule = match message.role {
+            Role::User => rule.cyan().bold(),
+            Role::Assistant => rule.green().bold(),
+            Role::System => rule.yellow().bold(),
+            Role::Tool => rule.magenta(),
+            Role::Info => rule.dimmed(),
+        };
+        let time = message
+            .timestamp
+            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+            .unwrap_or_default();
+        say!();
+        say!("{} {}", rule, time.dimmed());
+        match message.role {
+            Role::Tool | Role::Info => say!("{}", message.content.dimmed()),
+            _ => say!("{}", message.content),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Sources commands - list sources and turn them on or off.
+//!
+//! `enable`/`disable` set `enabled` in the `[sources.<name>]` section of
+//! echovault.toml; `extract`, `sync`, `watch` and the desktop app skip
+//! disabled sources.
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::extractors::storage_locations;
+use echovault_core::{all_extractors, ExtractorKind};
+
+#[derive(Subcommand)]
+pub enum SourcesAction {
+    /// List sources, whether they are enabled and the storage found for them
+    List,
+    /// Extract sessions of a source again
+    Enable {
+        /// Source name (e.g., "cursor")
+        name: String,
+    },
+    /// Stop extracting sessions of a source (sessions already in the vault stay)
+    Disable {
+        /// Source name (e.g., "cursor")
+        name: String,
+    },
+}
+
+pub fn cmd_sources(action: SourcesAction) -> Result<()> {
+    let mut config = crate::ensure_config()?;
+
+    match action {
+        SourcesAction::List => {
+            for extractor in all_extractors() {
+                let name = extractor.source_name();
+                let settings = config.source(name);
+                let status = if settings.enabled {
+                    "enabled".green()
+                } else {
+                    "disabled".red()
+                };
+                let kind = match extractor.extractor_kind() {
+                    ExtractorKind::Ide => "ide",
+                    ExtractorKind::Extension => "extension",
+                };
+                let locations = storage_locations(extractor.as_ref(), &settings).len();
+                println!(
+                    "{:<16} {:<9} {:<10} {}",
+                    name.bold(),
+                    kind,
+                    status,
+                    format!("{} locations", locations).dimmed()
+                );
+            }
+        }
+        SourcesAction::Enable { name } | SourcesAction::Disable { name } if !is_known(&name) => {
+            let known: Vec<_> = all_extractors().iter().map(|e| e.source_name()).collect();
+            bail!("Unknown source: {} (known: {})", name, known.join(", "));
+        }
+        SourcesAction::Enable { name } => set_enabled(&mut config, &name, true)?,
+        SourcesAction::Disable { name } => set_enabled(&mut config, &name, false)?,
+    }
+    Ok(())
+}
+
+fn is_known(name: &str) -> bool {
+    all_extractors().iter().any(|e| e.source_name() == name)
+}
+
+fn set_enabled(config: &mut echovault_core::Config, name: &str, enabled: bool) -> Result<()> {
+    let mut settings = config.source(name);
+    settings.enabled = enabled;
+    config.set_source(name, settings);
+    config.save_default()?;
+    let state = if enabled { "enabled" } else { "disabled" };
+    println!("{}", format!("✓ {} {}", name, state).green());
+    Ok(())
+}
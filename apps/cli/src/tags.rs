@@ -0,0 +1,155 @@
+//! Tag commands - add and remove the tags of a session, list them, and
+//! resolve the `--tag` filters of `list`, `search` and `grep`.
+//!
+//! A session's tags are those extracted from its conversation when parsed
+//! (languages, `error`, `refactor`, `test`) and those added here, which are
+//! kept with its annotation (see `notes`).
+
+use crate::output::{self, say};
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::storage::{AnnotationUpdate, VaultDb};
+use std::collections::HashSet;
+
+#[derive(Subcommand)]
+pub enum TagAction {
+    /// Add tags to a session
+    Add {
+        /// Session ID
+        session_id: String,
+
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags added to a session
+    Remove {
+        /// Session ID
+        session_id: String,
+
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// List the tags of a session, or every tag with its session count
+    List {
+        /// Session ID (all tags if omitted)
+        session_id: Option<String>,
+    },
+}
+
+pub fn cmd_tag(action: TagAction) -> Result<()> {
+    let config = crate::ensure_config()?;
+    let db = VaultDb::open(&config.vault_path)?;
+
+    match action {
+        TagAction::Add { session_id, tags } => {
+            if db.get_session(&session_id)?.is_none() {
+                bail!("Session '{}' not found in vault.db", session_id);
+            }
+            let mut user_tags = user_tags(&db, &session_id)?;
+            user_tags.extend(tags);
+            set_user_tags(&db, &config.vault_path, &session_id, user_tags)?;
+            print_session_tags(&db, &session_id)
+        }
+        TagAction::Remove { session_id, tags } => {
+            let remove: HashSet<String> = tags.iter().map(|t| t.trim().to_lowercase()).collect();
+            let user_tags = user_tags(&db, &session_id)?;
+            let kept: Vec<String> = user_tags
+                .iter()
+                .filter(|t| !remove.contains(&t.to_lowercase()))
+                .cloned()
+                .collect();
+            if kept.len() == user_tags.len() {
+                bail!(
+                    "No such tag added to {} (extracted tags come from the conversation and cannot be removed)",
+                    session_id
+                );
+            }
+            set_user_tags(&db, &config.vault_path, &session_id, kept)?;
+            print_session_tags(&db, &session_id)
+        }
+        TagAction::List {
+            session_id: Some(session_id),
+        } => print_session_tags(&db, &session_id),
+        TagAction::List { session_id: None } => {
+            let counts = db.tag_counts()?;
+            if output::is_json() {
+                let counts: Vec<_> = counts
+                    .iter()
+                    .map(|(tag, sessions)| serde_json::json!({ "tag": tag, "sessions": sessions }))
+                    .collect();
+                return output::emit(&counts);
+            }
+            if counts.is_empty() {
+                say!(
+                    "{}",
+                    "No tags yet; run `echovault-cli parse` to extract them.".yellow()
+                );
+            }
+            for (tag, sessions) in &counts {
+                say!("{:>6}  {}", sessions, tag.cyan());
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Tags added to a session by the user.
+fn user_tags(db: &VaultDb, session_id: &str) -> Result<Vec<String>> {
+    Ok(db
+        .annotation(session_id)?
+        .map(|a| a.tags)
+        .unwrap_or_default())
+}
+
+/// Replace the tags added to a session, in vault.db and its parsed Markdown.
+fn set_user_tags(
+    db: &VaultDb,
+    vault_dir: &std::path::Path,
+    session_id: &str,
+    tags: Vec<String>,
+) -> Result<()> {
+    let update = AnnotationUpdate {
+        tags: Some(tags),
+        ..Default::default()
+    };
+    let annotation = db.annotate(session_id, &update)?;
+    crate::notes::write_markdown(vault_dir, session_id, annotation.as_ref());
+    Ok(())
+}
+
+fn print_session_tags(db: &VaultDb, session_id: &str) -> Result<()> {
+    let extracted = db.session_tags(session_id)?;
+    let added = user_tags(db, session_id)?;
+    if output::is_json() {
+        return output::emit(&serde_json::json!({
+            "session_id": session_id,
+            "extracted": extracted,
+            "added": added,
+        }));
+    }
+    if extracted.is_empty() && added.is_empty() {
+        say!("No tags for {}", session_id);
+        return Ok(());
+    }
+    if !extracted.is_empty() {
+        say!("{} {}", "Extracted:".bold(), extracted.join(", ").cyan());
+    }
+    if !added.is_empty() {
+        say!("{} {}", "Added:    ".bold(), added.join(", ").cyan());
+    }
+    Ok(())
+}
+
+/// Sessions holding every tag of a `--tag` filter, or `None` without one.
+pub(crate) fn tagged_sessions(tags: &[String]) -> Result<Option<HashSet<String>>> {
+    if tags.is_empty() {
+        return Ok(None);
+    }
+    let config = crate::ensure_config()?;
+    Ok(Some(
+        VaultDb::open(&config.vault_path)?.sessions_with_tags(tags)?,
+    ))
+}
@@ -0,0 +1,357 @@
+//! Terminal dashboard (`echovault-cli tui`).
+//!
+//! Panes: sources with their session counts, the last sync with each target
+//! and the tail of the sync log, and a search box running keyword (FTS5) or
+//! semantic search over the vault. The selected result opens in
+//! `$VISUAL`/`$EDITOR`; the terminal is restored while the editor runs.
+
+use anyhow::{bail, Context, Result};
+use echovault_core::embedding::{self, EmbeddingConfig, SearchOptions};
+use echovault_core::parsers::session_file;
+use echovault_core::storage::fts::{HIGHLIGHT_END, HIGHLIGHT_START};
+use echovault_core::storage::{compressed, FtsFilter, FtsIndex, LocalDb, VaultDb};
+use echovault_core::Config;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Results shown per search
+const SEARCH_LIMIT: usize = 50;
+
+/// Sync log entries shown
+const LOG_LINES: usize = 20;
+
+/// Characters of a result's passage shown under its title
+const SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    Keyword,
+    Semantic,
+}
+
+impl SearchMode {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Semantic => "semantic",
+        }
+    }
+
+    fn toggle(self) -> Self {
+        match self {
+            Self::Keyword => Self::Semantic,
+            Self::Semantic => Self::Keyword,
+        }
+    }
+}
+
+/// A search result, keyword or semantic.
+struct Hit {
+    session_id: String,
+    source: String,
+    title: String,
+    snippet: String,
+}
+
+struct App {
+    config: Config,
+    /// Sessions per source in vault.db
+    sources: Vec<(String, usize)>,
+    /// Sync targets' state, then the sync log tail
+    sync: Vec<Line<'static>>,
+    query: String,
+    mode: SearchMode,
+    hits: Vec<Hit>,
+    selected: ListState,
+    /// Last message (search errors, editor exits)
+    status: String,
+}
+
+impl App {
+    fn new(config: Config) -> Self {
+        Self {
+            config,
+            sources: Vec::new(),
+            sync: Vec::new(),
+            query: String::new(),
+            mode: SearchMode::Keyword,
+            hits: Vec::new(),
+            selected: ListState::default(),
+            status: String::new(),
+        }
+    }
+
+    /// Reload the source and sync panes from vault.db and local.db.
+    fn refresh(&mut self) {
+        let db = match VaultDb::open(&self.config.vault_path) {
+            Ok(db) => db,
+            Err(e) => {
+                self.status = format!("Cannot open vault.db: {:#}", e);
+                return;
+            }
+        };
+
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for session in db.get_all_sessions().unwrap_or_default() {
+            *counts.entry(session.source).or_default() += 1;
+        }
+        self.sources = counts.into_iter().collect();
+
+        let states = LocalDb::open(&self.config.vault_path)
+            .and_then(|db| db.remote_sync_states())
+            .unwrap_or_default();
+        self.sync = self
+            .config
+            .sync
+            .target_names()
+            .into_iter()
+            .map(|name| match states.iter().find(|s| s.remote == name) {
+                None => Line::from(vec![
+                    Span::from(format!("{}: ", name)).bold(),
+                    Span::from("never synced").dark_gray(),
+                ]),
+                Some(state) => {
+                    let last = state
+                        .last_success
+                        .map(format_time)
+                        .unwrap_or_else(|| "never".to_string());
+                    match &state.error {
+                        Some(error) => Line::from(vec![
+                            Span::from(format!("{}: ", name)).bold(),
+                            Span::from(format!("failed: {} (last success: {})", error, last)).red(),
+                        ]),
+                        None => Line::from(vec![
+                            Span::from(format!("{}: ", name)).bold(),
+                            Span::from(format!("{} ({} files pushed)", last, state.files_pushed))
+                                .green(),
+                        ]),
+                    }
+                }
+            })
+            .collect();
+        self.sync.push(Line::default());
+        for entry in db.recent_sync_log(LOG_LINES).unwrap_or_default() {
+            self.sync.push(Line::from(vec![
+                Span::from(format!("{} ", format_time(entry.timestamp))).dark_gray(),
+                Span::from(format!("{:<7}", entry.action)).cyan(),
+                Span::from(entry.details.unwrap_or_default()),
+            ]));
+        }
+    }
+
+    /// Run the query in the current mode.
+    fn search(&mut self) {
+        let query = self.query.trim().to_string();
+        if query.is_empty() {
+            return;
+        }
+        let found = match self.mode {
+            SearchMode::Keyword => keyword_search(&self.config.vault_path, &query),
+            SearchMode::Semantic => semantic_search(&self.config, &query),
+        };
+        match found {
+            Ok(hits) => {
+                self.status = format!("{} results for \"{}\"", hits.len(), query);
+                self.selected.select((!hits.is_empty()).then_some(0));
+                self.hits = hits;
+            }
+            Err(e) => self.status = format!("Search failed: {:#}", e),
+        }
+    }
+
+    /// File of the selected result: its parsed Markdown, or else its raw
+    /// session file when stored plain.
+    fn selected_file(&self) -> Result<PathBuf> {
+        let hit = self
+            .selected
+            .selected()
+            .and_then(|i| self.hits.get(i))
+            .context("No result selected")?;
+        let path = session_file(&self.config.vault_path, &hit.session_id)?
+            .context("Session file not found")?;
+        if compressed::is_compressed(&path) {
+            bail!("Session is stored compressed; run `echovault-cli parse` to open it");
+        }
+        Ok(path)
+    }
+}
+
+fn format_time(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|d| {
+            d.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// First characters of a passage, on one line.
+fn snippet(text: &str) -> String {
+    text.replace(HIGHLIGHT_START, "")
+        .replace(HIGHLIGHT_END, "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(SNIPPET_CHARS)
+        .collect()
+}
+
+fn keyword_search(vault_dir: &Path, query: &str) -> Result<Vec<Hit>> {
+    let index = FtsIndex::open(vault_dir)?;
+    if index.is_empty()? {
+        bail!("Full-text index is empty; run `echovault-cli grep --reindex <query>` once");
+    }
+    let filter = FtsFilter {
+        exclude: echovault_core::dedupe::duplicate_ids(vault_dir),
+        ..Default::default()
+    };
+    Ok(index
+        .search(query, &filter, SEARCH_LIMIT)?
+        .into_iter()
+        .map(|hit| Hit {
+            title: hit.title.unwrap_or_else(|| "(untitled)".to_string()),
+            snippet: snippet(&hit.snippet),
+            session_id: hit.session_id,
+            source: hit.source,
+        })
+        .collect())
+}
+
+fn semantic_search(config: &Config, query: &str) -> Result<Vec<Hit>> {
+    let results = embedding::search_similar(
+        &EmbeddingConfig::from_config(config),
+        &config.vault_path,
+        query,
+        SEARCH_LIMIT,
+        &SearchOptions::default(),
+    )?;
+    Ok(results
+        .into_iter()
+        .map(|r| Hit {
+            title: r.title.unwrap_or_else(|| "(untitled)".to_string()),
+            snippet: snippet(&r.chunk_content),
+            session_id: r.session_id,
+            source: r.source,
+        })
+        .collect())
+}
+
+pub fn cmd_tui() -> Result<()> {
+    let config = crate::ensure_config()?;
+    let mut app = App::new(config);
+    app.refresh();
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if ctrl => return Ok(()),
+            KeyCode::Char('r') if ctrl => app.refresh(),
+            KeyCode::Char('o') if ctrl => match app.selected_file() {
+                Ok(path) => {
+                    ratatui::restore();
+                    let opened = crate::sessions::open_in_editor(&path);
+                    *terminal = ratatui::init();
+                    app.status = match opened {
+                        Ok(()) => format!("Closed {}", path.display()),
+                        Err(e) => format!("{:#}", e),
+                    };
+                }
+                Err(e) => app.status = format!("{:#}", e),
+            },
+            KeyCode::Char(c) if !ctrl => app.query.push(c),
+            KeyCode::Backspace => {
+                app.query.pop();
+            }
+            KeyCode::Tab => app.mode = app.mode.toggle(),
+            KeyCode::Enter => app.search(),
+            KeyCode::Down => app.selected.select_next(),
+            KeyCode::Up => app.selected.select_previous(),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [top, search, results, footer] = Layout::vertical([
+        Constraint::Length(12),
+        Constraint::Length(3),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [sources_area, sync_area] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(top);
+
+    let total: usize = app.sources.iter().map(|(_, count)| count).sum();
+    let sources: Vec<ListItem> = app
+        .sources
+        .iter()
+        .map(|(source, count)| ListItem::new(format!("{:<20} {:>6}", source, count)))
+        .collect();
+    frame.render_widget(
+        List::new(sources).block(Block::bordered().title(format!(" Sources ({}) ", total))),
+        sources_area,
+    );
+    frame.render_widget(
+        Paragraph::new(app.sync.clone()).block(Block::bordered().title(" Sync ")),
+        sync_area,
+    );
+
+    frame.render_widget(
+        Paragraph::new(format!("{}▏", app.query))
+            .block(Block::bordered().title(format!(" Search ({}) ", app.mode.label()))),
+        search,
+    );
+
+    let hits: Vec<ListItem> = app
+        .hits
+        .iter()
+        .map(|hit| {
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::from(hit.title.clone()).bold(),
+                    Span::from(format!("  [{}] {}", hit.source, hit.session_id)).dark_gray(),
+                ]),
+                Line::from(format!("  {}", hit.snippet)).dark_gray(),
+            ])
+        })
+        .collect();
+    frame.render_stateful_widget(
+        List::new(hits)
+            .block(Block::bordered().title(format!(" Results ({}) ", app.hits.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+        results,
+        &mut app.selected,
+    );
+
+    let help = "Enter: search · Tab: keyword/semantic · ↑/↓: select · Ctrl+O: open in $EDITOR · Ctrl+R: refresh · Esc: quit";
+    let footer_text = if app.status.is_empty() {
+        help.to_string()
+    } else {
+        format!("{}  |  {}", app.status, help)
+    };
+    frame.render_widget(Paragraph::new(footer_text).dark_gray(), footer);
+}
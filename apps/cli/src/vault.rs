@@ -0,0 +1,73 @@
+//! Vault commands - encrypt and decrypt the vault by hand, and re-encrypt it
+//! under a new passphrase.
+//!
+//! `sync` does both on its own (sealing before a push, opening after a
+//! pull); these run one step without touching the remote, e.g. to read a
+//! remote copied by other means. Encryption itself is set up with
+//! `encryption setup`.
+
+use crate::encryption::{cmd_rotate_key, unlock_key};
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use echovault_core::crypto::staging;
+
+#[derive(Subcommand)]
+pub enum VaultAction {
+    /// Encrypt changed files into the staging directory now, without pushing
+    Encrypt,
+    /// Decrypt the staging directory (e.g. a copied remote) into the vault
+    Decrypt,
+    /// Re-encrypt synced data under a new passphrase (resumes if interrupted)
+    Rekey,
+}
+
+pub fn cmd_vault(action: VaultAction) -> Result<()> {
+    match action {
+        VaultAction::Encrypt => cmd_encrypt(),
+        VaultAction::Decrypt => cmd_decrypt(),
+        VaultAction::Rekey => cmd_rotate_key(),
+    }
+}
+
+/// Seal the vault into the staging directory, as `sync` does before a push.
+fn cmd_encrypt() -> Result<()> {
+    let config = crate::ensure_config()?;
+    let Some(key) = unlock_key(&config)? else {
+        bail!("Encryption is not enabled. Run 'echovault-cli encryption setup' first.");
+    };
+    let stats = staging::seal(&config.vault_path, &key)?;
+    println!(
+        "{} {} files encrypted, {} copied into {}",
+        "✓".green(),
+        stats.encrypted,
+        stats.copied,
+        staging::staging_dir(&config.vault_path).display()
+    );
+    Ok(())
+}
+
+/// Open the staging directory into the vault, as `sync` does after a pull.
+fn cmd_decrypt() -> Result<()> {
+    let config = crate::ensure_config()?;
+    let Some(key) = unlock_key(&config)? else {
+        bail!("Encryption is not enabled. Run 'echovault-cli encryption setup' first.");
+    };
+    let stats = staging::open(
+        &config.vault_path,
+        &key,
+        config.encryption.allow_missing_manifest,
+    )?;
+    println!("{} {} files decrypted", "✓".green(), stats.decrypted);
+    if stats.unlisted > 0 {
+        println!(
+            "{}",
+            format!(
+                "{} files are not in the manifest yet and were left encrypted.",
+                stats.unlisted
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! Watch command - capture sessions as soon as source files change.
+//!
+//! Runs an incremental ingest of the changed sources after a debounce window
+//! whenever an extractor's storage location changes. With `--daemon` it is suitable for a systemd
+//! user service (`Type=notify`):
+//! - sends `READY=1` / `STATUS=` / `STOPPING=1` via sd_notify (Linux)
+//! - exits cleanly on SIGTERM / SIGINT
+//! - holds an exclusive PID/lock file so only one watcher runs at a time
+//!
+//! Detected changes are persisted to the `pending_changes` queue in vault.db
+//! before ingesting, and replayed on startup, so nothing is lost on a crash.
+//!
+//! Digests enabled in `[digest]` are written on startup and after each ingest
+//! once their day or week is over.
+//!
+//! With `--parse` / `--embed`, sessions captured by an ingest are also written
+//! as Markdown / embedded for semantic search right away.
+
+use crate::progress::{cancel_on_signals, BarProgress};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use echovault_core::extractors::storage_locations;
+use echovault_core::progress::{CancellationToken, NoProgress, Operation, Progress};
+use echovault_core::storage::VaultDb;
+use echovault_core::utils::power::defer_reason;
+use echovault_core::watcher::{build_ignore_set, is_capture_paused};
+use echovault_core::{all_extractors, Config, FileWatcher};
+use globset::GlobSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How long to wait for file events before re-checking the shutdown flag
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Exclusive PID/lock file, removed on drop.
+struct PidLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl PidLock {
+    /// Take the lock and write the current PID, failing if another process holds it.
+    fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Cannot open PID file: {}", path.display()))?;
+
+        if file.try_lock().is_err() {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            bail!(
+                "Another watcher is already running (PID {}, lock {})",
+                pid.trim(),
+                path.display()
+            );
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        Ok(Self {
+            _file: file,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Whether a watcher holds the default PID lock of the active profile.
+pub fn is_running() -> bool {
+    File::open(default_pid_file()).is_ok_and(|file| file.try_lock_shared().is_err())
+}
+
+/// Default PID file: $XDG_RUNTIME_DIR/echovault/watch.pid, falling back to the data dir.
+fn default_pid_file() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("echovault")
+        .join(match echovault_core::config::active_profile() {
+            Some(profile) => format!("watch-{}.pid", profile),
+            None => "watch.pid".to_string(),
+        })
+}
+
+/// Send a readiness/status notification to systemd (no-op elsewhere).
+#[cfg(target_os = "linux")]
+fn sd_notify(state: &[sd_notify::NotifyState]) {
+    if let Err(e) = sd_notify::notify(false, state) {
+        warn!("sd_notify failed: {}", e);
+    }
+}
+
+/// Print for interactive runs, log for daemon runs (journald captures stderr).
+fn report(daemon: bool, message: &str) {
+    if daemon {
+        info!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Write the digests that are due (see [`echovault_core::digest::generate_due`]).
+fn write_due_digests(daemon: bool, config: &Config) {
+    match echovault_core::digest::generate_due(config) {
+        Ok(written) => {
+            for (digest, path) in written {
+                report(
+                    daemon,
+                    &format!("{} written to {}", digest.title(), path.display()),
+                );
+            }
+        }
+        Err(e) => warn!("Digest failed: {:#}", e),
+    }
+}
+
+/// Steps run after an ingest that captured new or changed sessions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostIngest {
+    /// Write Markdown for the captured sessions (`--parse`)
+    pub parse: bool,
+    /// Embed the captured sessions (`--embed`)
+    pub embed: bool,
+}
+
+impl PostIngest {
+    /// Parse and/or embed sessions changed since they were last processed.
+    /// Both are incremental, so only the captured sessions are handled.
+    fn run(self, daemon: bool, config: &Config, op: &Operation) {
+        if self.parse {
+            match echovault_core::parsers::vault_parse::parse_vault(config, op) {
+                Ok(summary) => report(
+                    daemon,
+                    &format!(
+                        "Parsed {} sessions ({} errors)",
+                        summary.parsed, summary.errors
+                    ),
+                ),
+                Err(e) => warn!("Parse failed: {:#}", e),
+            }
+        }
+        if self.embed && !op.is_cancelled() {
+            if let Some(reason) = defer_reason(&config.schedule) {
+                report(daemon, &format!("Embedding deferred: {}", reason));
+                return;
+            }
+            let embedding_config = crate::embedding_config(config);
+            match echovault_core::embedding::embed_vault(&embedding_config, &config.vault_path, op)
+            {
+                Ok(result) => report(
+                    daemon,
+                    &format!(
+                        "Embedded {} sessions ({} chunks, {} errors)",
+                        result.sessions_processed,
+                        result.chunks_created,
+                        result.errors.len()
+                    ),
+                ),
+                Err(e) => warn!("Embedding failed: {:#}", e),
+            }
+        }
+    }
+}
+
+/// Ingest the given sources and clear their queued changes detected before
+/// the ingest started.
+fn ingest_and_clear(
+    vault_dir: &Path,
+    config: &Config,
+    sources: &[&str],
+    op: &Operation,
+) -> Result<bool> {
+    let started = chrono::Utc::now().timestamp_millis();
+    let has_changes = crate::ingest_sessions(vault_dir, config, Some(sources), op)?.copied > 0;
+    let db = VaultDb::open(vault_dir)?;
+    for source in sources {
+        db.clear_source_pending_changes(source, started)?;
+    }
+    Ok(has_changes)
+}
+
+/// A watched storage location and the ignore rules of its source.
+struct WatchedLocation {
+    path: PathBuf,
+    source: &'static str,
+    ignore: GlobSet,
+}
+
+impl WatchedLocation {
+    fn ignores(&self, changed: &Path) -> bool {
+        changed.starts_with(&self.path) && self.ignore.is_match(changed)
+    }
+}
+
+pub fn cmd_watch(daemon: bool, pid_file: Option<PathBuf>, post_ingest: PostIngest) -> Result<()> {
+    if !daemon {
+        println!("{}", "👀 EchoVault Watch".bold().cyan());
+        println!();
+    }
+
+    let config = crate::ensure_config()?;
+    if !config.watcher.enabled {
+        bail!("Watcher is disabled in config ([watcher] enabled = false)");
+    }
+
+    let pid_path = pid_file.unwrap_or_else(default_pid_file);
+    let _lock = PidLock::acquire(&pid_path)?;
+
+    // SIGTERM (systemd stop) and SIGINT (Ctrl+C) request a graceful shutdown,
+    // which also cancels an ingest in progress
+    let shutdown = CancellationToken::new();
+    cancel_on_signals(&shutdown)?;
+    let progress: Arc<dyn Progress> = if daemon {
+        Arc::new(NoProgress)
+    } else {
+        Arc::new(BarProgress::default())
+    };
+    let op = Operation::new(shutdown.clone(), progress);
+
+    let mut watcher = FileWatcher::new()?;
+    let mut watched = Vec::new();
+    for extractor in all_extractors() {
+        let source = extractor.source_name();
+        let settings = config.source(source);
+        if !settings.enabled || !config.watcher.is_source_enabled(source) {
+            continue;
+        }
+        let ignore = build_ignore_set(config.watcher.ignore_for(source))?;
+
+        for location in storage_locations(extractor.as_ref(), &settings) {
+            match watcher.watch(&location) {
+                Ok(()) => watched.push(WatchedLocation {
+                    path: location,
+                    source,
+                    ignore: ignore.clone(),
+                }),
+                Err(e) => warn!("Cannot watch {:?}: {}", location, e),
+            }
+        }
+    }
+
+    let debounce = Duration::from_secs(config.watcher.debounce_secs);
+    let status = format!("Watching {} source locations", watched.len());
+    report(daemon, &status);
+    if !daemon {
+        println!("Press {} to stop.", "Ctrl+C".cyan());
+    }
+
+    // Replay changes queued before the last shutdown/crash
+    let pending = VaultDb::open(&config.vault_path)?.pending_changes()?;
+    if !pending.is_empty() && !is_capture_paused() {
+        report(
+            daemon,
+            &format!(
+                "Replaying {} pending changes from previous run",
+                pending.len()
+            ),
+        );
+        let sources: BTreeSet<&str> = pending.iter().map(|c| c.source.as_str()).collect();
+        let sources: Vec<&str> = sources.into_iter().collect();
+        match ingest_and_clear(&config.vault_path, &config, &sources, &op) {
+            Ok(true) => post_ingest.run(daemon, &config, &op),
+            Ok(false) => {}
+            Err(e) => warn!("Ingest failed: {}", e),
+        }
+    }
+
+    write_due_digests(daemon, &config);
+
+    #[cfg(target_os = "linux")]
+    sd_notify(&[
+        sd_notify::NotifyState::Ready,
+        sd_notify::NotifyState::Status(&status),
+    ]);
+
+    while !shutdown.is_cancelled() {
+        let Some(mut changed) = watcher.next_debounced_timeout(debounce, SHUTDOWN_POLL_INTERVAL)
+        else {
+            break;
+        };
+
+        changed.retain(|path| !watched.iter().any(|loc| loc.ignores(path)));
+        if changed.is_empty() || shutdown.is_cancelled() {
+            continue;
+        }
+
+        if is_capture_paused() {
+            continue;
+        }
+
+        let config = Config::load_default().unwrap_or_else(|_| config.clone());
+
+        // Persist first so the batch survives a crash/shutdown before ingest
+        let mut by_source: BTreeMap<&str, Vec<PathBuf>> = BTreeMap::new();
+        for path in &changed {
+            if let Some(loc) = watched.iter().find(|loc| path.starts_with(&loc.path)) {
+                by_source.entry(loc.source).or_default().push(path.clone());
+            }
+        }
+        for (source, paths) in &by_source {
+            let result = VaultDb::open(&config.vault_path)
+                .and_then(|mut db| db.enqueue_pending_changes(source, paths));
+            if let Err(e) = result {
+                warn!("{}: cannot queue pending changes: {}", source, e);
+            }
+        }
+
+        if let Some(reason) = defer_reason(&config.schedule) {
+            report(daemon, &format!("Ingest deferred: {}", reason));
+            continue;
+        }
+
+        let sources: Vec<&str> = by_source.keys().copied().collect();
+        report(
+            daemon,
+            &format!(
+                "{} paths changed ({}), ingesting...",
+                changed.len(),
+                sources.join(", ")
+            ),
+        );
+
+        match ingest_and_clear(&config.vault_path, &config, &sources, &op) {
+            Ok(true) => {
+                report(daemon, "New sessions captured");
+                post_ingest.run(daemon, &config, &op);
+            }
+            Ok(false) => report(daemon, "All sessions already up-to-date"),
+            Err(e) => warn!("Ingest failed: {}", e),
+        }
+        write_due_digests(daemon, &config);
+    }
+
+    #[cfg(target_os = "linux")]
+    sd_notify(&[sd_notify::NotifyState::Stopping]);
+    report(daemon, "Watcher stopped");
+
+    Ok(())
+}
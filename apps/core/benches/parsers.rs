@@ -0,0 +1,96 @@
+//! Parse-time benchmarks for the largest session formats.
+//!
+//! Run with `cargo bench -p echovault-core --bench parsers`. Sessions are
+//! generated with bulky fields the parsers ignore (tool outputs, variable
+//! data), as found in long real-world sessions.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use echovault_core::parsers::claude_code::ClaudeCodeParser;
+use echovault_core::parsers::codex::CodexParser;
+use echovault_core::parsers::vscode_copilot::VSCodeCopilotParser;
+use echovault_core::parsers::Parser;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TURNS: usize = 2_000;
+
+fn bulky() -> String {
+    "lorem ipsum dolor sit amet ".repeat(200)
+}
+
+fn write_copilot_json(dir: &Path) -> PathBuf {
+    let requests: Vec<_> = (0..TURNS)
+        .map(|i| {
+            json!({
+                "message": { "text": format!("Question {}", i) },
+                "variableData": { "variables": [{ "value": bulky() }] },
+                "response": { "value": format!("Answer {}", i), "model": "gpt-4o" },
+                "result": { "metadata": { "toolCallRounds": [bulky()] } },
+            })
+        })
+        .collect();
+    let session = json!({
+        "sessionId": "bench",
+        "creationDate": 1_700_000_000_000i64,
+        "requests": requests,
+    });
+    let path = dir.join("copilot.json");
+    fs::write(&path, serde_json::to_vec(&session).unwrap()).unwrap();
+    path
+}
+
+fn write_jsonl(path: PathBuf, line: impl Fn(usize) -> serde_json::Value) -> PathBuf {
+    let mut content = String::new();
+    for i in 0..TURNS {
+        content.push_str(&line(i).to_string());
+        content.push('\n');
+    }
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn bench_parsers(c: &mut Criterion) {
+    let temp = TempDir::new().unwrap();
+    let copilot_json = write_copilot_json(temp.path());
+    let copilot_jsonl = write_jsonl(
+        temp.path().join("copilot.jsonl"),
+        |i| json!({ "kind": 1 + (i % 2), "v": format!("Message {}", i), "extra": bulky() }),
+    );
+    let project = temp.path().join("-home-user-project");
+    fs::create_dir_all(&project).unwrap();
+    let claude = write_jsonl(project.join("session.jsonl"), |i| {
+        json!({
+            "role": if i % 2 == 0 { "user" } else { "assistant" },
+            "content": [{ "type": "text", "text": format!("Message {}", i) }],
+            "timestamp": "2024-01-15T10:30:00Z",
+            "toolUseResult": { "stdout": bulky() },
+        })
+    });
+    let codex = write_jsonl(temp.path().join("rollout.jsonl"), |i| {
+        json!({
+            "type": "message",
+            "role": if i % 2 == 0 { "user" } else { "assistant" },
+            "content": format!("Message {}", i),
+            "timestamp": "2024-01-15T10:30:00Z",
+            "reasoning": bulky(),
+        })
+    });
+
+    let cases: [(&str, &dyn Parser, &Path); 4] = [
+        ("copilot_json", &VSCodeCopilotParser, &copilot_json),
+        ("copilot_jsonl", &VSCodeCopilotParser, &copilot_jsonl),
+        ("claude_code", &ClaudeCodeParser, &claude),
+        ("codex", &CodexParser, &codex),
+    ];
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(20);
+    for (name, parser, path) in cases {
+        group.bench_function(name, |b| b.iter(|| parser.parse(path).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parsers);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! Vector search benchmarks: full cosine scan against the HNSW graph.
+//!
+//! Run with `cargo bench -p echovault-core --features embedding --bench
+//! vector_search`. The store holds random unit-scale vectors of a typical
+//! small embedding model; the graph is built (and persisted) before timing.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use echovault_core::embedding::store::EmbeddingStore;
+use tempfile::TempDir;
+
+const CHUNKS: usize = 100_000;
+const DIMENSION: usize = 384;
+const CHUNKS_PER_SESSION: usize = 50;
+
+/// Deterministic pseudo-random vectors (xorshift)
+fn vectors(seed: u64, count: usize) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+    };
+    (0..count)
+        .map(|_| (0..DIMENSION).map(|_| next()).collect())
+        .collect()
+}
+
+fn bench_vector_search(c: &mut Criterion) {
+    let temp = TempDir::new().unwrap();
+    let mut store = EmbeddingStore::open(temp.path()).unwrap();
+    let chunks = vectors(7, CHUNKS);
+    for (session, batch) in chunks.chunks(CHUNKS_PER_SESSION).enumerate() {
+        let batch: Vec<(String, Vec<f32>)> = batch
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("chunk {} of session {}", i, session), v.clone()))
+            .collect();
+        store
+            .store_session_chunks(&format!("s{}", session), "bench", "bench", "h", &batch)
+            .unwrap();
+    }
+    let queries = vectors(11, 16);
+
+    // Builds and persists the graph
+    store.search_similar(&queries[0], 10).unwrap();
+
+    let mut group = c.benchmark_group("vector_search");
+    group.sample_size(10);
+    group.bench_function("ann", |b| {
+        b.iter(|| {
+            for query in &queries {
+                store.search_similar(query, 10).unwrap();
+            }
+        })
+    });
+    store.set_exact(true);
+    group.bench_function("exact", |b| {
+        b.iter(|| {
+            for query in &queries {
+                store.search_similar(query, 10).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_vector_search);
+criterion_main!(benches);
@@ -0,0 +1,795 @@
+//! Token usage and cost analytics.
+//!
+//! Usage comes from two places:
+//! - Parsed sessions: the counts their source records (Zed, OpenCode), or
+//!   else an estimate of each assistant reply from text length (about 4
+//!   characters per token), with everything before it in the conversation as
+//!   its input. Cached per session in the `usage` table of vault.db.
+//! - Interceptor captures (`<vault>/intercepted`): API responses report
+//!   exact counts (OpenAI, Anthropic and Gemini usage fields).
+//!
+//! [`cost_report`] groups usage by day, week or month, tool and model, and prices it with
+//! a [`PriceTable`]: built-in list prices, extended and overridden by
+//! `[analytics.prices]` in the config. Shown by `echovault-cli stats --costs`
+//! and the desktop dashboard.
+//!
+//! [`usage_dashboard`] summarizes activity from vault.db alone (sessions per
+//! day, messages per model, most active workspaces), for the dashboard charts
+//! and `echovault-cli stats --json`.
+//!
+//! [`vault_stats`] counts sessions per source, workspace and month, messages
+//! per role and model, characters and the busiest days across the whole vault.
+//! The counts of each conversation are cached in vault.db, so only sessions
+//! new or changed since the last run are parsed. Shown by `echovault-cli
+//! stats` and the desktop app.
+
+use crate::config::{Config, ModelPrice};
+use crate::parsers::{all_parsers, parse_raw_file, ParsedConversation, Role};
+use crate::storage::{
+    compressed, ConversationStats, DayCount, ModelCount, RoleCounts, SessionUsage, VaultDb,
+    WorkspaceActivity,
+};
+use crate::utils::Walk;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::BufRead;
+use std::path::Path;
+use tracing::debug;
+
+/// Average characters per token, for estimates from text length
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// Workspaces listed by [`usage_dashboard`] and [`vault_stats`]
+const TOP_WORKSPACES: usize = 10;
+
+/// Models and days listed by [`vault_stats`]
+const TOP_ENTRIES: usize = 10;
+
+/// Model of usage whose model is not recorded
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// Built-in list prices (USD per million tokens: input, output), matched by
+/// model name prefix. Prices change; override them in `[analytics.prices]`.
+const BUILTIN_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("o3-mini", 1.1, 4.4),
+    ("o4-mini", 1.1, 4.4),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-opus", 15.0, 75.0),
+    ("claude-opus-4", 15.0, 75.0),
+    ("gemini-1.5-flash", 0.075, 0.3),
+    ("gemini-1.5-pro", 1.25, 5.0),
+    ("gemini-2.0-flash", 0.1, 0.4),
+    ("gemini-2.5-flash", 0.3, 2.5),
+    ("gemini-2.5-pro", 1.25, 10.0),
+];
+
+/// Source of usage captured by the interceptor, whatever tool sent it
+pub const INTERCEPTOR_SOURCE: &str = "interceptor";
+
+/// Tokens used by one model on one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenUsage {
+    pub date: NaiveDate,
+    /// Tool (session source) that used them
+    pub source: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated from text length rather than reported by the API
+    pub estimated: bool,
+}
+
+/// Model prices by name prefix.
+pub struct PriceTable {
+    prices: BTreeMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    /// The built-in prices, extended and overridden by `overrides`.
+    pub fn new(overrides: &BTreeMap<String, ModelPrice>) -> Self {
+        let mut prices: BTreeMap<String, ModelPrice> = BUILTIN_PRICES
+            .iter()
+            .map(|&(model, input, output)| (model.to_string(), ModelPrice { input, output }))
+            .collect();
+        for (model, price) in overrides {
+            prices.insert(model.to_lowercase(), *price);
+        }
+        Self { prices }
+    }
+
+    /// Price of a model: the entry with the longest prefix of its name,
+    /// ignoring any provider prefix ("models/", "anthropic/").
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| name.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| *price)
+    }
+}
+
+/// Length of the periods a [`CostReport`] groups usage by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    /// Weeks start on Monday
+    Week,
+    Month,
+}
+
+impl Period {
+    /// First day of the period containing `date`.
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// Usage and cost of one model used by one tool in one period.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostRow {
+    pub period_start: NaiveDate,
+    pub source: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated cost in USD (`None` if the model has no price)
+    pub cost: Option<f64>,
+    /// Some of the tokens are estimates
+    pub estimated: bool,
+}
+
+/// Usage and costs grouped by period, tool and model.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostReport {
+    pub period: Period,
+    /// Newest period first, then by tool and model
+    pub rows: Vec<CostRow>,
+    /// Sum of the priced rows, in USD
+    pub total_cost: f64,
+    /// Models without a price (their rows have no cost)
+    pub unpriced_models: Vec<String>,
+}
+
+/// Group usage by period, tool and model and price it.
+pub fn cost_report(usage: &[TokenUsage], prices: &PriceTable, period: Period) -> CostReport {
+    let mut groups: BTreeMap<(NaiveDate, &str, &str), (u64, u64, bool)> = BTreeMap::new();
+    for u in usage {
+        let group = groups
+            .entry((period.start(u.date), u.source.as_str(), u.model.as_str()))
+            .or_default();
+        group.0 += u.input_tokens;
+        group.1 += u.output_tokens;
+        group.2 |= u.estimated;
+    }
+
+    let mut unpriced = BTreeSet::new();
+    let mut total_cost = 0.0;
+    let mut rows: Vec<CostRow> = groups
+        .into_iter()
+        .map(
+            |((period_start, source, model), (input_tokens, output_tokens, estimated))| {
+                let cost = prices.price(model).map(|price| {
+                    (input_tokens as f64 * price.input + output_tokens as f64 * price.output)
+                        / 1_000_000.0
+                });
+                match cost {
+                    Some(cost) => total_cost += cost,
+                    None => {
+                        unpriced.insert(model.to_string());
+                    }
+                }
+                CostRow {
+                    period_start,
+                    source: source.to_string(),
+                    model: model.to_string(),
+                    input_tokens,
+                    output_tokens,
+                    cost,
+                    estimated,
+                }
+            },
+        )
+        .collect();
+    rows.sort_by(|a, b| {
+        b.period_start
+            .cmp(&a.period_start)
+            .then_with(|| a.source.cmp(&b.source))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+
+    CostReport {
+        period,
+        rows,
+        total_cost,
+        unpriced_models: unpriced.into_iter().collect(),
+    }
+}
+
+/// Cost report of the vault in `config`, priced with its price table.
+///
+/// Usage of sessions new or changed since the last report is computed first
+/// (see [`update_conversation_stats`]); the rest comes from vault.db.
+pub fn vault_cost_report(config: &Config, period: Period) -> anyhow::Result<CostReport> {
+    let vault_dir = &config.vault_path;
+    let mut usage = Vec::new();
+    if vault_dir.join("vault.db").exists() {
+        update_conversation_stats(vault_dir)?;
+        for u in VaultDb::open(vault_dir)?.session_usage()? {
+            let Ok(date) = NaiveDate::parse_from_str(&u.date, "%Y-%m-%d") else {
+                continue;
+            };
+            usage.push(TokenUsage {
+                date,
+                source: u.source,
+                model: u.model,
+                input_tokens: u.input_tokens,
+                output_tokens: u.output_tokens,
+                estimated: u.estimated,
+            });
+        }
+    }
+    usage.extend(intercepted_usage(&vault_dir.join("intercepted")));
+
+    let prices = PriceTable::new(&config.analytics.prices);
+    Ok(cost_report(&usage, &prices, period))
+}
+
+/// Activity over the last days, from vault.db.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageDashboard {
+    /// First day covered (`YYYY-MM-DD`)
+    pub since: String,
+    pub sessions_per_day: Vec<DayCount>,
+    /// Sessions whose metadata has not been enriched yet are not counted
+    pub messages_per_model: Vec<ModelCount>,
+    pub top_workspaces: Vec<WorkspaceActivity>,
+}
+
+/// Activity of the sessions created in the last `days` days.
+pub fn usage_dashboard(vault_dir: &Path, days: u32) -> anyhow::Result<UsageDashboard> {
+    let since = (Utc::now() - Duration::days(days as i64))
+        .format("%Y-%m-%d")
+        .to_string();
+    if !vault_dir.join("vault.db").exists() {
+        return Ok(UsageDashboard {
+            since,
+            ..Default::default()
+        });
+    }
+
+    let db = VaultDb::open(vault_dir)?;
+    Ok(UsageDashboard {
+        sessions_per_day: db.sessions_per_day(&since)?,
+        messages_per_model: db.messages_per_model(&since)?,
+        top_workspaces: db.workspace_activity(&since, TOP_WORKSPACES)?,
+        since,
+    })
+}
+
+/// Sessions and messages of one source, workspace, month or day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupCount {
+    /// Source, workspace, month (`YYYY-MM`) or day (`YYYY-MM-DD`)
+    pub key: String,
+    pub sessions: u64,
+    pub messages: u64,
+}
+
+/// Statistics of the whole vault. Duplicates are left out.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VaultStats {
+    pub sessions: u64,
+    /// Sessions that could not be parsed, counted in sessions only
+    pub unparsed: u64,
+    pub messages: RoleCounts,
+    /// Characters of all messages
+    pub characters: u64,
+    /// Most sessions first
+    pub sources: Vec<GroupCount>,
+    /// The workspaces with the most sessions
+    pub workspaces: Vec<GroupCount>,
+    /// By month of creation, oldest first
+    pub months: Vec<GroupCount>,
+    /// The models behind the most assistant messages
+    pub models: Vec<ModelCount>,
+    /// The days of creation with the most messages
+    pub busiest_days: Vec<GroupCount>,
+}
+
+/// Message counts and text size of a parsed conversation.
+pub fn conversation_stats(conversation: &ParsedConversation, mtime: u64) -> ConversationStats {
+    let mut stats = ConversationStats {
+        session_id: conversation.id.clone(),
+        mtime,
+        ..Default::default()
+    };
+    for message in &conversation.messages {
+        let count = match message.role {
+            Role::User => &mut stats.messages.user,
+            Role::Assistant => &mut stats.messages.assistant,
+            Role::System => &mut stats.messages.system,
+            Role::Tool => &mut stats.messages.tool,
+            Role::Info => &mut stats.messages.info,
+        };
+        *count += 1;
+        stats.characters += message.content.chars().count() as u64;
+        if message.role == Role::Assistant {
+            let model = message
+                .model
+                .as_deref()
+                .or(conversation.model.as_deref())
+                .unwrap_or(UNKNOWN_MODEL);
+            *stats.models.entry(model.to_string()).or_default() += 1;
+        }
+    }
+    stats
+}
+
+/// Compute the conversation stats and token usage of sessions new or changed
+/// since they were cached. Returns how many were updated; sessions that fail
+/// to parse are retried on the next run.
+pub fn update_conversation_stats(vault_dir: &Path) -> anyhow::Result<usize> {
+    let mut db = VaultDb::open(vault_dir)?;
+    let sessions = db.sessions_missing_conversation_stats()?;
+    if sessions.is_empty() {
+        return Ok(0);
+    }
+
+    let parsers = all_parsers();
+    let sessions_dir = vault_dir.join("sessions");
+    let (stats, usage): (Vec<_>, Vec<_>) = sessions
+        .par_iter()
+        .filter_map(|session| {
+            let parser = parsers.iter().find(|p| p.source_name() == session.source)?;
+            let raw_path = compressed::locate(&vault_dir.join(&session.vault_path));
+            let conversation = match parse_raw_file(parser.as_ref(), &raw_path, &sessions_dir) {
+                Ok(conversation) => conversation,
+                Err(e) => {
+                    debug!("[analytics] Cannot parse {:?}: {}", raw_path, e);
+                    return None;
+                }
+            };
+            let stats = ConversationStats {
+                session_id: session.id.clone(),
+                ..conversation_stats(&conversation, session.mtime)
+            };
+            let usage: Vec<_> = conversation_usage(&conversation)
+                .into_iter()
+                .map(|u| SessionUsage {
+                    session_id: session.id.clone(),
+                    source: session.source.clone(),
+                    date: u.date.format("%Y-%m-%d").to_string(),
+                    model: u.model,
+                    input_tokens: u.input_tokens,
+                    output_tokens: u.output_tokens,
+                    estimated: u.estimated,
+                })
+                .collect();
+            Some((stats, usage))
+        })
+        .unzip();
+
+    db.set_conversation_stats(&stats, &usage.concat())?;
+    Ok(stats.len())
+}
+
+/// Add a session and its messages to the group `key`.
+fn count_in(groups: &mut HashMap<String, GroupCount>, key: &str, messages: u64) {
+    let group = groups.entry(key.to_string()).or_insert_with(|| GroupCount {
+        key: key.to_string(),
+        sessions: 0,
+        messages: 0,
+    });
+    group.sessions += 1;
+    group.messages += messages;
+}
+
+/// Groups with the most sessions (or messages) first, then by key.
+fn ranked(groups: HashMap<String, GroupCount>, by_messages: bool) -> Vec<GroupCount> {
+    let mut groups: Vec<_> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        let (a_count, b_count) = if by_messages {
+            (a.messages, b.messages)
+        } else {
+            (a.sessions, b.sessions)
+        };
+        b_count.cmp(&a_count).then_with(|| a.key.cmp(&b.key))
+    });
+    groups
+}
+
+/// Statistics of the vault, after updating the cached conversation stats.
+pub fn vault_stats(vault_dir: &Path) -> anyhow::Result<VaultStats> {
+    if !vault_dir.join("vault.db").exists() {
+        return Ok(VaultStats::default());
+    }
+    update_conversation_stats(vault_dir)?;
+
+    let mut stats = VaultStats::default();
+    let mut sources = HashMap::new();
+    let mut workspaces = HashMap::new();
+    let mut months = HashMap::new();
+    let mut days = HashMap::new();
+    // model -> (sessions, assistant messages)
+    let mut models: HashMap<String, (u64, u64)> = HashMap::new();
+
+    for (session, conversation) in VaultDb::open(vault_dir)?.sessions_with_conversation_stats()? {
+        stats.sessions += 1;
+        let messages = match &conversation {
+            Some(conversation) => {
+                stats.messages.add(&conversation.messages);
+                stats.characters += conversation.characters;
+                for (model, replies) in &conversation.models {
+                    let entry = models.entry(model.clone()).or_default();
+                    entry.0 += 1;
+                    entry.1 += replies;
+                }
+                conversation.messages.total()
+            }
+            None => {
+                stats.unparsed += 1;
+                0
+            }
+        };
+
+        count_in(&mut sources, &session.source, messages);
+        if let Some(workspace) = &session.workspace_name {
+            count_in(&mut workspaces, workspace, messages);
+        }
+        // RFC 3339: `YYYY-MM-DD...`
+        if let Some(created_at) = session.created_at.as_deref().filter(|d| d.len() >= 10) {
+            count_in(&mut months, &created_at[..7], messages);
+            count_in(&mut days, &created_at[..10], messages);
+        }
+    }
+
+    stats.sources = ranked(sources, false);
+    stats.workspaces = ranked(workspaces, false);
+    stats.workspaces.truncate(TOP_WORKSPACES);
+    stats.months = months.into_values().collect();
+    stats.months.sort_by(|a, b| a.key.cmp(&b.key));
+    stats.busiest_days = ranked(days, true);
+    stats.busiest_days.truncate(TOP_ENTRIES);
+    let mut models: Vec<_> = models
+        .into_iter()
+        .map(|(model, (sessions, messages))| ModelCount {
+            model,
+            sessions,
+            messages,
+        })
+        .collect();
+    models.sort_by(|a, b| {
+        b.messages
+            .cmp(&a.messages)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    models.truncate(TOP_ENTRIES);
+    stats.models = models;
+    Ok(stats)
+}
+
+/// Usage of a parsed conversation: the counts its source reports, as one
+/// entry on the day it started, or else an estimate of each assistant reply.
+pub fn conversation_usage(conversation: &ParsedConversation) -> Vec<TokenUsage> {
+    if let Some(reported) = &conversation.usage {
+        let date = conversation
+            .created_at
+            .or(conversation.updated_at)
+            .or_else(|| conversation.messages.iter().find_map(|m| m.timestamp));
+        let model = conversation
+            .model
+            .as_deref()
+            .or_else(|| {
+                conversation
+                    .messages
+                    .iter()
+                    .find_map(|m| m.model.as_deref())
+            })
+            .unwrap_or(UNKNOWN_MODEL);
+        return date
+            .map(|date| TokenUsage {
+                date: date.date_naive(),
+                source: conversation.source.clone(),
+                model: model.to_string(),
+                input_tokens: reported.input_tokens,
+                output_tokens: reported.output_tokens,
+                estimated: false,
+            })
+            .into_iter()
+            .collect();
+    }
+
+    let mut usage = Vec::new();
+    let mut context_tokens = 0;
+    for message in &conversation.messages {
+        let tokens = (message.content.chars().count() as u64).div_ceil(CHARS_PER_TOKEN);
+        if message.role == Role::Assistant {
+            let date = message.timestamp.or(conversation.created_at);
+            if let Some(date) = date {
+                let model = message
+                    .model
+                    .as_deref()
+                    .or(conversation.model.as_deref())
+                    .unwrap_or(UNKNOWN_MODEL);
+                usage.push(TokenUsage {
+                    date: date.date_naive(),
+                    source: conversation.source.clone(),
+                    model: model.to_string(),
+                    input_tokens: context_tokens,
+                    output_tokens: tokens,
+                    estimated: true,
+                });
+            }
+        }
+        context_tokens += tokens;
+    }
+    usage
+}
+
+/// Captured exchange, as written by the interceptor's logger.
+#[derive(Deserialize)]
+struct Exchange {
+    timestamp: String,
+    url: String,
+    request_body: Option<Value>,
+    response_body: Option<Value>,
+}
+
+/// Reported usage of the API exchanges captured by the interceptor.
+pub fn intercepted_usage(dir: &Path) -> Vec<TokenUsage> {
+    let files = Walk::new(dir).files();
+    let mut usage = Vec::new();
+    for path in files
+        .iter()
+        .filter(|p| p.extension().is_some_and(|e| e == "jsonl"))
+    {
+        let Ok(file) = std::fs::File::open(path) else {
+            continue;
+        };
+        for line in std::io::BufReader::new(file).lines().map_while(Result::ok) {
+            match serde_json::from_str::<Exchange>(&line) {
+                Ok(exchange) => usage.extend(exchange_usage(&exchange)),
+                Err(e) => debug!("[analytics] Skipping line of {:?}: {}", path, e),
+            }
+        }
+    }
+    usage
+}
+
+fn exchange_usage(exchange: &Exchange) -> Option<TokenUsage> {
+    let response = exchange.response_body.as_ref()?;
+    let count = |usage: &Value, key: &str| usage.get(key).and_then(Value::as_u64);
+
+    let (input_tokens, output_tokens) = if let Some(usage) = response.get("usageMetadata") {
+        // Gemini
+        (
+            count(usage, "promptTokenCount")?,
+            count(usage, "candidatesTokenCount").unwrap_or(0),
+        )
+    } else {
+        let usage = response.get("usage")?;
+        // OpenAI, then Anthropic
+        match (count(usage, "prompt_tokens"), count(usage, "input_tokens")) {
+            (Some(input), _) => (input, count(usage, "completion_tokens").unwrap_or(0)),
+            (None, Some(input)) => (input, count(usage, "output_tokens").unwrap_or(0)),
+            (None, None) => return None,
+        }
+    };
+
+    let model = ["model", "modelVersion"]
+        .iter()
+        .find_map(|key| response.get(key).and_then(Value::as_str))
+        .or_else(|| {
+            exchange
+                .request_body
+                .as_ref()
+                .and_then(|body| body.get("model"))
+                .and_then(Value::as_str)
+        })
+        .map(str::to_string)
+        .or_else(|| model_from_url(&exchange.url))
+        .unwrap_or_else(|| UNKNOWN_MODEL.to_string());
+
+    let date = DateTime::parse_from_rfc3339(&exchange.timestamp)
+        .ok()?
+        .with_timezone(&Utc)
+        .date_naive();
+    Some(TokenUsage {
+        date,
+        source: INTERCEPTOR_SOURCE.to_string(),
+        model,
+        input_tokens,
+        output_tokens,
+        estimated: false,
+    })
+}
+
+/// Model named in a Gemini-style URL (`.../models/<model>:generateContent`).
+fn model_from_url(url: &str) -> Option<String> {
+    let rest = url.split("/models/").nth(1)?;
+    let model = rest.split([':', '?', '/']).next()?;
+    (!model.is_empty()).then(|| model.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ParsedMessage, TokenCounts};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn message(role: Role, content: &str) -> ParsedMessage {
+        ParsedMessage {
+            role,
+            content: content.to_string(),
+            timestamp: None,
+            tool_name: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_cost_report_groups_and_prices_usage() -> anyhow::Result<()> {
+        let conversation = ParsedConversation {
+            id: "abc".to_string(),
+            source: "cursor".to_string(),
+            title: None,
+            workspace: None,
+            created_at: Some("2024-01-17T10:00:00Z".parse()?),
+            updated_at: None,
+            model: Some("gpt-4o-2024-08-06".to_string()),
+            messages: vec![
+                message(Role::User, &"q".repeat(400)),
+                message(Role::Assistant, &"a".repeat(800)),
+            ],
+            tags: vec![],
+            usage: None,
+        };
+        let estimated = conversation_usage(&conversation);
+        assert_eq!(estimated.len(), 1);
+        assert_eq!(
+            (estimated[0].input_tokens, estimated[0].output_tokens),
+            (100, 200)
+        );
+
+        let temp = TempDir::new()?;
+        let dir = temp
+            .path()
+            .join("generativelanguage.googleapis.com/2024-01-15");
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            dir.join("103000_abcdef.jsonl"),
+            "{\"timestamp\":\"2024-01-15T10:30:00Z\",\"method\":\"POST\",\
+             \"url\":\"https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-pro:generateContent\",\
+             \"request_body\":{},\"response_status\":200,\"response_content_type\":\"application/json\",\
+             \"response_body\":{\"usageMetadata\":{\"promptTokenCount\":1000000,\"candidatesTokenCount\":100000}}}\n",
+        )?;
+        let reported = intercepted_usage(temp.path());
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].model, "gemini-2.5-pro");
+        assert!(!reported[0].estimated);
+
+        let mut usage = [estimated, reported].concat();
+        usage.push(TokenUsage {
+            date: NaiveDate::from_ymd_opt(2024, 1, 16).unwrap(),
+            source: "cursor".to_string(),
+            model: "my-local-model".to_string(),
+            input_tokens: 10,
+            output_tokens: 10,
+            estimated: true,
+        });
+        let overrides = BTreeMap::from([(
+            "gpt-4o".to_string(),
+            ModelPrice {
+                input: 10.0,
+                output: 20.0,
+            },
+        )]);
+        let prices = PriceTable::new(&overrides);
+        assert_eq!(prices.price("models/gpt-4o-mini").unwrap().input, 0.15);
+
+        // Monday 2024-01-15 starts the week of all three days
+        let report = cost_report(&usage, &prices, Period::Week);
+        assert_eq!(report.rows.len(), 3);
+        assert!(report
+            .rows
+            .iter()
+            .all(|r| r.period_start == NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()));
+        let cost = 1.25 + 0.1 * 10.0 + (100.0 * 10.0 + 200.0 * 20.0) / 1_000_000.0;
+        assert!((report.total_cost - cost).abs() < 1e-9);
+        assert_eq!(report.unpriced_models, vec!["my-local-model".to_string()]);
+
+        assert_eq!(cost_report(&usage, &prices, Period::Day).rows.len(), 3);
+        let monthly = cost_report(&usage, &prices, Period::Month);
+        assert_eq!(
+            monthly.rows[0].period_start,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+        assert_eq!(monthly.rows[0].source, "cursor");
+
+        // Counts reported by the source replace the estimate
+        let reported = ParsedConversation {
+            usage: Some(TokenCounts {
+                input_tokens: 5000,
+                output_tokens: 700,
+            }),
+            ..conversation
+        };
+        let usage = conversation_usage(&reported);
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].input_tokens, usage[0].output_tokens), (5000, 700));
+        assert!(!usage[0].estimated);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vault_stats_counts_and_caches() -> anyhow::Result<()> {
+        use crate::storage::SessionEntry;
+
+        let vault = TempDir::new()?;
+        let dir = vault.path().join("sessions/claude-code");
+        fs::create_dir_all(&dir)?;
+        let mut entries = Vec::new();
+        for (id, day) in [("a", "15"), ("b", "15"), ("c", "16")] {
+            let path = dir.join(format!("{}.jsonl", id));
+            fs::write(
+                &path,
+                format!(
+                    "{{\"role\":\"user\",\"content\":\"Hello\",\"timestamp\":\"2024-01-{day}T10:30:00Z\"}}\n\
+                     {{\"role\":\"assistant\",\"content\":\"Hi\",\"timestamp\":\"2024-01-{day}T10:31:00Z\"}}\n"
+                ),
+            )?;
+            entries.push(SessionEntry {
+                id: id.to_string(),
+                source: "claude-code".to_string(),
+                mtime: 1_700_000_000,
+                file_size: 100,
+                title: None,
+                workspace_name: Some("project".to_string()),
+                created_at: Some(format!("2024-01-{}T10:30:00+00:00", day)),
+                vault_path: path.to_string_lossy().to_string(),
+                original_path: format!("/home/user/.claude/projects/p/{}.jsonl", id),
+                content_hash: None,
+            });
+        }
+        VaultDb::open(vault.path())?.ingest_batch(&entries)?;
+
+        let stats = vault_stats(vault.path())?;
+        assert_eq!((stats.sessions, stats.unparsed), (3, 0));
+        assert_eq!((stats.messages.user, stats.messages.assistant), (3, 3));
+        assert_eq!(stats.characters, 3 * 7);
+        assert_eq!(stats.sources[0].key, "claude-code");
+        assert_eq!(stats.workspaces[0].sessions, 3);
+        assert_eq!(stats.months[0].key, "2024-01");
+        assert_eq!(stats.busiest_days[0].key, "2024-01-15");
+        assert_eq!(stats.busiest_days[0].messages, 4);
+        assert_eq!(stats.models[0].model, UNKNOWN_MODEL);
+        assert_eq!(stats.models[0].messages, 3);
+
+        // Cached: nothing is parsed again until a session changes
+        assert_eq!(update_conversation_stats(vault.path())?, 0);
+        let usage = VaultDb::open(vault.path())?.session_usage()?;
+        assert_eq!(usage.len(), 3);
+        assert!(usage
+            .iter()
+            .all(|u| u.estimated && u.source == "claude-code"));
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+//! Local REST API for EchoVault.
+//!
+//! Serves vault data as JSON over HTTP, for the CLI `serve` command, the
+//! desktop app and third-party integrations such as browser extensions:
+//!
+//! - `GET /sessions?source=&limit=&offset=` - Browse sessions, newest first
+//! - `GET /sessions/{id}` - Session metadata and its parsed Markdown
+//! - `GET /search?q=&limit=&semantic=` - Full-text (FTS5) or semantic search
+//! - `POST /sync` - Run a sync through the handler the host registered
+//!
+//! Errors are returned as `{"error": "..."}` with a matching status code.
+//! Every request must carry `Authorization: Bearer <token>` and a localhost
+//! `Host`; others get 401 and 403.
+
+use crate::config::Config;
+use crate::extractors::SessionMetadata;
+use crate::progress::{is_cancelled, Operation};
+use crate::storage::SessionIndex;
+use crate::utils::http_auth;
+use axum::{
+    extract::{Path as UrlPath, Query, Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::info;
+
+/// Default port of the API server (the interceptor proxy uses 18080).
+pub const DEFAULT_PORT: u16 = 18081;
+
+/// Environment variable holding the bearer token of the API server
+pub const TOKEN_ENV: &str = "ECHOVAULT_API_TOKEN";
+
+/// Runs a sync (pull, extract, push) for `POST /sync`.
+///
+/// Sync needs the host's credentials and encryption key, so the CLI and the
+/// desktop app each provide their own.
+pub type SyncHandler = Box<dyn Fn(&Operation) -> anyhow::Result<()> + Send + Sync>;
+
+/// Shared state of the API server.
+pub struct ApiState {
+    config: Config,
+    /// Bearer token clients must send
+    token: String,
+    sync: Option<SyncHandler>,
+    syncing: AtomicBool,
+}
+
+impl ApiState {
+    pub fn new(config: Config, token: String) -> Self {
+        Self {
+            config,
+            token,
+            sync: None,
+            syncing: AtomicBool::new(false),
+        }
+    }
+
+    /// Enable `POST /sync` (it answers 501 Not Implemented otherwise).
+    pub fn with_sync(mut self, handler: SyncHandler) -> Self {
+        self.sync = Some(handler);
+        self
+    }
+
+    fn vault_dir(&self) -> &Path {
+        &self.config.vault_path
+    }
+}
+
+// ============ ERRORS ============
+
+/// Error response: `{"error": message}` with `status`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Run blocking vault work (SQLite, file reads) off the async runtime.
+async fn blocking<T, F>(work: F) -> ApiResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, ApiError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map(Json)
+}
+
+// ============ HANDLERS ============
+
+#[derive(Debug, Deserialize)]
+struct ListParams {
+    /// Source filter (e.g., "vscode-copilot", "cursor")
+    source: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+async fn list_sessions(
+    State(api): State<Arc<ApiState>>,
+    Query(params): Query<ListParams>,
+) -> ApiResult<Vec<SessionMetadata>> {
+    blocking(move || {
+        let index = SessionIndex::open(api.vault_dir())?;
+        let limit = params.limit.unwrap_or(50);
+        let offset = params.offset.unwrap_or(0);
+        let sessions = match params.source.as_deref() {
+            Some(source) => index.filter_by_source(source, limit, offset)?,
+            None => index.list(limit, offset)?,
+        };
+        Ok(sessions)
+    })
+    .await
+}
+
+/// A session with its parsed content (absent until `parse` has run).
+#[derive(Debug, Serialize)]
+struct SessionDetail {
+    #[serde(flatten)]
+    session: SessionMetadata,
+    markdown: Option<String>,
+}
+
+async fn get_session(
+    State(api): State<Arc<ApiState>>,
+    UrlPath(id): UrlPath<String>,
+) -> ApiResult<SessionDetail> {
+    blocking(move || {
+        let index = SessionIndex::open(api.vault_dir())?;
+        let Some(session) = index.get(&id)? else {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", id),
+            ));
+        };
+        let parsed_path = api
+            .vault_dir()
+            .join("parsed")
+            .join(&session.source)
+            .join(format!("{}.md", session.id));
+        let markdown = std::fs::read_to_string(parsed_path).ok();
+        Ok(SessionDetail { session, markdown })
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: String,
+    limit: Option<usize>,
+    /// Rank by embedding similarity instead of FTS5 (requires `embed`)
+    #[serde(default)]
+    semantic: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum SearchResults {
+    Sessions(Vec<SessionMetadata>),
+    #[cfg(feature = "embedding")]
+    Semantic(Vec<crate::embedding::SemanticSearchResult>),
+}
+
+async fn search(
+    State(api): State<Arc<ApiState>>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<SearchResults> {
+    if params.q.trim().is_empty() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "'q' parameter must not be empty",
+        ));
+    }
+    blocking(move || {
+        if params.semantic {
+            return semantic_search(&api, &params.q, params.limit.unwrap_or(10));
+        }
+        let index = SessionIndex::open(api.vault_dir())?;
+        let sessions = index.search(&params.q, params.limit.unwrap_or(20))?;
+        Ok(SearchResults::Sessions(sessions))
+    })
+    .await
+}
+
+fn semantic_search(api: &ApiState, query: &str, limit: usize) -> Result<SearchResults, ApiError> {
+    #[cfg(feature = "embedding")]
+    {
+        let config = &api.config;
+        let embedding_config = crate::embedding::EmbeddingConfig {
+            backend: config.embedding.backend,
+            api_base: config.embedding.api_base.clone(),
+            api_key: config.embedding.resolved_api_key(),
+            model: config.embedding.model.clone(),
+            chunk_size: config.embedding.chunk_size,
+            chunk_overlap: config.embedding.chunk_overlap,
+            batch_size: config.embedding.batch_size,
+            concurrency: config.embedding.concurrency,
+            request_timeout_secs: config.embedding.request_timeout_secs,
+            max_retries: config.embedding.max_retries,
+            rerank: config.embedding.rerank.clone(),
+        };
+        let results = crate::embedding::search_similar(
+            &embedding_config,
+            api.vault_dir(),
+            query,
+            limit,
+            &Default::default(),
+        )?;
+        Ok(SearchResults::Semantic(results))
+    }
+
+    #[cfg(not(feature = "embedding"))]
+    {
+        let _ = (api, query, limit);
+        Err(ApiError::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "Embedding feature not enabled. Rebuild with --features embedding",
+        ))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SyncStatus {
+    status: &'static str,
+}
+
+async fn sync(State(api): State<Arc<ApiState>>) -> ApiResult<SyncStatus> {
+    if api.sync.is_none() {
+        return Err(ApiError::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "Sync is not available from this server",
+        ));
+    }
+    if api.syncing.swap(true, Ordering::SeqCst) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "A sync is already running",
+        ));
+    }
+
+    blocking(move || {
+        let _running = SyncGuard(&api.syncing);
+        let handler = api.sync.as_ref().expect("checked above");
+        match handler(&Operation::default()) {
+            Ok(()) => Ok(SyncStatus {
+                status: "completed",
+            }),
+            Err(e) if is_cancelled(&e) => Ok(SyncStatus {
+                status: "cancelled",
+            }),
+            Err(e) => Err(e.into()),
+        }
+    })
+    .await
+}
+
+/// Clears the `syncing` flag when the sync ends, even if the handler panics.
+struct SyncGuard<'a>(&'a AtomicBool);
+
+impl Drop for SyncGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+// ============ SERVER ============
+
+/// Routes of the API, for embedding in another server.
+pub fn router(state: ApiState) -> Router {
+    let token = Arc::new(state.token.clone());
+    Router::new()
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{id}", get(get_session))
+        .route("/search", get(search))
+        .route("/sync", post(sync))
+        .with_state(Arc::new(state))
+        .layer(middleware::from_fn(move |request: Request, next: Next| {
+            let token = token.clone();
+            async move { http_auth::require_token(&token, request, next).await }
+        }))
+        // Outermost: checked before the token
+        .layer(middleware::from_fn(http_auth::require_loopback_host))
+}
+
+/// Serve the API on `addr` until `shutdown` completes.
+pub async fn serve(
+    addr: SocketAddr,
+    state: ApiState,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind API server to {}: {}", addr, e))?;
+    info!("[api] Listening on http://{}", listener.local_addr()?);
+
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(shutdown)
+        .await
+        .map_err(|e| anyhow::anyhow!("API server error: {}", e))
+}
@@ -0,0 +1,113 @@
+//! Config migrations.
+//!
+//! Each step upgrades the raw TOML table by one `config_version`, so a config
+//! of any age walks the chain up to [`CONFIG_VERSION`]. Before a migrated
+//! config is written back, the original file is copied next to it
+//! (`echovault.toml.v<old>.bak`).
+//!
+//! History:
+//! - 1: legacy `crates/echovault-core` format, synced to a GitHub repository
+//! - 2: Rclone-only sync (`[sync] remote_name`, `folder_name`)
+//! - 3: `version` renamed to `config_version`
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+/// Current config format version
+pub const CONFIG_VERSION: u32 = 3;
+
+/// `[sync]` keys still understood after the move to Rclone
+const SYNC_KEYS: &[&str] = &["remote_name", "folder_name"];
+
+/// Result of migrating a config table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// Version the config had before
+    pub from: u32,
+    /// What changed, for the log
+    pub notes: Vec<String>,
+}
+
+/// Version of a raw config table (unversioned configs are the legacy format).
+pub fn version_of(table: &Table) -> u32 {
+    table
+        .get("config_version")
+        .or_else(|| table.get("version"))
+        .and_then(|v| v.as_integer())
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(1)
+}
+
+/// 1 -> 2: drop GitHub sync settings; sync now goes through an rclone remote.
+fn v1_to_v2(table: &mut Table, notes: &mut Vec<String>) {
+    if let Some(github) = table.remove("github") {
+        if github.as_table().is_some_and(|t| !t.is_empty()) {
+            notes.push("removed [github] settings (GitHub sync was replaced by rclone)".into());
+        }
+    }
+    if let Some(sync) = table.get_mut("sync").and_then(|s| s.as_table_mut()) {
+        let legacy: Vec<String> = sync
+            .keys()
+            .filter(|key| !SYNC_KEYS.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        for key in legacy {
+            sync.remove(&key);
+            notes.push(format!("removed sync.{} (GitHub sync)", key));
+        }
+    }
+    if notes.iter().any(|note| note.contains("GitHub")) {
+        notes.push("run `echovault-cli auth` to set up Google Drive sync".into());
+    }
+}
+
+/// 2 -> 3: `version` is now `config_version`.
+fn v2_to_v3(table: &mut Table, _notes: &mut Vec<String>) {
+    table.remove("version");
+}
+
+/// Upgrades a table by one version, appending notes on what changed
+type Step = fn(&mut Table, &mut Vec<String>);
+
+/// Upgrade steps, indexed by the version they start from.
+const STEPS: &[(u32, Step)] = &[(1, v1_to_v2), (2, v2_to_v3)];
+
+/// Upgrade a raw config table to [`CONFIG_VERSION`].
+///
+/// Returns `None` when the config is already current. Configs from a newer
+/// EchoVault are left untouched (reported by `Config::validate`).
+pub fn migrate(table: &mut Table) -> Result<Option<Migration>> {
+    let from = version_of(table);
+    if from >= CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    let mut notes = Vec::new();
+    for version in from..CONFIG_VERSION {
+        let Some((_, step)) = STEPS.iter().find(|(start, _)| *start == version) else {
+            bail!("No migration from config version {}", version);
+        };
+        step(table, &mut notes);
+    }
+    table.remove("version");
+    table.insert(
+        "config_version".to_string(),
+        toml::Value::Integer(CONFIG_VERSION.into()),
+    );
+    Ok(Some(Migration { from, notes }))
+}
+
+/// Backup path for the original of a migrated config (never overwrites a backup).
+pub fn backup_path(path: &Path, from: u32) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "echovault.toml".to_string());
+    let base = path.with_file_name(format!("{}.v{}.bak", name, from));
+    if !base.exists() {
+        return base;
+    }
+    let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+    path.with_file_name(format!("{}.v{}.{}.bak", name, from, stamp))
+}
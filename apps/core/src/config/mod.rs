@@ -0,0 +1,3044 @@
+//! Config module - Manages EchoVault configuration (echovault.toml).
+//!
+//! Configuration file contains:
+//! - Rclone sync settings
+//! - Vault path
+//! - File watcher settings
+//! - Background scheduling (battery/metered network)
+//! - Daily/weekly digests of new conversations
+//! - Webhook notifications of sync and extraction events
+//! - Language of user-facing messages ([`crate::i18n`])
+//! - Named profiles (`[profiles.<name>]`) with their own vault, remote and sources,
+//!   or with a config file of their own listed in `profiles.toml` ([`profiles`])
+//! - `ECHOVAULT_*` environment overrides layered over the file
+//! - Validation ([`Config::validate`]) with actionable issues
+//! - Format migrations ([`migrate`]), applied on load with a backup of the original
+//! - Other settings
+//!
+//! New files start from a commented template; saving updates values in place
+//! so comments and layout written by hand are kept.
+
+use crate::i18n::Language;
+use crate::parsers::markdown_writer::MarkdownFlavor;
+use anyhow::{bail, Context, Result};
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+pub mod migrate;
+pub mod profiles;
+
+pub use migrate::CONFIG_VERSION;
+pub use profiles::ProfilesIndex;
+
+/// Environment variable selecting the active profile (same as `--profile`)
+pub const PROFILE_ENV: &str = "ECHOVAULT_PROFILE";
+
+/// Prefix of environment variables overriding config keys.
+///
+/// `ECHOVAULT_<KEY>` sets a top-level key and `__` separates nested keys
+/// (`ECHOVAULT_SYNC__FOLDER_NAME` -> `sync.folder_name`).
+pub const ENV_PREFIX: &str = "ECHOVAULT_";
+
+/// Short names of common overrides (`ECHOVAULT_<NAME>`) and the key they set
+const ENV_ALIASES: &[(&str, &str)] = &[
+    ("REMOTE", "sync.remote_name"),
+    ("REMOTE_FOLDER", "sync.folder_name"),
+    ("EMBEDDING_ENDPOINT", "embedding.api_base"),
+    ("EMBEDDING_MODEL", "embedding.model"),
+];
+
+/// `ECHOVAULT_*` variables with their own meaning, never read as config keys
+const ENV_RESERVED: &[&str] = &[
+    "PASSPHRASE",
+    "PROFILE",
+    "DIGEST_API_KEY",
+    "MCP_TOKEN",
+    "API_TOKEN",
+];
+
+/// Environment variable holding the API key of the digest chat endpoint
+pub const DIGEST_API_KEY_ENV: &str = "ECHOVAULT_DIGEST_API_KEY";
+
+/// Commented template written for new config files
+pub const CONFIG_TEMPLATE: &str = include_str!("template.toml");
+
+/// Profile chosen on the command line; takes precedence over `ECHOVAULT_PROFILE`
+static ACTIVE_PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Select the profile applied by [`Config::load_default`] for this process.
+pub fn set_active_profile(name: Option<String>) {
+    if let Ok(mut active) = ACTIVE_PROFILE.write() {
+        *active = name.filter(|n| !n.is_empty());
+    }
+}
+
+/// Name of the active profile (`--profile`, then `ECHOVAULT_PROFILE`, then
+/// the `default` of `profiles.toml`).
+pub fn active_profile() -> Option<String> {
+    ACTIVE_PROFILE
+        .read()
+        .ok()
+        .and_then(|active| active.clone())
+        .or_else(|| std::env::var(PROFILE_ENV).ok().filter(|n| !n.is_empty()))
+        .or_else(|| ProfilesIndex::load_default().ok()?.default)
+}
+
+/// Backend the vault is synced with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncProviderKind {
+    /// An rclone remote (Google Drive by default, or any rclone backend)
+    #[default]
+    Rclone,
+    /// Another directory: NAS mount, external drive (`sync.local_path`)
+    Local,
+    /// A value this version does not know; [`Config::validate`] reports it
+    /// and nothing is synced
+    #[serde(other)]
+    Unknown,
+}
+
+impl SyncProviderKind {
+    /// Where the vault is synced to, for messages.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rclone => "Google Drive",
+            Self::Local => "sync folder",
+            Self::Unknown => "unknown sync provider",
+        }
+    }
+}
+
+/// Name of the sync target configured by `[sync]` itself.
+pub const DEFAULT_SYNC_TARGET: &str = "default";
+
+/// An additional place the vault syncs to (`[sync.targets.<name>]`), such
+/// as a NAS next to the main cloud remote.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncTargetConfig {
+    /// Sync backend (rclone, local)
+    #[serde(default)]
+    pub provider: SyncProviderKind,
+    /// Remote name in rclone config, for the `rclone` provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_name: Option<String>,
+    /// Directory holding the sync folder, for the `local` provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_path: Option<PathBuf>,
+    /// Folder name on the remote, or inside `local_path` (default: "EchoVault")
+    #[serde(default = "default_folder_name")]
+    pub folder_name: String,
+}
+
+/// Sync configuration (rclone remote or local folder).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Sync backend (rclone, local)
+    #[serde(default)]
+    pub provider: SyncProviderKind,
+    /// Remote name in rclone config (e.g., "echovault")
+    pub remote_name: Option<String>,
+    /// Directory holding the sync folder with the `local` provider
+    /// (e.g., a NAS mount or external drive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_path: Option<PathBuf>,
+    /// Folder name on cloud, or inside `local_path` (default: "EchoVault")
+    #[serde(default = "default_folder_name")]
+    pub folder_name: String,
+    /// Files uploaded/downloaded in parallel (rclone `--transfers`)
+    #[serde(default = "default_transfers")]
+    pub transfers: usize,
+    /// Files compared with the remote in parallel (rclone `--checkers`)
+    #[serde(default = "default_checkers")]
+    pub checkers: usize,
+    /// Bandwidth limit in KiB/s for pulls and pushes (rclone `--bwlimit`;
+    /// unset: unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bwlimit_kib: Option<u64>,
+    /// Seconds a transfer may stall before it fails (rclone `--timeout`;
+    /// unset: rclone's 5 minutes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Additional sync targets by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, SyncTargetConfig>,
+    /// Sessions of a workspace matching one of these rules (the first) sync
+    /// only with its targets; other sessions sync with every target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<WorkspaceRule>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            provider: SyncProviderKind::default(),
+            remote_name: None,
+            local_path: None,
+            folder_name: default_folder_name(),
+            transfers: default_transfers(),
+            checkers: default_checkers(),
+            bwlimit_kib: None,
+            timeout_secs: None,
+            targets: BTreeMap::new(),
+            workspaces: Vec::new(),
+        }
+    }
+}
+
+impl SyncConfig {
+    /// Names of the sync targets, [`DEFAULT_SYNC_TARGET`] first.
+    pub fn target_names(&self) -> Vec<&str> {
+        std::iter::once(DEFAULT_SYNC_TARGET)
+            .chain(self.targets.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// Settings of a sync target: [`DEFAULT_SYNC_TARGET`] is this section
+    /// itself, other names its `targets`. Parallelism, bandwidth limit and
+    /// timeout are shared by all.
+    pub fn target(&self, name: &str) -> Option<SyncConfig> {
+        let base = SyncConfig {
+            targets: BTreeMap::new(),
+            ..self.clone()
+        };
+        if name == DEFAULT_SYNC_TARGET {
+            return Some(base);
+        }
+        let target = self.targets.get(name)?;
+        Some(SyncConfig {
+            provider: target.provider,
+            remote_name: target.remote_name.clone(),
+            local_path: target.local_path.clone(),
+            folder_name: target.folder_name.clone(),
+            ..base
+        })
+    }
+}
+
+/// Sync targets of the sessions of some workspaces (`[[sync.workspaces]]`),
+/// e.g. work projects to a work remote (see [`crate::sync::partition`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceRule {
+    /// Workspace name glob, case-insensitive (e.g. "acme-*")
+    pub workspace: String,
+    /// Sync targets of those sessions ([`DEFAULT_SYNC_TARGET`] is `[sync]`
+    /// itself)
+    pub targets: Vec<String>,
+}
+
+/// Report a missing or relative `local_path` of the `local` sync provider.
+fn validate_local_path(section: &str, path: Option<&Path>, issues: &mut Vec<ConfigIssue>) {
+    let key = format!("{}.local_path", section);
+    match path {
+        None => issues.push(ConfigIssue::error(
+            &key,
+            "is not set".to_string(),
+            "set it to the directory to sync with (NAS mount, external drive)",
+        )),
+        Some(path) if !path.is_absolute() => issues.push(ConfigIssue::warning(
+            &key,
+            format!("{} is relative to the working directory", path.display()),
+            "use an absolute path",
+        )),
+        Some(_) => {}
+    }
+}
+
+/// Report a `provider` value this version does not know.
+fn unknown_provider_issue(key: &str) -> ConfigIssue {
+    ConfigIssue::error(
+        key,
+        "is not a known sync provider".to_string(),
+        "use \"rclone\" or \"local\"",
+    )
+}
+
+fn default_folder_name() -> String {
+    "EchoVault".to_string()
+}
+
+fn default_transfers() -> usize {
+    8
+}
+
+fn default_checkers() -> usize {
+    16
+}
+
+/// Extractors configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ExtractorsConfig {
+    /// Enabled sources (default: all)
+    #[serde(default)]
+    pub enabled_sources: Vec<String>,
+
+    /// Store session copies zstd-compressed (`<file>.zst`)
+    #[serde(default)]
+    pub compress: bool,
+}
+
+impl ExtractorsConfig {
+    /// Whether sessions of a source are extracted (an empty list enables all).
+    pub fn is_enabled(&self, source: &str) -> bool {
+        self.enabled_sources.is_empty() || self.enabled_sources.iter().any(|s| s == source)
+    }
+}
+
+/// Per-source settings (`[sources.<name>]` section).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceConfig {
+    /// Whether sessions of this source are extracted
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Storage locations scanned besides the detected ones, with the same
+    /// layout (e.g. a second `~/.claude/projects` copied from another machine)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_paths: Vec<PathBuf>,
+
+    /// Glob patterns of session files to skip (e.g. "**/scratch-*")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// Skip session files larger than this many MB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size_mb: Option<u64>,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            extra_paths: Vec::new(),
+            exclude: Vec::new(),
+            max_file_size_mb: None,
+        }
+    }
+}
+
+impl SourceConfig {
+    /// Size limit in bytes, if any.
+    pub fn max_file_size(&self) -> Option<u64> {
+        self.max_file_size_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+}
+
+/// Named profile (`[profiles.<name>]` section).
+///
+/// Unset fields do not fall back to the top-level values: a profile gets its
+/// own vault (see [`default_profile_vault_path`]) and remote folder
+/// (`<folder_name>-<profile>`) so histories never mix. Sources default to the
+/// top-level list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Vault directory of this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault_path: Option<PathBuf>,
+
+    /// Remote of this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync: Option<SyncConfig>,
+
+    /// Sources extracted into this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extractors: Option<ExtractorsConfig>,
+}
+
+/// Profile applied to a loaded config.
+#[derive(Debug, Clone)]
+pub struct ActiveProfile {
+    /// Profile name
+    pub name: String,
+    /// Top-level values replaced by the profile, restored on save (`None`
+    /// for a profile loaded from its own config file)
+    base: Option<ProfileConfig>,
+}
+
+/// File watcher configuration (`[watcher]` section).
+///
+/// Controls auto-extract when source session files change.
+/// Per-source overrides live under `[watcher.sources.<source>]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherConfig {
+    /// Master switch for auto-extract on file changes
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Default quiet period (seconds) after the last change before ingesting
+    #[serde(default = "default_watcher_debounce_secs")]
+    pub debounce_secs: u64,
+
+    /// Per-source overrides keyed by source name (e.g., "cursor"), merged
+    /// over the built-in ones
+    #[serde(
+        default = "default_watcher_sources",
+        deserialize_with = "merge_watcher_sources"
+    )]
+    pub sources: BTreeMap<String, SourceWatcherConfig>,
+}
+
+/// Watcher overrides for a single source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceWatcherConfig {
+    /// Whether this source is watched
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Quiet period override (seconds)
+    #[serde(default)]
+    pub debounce_secs: Option<u64>,
+
+    /// Glob patterns for paths whose changes are ignored (e.g., "**/state.vscdb")
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl Default for SourceWatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_secs: None,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_watcher_debounce_secs() -> u64 {
+    5
+}
+
+fn default_watcher_sources() -> BTreeMap<String, SourceWatcherConfig> {
+    // VS Code forks rewrite state.vscdb constantly while the IDE is open,
+    // but sessions are read from chatSessions/, so ignore the database churn.
+    let vscdb_ignore = || SourceWatcherConfig {
+        ignore: vec![
+            "**/state.vscdb".to_string(),
+            "**/state.vscdb-*".to_string(),
+            "**/state.vscdb.backup".to_string(),
+        ],
+        ..SourceWatcherConfig::default()
+    };
+
+    let mut sources = BTreeMap::new();
+    sources.insert("cursor".to_string(), vscdb_ignore());
+    sources.insert("vscode-copilot".to_string(), vscdb_ignore());
+    sources
+}
+
+/// `[watcher.sources.<source>]` as written by the user: fields left out keep
+/// their built-in value.
+#[derive(Deserialize)]
+struct SourceWatcherOverride {
+    enabled: Option<bool>,
+    debounce_secs: Option<u64>,
+    ignore: Option<Vec<String>>,
+}
+
+/// Merge the user's `[watcher.sources]` over the built-in entries, field by
+/// field, so overriding one setting of a source keeps its default ignores.
+fn merge_watcher_sources<'de, D>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, SourceWatcherConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let overrides = BTreeMap::<String, SourceWatcherOverride>::deserialize(deserializer)?;
+    let mut sources = default_watcher_sources();
+    for (source, custom) in overrides {
+        let entry = sources.entry(source).or_default();
+        if let Some(enabled) = custom.enabled {
+            entry.enabled = enabled;
+        }
+        if custom.debounce_secs.is_some() {
+            entry.debounce_secs = custom.debounce_secs;
+        }
+        if let Some(ignore) = custom.ignore {
+            entry.ignore = ignore;
+        }
+    }
+    Ok(sources)
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            debounce_secs: default_watcher_debounce_secs(),
+            sources: default_watcher_sources(),
+        }
+    }
+}
+
+impl WatcherConfig {
+    /// Whether changes of a source should trigger auto-extract.
+    pub fn is_source_enabled(&self, source: &str) -> bool {
+        self.enabled && self.sources.get(source).is_none_or(|s| s.enabled)
+    }
+
+    /// Quiet period for a source (override or global default).
+    pub fn debounce_for(&self, source: &str) -> Duration {
+        let secs = self
+            .sources
+            .get(source)
+            .and_then(|s| s.debounce_secs)
+            .unwrap_or(self.debounce_secs);
+        Duration::from_secs(secs)
+    }
+
+    /// Ignore globs for a source.
+    pub fn ignore_for(&self, source: &str) -> &[String] {
+        self.sources
+            .get(source)
+            .map(|s| s.ignore.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// Background scheduling configuration (`[schedule]` section).
+///
+/// Periodic sync, watcher-triggered ingest and background embedding are
+/// deferred while these conditions hold. Both are off by default. Manual
+/// actions always run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// Defer background work while running on battery
+    #[serde(default)]
+    pub defer_on_battery: bool,
+
+    /// Defer background work on metered connections (where detectable)
+    #[serde(default)]
+    pub defer_on_metered: bool,
+}
+
+/// Local metrics configuration (`[metrics]` section).
+///
+/// Metrics are recorded in local.db, which is never synced. `telemetry` is the
+/// opt-in any export of anonymous counters must check first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Record sync durations, ingest counts and search latencies
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Allow exporting anonymous counters (nothing is exported today)
+    #[serde(default)]
+    pub telemetry: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            telemetry: false,
+        }
+    }
+}
+
+/// Parsing configuration (`[parse]` section).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseConfig {
+    /// Skip JSONL lines (single messages) larger than this many MB, so a
+    /// runaway tool output does not have to fit in memory
+    #[serde(default = "default_max_message_mb")]
+    pub max_message_mb: u64,
+}
+
+fn default_max_message_mb() -> u64 {
+    64
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            max_message_mb: default_max_message_mb(),
+        }
+    }
+}
+
+/// Price of a model in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Price of prompt (input) tokens
+    pub input: f64,
+    /// Price of completion (output) tokens
+    pub output: f64,
+}
+
+/// Token and cost analytics configuration (`[analytics]` section).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsConfig {
+    /// Prices keyed by model name prefix (e.g., "gpt-4o"). They extend and
+    /// override the built-in price list of [`crate::analytics`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prices: BTreeMap<String, ModelPrice>,
+}
+
+/// Obsidian mirror configuration (`[obsidian]` section).
+///
+/// When `vault_path` is set, parsed conversations are also written as notes
+/// into that Obsidian vault (see [`crate::parsers::obsidian`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObsidianConfig {
+    /// Obsidian vault to mirror conversations into (disabled when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault_path: Option<PathBuf>,
+
+    /// Folder inside the Obsidian vault that holds the notes
+    #[serde(default = "default_obsidian_folder")]
+    pub folder: String,
+
+    /// Note template replacing the built-in one (TinyTemplate syntax)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<PathBuf>,
+}
+
+fn default_obsidian_folder() -> String {
+    "EchoVault".to_string()
+}
+
+impl Default for ObsidianConfig {
+    fn default() -> Self {
+        Self {
+            vault_path: None,
+            folder: default_obsidian_folder(),
+            template: None,
+        }
+    }
+}
+
+/// Digest configuration (`[digest]` section).
+///
+/// Daily and weekly Markdown digests of new conversations, written to
+/// `<vault>/digests` (see [`crate::digest`]). The API key of the chat
+/// endpoint comes from `ECHOVAULT_DIGEST_API_KEY` or the OS keyring.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestConfig {
+    /// Write the digest of each day once it is over (in the background)
+    #[serde(default)]
+    pub daily: bool,
+
+    /// Write the digest of each week (Monday to Sunday) once it is over
+    #[serde(default)]
+    pub weekly: bool,
+
+    /// Show a desktop notification when a digest is written in the background
+    #[serde(default = "default_true")]
+    pub notify: bool,
+
+    /// Notable conversations listed per digest
+    #[serde(default = "default_digest_notable")]
+    pub notable: usize,
+
+    /// OpenAI-compatible chat endpoint writing a summary (none when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_api_base: Option<String>,
+
+    /// Chat model of the summary
+    #[serde(default = "default_digest_chat_model")]
+    pub chat_model: String,
+}
+
+fn default_digest_notable() -> usize {
+    10
+}
+
+fn default_digest_chat_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            daily: false,
+            weekly: false,
+            notify: true,
+            notable: default_digest_notable(),
+            chat_api_base: None,
+            chat_model: default_digest_chat_model(),
+        }
+    }
+}
+
+/// Notifications configuration (`[notifications]` section).
+///
+/// Webhooks called on sync and extraction events (see
+/// [`crate::notifications`]), each event type enabled or disabled here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Notify when a sync completes
+    #[serde(default = "default_true")]
+    pub sync_completed: bool,
+
+    /// Notify when a sync fails
+    #[serde(default = "default_true")]
+    pub sync_failed: bool,
+
+    /// Notify when extraction copies new or changed sessions into the vault
+    #[serde(default = "default_true")]
+    pub sessions_extracted: bool,
+
+    /// Retries of a failed delivery, with exponential backoff
+    #[serde(default = "default_notification_retries")]
+    pub max_retries: u32,
+
+    /// Webhooks (`[[notifications.webhooks]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+fn default_notification_retries() -> u32 {
+    3
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            sync_completed: true,
+            sync_failed: true,
+            sessions_extracted: true,
+            max_retries: default_notification_retries(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Whether events of this type are sent.
+    pub fn is_enabled(&self, event: NotificationEvent) -> bool {
+        match event {
+            NotificationEvent::SyncCompleted => self.sync_completed,
+            NotificationEvent::SyncFailed => self.sync_failed,
+            NotificationEvent::SessionsExtracted => self.sessions_extracted,
+        }
+    }
+}
+
+/// A webhook of `[[notifications.webhooks]]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// URL the events are POSTed to
+    pub url: String,
+
+    /// Payload format
+    #[serde(default)]
+    pub format: WebhookFormat,
+
+    /// Events sent to this webhook (default: every enabled event)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<NotificationEvent>,
+}
+
+impl WebhookConfig {
+    /// Whether this webhook receives events of this type.
+    pub fn wants(&self, event: NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Payload format of a webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{"event", "message", "timestamp", "data"}`
+    #[default]
+    Json,
+    /// Discord webhook (`{"content"}`)
+    Discord,
+    /// Slack incoming webhook (`{"text"}`)
+    Slack,
+}
+
+/// Type of a notification event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    SyncCompleted,
+    SyncFailed,
+    SessionsExtracted,
+}
+
+impl DigestConfig {
+    /// API key of the chat endpoint: `ECHOVAULT_DIGEST_API_KEY`, then the OS
+    /// keyring, then the embedding key when both use the same endpoint.
+    pub fn resolved_api_key(&self, embedding: &EmbeddingConfigToml) -> Option<SecretString> {
+        if let Some(key) = std::env::var(DIGEST_API_KEY_ENV)
+            .ok()
+            .filter(|k| !k.is_empty())
+        {
+            return Some(key.into());
+        }
+        if let Some(key) = crate::secrets::get_or_warn(crate::secrets::DIGEST_API_KEY) {
+            return Some(key);
+        }
+        let same_endpoint = self
+            .chat_api_base
+            .as_deref()
+            .map(|b| b.trim_end_matches('/'))
+            == Some(embedding.api_base.trim_end_matches('/'));
+        if same_endpoint {
+            embedding.resolved_api_key()
+        } else {
+            None
+        }
+    }
+}
+
+/// Encryption configuration (`[encryption]` section).
+///
+/// The encryption scheme itself lives in vault.json (shared by all machines);
+/// this section only holds machine-local settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// age identity file used to unlock age-encrypted vaults
+    /// (default: `<config dir>/age-identity.txt`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    /// Extra age recipients (other machines, hardware keys) added when age
+    /// encryption is set up, besides this machine's identity
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients: Vec<String>,
+    /// Decrypt pulled files of a remote that has no manifest, as sealed by
+    /// versions before manifests existed (the next push writes one)
+    #[serde(default)]
+    pub allow_missing_manifest: bool,
+}
+
+impl EncryptionConfig {
+    /// Path of the age identity file.
+    pub fn identity_path(&self) -> PathBuf {
+        self.identity_file
+            .clone()
+            .unwrap_or_else(|| default_config_dir().join("age-identity.txt"))
+    }
+}
+
+/// Destination name of `echovault-cli export` in
+/// [`RedactionConfig::destinations`].
+pub const EXPORT_DESTINATION: &str = "export";
+
+/// Secret redaction configuration (`[redaction]` section).
+///
+/// Conversations sent to a listed destination have their secrets masked
+/// (see [`crate::redact`]); the vault keeps the originals.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Sync target names ([`DEFAULT_SYNC_TARGET`] is `[sync]` itself) and
+    /// [`EXPORT_DESTINATION`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub destinations: Vec<String>,
+}
+
+impl RedactionConfig {
+    /// Whether copies sent to `destination` are redacted.
+    pub fn applies_to(&self, destination: &str) -> bool {
+        self.destinations.iter().any(|d| d == destination)
+    }
+}
+
+/// Config key set from an environment variable.
+#[derive(Debug, Clone)]
+pub struct EnvOverride {
+    /// Variable name (e.g. `ECHOVAULT_VAULT_PATH`)
+    pub var: String,
+    /// Dotted config key (e.g. `vault_path`)
+    pub key: String,
+    /// Value from the file, restored on save
+    original: Option<toml::Value>,
+}
+
+/// Severity of a [`ConfigIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueLevel {
+    /// Works, but probably not as intended
+    Warning,
+    /// Operations using the key will fail
+    Error,
+}
+
+/// Problem found by [`Config::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    /// Severity
+    pub level: IssueLevel,
+    /// Dotted config key (e.g. `embedding.api_base`)
+    pub key: String,
+    /// What is wrong
+    pub message: String,
+    /// How to fix it
+    pub hint: String,
+}
+
+impl ConfigIssue {
+    fn new(level: IssueLevel, key: &str, message: String, hint: &str) -> Self {
+        Self {
+            level,
+            key: key.to_string(),
+            message,
+            hint: hint.to_string(),
+        }
+    }
+
+    fn error(key: &str, message: String, hint: &str) -> Self {
+        Self::new(IssueLevel::Error, key, message, hint)
+    }
+
+    fn warning(key: &str, message: String, hint: &str) -> Self {
+        Self::new(IssueLevel::Warning, key, message, hint)
+    }
+
+    /// Whether the issue is an error.
+    pub fn is_error(&self) -> bool {
+        self.level == IssueLevel::Error
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.key, self.message, self.hint)
+    }
+}
+
+/// Main EchoVault configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Config format version (see [`migrate`])
+    #[serde(default = "default_version")]
+    pub config_version: u32,
+
+    /// Whether setup is complete
+    #[serde(default)]
+    pub setup_complete: bool,
+
+    /// Path to vault directory
+    pub vault_path: PathBuf,
+
+    /// Sync configuration
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    /// Extractors configuration
+    #[serde(default)]
+    pub extractors: ExtractorsConfig,
+
+    /// Per-source settings keyed by source name (e.g., "claude-code")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, SourceConfig>,
+
+    /// Export path for session exports
+    #[serde(default)]
+    pub export_path: Option<PathBuf>,
+
+    /// Markdown flavor of session exports (standard or logseq)
+    #[serde(default)]
+    pub export_format: MarkdownFlavor,
+
+    /// Embedding configuration
+    #[serde(default)]
+    pub embedding: EmbeddingConfigToml,
+
+    /// Parsing configuration
+    #[serde(default)]
+    pub parse: ParseConfig,
+
+    /// File watcher configuration
+    #[serde(default)]
+    pub watcher: WatcherConfig,
+
+    /// Background scheduling configuration
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+
+    /// Encryption configuration
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+
+    /// Local metrics configuration
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+
+    /// Token and cost analytics configuration
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+
+    /// Obsidian mirror configuration
+    #[serde(default)]
+    pub obsidian: ObsidianConfig,
+
+    /// Digest configuration
+    #[serde(default)]
+    pub digest: DigestConfig,
+
+    /// Webhook notifications configuration
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Secret redaction configuration
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// Log level of EchoVault crates (error, warn, info, debug, trace)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+
+    /// Language of tray labels, notifications and errors (default: system locale)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+
+    /// Named profiles
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Profile applied by [`Config::apply_profile`] (not stored)
+    #[serde(skip)]
+    pub active_profile: Option<ActiveProfile>,
+
+    /// Keys set by [`Config::apply_env_overrides`] (not stored)
+    #[serde(skip)]
+    pub env_overrides: Vec<EnvOverride>,
+
+    /// Keys of the loaded file that match no setting, dotted (not stored)
+    #[serde(skip)]
+    pub unknown_keys: Vec<String>,
+}
+
+/// Embedding provider preset.
+///
+/// Presets auto-fill api_base and model defaults so users only
+/// need to pick a provider and optionally enter an API key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingPreset {
+    /// Local Ollama (default, no API key needed)
+    #[default]
+    Ollama,
+    /// OpenAI API (requires API key)
+    OpenAI,
+    /// Any OpenAI-compatible endpoint (LiteLLM, vLLM, TGI, etc.)
+    Custom,
+}
+
+impl EmbeddingPreset {
+    /// Default API base URL for this preset.
+    pub fn default_api_base(&self) -> &'static str {
+        match self {
+            Self::Ollama => "http://localhost:11434/v1",
+            Self::OpenAI => "https://api.openai.com/v1",
+            Self::Custom => "http://localhost:8000/v1",
+        }
+    }
+
+    /// Default model name for this preset.
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            Self::Ollama => "nomic-embed-text",
+            Self::OpenAI => "text-embedding-3-small",
+            Self::Custom => "nomic-embed-text",
+        }
+    }
+
+    /// Whether this preset requires an API key.
+    pub fn requires_api_key(&self) -> bool {
+        matches!(self, Self::OpenAI)
+    }
+}
+
+/// Where embeddings are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingBackend {
+    /// OpenAI-compatible embedding API (`preset`, `api_base`)
+    #[default]
+    Api,
+    /// ONNX model run on this machine, downloaded on first use; `model`
+    /// is one of [`LOCAL_EMBEDDING_MODELS`]
+    Local,
+}
+
+/// Models of the local embedding backend.
+pub const LOCAL_EMBEDDING_MODELS: &[&str] = &[
+    "all-MiniLM-L6-v2",
+    "bge-small-en-v1.5",
+    "nomic-embed-text",
+    "multilingual-e5-small",
+];
+
+/// Where search results are reranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RerankBackend {
+    /// Rerank API (`/rerank` endpoint of Jina, Cohere-compatible proxies, vLLM)
+    #[default]
+    Api,
+    /// Cross-encoder run on this machine, downloaded on first use; `model`
+    /// is one of [`LOCAL_RERANK_MODELS`]
+    Local,
+}
+
+/// Models of the local rerank backend.
+pub const LOCAL_RERANK_MODELS: &[&str] = &[
+    "bge-reranker-base",
+    "bge-reranker-v2-m3",
+    "jina-reranker-v1-turbo-en",
+    "jina-reranker-v2-base-multilingual",
+];
+
+/// Reranking of semantic search results (`[embedding.rerank]` section).
+///
+/// The best hybrid results are scored again by a cross-encoder, which reads
+/// the query and each result together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankConfig {
+    /// Rerank search results
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Rerank API, or a model run locally
+    #[serde(default)]
+    pub backend: RerankBackend,
+
+    /// Base URL of the rerank API (default: `embedding.api_base`). Requests
+    /// carry the embedding API key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base: Option<String>,
+
+    /// Reranker model
+    #[serde(default = "default_rerank_model")]
+    pub model: String,
+
+    /// Hybrid results reranked per search
+    #[serde(default = "default_rerank_candidates")]
+    pub candidates: usize,
+}
+
+fn default_rerank_model() -> String {
+    "bge-reranker-base".to_string()
+}
+
+fn default_rerank_candidates() -> usize {
+    50
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: RerankBackend::default(),
+            api_base: None,
+            model: default_rerank_model(),
+            candidates: default_rerank_candidates(),
+        }
+    }
+}
+
+/// Embedding configuration in TOML.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingConfigToml {
+    /// Embedding API, or a model run locally
+    #[serde(default)]
+    pub backend: EmbeddingBackend,
+
+    /// Provider preset (ollama, openai, custom)
+    #[serde(default)]
+    pub preset: EmbeddingPreset,
+
+    /// API base URL (e.g., "http://localhost:11434/v1" for Ollama)
+    #[serde(default = "default_embedding_api_base")]
+    pub api_base: String,
+
+    /// Optional API key. Kept in the OS keyring; only read here from older
+    /// configs (moved on startup by `secrets::migrate_default_config`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    /// Model name (e.g., "nomic-embed-text")
+    #[serde(default = "default_embedding_model")]
+    pub model: String,
+
+    /// Target characters per chunk
+    #[serde(default = "default_embedding_chunk_size")]
+    pub chunk_size: usize,
+
+    /// Overlap characters between chunks
+    #[serde(default = "default_embedding_chunk_overlap")]
+    pub chunk_overlap: usize,
+
+    /// Batch size for API calls
+    #[serde(default = "default_embedding_batch_size")]
+    pub batch_size: usize,
+
+    /// Batch requests in flight at once
+    #[serde(default = "default_embedding_concurrency")]
+    pub concurrency: usize,
+
+    /// Timeout of one API request, in seconds
+    #[serde(default = "default_embedding_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// Retries of a request rejected with 429/5xx or timed out
+    #[serde(default = "default_embedding_max_retries")]
+    pub max_retries: u32,
+
+    /// Reranking of search results
+    #[serde(default)]
+    pub rerank: RerankConfig,
+}
+
+fn default_embedding_api_base() -> String {
+    "http://localhost:11434/v1".to_string()
+}
+
+fn default_embedding_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+fn default_embedding_chunk_size() -> usize {
+    1000
+}
+
+fn default_embedding_chunk_overlap() -> usize {
+    200
+}
+
+fn default_embedding_batch_size() -> usize {
+    32
+}
+
+fn default_embedding_concurrency() -> usize {
+    4
+}
+
+fn default_embedding_request_timeout_secs() -> u64 {
+    60
+}
+
+fn default_embedding_max_retries() -> u32 {
+    5
+}
+
+impl Default for EmbeddingConfigToml {
+    fn default() -> Self {
+        let preset = EmbeddingPreset::default();
+        Self {
+            backend: EmbeddingBackend::default(),
+            preset,
+            api_base: preset.default_api_base().to_string(),
+            api_key: None,
+            model: preset.default_model().to_string(),
+            chunk_size: default_embedding_chunk_size(),
+            chunk_overlap: default_embedding_chunk_overlap(),
+            batch_size: default_embedding_batch_size(),
+            concurrency: default_embedding_concurrency(),
+            request_timeout_secs: default_embedding_request_timeout_secs(),
+            max_retries: default_embedding_max_retries(),
+            rerank: RerankConfig::default(),
+        }
+    }
+}
+
+impl EmbeddingConfigToml {
+    /// API key from the config, falling back to the OS keyring.
+    pub fn resolved_api_key(&self) -> Option<SecretString> {
+        if let Some(key) = self.api_key.as_ref().filter(|k| !k.is_empty()) {
+            return Some(key.as_str().into());
+        }
+        if self.preset == EmbeddingPreset::Ollama {
+            return None;
+        }
+        crate::secrets::get_or_warn(crate::secrets::EMBEDDING_API_KEY)
+    }
+
+    /// Create config from a preset, applying its defaults.
+    pub fn from_preset(preset: EmbeddingPreset) -> Self {
+        Self {
+            preset,
+            api_base: preset.default_api_base().to_string(),
+            api_key: None,
+            model: preset.default_model().to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+fn default_version() -> u32 {
+    CONFIG_VERSION
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: default_version(),
+            setup_complete: false,
+            vault_path: default_vault_path(),
+            sync: SyncConfig::default(),
+            extractors: ExtractorsConfig::default(),
+            sources: BTreeMap::new(),
+            export_path: None,
+            export_format: MarkdownFlavor::default(),
+            embedding: EmbeddingConfigToml::default(),
+            parse: ParseConfig::default(),
+            watcher: WatcherConfig::default(),
+            schedule: ScheduleConfig::default(),
+            metrics: MetricsConfig::default(),
+            analytics: AnalyticsConfig::default(),
+            obsidian: ObsidianConfig::default(),
+            digest: DigestConfig::default(),
+            notifications: NotificationsConfig::default(),
+            redaction: RedactionConfig::default(),
+            encryption: EncryptionConfig::default(),
+            log_level: None,
+            language: None,
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            env_overrides: Vec::new(),
+            unknown_keys: Vec::new(),
+        }
+    }
+}
+
+/// Get default vault path.
+pub fn default_vault_path() -> PathBuf {
+    dirs::data_dir()
+        .map(|d| d.join("echovault").join("vault"))
+        .unwrap_or_else(|| PathBuf::from("./vault"))
+}
+
+/// Get default export path (`exports` next to the default vault).
+pub fn default_export_path() -> PathBuf {
+    default_vault_path()
+        .parent()
+        .map(|p| p.join("exports"))
+        .unwrap_or_else(|| PathBuf::from("./exports"))
+}
+
+/// Get default vault path of a profile.
+pub fn default_profile_vault_path(profile: &str) -> PathBuf {
+    dirs::data_dir()
+        .map(|d| d.join("echovault"))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("profiles")
+        .join(profile)
+        .join("vault")
+}
+
+/// Config key set by an `ECHOVAULT_*` variable, if any.
+fn env_key(var: &str) -> Option<String> {
+    let name = var.strip_prefix(ENV_PREFIX)?;
+    if name.is_empty() || ENV_RESERVED.contains(&name) {
+        return None;
+    }
+    if let Some((_, key)) = ENV_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Some(key.to_string());
+    }
+    Some(name.to_lowercase().replace("__", "."))
+}
+
+/// Ways to read an override, most specific first: a TOML value (`true`,
+/// `30`, `["a"]`), the text itself, then a `a,b,c` list.
+fn env_value_candidates(raw: &str) -> Vec<toml::Value> {
+    let mut candidates: Vec<toml::Value> = toml::from_str::<toml::Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .into_iter()
+        .collect();
+    candidates.push(toml::Value::String(raw.to_string()));
+    if !raw.trim_start().starts_with('[') {
+        candidates.push(toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| toml::Value::String(s.to_string()))
+                .collect(),
+        ));
+    }
+    candidates
+}
+
+/// Set a dotted key from text, typed after the config field it sets: the
+/// first of [`env_value_candidates`] the config deserializes with, so `30`
+/// stays text for a string field and `a,b` becomes a list for a list field.
+///
+/// Returns the updated TOML value and the config read from it; when no
+/// reading fits, the first one is kept and the error returned.
+fn set_typed(value: &mut toml::Value, key: &str, raw: &str) -> Result<Config> {
+    let mut first = None;
+    for candidate in env_value_candidates(raw) {
+        let mut typed = value.clone();
+        set_key(&mut typed, key, Some(candidate))?;
+        match typed.clone().try_into::<Config>() {
+            Ok(config) => {
+                *value = typed;
+                return Ok(config);
+            }
+            Err(e) => {
+                first.get_or_insert((typed, e));
+            }
+        }
+    }
+    let (typed, e) = first.context("No value to set")?;
+    *value = typed;
+    Err(e.into())
+}
+
+fn get_key<'a>(value: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.')
+        .try_fold(value, |value, part| value.as_table()?.get(part))
+}
+
+/// Set (or remove, with `None`) a dotted key, creating missing tables.
+fn set_key(value: &mut toml::Value, key: &str, new: Option<toml::Value>) -> Result<()> {
+    let (parents, last) = match key.rsplit_once('.') {
+        Some((parents, last)) => (Some(parents), last),
+        None => (None, key),
+    };
+    let mut table = value.as_table_mut().context("Config is not a TOML table")?;
+    for part in parents.into_iter().flat_map(|p| p.split('.')) {
+        table = table
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("Config key '{}' is not a table", part))?;
+    }
+    match new {
+        Some(new) => table.insert(last.to_string(), new),
+        None => table.remove(last),
+    };
+    Ok(())
+}
+
+/// Collect the keys of a config file missing from the parsed config, i.e.
+/// dropped by serde. Empty sections and lists are never serialized, so they
+/// are not reported.
+fn collect_unknown_keys(
+    file: &toml::Table,
+    parsed: &toml::Table,
+    prefix: &str,
+    unknown: &mut Vec<String>,
+) {
+    for (name, value) in file {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match (value, parsed.get(name)) {
+            (toml::Value::Table(table), Some(toml::Value::Table(parsed))) => {
+                collect_unknown_keys(table, parsed, &key, unknown)
+            }
+            (toml::Value::Array(items), Some(toml::Value::Array(parsed))) => {
+                for (i, (item, parsed)) in items.iter().zip(parsed).enumerate() {
+                    if let (toml::Value::Table(table), toml::Value::Table(parsed)) = (item, parsed)
+                    {
+                        collect_unknown_keys(table, parsed, &format!("{}[{}]", key, i), unknown);
+                    }
+                }
+            }
+            (_, Some(_)) => {}
+            (toml::Value::Table(table), None) if table.is_empty() => {}
+            (toml::Value::Array(items), None) if items.is_empty() => {}
+            (_, None) => unknown.push(key),
+        }
+    }
+}
+
+/// Reason an API base URL is unusable, if any.
+fn url_problem(url: &str) -> Option<&'static str> {
+    let Some(rest) = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+    else {
+        return Some("must start with http:// or https://");
+    };
+    if url.chars().any(char::is_whitespace) {
+        return Some("contains whitespace");
+    }
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.is_empty() || host.starts_with(':') {
+        return Some("has no host");
+    }
+    if let Some((_, port)) = host.rsplit_once(':').filter(|_| !host.ends_with(']')) {
+        if port.parse::<u16>().is_err() {
+            return Some("has an invalid port");
+        }
+    }
+    None
+}
+
+/// Copy values of `new` into `doc`, keeping the comments and layout of `doc`.
+///
+/// Keys missing from `new` are removed; new keys and tables are appended.
+fn merge_toml(doc: &mut toml_edit::Table, new: &toml_edit::Table) {
+    let stale: Vec<String> = doc
+        .iter()
+        .filter(|(key, _)| !new.contains_key(key))
+        .map(|(key, _)| key.to_string())
+        .collect();
+    for key in stale {
+        doc.remove(&key);
+    }
+
+    for (key, item) in new.iter() {
+        match (doc.get_mut(key), item) {
+            (Some(toml_edit::Item::Table(existing)), toml_edit::Item::Table(table)) => {
+                merge_toml(existing, table)
+            }
+            (Some(toml_edit::Item::Value(existing)), toml_edit::Item::Value(value)) => {
+                let decor = existing.decor().clone();
+                *existing = value.clone();
+                *existing.decor_mut() = decor;
+            }
+            _ => {
+                doc.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// Number tables in document order so appended tables follow their parent.
+fn renumber_tables(table: &mut toml_edit::Table, next: &mut usize) {
+    for (_, item) in table.iter_mut() {
+        if let toml_edit::Item::Table(sub) = item {
+            sub.set_position(*next);
+            *next += 1;
+            renumber_tables(sub, next);
+        }
+    }
+}
+
+/// Get default config directory (~/.config/echovault/).
+pub fn default_config_dir() -> PathBuf {
+    dirs::config_dir()
+        .map(|d| d.join("echovault"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Get default config file path: the config file of the active profile when
+/// it is listed in `profiles.toml`, otherwise [`main_config_path`].
+pub fn default_config_path() -> PathBuf {
+    active_profile()
+        .and_then(|name| ProfilesIndex::load_default().ok()?.config_path(&name))
+        .unwrap_or_else(main_config_path)
+}
+
+/// Get the path of echovault.toml, the config of no profile and of the
+/// `[profiles.<name>]` sections.
+pub fn main_config_path() -> PathBuf {
+    default_config_dir().join("echovault.toml")
+}
+
+#[allow(dead_code)]
+impl Config {
+    /// Create new config with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create config with specific vault path.
+    pub fn with_vault_path(vault_path: PathBuf) -> Self {
+        Self {
+            vault_path,
+            ..Self::default()
+        }
+    }
+
+    /// Defaults of a profile without a config file yet: its own vault (see
+    /// [`default_profile_vault_path`]) and remote folder (`EchoVault-<name>`).
+    pub fn for_profile(name: &str) -> Self {
+        let mut config = Self::with_vault_path(default_profile_vault_path(name));
+        config.sync.folder_name = format!("{}-{}", config.sync.folder_name, name);
+        config
+    }
+
+    /// Directory session exports are written to.
+    pub fn export_dir(&self) -> PathBuf {
+        self.export_path.clone().unwrap_or_else(default_export_path)
+    }
+
+    /// Language of user-facing messages: `language`, else the system locale
+    /// (`LC_ALL`, `LC_MESSAGES`, `LANG`), else English.
+    pub fn language(&self) -> Language {
+        self.language
+            .or_else(|| {
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .iter()
+                    .filter_map(|var| std::env::var(var).ok())
+                    .find(|locale| !locale.is_empty())
+                    .and_then(|locale| Language::from_locale(&locale))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Load config from file, migrating older formats.
+    ///
+    /// A migrated config is written back after the original is copied to
+    /// `<file>.v<old version>.bak`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read config file: {}", path.display()))?;
+
+        let mut table: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Cannot parse config file: {}", path.display()))?;
+        let migration = migrate::migrate(&mut table)
+            .with_context(|| format!("Cannot migrate config file: {}", path.display()))?;
+
+        let mut config: Config = toml::Value::Table(table.clone())
+            .try_into()
+            .with_context(|| format!("Cannot parse config file: {}", path.display()))?;
+        // Misspelled keys would otherwise fall back to their default unnoticed
+        if let toml::Value::Table(parsed) =
+            toml::Value::try_from(&config).context("Cannot serialize config")?
+        {
+            collect_unknown_keys(&table, &parsed, "", &mut config.unknown_keys);
+        }
+
+        if let Some(migration) = migration {
+            let backup = migrate::backup_path(path, migration.from);
+            let written = std::fs::copy(path, &backup)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| config.save(path));
+            match written {
+                Ok(()) => tracing::info!(
+                    "Migrated {} from version {} to {} (original saved as {})",
+                    path.display(),
+                    migration.from,
+                    CONFIG_VERSION,
+                    backup.display()
+                ),
+                Err(e) => {
+                    tracing::warn!("Cannot write migrated config {}: {:#}", path.display(), e)
+                }
+            }
+            for note in &migration.notes {
+                tracing::info!("Config migration: {}", note);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load config from default path, with the active profile and
+    /// `ECHOVAULT_*` overrides applied.
+    ///
+    /// A profile listed in `profiles.toml` is loaded from its own config file
+    /// (see [`Self::for_profile`] when it does not exist yet); any other
+    /// profile is applied from its `[profiles.<name>]` section of
+    /// echovault.toml. Also applies the process-wide parser message size limit.
+    pub fn load_default() -> Result<Self> {
+        let profile = active_profile();
+        if let Some(name) = &profile {
+            profiles::check_profile_name(name)?;
+        }
+        let own_file = match &profile {
+            Some(name) => ProfilesIndex::load_default()?.config_path(name),
+            None => None,
+        };
+        let mut config = match (&profile, own_file) {
+            (Some(name), Some(path)) => {
+                let mut config = if path.exists() {
+                    Self::load(&path)?
+                } else {
+                    Self::for_profile(name)
+                };
+                config.active_profile = Some(ActiveProfile {
+                    name: name.clone(),
+                    base: None,
+                });
+                config
+            }
+            _ => {
+                let path = main_config_path();
+                let mut config = if path.exists() {
+                    Self::load(&path)?
+                } else {
+                    Self::default()
+                };
+                if let Some(name) = &profile {
+                    config.apply_profile(name)?;
+                }
+                config
+            }
+        };
+        config.apply_env_overrides()?;
+        crate::parsers::set_max_message_bytes(
+            config.parse.max_message_mb.saturating_mul(1024 * 1024) as usize,
+        );
+        Ok(config)
+    }
+
+    /// Apply `ECHOVAULT_*` environment variables (see [`ENV_PREFIX`]).
+    ///
+    /// Overridden keys keep their file value when the config is saved.
+    pub fn apply_env_overrides(&mut self) -> Result<()> {
+        self.apply_overrides(std::env::vars())
+    }
+
+    pub(crate) fn apply_overrides(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<()> {
+        let mut value = toml::Value::try_from(&*self).context("Cannot serialize config")?;
+        let mut applied = Vec::new();
+        for (var, raw) in vars {
+            let Some(key) = env_key(&var) else {
+                continue;
+            };
+            let original = get_key(&value, &key).cloned();
+            // An invalid value fails below, naming its variable
+            let _ = set_typed(&mut value, &key, &raw);
+            applied.push(EnvOverride { var, key, original });
+        }
+        if applied.is_empty() {
+            return Ok(());
+        }
+
+        let names: Vec<&str> = applied.iter().map(|o| o.var.as_str()).collect();
+        let mut config: Config = value
+            .try_into()
+            .with_context(|| format!("Invalid value in {}", names.join(", ")))?;
+
+        // Keys that match no field are dropped by serde; report them
+        let check = toml::Value::try_from(&config).context("Cannot serialize config")?;
+        applied.retain(|o| {
+            let known = get_key(&check, &o.key).is_some();
+            if !known {
+                tracing::warn!("{} does not match a config key, ignored", o.var);
+            }
+            known
+        });
+
+        config.active_profile = self.active_profile.take();
+        config.env_overrides = std::mem::take(&mut self.env_overrides);
+        config.env_overrides.extend(applied);
+        config.unknown_keys = std::mem::take(&mut self.unknown_keys);
+        *self = config;
+        Ok(())
+    }
+
+    /// Value of a dotted key (`sync.remote_name`), `None` when unset.
+    pub fn get(&self, key: &str) -> Result<Option<toml::Value>> {
+        let value = toml::Value::try_from(self).context("Cannot serialize config")?;
+        Ok(get_key(&value, key).cloned())
+    }
+
+    /// Set a dotted key from text, typed after the field it sets like
+    /// `ECHOVAULT_*` overrides; `None` resets the key to its default.
+    ///
+    /// Fails for unknown keys, whole sections and values of the wrong type.
+    /// The result is not validated (see [`Self::validate`]).
+    pub fn set(&mut self, key: &str, raw: Option<&str>) -> Result<()> {
+        if let Some(env) = self.env_overrides.iter().find(|o| o.key == key) {
+            bail!("{} is set by {}; unset it first", key, env.var);
+        }
+        let mut value = toml::Value::try_from(&*self).context("Cannot serialize config")?;
+        if get_key(&value, key).is_some_and(toml::Value::is_table) {
+            bail!("'{}' is a section; set one of its keys", key);
+        }
+        let config = match raw {
+            Some(raw) => set_typed(&mut value, key, raw),
+            None => {
+                set_key(&mut value, key, None)?;
+                value.try_into().map_err(anyhow::Error::from)
+            }
+        };
+        let mut config = config.with_context(|| match raw {
+            Some(raw) => format!("Invalid value for {}: {}", key, raw),
+            None => format!("{} cannot be unset", key),
+        })?;
+
+        // Keys that match no field are dropped by serde
+        if raw.is_some() && config.get(key)?.is_none() {
+            bail!("Unknown config key: {}", key);
+        }
+
+        config.active_profile = self.active_profile.take();
+        config.env_overrides = std::mem::take(&mut self.env_overrides);
+        config.unknown_keys = std::mem::take(&mut self.unknown_keys);
+        config.unknown_keys.retain(|k| k != key);
+        *self = config;
+        Ok(())
+    }
+
+    /// Replace the vault, remote and sources with those of a profile.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        profiles::check_profile_name(name)?;
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            bail!(
+                "Unknown profile '{}' (available: {})",
+                name,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            );
+        };
+
+        let base = ProfileConfig {
+            vault_path: Some(self.vault_path.clone()),
+            sync: Some(self.sync.clone()),
+            extractors: Some(self.extractors.clone()),
+        };
+        self.vault_path = profile
+            .vault_path
+            .unwrap_or_else(|| default_profile_vault_path(name));
+        self.sync = profile.sync.unwrap_or_else(|| SyncConfig {
+            folder_name: format!("{}-{}", self.sync.folder_name, name),
+            ..self.sync.clone()
+        });
+        if let Some(extractors) = profile.extractors {
+            self.extractors = extractors;
+        }
+        self.active_profile = Some(ActiveProfile {
+            name: name.to_string(),
+            base: Some(base),
+        });
+        Ok(())
+    }
+
+    /// Name of the applied profile, if any.
+    pub fn profile_name(&self) -> Option<&str> {
+        self.active_profile.as_ref().map(|p| p.name.as_str())
+    }
+
+    /// The config as stored on disk: overridden keys get their file value
+    /// back and profile values go back to their section.
+    fn to_stored(&self) -> Result<Self> {
+        let mut stored = self.clone();
+        if !self.env_overrides.is_empty() {
+            let mut value = toml::Value::try_from(self).context("Cannot serialize config")?;
+            for o in self.env_overrides.iter().rev() {
+                set_key(&mut value, &o.key, o.original.clone())?;
+            }
+            stored = value.try_into().context("Cannot restore overridden keys")?;
+            stored.active_profile = self.active_profile.clone();
+        }
+        if let Some(ActiveProfile {
+            name,
+            base: Some(base),
+        }) = stored.active_profile.take()
+        {
+            stored.profiles.insert(
+                name,
+                ProfileConfig {
+                    vault_path: Some(stored.vault_path.clone()),
+                    sync: Some(stored.sync.clone()),
+                    extractors: Some(stored.extractors.clone()),
+                },
+            );
+            stored.vault_path = base.vault_path.unwrap_or_default();
+            stored.sync = base.sync.unwrap_or_default();
+            stored.extractors = base.extractors.unwrap_or_default();
+        }
+        Ok(stored)
+    }
+
+    /// Save config to file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        // Create parent directory if it doesn't exist
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let fresh: toml_edit::Document = toml::to_string_pretty(&self.to_stored()?)
+            .with_context(|| "Cannot serialize config to TOML")?
+            .parse()
+            .context("Cannot serialize config to TOML")?;
+
+        // Update the existing file (or the template) in place
+        let existing = std::fs::read_to_string(path).unwrap_or_default();
+        let mut doc = existing
+            .parse::<toml_edit::Document>()
+            .ok()
+            .filter(|_| !existing.trim().is_empty())
+            .unwrap_or_else(|| {
+                CONFIG_TEMPLATE
+                    .parse()
+                    .expect("config template is valid TOML")
+            });
+        merge_toml(doc.as_table_mut(), fresh.as_table());
+        renumber_tables(doc.as_table_mut(), &mut 0);
+        let content = doc.to_string();
+
+        std::fs::write(path, content)
+            .with_context(|| format!("Cannot write config file: {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Save config to default path.
+    pub fn save_default(&self) -> Result<PathBuf> {
+        let path = default_config_path();
+        self.save(&path)?;
+        Ok(path)
+    }
+
+    /// Effective settings of a source. A source left out of
+    /// `extractors.enabled_sources` is disabled as well.
+    pub fn source(&self, name: &str) -> SourceConfig {
+        let mut settings = self.sources.get(name).cloned().unwrap_or_default();
+        settings.enabled &= self.extractors.is_enabled(name);
+        settings
+    }
+
+    /// Replace the settings of a source. Enabling a source left out of
+    /// `extractors.enabled_sources` adds it there, and default settings
+    /// remove its `[sources.<name>]` section.
+    pub fn set_source(&mut self, name: &str, settings: SourceConfig) {
+        if settings.enabled && !self.extractors.is_enabled(name) {
+            self.extractors.enabled_sources.push(name.to_string());
+        }
+        if settings == SourceConfig::default() {
+            self.sources.remove(name);
+        } else {
+            self.sources.insert(name.to_string(), settings);
+        }
+    }
+
+    /// Check the config for mistakes, most severe first.
+    ///
+    /// Covers paths, URLs and option combinations that would otherwise fail
+    /// deep inside sync, extract or embed. Does not touch the network or keyring.
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        if self.config_version > CONFIG_VERSION {
+            issues.push(ConfigIssue::error(
+                "config_version",
+                format!(
+                    "written by a newer EchoVault (version {}, this build reads {})",
+                    self.config_version, CONFIG_VERSION
+                ),
+                "upgrade EchoVault on this machine",
+            ));
+        }
+
+        // Not an error: a typo or a key from a newer version must not stop
+        // every command (`config check` fails on them)
+        for key in &self.unknown_keys {
+            issues.push(ConfigIssue::warning(
+                key,
+                "is not a known setting and is ignored".to_string(),
+                "fix its name or remove it (a misspelled key leaves the setting at its default)",
+            ));
+        }
+
+        // Vault
+        let vault = &self.vault_path;
+        if vault.as_os_str().is_empty() {
+            issues.push(ConfigIssue::error(
+                "vault_path",
+                "is empty".to_string(),
+                "set vault_path to the vault directory",
+            ));
+        } else if !vault.is_absolute() {
+            issues.push(ConfigIssue::warning(
+                "vault_path",
+                format!("{} is relative to the working directory", vault.display()),
+                "use an absolute path",
+            ));
+        } else if vault.exists() && !vault.is_dir() {
+            issues.push(ConfigIssue::error(
+                "vault_path",
+                format!("{} is a file, not a directory", vault.display()),
+                "point vault_path at a directory",
+            ));
+        } else if !vault.exists() && self.setup_complete {
+            issues.push(ConfigIssue::warning(
+                "vault_path",
+                format!("{} does not exist", vault.display()),
+                "it is created by the next extract; fix the path if the vault was moved",
+            ));
+        }
+
+        if let Some(export) = &self.export_path {
+            if export.as_os_str().is_empty() {
+                issues.push(ConfigIssue::error(
+                    "export_path",
+                    "is empty".to_string(),
+                    "remove export_path to use the default, or set a directory",
+                ));
+            } else if !export.is_absolute() {
+                issues.push(ConfigIssue::warning(
+                    "export_path",
+                    format!("{} is relative to the working directory", export.display()),
+                    "use an absolute path",
+                ));
+            } else if export.exists() && !export.is_dir() {
+                issues.push(ConfigIssue::error(
+                    "export_path",
+                    format!("{} is a file, not a directory", export.display()),
+                    "point export_path at a directory",
+                ));
+            }
+        }
+
+        // Sync
+        if let Some(remote) = &self.sync.remote_name {
+            if remote.is_empty() || remote.contains(':') || remote.contains('/') {
+                issues.push(ConfigIssue::error(
+                    "sync.remote_name",
+                    format!("'{}' is not an rclone remote name", remote),
+                    "use a name from `rclone listremotes`, without the trailing colon",
+                ));
+            }
+        }
+        match self.sync.provider {
+            SyncProviderKind::Local => {
+                validate_local_path("sync", self.sync.local_path.as_deref(), &mut issues);
+            }
+            SyncProviderKind::Unknown => issues.push(unknown_provider_issue("sync.provider")),
+            SyncProviderKind::Rclone => {}
+        }
+        for (name, target) in &self.sync.targets {
+            let key = format!("sync.targets.{}", name);
+            if name == DEFAULT_SYNC_TARGET
+                || name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                issues.push(ConfigIssue::error(
+                    &key,
+                    format!("'{}' is not a valid sync target name", name),
+                    "use letters, digits, '-' and '_' only (\"default\" is [sync] itself)",
+                ));
+            }
+            match target.provider {
+                SyncProviderKind::Rclone if target.remote_name.is_none() => {
+                    issues.push(ConfigIssue::error(
+                        &format!("{}.remote_name", key),
+                        "is not set".to_string(),
+                        "use a name from `rclone listremotes`",
+                    ));
+                }
+                SyncProviderKind::Rclone => {}
+                SyncProviderKind::Local => {
+                    validate_local_path(&key, target.local_path.as_deref(), &mut issues);
+                }
+                SyncProviderKind::Unknown => {
+                    issues.push(unknown_provider_issue(&format!("{}.provider", key)));
+                }
+            }
+        }
+        let targets = self.sync.target_names();
+        for (i, rule) in self.sync.workspaces.iter().enumerate() {
+            let key = format!("sync.workspaces[{}]", i);
+            if let Err(e) = crate::sync::partition::workspace_glob(&rule.workspace) {
+                issues.push(ConfigIssue::error(
+                    &format!("{}.workspace", key),
+                    format!("{:#}", e),
+                    "fix the glob pattern (e.g. \"acme-*\")",
+                ));
+            }
+            if rule.targets.is_empty() {
+                issues.push(ConfigIssue::error(
+                    &format!("{}.targets", key),
+                    "is empty".to_string(),
+                    "list the sync targets of these workspaces",
+                ));
+            }
+            for target in &rule.targets {
+                if !targets.contains(&target.as_str()) {
+                    issues.push(ConfigIssue::error(
+                        &format!("{}.targets", key),
+                        format!("'{}' is not a sync target", target),
+                        "use \"default\" or a [sync.targets.<name>] name",
+                    ));
+                }
+            }
+        }
+        for destination in &self.redaction.destinations {
+            if destination != EXPORT_DESTINATION && !targets.contains(&destination.as_str()) {
+                issues.push(ConfigIssue::warning(
+                    "redaction.destinations",
+                    format!("'{}' is neither a sync target nor \"export\"", destination),
+                    "use \"export\", \"default\" or a [sync.targets.<name>] name",
+                ));
+            }
+        }
+        let folder = &self.sync.folder_name;
+        if folder.starts_with('/') || folder.split(['/', '\\']).any(|part| part == "..") {
+            issues.push(ConfigIssue::error(
+                "sync.folder_name",
+                format!("'{}' must be a folder inside the remote", folder),
+                "use a relative folder such as \"EchoVault\"",
+            ));
+        }
+        if self.sync.transfers == 0 {
+            issues.push(ConfigIssue::error(
+                "sync.transfers",
+                "must be greater than 0".to_string(),
+                "the default is 8 (lower it on slow or metered connections)",
+            ));
+        }
+        if self.sync.checkers == 0 {
+            issues.push(ConfigIssue::error(
+                "sync.checkers",
+                "must be greater than 0".to_string(),
+                "the default is 16",
+            ));
+        }
+        if self.sync.bwlimit_kib == Some(0) {
+            issues.push(ConfigIssue::error(
+                "sync.bwlimit_kib",
+                "must be greater than 0".to_string(),
+                "remove it for unlimited bandwidth",
+            ));
+        }
+        if self.sync.timeout_secs == Some(0) {
+            issues.push(ConfigIssue::error(
+                "sync.timeout_secs",
+                "must be greater than 0".to_string(),
+                "remove it for rclone's default of 300 seconds",
+            ));
+        }
+
+        // Analytics
+        for (model, price) in &self.analytics.prices {
+            if !(price.input >= 0.0 && price.output >= 0.0) {
+                issues.push(ConfigIssue::error(
+                    &format!("analytics.prices.{}", model),
+                    "prices must be 0 or more".to_string(),
+                    "set input and output in USD per million tokens",
+                ));
+            }
+        }
+
+        // Obsidian
+        if let Some(vault) = &self.obsidian.vault_path {
+            if vault.exists() && !vault.is_dir() {
+                issues.push(ConfigIssue::error(
+                    "obsidian.vault_path",
+                    format!("{} is a file, not a directory", vault.display()),
+                    "point vault_path at the Obsidian vault folder",
+                ));
+            } else if !vault.exists() {
+                issues.push(ConfigIssue::warning(
+                    "obsidian.vault_path",
+                    format!("{} does not exist", vault.display()),
+                    "it is created on the next parse; fix the path if the vault was moved",
+                ));
+            }
+        }
+        let folder = &self.obsidian.folder;
+        if Path::new(folder).is_absolute() || folder.split(['/', '\\']).any(|part| part == "..") {
+            issues.push(ConfigIssue::error(
+                "obsidian.folder",
+                format!("'{}' must be a folder inside the Obsidian vault", folder),
+                "use a relative folder such as \"EchoVault\"",
+            ));
+        }
+        if let Some(template) = &self.obsidian.template {
+            match std::fs::read_to_string(template) {
+                Ok(text) => {
+                    if let Err(e) = crate::parsers::obsidian::check_template(&text) {
+                        issues.push(ConfigIssue::error(
+                            "obsidian.template",
+                            format!("{:#}", e),
+                            "see the template syntax in the EchoVault docs",
+                        ));
+                    }
+                }
+                Err(e) => issues.push(ConfigIssue::error(
+                    "obsidian.template",
+                    format!("cannot read {}: {}", template.display(), e),
+                    "fix the path or remove it to use the built-in template",
+                )),
+            }
+        }
+
+        // Digest
+        if let Some(api_base) = &self.digest.chat_api_base {
+            if let Some(problem) = url_problem(api_base) {
+                issues.push(ConfigIssue::error(
+                    "digest.chat_api_base",
+                    format!("'{}' {}", api_base, problem),
+                    "use the endpoint URL, e.g. \"https://api.openai.com/v1\"",
+                ));
+            }
+        }
+        if self.digest.notable == 0 {
+            issues.push(ConfigIssue::warning(
+                "digest.notable",
+                "0 lists no conversations in digests".to_string(),
+                "the default is 10",
+            ));
+        }
+
+        // Notifications
+        for webhook in &self.notifications.webhooks {
+            if let Some(problem) = url_problem(&webhook.url) {
+                issues.push(ConfigIssue::error(
+                    "notifications.webhooks",
+                    format!("'{}' {}", webhook.url, problem),
+                    "use the webhook URL, e.g. \"https://discord.com/api/webhooks/...\"",
+                ));
+            }
+        }
+
+        // Sources
+        let known: Vec<&'static str> = crate::extractors::all_extractors()
+            .iter()
+            .map(|e| e.source_name())
+            .collect();
+        let known_hint = format!("known sources: {}", known.join(", "));
+        for source in &self.extractors.enabled_sources {
+            if !known.contains(&source.as_str()) {
+                issues.push(ConfigIssue::warning(
+                    "extractors.enabled_sources",
+                    format!("unknown source '{}'", source),
+                    &known_hint,
+                ));
+            }
+        }
+        for (source, watch) in &self.watcher.sources {
+            let key = format!("watcher.sources.{}", source);
+            if !known.contains(&source.as_str()) {
+                issues.push(ConfigIssue::warning(
+                    &key,
+                    format!("unknown source '{}'", source),
+                    &known_hint,
+                ));
+            }
+            if let Err(e) = crate::watcher::build_ignore_set(&watch.ignore) {
+                issues.push(ConfigIssue::error(
+                    &format!("{}.ignore", key),
+                    format!("{:#}", e),
+                    "fix the glob pattern (e.g. \"**/state.vscdb\")",
+                ));
+            }
+        }
+
+        for (source, settings) in &self.sources {
+            let key = format!("sources.{}", source);
+            if !known.contains(&source.as_str()) {
+                issues.push(ConfigIssue::warning(
+                    &key,
+                    format!("unknown source '{}'", source),
+                    &known_hint,
+                ));
+            }
+            if let Err(e) = crate::watcher::build_ignore_set(&settings.exclude) {
+                issues.push(ConfigIssue::error(
+                    &format!("{}.exclude", key),
+                    format!("{:#}", e),
+                    "fix the glob pattern (e.g. \"**/scratch-*\")",
+                ));
+            }
+            for path in settings.extra_paths.iter().filter(|p| !p.is_dir()) {
+                issues.push(ConfigIssue::warning(
+                    &format!("{}.extra_paths", key),
+                    format!("{} is not a directory", path.display()),
+                    "fix or remove the path",
+                ));
+            }
+            if settings.max_file_size_mb == Some(0) {
+                issues.push(ConfigIssue::warning(
+                    &format!("{}.max_file_size_mb", key),
+                    "0 skips every session file".to_string(),
+                    "remove the limit or set it in MB",
+                ));
+            }
+        }
+
+        // Embedding
+        let embedding = &self.embedding;
+        if let Some(problem) = url_problem(&embedding.api_base) {
+            issues.push(ConfigIssue::error(
+                "embedding.api_base",
+                format!("'{}' {}", embedding.api_base, problem),
+                &format!(
+                    "use the endpoint URL, e.g. \"{}\"",
+                    embedding.preset.default_api_base()
+                ),
+            ));
+        }
+        if embedding.model.trim().is_empty() {
+            issues.push(ConfigIssue::error(
+                "embedding.model",
+                "is empty".to_string(),
+                &format!(
+                    "set a model name, e.g. \"{}\"",
+                    embedding.preset.default_model()
+                ),
+            ));
+        } else if embedding.backend == EmbeddingBackend::Local
+            && !LOCAL_EMBEDDING_MODELS.contains(&embedding.model.as_str())
+        {
+            issues.push(ConfigIssue::error(
+                "embedding.model",
+                format!("'{}' is not a local model", embedding.model),
+                &format!("use one of {}", LOCAL_EMBEDDING_MODELS.join(", ")),
+            ));
+        }
+        if embedding.chunk_size == 0 {
+            issues.push(ConfigIssue::error(
+                "embedding.chunk_size",
+                "must be greater than 0".to_string(),
+                "the default is 1000 characters",
+            ));
+        } else if embedding.chunk_overlap >= embedding.chunk_size {
+            issues.push(ConfigIssue::error(
+                "embedding.chunk_overlap",
+                format!(
+                    "{} is not smaller than chunk_size ({})",
+                    embedding.chunk_overlap, embedding.chunk_size
+                ),
+                "use an overlap of about a fifth of chunk_size",
+            ));
+        }
+        if embedding.batch_size == 0 {
+            issues.push(ConfigIssue::error(
+                "embedding.batch_size",
+                "must be greater than 0".to_string(),
+                "the default is 32",
+            ));
+        }
+        if embedding.concurrency == 0 {
+            issues.push(ConfigIssue::error(
+                "embedding.concurrency",
+                "must be greater than 0".to_string(),
+                "the default is 4 (use 1 for local providers that serialize requests)",
+            ));
+        }
+        if embedding.request_timeout_secs == 0 {
+            issues.push(ConfigIssue::error(
+                "embedding.request_timeout_secs",
+                "must be greater than 0".to_string(),
+                "the default is 60 seconds",
+            ));
+        }
+        let rerank = &embedding.rerank;
+        if rerank.enabled {
+            if let Some(api_base) = &rerank.api_base {
+                if let Some(problem) = url_problem(api_base) {
+                    issues.push(ConfigIssue::error(
+                        "embedding.rerank.api_base",
+                        format!("'{}' {}", api_base, problem),
+                        "use the endpoint URL, or remove it to use embedding.api_base",
+                    ));
+                }
+            }
+            if rerank.backend == RerankBackend::Local
+                && !LOCAL_RERANK_MODELS.contains(&rerank.model.as_str())
+            {
+                issues.push(ConfigIssue::error(
+                    "embedding.rerank.model",
+                    format!("'{}' is not a local model", rerank.model),
+                    &format!("use one of {}", LOCAL_RERANK_MODELS.join(", ")),
+                ));
+            }
+            if rerank.candidates == 0 {
+                issues.push(ConfigIssue::error(
+                    "embedding.rerank.candidates",
+                    "must be greater than 0".to_string(),
+                    "the default is 50",
+                ));
+            }
+        }
+
+        if let Some(level) = &self.log_level {
+            if level.parse::<tracing::Level>().is_err() {
+                issues.push(ConfigIssue::warning(
+                    "log_level",
+                    format!("unknown level '{}', using info", level),
+                    "use error, warn, info, debug or trace",
+                ));
+            }
+        }
+
+        if let Some(identity) = &self.encryption.identity_file {
+            if !identity.exists() {
+                issues.push(ConfigIssue::warning(
+                    "encryption.identity_file",
+                    format!("{} does not exist", identity.display()),
+                    "fix the path, or remove it to use the default identity",
+                ));
+            }
+        }
+        let recipients = &self.encryption.recipients;
+        if !recipients.is_empty() {
+            if let Err(e) = crate::crypto::age_backend::validate_recipients(recipients) {
+                issues.push(ConfigIssue::error(
+                    "encryption.recipients",
+                    format!("{:#}", e),
+                    "use age1... public keys, and install the plugin of plugin recipients",
+                ));
+            }
+        }
+
+        self.validate_profiles(&mut issues);
+
+        issues.sort_by_key(|issue| !issue.is_error());
+        issues
+    }
+
+    /// Profiles must not share a vault or remote folder with each other or
+    /// with the top-level config.
+    fn validate_profiles(&self, issues: &mut Vec<ConfigIssue>) {
+        let Ok(stored) = self.to_stored() else {
+            return;
+        };
+        let mut targets = vec![(
+            "the default config".to_string(),
+            "vault_path".to_string(),
+            stored.vault_path.clone(),
+            stored.sync.clone(),
+        )];
+        for (name, profile) in &stored.profiles {
+            if !profiles::is_valid_profile_name(name) {
+                issues.push(ConfigIssue::error(
+                    &format!("profiles.{}", name),
+                    format!("'{}' is not a valid profile name", name),
+                    "use letters, digits, '-' and '_' only",
+                ));
+            }
+            let sync = profile.sync.clone().unwrap_or_else(|| SyncConfig {
+                folder_name: format!("{}-{}", stored.sync.folder_name, name),
+                ..stored.sync.clone()
+            });
+            targets.push((
+                format!("profile '{}'", name),
+                format!("profiles.{}", name),
+                profile
+                    .vault_path
+                    .clone()
+                    .unwrap_or_else(|| default_profile_vault_path(name)),
+                sync,
+            ));
+        }
+
+        for (i, (label, key, vault, sync)) in targets.iter().enumerate() {
+            for (other, _, other_vault, other_sync) in &targets[..i] {
+                if vault == other_vault {
+                    issues.push(ConfigIssue::error(
+                        &format!("{}.vault_path", key),
+                        format!("{} shares its vault with {}", label, other),
+                        "give each profile its own vault_path",
+                    ));
+                }
+                let same_target = match sync.provider {
+                    SyncProviderKind::Rclone => sync.remote_name == other_sync.remote_name,
+                    SyncProviderKind::Local => sync.local_path == other_sync.local_path,
+                    SyncProviderKind::Unknown => false,
+                };
+                if sync.provider == other_sync.provider
+                    && same_target
+                    && sync.folder_name == other_sync.folder_name
+                {
+                    issues.push(ConfigIssue::error(
+                        &format!("{}.sync", key),
+                        format!("{} syncs to the same remote folder as {}", label, other),
+                        "give each profile its own sync.folder_name",
+                    ));
+                }
+            }
+        }
+
+        // A profile with its own config file is never read from its section
+        if let Ok(index) = ProfilesIndex::load_default() {
+            for name in stored.profiles.keys() {
+                if let Some(path) = index.config_path(name) {
+                    issues.push(ConfigIssue::warning(
+                        &format!("profiles.{}", name),
+                        format!(
+                            "profile '{}' is listed in profiles.toml, so this section is ignored",
+                            name
+                        ),
+                        &format!("move its settings to {}", path.display()),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Check if config is initialized (has a remote, or a sync directory).
+    pub fn is_initialized(&self) -> bool {
+        match self.sync.provider {
+            SyncProviderKind::Rclone => self.sync.remote_name.is_some(),
+            SyncProviderKind::Local => self.sync.local_path.is_some(),
+            SyncProviderKind::Unknown => false,
+        }
+    }
+
+    /// Get vault directory path.
+    pub fn vault_dir(&self) -> &Path {
+        &self.vault_path
+    }
+
+    /// Get index database path.
+    pub fn index_db_path(&self) -> PathBuf {
+        self.vault_path.join("index.db")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+        assert_eq!(config.config_version, CONFIG_VERSION);
+        assert!(!config.is_initialized());
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("test.toml");
+
+        let mut config = Config::new();
+        config.sync.remote_name = Some("echovault".to_string());
+        config.save(&config_path)?;
+
+        let loaded = Config::load(&config_path)?;
+        assert!(loaded.is_initialized());
+        assert_eq!(loaded.sync.remote_name, Some("echovault".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_profiles_are_separated() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+        let mut config: Config = toml::from_str(
+            r#"
+            vault_path = "/data/personal"
+
+            [sync]
+            remote_name = "gdrive"
+
+            [profiles.acme]
+            vault_path = "/data/acme"
+
+            [profiles.acme.extractors]
+            enabled_sources = ["cursor"]
+
+            [profiles.globex]
+            "#,
+        )?;
+        assert!(config.apply_profile("initech").is_err());
+
+        // Profile names end up in paths
+        let mut escaping = config.clone();
+        escaping
+            .profiles
+            .insert("../escape".to_string(), ProfileConfig::default());
+        assert!(escaping.apply_profile("../escape").is_err());
+
+        config.apply_profile("acme")?;
+        assert_eq!(config.profile_name(), Some("acme"));
+        assert_eq!(config.vault_path, PathBuf::from("/data/acme"));
+        assert_eq!(config.sync.folder_name, "EchoVault-acme");
+        assert!(config.extractors.is_enabled("cursor"));
+        assert!(!config.extractors.is_enabled("zed"));
+
+        // Saving keeps the top-level values and writes changes into the profile
+        config.sync.folder_name = "Acme".to_string();
+        config.save(&config_path)?;
+        let mut loaded = Config::load(&config_path)?;
+        assert_eq!(loaded.vault_path, PathBuf::from("/data/personal"));
+        assert_eq!(loaded.sync.folder_name, "EchoVault");
+        assert!(loaded.extractors.is_enabled("zed"));
+        loaded.apply_profile("acme")?;
+        assert_eq!(loaded.sync.folder_name, "Acme");
+
+        // An empty profile still gets its own vault and remote folder
+        let mut globex = Config::load(&config_path)?;
+        globex.apply_profile("globex")?;
+        assert_eq!(globex.vault_path, default_profile_vault_path("globex"));
+        assert_eq!(globex.sync.folder_name, "EchoVault-globex");
+        assert_eq!(globex.sync.remote_name.as_deref(), Some("gdrive"));
+
+        // So does a new profile with its own config file
+        let initech = Config::for_profile("initech");
+        assert_eq!(initech.vault_path, default_profile_vault_path("initech"));
+        assert_eq!(initech.sync.folder_name, "EchoVault-initech");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_keys_and_set() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+config_version = 3
+vault_path = "/data/vault"
+
+[sync]
+remote = "gdrive"
+
+[[sync.workspaces]]
+workspace = "acme-*"
+targets = ["default"]
+target = "work"
+
+[sources]
+"#,
+        )?;
+        let mut config = Config::load(&config_path)?;
+        assert_eq!(
+            config.unknown_keys,
+            vec!["sync.remote", "sync.workspaces[0].target"]
+        );
+        assert!(config
+            .validate()
+            .iter()
+            .any(|issue| issue.key == "sync.remote" && !issue.is_error()));
+
+        config.set("sync.remote_name", Some("gdrive"))?;
+        assert_eq!(config.sync.remote_name.as_deref(), Some("gdrive"));
+        config.set("embedding.chunk_size", Some("800"))?;
+        assert_eq!(
+            config.get("embedding.chunk_size")?,
+            Some(toml::Value::Integer(800))
+        );
+        assert!(config.set("embedding.chunk_size", Some("big")).is_err());
+        assert!(config.set("sync.remte_name", Some("gdrive")).is_err());
+        assert!(config.set("sync", Some("gdrive")).is_err());
+        assert_eq!(config.embedding.chunk_size, 800);
+        // Lists left empty are not serialized but still take `a,b`
+        config.set("sources.cursor.exclude", Some("**/tmp-*,**/scratch-*"))?;
+        assert_eq!(
+            config.source("cursor").exclude,
+            vec!["**/tmp-*", "**/scratch-*"]
+        );
+
+        config.set("sync.remote_name", None)?;
+        assert_eq!(config.sync.remote_name, None);
+        assert_eq!(config.get("sync.remote_name")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_config_is_migrated() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+        let legacy = r#"# my vault
+vault_path = "/data/vault"
+
+[sync]
+provider = "github"
+repo_name = "echovault-vault"
+remote_name = "gdrive"
+
+[github]
+client_id = "abc"
+"#;
+        std::fs::write(&config_path, legacy)?;
+
+        let config = Config::load(&config_path)?;
+        assert_eq!(config.config_version, CONFIG_VERSION);
+        assert_eq!(config.vault_path, PathBuf::from("/data/vault"));
+        assert_eq!(config.sync.remote_name.as_deref(), Some("gdrive"));
+
+        // Original kept as a backup, migrated file keeps comments
+        let backup = temp_dir.path().join("echovault.toml.v1.bak");
+        assert_eq!(std::fs::read_to_string(&backup)?, legacy);
+        let content = std::fs::read_to_string(&config_path)?;
+        assert!(content.starts_with("# my vault"));
+        assert!(!content.contains("github") && !content.contains("repo_name"));
+        assert!(content.contains(&format!("config_version = {}", CONFIG_VERSION)));
+
+        // Current configs are not touched again
+        Config::load(&config_path)?;
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 2);
+
+        // Version 2 only renames the key
+        let mut table: toml::Table = toml::from_str("version = 2\nvault_path = \"/v\"")?;
+        let migration = migrate::migrate(&mut table)?.expect("migrated");
+        assert_eq!(migration.from, 2);
+        assert!(migration.notes.is_empty());
+        assert!(!table.contains_key("version"));
+        assert_eq!(migrate::version_of(&table), CONFIG_VERSION);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_keeps_comments() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+
+        // New files start from the template
+        let config = Config::with_vault_path(PathBuf::from("/data/vault"));
+        config.save(&config_path)?;
+        let content = std::fs::read_to_string(&config_path)?;
+        assert!(content.starts_with("# EchoVault configuration"));
+        assert!(
+            content.contains("# Quiet period (seconds) after the last change\ndebounce_secs = 5")
+        );
+
+        // Hand-written comments survive a load / modify / save cycle
+        let edited = content.replace(
+            "[sync]\n",
+            "[sync]\n# my team drive\nremote_name = \"team\" # shared\n",
+        );
+        std::fs::write(&config_path, edited)?;
+        let mut config = Config::load(&config_path)?;
+        assert_eq!(config.sync.remote_name.as_deref(), Some("team"));
+        config.sync.remote_name = Some("personal".to_string());
+        config.watcher.debounce_secs = 10;
+        config.export_path = Some(PathBuf::from("/exports"));
+        config.save(&config_path)?;
+
+        let content = std::fs::read_to_string(&config_path)?;
+        assert!(content.contains("# my team drive\nremote_name = \"personal\" # shared"));
+        assert!(
+            content.contains("# Quiet period (seconds) after the last change\ndebounce_secs = 10")
+        );
+        let loaded = Config::load(&config_path)?;
+        assert_eq!(loaded.watcher.debounce_secs, 10);
+        assert_eq!(loaded.export_path, Some(PathBuf::from("/exports")));
+        assert_eq!(loaded.watcher.ignore_for("cursor").len(), 3);
+
+        // Cleared options are removed
+        let mut config = loaded;
+        config.export_path = None;
+        config.save(&config_path)?;
+        assert_eq!(Config::load(&config_path)?.export_path, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_settings() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            vault_path = "/tmp/vault"
+
+            [extractors]
+            enabled_sources = ["cursor", "zed"]
+
+            [sources.cursor]
+            enabled = false
+
+            [sources.zed]
+            exclude = ["**/scratch-*"]
+            max_file_size_mb = 2
+            "#,
+        )?;
+
+        assert!(!config.source("cursor").enabled);
+        assert!(!config.source("claude-code").enabled);
+        let zed = config.source("zed");
+        assert!(zed.enabled);
+        assert_eq!(zed.exclude, vec!["**/scratch-*"]);
+        assert_eq!(zed.max_file_size(), Some(2 * 1024 * 1024));
+
+        // Enabling keeps the other settings and the allow-list in step
+        let mut config = config;
+        let mut claude = config.source("claude-code");
+        claude.enabled = true;
+        config.set_source("claude-code", claude);
+        assert!(config.source("claude-code").enabled);
+        assert!(!config.sources.contains_key("claude-code"));
+        let mut zed = config.source("zed");
+        zed.enabled = false;
+        config.set_source("zed", zed);
+        assert_eq!(config.sources["zed"].exclude, vec!["**/scratch-*"]);
+        assert!(!config.source("zed").enabled);
+
+        // Untouched sources are not written out
+        let saved = toml::to_string(&Config::default())?;
+        assert!(!saved.contains("[sources"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_reports_issues() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut config = Config::with_vault_path(temp_dir.path().to_path_buf());
+        config.sync.folder_name = "EchoVault".to_string();
+        assert_eq!(config.validate(), Vec::new());
+
+        config.sync.remote_name = Some("gdrive:".to_string());
+        config.embedding.api_base = "localhost:11434/v1".to_string();
+        config.embedding.chunk_overlap = config.embedding.chunk_size;
+        config.embedding.rerank.enabled = true;
+        config.embedding.rerank.candidates = 0;
+        config.extractors.enabled_sources = vec!["cursor".to_string(), "vim".to_string()];
+        config
+            .watcher
+            .sources
+            .entry("zed".to_string())
+            .or_default()
+            .ignore = vec!["**/[".to_string()];
+        config.encryption.recipients = vec!["not-a-key".to_string()];
+        config.notifications.webhooks = vec![WebhookConfig {
+            url: "hooks.slack.com/services/T0".to_string(),
+            format: WebhookFormat::Slack,
+            events: Vec::new(),
+        }];
+        config.profiles.insert(
+            "acme".to_string(),
+            ProfileConfig {
+                vault_path: Some(temp_dir.path().to_path_buf()),
+                ..ProfileConfig::default()
+            },
+        );
+
+        let issues = config.validate();
+        let keys: Vec<(&str, bool)> = issues
+            .iter()
+            .map(|i| (i.key.as_str(), i.is_error()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("sync.remote_name", true),
+                ("notifications.webhooks", true),
+                ("watcher.sources.zed.ignore", true),
+                ("embedding.api_base", true),
+                ("embedding.chunk_overlap", true),
+                ("embedding.rerank.candidates", true),
+                ("encryption.recipients", true),
+                ("profiles.acme.vault_path", true),
+                ("extractors.enabled_sources", false),
+            ]
+        );
+        assert!(issues[3].to_string().contains("http://localhost:11434/v1"));
+
+        assert_eq!(url_problem("http://localhost:11434/v1"), None);
+        assert_eq!(url_problem("https://[::1]:8000"), None);
+        assert!(url_problem("http://host:port/v1").is_some());
+        assert!(url_problem("https:///v1").is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_sync_provider() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "config_version = 3\nvault_path = {:?}\n[sync]\nprovider = \"local\"\n",
+                temp_dir.path().join("vault")
+            ),
+        )?;
+
+        let mut config = Config::load(&config_path)?;
+        assert_eq!(config.sync.provider, SyncProviderKind::Local);
+        assert!(!config.is_initialized());
+        let keys: Vec<String> = config.validate().into_iter().map(|i| i.key).collect();
+        assert_eq!(keys, vec!["sync.local_path"]);
+
+        config.sync.local_path = Some(temp_dir.path().join("nas"));
+        config.save(&config_path)?;
+        let loaded = Config::load(&config_path)?;
+        assert!(loaded.is_initialized());
+        assert_eq!(loaded.sync.local_path, config.sync.local_path);
+        assert_eq!(loaded.validate(), Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_sync_provider() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "config_version = 3\nvault_path = {:?}\n[sync]\nprovider = \"dropbox\"\n\
+                 [sync.targets.nas]\nprovider = \"ftp\"\n",
+                temp_dir.path().join("vault")
+            ),
+        )?;
+
+        let config = Config::load(&config_path)?;
+        assert_eq!(config.sync.provider, SyncProviderKind::Unknown);
+        assert!(!config.is_initialized());
+        let issues = config.validate();
+        let keys: Vec<&str> = issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, vec!["sync.provider", "sync.targets.nas.provider"]);
+        assert!(issues
+            .iter()
+            .all(|i| i.is_error() && i.hint.contains("\"local\"")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_targets() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            vault_path = "/tmp/vault"
+
+            [sync]
+            remote_name = "gdrive"
+            transfers = 4
+
+            [sync.targets.nas]
+            provider = "local"
+            local_path = "/mnt/nas"
+
+            [sync.targets.backup]
+
+            [[sync.workspaces]]
+            workspace = "acme-*"
+            targets = ["nas"]
+
+            [[sync.workspaces]]
+            workspace = "side-project"
+            targets = ["s3"]
+
+            [redaction]
+            destinations = ["nas", "export", "s3"]
+            "#,
+        )?;
+        assert_eq!(config.sync.target_names(), vec!["default", "backup", "nas"]);
+
+        let nas = config.sync.target("nas").unwrap();
+        assert_eq!(nas.provider, SyncProviderKind::Local);
+        assert_eq!(nas.folder_name, "EchoVault");
+        assert_eq!(nas.transfers, 4);
+        assert_eq!(
+            config
+                .sync
+                .target("default")
+                .unwrap()
+                .remote_name
+                .as_deref(),
+            Some("gdrive")
+        );
+        assert!(config.sync.target("missing").is_none());
+        assert_eq!(config.sync.workspaces[0].targets, vec!["nas"]);
+
+        assert!(config.redaction.applies_to("nas"));
+        assert!(!config.redaction.applies_to("default"));
+
+        // An rclone target needs its own remote; workspace rules and
+        // redaction only name targets
+        let keys: Vec<String> = config.validate().into_iter().map(|i| i.key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "sync.targets.backup.remote_name",
+                "sync.workspaces[1].targets",
+                "redaction.destinations"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_overrides() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("echovault.toml");
+        let mut config = Config::with_vault_path(PathBuf::from("/data/vault"));
+        config.sync.remote_name = Some("gdrive".to_string());
+        config.save(&config_path)?;
+
+        let vars = [
+            ("ECHOVAULT_VAULT_PATH", "/srv/vault"),
+            ("ECHOVAULT_REMOTE", "123"),
+            ("ECHOVAULT_EMBEDDING_ENDPOINT", "http://ollama:11434/v1"),
+            ("ECHOVAULT_LOG_LEVEL", "debug"),
+            ("ECHOVAULT_WATCHER__DEBOUNCE_SECS", "30"),
+            ("ECHOVAULT_EXTRACTORS__ENABLED_SOURCES", "cursor, zed"),
+            ("ECHOVAULT_PASSPHRASE", "secret"),
+            ("ECHOVAULT_MCP_TOKEN", "token"),
+            ("ECHOVAULT_NO_SUCH_KEY", "x"),
+            ("HOME", "/root"),
+        ];
+        let mut config = Config::load(&config_path)?;
+        config.apply_overrides(vars.map(|(k, v)| (k.to_string(), v.to_string())))?;
+        assert_eq!(config.vault_path, PathBuf::from("/srv/vault"));
+        assert_eq!(config.sync.remote_name.as_deref(), Some("123"));
+        assert_eq!(config.embedding.api_base, "http://ollama:11434/v1");
+        assert_eq!(config.log_level.as_deref(), Some("debug"));
+        assert_eq!(config.watcher.debounce_secs, 30);
+        assert_eq!(config.extractors.enabled_sources, vec!["cursor", "zed"]);
+        assert_eq!(config.env_overrides.len(), 6);
+
+        // Overrides are not written back
+        config.export_path = Some(PathBuf::from("/exports"));
+        config.save(&config_path)?;
+        let loaded = Config::load(&config_path)?;
+        assert_eq!(loaded.vault_path, PathBuf::from("/data/vault"));
+        assert_eq!(loaded.sync.remote_name.as_deref(), Some("gdrive"));
+        assert_eq!(loaded.log_level, None);
+        assert!(loaded.extractors.enabled_sources.is_empty());
+        assert_eq!(loaded.export_path, Some(PathBuf::from("/exports")));
+
+        // A value of the wrong type is an error
+        let mut config = Config::load(&config_path)?;
+        assert!(config
+            .apply_overrides([(
+                "ECHOVAULT_WATCHER__ENABLED".to_string(),
+                "sometimes".to_string()
+            )])
+            .is_err());
+
+        // Unset keys are typed after their field, not their TOML look
+        config.apply_overrides([
+            ("ECHOVAULT_LOG_LEVEL".to_string(), "2".to_string()),
+            ("ECHOVAULT_SYNC__BWLIMIT_KIB".to_string(), "512".to_string()),
+        ])?;
+        assert_eq!(config.log_level.as_deref(), Some("2"));
+        assert_eq!(config.sync.bwlimit_kib, Some(512));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_watcher_source_overrides() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            vault_path = "/tmp/vault"
+
+            [watcher]
+            debounce_secs = 3
+
+            [watcher.sources.zed]
+            enabled = false
+
+            [watcher.sources.cursor]
+            debounce_secs = 30
+            ignore = ["**/state.vscdb"]
+
+            [watcher.sources.vscode-copilot]
+            debounce_secs = 10
+            "#,
+        )?;
+
+        assert!(config.watcher.is_source_enabled("claude-code"));
+        assert!(!config.watcher.is_source_enabled("zed"));
+        assert_eq!(
+            config.watcher.debounce_for("claude-code"),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            config.watcher.debounce_for("cursor"),
+            Duration::from_secs(30)
+        );
+        assert_eq!(config.watcher.ignore_for("cursor"), ["**/state.vscdb"]);
+        assert!(config.watcher.ignore_for("aider").is_empty());
+        // Built-in entries not overridden field by field are kept
+        assert_eq!(
+            config.watcher.debounce_for("vscode-copilot"),
+            Duration::from_secs(10)
+        );
+        assert_eq!(config.watcher.ignore_for("vscode-copilot").len(), 3);
+
+        Ok(())
+    }
+}
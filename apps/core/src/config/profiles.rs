@@ -0,0 +1,181 @@
+//! Profiles index (`profiles.toml`).
+//!
+//! Besides the `[profiles.<name>]` sections of echovault.toml, a profile can
+//! have a config file of its own (`<config dir>/profiles/<name>.toml`), with
+//! every setting of echovault.toml: vault, remotes, sync targets, sources.
+//! The index lists those profiles and the one used when neither `--profile`
+//! nor `ECHOVAULT_PROFILE` is given (chosen in the desktop app's settings):
+//!
+//! ```toml
+//! default = "acme"
+//!
+//! [profiles.acme]
+//! config = "/home/me/clients/acme/echovault.toml"   # optional
+//!
+//! [profiles.globex]
+//! ```
+//!
+//! [`Config::load_default`](super::Config::load_default) loads the file of an
+//! indexed profile instead of echovault.toml, and
+//! [`default_config_path`](super::default_config_path) points at it, so saves
+//! go there too.
+
+use super::default_config_dir;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Profile with its own config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileEntry {
+    /// Config file (default: `<config dir>/profiles/<name>.toml`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<PathBuf>,
+}
+
+/// Contents of `profiles.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfilesIndex {
+    /// Profile used when neither `--profile` nor `ECHOVAULT_PROFILE` is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+
+    /// Profiles with their own config file, by name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileEntry>,
+}
+
+/// Path of the profiles index.
+pub fn profiles_index_path() -> PathBuf {
+    default_config_dir().join("profiles.toml")
+}
+
+/// Default config file of an indexed profile.
+pub fn default_profile_config_path(name: &str) -> PathBuf {
+    default_config_dir()
+        .join("profiles")
+        .join(format!("{}.toml", name))
+}
+
+/// Whether `name` can name a profile (it ends up in file names).
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Fail unless `name` can name a profile (see [`is_valid_profile_name`]).
+pub fn check_profile_name(name: &str) -> Result<()> {
+    if !is_valid_profile_name(name) {
+        bail!(
+            "'{}' is not a valid profile name (use letters, digits, '-' and '_' only)",
+            name
+        );
+    }
+    Ok(())
+}
+
+impl ProfilesIndex {
+    /// Load the index from its default path (empty when missing).
+    pub fn load_default() -> Result<Self> {
+        Self::load(&profiles_index_path())
+    }
+
+    /// Load an index file (empty when missing).
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read profiles index: {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Cannot parse profiles index: {}", path.display()))
+    }
+
+    /// Save the index to its default path.
+    pub fn save_default(&self) -> Result<PathBuf> {
+        let path = profiles_index_path();
+        self.save(&path)?;
+        Ok(path)
+    }
+
+    /// Save the index to a file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).context("Cannot serialize profiles index")?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Cannot write profiles index: {}", path.display()))
+    }
+
+    /// Config file of an indexed profile.
+    pub fn config_path(&self, name: &str) -> Option<PathBuf> {
+        let entry = self.profiles.get(name)?;
+        Some(
+            entry
+                .config
+                .clone()
+                .unwrap_or_else(|| default_profile_config_path(name)),
+        )
+    }
+
+    /// Add a profile, returning its config file.
+    pub fn add(&mut self, name: &str, config: Option<PathBuf>) -> Result<PathBuf> {
+        check_profile_name(name)?;
+        if self.profiles.contains_key(name) {
+            bail!("Profile '{}' already exists", name);
+        }
+        self.profiles
+            .insert(name.to_string(), ProfileEntry { config });
+        Ok(self.config_path(name).expect("profile just added"))
+    }
+
+    /// Remove a profile from the index (its config file and vault are kept).
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        if self.profiles.remove(name).is_none() {
+            bail!("Profile '{}' is not in the profiles index", name);
+        }
+        if self.default.as_deref() == Some(name) {
+            self.default = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_profiles_index() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("profiles.toml");
+        let mut index = ProfilesIndex::load(&path)?;
+        assert_eq!(index, ProfilesIndex::default());
+
+        let acme = index.add("acme", None)?;
+        assert_eq!(acme, default_profile_config_path("acme"));
+        index.add("globex", Some(temp.path().join("globex.toml")))?;
+        assert!(index.add("acme", None).is_err());
+        assert!(index.add("../etc", None).is_err());
+        index.default = Some("globex".to_string());
+        index.save(&path)?;
+
+        let mut loaded = ProfilesIndex::load(&path)?;
+        assert_eq!(loaded, index);
+        assert_eq!(
+            loaded.config_path("globex"),
+            Some(temp.path().join("globex.toml"))
+        );
+        assert_eq!(loaded.config_path("initech"), None);
+
+        loaded.remove("globex")?;
+        assert_eq!(loaded.default, None);
+        assert!(loaded.remove("globex").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+//! age backend - encrypt to X25519 or plugin recipients instead of a passphrase.
+//!
+//! Staged files are standard age files, so they stay readable without
+//! EchoVault: `age -d -i <identity file> file.json.enc > file.json`.
+//! Several recipients can be listed (e.g. one per machine plus a backup key);
+//! any of their identities decrypts. Plugin recipients such as
+//! `age1yubikey1...` need the matching `age-plugin-*` binary on `PATH`.
+//!
+//! The integrity manifest is keyed with X25519 between the sealing identity
+//! and each native recipient (see [`super::manifest`]), so every machine
+//! needs at least one native identity besides any plugin ones.
+
+use age::{plugin, x25519, Callbacks, Decryptor, Encryptor, IdentityFile};
+use anyhow::{anyhow, bail, Context, Result};
+use bech32::FromBase32;
+use secrecy::{ExposeSecret, SecretString};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use tracing::info;
+use zeroize::Zeroizing;
+
+/// First line of every age file
+pub const AGE_HEADER: &[u8] = b"age-encryption.org/v1\n";
+
+/// Forwards plugin messages (e.g. "touch your YubiKey") to the log.
+///
+/// Interactive requests are declined, so plugins needing a PIN must cache it
+/// themselves (as `age-plugin-yubikey` does with its PIN policy).
+#[derive(Clone, Copy)]
+struct LogCallbacks;
+
+impl Callbacks for LogCallbacks {
+    fn display_message(&self, message: &str) {
+        info!("[crypto] age: {}", message);
+    }
+
+    fn confirm(&self, _: &str, _: &str, _: Option<&str>) -> Option<bool> {
+        None
+    }
+
+    fn request_public_string(&self, _: &str) -> Option<String> {
+        None
+    }
+
+    fn request_passphrase(&self, _: &str) -> Option<SecretString> {
+        None
+    }
+}
+
+/// Check whether data is an age file.
+pub fn is_age(data: &[u8]) -> bool {
+    data.starts_with(AGE_HEADER)
+}
+
+/// Parsed recipients, ready to encrypt any number of files.
+///
+/// Parsing looks up the `age-plugin-*` binaries, so it is done once per
+/// unlocked key rather than per file.
+pub struct Recipients {
+    parsed: Vec<Box<dyn age::Recipient + Send + Sync>>,
+    /// Native X25519 recipients (`age1...`), as given
+    native: Vec<String>,
+}
+
+impl Recipients {
+    /// Parse recipient strings, grouping plugin recipients per plugin.
+    pub fn parse(list: &[String]) -> Result<Self> {
+        let native = list
+            .iter()
+            .map(|r| r.trim())
+            .filter(|r| r.parse::<x25519::Recipient>().is_ok())
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            parsed: parse_recipients(list)?,
+            native,
+        })
+    }
+
+    /// Native X25519 recipients, the ones manifests are signed for.
+    pub(crate) fn native(&self) -> &[String] {
+        &self.native
+    }
+}
+
+fn parse_recipients(list: &[String]) -> Result<Vec<Box<dyn age::Recipient + Send + Sync>>> {
+    let mut parsed: Vec<Box<dyn age::Recipient + Send + Sync>> = Vec::new();
+    let mut plugins: BTreeMap<String, Vec<plugin::Recipient>> = BTreeMap::new();
+
+    for recipient in list.iter().map(|r| r.trim()) {
+        if let Ok(native) = recipient.parse::<x25519::Recipient>() {
+            parsed.push(Box::new(native));
+        } else if let Ok(plugin) = recipient.parse::<plugin::Recipient>() {
+            plugins
+                .entry(plugin.plugin().to_string())
+                .or_default()
+                .push(plugin);
+        } else {
+            bail!("Invalid age recipient: {}", recipient);
+        }
+    }
+
+    for (name, recipients) in plugins {
+        let plugin = plugin::RecipientPluginV1::new(&name, &recipients, &[], LogCallbacks)
+            .map_err(|e| anyhow!("{}", e))?;
+        parsed.push(Box::new(plugin));
+    }
+
+    if parsed.is_empty() {
+        bail!("At least one age recipient is required");
+    }
+    Ok(parsed)
+}
+
+/// Check that every recipient is valid (and its plugin, if any, is installed).
+pub fn validate_recipients(list: &[String]) -> Result<()> {
+    parse_recipients(list).map(|_| ())
+}
+
+/// Encrypt bytes to all recipients.
+pub fn encrypt(recipients: &Recipients, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let encryptor = Encryptor::with_recipients(
+        recipients
+            .parsed
+            .iter()
+            .map(|r| r.as_ref() as &dyn age::Recipient),
+    )
+    .map_err(|e| anyhow!("age encryption failed: {}", e))?;
+
+    let mut out = Vec::with_capacity(plaintext.len() + 256);
+    let mut writer = encryptor.wrap_output(&mut out)?;
+    writer.write_all(plaintext)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+/// Decrypt an age file with the identities in an identity file.
+pub fn decrypt(identity_file: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let identities = IdentityFile::from_buffer(identity_file)
+        .context("Invalid age identity file")?
+        .with_callbacks(LogCallbacks)
+        .into_identities()
+        .map_err(|e| anyhow!("Cannot load age identities: {}", e))?;
+
+    let decryptor =
+        Decryptor::new_buffered(data).map_err(|e| anyhow!("Invalid age file: {}", e))?;
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|i| i.as_ref() as &dyn age::Identity))
+        .map_err(|e| anyhow!("Decryption failed: {}", e))?;
+
+    let mut out = Vec::new();
+    reader
+        .read_to_end(&mut out)
+        .map_err(|_| anyhow!("Decryption failed (corrupted data)"))?;
+    Ok(out)
+}
+
+/// Generate a new X25519 identity.
+///
+/// Returns the identity file contents (in the format written by `age-keygen`)
+/// and its public recipient.
+pub fn generate_identity() -> (SecretString, String) {
+    let identity = x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+    let contents = format!(
+        "# created: {}\n# public key: {}\n{}\n",
+        chrono::Utc::now().to_rfc3339(),
+        recipient,
+        identity.to_string().expose_secret()
+    );
+    (contents.into(), recipient)
+}
+
+/// Public recipients of the native identities in an identity file.
+///
+/// Plugin identities are skipped: their recipients must be given explicitly.
+pub fn recipients_of(identity_file: &str) -> Vec<String> {
+    native_identities(identity_file)
+        .into_iter()
+        .map(|(recipient, _)| recipient)
+        .collect()
+}
+
+/// Native X25519 identities of an identity file, with their recipients.
+pub(crate) fn native_identities(identity_file: &str) -> Vec<(String, x25519::Identity)> {
+    identity_file
+        .lines()
+        .filter_map(|line| line.trim().parse::<x25519::Identity>().ok())
+        .map(|identity| (identity.to_public().to_string(), identity))
+        .collect()
+}
+
+/// X25519 shared secret of an identity and a native recipient.
+///
+/// Symmetric: identity A with recipient B gives the same secret as identity
+/// B with recipient A, and nobody without either identity can compute it.
+pub(crate) fn shared_secret(
+    identity: &x25519::Identity,
+    recipient: &str,
+) -> Result<Zeroizing<[u8; 32]>> {
+    let secret = decode_key(identity.to_string().expose_secret(), "age-secret-key-")?;
+    let public = decode_key(recipient, "age")?;
+    let shared = x25519_dalek::StaticSecret::from(*secret)
+        .diffie_hellman(&x25519_dalek::PublicKey::from(*public));
+    if !shared.was_contributory() {
+        bail!("Invalid age recipient: {}", recipient);
+    }
+    Ok(Zeroizing::new(shared.to_bytes()))
+}
+
+/// Raw 32 bytes of a Bech32 age key with the given prefix.
+fn decode_key(encoded: &str, hrp: &str) -> Result<Zeroizing<[u8; 32]>> {
+    let (found, data, _) =
+        bech32::decode(encoded).map_err(|e| anyhow!("Invalid age key: {}", e))?;
+    let bytes = Zeroizing::new(
+        Vec::<u8>::from_base32(&data).map_err(|e| anyhow!("Invalid age key: {}", e))?,
+    );
+    if found != hrp || bytes.len() != 32 {
+        bail!("Invalid age key");
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+/// Read an identity file.
+pub fn load_identity(path: &Path) -> Result<SecretString> {
+    let contents = fs::read_to_string(path).with_context(|| {
+        format!(
+            "Cannot read age identity file {:?}. Copy your identity file there or set \
+             [encryption] identity_file in the config.",
+            path
+        )
+    })?;
+    Ok(contents.into())
+}
+
+/// Write an identity file, readable only by the current user.
+pub fn save_identity(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write {:?}", path))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age_roundtrip_multiple_recipients() {
+        let (alice, alice_pub) = generate_identity();
+        let (bob, bob_pub) = generate_identity();
+        let (eve, _) = generate_identity();
+        let (alice, bob, eve) = (
+            alice.expose_secret(),
+            bob.expose_secret(),
+            eve.expose_secret(),
+        );
+        assert_eq!(recipients_of(alice), vec![alice_pub.clone()]);
+
+        let recipients = Recipients::parse(&[alice_pub, bob_pub]).unwrap();
+        let blob = encrypt(&recipients, b"shared history").unwrap();
+        assert!(is_age(&blob));
+        assert_eq!(decrypt(alice.as_bytes(), &blob).unwrap(), b"shared history");
+        assert_eq!(decrypt(bob.as_bytes(), &blob).unwrap(), b"shared history");
+        assert!(decrypt(eve.as_bytes(), &blob).is_err());
+
+        // Both ends of a pair derive the same secret, a third one does not
+        let alice_id = &native_identities(alice)[0].1;
+        let (bob_pub, bob_id) = &native_identities(bob)[0];
+        let (eve_pub, _) = &native_identities(eve)[0];
+        let shared = shared_secret(alice_id, bob_pub).unwrap();
+        assert_eq!(
+            *shared,
+            *shared_secret(bob_id, &recipients.native()[0]).unwrap()
+        );
+        assert_ne!(*shared, *shared_secret(alice_id, eve_pub).unwrap());
+
+        assert!(validate_recipients(&["not-a-recipient".to_string()]).is_err());
+        assert!(validate_recipients(&[]).is_err());
+    }
+}
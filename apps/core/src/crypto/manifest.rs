@@ -0,0 +1,479 @@
+//! Integrity manifest of the encrypted staging directory.
+//!
+//! `.encrypted/manifest.json` lists every encrypted file with its size,
+//! SHA-256 and (passphrase backend) nonce, signed with HMAC-SHA256 so that
+//! only key holders can produce a valid manifest:
+//!
+//! - Passphrase backend: under a random MAC key that is itself encrypted
+//!   with the vault key.
+//! - age backend: encrypting needs only the public recipients, so a wrapped
+//!   key would prove nothing. The sealing machine signs with one of its
+//!   X25519 identities instead, once per native recipient, under a key
+//!   derived from their shared secret ([`age_backend::shared_secret`]).
+//!   Checking needs the identity of one of those recipients.
+//!
+//! Pulls check files against it before decrypting; `encryption verify` checks
+//! every file.
+
+use super::{age_backend, staging};
+use super::{decrypt, encrypt, is_aes_blob, KeyMaterial, VaultKey, MAGIC, NONCE_LEN};
+use crate::storage::{atomic, mapped};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Manifest file name inside the staging directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+const MANIFEST_VERSION: u32 = 1;
+const MAC_KEY_LEN: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Integrity record of one encrypted file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Size of the encrypted file in bytes
+    pub size: u64,
+    /// Hex SHA-256 of the encrypted file
+    pub sha256: String,
+    /// Base64 nonce (passphrase backend only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+impl ManifestEntry {
+    /// Describe an encrypted blob.
+    pub fn for_blob(blob: &[u8]) -> Self {
+        let nonce =
+            is_aes_blob(blob).then(|| BASE64.encode(&blob[MAGIC.len()..MAGIC.len() + NONCE_LEN]));
+        Self {
+            size: blob.len() as u64,
+            sha256: sha256_hex(blob),
+            nonce,
+        }
+    }
+}
+
+/// Signed list of encrypted files, keyed by staging-relative path (`/`-separated).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Manifest format version
+    pub version: u32,
+    /// Encrypted files
+    pub files: BTreeMap<String, ManifestEntry>,
+    /// When the manifest was last written
+    pub updated_at: String,
+    /// Base64 MAC key, encrypted with the vault key (passphrase backend)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mac_key: String,
+    /// Hex HMAC-SHA256 over version, files and updated_at (passphrase backend)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signature: String,
+    /// Recipient of the identity that signed the manifest (age backend)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// Hex HMAC-SHA256 per native recipient, keyed with its shared secret
+    /// with `signer` (age backend)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub signatures: BTreeMap<String, String>,
+}
+
+/// Result of checking the staging directory against the manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Files whose size and hash match
+    pub verified: usize,
+    /// Files whose size or hash differ from the manifest
+    pub corrupted: Vec<String>,
+    /// Files listed in the manifest but missing locally
+    pub missing: Vec<String>,
+    /// Encrypted files not listed in the manifest
+    pub unlisted: Vec<String>,
+}
+
+impl VerifyReport {
+    /// True when every file matches the manifest.
+    pub fn is_clean(&self) -> bool {
+        self.corrupted.is_empty() && self.missing.is_empty() && self.unlisted.is_empty()
+    }
+}
+
+fn manifest_path(staging: &Path) -> PathBuf {
+    staging.join(MANIFEST_FILE)
+}
+
+/// `.encrypted/sessions/a/b.json.enc` -> `sessions/a/b.json.enc`
+pub fn manifest_key(staging: &Path, path: &Path) -> Result<String> {
+    Ok(path
+        .strip_prefix(staging)?
+        .to_string_lossy()
+        .replace('\\', "/"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+impl Manifest {
+    /// Load the manifest of a staging directory, if present.
+    pub fn load(staging: &Path) -> Result<Option<Self>> {
+        let path = manifest_path(staging);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let manifest = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid manifest {:?}", path))?;
+        Ok(Some(manifest))
+    }
+
+    /// Load the manifest and check its signature with the vault key.
+    pub fn load_verified(staging: &Path, key: &VaultKey) -> Result<Option<Self>> {
+        let Some(manifest) = Self::load(staging)? else {
+            return Ok(None);
+        };
+        manifest.verify_signature(key)?;
+        Ok(Some(manifest))
+    }
+
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&(
+            self.version,
+            &self.files,
+            &self.updated_at,
+        ))?)
+    }
+
+    fn unwrap_mac_key(&self, key: &VaultKey) -> Result<Zeroizing<Vec<u8>>> {
+        let wrapped = BASE64
+            .decode(&self.mac_key)
+            .context("Invalid manifest MAC key")?;
+        decrypt(key, &wrapped)
+            .map(Zeroizing::new)
+            .context("Manifest was not signed with this vault key")
+    }
+
+    fn mac(mac_key: &[u8]) -> HmacSha256 {
+        <HmacSha256 as Mac>::new_from_slice(mac_key).expect("HMAC accepts any key length")
+    }
+
+    /// MAC keyed for the `signer` -> `recipient` pair of an age vault.
+    fn pair_mac(shared: &[u8], signer: &str, recipient: &str) -> HmacSha256 {
+        let mut kdf = Self::mac(shared);
+        kdf.update(b"echovault-manifest\0");
+        kdf.update(signer.as_bytes());
+        kdf.update(b"\0");
+        kdf.update(recipient.as_bytes());
+        Self::mac(&kdf.finalize().into_bytes())
+    }
+
+    /// Check the manifest signature.
+    pub fn verify_signature(&self, key: &VaultKey) -> Result<()> {
+        let (mut mac, signature) = match &key.material {
+            KeyMaterial::Aes(_) => (Self::mac(&self.unwrap_mac_key(key)?), &self.signature),
+            KeyMaterial::Age {
+                recipients,
+                identity,
+            } => {
+                let Some(signer) = &self.signer else {
+                    bail!("Manifest is not signed with an age identity");
+                };
+                if !recipients.native().contains(signer) {
+                    bail!("Manifest was signed by {}, not a vault recipient", signer);
+                }
+                let identity = std::str::from_utf8(identity).unwrap_or_default();
+                let Some((recipient, identity, signature)) =
+                    age_backend::native_identities(identity)
+                        .into_iter()
+                        .find_map(|(recipient, identity)| {
+                            let signature = self.signatures.get(&recipient)?;
+                            Some((recipient, identity, signature))
+                        })
+                else {
+                    bail!("Manifest was not signed for this age identity");
+                };
+                let shared = age_backend::shared_secret(&identity, signer)?;
+                (
+                    Self::pair_mac(shared.as_ref(), signer, &recipient),
+                    signature,
+                )
+            }
+        };
+
+        let signature = (0..signature.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(signature.get(i..i + 2).unwrap_or(""), 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .context("Invalid manifest signature")?;
+        mac.update(&self.signed_bytes()?);
+        if mac.verify_slice(&signature).is_err() {
+            bail!("Manifest signature is invalid (tampered or corrupted)");
+        }
+        Ok(())
+    }
+
+    /// Sign the manifest: with its MAC key (kept when it still unwraps) for
+    /// passphrase vaults, with an identity of this machine for age vaults.
+    fn sign(&mut self, key: &VaultKey) -> Result<()> {
+        self.updated_at = chrono::Utc::now().to_rfc3339();
+        let signed = self.signed_bytes()?;
+
+        match &key.material {
+            KeyMaterial::Aes(_) => {
+                let mac_key = match self.unwrap_mac_key(key) {
+                    Ok(mac_key) => mac_key,
+                    Err(_) => {
+                        let mut mac_key = Zeroizing::new(vec![0u8; MAC_KEY_LEN]);
+                        OsRng.fill_bytes(&mut mac_key);
+                        self.mac_key = BASE64.encode(encrypt(key, &mac_key)?);
+                        mac_key
+                    }
+                };
+                let mut mac = Self::mac(&mac_key);
+                mac.update(&signed);
+                self.signature = to_hex(&mac.finalize().into_bytes());
+            }
+            KeyMaterial::Age {
+                recipients,
+                identity,
+            } => {
+                let identity = std::str::from_utf8(identity).unwrap_or_default();
+                let (signer, identity) = age_backend::native_identities(identity)
+                    .into_iter()
+                    .find(|(recipient, _)| recipients.native().contains(recipient))
+                    .context(
+                        "Signing the manifest needs an X25519 identity (AGE-SECRET-KEY-1...) \
+                         of a vault recipient in the identity file",
+                    )?;
+                let mut signatures = BTreeMap::new();
+                for recipient in recipients.native() {
+                    let shared = age_backend::shared_secret(&identity, recipient)?;
+                    let mut mac = Self::pair_mac(shared.as_ref(), &signer, recipient);
+                    mac.update(&signed);
+                    signatures.insert(recipient.clone(), to_hex(&mac.finalize().into_bytes()));
+                }
+                self.signer = Some(signer);
+                self.signatures = signatures;
+                self.mac_key.clear();
+                self.signature.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// Check an encrypted blob against its entry.
+    ///
+    /// Returns `Ok(false)` when the file is not listed (e.g. uploaded by an
+    /// interrupted push) and an error when it does not match.
+    pub fn check(&self, name: &str, blob: &[u8]) -> Result<bool> {
+        let Some(entry) = self.files.get(name) else {
+            return Ok(false);
+        };
+        if entry.size != blob.len() as u64 || entry.sha256 != sha256_hex(blob) {
+            bail!(
+                "{} does not match the manifest (tampered or corrupted)",
+                name
+            );
+        }
+        Ok(true)
+    }
+}
+
+/// Update the staging manifest after a seal.
+///
+/// `changed` holds entries of files just encrypted. Entries of a previous,
+/// validly signed manifest are reused for other files; anything else in the
+/// staging directory is hashed. Returns whether the manifest was rewritten.
+pub fn update(
+    staging: &Path,
+    key: &VaultKey,
+    changed: BTreeMap<String, ManifestEntry>,
+) -> Result<bool> {
+    let previous = Manifest::load(staging)
+        .ok()
+        .flatten()
+        .filter(|m| m.verify_signature(key).is_ok());
+    let valid = previous.is_some();
+    let mut manifest = previous.unwrap_or_else(|| Manifest {
+        version: MANIFEST_VERSION,
+        files: BTreeMap::new(),
+        updated_at: String::new(),
+        mac_key: String::new(),
+        signature: String::new(),
+        signer: None,
+        signatures: BTreeMap::new(),
+    });
+
+    let mut files = BTreeMap::new();
+    for path in staging::encrypted_files(staging)? {
+        let name = manifest_key(staging, &path)?;
+        let entry = match changed.get(&name) {
+            Some(entry) => entry.clone(),
+            None => match manifest.files.get(&name) {
+                Some(entry) if fs::metadata(&path)?.len() == entry.size => entry.clone(),
+                _ => ManifestEntry::for_blob(&mapped::read(&path)?),
+            },
+        };
+        files.insert(name, entry);
+    }
+
+    if valid && files == manifest.files {
+        return Ok(false);
+    }
+    manifest.files = files;
+    manifest.sign(key)?;
+
+    let path = manifest_path(staging);
+    atomic::write(&path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(true)
+}
+
+/// Hash every encrypted file in a staging directory and compare with the manifest.
+pub fn verify_staging(staging: &Path, key: &VaultKey) -> Result<VerifyReport> {
+    let Some(manifest) = Manifest::load_verified(staging, key)? else {
+        bail!("No manifest found in {:?}; sync once to create it", staging);
+    };
+
+    let mut report = VerifyReport::default();
+    let mut seen = Vec::new();
+    for path in staging::encrypted_files(staging)? {
+        let name = manifest_key(staging, &path)?;
+        match manifest.check(&name, &fs::read(&path)?) {
+            Ok(true) => report.verified += 1,
+            Ok(false) => report.unlisted.push(name.clone()),
+            Err(_) => report.corrupted.push(name.clone()),
+        }
+        seen.push(name);
+    }
+    report.missing = manifest
+        .files
+        .keys()
+        .filter(|name| !seen.contains(name))
+        .cloned()
+        .collect();
+
+    report.corrupted.sort();
+    report.unlisted.sort();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::tests::test_params;
+    use crate::crypto::EncryptionMeta;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_detects_tampering() {
+        let (_, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+        let temp = TempDir::new().unwrap();
+        let staging = temp.path();
+        let dir = staging.join("sessions/cursor");
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["a.json.enc", "b.json.enc"] {
+            fs::write(dir.join(name), encrypt(&key, name.as_bytes()).unwrap()).unwrap();
+        }
+
+        assert!(update(staging, &key, BTreeMap::new()).unwrap());
+        assert!(!update(staging, &key, BTreeMap::new()).unwrap());
+        let report = verify_staging(staging, &key).unwrap();
+        assert_eq!(report.verified, 2);
+        assert!(report.is_clean());
+
+        // Flip a byte in one file
+        let target = dir.join("a.json.enc");
+        let mut blob = fs::read(&target).unwrap();
+        blob[20] ^= 0xff;
+        fs::write(&target, &blob).unwrap();
+        let report = verify_staging(staging, &key).unwrap();
+        assert_eq!(report.corrupted, vec!["sessions/cursor/a.json.enc"]);
+
+        // A manifest edited without the key fails its signature
+        let mut manifest = Manifest::load(staging).unwrap().unwrap();
+        manifest
+            .files
+            .get_mut("sessions/cursor/a.json.enc")
+            .unwrap()
+            .sha256 = sha256_hex(&blob);
+        assert!(manifest.verify_signature(&key).is_err());
+
+        // Another key cannot verify it either
+        let (_, other) = EncryptionMeta::create_with_params("other", test_params()).unwrap();
+        assert!(Manifest::load_verified(staging, &other).is_err());
+    }
+
+    #[test]
+    fn test_age_manifest_cannot_be_forged_from_recipients() {
+        use secrecy::ExposeSecret;
+
+        let (alice, alice_pub) = age_backend::generate_identity();
+        let (bob, bob_pub) = age_backend::generate_identity();
+        let (eve, eve_pub) = age_backend::generate_identity();
+        let meta = EncryptionMeta::create_age(vec![alice_pub.clone(), bob_pub.clone()]).unwrap();
+        let alice_key = meta.unlock_with_identity(alice.expose_secret()).unwrap();
+        let bob_key = meta.unlock_with_identity(bob.expose_secret()).unwrap();
+
+        let temp = TempDir::new().unwrap();
+        let staging = temp.path();
+        let file = staging.join("sessions/cursor/a.json.enc");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, encrypt(&alice_key, b"history").unwrap()).unwrap();
+
+        // Signed on one machine, checked on the other
+        assert!(update(staging, &alice_key, BTreeMap::new()).unwrap());
+        assert!(Manifest::load_verified(staging, &bob_key).is_ok());
+        assert!(verify_staging(staging, &bob_key).unwrap().is_clean());
+        let genuine = Manifest::load(staging).unwrap().unwrap();
+
+        // Replace the file and re-sign with what anyone can read: the
+        // recipients in vault.json
+        let forged_blob = encrypt(&alice_key, b"injected").unwrap();
+        fs::write(&file, &forged_blob).unwrap();
+        let mut forged = genuine.clone();
+        forged.files.insert(
+            "sessions/cursor/a.json.enc".to_string(),
+            ManifestEntry::for_blob(&forged_blob),
+        );
+
+        // A MAC key wrapped to the recipients, as for passphrase vaults
+        let mut wrapped = forged.clone();
+        let mac_key = [7u8; MAC_KEY_LEN];
+        wrapped.mac_key = BASE64.encode(encrypt(&alice_key, &mac_key).unwrap());
+        wrapped.signer = None;
+        wrapped.signatures.clear();
+        let mut mac = Manifest::mac(&mac_key);
+        mac.update(&wrapped.signed_bytes().unwrap());
+        wrapped.signature = to_hex(&mac.finalize().into_bytes());
+        assert!(wrapped.verify_signature(&bob_key).is_err());
+
+        // Signed by an identity that is not a vault recipient
+        let eve_meta = EncryptionMeta::create_age(vec![alice_pub, bob_pub, eve_pub]).unwrap();
+        let eve_key = eve_meta.unlock_with_identity(eve.expose_secret()).unwrap();
+        let mut foreign = forged.clone();
+        foreign.sign(&eve_key).unwrap();
+        assert!(foreign.verify_signature(&bob_key).is_err());
+
+        // Claiming a recipient as signer without its identity
+        let mut claimed = forged;
+        claimed.sign(&eve_key).unwrap();
+        claimed.signer = genuine.signer.clone();
+        assert!(claimed.verify_signature(&bob_key).is_err());
+        assert!(claimed.verify_signature(&alice_key).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//! Crypto module - End-to-end encryption for synced vault data.
+//!
+//! Encryption is an optional layer between the vault and sync providers.
+//! Two backends are available:
+//! - Passphrase (default): Argon2id key derivation + AES-256-GCM with a random
+//!   96-bit nonce per file
+//! - age: files are encrypted to one or more X25519/plugin recipients and
+//!   decrypted with an identity file (see [`age_backend`])
+//!
+//! Either way, a known plaintext encrypted with the key (`verifier`) checks
+//! the passphrase or identity without touching vault data.
+//!
+//! The local vault stays plaintext. Before a push, session files and parsed
+//! output are sealed into a staging directory (see [`staging`]) and the
+//! staging directory is what providers upload. A signed [`manifest`] of the
+//! encrypted files lets pulls detect tampered or corrupted objects. Keys can
+//! be rotated with [`rotate::rotate_key`].
+//!
+//! Passphrase blob layout: `MAGIC (4) | nonce (12) | ciphertext + tag`.
+//! age blobs are standard age files.
+
+pub mod age_backend;
+pub mod manifest;
+pub mod recovery;
+pub mod rotate;
+pub mod staging;
+
+use crate::storage::{atomic, mapped};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Magic prefix of encrypted blobs ("EVE" + format version 1)
+pub const MAGIC: &[u8; 4] = b"EVE\x01";
+
+/// Cipher identifier stored in vault.json
+pub const ALGORITHM: &str = "aes-256-gcm";
+
+/// KDF identifier stored in vault.json
+pub const KDF: &str = "argon2id";
+
+/// Algorithm identifier of the age backend
+pub const ALGORITHM_AGE: &str = "age";
+
+/// KDF identifier of the age backend (keys are not derived)
+pub const KDF_NONE: &str = "none";
+
+/// Environment variable read by non-interactive callers (CLI daemon, CI)
+pub const PASSPHRASE_ENV: &str = "ECHOVAULT_PASSPHRASE";
+
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+pub(crate) const KEY_LEN: usize = 32;
+
+/// Known plaintext used to check a passphrase without touching vault data
+const VERIFIER_PLAINTEXT: &[u8] = b"echovault-key-check";
+
+/// Argon2id cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of iterations
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // OWASP recommended Argon2id baseline (19 MiB, 2 passes, 1 lane)
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Unlocked vault key: a 256-bit key derived from the passphrase, or age
+/// recipients plus the identity file that decrypts them.
+///
+/// Secret bytes are cleared on drop and never printed.
+#[derive(Clone)]
+pub struct VaultKey {
+    material: KeyMaterial,
+}
+
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+enum KeyMaterial {
+    Aes([u8; KEY_LEN]),
+    Age {
+        #[zeroize(skip)]
+        recipients: Arc<age_backend::Recipients>,
+        identity: Vec<u8>,
+    },
+}
+
+impl VaultKey {
+    /// Derive a key from a passphrase with Argon2id.
+    pub fn derive(passphrase: &str, salt: &[u8], params: &KdfParams) -> Result<Self> {
+        let params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|e| anyhow!("Invalid KDF parameters: {}", e))?;
+
+        let mut bytes = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), salt, bytes.as_mut())
+            .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+
+        Ok(Self {
+            material: KeyMaterial::Aes(*bytes),
+        })
+    }
+
+    /// Use 256 random bits (e.g. a key file) as the key directly.
+    pub(crate) fn from_raw(bytes: &[u8; KEY_LEN]) -> Self {
+        Self {
+            material: KeyMaterial::Aes(*bytes),
+        }
+    }
+}
+
+impl fmt::Debug for VaultKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VaultKey(<redacted>)")
+    }
+}
+
+/// Encryption settings stored in vault.json (shared by all machines).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionMeta {
+    /// Cipher identifier ("aes-256-gcm" or "age")
+    pub algorithm: String,
+    /// KDF identifier ("argon2id", or "none" for age)
+    pub kdf: String,
+    /// KDF cost parameters used to derive the key
+    #[serde(default)]
+    pub kdf_params: KdfParams,
+    /// Base64 KDF salt (empty for age)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub salt: String,
+    /// age recipients files are encrypted to (age backend only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients: Vec<String>,
+    /// Base64 encrypted known plaintext, used to verify the passphrase
+    pub verifier: String,
+    /// Recovery code that can unlock the key without the passphrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<recovery::RecoveryMeta>,
+    /// When encryption was set up
+    pub created_at: String,
+}
+
+impl EncryptionMeta {
+    /// Set up encryption for a new passphrase with default KDF parameters.
+    pub fn create(passphrase: &str) -> Result<(Self, VaultKey)> {
+        Self::create_with_params(passphrase, KdfParams::default())
+    }
+
+    /// Set up encryption with explicit KDF parameters.
+    pub fn create_with_params(passphrase: &str, kdf_params: KdfParams) -> Result<(Self, VaultKey)> {
+        if passphrase.is_empty() {
+            bail!("Passphrase must not be empty");
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let key = VaultKey::derive(passphrase, &salt, &kdf_params)?;
+        let verifier = encrypt(&key, VERIFIER_PLAINTEXT)?;
+
+        let meta = Self {
+            algorithm: ALGORITHM.to_string(),
+            kdf: KDF.to_string(),
+            kdf_params,
+            salt: BASE64.encode(salt),
+            recipients: Vec::new(),
+            verifier: BASE64.encode(verifier),
+            recovery: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        Ok((meta, key))
+    }
+
+    /// Set up age encryption to a list of recipients.
+    pub fn create_age(recipients: Vec<String>) -> Result<Self> {
+        let parsed = age_backend::Recipients::parse(&recipients)?;
+        let verifier = age_backend::encrypt(&parsed, VERIFIER_PLAINTEXT)?;
+
+        Ok(Self {
+            algorithm: ALGORITHM_AGE.to_string(),
+            kdf: KDF_NONE.to_string(),
+            kdf_params: KdfParams::default(),
+            salt: String::new(),
+            recipients,
+            verifier: BASE64.encode(verifier),
+            recovery: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// Whether this vault uses the age backend.
+    pub fn is_age(&self) -> bool {
+        self.algorithm == ALGORITHM_AGE
+    }
+
+    /// Derive the key for a passphrase and check it against the verifier.
+    pub fn unlock(&self, passphrase: &str) -> Result<VaultKey> {
+        if self.is_age() {
+            bail!("This vault is encrypted with age; unlock it with an identity file");
+        }
+        if self.algorithm != ALGORITHM || self.kdf != KDF {
+            bail!(
+                "Unsupported encryption scheme: {} / {}",
+                self.algorithm,
+                self.kdf
+            );
+        }
+
+        let salt = BASE64
+            .decode(&self.salt)
+            .context("Invalid salt in vault.json")?;
+        let verifier = BASE64
+            .decode(&self.verifier)
+            .context("Invalid verifier in vault.json")?;
+
+        let key = VaultKey::derive(passphrase, &salt, &self.kdf_params)?;
+        match decrypt(&key, &verifier) {
+            Ok(plain) if plain == VERIFIER_PLAINTEXT => Ok(key),
+            _ => bail!("Incorrect passphrase"),
+        }
+    }
+
+    /// Check an age identity file against the verifier.
+    pub fn unlock_with_identity(&self, identity_file: &str) -> Result<VaultKey> {
+        if !self.is_age() {
+            bail!("This vault is encrypted with a passphrase, not age");
+        }
+
+        let verifier = BASE64
+            .decode(&self.verifier)
+            .context("Invalid verifier in vault.json")?;
+        match age_backend::decrypt(identity_file.as_bytes(), &verifier) {
+            Ok(plain) if plain == VERIFIER_PLAINTEXT => Ok(VaultKey {
+                material: KeyMaterial::Age {
+                    recipients: Arc::new(age_backend::Recipients::parse(&self.recipients)?),
+                    identity: identity_file.as_bytes().to_vec(),
+                },
+            }),
+            _ => bail!("The age identity cannot decrypt this vault"),
+        }
+    }
+
+    /// Check whether a passphrase is correct.
+    pub fn verify(&self, passphrase: &str) -> bool {
+        self.unlock(passphrase).is_ok()
+    }
+}
+
+/// Check whether data is an encrypted blob (either backend).
+pub fn is_encrypted(data: &[u8]) -> bool {
+    is_aes_blob(data) || age_backend::is_age(data)
+}
+
+fn is_aes_blob(data: &[u8]) -> bool {
+    data.len() >= MAGIC.len() + NONCE_LEN && data.starts_with(MAGIC)
+}
+
+fn cipher(bytes: &[u8; KEY_LEN]) -> Aes256Gcm {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(bytes))
+}
+
+/// Encrypt bytes into an encrypted blob.
+pub fn encrypt(key: &VaultKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let bytes = match &key.material {
+        KeyMaterial::Aes(bytes) => bytes,
+        KeyMaterial::Age { recipients, .. } => return age_backend::encrypt(recipients, plaintext),
+    };
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(bytes)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("Encryption failed"))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt an encrypted blob. Fails on a wrong key or tampered data.
+pub fn decrypt(key: &VaultKey, data: &[u8]) -> Result<Vec<u8>> {
+    let bytes = match &key.material {
+        KeyMaterial::Aes(bytes) => bytes,
+        KeyMaterial::Age { identity, .. } => {
+            if !age_backend::is_age(data) {
+                bail!("Data is not an age file");
+            }
+            return age_backend::decrypt(identity, data);
+        }
+    };
+    if !is_aes_blob(data) {
+        bail!("Data is not an EchoVault encrypted blob");
+    }
+
+    let (nonce, ciphertext) = data[MAGIC.len()..].split_at(NONCE_LEN);
+    cipher(bytes)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Decryption failed (wrong key or corrupted data)"))
+}
+
+/// Encrypt a file into `dst`.
+pub fn encrypt_file(key: &VaultKey, src: &Path, dst: &Path) -> Result<()> {
+    let plaintext = mapped::read(src).with_context(|| format!("Failed to read {:?}", src))?;
+    let blob = encrypt(key, &plaintext)?;
+    atomic::write(dst, blob).with_context(|| format!("Failed to write {:?}", dst))
+}
+
+/// Decrypt a file into `dst`.
+pub fn decrypt_file(key: &VaultKey, src: &Path, dst: &Path) -> Result<()> {
+    let blob = mapped::read(src).with_context(|| format!("Failed to read {:?}", src))?;
+    let plaintext = decrypt(key, &blob).with_context(|| format!("Cannot decrypt {:?}", src))?;
+    atomic::write(dst, plaintext).with_context(|| format!("Failed to write {:?}", dst))
+}
+
+/// Read the passphrase from `ECHOVAULT_PASSPHRASE`, if set.
+pub fn passphrase_from_env() -> Option<SecretString> {
+    std::env::var(PASSPHRASE_ENV)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(SecretString::from)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use secrecy::ExposeSecret;
+
+    /// Cheap KDF parameters so tests run fast
+    pub(crate) fn test_params() -> KdfParams {
+        KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let (_, key) = EncryptionMeta::create_with_params("secret", test_params()).unwrap();
+
+        let blob = encrypt(&key, b"hello vault").unwrap();
+        assert!(is_encrypted(&blob));
+        assert_ne!(&blob[MAGIC.len() + NONCE_LEN..], b"hello vault");
+        assert_eq!(decrypt(&key, &blob).unwrap(), b"hello vault");
+
+        // Nonces are random: same plaintext gives different blobs
+        assert_ne!(blob, encrypt(&key, b"hello vault").unwrap());
+    }
+
+    #[test]
+    fn test_unlock_checks_passphrase() {
+        let (meta, key) = EncryptionMeta::create_with_params("correct", test_params()).unwrap();
+        assert!(meta.verify("correct"));
+        assert!(!meta.verify("wrong"));
+
+        // Wrong key cannot decrypt
+        let blob = encrypt(&key, b"data").unwrap();
+        let (_, other) = EncryptionMeta::create_with_params("other", test_params()).unwrap();
+        assert!(decrypt(&other, &blob).is_err());
+
+        // Same passphrase + salt derives the same key
+        let unlocked = meta.unlock("correct").unwrap();
+        assert_eq!(decrypt(&unlocked, &blob).unwrap(), b"data");
+    }
+
+    #[test]
+    fn test_tampered_blob_rejected() {
+        let (_, key) = EncryptionMeta::create_with_params("secret", test_params()).unwrap();
+        let mut blob = encrypt(&key, b"payload").unwrap();
+        let last = blob.len() - 1;
+        blob[last] ^= 0xff;
+        assert!(decrypt(&key, &blob).is_err());
+        assert!(decrypt(&key, b"plain text").is_err());
+    }
+
+    #[test]
+    fn test_age_meta_unlock() {
+        let (identity, recipient) = age_backend::generate_identity();
+        let (other, _) = age_backend::generate_identity();
+        let meta = EncryptionMeta::create_age(vec![recipient]).unwrap();
+        assert!(meta.is_age());
+        assert!(meta.unlock("passphrase").is_err());
+        assert!(meta.unlock_with_identity(other.expose_secret()).is_err());
+
+        let key = meta.unlock_with_identity(identity.expose_secret()).unwrap();
+        assert_eq!(format!("{:?}", key), "VaultKey(<redacted>)");
+        let blob = encrypt(&key, b"hello age").unwrap();
+        assert!(is_encrypted(&blob));
+        assert_eq!(decrypt(&key, &blob).unwrap(), b"hello age");
+
+        // vault.json roundtrip keeps recipients and omits the salt
+        let json = serde_json::to_string(&meta).unwrap();
+        assert!(!json.contains("\"salt\""));
+        assert_eq!(serde_json::from_str::<EncryptionMeta>(&json).unwrap(), meta);
+    }
+}
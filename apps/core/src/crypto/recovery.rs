@@ -0,0 +1,236 @@
+//! Recovery codes - a 24-word backup that unlocks the vault without the passphrase.
+//!
+//! The recovery code is a random X25519 secret shown once as a BIP39 word
+//! list. Only its public half is stored in vault.json, together with the vault
+//! key encrypted to it (age format). Because wrapping needs only the public
+//! half, key rotation re-wraps the new key and the same words keep working.
+//!
+//! Recovery codes apply to passphrase vaults; age vaults should list a backup
+//! recipient instead.
+
+use super::{
+    age_backend, decrypt, EncryptionMeta, KeyMaterial, VaultKey, KEY_LEN, VERIFIER_PLAINTEXT,
+};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use age::x25519;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bech32::{ToBase32, Variant};
+use bip39::Mnemonic;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Number of words in a recovery code
+pub const RECOVERY_WORDS: usize = 24;
+
+/// Bech32 prefix of age X25519 secret keys
+const SECRET_KEY_PREFIX: &str = "age-secret-key-";
+
+/// Recovery settings stored in vault.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryMeta {
+    /// age recipient (public half) of the recovery code
+    pub recipient: String,
+    /// Base64 vault key, age-encrypted to `recipient`
+    pub wrapped_key: String,
+    /// When the recovery code was generated
+    pub created_at: String,
+}
+
+/// Recovery secret (256 bits). Cleared on drop and never printed.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct RecoveryCode {
+    entropy: [u8; KEY_LEN],
+}
+
+impl RecoveryCode {
+    /// Generate a new random recovery code.
+    pub fn generate() -> Self {
+        let mut entropy = [0u8; KEY_LEN];
+        OsRng.fill_bytes(&mut entropy);
+        Self { entropy }
+    }
+
+    /// Parse a recovery code typed by the user (BIP39 English words, checksum checked).
+    pub fn parse(phrase: &str) -> Result<Self> {
+        let normalized = Zeroizing::new(
+            phrase
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        let mnemonic = Mnemonic::parse_normalized(&normalized)
+            .map_err(|e| anyhow!("Invalid recovery code: {}", e))?;
+        let (bytes, len) = mnemonic.to_entropy_array();
+        let bytes = Zeroizing::new(bytes);
+        if len != KEY_LEN {
+            bail!("Recovery code must have {} words", RECOVERY_WORDS);
+        }
+
+        let mut entropy = [0u8; KEY_LEN];
+        entropy.copy_from_slice(&bytes[..KEY_LEN]);
+        Ok(Self { entropy })
+    }
+
+    /// The recovery words, in order.
+    pub fn words(&self) -> Vec<&'static str> {
+        Mnemonic::from_entropy(&self.entropy)
+            .expect("32 bytes is a valid entropy length")
+            .words()
+            .collect()
+    }
+
+    /// Pick `count` distinct random word positions (1-based) to confirm the code was written down.
+    pub fn challenge(count: usize) -> Vec<usize> {
+        let mut positions: Vec<usize> = Vec::with_capacity(count);
+        while positions.len() < count.min(RECOVERY_WORDS) {
+            let position = (OsRng.next_u32() as usize % RECOVERY_WORDS) + 1;
+            if !positions.contains(&position) {
+                positions.push(position);
+            }
+        }
+        positions.sort_unstable();
+        positions
+    }
+
+    /// Check user answers for challenge positions (1-based).
+    pub fn check_words(&self, answers: &[(usize, String)]) -> bool {
+        let words = self.words();
+        answers.iter().all(|(position, word)| {
+            words
+                .get(position.wrapping_sub(1))
+                .is_some_and(|expected| expected.eq_ignore_ascii_case(word.trim()))
+        })
+    }
+
+    /// The code as an age identity file.
+    fn identity(&self) -> Zeroizing<String> {
+        let encoded = Zeroizing::new(
+            bech32::encode(SECRET_KEY_PREFIX, self.entropy.to_base32(), Variant::Bech32)
+                .expect("HRP is valid"),
+        );
+        Zeroizing::new(encoded.to_uppercase())
+    }
+
+    /// Public age recipient of the code.
+    fn recipient(&self) -> Result<String> {
+        let identity: x25519::Identity = self
+            .identity()
+            .parse()
+            .map_err(|e| anyhow!("Invalid recovery identity: {}", e))?;
+        Ok(identity.to_public().to_string())
+    }
+}
+
+impl fmt::Debug for RecoveryCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecoveryCode(<redacted>)")
+    }
+}
+
+/// Encrypt the vault key to a recovery recipient.
+fn wrap(key: &VaultKey, recipient: &str) -> Result<RecoveryMeta> {
+    let KeyMaterial::Aes(bytes) = &key.material else {
+        bail!("Recovery codes are only available for passphrase-encrypted vaults");
+    };
+    let wrapped = age_backend::encrypt(
+        &age_backend::Recipients::parse(&[recipient.to_string()])?,
+        bytes,
+    )?;
+    Ok(RecoveryMeta {
+        recipient: recipient.to_string(),
+        wrapped_key: BASE64.encode(wrapped),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+impl EncryptionMeta {
+    /// Generate a new recovery code for this vault (replacing any previous one).
+    pub fn add_recovery(&mut self, key: &VaultKey) -> Result<RecoveryCode> {
+        if self.is_age() {
+            bail!("Recovery codes are only available for passphrase-encrypted vaults; add a backup age recipient instead");
+        }
+        let code = RecoveryCode::generate();
+        self.recovery = Some(wrap(key, &code.recipient()?)?);
+        Ok(code)
+    }
+
+    /// Re-wrap the existing recovery code for a new vault key (after rotation).
+    pub(super) fn rewrap_recovery(&mut self, new_key: &VaultKey) -> Result<()> {
+        if let Some(recovery) = &self.recovery {
+            self.recovery = Some(wrap(new_key, &recovery.recipient)?);
+        }
+        Ok(())
+    }
+
+    /// Unlock the vault key with a recovery code.
+    pub fn unlock_with_recovery(&self, code: &RecoveryCode) -> Result<VaultKey> {
+        let Some(recovery) = &self.recovery else {
+            bail!("This vault has no recovery code");
+        };
+        if code.recipient()? != recovery.recipient {
+            bail!("Recovery code does not match this vault");
+        }
+
+        let wrapped = BASE64
+            .decode(&recovery.wrapped_key)
+            .context("Invalid recovery key in vault.json")?;
+        let bytes = Zeroizing::new(age_backend::decrypt(code.identity().as_bytes(), &wrapped)?);
+        let bytes: [u8; KEY_LEN] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("Invalid recovery key in vault.json"))?;
+        let key = VaultKey {
+            material: KeyMaterial::Aes(bytes),
+        };
+
+        let verifier = BASE64
+            .decode(&self.verifier)
+            .context("Invalid verifier in vault.json")?;
+        match decrypt(&key, &verifier) {
+            Ok(plain) if plain == VERIFIER_PLAINTEXT => Ok(key),
+            _ => bail!("Recovery key does not unlock this vault"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{encrypt, tests::test_params};
+
+    #[test]
+    fn test_recovery_code_unlocks_vault() {
+        let (mut meta, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+        let code = meta.add_recovery(&key).unwrap();
+        assert_eq!(code.words().len(), RECOVERY_WORDS);
+
+        // Words survive a print / retype roundtrip (case and spacing are ignored)
+        let typed = code.words().join("  ").to_uppercase();
+        let parsed = RecoveryCode::parse(&typed).unwrap();
+        let recovered = meta.unlock_with_recovery(&parsed).unwrap();
+        let blob = encrypt(&key, b"data").unwrap();
+        assert_eq!(decrypt(&recovered, &blob).unwrap(), b"data");
+
+        // Challenge answers
+        let words = code.words();
+        let positions = RecoveryCode::challenge(3);
+        assert_eq!(positions.len(), 3);
+        let answers: Vec<_> = positions
+            .iter()
+            .map(|&p| (p, words[p - 1].to_string()))
+            .collect();
+        assert!(code.check_words(&answers));
+        assert!(!code.check_words(&[(positions[0], "wrong".to_string())]));
+
+        // Another code is rejected, and so is text that is not a word list
+        assert!(meta
+            .unlock_with_recovery(&RecoveryCode::generate())
+            .is_err());
+        assert!(RecoveryCode::parse("not a recovery code").is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! Key rotation - re-encrypt the staged vault under a new passphrase.
+//!
+//! Files are re-encrypted one at a time and replaced atomically. A journal
+//! (`<vault>/.key-rotation.json`) records the old and new key metadata, so an
+//! interrupted rotation can be resumed: AES-GCM authentication tells which key
+//! a file is currently encrypted with, so no per-file progress is tracked.
+//! vault.json is switched to the new key only after every file is rotated.
+
+use super::manifest;
+use super::staging;
+use super::{decrypt, encrypt, EncryptionMeta, VaultKey};
+use crate::storage::{atomic, VaultDb};
+use crate::vault::VaultMetadata;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::info;
+
+/// Journal file of an in-progress rotation (inside the vault, never synced)
+pub const JOURNAL_FILE: &str = ".key-rotation.json";
+
+/// Old and new key metadata of an in-progress rotation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationJournal {
+    /// Key the vault is being rotated away from
+    pub old: EncryptionMeta,
+    /// Key the vault is being rotated to
+    pub new: EncryptionMeta,
+    /// When the rotation started
+    pub started_at: String,
+}
+
+/// Result of a key rotation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RotationStats {
+    /// Files re-encrypted in this run
+    pub rotated: usize,
+    /// Files already under the new key (rotated before an interruption)
+    pub skipped: usize,
+    /// Whether an interrupted rotation was resumed
+    pub resumed: bool,
+}
+
+fn journal_path(vault_dir: &Path) -> PathBuf {
+    vault_dir.join(JOURNAL_FILE)
+}
+
+/// Interrupted rotation of this vault, if any.
+pub fn pending_rotation(vault_dir: &Path) -> Option<RotationJournal> {
+    let content = fs::read_to_string(journal_path(vault_dir)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Fail if a rotation is in progress (syncing mixed keys would corrupt the remote).
+pub fn ensure_no_rotation(vault_dir: &Path) -> Result<()> {
+    if journal_path(vault_dir).exists() {
+        bail!("Key rotation in progress. Finish it with 'echovault-cli encryption rotate-key'.");
+    }
+    Ok(())
+}
+
+/// Re-encrypt all staged files under a new passphrase.
+///
+/// Resumes an interrupted rotation when a journal exists; `new_passphrase`
+/// must then match the one used when it started.
+/// Returns the new key and rotation stats.
+pub fn rotate_key(
+    vault_dir: &Path,
+    old_passphrase: &str,
+    new_passphrase: &str,
+) -> Result<(VaultKey, RotationStats)> {
+    let Some(current) = VaultMetadata::load(vault_dir)?.encryption else {
+        bail!("Encryption is not enabled");
+    };
+    let old_key = current.unlock(old_passphrase)?;
+    rotate_with_key(vault_dir, &old_key, new_passphrase)
+}
+
+/// Re-encrypt all staged files under a new passphrase, given the unlocked
+/// current key (e.g. from a recovery code).
+///
+/// A recovery code, if any, is re-wrapped so it keeps working.
+pub fn rotate_with_key(
+    vault_dir: &Path,
+    old_key: &VaultKey,
+    new_passphrase: &str,
+) -> Result<(VaultKey, RotationStats)> {
+    let mut metadata = VaultMetadata::load(vault_dir)?;
+    let Some(current) = metadata.encryption.clone() else {
+        bail!("Encryption is not enabled");
+    };
+    if current.is_age() {
+        bail!("Key rotation applies to passphrase-encrypted vaults; age vaults change recipients instead");
+    }
+
+    let mut stats = RotationStats::default();
+    let (new_meta, new_key) = match pending_rotation(vault_dir) {
+        Some(journal) => {
+            if journal.old != current {
+                bail!(
+                    "Rotation journal does not match vault.json; remove {} to start over",
+                    JOURNAL_FILE
+                );
+            }
+            let key = journal
+                .new
+                .unlock(new_passphrase)
+                .context("New passphrase does not match the interrupted rotation")?;
+            stats.resumed = true;
+            (journal.new, key)
+        }
+        None => {
+            let (mut meta, key) =
+                EncryptionMeta::create_with_params(new_passphrase, current.kdf_params)?;
+            meta.recovery = current.recovery.clone();
+            meta.rewrap_recovery(&key)?;
+            let journal = RotationJournal {
+                old: current.clone(),
+                new: meta.clone(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+            };
+            write_atomic(
+                &journal_path(vault_dir),
+                serde_json::to_string_pretty(&journal)?.as_bytes(),
+            )?;
+            (meta, key)
+        }
+    };
+
+    let staging = staging::staging_dir(vault_dir);
+    for path in staging::encrypted_files(&staging)? {
+        let blob = fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        if decrypt(&new_key, &blob).is_ok() {
+            stats.skipped += 1;
+            continue;
+        }
+        let plaintext =
+            decrypt(old_key, &blob).with_context(|| format!("Cannot decrypt {:?}", path))?;
+        write_atomic(&path, &encrypt(&new_key, &plaintext)?)?;
+
+        // Fresh mtime so the next push re-uploads the file; the plaintext
+        // counterpart gets the same mtime so it is not decrypted again.
+        let now = SystemTime::now();
+        set_mtime(&path, now)?;
+        let plain_path = vault_dir.join(path.strip_prefix(&staging)?.with_extension(""));
+        if plain_path.exists() {
+            set_mtime(&plain_path, now)?;
+        }
+        stats.rotated += 1;
+    }
+
+    // Hashes changed and the manifest must be signed with the new key
+    manifest::update(&staging, &new_key, BTreeMap::new())?;
+
+    metadata.encryption = Some(new_meta);
+    metadata.save(vault_dir)?;
+    fs::remove_file(journal_path(vault_dir))?;
+
+    let details = format!(
+        "rotated={} skipped={} resumed={}",
+        stats.rotated, stats.skipped, stats.resumed
+    );
+    VaultDb::open(vault_dir)?.log_sync("rotate-key", Some(&details))?;
+    info!("[crypto] Key rotation complete: {}", details);
+
+    Ok((new_key, stats))
+}
+
+/// Write to a temporary sibling, then rename over the target.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    atomic::write(path, data).with_context(|| format!("Failed to replace {:?}", path))
+}
+
+fn set_mtime(path: &Path, mtime: SystemTime) -> Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(mtime)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::tests::test_params;
+    use tempfile::TempDir;
+
+    fn encrypted_vault(files: usize) -> (TempDir, VaultKey) {
+        let temp = TempDir::new().unwrap();
+        let (meta, key) = EncryptionMeta::create_with_params("old", test_params()).unwrap();
+        let mut metadata = VaultMetadata::new();
+        metadata.encryption = Some(meta);
+        metadata.save(temp.path()).unwrap();
+
+        let dir = temp.path().join("sessions/cursor");
+        fs::create_dir_all(&dir).unwrap();
+        for i in 0..files {
+            fs::write(dir.join(format!("{}.json", i)), format!("session {}", i)).unwrap();
+        }
+        staging::seal(temp.path(), &key).unwrap();
+        (temp, key)
+    }
+
+    #[test]
+    fn test_rotate_key() {
+        let (temp, _) = encrypted_vault(3);
+
+        assert!(rotate_key(temp.path(), "wrong", "new").is_err());
+
+        let (new_key, stats) = rotate_key(temp.path(), "old", "new").unwrap();
+        assert_eq!(stats.rotated, 3);
+        assert!(!stats.resumed);
+        assert!(pending_rotation(temp.path()).is_none());
+
+        let meta = VaultMetadata::load(temp.path())
+            .unwrap()
+            .encryption
+            .unwrap();
+        assert!(meta.verify("new"));
+        assert!(!meta.verify("old"));
+
+        let blob =
+            fs::read(staging::staging_dir(temp.path()).join("sessions/cursor/0.json.enc")).unwrap();
+        assert_eq!(decrypt(&new_key, &blob).unwrap(), b"session 0");
+    }
+
+    #[test]
+    fn test_recovery_code_survives_rotation() {
+        let (temp, key) = encrypted_vault(1);
+        let mut metadata = VaultMetadata::load(temp.path()).unwrap();
+        let code = metadata
+            .encryption
+            .as_mut()
+            .unwrap()
+            .add_recovery(&key)
+            .unwrap();
+        metadata.save(temp.path()).unwrap();
+
+        // Recover: unlock with the code, then set a new passphrase
+        let meta = metadata.encryption.unwrap();
+        let recovered = meta.unlock_with_recovery(&code).unwrap();
+        rotate_with_key(temp.path(), &recovered, "new").unwrap();
+
+        let meta = VaultMetadata::load(temp.path())
+            .unwrap()
+            .encryption
+            .unwrap();
+        assert!(meta.verify("new"));
+        let after = meta.unlock_with_recovery(&code).unwrap();
+        let blob =
+            fs::read(staging::staging_dir(temp.path()).join("sessions/cursor/0.json.enc")).unwrap();
+        assert_eq!(decrypt(&after, &blob).unwrap(), b"session 0");
+    }
+
+    #[test]
+    fn test_rotate_key_resumes() {
+        let (temp, old_key) = encrypted_vault(2);
+        let current = VaultMetadata::load(temp.path())
+            .unwrap()
+            .encryption
+            .unwrap();
+
+        // Simulate a crash after one file was rotated
+        let (new_meta, new_key) = EncryptionMeta::create_with_params("new", test_params()).unwrap();
+        let journal = RotationJournal {
+            old: current,
+            new: new_meta,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        fs::write(
+            journal_path(temp.path()),
+            serde_json::to_string(&journal).unwrap(),
+        )
+        .unwrap();
+        let first = staging::staging_dir(temp.path()).join("sessions/cursor/0.json.enc");
+        let plain = decrypt(&old_key, &fs::read(&first).unwrap()).unwrap();
+        fs::write(&first, encrypt(&new_key, &plain).unwrap()).unwrap();
+
+        assert!(ensure_no_rotation(temp.path()).is_err());
+        assert!(rotate_key(temp.path(), "old", "other").is_err());
+
+        let (_, stats) = rotate_key(temp.path(), "old", "new").unwrap();
+        assert!(stats.resumed);
+        assert_eq!(stats.rotated, 1);
+        assert_eq!(stats.skipped, 1);
+        assert!(ensure_no_rotation(temp.path()).is_ok());
+    }
+}
@@ -0,0 +1,646 @@
+//! Encrypted staging directory used as the sync root of encrypted vaults.
+//!
+//! Layout of `<vault>/.encrypted/` (mirrors the remote):
+//! ```text
+//! .encrypted/
+//! ├── vault.json              # Plaintext (holds salt + verifier)
+//! ├── vault.db.enc            # Encrypted snapshot of the session index
+//! ├── embeddings.db.enc       # Encrypted snapshot of chunks + embeddings
+//! ├── manifest.json           # Signed size/hash list of the .enc files
+//! ├── sessions/**/<file>.enc  # Encrypted raw session files
+//! └── parsed/**/<file>.enc    # Encrypted parsed Markdown
+//! ```
+//!
+//! Files (and database snapshots) larger than [`chunked::CHUNK_SIZE`] are
+//! stored as `<file>.chunks/` directories of compressed, encrypted parts
+//! instead of a single `.enc` file.
+//!
+//! Work is incremental: a file is (re-)processed only when its source is newer
+//! than its counterpart, and outputs copy the source mtime so a seal followed
+//! by an open does not bounce files back and forth.
+
+use super::manifest::{self, Manifest, ManifestEntry};
+use super::{decrypt, encrypt, EncryptionMeta, VaultKey};
+use crate::storage::{atomic, chunked, mapped};
+use crate::utils::Walk;
+use crate::vault::VaultMetadata;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+/// Staging directory name inside the vault
+pub const STAGING_DIR: &str = ".encrypted";
+
+/// Vault subdirectories whose files are encrypted before upload
+pub const ENCRYPTED_DIRS: &[&str] = &["sessions", "parsed"];
+
+/// Extension appended to encrypted files
+pub const ENCRYPTED_EXT: &str = "enc";
+
+/// SQLite databases in the vault root, encrypted as consistent snapshots
+pub const ENCRYPTED_DBS: &[&str] = &["vault.db", "embeddings.db"];
+
+/// Vault files copied as-is into the staging directory
+const PLAIN_FILES: &[&str] = &["vault.json"];
+
+/// Result of sealing the vault before a push.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SealStats {
+    /// Files newly encrypted into staging
+    pub encrypted: usize,
+    /// Plain metadata files copied into staging
+    pub copied: usize,
+}
+
+/// Result of opening the staging directory after a pull.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpenStats {
+    /// Files decrypted into the vault
+    pub decrypted: usize,
+    /// Files not decrypted because the manifest does not list them yet
+    pub unlisted: usize,
+}
+
+/// Path of the staging directory for a vault.
+pub fn staging_dir(vault_dir: &Path) -> PathBuf {
+    vault_dir.join(STAGING_DIR)
+}
+
+/// Encryption settings found in the pulled staging directory, if any.
+///
+/// Used when setting up encryption on another machine so it joins the
+/// existing key instead of creating a new one.
+pub fn remote_meta(vault_dir: &Path) -> Option<EncryptionMeta> {
+    VaultMetadata::load(&staging_dir(vault_dir))
+        .ok()
+        .and_then(|meta| meta.encryption)
+}
+
+/// Encrypt changed session files and parsed output into the staging directory.
+pub fn seal(vault_dir: &Path, key: &VaultKey) -> Result<SealStats> {
+    super::rotate::ensure_no_rotation(vault_dir)?;
+    let staging = staging_dir(vault_dir);
+    let mut stats = SealStats::default();
+    let mut changed = BTreeMap::new();
+
+    for dir in ENCRYPTED_DIRS {
+        for src in collect_files(&vault_dir.join(dir))? {
+            if chunked::is_chunk_part(&src) {
+                continue;
+            }
+            let rel = src.strip_prefix(vault_dir)?;
+            let dst = with_enc_ext(&staging.join(rel));
+            let chunks = chunked::chunks_dir(&staging.join(rel));
+
+            if chunked::needs_chunking(fs::metadata(&src)?.len()) {
+                let index = chunked::index_path(&chunks, true);
+                if !is_newer(&src, &index) {
+                    continue;
+                }
+                chunked::write_file(&src, &chunks, Some(key))?;
+                copy_mtime(&src, &index)?;
+                remove_stale(&dst)?;
+                stats.encrypted += 1;
+                continue;
+            }
+
+            if !is_newer(&src, &dst) {
+                continue;
+            }
+            ensure_parent(&dst)?;
+            let plaintext =
+                mapped::read(&src).with_context(|| format!("Failed to read {:?}", src))?;
+            let blob = encrypt(key, &plaintext)?;
+            atomic::write(&dst, &blob).with_context(|| format!("Failed to write {:?}", dst))?;
+            copy_mtime(&src, &dst)?;
+            changed.insert(
+                manifest::manifest_key(&staging, &dst)?,
+                ManifestEntry::for_blob(&blob),
+            );
+            remove_stale(&chunks)?;
+            stats.encrypted += 1;
+        }
+    }
+
+    for name in ENCRYPTED_DBS {
+        let src = vault_dir.join(name);
+        let dst = with_enc_ext(&staging.join(name));
+        let chunks = chunked::chunks_dir(&staging.join(name));
+        let index = chunked::index_path(&chunks, true);
+        let sealed = if index.exists() { &index } else { &dst };
+        if !src.exists() || !is_newer_db(&src, sealed) {
+            continue;
+        }
+        fs::create_dir_all(&staging)?;
+        let written = with_db_snapshot(&src, &staging, |snapshot| {
+            if chunked::needs_chunking(snapshot.len() as u64) {
+                chunked::write(snapshot, &chunks, Some(key))?;
+                remove_stale(&dst)?;
+                Ok(index)
+            } else {
+                let blob = encrypt(key, snapshot)?;
+                atomic::write(&dst, &blob).with_context(|| format!("Failed to write {:?}", dst))?;
+                changed.insert(
+                    manifest::manifest_key(&staging, &dst)?,
+                    ManifestEntry::for_blob(&blob),
+                );
+                remove_stale(&chunks)?;
+                Ok(dst)
+            }
+        })?;
+        if let Some(mtime) = db_modified(&src) {
+            set_mtime(&written, mtime)?;
+        }
+        stats.encrypted += 1;
+
+        // Plaintext copy left by versions that did not encrypt databases
+        let legacy = staging.join(name);
+        if legacy.exists() {
+            fs::remove_file(&legacy)?;
+        }
+    }
+
+    for name in PLAIN_FILES {
+        let src = vault_dir.join(name);
+        let dst = staging.join(name);
+        if src.exists() && is_newer(&src, &dst) {
+            ensure_parent(&dst)?;
+            fs::copy(&src, &dst).with_context(|| format!("Failed to copy {:?}", src))?;
+            copy_mtime(&src, &dst)?;
+            stats.copied += 1;
+        }
+    }
+
+    if staging.exists() {
+        manifest::update(&staging, key, changed)?;
+    }
+
+    debug!(
+        "[crypto] Sealed {} files ({} plain) into {:?}",
+        stats.encrypted, stats.copied, staging
+    );
+    Ok(stats)
+}
+
+/// Decrypt pulled files from the staging directory into the vault.
+///
+/// The local vault.json is kept: it is the source of truth for this machine.
+/// Each file is checked against the signed manifest before decrypting; a
+/// mismatch aborts the open. Encrypted files without a manifest are refused
+/// unless `allow_missing_manifest` (`[encryption] allow_missing_manifest`,
+/// for remotes sealed before manifests existed).
+pub fn open(vault_dir: &Path, key: &VaultKey, allow_missing_manifest: bool) -> Result<OpenStats> {
+    super::rotate::ensure_no_rotation(vault_dir)?;
+    ensure_same_key(vault_dir)?;
+    let staging = staging_dir(vault_dir);
+    let mut stats = OpenStats::default();
+    let manifest = Manifest::load_verified(&staging, key)?;
+    if manifest.is_none()
+        && (!encrypted_files(&staging)?.is_empty() || !staged_chunk_dirs(&staging).is_empty())
+    {
+        if !allow_missing_manifest {
+            bail!(
+                "{:?} holds encrypted files but no {} (removed or never written). If the \
+                 remote was sealed by a version without manifests, set [encryption] \
+                 allow_missing_manifest = true for one sync.",
+                staging,
+                manifest::MANIFEST_FILE
+            );
+        }
+        warn!(
+            "[crypto] No manifest in {:?}, decrypting files unverified",
+            staging
+        );
+    }
+
+    for src in encrypted_files(&staging)? {
+        if chunked::is_chunk_part(&src) {
+            continue;
+        }
+        let dst = vault_dir.join(src.strip_prefix(&staging)?.with_extension(""));
+        let is_db = src.parent() == Some(staging.as_path());
+        let newer = if is_db {
+            is_newer_db(&src, &dst)
+        } else {
+            is_newer(&src, &dst)
+        };
+        if !newer {
+            continue;
+        }
+
+        let blob = mapped::read(&src).with_context(|| format!("Failed to read {:?}", src))?;
+        if let Some(manifest) = &manifest {
+            let name = manifest::manifest_key(&staging, &src)?;
+            if !manifest.check(&name, &blob)? {
+                warn!("[crypto] {} is not in the manifest yet, skipping", name);
+                stats.unlisted += 1;
+                continue;
+            }
+        }
+        let plaintext = decrypt(key, &blob).with_context(|| format!("Cannot decrypt {:?}", src))?;
+        ensure_parent(&dst)?;
+        if is_db {
+            replace_db(&dst, &plaintext)?;
+        } else {
+            atomic::write(&dst, plaintext).with_context(|| format!("Failed to write {:?}", dst))?;
+        }
+        copy_mtime(&src, &dst)?;
+        stats.decrypted += 1;
+    }
+
+    for dir in staged_chunk_dirs(&staging) {
+        let Some(original) = chunked::original_path(&dir) else {
+            continue;
+        };
+        let index = chunked::index_path(&dir, true);
+        let dst = vault_dir.join(original.strip_prefix(&staging)?);
+        let is_db = original.parent() == Some(staging.as_path());
+        let newer = if is_db {
+            is_newer_db(&index, &dst)
+        } else {
+            is_newer(&index, &dst)
+        };
+        if !newer {
+            continue;
+        }
+
+        if let Some(manifest) = &manifest {
+            let mut listed = true;
+            for part in collect_files(&dir)? {
+                let name = manifest::manifest_key(&staging, &part)?;
+                listed &= manifest.check(&name, &fs::read(&part)?)?;
+            }
+            if !listed {
+                warn!("[crypto] {:?} is not in the manifest yet, skipping", dir);
+                stats.unlisted += 1;
+                continue;
+            }
+        }
+        if is_db {
+            replace_db(&dst, &chunked::read(&dir, Some(key))?)?;
+        } else {
+            chunked::reassemble(&dir, &dst, Some(key))?;
+        }
+        copy_mtime(&index, &dst)?;
+        stats.decrypted += 1;
+    }
+
+    debug!(
+        "[crypto] Opened {} files ({} unlisted) from {:?}",
+        stats.decrypted, stats.unlisted, staging
+    );
+    Ok(stats)
+}
+
+/// Fail if the pulled vault.json was re-keyed on another machine.
+///
+/// An older remote key is fine: it is replaced by the next push.
+fn ensure_same_key(vault_dir: &Path) -> Result<()> {
+    let Some(remote) = remote_meta(vault_dir) else {
+        return Ok(());
+    };
+    let local = VaultMetadata::load(vault_dir)?.encryption;
+    let Some(local) = local else {
+        return Ok(());
+    };
+    if remote == local {
+        return Ok(());
+    }
+
+    let created =
+        |meta: &EncryptionMeta| chrono::DateTime::parse_from_rfc3339(&meta.created_at).ok();
+    if created(&remote) > created(&local) {
+        bail!(
+            "The vault key was changed on another machine. Re-enter the new passphrase \
+             with 'echovault-cli encryption setup'."
+        );
+    }
+    Ok(())
+}
+
+/// `a/b/file.json` -> `a/b/file.json.enc`
+fn with_enc_ext(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ENCRYPTED_EXT);
+    PathBuf::from(name)
+}
+
+/// All encrypted files in a staging directory (session files, parsed output, databases).
+pub(super) fn encrypted_files(staging: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in ENCRYPTED_DIRS {
+        files.extend(
+            collect_files(&staging.join(dir))?
+                .into_iter()
+                .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(ENCRYPTED_EXT))
+                .filter(|p| !chunked::is_partial(p)),
+        );
+    }
+    files.extend(
+        ENCRYPTED_DBS
+            .iter()
+            .map(|name| with_enc_ext(&staging.join(name)))
+            .filter(|path| path.is_file()),
+    );
+    for name in ENCRYPTED_DBS {
+        files.extend(
+            collect_files(&chunked::chunks_dir(&staging.join(name)))?
+                .into_iter()
+                .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(ENCRYPTED_EXT))
+                .filter(|p| !chunked::is_partial(p)),
+        );
+    }
+    Ok(files)
+}
+
+/// Chunk directories in a staging directory (large files and database snapshots).
+fn staged_chunk_dirs(staging: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = ENCRYPTED_DIRS
+        .iter()
+        .flat_map(|dir| chunked::find_chunk_dirs(&staging.join(dir)))
+        .collect();
+    dirs.extend(
+        ENCRYPTED_DBS
+            .iter()
+            .map(|name| chunked::chunks_dir(&staging.join(name)))
+            .filter(|dir| dir.is_dir()),
+    );
+    dirs
+}
+
+/// `<db>-wal` next to a database file.
+fn wal_path(db: &Path) -> PathBuf {
+    let mut name = db.as_os_str().to_os_string();
+    name.push("-wal");
+    PathBuf::from(name)
+}
+
+/// Last write to a database, including writes still in its WAL.
+fn db_modified(db: &Path) -> Option<SystemTime> {
+    modified(db).max(modified(&wal_path(db)))
+}
+
+/// True if `src` is newer than `dst`, where either may be a live database.
+fn is_newer_db(src: &Path, dst: &Path) -> bool {
+    match (db_modified(src), db_modified(dst)) {
+        (Some(src), Some(dst)) => src > dst,
+        _ => true,
+    }
+}
+
+/// Consistent copy of a live SQLite database (WAL included), passed to `f`.
+///
+/// Uses `VACUUM INTO` and maps the snapshot; falls back to reading the raw
+/// file if SQLite cannot open it. The snapshot is removed afterwards.
+fn with_db_snapshot<R>(
+    db: &Path,
+    scratch_dir: &Path,
+    f: impl FnOnce(&[u8]) -> Result<R>,
+) -> Result<R> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let name = db.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = scratch_dir.join(format!("{}.snapshot", name));
+    let _ = fs::remove_file(&tmp);
+
+    let vacuumed = Connection::open_with_flags(db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.execute("VACUUM INTO ?1", [tmp.to_string_lossy()]));
+    let result = match vacuumed {
+        Ok(_) => mapped::read(&tmp)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| f(&data)),
+        Err(e) => {
+            warn!(
+                "[crypto] Cannot snapshot {:?} ({}), copying raw file",
+                db, e
+            );
+            let data = fs::read(db).with_context(|| format!("Failed to read {:?}", db))?;
+            f(&data)
+        }
+    };
+    // The mapping is dropped by now (Windows cannot remove mapped files)
+    let _ = fs::remove_file(&tmp);
+    result
+}
+
+/// Replace a local database with a pulled snapshot.
+///
+/// The local WAL is checkpointed first so stale WAL frames are not replayed
+/// on top of the new file.
+fn replace_db(db: &Path, data: &[u8]) -> Result<()> {
+    if db.exists() {
+        if let Ok(conn) = rusqlite::Connection::open(db) {
+            let _ = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);");
+        }
+    }
+
+    let mut tmp = db.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, data).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, db).with_context(|| format!("Failed to replace {:?}", db))?;
+    Ok(())
+}
+
+/// Recursively list regular files under a directory (empty if missing).
+pub(super) fn collect_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    Ok(Walk::new(dir).try_files()?)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// True if `dst` is missing or older than `src`.
+fn is_newer(src: &Path, dst: &Path) -> bool {
+    match (modified(src), modified(dst)) {
+        (Some(src), Some(dst)) => src > dst,
+        _ => true,
+    }
+}
+
+fn copy_mtime(src: &Path, dst: &Path) -> Result<()> {
+    if let Some(mtime) = modified(src) {
+        set_mtime(dst, mtime)?;
+    }
+    Ok(())
+}
+
+fn set_mtime(path: &Path, mtime: SystemTime) -> Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(mtime)?;
+    Ok(())
+}
+
+/// Remove the other representation of a file (`.enc` file or chunk directory).
+fn remove_stale(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn ensure_parent(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::tests::test_params;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let (_, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+
+        let machine_a = TempDir::new().unwrap();
+        let session = machine_a.path().join("sessions/cursor/abc.json");
+        fs::create_dir_all(session.parent().unwrap()).unwrap();
+        fs::write(&session, r#"{"secret":"prompt"}"#).unwrap();
+        VaultMetadata::new().save(machine_a.path()).unwrap();
+
+        let stats = seal(machine_a.path(), &key).unwrap();
+        assert_eq!(stats.encrypted, 1);
+        assert_eq!(stats.copied, 1);
+
+        let sealed = staging_dir(machine_a.path()).join("sessions/cursor/abc.json.enc");
+        let blob = fs::read(&sealed).unwrap();
+        assert!(crate::crypto::is_encrypted(&blob));
+
+        // Unchanged files are not re-encrypted
+        assert_eq!(seal(machine_a.path(), &key).unwrap().encrypted, 0);
+
+        // Simulate the remote landing in another machine's staging directory
+        let machine_b = TempDir::new().unwrap();
+        let b_sealed = staging_dir(machine_b.path()).join("sessions/cursor/abc.json.enc");
+        fs::create_dir_all(b_sealed.parent().unwrap()).unwrap();
+        fs::copy(&sealed, &b_sealed).unwrap();
+        let manifest = staging_dir(machine_a.path()).join(manifest::MANIFEST_FILE);
+        fs::copy(
+            &manifest,
+            staging_dir(machine_b.path()).join(manifest::MANIFEST_FILE),
+        )
+        .unwrap();
+
+        let opened = open(machine_b.path(), &key, false).unwrap();
+        assert_eq!(opened.decrypted, 1);
+        assert_eq!(
+            fs::read_to_string(machine_b.path().join("sessions/cursor/abc.json")).unwrap(),
+            r#"{"secret":"prompt"}"#
+        );
+        assert_eq!(open(machine_b.path(), &key, false).unwrap().decrypted, 0);
+
+        // A corrupted remote object is rejected before decrypting
+        let machine_c = TempDir::new().unwrap();
+        let c_sealed = staging_dir(machine_c.path()).join("sessions/cursor/abc.json.enc");
+        fs::create_dir_all(c_sealed.parent().unwrap()).unwrap();
+        let mut blob = fs::read(&sealed).unwrap();
+        blob.push(0);
+        fs::write(&c_sealed, blob).unwrap();
+        fs::copy(
+            &manifest,
+            staging_dir(machine_c.path()).join(manifest::MANIFEST_FILE),
+        )
+        .unwrap();
+        assert!(open(machine_c.path(), &key, false).is_err());
+        assert!(!machine_c.path().join("sessions/cursor/abc.json").exists());
+    }
+
+    #[test]
+    fn test_open_reassembles_chunked_files() {
+        let (_, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+        let vault = TempDir::new().unwrap();
+        let staging = staging_dir(vault.path());
+        let chunks = chunked::chunks_dir(&staging.join("sessions/cursor/state.vscdb"));
+        chunked::write(&b"huge cursor database"[..], &chunks, Some(&key)).unwrap();
+        manifest::update(&staging, &key, BTreeMap::new()).unwrap();
+
+        let opened = open(vault.path(), &key, false).unwrap();
+        assert_eq!(opened.decrypted, 1);
+        assert_eq!(
+            fs::read(vault.path().join("sessions/cursor/state.vscdb")).unwrap(),
+            b"huge cursor database"
+        );
+        assert!(!vault
+            .path()
+            .join("sessions/cursor/state.vscdb.chunks")
+            .exists());
+        assert_eq!(open(vault.path(), &key, false).unwrap().decrypted, 0);
+    }
+
+    #[test]
+    fn test_databases_are_encrypted() {
+        let (_, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+
+        let machine_a = TempDir::new().unwrap();
+        let db_path = machine_a.path().join("embeddings.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE chunks (content TEXT);
+             INSERT INTO chunks VALUES ('secret chunk text');",
+        )
+        .unwrap();
+
+        // Sealed while the connection is open: WAL content is included
+        assert_eq!(seal(machine_a.path(), &key).unwrap().encrypted, 1);
+        drop(conn);
+        let sealed = staging_dir(machine_a.path()).join("embeddings.db.enc");
+        let blob = fs::read(&sealed).unwrap();
+        assert!(!blob
+            .windows(b"secret chunk".len())
+            .any(|w| w == b"secret chunk"));
+
+        let machine_b = TempDir::new().unwrap();
+        let b_staging = staging_dir(machine_b.path());
+        fs::create_dir_all(&b_staging).unwrap();
+        fs::copy(&sealed, b_staging.join("embeddings.db.enc")).unwrap();
+        let manifest = staging_dir(machine_a.path()).join(manifest::MANIFEST_FILE);
+        fs::copy(&manifest, b_staging.join(manifest::MANIFEST_FILE)).unwrap();
+        assert_eq!(open(machine_b.path(), &key, false).unwrap().decrypted, 1);
+
+        let conn = rusqlite::Connection::open(machine_b.path().join("embeddings.db")).unwrap();
+        let content: String = conn
+            .query_row("SELECT content FROM chunks", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content, "secret chunk text");
+    }
+
+    #[test]
+    fn test_open_requires_manifest() {
+        let (_, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+        let vault = TempDir::new().unwrap();
+        let session = vault.path().join("sessions/cursor/abc.json");
+        fs::create_dir_all(session.parent().unwrap()).unwrap();
+        fs::write(&session, "prompt").unwrap();
+        seal(vault.path(), &key).unwrap();
+        fs::remove_file(&session).unwrap();
+
+        // Nothing encrypted yet: nothing to verify
+        let empty = TempDir::new().unwrap();
+        assert_eq!(open(empty.path(), &key, false).unwrap().decrypted, 0);
+
+        fs::remove_file(staging_dir(vault.path()).join(manifest::MANIFEST_FILE)).unwrap();
+        assert!(open(vault.path(), &key, false).is_err());
+        assert!(!session.exists());
+
+        // Explicit opt-in for remotes sealed before manifests
+        assert_eq!(open(vault.path(), &key, true).unwrap().decrypted, 1);
+        assert_eq!(fs::read_to_string(&session).unwrap(), "prompt");
+    }
+}
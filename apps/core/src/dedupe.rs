@@ -0,0 +1,291 @@
+//! Near-duplicate conversation detection.
+//!
+//! The same conversation can be captured by more than one source (e.g., the
+//! Copilot extractor and the interceptor). Parsing records a MinHash
+//! signature of each conversation's word shingles in vault.db; signatures are
+//! bucketed with LSH bands, and candidates from different sources whose
+//! estimated Jaccard similarity reaches [`SIMILARITY_THRESHOLD`] are linked in
+//! the `duplicates` table to the most complete capture. Session lists, search,
+//! embedding and usage stats leave the linked copies out.
+//!
+//! Parsing re-links duplicates as it goes; `echovault-cli dedupe` previews the
+//! links over the whole vault and applies them on demand.
+
+use crate::parsers::{ParsedConversation, Role};
+use crate::storage::{DuplicateLink, SessionSignature, VaultDb};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Hash functions per signature.
+pub const NUM_HASHES: usize = 128;
+
+/// LSH bands (of `NUM_HASHES / BANDS` rows): pairs above about 0.4
+/// similarity share a band and get compared.
+const BANDS: usize = 32;
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 5;
+
+/// Conversations with fewer shingles are too short to tell apart.
+const MIN_SHINGLES: usize = 20;
+
+/// Estimated similarity from which two captures are the same conversation.
+pub const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// MinHash signature of a conversation's user and assistant text, or `None`
+/// if it is too short. Tool output and system prompts are left out, since
+/// sources capture them differently.
+pub fn signature(conv: &ParsedConversation) -> Option<SessionSignature> {
+    let words: Vec<String> = conv
+        .messages
+        .iter()
+        .filter(|m| matches!(m.role, Role::User | Role::Assistant))
+        .flat_map(|m| m.content.split(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let shingles: HashSet<u64> = words
+        .windows(SHINGLE_WORDS)
+        .map(|w| fnv1a(w.join(" ").as_bytes()))
+        .collect();
+    if shingles.len() < MIN_SHINGLES {
+        return None;
+    }
+
+    let minhash = seeds()
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|&h| (mix(h ^ seed) >> 32) as u32)
+                .min()
+                .unwrap_or(u32::MAX)
+        })
+        .collect();
+    Some(SessionSignature {
+        session_id: conv.id.clone(),
+        source: conv.source.clone(),
+        shingles: shingles.len() as u64,
+        minhash,
+    })
+}
+
+/// Estimated Jaccard similarity of two signatures.
+pub fn similarity(a: &SessionSignature, b: &SessionSignature) -> f64 {
+    let same = a
+        .minhash
+        .iter()
+        .zip(&b.minhash)
+        .filter(|(x, y)| x == y)
+        .count();
+    same as f64 / a.minhash.len().max(1) as f64
+}
+
+/// Link captures of the same conversation from different sources.
+///
+/// Each group of matching captures keeps the one with the most shingles
+/// (the most complete); the others are linked to it.
+pub fn find_duplicates(signatures: &[SessionSignature]) -> Vec<DuplicateLink> {
+    let rows = NUM_HASHES / BANDS;
+    let mut buckets: HashMap<(usize, &[u32]), Vec<usize>> = HashMap::new();
+    for (i, sig) in signatures.iter().enumerate() {
+        if sig.minhash.len() != NUM_HASHES {
+            continue;
+        }
+        for band in 0..BANDS {
+            let key = &sig.minhash[band * rows..(band + 1) * rows];
+            buckets.entry((band, key)).or_default().push(i);
+        }
+    }
+
+    let mut groups = UnionFind::new(signatures.len());
+    let mut compared = HashSet::new();
+    for members in buckets.values().filter(|m| m.len() > 1) {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[n + 1..] {
+                if signatures[i].source == signatures[j].source || !compared.insert((i, j)) {
+                    continue;
+                }
+                if similarity(&signatures[i], &signatures[j]) >= SIMILARITY_THRESHOLD {
+                    groups.union(i, j);
+                }
+            }
+        }
+    }
+
+    let mut by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..signatures.len() {
+        by_root.entry(groups.find(i)).or_default().push(i);
+    }
+
+    let mut links = Vec::new();
+    for members in by_root.values().filter(|m| m.len() > 1) {
+        let canonical = *members
+            .iter()
+            .max_by(|&&a, &&b| {
+                let (a, b) = (&signatures[a], &signatures[b]);
+                a.shingles
+                    .cmp(&b.shingles)
+                    .then_with(|| b.session_id.cmp(&a.session_id))
+            })
+            .expect("group is not empty");
+        for &i in members.iter().filter(|&&i| i != canonical) {
+            links.push(DuplicateLink {
+                session_id: signatures[i].session_id.clone(),
+                canonical_id: signatures[canonical].session_id.clone(),
+                similarity: similarity(&signatures[i], &signatures[canonical]),
+            });
+        }
+    }
+    links.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    links
+}
+
+/// Store new signatures and re-link duplicates across the whole vault.
+/// Returns the number of sessions linked as duplicates.
+pub fn update_duplicates(vault_dir: &Path, signatures: &[SessionSignature]) -> Result<usize> {
+    let mut db = VaultDb::open(vault_dir)?;
+    db.save_signatures(signatures)?;
+    let links = find_duplicates(&db.signatures()?);
+    db.replace_duplicates(&links)?;
+    Ok(links.len())
+}
+
+/// Duplicate links of the stored signatures, without storing them.
+pub fn plan_duplicates(vault_dir: &Path) -> Result<Vec<DuplicateLink>> {
+    let db = VaultDb::open(vault_dir)?;
+    Ok(find_duplicates(&db.signatures()?))
+}
+
+/// IDs of sessions linked as duplicates (empty if vault.db can't be read).
+pub fn duplicate_ids(vault_dir: &Path) -> HashSet<String> {
+    VaultDb::open(vault_dir)
+        .and_then(|db| db.duplicate_ids())
+        .unwrap_or_default()
+}
+
+/// Stable 64-bit FNV-1a hash (signatures are stored, so no `DefaultHasher`).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// One fixed seed per hash function.
+fn seeds() -> impl Iterator<Item = u64> {
+    (1..=NUM_HASHES as u64).map(|i| mix(i.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        self.parent[i] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b] = a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ParsedMessage;
+
+    fn conversation(id: &str, source: &str, texts: &[&str]) -> ParsedConversation {
+        ParsedConversation {
+            id: id.to_string(),
+            source: source.to_string(),
+            title: None,
+            workspace: None,
+            created_at: None,
+            updated_at: None,
+            model: None,
+            messages: texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| ParsedMessage {
+                    role: if i % 2 == 0 {
+                        Role::User
+                    } else {
+                        Role::Assistant
+                    },
+                    content: text.to_string(),
+                    timestamp: None,
+                    tool_name: None,
+                    model: None,
+                })
+                .collect(),
+            tags: vec![],
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_links_same_conversation_across_sources() {
+        let question = "How do I configure the tokio runtime with a custom number of \
+                        worker threads and enable the io and time drivers in my server?";
+        let answer = "Use tokio::runtime::Builder::new_multi_thread, call worker_threads \
+                      with the count you want, then enable_all to turn on the io and time \
+                      drivers, and finally build the runtime and block_on your main future.";
+        let follow_up = "Thanks, that works!";
+
+        let extractor = conversation("copilot-1", "vscode-copilot", &[question, answer]);
+        let intercepted = conversation("proxy-1", "interceptor", &[question, answer, follow_up]);
+        let same_source = conversation("copilot-2", "vscode-copilot", &[question, answer]);
+        let other = conversation(
+            "cursor-1",
+            "cursor",
+            &[
+                "Write a Python script that renames every photo in a folder by the \
+               date it was taken, reading the EXIF data with Pillow and skipping \
+               files that have no date at all",
+            ],
+        );
+        assert!(signature(&conversation("short", "cursor", &["hi there"])).is_none());
+
+        let signatures: Vec<_> = [&extractor, &intercepted, &same_source, &other]
+            .into_iter()
+            .filter_map(signature)
+            .collect();
+        assert_eq!(signatures.len(), 4);
+        assert_eq!(similarity(&signatures[0], &signatures[2]), 1.0);
+
+        // Both Copilot copies match the intercepted capture, which has more text
+        let links = find_duplicates(&signatures);
+        let linked: Vec<_> = links
+            .iter()
+            .map(|l| (l.session_id.as_str(), l.canonical_id.as_str()))
+            .collect();
+        assert_eq!(
+            linked,
+            vec![("copilot-1", "proxy-1"), ("copilot-2", "proxy-1")]
+        );
+        assert!(links.iter().all(|l| l.similarity >= SIMILARITY_THRESHOLD));
+    }
+}
@@ -0,0 +1,376 @@
+//! Daily and weekly digests of new conversations.
+//!
+//! A digest covers the sessions created in one day, or one week starting on
+//! Monday: counts per source, the notable conversations (most messages
+//! first) and, when `[digest] chat_api_base` is set, a short summary written
+//! by an OpenAI-compatible chat model. Digests are saved as Markdown in
+//! `<vault>/digests` (`2026-10-15.md`, `2026-W41.md`), so they sync with the
+//! rest of the vault.
+//!
+//! [`generate_due`] is the scheduler hook: it writes the digests of the last
+//! complete day and week (as enabled in `[digest]`) that are not saved yet.
+//! The desktop app calls it after background syncs, `echovault-cli watch`
+//! between batches; `echovault-cli digest` writes one on demand.
+
+use crate::analytics::Period;
+use crate::config::Config;
+use crate::storage::{atomic, NewSession, VaultDb};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Folder of the vault holding digests
+pub const DIGESTS_DIR: &str = "digests";
+
+/// Session titles sent to the chat model for the summary
+#[cfg(feature = "embedding")]
+const SUMMARY_TITLES: usize = 50;
+
+/// Conversations created in one period.
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub period: Period,
+    /// First day of the period
+    pub start: NaiveDate,
+    /// Last day of the period
+    pub end: NaiveDate,
+    /// Sessions created in the period
+    pub total: u64,
+    /// Sessions per source, most first
+    pub per_source: Vec<(String, u64)>,
+    /// Sessions with the most messages
+    pub notable: Vec<NewSession>,
+    /// Summary written by the chat model
+    pub summary: Option<String>,
+}
+
+/// First day after the period starting on `start`.
+fn period_end(period: Period, start: NaiveDate) -> NaiveDate {
+    match period {
+        Period::Day => start + Duration::days(1),
+        Period::Week => start + Duration::days(7),
+        Period::Month => start
+            .checked_add_months(Months::new(1))
+            .unwrap_or(NaiveDate::MAX),
+    }
+}
+
+/// First day of the last period that is over on `today`.
+pub fn last_complete(period: Period, today: NaiveDate) -> NaiveDate {
+    period.start(period.start(today) - Duration::days(1))
+}
+
+/// Path of the digest of the period starting on `start`.
+pub fn digest_path(vault_dir: &Path, period: Period, start: NaiveDate) -> PathBuf {
+    let name = match period {
+        Period::Day => start.format("%Y-%m-%d").to_string(),
+        Period::Week => {
+            let week = start.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+        Period::Month => start.format("%Y-%m").to_string(),
+    };
+    vault_dir.join(DIGESTS_DIR).join(format!("{}.md", name))
+}
+
+/// Collect the sessions of the period containing `date` from vault.db.
+pub fn build(db: &VaultDb, period: Period, date: NaiveDate, notable: usize) -> Result<Digest> {
+    let start = period.start(date);
+    let until = period_end(period, start);
+    let sessions = db.sessions_created_between(
+        &start.format("%Y-%m-%d").to_string(),
+        &until.format("%Y-%m-%d").to_string(),
+    )?;
+
+    let mut per_source: BTreeMap<&str, u64> = BTreeMap::new();
+    for session in &sessions {
+        *per_source.entry(&session.source).or_default() += 1;
+    }
+    let mut per_source: Vec<(String, u64)> = per_source
+        .into_iter()
+        .map(|(source, count)| (source.to_string(), count))
+        .collect();
+    per_source.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    Ok(Digest {
+        period,
+        start,
+        end: until - Duration::days(1),
+        total: sessions.len() as u64,
+        per_source,
+        notable: sessions.into_iter().take(notable).collect(),
+        summary: None,
+    })
+}
+
+impl Digest {
+    /// Heading of the digest, e.g. "Weekly digest: 2026-10-05 to 2026-10-11".
+    pub fn title(&self) -> String {
+        match self.period {
+            Period::Day => format!("Daily digest: {}", self.start),
+            Period::Week => format!("Weekly digest: {} to {}", self.start, self.end),
+            Period::Month => format!("Monthly digest: {}", self.start.format("%Y-%m")),
+        }
+    }
+
+    /// Render as Markdown with YAML frontmatter.
+    pub fn to_markdown(&self) -> String {
+        let period = match self.period {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        };
+        let mut md = format!(
+            "---\ntype: digest\nperiod: {}\nstart: {}\nend: {}\nsessions: {}\n---\n\n# {}\n\n",
+            period,
+            self.start,
+            self.end,
+            self.total,
+            self.title()
+        );
+
+        if self.total == 0 {
+            md.push_str("No new conversations.\n");
+            return md;
+        }
+
+        if let Some(summary) = &self.summary {
+            md.push_str(&format!("## Summary\n\n{}\n\n", summary.trim()));
+        }
+
+        md.push_str("## New conversations by source\n\n| Source | Sessions |\n|---|---|\n");
+        for (source, count) in &self.per_source {
+            md.push_str(&format!("| {} | {} |\n", source, count));
+        }
+
+        md.push_str("\n## Notable conversations\n\n");
+        for session in &self.notable {
+            let mut details = vec![session.source.clone()];
+            if let Some(workspace) = &session.workspace_name {
+                details.push(workspace.clone());
+            }
+            if session.message_count > 0 {
+                details.push(format!("{} messages", session.message_count));
+            }
+            md.push_str(&format!(
+                "- **{}** ({}) `{}`\n",
+                session.title.as_deref().unwrap_or("(untitled)"),
+                details.join(", "),
+                session.id
+            ));
+        }
+        md
+    }
+}
+
+/// Write the digest of the period containing `date` into the vault,
+/// replacing an existing one. The summary is skipped if `summarize` is false
+/// or no chat endpoint is configured; a failed summary is logged, not fatal.
+pub fn generate(
+    config: &Config,
+    period: Period,
+    date: NaiveDate,
+    summarize: bool,
+) -> Result<(Digest, PathBuf)> {
+    let db = VaultDb::open(&config.vault_path)?;
+    let mut digest = build(&db, period, date, config.digest.notable)?;
+    if summarize && digest.total > 0 && config.digest.chat_api_base.is_some() {
+        match summary(config, &digest) {
+            Ok(text) => digest.summary = Some(text),
+            Err(e) => warn!("[digest] Summary failed: {:#}", e),
+        }
+    }
+
+    let path = digest_path(&config.vault_path, period, digest.start);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic::write(&path, digest.to_markdown())
+        .with_context(|| format!("Cannot write {}", path.display()))?;
+    info!("[digest] Wrote {}", path.display());
+    Ok((digest, path))
+}
+
+/// Scheduler hook: write the digests of the last complete periods enabled in
+/// `[digest]` that are not in the vault yet. Periods without new
+/// conversations are skipped. Returns the digests written.
+pub fn generate_due(config: &Config) -> Result<Vec<(Digest, PathBuf)>> {
+    let today = Utc::now().date_naive();
+    let enabled = [
+        (Period::Day, config.digest.daily),
+        (Period::Week, config.digest.weekly),
+    ];
+
+    let mut written = Vec::new();
+    for (period, _) in enabled.into_iter().filter(|(_, on)| *on) {
+        let start = last_complete(period, today);
+        if digest_path(&config.vault_path, period, start).exists() {
+            continue;
+        }
+        let db = VaultDb::open(&config.vault_path)?;
+        if build(&db, period, start, 0)?.total == 0 {
+            continue;
+        }
+        written.push(generate(config, period, start, true)?);
+    }
+    Ok(written)
+}
+
+/// Ask the configured chat endpoint for a summary of the digest.
+#[cfg(feature = "embedding")]
+fn summary(config: &Config, digest: &Digest) -> Result<String> {
+    use secrecy::ExposeSecret;
+
+    let api_base = config
+        .digest
+        .chat_api_base
+        .as_deref()
+        .context("No chat endpoint configured ([digest] chat_api_base)")?;
+    let db = VaultDb::open(&config.vault_path)?;
+    let sessions = build(&db, digest.period, digest.start, SUMMARY_TITLES)?.notable;
+    let titles: Vec<String> = sessions
+        .iter()
+        .map(|s| {
+            format!(
+                "- {} [{}{}]",
+                s.title.as_deref().unwrap_or("(untitled)"),
+                s.source,
+                s.workspace_name
+                    .as_deref()
+                    .map(|w| format!(", {}", w))
+                    .unwrap_or_default()
+            )
+        })
+        .collect();
+    let prompt = format!(
+        "These are the AI coding conversations I had ({}), with their tool and \
+         workspace. Summarize in 3-5 sentences what I worked on, grouping related \
+         topics. Reply with the summary only.\n\n{}",
+        digest.title(),
+        titles.join("\n")
+    );
+
+    let body = serde_json::json!({
+        "model": config.digest.chat_model,
+        "messages": [{ "role": "user", "content": prompt }],
+    });
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(
+            config.embedding.request_timeout_secs,
+        )))
+        .build()
+        .into();
+    let mut request = agent
+        .post(&format!(
+            "{}/chat/completions",
+            api_base.trim_end_matches('/')
+        ))
+        .header("Content-Type", "application/json");
+    if let Some(key) = config.digest.resolved_api_key(&config.embedding) {
+        request = request.header("Authorization", &format!("Bearer {}", key.expose_secret()));
+    }
+
+    let response: serde_json::Value = request
+        .send_json(&body)
+        .context("Chat API request failed")?
+        .body_mut()
+        .read_json()
+        .context("Failed to parse chat API response")?;
+    response["choices"][0]["message"]["content"]
+        .as_str()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .context("Chat API returned no summary")
+}
+
+#[cfg(not(feature = "embedding"))]
+fn summary(_config: &Config, _digest: &Digest) -> Result<String> {
+    anyhow::bail!("Summaries need the `embedding` feature (HTTP client)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{EnrichedMetadata, SessionEntry};
+
+    fn session(id: &str, source: &str, created_at: &str) -> SessionEntry {
+        SessionEntry {
+            id: id.to_string(),
+            source: source.to_string(),
+            mtime: 1,
+            file_size: 1,
+            title: Some(format!("Session {}", id)),
+            workspace_name: Some("api".to_string()),
+            created_at: Some(created_at.to_string()),
+            vault_path: String::new(),
+            original_path: String::new(),
+            content_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_periods() {
+        let thursday = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        assert_eq!(
+            last_complete(Period::Day, thursday),
+            NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()
+        );
+        let last_monday = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        assert_eq!(last_complete(Period::Week, thursday), last_monday);
+        assert_eq!(
+            digest_path(Path::new("/v"), Period::Week, last_monday),
+            Path::new("/v/digests/2026-W41.md")
+        );
+        let september = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        assert_eq!(last_complete(Period::Month, thursday), september);
+        assert_eq!(
+            period_end(Period::Month, september),
+            NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()
+        );
+        assert_eq!(
+            digest_path(Path::new("/v"), Period::Month, september),
+            Path::new("/v/digests/2026-09.md")
+        );
+    }
+
+    #[test]
+    fn test_build_digest() -> Result<()> {
+        let mut db = VaultDb::open_in_memory()?;
+        db.upsert_batch(&[
+            session("a", "cursor", "2026-10-05T09:00:00Z"),
+            session("b", "cursor", "2026-10-07T09:00:00Z"),
+            session("c", "claude-code", "2026-10-11T23:00:00Z"),
+            session("d", "cursor", "2026-10-12T09:00:00Z"),
+        ])?;
+        db.set_session_metadata(&[EnrichedMetadata {
+            id: "c".to_string(),
+            mtime: 1,
+            title: None,
+            created_at: None,
+            model: None,
+            message_count: 30,
+        }])?;
+
+        let date = NaiveDate::from_ymd_opt(2026, 10, 8).unwrap();
+        let digest = build(&db, Period::Week, date, 2)?;
+        assert_eq!(digest.start, NaiveDate::from_ymd_opt(2026, 10, 5).unwrap());
+        assert_eq!(digest.end, NaiveDate::from_ymd_opt(2026, 10, 11).unwrap());
+        assert_eq!(digest.total, 3);
+        assert_eq!(digest.per_source[0], ("cursor".to_string(), 2));
+        assert_eq!(digest.notable.len(), 2);
+        assert_eq!(digest.notable[0].id, "c");
+
+        let md = digest.to_markdown();
+        assert!(md.contains("# Weekly digest: 2026-10-05 to 2026-10-11"));
+        assert!(md.contains("| claude-code | 1 |"));
+        assert!(md.contains("**Session c** (claude-code, api, 30 messages)"));
+
+        let empty = build(&db, Period::Day, date, 2)?;
+        assert_eq!(empty.total, 0);
+        assert!(empty.to_markdown().contains("No new conversations."));
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+//! Approximate nearest-neighbor index of the embedding store (HNSW).
+//!
+//! A cosine scan reads and scores every chunk, which stops being interactive
+//! past ~100k chunks. Large stores are searched through a hierarchical
+//! navigable small world graph instead: `embeddings.hnsw` in the vault holds
+//! the graph (chunk row IDs and their links, not the vectors), and a search
+//! reads from `embeddings.db` only the vectors of the nodes it visits.
+//!
+//! The graph records the store revision it was built from. Every write to the
+//! chunks changes the revision, and the next search rebuilds the graph. The
+//! file is a per-machine cache and is never synced.
+
+use anyhow::{bail, Context, Result};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Graph file in the vault root (excluded from sync)
+pub const ANN_FILE: &str = "embeddings.hnsw";
+
+/// Stores with fewer chunks are always scanned exactly
+pub const ANN_MIN_CHUNKS: usize = 20_000;
+
+const MAGIC: &[u8; 8] = b"EVHNSW01";
+/// Links kept per node on upper layers (twice as many on layer 0)
+const M: usize = 16;
+/// Candidates kept while linking a new node
+const EF_CONSTRUCTION: usize = 100;
+/// Minimum candidates kept while searching
+const EF_SEARCH: usize = 128;
+/// Longest store revision accepted in a graph file
+const MAX_REVISION_LEN: usize = 64;
+/// Entry point of an empty graph
+const NO_ENTRY: u32 = u32::MAX;
+
+/// A node and its distance to the query (1 - cosine similarity).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// HNSW graph over the chunks of an embedding store.
+pub struct HnswIndex {
+    /// Store revision the graph was built from
+    revision: String,
+    /// Chunk row ID of each node
+    ids: Vec<i64>,
+    /// Links of each node, per layer (`links[node][layer]`)
+    links: Vec<Vec<Vec<u32>>>,
+    /// Node on the top layer where searches start
+    entry: u32,
+}
+
+impl HnswIndex {
+    /// Build the graph of chunks (row IDs and their vectors).
+    pub fn build(revision: &str, ids: Vec<i64>, mut vectors: Vec<Vec<f32>>) -> Result<Self> {
+        if ids.len() != vectors.len() || ids.len() >= NO_ENTRY as usize {
+            bail!(
+                "Cannot index {} vectors for {} chunks",
+                vectors.len(),
+                ids.len()
+            );
+        }
+        vectors.iter_mut().for_each(|v| normalize(v));
+
+        let mut index = Self {
+            revision: revision.to_string(),
+            links: Vec::with_capacity(ids.len()),
+            ids,
+            entry: NO_ENTRY,
+        };
+        // Fixed seed: the same chunks always give the same graph
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        let level_factor = 1.0 / (M as f64).ln();
+        for node in 0..vectors.len() as u32 {
+            let level = (-rng.next_unit().ln() * level_factor) as usize;
+            index.insert(node, level, &vectors)?;
+        }
+        Ok(index)
+    }
+
+    /// Store revision the graph was built from.
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+
+    /// Number of indexed chunks.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether no chunk is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Row IDs of the chunks nearest the query, nearest first.
+    ///
+    /// `distance` gives the distance from the query to a chunk (by row ID),
+    /// so vectors are only read for the nodes the search visits.
+    pub fn search(
+        &self,
+        limit: usize,
+        distance: &mut impl FnMut(i64) -> Result<f32>,
+    ) -> Result<Vec<i64>> {
+        if self.entry == NO_ENTRY || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut node_distance = |node: u32| distance(self.ids[node as usize]);
+
+        let mut nearest = vec![Candidate {
+            distance: node_distance(self.entry)?,
+            node: self.entry,
+        }];
+        for layer in (1..=self.top_layer()).rev() {
+            nearest = self.search_layer(&nearest, 1, layer, &mut node_distance)?;
+        }
+        let nearest = self.search_layer(&nearest, limit.max(EF_SEARCH), 0, &mut node_distance)?;
+        Ok(nearest
+            .into_iter()
+            .take(limit)
+            .map(|c| self.ids[c.node as usize])
+            .collect())
+    }
+
+    /// Read a graph saved by [`HnswIndex::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut input = BufReader::new(
+            File::open(path).with_context(|| format!("Cannot open {}", path.display()))?,
+        );
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            bail!("Not an EchoVault HNSW index: {}", path.display());
+        }
+
+        let revision_len = read_u32(&mut input)? as usize;
+        if revision_len > MAX_REVISION_LEN {
+            bail!("Corrupt HNSW index: {}", path.display());
+        }
+        let mut revision = vec![0u8; revision_len];
+        input.read_exact(&mut revision)?;
+        let revision = String::from_utf8(revision).context("Invalid index revision")?;
+        let entry = read_u32(&mut input)?;
+        let count = read_u32(&mut input)? as usize;
+
+        let mut ids = Vec::new();
+        let mut links = Vec::new();
+        for _ in 0..count {
+            ids.push(i64::from_le_bytes(read_array(&mut input)?));
+            let mut layers = Vec::new();
+            for _ in 0..read_u32(&mut input)? {
+                let mut layer = Vec::new();
+                for _ in 0..read_u32(&mut input)? {
+                    let link = read_u32(&mut input)?;
+                    if link as usize >= count {
+                        bail!("Corrupt HNSW index: {}", path.display());
+                    }
+                    layer.push(link);
+                }
+                layers.push(layer);
+            }
+            links.push(layers);
+        }
+        let entry_valid = match links.get(entry as usize) {
+            Some(layers) => !layers.is_empty(),
+            None => entry == NO_ENTRY && count == 0,
+        };
+        if !entry_valid {
+            bail!("Corrupt HNSW index: {}", path.display());
+        }
+
+        Ok(Self {
+            revision,
+            ids,
+            links,
+            entry,
+        })
+    }
+
+    /// Write the graph to a file, replacing it only once complete.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp = path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut out = BufWriter::new(
+                File::create(&tmp).with_context(|| format!("Cannot write {}", tmp.display()))?,
+            );
+            out.write_all(MAGIC)?;
+            out.write_all(&(self.revision.len() as u32).to_le_bytes())?;
+            out.write_all(self.revision.as_bytes())?;
+            out.write_all(&self.entry.to_le_bytes())?;
+            out.write_all(&(self.ids.len() as u32).to_le_bytes())?;
+            for (id, layers) in self.ids.iter().zip(&self.links) {
+                out.write_all(&id.to_le_bytes())?;
+                out.write_all(&(layers.len() as u32).to_le_bytes())?;
+                for layer in layers {
+                    out.write_all(&(layer.len() as u32).to_le_bytes())?;
+                    for link in layer {
+                        out.write_all(&link.to_le_bytes())?;
+                    }
+                }
+            }
+            out.flush()?;
+        }
+        fs::rename(&tmp, path).with_context(|| format!("Cannot replace {}", path.display()))?;
+        Ok(())
+    }
+
+    fn top_layer(&self) -> usize {
+        self.links[self.entry as usize].len().saturating_sub(1)
+    }
+
+    /// Link a new node (whose vector is `vectors[node]`) on layers 0..=level.
+    fn insert(&mut self, node: u32, level: usize, vectors: &[Vec<f32>]) -> Result<()> {
+        self.links.push(vec![Vec::new(); level + 1]);
+        if self.entry == NO_ENTRY {
+            self.entry = node;
+            return Ok(());
+        }
+
+        let query = &vectors[node as usize];
+        let mut distance =
+            |other: u32| -> Result<f32> { Ok(cosine_distance(query, &vectors[other as usize])) };
+        let top = self.top_layer();
+
+        let mut nearest = vec![Candidate {
+            distance: distance(self.entry)?,
+            node: self.entry,
+        }];
+        for layer in (level + 1..=top).rev() {
+            nearest = self.search_layer(&nearest, 1, layer, &mut distance)?;
+        }
+        for layer in (0..=level.min(top)).rev() {
+            nearest = self.search_layer(&nearest, EF_CONSTRUCTION, layer, &mut distance)?;
+            let max_links = if layer == 0 { 2 * M } else { M };
+            let neighbors: Vec<u32> = nearest.iter().take(M).map(|c| c.node).collect();
+            for &neighbor in &neighbors {
+                let base = &vectors[neighbor as usize];
+                let links = &mut self.links[neighbor as usize][layer];
+                links.push(node);
+                if links.len() > max_links {
+                    // Keep the closest links of the neighbor
+                    links.sort_by(|&a, &b| {
+                        cosine_distance(base, &vectors[a as usize])
+                            .total_cmp(&cosine_distance(base, &vectors[b as usize]))
+                    });
+                    links.truncate(max_links);
+                }
+            }
+            self.links[node as usize][layer] = neighbors;
+        }
+
+        if level > top {
+            self.entry = node;
+        }
+        Ok(())
+    }
+
+    /// Best-first search of one layer, keeping the `ef` nearest nodes found
+    /// (sorted nearest first).
+    fn search_layer(
+        &self,
+        entry: &[Candidate],
+        ef: usize,
+        layer: usize,
+        distance: &mut impl FnMut(u32) -> Result<f32>,
+    ) -> Result<Vec<Candidate>> {
+        let mut visited: HashSet<u32> = entry.iter().map(|c| c.node).collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> =
+            entry.iter().copied().map(Reverse).collect();
+        let mut nearest: BinaryHeap<Candidate> = entry.iter().copied().collect();
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let furthest = nearest.peek().map_or(f32::INFINITY, |c| c.distance);
+            if current.distance > furthest && nearest.len() >= ef {
+                break;
+            }
+            let Some(links) = self.links[current.node as usize].get(layer) else {
+                continue;
+            };
+            for &neighbor in links {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let candidate = Candidate {
+                    distance: distance(neighbor)?,
+                    node: neighbor,
+                };
+                let furthest = nearest.peek().map_or(f32::INFINITY, |c| c.distance);
+                if nearest.len() < ef || candidate.distance < furthest {
+                    candidates.push(Reverse(candidate));
+                    nearest.push(candidate);
+                    if nearest.len() > ef {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+
+        Ok(nearest.into_sorted_vec())
+    }
+}
+
+/// 1 - cosine similarity of two normalized vectors.
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 1.0;
+    }
+    1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>()
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32(input: &mut impl Read) -> Result<u32> {
+    Ok(u32::from_le_bytes(read_array(input)?))
+}
+
+/// Small deterministic generator for node levels.
+struct XorShift(u64);
+
+impl XorShift {
+    /// Uniform in (0, 1]
+    fn next_unit(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        ((self.0 >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn random_vectors(count: usize, dimension: usize) -> Vec<Vec<f32>> {
+        let mut rng = XorShift(42);
+        (0..count)
+            .map(|_| {
+                (0..dimension)
+                    .map(|_| rng.next_unit() as f32 - 0.5)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_recall_and_persistence() -> Result<()> {
+        let mut vectors = random_vectors(2_000, 32);
+        vectors.iter_mut().for_each(|v| normalize(v));
+        let ids: Vec<i64> = (1..=vectors.len() as i64).collect();
+        let index = HnswIndex::build("r1", ids, vectors.clone())?;
+        assert_eq!(index.len(), 2_000);
+
+        // Compare the 10 nearest against an exact scan
+        let queries = random_vectors(20, 32);
+        let mut found = 0;
+        for query in &queries {
+            let mut query = query.clone();
+            normalize(&mut query);
+            let distance = |id: i64| cosine_distance(&query, &vectors[id as usize - 1]);
+            let mut exact: Vec<i64> = (1..=vectors.len() as i64).collect();
+            exact.sort_by(|&a, &b| distance(a).total_cmp(&distance(b)));
+            exact.truncate(10);
+
+            let approx = index.search(10, &mut |id| Ok(distance(id)))?;
+            assert_eq!(approx.len(), 10);
+            found += approx.iter().filter(|id| exact.contains(id)).count();
+        }
+        let recall = found as f64 / (queries.len() * 10) as f64;
+        assert!(recall > 0.9, "recall {}", recall);
+
+        let temp = TempDir::new()?;
+        let path = temp.path().join(ANN_FILE);
+        index.save(&path)?;
+        let loaded = HnswIndex::load(&path)?;
+        assert_eq!(loaded.revision(), "r1");
+        assert_eq!(loaded.ids, index.ids);
+        assert_eq!(loaded.links, index.links);
+        assert_eq!(loaded.entry, index.entry);
+
+        fs::write(&path, b"EVHNSW01garbage")?;
+        assert!(HnswIndex::load(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_index() -> Result<()> {
+        let index = HnswIndex::build("r0", Vec::new(), Vec::new())?;
+        assert!(index.is_empty());
+        assert!(index.search(5, &mut |_| Ok(0.0))?.is_empty());
+        Ok(())
+    }
+}
@@ -6,32 +6,54 @@
 //! 3. Interceptor captures API traffic (Phase 3)
 //! 4. Embedding chunks + embeds conversations for semantic search (Phase 4)
 //!
-//! Supports any OpenAI-compatible embedding API (Ollama, LiteLLM, OpenAI, vLLM).
-//! Vectors are stored in SQLite with cosine similarity search in Rust.
+//! Supports any OpenAI-compatible embedding API (Ollama, LiteLLM, OpenAI, vLLM),
+//! or a local ONNX model with the `local-embedding` feature.
+//! Vectors are stored in SQLite with cosine similarity search in Rust, through
+//! an HNSW graph once the store is large. Search results can be reranked by a
+//! cross-encoder (`[embedding.rerank]`).
 
+pub mod ann;
 pub mod chunker;
 pub mod provider;
+pub mod rerank;
 pub mod store;
 
-use crate::parsers::{all_parsers, parse_vault_source, ParsedConversation};
+pub use store::SearchFilter;
+
+use crate::config::{Config, EmbeddingBackend, RerankBackend, RerankConfig};
+use crate::parsers::{all_parsers, parse_source_files, ParsedFile};
+use crate::progress::Operation;
+use crate::storage::hash_cache::{self, HashCache};
+use crate::storage::VaultDb;
 use anyhow::{Context, Result};
-use chunker::{chunk_conversation, ChunkConfig};
-use provider::EmbeddingProvider;
+use chunker::{chunk_conversation, Chunk, ChunkConfig};
+use provider::{Embedder, EmbeddingProvider};
+use rerank::Reranker;
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
-use store::EmbeddingStore;
+use std::time::Duration;
+use store::{EmbeddingStore, SessionMeta};
 use tracing::{debug, info, warn};
 
+/// Sessions whose batches are embedded concurrently before being stored
+const SESSION_WINDOW: usize = 64;
+
 /// Configuration for the embedding pipeline.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
+    /// Embedding API, or a model run locally
+    #[serde(default)]
+    pub backend: EmbeddingBackend,
+
     /// API base URL (e.g., "http://localhost:11434/v1" for Ollama)
     #[serde(default = "default_api_base")]
     pub api_base: String,
 
-    /// Optional API key
-    #[serde(default)]
-    pub api_key: Option<String>,
+    /// Optional API key (never serialized)
+    #[serde(default, skip_serializing)]
+    pub api_key: Option<SecretString>,
 
     /// Model name (e.g., "nomic-embed-text", "text-embedding-3-small")
     #[serde(default = "default_model")]
@@ -48,6 +70,22 @@ pub struct EmbeddingConfig {
     /// Number of texts per API batch call
     #[serde(default = "default_batch_size")]
     pub batch_size: usize,
+
+    /// Batch calls in flight at once
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+
+    /// Timeout of one API call, in seconds
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// Retries of a call rejected with 429/5xx or timed out
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+
+    /// Reranking of search results
+    #[serde(default)]
+    pub rerank: RerankConfig,
 }
 
 fn default_api_base() -> String {
@@ -70,19 +108,116 @@ fn default_batch_size() -> usize {
     32
 }
 
+fn default_concurrency() -> usize {
+    4
+}
+
+fn default_request_timeout_secs() -> u64 {
+    60
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
 impl Default for EmbeddingConfig {
     fn default() -> Self {
         Self {
+            backend: EmbeddingBackend::default(),
             api_base: default_api_base(),
             api_key: None,
             model: default_model(),
             chunk_size: default_chunk_size(),
             chunk_overlap: default_chunk_overlap(),
             batch_size: default_batch_size(),
+            concurrency: default_concurrency(),
+            request_timeout_secs: default_request_timeout_secs(),
+            max_retries: default_max_retries(),
+            rerank: RerankConfig::default(),
+        }
+    }
+}
+
+impl EmbeddingConfig {
+    /// Settings of the `[embedding]` config section, with the API key
+    /// falling back to the OS keyring.
+    pub fn from_config(config: &Config) -> Self {
+        let embedding = &config.embedding;
+        Self {
+            backend: embedding.backend,
+            api_base: embedding.api_base.clone(),
+            api_key: embedding.resolved_api_key(),
+            model: embedding.model.clone(),
+            chunk_size: embedding.chunk_size,
+            chunk_overlap: embedding.chunk_overlap,
+            batch_size: embedding.batch_size,
+            concurrency: embedding.concurrency,
+            request_timeout_secs: embedding.request_timeout_secs,
+            max_retries: embedding.max_retries,
+            rerank: embedding.rerank.clone(),
+        }
+    }
+}
+
+/// Embedder of the configured backend: the API provider with the configured
+/// timeout and retries, or the local model.
+pub fn new_embedder(config: &EmbeddingConfig) -> Result<Box<dyn Embedder>> {
+    match config.backend {
+        EmbeddingBackend::Api => Ok(Box::new(
+            EmbeddingProvider::new(&config.api_base, config.api_key.clone(), &config.model)
+                .with_limits(
+                    Duration::from_secs(config.request_timeout_secs),
+                    config.max_retries,
+                ),
+        )),
+        #[cfg(feature = "local-embedding")]
+        EmbeddingBackend::Local => Ok(Box::new(provider::LocalProvider::new(
+            &config.model,
+            &local_model_dir(),
+        )?)),
+        #[cfg(not(feature = "local-embedding"))]
+        EmbeddingBackend::Local => {
+            anyhow::bail!(
+                "This build has no local embedding backend; set [embedding] backend = \"api\""
+            )
         }
     }
 }
 
+/// Reranker of the configured rerank backend. The rerank API uses the
+/// embedding API base unless it has its own, and the embedding API key.
+pub fn new_reranker(config: &EmbeddingConfig) -> Result<Box<dyn Reranker>> {
+    let rerank = &config.rerank;
+    match rerank.backend {
+        RerankBackend::Api => Ok(Box::new(rerank::ApiReranker::new(
+            rerank.api_base.as_deref().unwrap_or(&config.api_base),
+            config.api_key.clone(),
+            &rerank.model,
+            Duration::from_secs(config.request_timeout_secs),
+            config.max_retries,
+        ))),
+        #[cfg(feature = "local-embedding")]
+        RerankBackend::Local => Ok(Box::new(rerank::LocalReranker::new(
+            &rerank.model,
+            &local_model_dir(),
+        )?)),
+        #[cfg(not(feature = "local-embedding"))]
+        RerankBackend::Local => {
+            anyhow::bail!(
+                "This build has no local rerank backend; set [embedding.rerank] backend = \"api\""
+            )
+        }
+    }
+}
+
+/// Where local embedding models are downloaded (machine-wide, outside the vault).
+#[cfg(feature = "local-embedding")]
+pub fn local_model_dir() -> std::path::PathBuf {
+    dirs::cache_dir()
+        .map(|d| d.join("echovault").join("models"))
+        .unwrap_or_else(|| crate::config::default_config_dir().join("models"))
+}
+
 /// Result of embedding vault conversations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbedResult {
@@ -105,19 +240,40 @@ pub struct SemanticSearchResult {
     pub source: String,
     /// Relevant chunk content
     pub chunk_content: String,
-    /// Cosine similarity score (0.0 to 1.0)
+    /// Relevance score (higher is better): the fused hybrid score, or the
+    /// reranker's when results are reranked
     pub score: f32,
     /// Session title (if available from parsed data)
     pub title: Option<String>,
 }
 
+/// Options of a semantic search.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Score every chunk instead of searching the ANN graph of a large store
+    pub exact: bool,
+    /// Keep the hybrid ranking even when reranking is enabled
+    pub skip_rerank: bool,
+    /// Only search the chunks of matching sessions
+    pub filter: SearchFilter,
+}
+
 /// Embed all parsed conversations in the vault.
 ///
 /// Reads parsed Markdown files, chunks them, calls the embedding API,
 /// and stores vectors in `embeddings.db`.
 ///
-/// Skips sessions that already have embeddings (incremental).
-pub fn embed_vault(config: &EmbeddingConfig, vault_dir: &Path) -> Result<EmbedResult> {
+/// Skips raw files unchanged since they were embedded, and sessions whose
+/// chunks and model hash the same as when they were embedded (incremental).
+/// A changed session is re-chunked and its old chunks are replaced.
+/// Annotation notes are embedded as an extra chunk of their session.
+/// Sessions linked as duplicates are left out. A cancelled `op` stops
+/// between windows of sessions, keeping those already stored.
+pub fn embed_vault(
+    config: &EmbeddingConfig,
+    vault_dir: &Path,
+    op: &Operation,
+) -> Result<EmbedResult> {
     let sessions_dir = vault_dir.join("sessions");
     if !sessions_dir.exists() {
         return Ok(EmbedResult {
@@ -132,8 +288,7 @@ pub fn embed_vault(config: &EmbeddingConfig, vault_dir: &Path) -> Result<EmbedRe
     let store = EmbeddingStore::open(vault_dir).context("Failed to open embedding store")?;
 
     // Create embedding provider
-    let provider =
-        EmbeddingProvider::new(&config.api_base, config.api_key.as_deref(), &config.model);
+    let provider = new_embedder(config)?;
 
     // Chunk config
     let chunk_config = ChunkConfig {
@@ -142,80 +297,153 @@ pub fn embed_vault(config: &EmbeddingConfig, vault_dir: &Path) -> Result<EmbedRe
         min_chunk_size: 50,
     };
 
-    // Collect all parsed conversations
+    // Collect conversations of raw files changed since they were last embedded.
+    // Stores embedded before session metadata was recorded have every file
+    // parsed once to fill it in.
+    let cache = HashCache::load(vault_dir, hash_cache::EMBED);
+    let backfill_meta = store.needs_meta_backfill()?;
     let parsers = all_parsers();
-    let mut all_conversations: Vec<ParsedConversation> = Vec::new();
+    let mut all_files: Vec<ParsedFile> = Vec::new();
+    let mut unchanged = 0;
 
     for parser in &parsers {
-        let (conversations, _errors) = parse_vault_source(parser.as_ref(), &sessions_dir);
-        all_conversations.extend(conversations);
+        let parsed = parse_source_files(
+            parser.as_ref(),
+            &sessions_dir,
+            (!backfill_meta).then_some(&cache),
+            op,
+        )?;
+        all_files.extend(parsed.files);
+        unchanged += parsed.skipped;
     }
 
     info!(
-        "Embedding pipeline: {} conversations found",
-        all_conversations.len()
+        "Embedding pipeline: {} changed conversations found, {} unchanged",
+        all_files.len(),
+        unchanged
     );
 
     let mut result = EmbedResult {
         sessions_processed: 0,
         chunks_created: 0,
-        sessions_skipped: 0,
+        sessions_skipped: unchanged,
         errors: Vec::new(),
     };
 
-    for conv in &all_conversations {
-        // Skip empty conversations
-        if conv.is_empty() {
+    let duplicates = crate::dedupe::duplicate_ids(vault_dir);
+    let mut pending: Vec<(&ParsedFile, Vec<Chunk>, String)> = Vec::new();
+    for file in &all_files {
+        let conv = &file.conversation;
+
+        // Duplicates of another capture aren't embedded. They stay out of the
+        // hash cache, so they get embedded if they are unlinked later.
+        if duplicates.contains(&conv.id) {
+            if let Err(e) = store.delete_session_chunks(&conv.id) {
+                result.errors.push((conv.id.clone(), e.to_string()));
+            }
+            result.sessions_skipped += 1;
+            continue;
+        }
+
+        let chunks = chunk_conversation(conv, &chunk_config);
+        let hash = content_hash(provider.model(), &chunks);
+
+        let meta = SessionMeta {
+            workspace: conv.workspace.clone(),
+            started_at: conv.created_at,
+            model: conv.model.clone(),
+        };
+        if let Err(e) = store.set_session_meta(&conv.id, &meta) {
+            result.errors.push((conv.id.clone(), e.to_string()));
             continue;
         }
 
-        // Skip if already embedded
-        match store.has_session(&conv.id) {
-            Ok(true) => {
+        // Skip if embedded from the same content with the same model
+        match store.session_hash(&conv.id) {
+            Ok(Some(stored)) if stored == hash => {
+                cache.record(&file.path);
                 result.sessions_skipped += 1;
                 continue;
             }
-            Ok(false) => {}
+            Ok(_) => {}
             Err(e) => {
                 result.errors.push((conv.id.clone(), e.to_string()));
                 continue;
             }
         }
 
-        // Chunk the conversation
-        let chunks = chunk_conversation(conv, &chunk_config);
         if chunks.is_empty() {
             debug!("Session {} produced no chunks, skipping", conv.id);
+            if let Err(e) = store.delete_session_chunks(&conv.id) {
+                result.errors.push((conv.id.clone(), e.to_string()));
+                continue;
+            }
+            cache.record(&file.path);
             continue;
         }
+        pending.push((file, chunks, hash));
+    }
 
-        // Embed chunks in batches
-        let chunk_texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
-        let mut all_embeddings: Vec<Vec<f32>> = Vec::with_capacity(chunk_texts.len());
+    // Embed the batches of several sessions concurrently, a window at a time
+    op.start("embed", pending.len() as u64);
+    for window in pending.chunks(SESSION_WINDOW) {
+        if op.is_cancelled() {
+            break;
+        }
+        let mut batches: Vec<Vec<&str>> = Vec::new();
+        let mut owners: Vec<usize> = Vec::new();
+        for (i, (_, chunks, _)) in window.iter().enumerate() {
+            let chunk_texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+            for batch in chunk_texts.chunks(config.batch_size) {
+                batches.push(batch.to_vec());
+                owners.push(i);
+            }
+        }
 
-        for batch in chunk_texts.chunks(config.batch_size) {
-            match provider.embed_batch(batch) {
-                Ok(embeddings) => {
-                    all_embeddings.extend(embeddings);
-                }
-                Err(e) => {
+        let mut embeddings: Vec<Result<Vec<Vec<f32>>, String>> =
+            window.iter().map(|_| Ok(Vec::new())).collect();
+        let batch_results = provider.embed_batches(&batches, config.concurrency);
+        for (owner, batch_result) in owners.into_iter().zip(batch_results) {
+            match (&mut embeddings[owner], batch_result) {
+                (Ok(all), Ok(vectors)) => all.extend(vectors),
+                (slot @ Ok(_), Err(e)) => {
+                    let conv = &window[owner].0.conversation;
                     warn!("Failed to embed batch for session {}: {}", conv.id, e);
-                    result.errors.push((conv.id.clone(), e.to_string()));
-                    break;
+                    *slot = Err(e.to_string());
                 }
+                (Err(_), _) => {}
             }
         }
 
-        // If we got all embeddings, store them
-        if all_embeddings.len() == chunk_texts.len() {
+        for ((file, chunks, hash), session_embeddings) in window.iter().zip(embeddings) {
+            let conv = &file.conversation;
+            let all_embeddings = match session_embeddings {
+                Ok(all) => all,
+                Err(e) => {
+                    result.errors.push((conv.id.clone(), e));
+                    continue;
+                }
+            };
+
+            // If we got all embeddings, store them
+            if all_embeddings.len() != chunks.len() {
+                continue;
+            }
             let chunk_pairs: Vec<(String, Vec<f32>)> = chunks
                 .iter()
-                .zip(all_embeddings.into_iter())
+                .zip(all_embeddings)
                 .map(|(chunk, emb)| (chunk.content.clone(), emb))
                 .collect();
 
-            match store.store_session_chunks(&conv.id, &conv.source, &config.model, &chunk_pairs) {
+            match store.store_session_chunks(
+                &conv.id,
+                &conv.source,
+                provider.model(),
+                hash,
+                &chunk_pairs,
+            ) {
                 Ok(count) => {
+                    cache.record(&file.path);
                     result.sessions_processed += 1;
                     result.chunks_created += count;
                     debug!("Embedded session {}: {} chunks", conv.id, count);
@@ -225,7 +453,25 @@ pub fn embed_vault(config: &EmbeddingConfig, vault_dir: &Path) -> Result<EmbedRe
                 }
             }
         }
+        op.advance(window.len() as u64);
     }
+    op.finish();
+
+    if !op.is_cancelled() {
+        if backfill_meta {
+            if let Err(e) = store.set_meta_backfilled() {
+                warn!("Failed to record session metadata: {}", e);
+            }
+        }
+        if let Err(e) = embed_notes(config, provider.as_ref(), &store, vault_dir, &mut result) {
+            warn!("Failed to embed annotation notes: {}", e);
+        }
+    }
+
+    if let Err(e) = cache.save() {
+        warn!("Failed to save embed hashes: {}", e);
+    }
+    op.check()?;
 
     info!(
         "Embedding complete: {} processed, {} chunks, {} skipped, {} errors",
@@ -238,70 +484,184 @@ pub fn embed_vault(config: &EmbeddingConfig, vault_dir: &Path) -> Result<EmbedRe
     Ok(result)
 }
 
+/// Embed annotation notes added or changed since the last run, and drop
+/// those of sessions whose note was removed.
+fn embed_notes(
+    config: &EmbeddingConfig,
+    provider: &dyn Embedder,
+    store: &EmbeddingStore,
+    vault_dir: &Path,
+    result: &mut EmbedResult,
+) -> Result<()> {
+    let db = VaultDb::open(vault_dir)?;
+    let notes: HashMap<String, String> = db
+        .annotations(false)?
+        .into_iter()
+        .filter(|a| !a.note.is_empty())
+        .map(|a| (a.session_id, format!("Note: {}", a.note)))
+        .collect();
+
+    let embedded = store.notes()?;
+    for id in embedded.keys().filter(|id| !notes.contains_key(*id)) {
+        store.delete_note(id)?;
+    }
+
+    let changed: Vec<(&String, &String)> = notes
+        .iter()
+        .filter(|(id, text)| embedded.get(*id) != Some(text))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let sources: HashMap<String, String> = db
+        .get_all_sessions()?
+        .into_iter()
+        .map(|s| (s.id, s.source))
+        .collect();
+
+    let texts: Vec<&str> = changed.iter().map(|(_, text)| text.as_str()).collect();
+    let batches: Vec<Vec<&str>> = texts
+        .chunks(config.batch_size)
+        .map(<[&str]>::to_vec)
+        .collect();
+    let batch_results = provider.embed_batches(&batches, config.concurrency);
+    for (notes, batch_result) in changed.chunks(config.batch_size).zip(batch_results) {
+        let vectors = match batch_result {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                for (id, _) in notes {
+                    result.errors.push(((*id).clone(), e.to_string()));
+                }
+                continue;
+            }
+        };
+        for ((id, text), vector) in notes.iter().zip(vectors) {
+            let source = sources.get(*id).map(String::as_str).unwrap_or_default();
+            match store.store_note(id, source, provider.model(), text, &vector) {
+                Ok(()) => result.chunks_created += 1,
+                Err(e) => result.errors.push(((*id).clone(), e.to_string())),
+            }
+        }
+    }
+    debug!("Embedded {} annotation notes", changed.len());
+    Ok(())
+}
+
+/// Hash of a session's chunks and the model embedding them: a new model or
+/// chunk settings re-embed it as much as new content does.
+fn content_hash(model: &str, chunks: &[Chunk]) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    for chunk in chunks {
+        hasher.update([0]);
+        hasher.update(chunk.content.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
 /// Perform semantic search across all embedded conversations.
 ///
 /// Uses hybrid search (vector + FTS5 keyword) when available,
 /// with Reciprocal Rank Fusion (RRF) scoring.
 /// Falls back to vector-only search if FTS5 query fails.
+/// With reranking enabled, the best `rerank.candidates` sessions are
+/// reranked by the cross-encoder (keeping the hybrid order if it fails).
 pub fn search_similar(
     config: &EmbeddingConfig,
     vault_dir: &Path,
     query: &str,
     limit: usize,
+    options: &SearchOptions,
 ) -> Result<Vec<SemanticSearchResult>> {
     // Open embedding store
-    let store = EmbeddingStore::open(vault_dir).context("Failed to open embedding store")?;
+    let mut store = EmbeddingStore::open(vault_dir).context("Failed to open embedding store")?;
+    store.set_exact(options.exact);
+    store.set_filter(options.filter.clone());
 
     // Create embedding provider and embed the query
-    let provider =
-        EmbeddingProvider::new(&config.api_base, config.api_key.as_deref(), &config.model);
+    let provider = new_embedder(config)?;
 
     let query_embedding = provider
         .embed_single(query)
         .context("Failed to embed search query")?;
 
+    let rerank = config.rerank.enabled && !options.skip_rerank;
+    let fetch_limit = if rerank {
+        config.rerank.candidates.max(limit)
+    } else {
+        limit
+    };
+
     // Try hybrid search first (vector + keyword), fall back to vector-only
     let alpha = 0.6; // Bias towards vector similarity
-    let hybrid_results = store.search_hybrid_sessions(query, &query_embedding, limit, alpha);
+    let candidates: Vec<(String, String, String, f32)> =
+        match store.search_hybrid_sessions(query, &query_embedding, fetch_limit, alpha) {
+            Ok(results) => {
+                debug!("Hybrid search returned {} results", results.len());
+                results
+                    .into_iter()
+                    .map(|r| (r.session_id, r.source, r.chunk_content, r.score))
+                    .collect()
+            }
+            Err(e) => {
+                // Fallback to vector-only search
+                warn!("Hybrid search failed ({}), falling back to vector-only", e);
+                store
+                    .search_sessions(&query_embedding, fetch_limit)?
+                    .into_iter()
+                    .map(|r| (r.session_id, r.source, r.chunk_content, r.score))
+                    .collect()
+            }
+        };
 
     let parsed_dir = vault_dir.join("parsed");
+    // Captures of a conversation another source also holds are left out
+    let duplicates = crate::dedupe::duplicate_ids(vault_dir);
+
+    let mut results: Vec<SemanticSearchResult> = candidates
+        .into_iter()
+        .filter(|(session_id, ..)| !duplicates.contains(session_id))
+        .map(|(session_id, source, chunk_content, score)| {
+            let title = read_parsed_title(&parsed_dir, &source, &session_id);
+            SemanticSearchResult {
+                session_id,
+                source,
+                chunk_content,
+                score,
+                title,
+            }
+        })
+        .collect();
 
-    match hybrid_results {
-        Ok(results) => {
-            debug!("Hybrid search returned {} results", results.len());
-            Ok(results
-                .into_iter()
-                .map(|r| {
-                    let title = read_parsed_title(&parsed_dir, &r.source, &r.session_id);
-                    SemanticSearchResult {
-                        session_id: r.session_id,
-                        source: r.source,
-                        chunk_content: r.chunk_content,
-                        score: r.score,
-                        title,
-                    }
-                })
-                .collect())
-        }
-        Err(e) => {
-            // Fallback to vector-only search
-            warn!("Hybrid search failed ({}), falling back to vector-only", e);
-            let results = store.search_sessions(&query_embedding, limit)?;
-            Ok(results
-                .into_iter()
-                .map(|r| {
-                    let title = read_parsed_title(&parsed_dir, &r.source, &r.session_id);
-                    SemanticSearchResult {
-                        session_id: r.session_id,
-                        source: r.source,
-                        chunk_content: r.chunk_content,
-                        score: r.score,
-                        title,
-                    }
-                })
-                .collect())
+    if rerank {
+        if let Err(e) = rerank_results(config, query, &mut results) {
+            warn!("Reranking failed ({}), keeping the hybrid ranking", e);
         }
     }
+    results.truncate(limit);
+
+    Ok(results)
+}
+
+/// Score results again with the configured reranker and sort them by it.
+fn rerank_results(
+    config: &EmbeddingConfig,
+    query: &str,
+    results: &mut [SemanticSearchResult],
+) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let reranker = new_reranker(config)?;
+    let documents: Vec<&str> = results.iter().map(|r| r.chunk_content.as_str()).collect();
+    let scores = reranker.rerank(query, &documents)?;
+    for (result, score) in results.iter_mut().zip(scores) {
+        result.score = score;
+    }
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(())
 }
 
 /// Get embedding store statistics.
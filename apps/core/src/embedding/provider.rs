@@ -5,11 +5,36 @@
 //! - Ollama (localhost:11434/v1)
 //! - LiteLLM proxy
 //! - vLLM, TGI, etc.
+//!
+//! With the `local-embedding` feature, [`LocalProvider`] runs a small ONNX
+//! model (all-MiniLM, BGE, Nomic, E5) on this machine instead, so `embed`
+//! and `search` work offline. Both implement [`Embedder`].
+//!
+//! Requests time out, and those rejected as rate limited (429) or failed
+//! (5xx, timeouts) are retried with exponential backoff, honoring
+//! `Retry-After`. [`EmbeddingProvider::embed_batches`] keeps a bounded number
+//! of batch requests in flight.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 use tracing::{debug, warn};
 
+/// Default timeout of one API request
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Default retries of a rate-limited or failed request
+const DEFAULT_MAX_RETRIES: u32 = 5;
+/// First retry delay; doubled on every further retry
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+/// Longest wait between retries (also caps `Retry-After`)
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Vectors of a batch, in input order
+type Vectors = Vec<Vec<f32>>;
+
 /// Status of an embedding provider check.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "status", rename_all = "snake_case")]
@@ -22,11 +47,48 @@ pub enum ProviderStatus {
     Unavailable { reason: String },
 }
 
+/// Computes embeddings: an embedding API or a local model.
+pub trait Embedder: Send + Sync {
+    /// Embed multiple texts, returning vectors in input order.
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>>;
+
+    /// Embed several batches; one result per batch, in input order.
+    fn embed_batches(&self, batches: &[Vec<&str>], concurrency: usize) -> Vec<Result<Vectors>>;
+
+    /// Model name stored with the vectors.
+    fn model(&self) -> &str;
+
+    /// Embed a single text and return its vector.
+    fn embed_single(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text])?
+            .into_iter()
+            .next()
+            .context("Empty response from embedding provider")
+    }
+
+    /// Check provider status with detailed information.
+    fn check_provider_status(&self) -> ProviderStatus {
+        match self.embed_single("test") {
+            Ok(v) => ProviderStatus::Available { dimension: v.len() },
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("404") || msg.contains("not found") || msg.contains("model") {
+                    ProviderStatus::ModelNotFound { message: msg }
+                } else {
+                    ProviderStatus::Unavailable { reason: msg }
+                }
+            }
+        }
+    }
+}
+
 /// Embedding API provider using OpenAI-compatible HTTP endpoint.
 pub struct EmbeddingProvider {
     api_base: String,
-    api_key: Option<String>,
+    api_key: Option<SecretString>,
     model: String,
+    agent: ureq::Agent,
+    max_retries: u32,
 }
 
 /// Request body for embedding API.
@@ -78,17 +140,26 @@ struct ErrorDetail {
 
 impl EmbeddingProvider {
     /// Create a new embedding provider.
-    pub fn new(api_base: &str, api_key: Option<&str>, model: &str) -> Self {
+    pub fn new(api_base: &str, api_key: Option<SecretString>, model: &str) -> Self {
         // Normalize API base URL (strip trailing slash)
         let api_base = api_base.trim_end_matches('/').to_string();
 
         Self {
             api_base,
-            api_key: api_key.map(|s| s.to_string()),
+            api_key,
             model: model.to_string(),
+            agent: new_agent(DEFAULT_TIMEOUT),
+            max_retries: DEFAULT_MAX_RETRIES,
         }
     }
 
+    /// Set the per-request timeout and the retries of failed requests.
+    pub fn with_limits(mut self, timeout: Duration, max_retries: u32) -> Self {
+        self.agent = new_agent(timeout);
+        self.max_retries = max_retries;
+        self
+    }
+
     /// Embed a single text and return its vector.
     pub fn embed_single(&self, text: &str) -> Result<Vec<f32>> {
         let results = self.embed_batch(&[text])?;
@@ -119,22 +190,7 @@ impl EmbeddingProvider {
             self.model
         );
 
-        let mut request = ureq::post(&url).header("Content-Type", "application/json");
-
-        if let Some(ref key) = self.api_key {
-            request = request.header("Authorization", &format!("Bearer {}", key));
-        }
-
-        let mut response = request.send_json(&body).map_err(|e| match e {
-            ureq::Error::StatusCode(status) => {
-                // Try to parse error response body
-                anyhow::anyhow!("Embedding API returned status {}", status)
-            }
-            ureq::Error::Io(io_err) => {
-                anyhow::anyhow!("Embedding API connection failed: {}", io_err)
-            }
-            other => anyhow::anyhow!("Embedding API error: {}", other),
-        })?;
+        let mut response = self.send_with_retries(&url, &body)?;
 
         let resp: EmbeddingResponse = response
             .body_mut()
@@ -158,6 +214,103 @@ impl EmbeddingProvider {
         Ok(vectors)
     }
 
+    /// Embed several batches, keeping up to `concurrency` requests in flight.
+    ///
+    /// Returns one result per batch, in input order. Once a batch fails, the
+    /// batches not yet started fail too instead of being sent.
+    pub fn embed_batches(&self, batches: &[Vec<&str>], concurrency: usize) -> Vec<Result<Vectors>> {
+        let results: Mutex<Vec<Option<Result<Vectors>>>> =
+            Mutex::new((0..batches.len()).map(|_| None).collect());
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let workers = concurrency.clamp(1, batches.len().max(1));
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(batch) = batches.get(i) else {
+                        break;
+                    };
+                    let result = if failed.load(Ordering::Relaxed) {
+                        Err(anyhow::anyhow!("Skipped after an earlier batch failed"))
+                    } else {
+                        self.embed_batch(batch)
+                    };
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    if let Ok(mut results) = results.lock() {
+                        results[i] = Some(result);
+                    }
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(anyhow::anyhow!("Batch was not embedded"))))
+            .collect()
+    }
+
+    /// POST a request, retrying rate-limited (429), failed (5xx) and timed
+    /// out requests with exponential backoff.
+    pub(crate) fn send_with_retries(
+        &self,
+        url: &str,
+        body: &impl Serialize,
+    ) -> Result<ureq::http::Response<ureq::Body>> {
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .agent
+                .post(url)
+                .header("Content-Type", "application/json");
+            if let Some(ref key) = self.api_key {
+                request =
+                    request.header("Authorization", &format!("Bearer {}", key.expose_secret()));
+            }
+
+            let (error, retry_after) = match request.send_json(body) {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    if (200..300).contains(&status) {
+                        return Ok(response);
+                    }
+                    let error = anyhow::anyhow!("Embedding API returned status {}", status);
+                    if status != 429 && status < 500 {
+                        return Err(error);
+                    }
+                    (error, retry_after(&response))
+                }
+                Err(ureq::Error::Timeout(timeout)) => (
+                    anyhow::anyhow!("Embedding API timed out ({})", timeout),
+                    None,
+                ),
+                Err(ureq::Error::Io(io_err)) => {
+                    bail!("Embedding API connection failed: {}", io_err)
+                }
+                Err(other) => bail!("Embedding API error: {}", other),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error.context(format!("Giving up after {} retries", attempt)));
+            }
+            let delay = retry_after.unwrap_or_else(|| backoff(attempt));
+            warn!(
+                "{}, retrying in {:.1}s ({}/{})",
+                error,
+                delay.as_secs_f64(),
+                attempt + 1,
+                self.max_retries
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
     /// Check if the embedding API is reachable.
     pub fn health_check(&self) -> Result<bool> {
         // Try embedding a simple test string
@@ -173,21 +326,6 @@ impl EmbeddingProvider {
         }
     }
 
-    /// Check provider status with detailed information.
-    pub fn check_provider_status(&self) -> ProviderStatus {
-        match self.embed_single("test") {
-            Ok(v) => ProviderStatus::Available { dimension: v.len() },
-            Err(e) => {
-                let msg = e.to_string();
-                if msg.contains("404") || msg.contains("not found") || msg.contains("model") {
-                    ProviderStatus::ModelNotFound { message: msg }
-                } else {
-                    ProviderStatus::Unavailable { reason: msg }
-                }
-            }
-        }
-    }
-
     /// Get the model name.
     pub fn model(&self) -> &str {
         &self.model
@@ -199,6 +337,120 @@ impl EmbeddingProvider {
     }
 }
 
+impl Embedder for EmbeddingProvider {
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        EmbeddingProvider::embed_batch(self, texts)
+    }
+
+    fn embed_batches(&self, batches: &[Vec<&str>], concurrency: usize) -> Vec<Result<Vectors>> {
+        EmbeddingProvider::embed_batches(self, batches, concurrency)
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+/// Embedding model run on this machine with ONNX Runtime. The model is
+/// downloaded into the cache directory on first use.
+#[cfg(feature = "local-embedding")]
+pub struct LocalProvider {
+    name: String,
+    model: Mutex<fastembed::TextEmbedding>,
+}
+
+#[cfg(feature = "local-embedding")]
+impl LocalProvider {
+    /// Load a model of [`crate::config::LOCAL_EMBEDDING_MODELS`], caching its
+    /// files in `cache_dir`.
+    pub fn new(name: &str, cache_dir: &std::path::Path) -> Result<Self> {
+        use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+
+        let model = match name {
+            "all-MiniLM-L6-v2" => EmbeddingModel::AllMiniLML6V2,
+            "bge-small-en-v1.5" => EmbeddingModel::BGESmallENV15,
+            "nomic-embed-text" => EmbeddingModel::NomicEmbedTextV15,
+            "multilingual-e5-small" => EmbeddingModel::MultilingualE5Small,
+            other => bail!(
+                "Unknown local embedding model '{}' (use one of {})",
+                other,
+                crate::config::LOCAL_EMBEDDING_MODELS.join(", ")
+            ),
+        };
+        debug!(
+            "Loading local embedding model {} from {:?}",
+            name, cache_dir
+        );
+        let options = InitOptions::new(model)
+            .with_cache_dir(cache_dir.to_path_buf())
+            .with_show_download_progress(false);
+        let model = TextEmbedding::try_new(options)
+            .with_context(|| format!("Cannot load local embedding model '{}'", name))?;
+        Ok(Self {
+            name: name.to_string(),
+            model: Mutex::new(model),
+        })
+    }
+}
+
+#[cfg(feature = "local-embedding")]
+impl Embedder for LocalProvider {
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let model = self
+            .model
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Local embedding model is poisoned"))?;
+        model
+            .embed(texts.to_vec(), Some(texts.len()))
+            .context("Local embedding failed")
+    }
+
+    /// Batches run one after another: ONNX Runtime already uses every core.
+    fn embed_batches(&self, batches: &[Vec<&str>], _concurrency: usize) -> Vec<Result<Vectors>> {
+        batches
+            .iter()
+            .map(|batch| self.embed_batch(batch))
+            .collect()
+    }
+
+    fn model(&self) -> &str {
+        &self.name
+    }
+}
+
+/// HTTP agent with a per-request timeout that returns error statuses as
+/// responses, so 429s can be inspected.
+fn new_agent(timeout: Duration) -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+/// Delay before retry number `attempt` (0-based): 0.5s, 1s, 2s, ... up to 30s.
+fn backoff(attempt: u32) -> Duration {
+    BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_MAX)
+}
+
+/// Delay requested by a `Retry-After: <seconds>` header.
+fn retry_after(response: &ureq::http::Response<ureq::Body>) -> Option<Duration> {
+    let secs: u64 = response
+        .headers()
+        .get("retry-after")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(BACKOFF_MAX))
+}
+
 /// Compute cosine similarity between two vectors.
 ///
 /// Returns a value between -1.0 and 1.0, where 1.0 means identical direction.
@@ -257,6 +509,15 @@ pub fn check_ollama_available() -> Option<Vec<String>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), BACKOFF_MAX);
+        assert_eq!(backoff(u32::MAX), BACKOFF_MAX);
+    }
+
     #[test]
     fn test_cosine_similarity_identical() {
         let a = vec![1.0, 2.0, 3.0];
@@ -0,0 +1,197 @@
+//! Reranking of search results with a cross-encoder.
+//!
+//! Hybrid search ranks chunks by vector and keyword scores computed apart
+//! from the query. A cross-encoder reads the query and a chunk together,
+//! which ranks better but is too slow for the whole store, so only the best
+//! hybrid results (`embedding.rerank.candidates`) are reranked.
+//!
+//! [`ApiReranker`] calls a `/rerank` endpoint (Jina, Cohere-compatible
+//! proxies such as LiteLLM, vLLM); with the `local-embedding` feature,
+//! [`LocalReranker`] runs an ONNX cross-encoder on this machine.
+
+use crate::embedding::provider::EmbeddingProvider;
+use anyhow::{Context, Result};
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::debug;
+
+/// Scores how relevant documents are to a query.
+pub trait Reranker: Send + Sync {
+    /// Relevance of each document to the query, in input order (higher is
+    /// more relevant; scales differ between models).
+    fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>>;
+}
+
+/// Rerank API client (`POST <api_base>/rerank`).
+pub struct ApiReranker {
+    api_base: String,
+    model: String,
+    /// Carries the key, timeout and retries of the requests
+    client: EmbeddingProvider,
+}
+
+#[derive(Serialize)]
+struct RerankRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: &'a [&'a str],
+}
+
+#[derive(Deserialize)]
+struct RerankResponse {
+    results: Vec<RerankResult>,
+}
+
+#[derive(Deserialize)]
+struct RerankResult {
+    index: usize,
+    relevance_score: f32,
+}
+
+impl ApiReranker {
+    /// Create a client of the rerank API at `api_base`.
+    pub fn new(
+        api_base: &str,
+        api_key: Option<SecretString>,
+        model: &str,
+        timeout: Duration,
+        max_retries: u32,
+    ) -> Self {
+        Self {
+            api_base: api_base.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            client: EmbeddingProvider::new(api_base, api_key, model)
+                .with_limits(timeout, max_retries),
+        }
+    }
+}
+
+impl Reranker for ApiReranker {
+    fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let url = format!("{}/rerank", self.api_base);
+        debug!(
+            "Reranking {} documents via {} (model: {})",
+            documents.len(),
+            url,
+            self.model
+        );
+        let body = RerankRequest {
+            model: &self.model,
+            query,
+            documents,
+        };
+        let mut response = self
+            .client
+            .send_with_retries(&url, &body)
+            .context("Rerank request failed")?;
+        let response: RerankResponse = response
+            .body_mut()
+            .read_json()
+            .context("Failed to parse rerank API response")?;
+        scores_in_order(response.results, documents.len())
+    }
+}
+
+/// Scores of results (possibly sorted by relevance) back in document order.
+fn scores_in_order(results: Vec<RerankResult>, count: usize) -> Result<Vec<f32>> {
+    let mut scores = vec![None; count];
+    for result in results {
+        if let Some(score) = scores.get_mut(result.index) {
+            *score = Some(result.relevance_score);
+        }
+    }
+    scores
+        .into_iter()
+        .collect::<Option<Vec<f32>>>()
+        .context("Rerank API did not score every document")
+}
+
+/// Cross-encoder run on this machine with ONNX Runtime. The model is
+/// downloaded into the cache directory on first use.
+#[cfg(feature = "local-embedding")]
+pub struct LocalReranker {
+    model: std::sync::Mutex<fastembed::TextRerank>,
+}
+
+#[cfg(feature = "local-embedding")]
+impl LocalReranker {
+    /// Load a model of [`crate::config::LOCAL_RERANK_MODELS`], caching its
+    /// files in `cache_dir`.
+    pub fn new(name: &str, cache_dir: &std::path::Path) -> Result<Self> {
+        use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+
+        let model = match name {
+            "bge-reranker-base" => RerankerModel::BGERerankerBase,
+            "bge-reranker-v2-m3" => RerankerModel::BGERerankerV2M3,
+            "jina-reranker-v1-turbo-en" => RerankerModel::JINARerankerV1TurboEn,
+            "jina-reranker-v2-base-multilingual" => RerankerModel::JINARerankerV2BaseMultiligual,
+            other => anyhow::bail!(
+                "Unknown local rerank model '{}' (use one of {})",
+                other,
+                crate::config::LOCAL_RERANK_MODELS.join(", ")
+            ),
+        };
+        debug!("Loading local rerank model {} from {:?}", name, cache_dir);
+        let options = RerankInitOptions::new(model)
+            .with_cache_dir(cache_dir.to_path_buf())
+            .with_show_download_progress(false);
+        let model = TextRerank::try_new(options)
+            .with_context(|| format!("Cannot load local rerank model '{}'", name))?;
+        Ok(Self {
+            model: std::sync::Mutex::new(model),
+        })
+    }
+}
+
+#[cfg(feature = "local-embedding")]
+impl Reranker for LocalReranker {
+    fn rerank(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let model = self
+            .model
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Local rerank model is poisoned"))?;
+        let results = model
+            .rerank(query, documents.to_vec(), false, None)
+            .context("Local reranking failed")?;
+        let mut scores = vec![f32::MIN; documents.len()];
+        for result in results {
+            if let Some(score) = scores.get_mut(result.index) {
+                *score = result.score;
+            }
+        }
+        Ok(scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_in_order() {
+        let results = vec![
+            RerankResult {
+                index: 1,
+                relevance_score: 0.9,
+            },
+            RerankResult {
+                index: 0,
+                relevance_score: 0.2,
+            },
+        ];
+        assert_eq!(scores_in_order(results, 2).unwrap(), vec![0.2, 0.9]);
+
+        let partial = vec![RerankResult {
+            index: 0,
+            relevance_score: 0.5,
+        }];
+        assert!(scores_in_order(partial, 2).is_err());
+    }
+}
@@ -2,17 +2,105 @@
 //!
 //! Uses a dedicated `embeddings.db` in the vault directory.
 //! Vectors are stored as f32 byte arrays (BLOB) for compact storage.
-//! Cosine similarity search is performed in Rust for portability.
+//! Cosine similarity search is performed in Rust for portability: a full scan
+//! for small stores, an HNSW graph ([`super::ann`]) for large ones.
+//!
+//! Each embedded session records a hash of its chunks and model
+//! (`session_hashes`), so `embed` re-embeds a conversation only when its
+//! content changed, and its workspace, start time and model
+//! (`session_meta`), which [`SearchFilter`] restricts searches by.
 
+use crate::embedding::ann::{HnswIndex, ANN_FILE, ANN_MIN_CHUNKS};
 use crate::embedding::provider::cosine_similarity;
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
-use std::path::Path;
-use tracing::{debug, info};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, params_from_iter, Connection};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Chunk index of the annotation note of a session. Notes are kept apart
+/// from the conversation's chunks (0..), so re-embedding one leaves the other.
+pub const NOTE_CHUNK: i64 = -1;
 
 /// SQLite-based embedding storage.
 pub struct EmbeddingStore {
     conn: Connection,
+    /// Where the ANN graph is persisted (kept in memory only if `None`)
+    ann_path: Option<PathBuf>,
+    /// ANN graph loaded or built by an earlier search
+    ann: RefCell<Option<HnswIndex>>,
+    /// Always scan every chunk instead of searching the ANN graph
+    exact: bool,
+    /// Restricts the chunks searched
+    filter: SearchFilter,
+}
+
+/// Restricts a search to the chunks of matching sessions, in SQL before
+/// scoring. Sessions without metadata only match an empty filter.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// Only this source (e.g. "cursor")
+    pub source: Option<String>,
+    /// Only workspaces whose name contains this (case-insensitive)
+    pub workspace: Option<String>,
+    /// Only conversations started on or after this day
+    pub since: Option<NaiveDate>,
+    /// Only models whose name contains this (case-insensitive)
+    pub model: Option<String>,
+    /// Only these sessions (e.g. those holding a tag)
+    pub only: Option<HashSet<String>>,
+}
+
+impl SearchFilter {
+    /// Whether no condition is set.
+    pub fn is_empty(&self) -> bool {
+        self.source.is_none()
+            && self.workspace.is_none()
+            && self.since.is_none()
+            && self.model.is_none()
+            && self.only.is_none()
+    }
+
+    /// SQL conditions on `chunks c LEFT JOIN session_meta m` (each starting
+    /// with " AND "), with their arguments.
+    fn sql(&self) -> (String, Vec<String>) {
+        let mut sql = String::new();
+        let mut args = Vec::new();
+        if let Some(source) = &self.source {
+            sql.push_str(" AND c.source = ?");
+            args.push(source.clone());
+        }
+        if let Some(workspace) = &self.workspace {
+            sql.push_str(" AND instr(lower(m.workspace), lower(?)) > 0");
+            args.push(workspace.clone());
+        }
+        // started_at is RFC 3339 in UTC, so days compare as strings
+        if let Some(since) = self.since {
+            sql.push_str(" AND m.started_at >= ?");
+            args.push(since.to_string());
+        }
+        if let Some(model) = &self.model {
+            sql.push_str(" AND instr(lower(m.model), lower(?)) > 0");
+            args.push(model.clone());
+        }
+        if let Some(only) = &self.only {
+            // One JSON array parameter, however many sessions match
+            sql.push_str(" AND c.session_id IN (SELECT value FROM json_each(?))");
+            args.push(serde_json::to_string(only).unwrap_or_else(|_| "[]".to_string()));
+        }
+        (sql, args)
+    }
+}
+
+/// Metadata of an embedded session, matched by [`SearchFilter`].
+#[derive(Debug, Clone, Default)]
+pub struct SessionMeta {
+    pub workspace: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub model: Option<String>,
 }
 
 /// A search result from similarity search.
@@ -22,7 +110,7 @@ pub struct SearchResult {
     pub session_id: String,
     /// Source (vscode-copilot, cursor, etc.)
     pub source: String,
-    /// Chunk index within the session
+    /// Chunk index within the session (`usize::MAX` for the annotation note)
     pub chunk_index: usize,
     /// The chunk text content
     pub chunk_content: String,
@@ -79,7 +167,13 @@ impl EmbeddingStore {
              PRAGMA cache_size = -8000;",
         )?;
 
-        let store = Self { conn };
+        let store = Self {
+            conn,
+            ann_path: Some(vault_dir.join(ANN_FILE)),
+            ann: RefCell::new(None),
+            exact: false,
+            filter: SearchFilter::default(),
+        };
         store.init_schema()?;
 
         Ok(store)
@@ -89,7 +183,13 @@ impl EmbeddingStore {
     #[allow(dead_code)]
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        let store = Self { conn };
+        let store = Self {
+            conn,
+            ann_path: None,
+            ann: RefCell::new(None),
+            exact: false,
+            filter: SearchFilter::default(),
+        };
         store.init_schema()?;
         Ok(store)
     }
@@ -116,12 +216,46 @@ impl EmbeddingStore {
             CREATE INDEX IF NOT EXISTS idx_chunks_source
                 ON chunks(source);
 
+            -- Hash of the chunks and model each session was embedded from
+            CREATE TABLE IF NOT EXISTS session_hashes (
+                session_id TEXT PRIMARY KEY,
+                content_hash TEXT NOT NULL
+            );
+
+            -- Session metadata matched by search filters
+            CREATE TABLE IF NOT EXISTS session_meta (
+                session_id TEXT PRIMARY KEY,
+                workspace TEXT,
+                started_at TEXT,
+                model TEXT
+            );
+
             -- Metadata table for store-level info
             CREATE TABLE IF NOT EXISTS embedding_meta (
                 key TEXT PRIMARY KEY,
                 value TEXT NOT NULL
             );
 
+            -- Revision of the chunks, changed by every write: an ANN graph
+            -- built from another revision is rebuilt
+            INSERT OR IGNORE INTO embedding_meta (key, value)
+                VALUES ('revision', lower(hex(randomblob(8))));
+
+            CREATE TRIGGER IF NOT EXISTS chunks_rev_ai AFTER INSERT ON chunks BEGIN
+                UPDATE embedding_meta SET value = lower(hex(randomblob(8)))
+                WHERE key = 'revision';
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS chunks_rev_ad AFTER DELETE ON chunks BEGIN
+                UPDATE embedding_meta SET value = lower(hex(randomblob(8)))
+                WHERE key = 'revision';
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS chunks_rev_au AFTER UPDATE ON chunks BEGIN
+                UPDATE embedding_meta SET value = lower(hex(randomblob(8)))
+                WHERE key = 'revision';
+            END;
+
             -- FTS5 virtual table for keyword search on chunk content
             CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
                 session_id,
@@ -153,14 +287,17 @@ impl EmbeddingStore {
         Ok(())
     }
 
-    /// Store chunks with their embeddings for a session.
+    /// Store chunks with their embeddings for a session, and the hash of the
+    /// content they were embedded from.
     ///
-    /// Replaces any existing chunks for the same session.
+    /// Replaces any existing chunks for the same session (but not its note)
+    /// in one transaction, so obsolete chunks never outlive the new ones.
     pub fn store_session_chunks(
         &self,
         session_id: &str,
         source: &str,
         model: &str,
+        content_hash: &str,
         chunks: &[(String, Vec<f32>)],
     ) -> Result<usize> {
         if chunks.is_empty() {
@@ -172,9 +309,14 @@ impl EmbeddingStore {
 
         // Delete existing chunks for this session
         tx.execute(
-            "DELETE FROM chunks WHERE session_id = ?1",
+            "DELETE FROM chunks WHERE session_id = ?1 AND chunk_index >= 0",
             params![session_id],
         )?;
+        tx.execute(
+            "INSERT INTO session_hashes (session_id, content_hash) VALUES (?1, ?2)
+             ON CONFLICT(session_id) DO UPDATE SET content_hash = excluded.content_hash",
+            params![session_id, content_hash],
+        )?;
 
         let mut stmt = tx.prepare(
             "INSERT INTO chunks (session_id, source, chunk_index, content, embedding, model, dimension)
@@ -207,69 +349,200 @@ impl EmbeddingStore {
         Ok(chunks.len())
     }
 
-    /// Check if a session already has embeddings.
+    /// Check if a session's conversation already has embeddings.
     pub fn has_session(&self, session_id: &str) -> Result<bool> {
         let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM chunks WHERE session_id = ?1",
+            "SELECT COUNT(*) FROM chunks WHERE session_id = ?1 AND chunk_index >= 0",
             params![session_id],
             |row| row.get(0),
         )?;
         Ok(count > 0)
     }
 
+    /// Hash of the content a session's chunks were embedded from, if known.
+    pub fn session_hash(&self, session_id: &str) -> Result<Option<String>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT content_hash FROM session_hashes WHERE session_id = ?1")?;
+        let mut rows = stmt.query(params![session_id])?;
+        Ok(match rows.next()? {
+            Some(row) => Some(row.get(0)?),
+            None => None,
+        })
+    }
+
+    /// Delete the conversation chunks of a session (not its note), e.g. when
+    /// it no longer yields any.
+    pub fn delete_session_chunks(&self, session_id: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let affected = tx.execute(
+            "DELETE FROM chunks WHERE session_id = ?1 AND chunk_index >= 0",
+            params![session_id],
+        )?;
+        tx.execute(
+            "DELETE FROM session_hashes WHERE session_id = ?1",
+            params![session_id],
+        )?;
+        tx.commit()?;
+        Ok(affected)
+    }
+
+    /// Record the metadata of a session, replacing the old one.
+    pub fn set_session_meta(&self, session_id: &str, meta: &SessionMeta) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO session_meta (session_id, workspace, started_at, model)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?
+            .execute(params![
+                session_id,
+                meta.workspace,
+                meta.started_at.map(|d| d.to_rfc3339()),
+                meta.model,
+            ])?;
+        Ok(())
+    }
+
+    /// Whether sessions may lack metadata: set until every session was
+    /// seen by an `embed` run (stores embedded before it was recorded).
+    pub fn needs_meta_backfill(&self) -> Result<bool> {
+        let done: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM embedding_meta WHERE key = 'meta_backfilled')",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(!done)
+    }
+
+    /// Record that every session has metadata.
+    pub fn set_meta_backfilled(&self) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO embedding_meta (key, value) VALUES ('meta_backfilled', '1')",
+            [],
+        )?;
+        Ok(())
+    }
+
     /// Delete all chunks for a session.
     pub fn delete_session(&self, session_id: &str) -> Result<usize> {
-        let affected = self.conn.execute(
+        let tx = self.conn.unchecked_transaction()?;
+        let affected = tx.execute(
             "DELETE FROM chunks WHERE session_id = ?1",
             params![session_id],
         )?;
+        tx.execute(
+            "DELETE FROM session_hashes WHERE session_id = ?1",
+            params![session_id],
+        )?;
+        tx.execute(
+            "DELETE FROM session_meta WHERE session_id = ?1",
+            params![session_id],
+        )?;
+        tx.commit()?;
         Ok(affected)
     }
 
+    /// Store the embedded annotation note of a session, replacing the old one.
+    pub fn store_note(
+        &self,
+        session_id: &str,
+        source: &str,
+        model: &str,
+        content: &str,
+        embedding: &[f32],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM chunks WHERE session_id = ?1 AND chunk_index = ?2",
+            params![session_id, NOTE_CHUNK],
+        )?;
+        tx.execute(
+            "INSERT INTO chunks (session_id, source, chunk_index, content, embedding, model, dimension)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                session_id,
+                source,
+                NOTE_CHUNK,
+                content,
+                embedding_to_blob(embedding),
+                model,
+                embedding.len() as i64,
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Embedded annotation notes, by session ID.
+    pub fn notes(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT session_id, content FROM chunks WHERE chunk_index = ?1")?;
+        let rows = stmt.query_map(params![NOTE_CHUNK], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Delete the embedded annotation note of a session.
+    pub fn delete_note(&self, session_id: &str) -> Result<bool> {
+        let affected = self.conn.execute(
+            "DELETE FROM chunks WHERE session_id = ?1 AND chunk_index = ?2",
+            params![session_id, NOTE_CHUNK],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Scan every chunk instead of searching the ANN graph (exact results,
+    /// slower on large stores).
+    pub fn set_exact(&mut self, exact: bool) {
+        self.exact = exact;
+    }
+
+    /// Restrict searches to the chunks of sessions matching a filter.
+    pub fn set_filter(&mut self, filter: SearchFilter) {
+        self.filter = filter;
+    }
+
     /// Search for similar chunks using cosine similarity.
     ///
-    /// Loads all embeddings into memory and computes similarity.
-    /// Efficient for up to ~100k chunks.
+    /// Stores of [`ANN_MIN_CHUNKS`] chunks or more are searched through the
+    /// ANN graph unless exact mode or a filter is set. Smaller stores, or a
+    /// graph that cannot be used, are scanned in full (the matching chunks
+    /// only, with a filter).
     pub fn search_similar(
         &self,
         query_embedding: &[f32],
         limit: usize,
     ) -> Result<Vec<SearchResult>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT session_id, source, chunk_index, content, embedding
-             FROM chunks",
-        )?;
+        if !self.exact && self.filter.is_empty() && self.chunk_count()? >= ANN_MIN_CHUNKS {
+            match self.search_ann(query_embedding, limit) {
+                Ok(results) => return Ok(results),
+                Err(e) => warn!("ANN search failed ({}), scanning all chunks", e),
+            }
+        }
+        self.search_exact(query_embedding, limit)
+    }
 
-        let mut results: Vec<SearchResult> = stmt
-            .query_map([], |row| {
-                let session_id: String = row.get(0)?;
-                let source: String = row.get(1)?;
-                let chunk_index: i64 = row.get(2)?;
-                let content: String = row.get(3)?;
-                let blob: Vec<u8> = row.get(4)?;
+    /// Search for similar chunks by scoring every chunk (matching the filter).
+    ///
+    /// Loads all embeddings into memory and computes similarity.
+    pub fn search_exact(&self, query_embedding: &[f32], limit: usize) -> Result<Vec<SearchResult>> {
+        let (conditions, args) = self.filter.sql();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT c.session_id, c.source, c.chunk_index, c.content, c.embedding
+             FROM chunks c
+             LEFT JOIN session_meta m ON m.session_id = c.session_id
+             WHERE 1 = 1{}",
+            conditions
+        ))?;
 
-                Ok((session_id, source, chunk_index as usize, content, blob))
+        let mut results: Vec<SearchResult> = stmt
+            .query_map(params_from_iter(args), |row| {
+                scored_result(row, query_embedding)
             })?
             .filter_map(|r| r.ok())
-            .map(|(session_id, source, chunk_index, content, blob)| {
-                let embedding = blob_to_embedding(&blob);
-                let score = cosine_similarity(query_embedding, &embedding);
-                SearchResult {
-                    session_id,
-                    source,
-                    chunk_index,
-                    chunk_content: content,
-                    score,
-                }
-            })
             .collect();
 
-        // Sort by score descending
-        results.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        sort_by_score(&mut results);
 
         // Take top-k
         results.truncate(limit);
@@ -277,6 +550,91 @@ impl EmbeddingStore {
         Ok(results)
     }
 
+    /// Search for similar chunks through the ANN graph, loading or
+    /// rebuilding it as needed. Scores are exact, recall is approximate.
+    fn search_ann(&self, query_embedding: &[f32], limit: usize) -> Result<Vec<SearchResult>> {
+        let revision = self.revision()?;
+        let mut cached = self.ann.borrow_mut();
+        let index = match cached.take() {
+            Some(index) if index.revision() == revision => index,
+            _ => self.load_or_build_ann(&revision)?,
+        };
+        let index = cached.insert(index);
+
+        let mut vector = self
+            .conn
+            .prepare_cached("SELECT embedding FROM chunks WHERE id = ?1")?;
+        let ids = index.search(limit, &mut |id| {
+            let blob: Vec<u8> = vector.query_row(params![id], |row| row.get(0))?;
+            Ok(1.0 - cosine_similarity(query_embedding, &blob_to_embedding(&blob)))
+        })?;
+
+        let mut chunk = self.conn.prepare_cached(
+            "SELECT session_id, source, chunk_index, content, embedding
+             FROM chunks WHERE id = ?1",
+        )?;
+        let mut results = ids
+            .into_iter()
+            .map(|id| chunk.query_row(params![id], |row| scored_result(row, query_embedding)))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        sort_by_score(&mut results);
+
+        Ok(results)
+    }
+
+    /// The persisted ANN graph if it matches the store revision, else a new
+    /// one built from every chunk (and persisted).
+    fn load_or_build_ann(&self, revision: &str) -> Result<HnswIndex> {
+        if let Some(path) = &self.ann_path {
+            match HnswIndex::load(path) {
+                Ok(index) if index.revision() == revision => return Ok(index),
+                Ok(_) => debug!("ANN index is stale, rebuilding"),
+                Err(e) if path.exists() => warn!("Cannot read ANN index ({}), rebuilding", e),
+                Err(_) => {}
+            }
+        }
+
+        let started = Instant::now();
+        let mut stmt = self.conn.prepare("SELECT id, embedding FROM chunks")?;
+        let (ids, vectors): (Vec<i64>, Vec<Vec<f32>>) = stmt
+            .query_map([], |row| {
+                let blob: Vec<u8> = row.get(1)?;
+                Ok((row.get::<_, i64>(0)?, blob_to_embedding(&blob)))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let index = HnswIndex::build(revision, ids, vectors)?;
+        info!(
+            "Built ANN index of {} chunks in {:.1?}",
+            index.len(),
+            started.elapsed()
+        );
+
+        if let Some(path) = &self.ann_path {
+            if let Err(e) = index.save(path) {
+                warn!("Cannot save ANN index: {}", e);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Current revision of the chunks.
+    fn revision(&self) -> Result<String> {
+        Ok(self.conn.query_row(
+            "SELECT value FROM embedding_meta WHERE key = 'revision'",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn chunk_count(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
     /// Search similar chunks, grouped by session (best chunk per session).
     pub fn search_sessions(
         &self,
@@ -303,19 +661,24 @@ impl EmbeddingStore {
 
     /// Keyword search using FTS5 on chunk content.
     ///
-    /// Returns chunks matching the FTS5 query, ranked by BM25.
+    /// Returns chunks matching the FTS5 query (and the filter), ranked by BM25.
     pub fn search_keyword(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        let mut stmt = self.conn.prepare(
+        let (conditions, filter_args) = self.filter.sql();
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT c.session_id, c.source, c.chunk_index, c.content, rank
              FROM chunks c
              JOIN chunks_fts fts ON c.rowid = fts.rowid
-             WHERE chunks_fts MATCH ?1
+             LEFT JOIN session_meta m ON m.session_id = c.session_id
+             WHERE chunks_fts MATCH ?{}
              ORDER BY rank
-             LIMIT ?2",
-        )?;
+             LIMIT {}",
+            conditions, limit
+        ))?;
+        let mut args = vec![query.to_string()];
+        args.extend(filter_args);
 
         let results: Vec<SearchResult> = stmt
-            .query_map(params![query, limit as i64], |row| {
+            .query_map(params_from_iter(args), |row| {
                 let session_id: String = row.get(0)?;
                 let source: String = row.get(1)?;
                 let chunk_index: i64 = row.get(2)?;
@@ -351,8 +714,6 @@ impl EmbeddingStore {
         limit: usize,
         alpha: f32,
     ) -> Result<Vec<HybridResult>> {
-        use std::collections::HashMap;
-
         let k = 60.0f32;
         let fetch_limit = limit * 3;
 
@@ -477,12 +838,37 @@ impl EmbeddingStore {
 
     /// Clear all embeddings.
     pub fn clear(&self) -> Result<()> {
-        self.conn.execute("DELETE FROM chunks", [])?;
+        self.conn.execute_batch(
+            "DELETE FROM chunks; DELETE FROM session_hashes; DELETE FROM session_meta;",
+        )?;
         info!("Cleared all embeddings");
         Ok(())
     }
 }
 
+/// Chunk row (session_id, source, chunk_index, content, embedding) scored
+/// against a query.
+fn scored_result(row: &rusqlite::Row, query_embedding: &[f32]) -> rusqlite::Result<SearchResult> {
+    let chunk_index: i64 = row.get(2)?;
+    let blob: Vec<u8> = row.get(4)?;
+    Ok(SearchResult {
+        session_id: row.get(0)?,
+        source: row.get(1)?,
+        chunk_index: chunk_index as usize,
+        chunk_content: row.get(3)?,
+        score: cosine_similarity(query_embedding, &blob_to_embedding(&blob)),
+    })
+}
+
+/// Sort by score descending.
+fn sort_by_score(results: &mut [SearchResult]) {
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
 /// Convert f32 vector to byte blob for SQLite storage.
 fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
     let mut blob = Vec::with_capacity(embedding.len() * 4);
@@ -527,7 +913,7 @@ mod tests {
             ("Goodbye world".to_string(), vec![0.0, 1.0, 0.0]),
         ];
 
-        store.store_session_chunks("s1", "test", "test-model", &chunks)?;
+        store.store_session_chunks("s1", "test", "test-model", "h1", &chunks)?;
         assert!(store.has_session("s1")?);
 
         // Search with a query similar to first chunk
@@ -544,17 +930,22 @@ mod tests {
         let store = EmbeddingStore::open_in_memory()?;
 
         let chunks1 = vec![("Old content".to_string(), vec![1.0, 0.0])];
-        store.store_session_chunks("s1", "test", "m1", &chunks1)?;
+        store.store_session_chunks("s1", "test", "m1", "h1", &chunks1)?;
 
         let chunks2 = vec![
             ("New content A".to_string(), vec![0.5, 0.5]),
             ("New content B".to_string(), vec![0.0, 1.0]),
         ];
-        store.store_session_chunks("s1", "test", "m1", &chunks2)?;
+        store.store_session_chunks("s1", "test", "m1", "h2", &chunks2)?;
 
         let stats = store.stats()?;
         assert_eq!(stats.total_chunks, 2); // Old was replaced
         assert_eq!(stats.total_sessions, 1);
+        assert_eq!(store.session_hash("s1")?.as_deref(), Some("h2"));
+
+        store.delete_session_chunks("s1")?;
+        assert_eq!(store.stats()?.total_chunks, 0);
+        assert_eq!(store.session_hash("s1")?, None);
 
         Ok(())
     }
@@ -564,7 +955,7 @@ mod tests {
         let store = EmbeddingStore::open_in_memory()?;
 
         let chunks = vec![("Content".to_string(), vec![1.0])];
-        store.store_session_chunks("s1", "test", "m1", &chunks)?;
+        store.store_session_chunks("s1", "test", "m1", "h1", &chunks)?;
         assert!(store.has_session("s1")?);
 
         store.delete_session("s1")?;
@@ -573,6 +964,169 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_note_kept_apart_from_conversation() -> Result<()> {
+        let store = EmbeddingStore::open_in_memory()?;
+
+        store.store_note("s1", "test", "m1", "Note: flaky test fix", &[0.0, 1.0])?;
+        assert!(!store.has_session("s1")?);
+
+        let chunks = vec![("Content".to_string(), vec![1.0, 0.0])];
+        store.store_session_chunks("s1", "test", "m1", "h1", &chunks)?;
+        store.store_session_chunks("s1", "test", "m1", "h1", &chunks)?;
+        assert!(store.has_session("s1")?);
+        assert_eq!(store.notes()?["s1"], "Note: flaky test fix");
+
+        let results = store.search_keyword("flaky", 10)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_index, usize::MAX);
+
+        assert!(store.delete_note("s1")?);
+        assert!(store.notes()?.is_empty());
+        assert_eq!(store.stats()?.total_chunks, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ann_search_follows_revision() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let store = EmbeddingStore::open(temp.path())?;
+        store.store_session_chunks(
+            "s1",
+            "test",
+            "m1",
+            "h1",
+            &[
+                ("a".to_string(), vec![1.0, 0.0, 0.0]),
+                ("b".to_string(), vec![0.0, 1.0, 0.0]),
+            ],
+        )?;
+
+        let results = store.search_ann(&[0.0, 1.0, 0.0], 1)?;
+        assert_eq!(results[0].chunk_content, "b");
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert!(temp.path().join(ANN_FILE).exists());
+
+        // New chunks change the revision, so the graph is rebuilt
+        let revision = store.revision()?;
+        store.store_session_chunks(
+            "s2",
+            "test",
+            "m1",
+            "h2",
+            &[("c".to_string(), vec![0.0, 0.0, 1.0])],
+        )?;
+        assert_ne!(store.revision()?, revision);
+        let results = store.search_ann(&[0.0, 0.0, 1.0], 1)?;
+        assert_eq!(results[0].chunk_content, "c");
+        assert_eq!(HnswIndex::load(&temp.path().join(ANN_FILE))?.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ann_index_reloaded() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        {
+            let store = EmbeddingStore::open(temp.path())?;
+            store.store_session_chunks(
+                "s1",
+                "test",
+                "m1",
+                "h1",
+                &[
+                    ("a".to_string(), vec![1.0, 0.0, 0.0]),
+                    ("b".to_string(), vec![0.0, 1.0, 0.0]),
+                ],
+            )?;
+            store.search_ann(&[1.0, 0.0, 0.0], 1)?;
+        }
+
+        // A new store loads the persisted graph of the same revision
+        let store = EmbeddingStore::open(temp.path())?;
+        let saved = HnswIndex::load(&temp.path().join(ANN_FILE))?;
+        assert_eq!(saved.revision(), store.revision()?);
+        let index = store.load_or_build_ann(&store.revision()?)?;
+        assert_eq!(index.len(), 2);
+        let results = store.search_ann(&[0.0, 1.0, 0.0], 1)?;
+        assert_eq!(results[0].chunk_content, "b");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_filter() -> Result<()> {
+        use chrono::TimeZone;
+
+        let mut store = EmbeddingStore::open_in_memory()?;
+        store.store_session_chunks(
+            "s1",
+            "cursor",
+            "m1",
+            "h1",
+            &[("fix the parser".to_string(), vec![1.0, 0.0])],
+        )?;
+        store.store_session_chunks(
+            "s2",
+            "cline",
+            "m1",
+            "h2",
+            &[("fix the layout".to_string(), vec![0.9, 0.1])],
+        )?;
+        assert!(store.needs_meta_backfill()?);
+        store.set_session_meta(
+            "s1",
+            &SessionMeta {
+                workspace: Some("EchoVault".to_string()),
+                started_at: Some(Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap()),
+                model: Some("claude-sonnet-4".to_string()),
+            },
+        )?;
+        store.set_session_meta("s2", &SessionMeta::default())?;
+        store.set_meta_backfilled()?;
+        assert!(!store.needs_meta_backfill()?);
+
+        let only = |filter: SearchFilter, store: &mut EmbeddingStore| -> Result<Vec<String>> {
+            store.set_filter(filter);
+            let mut ids: Vec<String> = store
+                .search_similar(&[1.0, 0.0], 10)?
+                .into_iter()
+                .map(|r| r.session_id)
+                .collect();
+            let mut keyword: Vec<String> = store
+                .search_keyword("fix", 10)?
+                .into_iter()
+                .map(|r| r.session_id)
+                .collect();
+            ids.sort();
+            keyword.sort();
+            assert_eq!(ids, keyword);
+            Ok(ids)
+        };
+
+        assert_eq!(only(SearchFilter::default(), &mut store)?, vec!["s1", "s2"]);
+        let by_source = SearchFilter {
+            source: Some("cline".to_string()),
+            ..SearchFilter::default()
+        };
+        assert_eq!(only(by_source, &mut store)?, vec!["s2"]);
+        let by_meta = SearchFilter {
+            workspace: Some("echo".to_string()),
+            since: NaiveDate::from_ymd_opt(2025, 1, 1),
+            model: Some("Claude".to_string()),
+            ..SearchFilter::default()
+        };
+        assert_eq!(only(by_meta, &mut store)?, vec!["s1"]);
+        let too_late = SearchFilter {
+            since: NaiveDate::from_ymd_opt(2025, 6, 1),
+            ..SearchFilter::default()
+        };
+        assert!(only(too_late, &mut store)?.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_search_sessions() -> Result<()> {
         let store = EmbeddingStore::open_in_memory()?;
@@ -582,6 +1136,7 @@ mod tests {
             "s1",
             "src1",
             "m1",
+            "h1",
             &[
                 ("s1 chunk 0".to_string(), vec![1.0, 0.0, 0.0]),
                 ("s1 chunk 1".to_string(), vec![0.9, 0.1, 0.0]),
@@ -592,6 +1147,7 @@ mod tests {
             "s2",
             "src2",
             "m1",
+            "h2",
             &[
                 ("s2 chunk 0".to_string(), vec![0.0, 1.0, 0.0]),
                 ("s2 chunk 1".to_string(), vec![0.0, 0.0, 1.0]),
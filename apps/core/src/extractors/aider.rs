@@ -261,7 +261,7 @@ impl Extractor for AiderExtractor {
                 true
             };
             if should_copy {
-                std::fs::copy(&chat_src, &chat_dest)?;
+                super::copy_session_file(&chat_src, &chat_dest)?;
                 copied = true;
             }
         }
@@ -278,7 +278,7 @@ impl Extractor for AiderExtractor {
                 true
             };
             if should_copy {
-                std::fs::copy(&llm_src, &llm_dest)?;
+                super::copy_session_file(&llm_src, &llm_dest)?;
                 copied = true;
             }
         }
@@ -299,7 +299,7 @@ impl Extractor for AntigravityExtractor {
         }
 
         // Sort by creation time (newest first)
-        sessions.sort_by(|a, b| b.metadata.created_at.cmp(&a.metadata.created_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
 
         Ok(sessions)
     }
@@ -367,7 +367,7 @@ impl Extractor for AntigravityExtractor {
         };
 
         if should_copy {
-            std::fs::copy(&session.source_path, &dest_path)?;
+            super::copy_session_file(&session.source_path, &dest_path)?;
             Ok(Some(dest_path))
         } else {
             Ok(None)
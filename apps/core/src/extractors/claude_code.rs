@@ -247,10 +247,27 @@ impl Extractor for ClaudeCodeExtractor {
             })
             .collect();
 
-        sessions.sort_by(|a, b| b.metadata.created_at.cmp(&a.metadata.created_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
         Ok(sessions)
     }
 
+    fn list_session_files_fast(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        let project_name = self.get_workspace_name(location);
+        Ok(std::fs::read_dir(location)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+            .filter_map(|path| {
+                super::path_metadata(&path, "claude-code", Some(project_name.clone())).map(
+                    |metadata| SessionFile {
+                        source_path: path,
+                        metadata,
+                    },
+                )
+            })
+            .collect())
+    }
+
     fn count_sessions(&self, location: &Path) -> Result<usize> {
         let count = std::fs::read_dir(location)?
             .flatten()
@@ -0,0 +1,203 @@
+//! Claude Desktop App Extractor
+//!
+//! Extracts conversations from the Claude Desktop app (Anthropic).
+//! ONLY COPY raw files, DO NOT parse/transform content.
+//!
+//! Claude Desktop is an Electron app: it keeps no conversation files of its
+//! own, but its HTTP cache holds the claude.ai API responses of every
+//! conversation opened in the app (`/api/organizations/{org}/chat_conversations/{uuid}`).
+//! Each response is a Chromium "simple cache" entry file, copied as-is; the
+//! parser unwraps the JSON body.
+//!
+//! Storage locations:
+//! - Windows: %APPDATA%/Claude/Cache/Cache_Data
+//! - macOS: ~/Library/Application Support/Claude/Cache/Cache_Data
+//! - Linux (community builds): ~/.config/Claude/Cache/Cache_Data
+
+use super::{Extractor, SessionFile, SessionMetadata};
+use crate::utils::wsl;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Magic number at the start of a Chromium simple cache entry file.
+pub const SIMPLE_CACHE_MAGIC: u64 = 0xfcfb6d1ba7725c30;
+/// Size of the entry header (magic, version, key length, key hash, padding).
+pub const SIMPLE_CACHE_HEADER_LEN: usize = 24;
+/// Path segment of the conversation API in cache keys.
+const CONVERSATION_API: &str = "/chat_conversations/";
+/// HTTP cache directory inside the app data directory.
+const CACHE_DIR: &str = "Cache/Cache_Data";
+/// Longest cache key read (keys are URLs with a short prefix).
+const MAX_KEY_LEN: usize = 4096;
+
+/// Claude Desktop Extractor
+pub struct ClaudeDesktopExtractor {
+    /// App data directories that may exist
+    storage_paths: Vec<PathBuf>,
+}
+
+impl ClaudeDesktopExtractor {
+    /// Create new extractor with default paths per platform.
+    pub fn new() -> Self {
+        let mut storage_paths = Vec::new();
+
+        // Prefer reading from HOME env variable (for testing with HOME override)
+        if let Ok(home) = std::env::var("HOME") {
+            storage_paths.push(PathBuf::from(home).join(".config/Claude"));
+        }
+
+        // Per platform via dirs crate: %APPDATA% (Windows),
+        // ~/Library/Application Support (macOS), ~/.config (Linux)
+        if let Some(config_dir) = dirs::config_dir() {
+            let path = config_dir.join("Claude");
+            if !storage_paths.contains(&path) {
+                storage_paths.push(path);
+            }
+        }
+
+        // Windows: Scan WSL for Linux builds of the app
+        for wsl_path in wsl::find_wsl_paths(".config/Claude") {
+            if !storage_paths.contains(&wsl_path) {
+                storage_paths.push(wsl_path);
+            }
+        }
+
+        Self { storage_paths }
+    }
+
+    /// Conversation UUID of a cache entry file, if it caches a conversation.
+    /// Only the header and key are read.
+    fn conversation_id(path: &Path) -> Option<String> {
+        let mut file = std::fs::File::open(path).ok()?;
+        let mut header = [0u8; SIMPLE_CACHE_HEADER_LEN];
+        file.read_exact(&mut header).ok()?;
+        if u64::from_le_bytes(header[..8].try_into().ok()?) != SIMPLE_CACHE_MAGIC {
+            return None;
+        }
+        let key_len = u32::from_le_bytes(header[12..16].try_into().ok()?) as usize;
+        if key_len > MAX_KEY_LEN {
+            return None;
+        }
+        let mut key = vec![0u8; key_len];
+        file.read_exact(&mut key).ok()?;
+        conversation_id_from_key(&String::from_utf8_lossy(&key))
+    }
+
+    fn extract_metadata(path: &Path) -> Option<SessionMetadata> {
+        let id = Self::conversation_id(path)?;
+        let metadata = std::fs::metadata(path).ok()?;
+        let created_at = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| DateTime::<Utc>::from_timestamp(d.as_secs() as i64, 0));
+
+        Some(SessionMetadata {
+            id,
+            source: "claude-desktop".to_string(),
+            title: None, // Filled in by enrichment from the parsed copy
+            created_at,
+            vault_path: PathBuf::new(),
+            original_path: path.to_path_buf(),
+            file_size: metadata.len(),
+            workspace_name: None,
+            ide_origin: None,
+        })
+    }
+}
+
+/// Conversation UUID in a cache key such as
+/// `1/0/_dk_https://claude.ai https://claude.ai https://claude.ai/api/organizations/{org}/chat_conversations/{uuid}?tree=True`.
+/// Keys of the conversation list (no UUID) yield `None`.
+fn conversation_id_from_key(key: &str) -> Option<String> {
+    let rest = &key[key.rfind(CONVERSATION_API)? + CONVERSATION_API.len()..];
+    let id = rest.split(['?', '/', '#']).next()?;
+    let is_uuid = id.len() == 36 && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    is_uuid.then(|| id.to_string())
+}
+
+impl Default for ClaudeDesktopExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extractor for ClaudeDesktopExtractor {
+    fn source_name(&self) -> &'static str {
+        "claude-desktop"
+    }
+
+    fn find_storage_locations(&self) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .storage_paths
+            .iter()
+            .map(|base| base.join(CACHE_DIR))
+            .filter(|dir| dir.is_dir())
+            .collect())
+    }
+
+    fn get_workspace_name(&self, _location: &Path) -> String {
+        // Claude Desktop conversations are not tied to a workspace
+        "Global".to_string()
+    }
+
+    fn list_session_files(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        // Stream 0/1 of an entry live in `{hash}_0`; other files hold sparse data
+        let entries = std::fs::read_dir(location)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().ends_with("_0"))
+            })
+            .filter_map(|path| Self::extract_metadata(&path));
+
+        // The same conversation may be cached under several URLs
+        // (query parameters differ); keep the most recent response
+        let mut latest: HashMap<String, SessionMetadata> = HashMap::new();
+        for metadata in entries {
+            match latest.get(&metadata.id) {
+                Some(existing) if existing.created_at >= metadata.created_at => {}
+                _ => {
+                    latest.insert(metadata.id.clone(), metadata);
+                }
+            }
+        }
+
+        let mut sessions: Vec<SessionFile> = latest
+            .into_values()
+            .map(|metadata| SessionFile {
+                source_path: metadata.original_path.clone(),
+                metadata,
+            })
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
+        Ok(sessions)
+    }
+
+    fn count_sessions(&self, location: &Path) -> Result<usize> {
+        Ok(self.list_session_files(location)?.len())
+    }
+
+    fn copy_to_vault(&self, session: &SessionFile, vault_dir: &Path) -> Result<Option<PathBuf>> {
+        // Cache file names are URL hashes; name the copy after the conversation
+        let source_dir = vault_dir.join(self.source_name());
+        std::fs::create_dir_all(&source_dir)?;
+        let dest_path = source_dir.join(&session.metadata.id);
+
+        let should_copy = match (session.source_path.metadata(), dest_path.metadata()) {
+            (Ok(src), Ok(dest)) => src.modified()? > dest.modified()? || src.len() != dest.len(),
+            _ => true,
+        };
+
+        if should_copy {
+            super::copy_session_file(&session.source_path, &dest_path)?;
+            Ok(Some(dest_path))
+        } else {
+            Ok(None)
+        }
+    }
+}
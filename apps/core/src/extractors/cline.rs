@@ -16,17 +16,14 @@ use serde_json::Value;
 use std::path::{Path, PathBuf};
 
 /// Cline VS Code Extension Extractor.
-/// Also supports Roo Code (fork of Cline) with extension ID `rooveterinaryinc.roo-cline`.
+/// Roo Code (the Cline fork) has its own extractor in [`super::roo_code`].
 pub struct ClineExtractor {
     /// Paths that may contain globalStorage
     storage_paths: Vec<PathBuf>,
 }
 
-/// Extension IDs for Cline and its forks.
-const CLINE_EXTENSION_IDS: &[&str] = &[
-    "saoudrizwan.claude-dev",     // Cline
-    "rooveterinaryinc.roo-cline", // Roo Code (Cline fork)
-];
+/// Extension IDs of Cline.
+const CLINE_EXTENSION_IDS: &[&str] = &["saoudrizwan.claude-dev"];
 
 /// VS Code variants that may host Cline.
 const VSCODE_VARIANTS: &[&str] = &["Code", "Code - Insiders", "Cursor", "Cursor - Insiders"];
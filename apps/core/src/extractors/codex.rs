@@ -8,7 +8,7 @@
 //! Config: ~/.codex/config.toml or .codex/config.toml (project-level)
 
 use super::{Extractor, SessionFile, SessionMetadata};
-use crate::utils::wsl;
+use crate::utils::{wsl, Walk};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rayon::prelude::*;
@@ -61,32 +61,13 @@ impl CodexExtractor {
     /// Find all JSONL session files recursively in sessions/ directory.
     /// Structure: sessions/YYYY/MM/DD/rollout-<timestamp>.jsonl
     fn find_session_files(sessions_dir: &Path) -> Vec<PathBuf> {
-        let mut files = Vec::new();
-
-        if !sessions_dir.exists() {
-            return files;
-        }
-
-        // Walk up to 4 levels: sessions/YYYY/MM/DD/*.jsonl
-        Self::walk_dir(sessions_dir, 0, 4, &mut files);
-        files
-    }
-
-    fn walk_dir(dir: &Path, depth: usize, max_depth: usize, files: &mut Vec<PathBuf>) {
-        if depth > max_depth {
-            return;
-        }
-
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    Self::walk_dir(&path, depth + 1, max_depth, files);
-                } else if path.extension().is_some_and(|ext| ext == "jsonl") {
-                    files.push(path);
-                }
-            }
-        }
+        // Sessions live at sessions/YYYY/MM/DD/*.jsonl
+        Walk::new(sessions_dir)
+            .max_depth(5)
+            .files()
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect()
     }
 
     /// Extract metadata from a Codex session JSONL file.
@@ -236,10 +217,24 @@ impl Extractor for CodexExtractor {
             })
             .collect();
 
-        sessions.sort_by(|a, b| b.metadata.created_at.cmp(&a.metadata.created_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
         Ok(sessions)
     }
 
+    fn list_session_files_fast(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        Ok(Self::find_session_files(location)
+            .into_iter()
+            .filter_map(|path| {
+                super::path_metadata(&path, "codex", Some("Codex CLI".to_string())).map(
+                    |metadata| SessionFile {
+                        source_path: path,
+                        metadata,
+                    },
+                )
+            })
+            .collect())
+    }
+
     fn count_sessions(&self, location: &Path) -> Result<usize> {
         Ok(Self::find_session_files(location).len())
     }
@@ -74,6 +74,22 @@ impl CursorExtractor {
         Self { storage_paths }
     }
 
+    /// JSON and JSONL session files in a workspace's `chatSessions` directory.
+    fn chat_session_paths(location: &Path) -> Result<Vec<PathBuf>> {
+        let chat_sessions_dir = location.join("chatSessions");
+        if !chat_sessions_dir.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(std::fs::read_dir(&chat_sessions_dir)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|ext| ext == "json" || ext == "jsonl")
+            })
+            .collect())
+    }
+
     /// Quick metadata extraction from JSON/JSONL file (only read required fields).
     fn extract_quick_metadata(
         &self,
@@ -239,22 +255,8 @@ impl Extractor for CursorExtractor {
     }
 
     fn list_session_files(&self, location: &Path) -> Result<Vec<SessionFile>> {
-        let chat_sessions_dir = location.join("chatSessions");
-        if !chat_sessions_dir.exists() {
-            return Ok(Vec::new());
-        }
-
         let workspace_name = self.get_workspace_name(location);
-
-        // Collect all JSON and JSONL paths
-        let json_paths: Vec<PathBuf> = std::fs::read_dir(&chat_sessions_dir)?
-            .flatten()
-            .map(|e| e.path())
-            .filter(|p| {
-                p.extension()
-                    .is_some_and(|ext| ext == "json" || ext == "jsonl")
-            })
-            .collect();
+        let json_paths = Self::chat_session_paths(location)?;
 
         // Extract metadata in parallel with rayon
         let mut sessions: Vec<SessionFile> = json_paths
@@ -269,11 +271,31 @@ impl Extractor for CursorExtractor {
             .collect();
 
         // Sort by creation time (newest first)
-        sessions.sort_by(|a, b| b.metadata.created_at.cmp(&a.metadata.created_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
 
         Ok(sessions)
     }
 
+    fn list_session_files_fast(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        // Ids come from the sessionId in the content, as list_session_files
+        // reads them, so sessions keep their ids
+        let workspace_name = self.get_workspace_name(location);
+        Ok(Self::chat_session_paths(location)?
+            .into_iter()
+            .filter_map(|path| {
+                let mut metadata =
+                    super::path_metadata(&path, "cursor", Some(workspace_name.clone()))?;
+                if let Some(id) = super::chat_session_id(&path) {
+                    metadata.id = id;
+                }
+                Some(SessionFile {
+                    source_path: path,
+                    metadata,
+                })
+            })
+            .collect())
+    }
+
     fn count_sessions(&self, location: &Path) -> Result<usize> {
         let chat_sessions_dir = location.join("chatSessions");
         if !chat_sessions_dir.exists() {
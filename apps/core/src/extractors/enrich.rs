@@ -0,0 +1,158 @@
+//! Background enrichment of session metadata.
+//!
+//! Ingest lists sessions from their file names and sizes alone (see
+//! [`super::Extractor::list_session_files_fast`]), so copying thousands of sessions
+//! is not serialized behind reading and parsing each one. This pass runs after
+//! the copy and fills in the titles and creation times of sessions that lack
+//! them, by parsing their copies in the vault. It also records the model and
+//! message count of each session for the usage dashboard.
+
+use crate::parsers::{all_parsers, parse_raw_file, ParsedConversation, Role};
+use crate::storage::{compressed, EnrichedMetadata, VaultDb};
+use anyhow::Result;
+use chrono::DateTime;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Fill in missing titles and creation times, and the models and message
+/// counts of new or changed sessions, from their vault copies. Returns how
+/// many sessions were updated.
+///
+/// Sessions whose content has no timestamp fall back to the source file's
+/// modification time, as listing does. So do sessions that fail to parse,
+/// which are recorded with no model or messages, so they are not parsed
+/// again until their file changes.
+pub fn enrich_metadata(vault_dir: &Path) -> Result<usize> {
+    let mut db = VaultDb::open(vault_dir)?;
+    let sessions = db.sessions_missing_metadata()?;
+    if sessions.is_empty() {
+        return Ok(0);
+    }
+
+    let parsers = all_parsers();
+    let metadata: Vec<_> = sessions
+        .par_iter()
+        .map(|session| {
+            let modified = DateTime::from_timestamp(session.mtime as i64, 0);
+            let conversation = parsers
+                .iter()
+                .find(|p| p.source_name() == session.source)
+                .and_then(|parser| {
+                    let raw_path = compressed::locate(&vault_dir.join(&session.vault_path));
+                    parse_raw_file(parser.as_ref(), &raw_path, &vault_dir.join("sessions")).ok()
+                });
+            let Some(conversation) = conversation else {
+                return EnrichedMetadata {
+                    id: session.id.clone(),
+                    mtime: session.mtime,
+                    title: None,
+                    created_at: modified.map(|d| d.to_rfc3339()),
+                    model: None,
+                    message_count: 0,
+                };
+            };
+            EnrichedMetadata {
+                id: session.id.clone(),
+                mtime: session.mtime,
+                model: main_model(&conversation),
+                message_count: conversation
+                    .messages
+                    .iter()
+                    .filter(|m| m.role == Role::User || m.role == Role::Assistant)
+                    .count() as u64,
+                title: conversation.title,
+                created_at: conversation.created_at.or(modified).map(|d| d.to_rfc3339()),
+            }
+        })
+        .collect();
+
+    db.set_session_metadata(&metadata)
+}
+
+/// Model of the conversation, or else the one behind most of its replies.
+fn main_model(conversation: &ParsedConversation) -> Option<String> {
+    if conversation.model.is_some() {
+        return conversation.model.clone();
+    }
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for model in conversation
+        .messages
+        .iter()
+        .filter_map(|m| m.model.as_deref())
+    {
+        *counts.entry(model).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(model, _)| model.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SessionEntry;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_enrich_fills_missing_metadata_once() -> Result<()> {
+        let vault = TempDir::new()?;
+        let dir = vault.path().join("sessions/claude-code");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("abc.jsonl");
+        fs::write(
+            &path,
+            "{\"role\":\"user\",\"content\":\"Fix the login bug\",\"timestamp\":\"2024-01-15T10:30:00Z\"}\n\
+             {\"role\":\"assistant\",\"content\":\"Done\",\"timestamp\":\"2024-01-15T10:31:00Z\"}\n",
+        )?;
+
+        let mut db = VaultDb::open(vault.path())?;
+        db.ingest_batch(&[SessionEntry {
+            id: "abc".to_string(),
+            source: "claude-code".to_string(),
+            mtime: 1_700_000_000,
+            file_size: 100,
+            title: None,
+            workspace_name: Some("project".to_string()),
+            created_at: None,
+            vault_path: path.to_string_lossy().to_string(),
+            original_path: "/home/user/.claude/projects/p/abc.jsonl".to_string(),
+            content_hash: None,
+        }])?;
+
+        assert_eq!(enrich_metadata(vault.path())?, 1);
+        let session = &db.get_all_sessions()?[0];
+        assert_eq!(session.title.as_deref(), Some("Fix the login bug"));
+        assert!(session
+            .created_at
+            .as_deref()
+            .unwrap()
+            .starts_with("2024-01-15T10:30:00"));
+        // Enriched sessions are not parsed again
+        assert_eq!(enrich_metadata(vault.path())?, 0);
+
+        // Dashboard queries see the enriched session
+        let models = db.messages_per_model("2024-01-01")?;
+        assert_eq!((models[0].sessions, models[0].messages), (1, 2));
+        let days = db.sessions_per_day("2024-01-01")?;
+        assert_eq!(days[0].date, "2024-01-15");
+        let workspaces = db.workspace_activity("2024-01-01", 5)?;
+        assert_eq!(workspaces[0].workspace, "project");
+        assert_eq!(workspaces[0].weekly[0].date, "2024-01-15");
+        assert!(db.sessions_per_day("2024-02-01")?.is_empty());
+
+        // A session that fails to parse is not parsed again until it changes
+        let broken = dir.join("missing.jsonl");
+        let mut entry = db.get_all_sessions()?.remove(0);
+        entry.id = "broken".to_string();
+        entry.title = None;
+        entry.created_at = None;
+        entry.vault_path = broken.to_string_lossy().to_string();
+        db.ingest_batch(&[entry])?;
+        assert_eq!(enrich_metadata(vault.path())?, 1);
+        assert_eq!(enrich_metadata(vault.path())?, 0);
+        Ok(())
+    }
+}
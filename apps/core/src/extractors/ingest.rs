@@ -0,0 +1,423 @@
+//! Ingest: copy new and changed sessions of every enabled source into the vault.
+//!
+//! Shared by the CLI (`extract`, `sync`, `watch`) and the desktop app, so both
+//! scan the same sources from [`super::all_extractors`] and record sessions the
+//! same way. Adding a source only takes registering its extractor there.
+//!
+//! Sessions pulled from other machines are recorded by
+//! [`import_vault_sessions`].
+
+use super::{scan_sources, store_session_file, LocationCache, SessionFile, SessionMetadata};
+use crate::config::Config;
+use crate::metrics;
+use crate::progress::Operation;
+use crate::storage::{compressed, mapped, SessionEntry, VaultDb};
+use crate::sync::delta;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Attempts at opening vault.db, which another process may hold briefly.
+const OPEN_ATTEMPTS: u32 = 3;
+
+/// Outcome of [`ingest_sessions`].
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Sessions found per enabled source, or why scanning it failed (registry order)
+    pub sources: Vec<(&'static str, Result<usize, String>)>,
+    /// Sessions found across sources
+    pub found: usize,
+    /// Sessions copied into the vault (new or changed)
+    pub copied: usize,
+    /// Sessions skipped because their vault copy is up to date
+    pub up_to_date: usize,
+    /// Copies that failed; their locations are rescanned next time
+    pub errors: Vec<String>,
+    /// Whether the copied sessions were recorded in vault.db
+    pub recorded: bool,
+}
+
+impl IngestReport {
+    /// Whether new sessions were recorded, whose titles and creation times
+    /// are left for [`super::enrich::enrich_metadata`] to fill in.
+    pub fn needs_enrichment(&self) -> bool {
+        self.recorded && self.copied > 0
+    }
+}
+
+/// Path of a session's plain copy in `sessions_dir`: `<source>/<id>.<ext>`,
+/// with the extension of the source file.
+pub fn vault_file_path(sessions_dir: &Path, metadata: &SessionMetadata) -> PathBuf {
+    let dest_dir = sessions_dir.join(&metadata.source);
+    let extension = metadata
+        .original_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("json");
+    if metadata.id.ends_with(extension) {
+        dest_dir.join(&metadata.id)
+    } else {
+        dest_dir.join(format!("{}.{}", metadata.id, extension))
+    }
+}
+
+fn open_vault_db(vault_dir: &Path) -> Result<VaultDb> {
+    let mut attempt = 1;
+    loop {
+        match VaultDb::open(vault_dir) {
+            Ok(db) => return Ok(db),
+            Err(e) if attempt < OPEN_ATTEMPTS => {
+                tracing::warn!("vault.db open attempt {} failed: {}, retrying", attempt, e);
+                std::thread::sleep(Duration::from_millis(500));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Modification time of a session's source file, in Unix seconds.
+fn source_mtime(session: &SessionFile) -> Option<u64> {
+    let modified = std::fs::metadata(&session.metadata.original_path)
+        .ok()?
+        .modified()
+        .ok()?;
+    Some(
+        modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    )
+}
+
+/// Scan the sources enabled in `config` and copy sessions that are new or
+/// changed since the last ingest into `<vault>/sessions/<source>/`, recording
+/// them in vault.db.
+///
+/// Files with a newer mtime are compared by BLAKE3 hash with the recorded
+/// content, so a source file rewritten as-is is not copied (or pushed) again.
+/// With `[extractors] compress`, copies are stored as `<id>.<ext>.zst`.
+///
+/// Storage locations unchanged since the last successful ingest are skipped.
+/// Runs on the current rayon pool. When `op` is cancelled, sessions already
+/// copied are still recorded and the rest are picked up next time.
+pub fn ingest_sessions(vault_dir: &Path, config: &Config, op: &Operation) -> Result<IngestReport> {
+    ingest(vault_dir, config, None, op)
+}
+
+/// Like [`ingest_sessions`], limited to the listed sources (e.g. the ones
+/// whose storage a watcher saw change).
+pub fn ingest_sources(
+    vault_dir: &Path,
+    config: &Config,
+    sources: &[&str],
+    op: &Operation,
+) -> Result<IngestReport> {
+    ingest(vault_dir, config, Some(sources), op)
+}
+
+fn ingest(
+    vault_dir: &Path,
+    config: &Config,
+    only: Option<&[&str]>,
+    op: &Operation,
+) -> Result<IngestReport> {
+    let mut report = IngestReport::default();
+    let cache = LocationCache::load(vault_dir);
+    let save_cache = || {
+        if let Err(e) = cache.save(vault_dir) {
+            tracing::warn!("Failed to save location cache: {}", e);
+        }
+    };
+
+    let mut sessions = Vec::new();
+    for scan in scan_sources(config, Some(&cache), only) {
+        match scan.sessions {
+            Ok(files) => {
+                report.sources.push((scan.source, Ok(files.len())));
+                sessions.extend(files);
+            }
+            Err(e) => report.sources.push((scan.source, Err(format!("{:#}", e)))),
+        }
+    }
+    report.found = sessions.len();
+    if sessions.is_empty() {
+        save_cache();
+        return Ok(report);
+    }
+
+    let mut vault_db = open_vault_db(vault_dir)?;
+    let sessions_dir = vault_dir.join("sessions");
+    std::fs::create_dir_all(&sessions_dir)?;
+
+    // Only sessions whose source file changed since it was last recorded,
+    // with the content hash recorded for them
+    let to_copy: Vec<(SessionFile, u64, Option<String>)> = sessions
+        .into_iter()
+        .filter_map(|session| {
+            let mtime = source_mtime(&session)?;
+            match vault_db.get_session_mtime(&session.metadata.id) {
+                Ok(Some(recorded)) if mtime <= recorded => None,
+                _ => {
+                    let hash = vault_db
+                        .get_session_hash(&session.metadata.id)
+                        .ok()
+                        .flatten();
+                    Some((session, mtime, hash))
+                }
+            }
+        })
+        .collect();
+    report.up_to_date = report.found - to_copy.len();
+    if to_copy.is_empty() {
+        save_cache();
+        return Ok(report);
+    }
+
+    op.start("ingest", to_copy.len() as u64);
+    let errors = Mutex::new(Vec::new());
+    // Recorded sessions, and whether each was copied: a file rewritten with
+    // the same content is only recorded with its new mtime
+    let entries: Vec<(SessionEntry, bool)> = to_copy
+        .par_iter()
+        .filter_map(|(session, mtime, recorded_hash)| {
+            if op.is_cancelled() {
+                return None;
+            }
+            let metadata = &session.metadata;
+            let plain_path = vault_file_path(&sessions_dir, metadata);
+            let dest_path =
+                if config.extractors.compress && compressed::applies_to(&metadata.source) {
+                    compressed::compressed_path(&plain_path)
+                } else {
+                    plain_path
+                };
+            let fail = |e: std::io::Error| {
+                if let Ok(mut errors) = errors.lock() {
+                    errors.push(format!("Failed to copy {}: {}", metadata.id, e));
+                }
+            };
+            let hash = match mapped::blake3_file(&metadata.original_path) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    op.advance(1);
+                    fail(e);
+                    return None;
+                }
+            };
+            // An unchanged copy is kept in whichever form it is stored
+            let existing = compressed::locate(&dest_path);
+            let unchanged = recorded_hash.as_ref() == Some(&hash) && existing.exists();
+            let dest_path = if unchanged { existing } else { dest_path };
+            let copied = if unchanged {
+                Ok(())
+            } else {
+                dest_path
+                    .parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .and_then(|_| store_session_file(&metadata.original_path, &dest_path))
+            };
+            op.advance(1);
+            if let Err(e) = copied {
+                fail(e);
+                return None;
+            }
+            let entry = SessionEntry {
+                id: metadata.id.clone(),
+                source: metadata.source.clone(),
+                mtime: *mtime,
+                file_size: metadata.file_size,
+                title: metadata.title.clone(),
+                workspace_name: metadata.workspace_name.clone(),
+                created_at: metadata.created_at.map(|d| d.to_rfc3339()),
+                vault_path: dest_path.to_string_lossy().to_string(),
+                original_path: metadata.original_path.to_string_lossy().to_string(),
+                content_hash: Some(hash),
+            };
+            Some((entry, !unchanged))
+        })
+        .collect();
+    op.finish();
+
+    report.copied = entries.iter().filter(|(_, copied)| *copied).count();
+    report.up_to_date += entries.len() - report.copied;
+    report.errors = errors.into_inner().unwrap_or_default();
+    report.recorded = entries.is_empty();
+    if !entries.is_empty() {
+        let records: Vec<SessionEntry> = entries.iter().map(|(entry, _)| entry.clone()).collect();
+        match vault_db.ingest_batch(&records) {
+            Ok(_) => report.recorded = true,
+            Err(e) => tracing::warn!("Failed to update vault.db: {}", e),
+        }
+        // Copied sessions go out with the next delta push
+        let copied: Vec<PathBuf> = entries
+            .iter()
+            .filter(|(_, copied)| *copied)
+            .map(|(entry, _)| PathBuf::from(&entry.vault_path))
+            .collect();
+        delta::mark_written(vault_dir, &copied);
+    }
+    metrics::record(config, metrics::INGEST_SESSIONS, report.copied as f64);
+
+    // Failed and cancelled copies are retried by rescanning their locations next time
+    if report.errors.is_empty() && report.recorded && !op.is_cancelled() {
+        save_cache();
+    }
+    for error in &report.errors {
+        tracing::warn!("{}", error);
+    }
+    op.check()?;
+
+    Ok(report)
+}
+
+/// Record sessions pulled from other machines in vault.db: session files in
+/// `sessions/` that are unknown or newer than their recorded mtime. Returns
+/// the number of sessions recorded. Compressed files are read through a
+/// plain copy.
+pub fn import_vault_sessions(vault_dir: &Path) -> Result<usize> {
+    let sessions_dir = vault_dir.join("sessions");
+    if !sessions_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut vault_db = VaultDb::open(vault_dir)?;
+    let existing_mtimes: HashMap<String, u64> = vault_db
+        .get_all_sessions()?
+        .into_iter()
+        .map(|s| (s.id, s.mtime))
+        .collect();
+
+    let mut sessions = Vec::new();
+    for source_dir in std::fs::read_dir(&sessions_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+    {
+        let source_name = source_dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let Ok(files) = std::fs::read_dir(&source_dir) else {
+            continue;
+        };
+
+        for stored_path in files.filter_map(|f| f.ok()).map(|f| f.path()) {
+            let file_path = compressed::plain_path(&stored_path);
+            let extension = file_path.extension().and_then(|e| e.to_str());
+            if !matches!(extension, Some("json" | "jsonl" | "pb" | "md")) {
+                continue;
+            }
+            let session_id = file_path
+                .file_stem()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+            if session_id.is_empty() {
+                continue;
+            }
+
+            let Ok(metadata) = std::fs::metadata(&stored_path) else {
+                continue;
+            };
+            let file_mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            if existing_mtimes
+                .get(&session_id)
+                .is_some_and(|&existing| existing >= file_mtime)
+            {
+                continue;
+            }
+
+            let Ok(plain) = compressed::Plain::open(&stored_path, &sessions_dir) else {
+                continue;
+            };
+            let (title, workspace_name, created_at) = pulled_metadata(plain.path());
+            let vault_path = format!(
+                "sessions/{}/{}",
+                source_name,
+                stored_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            );
+            sessions.push(SessionEntry {
+                id: session_id,
+                source: source_name.clone(),
+                mtime: file_mtime,
+                file_size: std::fs::metadata(plain.path()).map_or(metadata.len(), |m| m.len()),
+                title,
+                workspace_name,
+                created_at,
+                vault_path,
+                original_path: stored_path.to_string_lossy().to_string(),
+                content_hash: mapped::blake3_file(plain.path()).ok(),
+            });
+        }
+    }
+
+    if !sessions.is_empty() {
+        vault_db.upsert_batch(&sessions)?;
+    }
+    Ok(sessions.len())
+}
+
+/// Title, workspace name and creation time of a pulled session file, from
+/// the first line of a JSONL session (`v.customTitle`, `v.creationDate`) or
+/// the top-level fields of a JSON one.
+fn pulled_metadata(path: &Path) -> (Option<String>, Option<String>, Option<String>) {
+    let str_field = |json: &serde_json::Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| json.get(key))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    };
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("jsonl") => {
+            let first_line = std::fs::File::open(path)
+                .ok()
+                .and_then(|file| BufReader::new(file).lines().next())
+                .and_then(|line| line.ok());
+            let Some(obj) =
+                first_line.and_then(|l| serde_json::from_str::<serde_json::Value>(&l).ok())
+            else {
+                return (None, None, None);
+            };
+            let v = obj.get("v");
+            let title = v
+                .and_then(|v| v.get("customTitle"))
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            let created = v
+                .and_then(|v| v.get("creationDate"))
+                .and_then(|v| v.as_i64())
+                .map(|ts| {
+                    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ts)
+                        .map(|d| d.to_rfc3339())
+                        .unwrap_or_default()
+                });
+            (title, None, created)
+        }
+        Some("json") => match mapped::read(path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<serde_json::Value>(&content).ok())
+        {
+            Some(json) => (
+                str_field(&json, &["title", "name"]),
+                str_field(&json, &["workspace_name", "workspaceName"]),
+                str_field(&json, &["created_at", "createdAt"]),
+            ),
+            None => (None, None, None),
+        },
+        _ => (None, None, None),
+    }
+}
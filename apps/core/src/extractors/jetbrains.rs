@@ -250,7 +250,7 @@ impl Extractor for JetBrainsExtractor {
             }
         }
 
-        sessions.sort_by(|a, b| b.metadata.created_at.cmp(&a.metadata.created_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
         Ok(sessions)
     }
 
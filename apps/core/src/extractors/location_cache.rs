@@ -0,0 +1,156 @@
+//! Per-location scan cache.
+//!
+//! Listing a storage location opens and parses every session file for its
+//! metadata. Instead, each location gets a fingerprint built from a
+//! metadata-only walk (relative path, size and mtime of every entry, plus the
+//! source settings) and stored in vault.db. Locations whose fingerprint still
+//! matches the last successful ingest are skipped. Rows are keyed by host,
+//! since storage locations are machine-specific.
+
+use crate::config::SourceConfig;
+use crate::storage::VaultDb;
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Deepest nesting of session files below a location (Codex: sessions/YYYY/MM/DD/*.jsonl)
+const MAX_DEPTH: usize = 4;
+
+/// Fingerprint of a location's contents and the settings it is scanned with.
+pub fn fingerprint(location: &Path, settings: &SourceConfig) -> Option<String> {
+    let mut entries = Vec::new();
+    let mut pending = vec![(location.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        for entry in fs::read_dir(&dir).ok()?.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos());
+            let relative = path.strip_prefix(location).unwrap_or(&path).to_path_buf();
+            entries.push(format!(
+                "{}\t{}\t{}",
+                relative.to_string_lossy(),
+                metadata.len(),
+                mtime
+            ));
+            if metadata.is_dir() && depth < MAX_DEPTH {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{:?}\n", settings));
+    for entry in &entries {
+        hasher.update(entry.as_bytes());
+        hasher.update(b"\n");
+    }
+    Some(
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+/// Fingerprints from the last successful ingest, and those seen by this scan.
+#[derive(Default)]
+pub struct LocationCache {
+    known: HashMap<String, String>,
+    scanned: Mutex<Vec<(String, String)>>,
+}
+
+impl LocationCache {
+    /// Load the cache of this host (empty when the vault has none yet).
+    pub fn load(vault_dir: &Path) -> Self {
+        let known = if vault_dir.join("vault.db").exists() {
+            VaultDb::open(vault_dir)
+                .and_then(|db| db.location_fingerprints())
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        Self {
+            known,
+            scanned: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Whether a location is unchanged since the last saved scan.
+    ///
+    /// Changed locations are remembered so [`LocationCache::save`] can store
+    /// their new fingerprint once the ingest has succeeded.
+    pub fn is_unchanged(&self, location: &Path, settings: &SourceConfig) -> bool {
+        let Some(current) = fingerprint(location, settings) else {
+            return false;
+        };
+        let key = location.to_string_lossy().to_string();
+        if self.known.get(&key) == Some(&current) {
+            return true;
+        }
+        if let Ok(mut scanned) = self.scanned.lock() {
+            scanned.push((key, current));
+        }
+        false
+    }
+
+    /// Number of locations that were scanned (not skipped).
+    pub fn scanned(&self) -> usize {
+        self.scanned.lock().map_or(0, |s| s.len())
+    }
+
+    /// Store the fingerprints of the locations scanned by this run.
+    /// Call only after their sessions were ingested successfully.
+    pub fn save(&self, vault_dir: &Path) -> Result<()> {
+        let scanned = self.scanned.lock().map(|s| s.clone()).unwrap_or_default();
+        if scanned.is_empty() {
+            return Ok(());
+        }
+        VaultDb::open(vault_dir)?.save_location_fingerprints(&scanned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unchanged_locations_are_skipped() -> Result<()> {
+        let vault = TempDir::new()?;
+        let storage = TempDir::new()?;
+        let location = storage.path().join("chatSessions");
+        fs::create_dir_all(&location)?;
+        fs::write(location.join("a.json"), "{}")?;
+        let settings = SourceConfig::default();
+
+        let cache = LocationCache::load(vault.path());
+        assert!(!cache.is_unchanged(storage.path(), &settings));
+        cache.save(vault.path())?;
+
+        let cache = LocationCache::load(vault.path());
+        assert!(cache.is_unchanged(storage.path(), &settings));
+        assert_eq!(cache.scanned(), 0);
+
+        // New settings or a modified (nested) session file force a rescan
+        let excluding = SourceConfig {
+            exclude: vec!["**/b.json".to_string()],
+            ..SourceConfig::default()
+        };
+        assert!(!cache.is_unchanged(storage.path(), &excluding));
+        fs::write(location.join("a.json"), "{\"requests\": []}")?;
+        assert!(!cache.is_unchanged(storage.path(), &settings));
+        Ok(())
+    }
+}
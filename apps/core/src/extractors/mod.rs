@@ -3,11 +3,12 @@
 //! Principle: ONLY COPY raw files, DO NOT format/transform data.
 //! This ensures no information loss when IDE changes format.
 //!
-//! ## Supported sources (12)
+//! ## Supported sources (15)
 //!
 //! ### Extensions (plugins inside host IDEs)
 //! - `vscode-copilot`: GitHub Copilot Chat (VS Code, VS Code Insiders)
-//! - `cline`: Cline (Claude Dev) and Roo Code fork (VS Code, Cursor)
+//! - `cline`: Cline (Claude Dev) (VS Code, Cursor)
+//! - `roo-code`: Roo Code, the Cline fork (VS Code, Cursor, Windsurf, VSCodium)
 //! - `continue-dev`: Continue.dev (VS Code, JetBrains)
 //!
 //! ### Standalone IDEs
@@ -15,6 +16,10 @@
 //! - `jetbrains`: JetBrains AI Assistant (IntelliJ, PyCharm, WebStorm, etc.)
 //! - `zed`: Zed Editor (built-in AI)
 //! - `antigravity`: Google Antigravity IDE
+//! - `windsurf`: Windsurf IDE (Codeium Cascade)
+//!
+//! ### Desktop apps
+//! - `claude-desktop`: Claude Desktop app (cached conversations)
 //!
 //! ### CLI tools
 //! - `gemini-cli`: Google Gemini CLI
@@ -26,21 +31,32 @@
 pub mod aider;
 pub mod antigravity;
 pub mod claude_code;
+pub mod claude_desktop;
 pub mod cline;
 pub mod codex;
 pub mod continue_dev;
 pub mod cursor;
+pub mod enrich;
 pub mod gemini_cli;
+pub mod ingest;
 pub mod jetbrains;
+pub mod location_cache;
 pub mod opencode;
+pub mod roo_code;
 pub mod vscode_copilot;
+pub mod windsurf;
 pub mod zed;
 
+use crate::config::{Config, SourceConfig};
+use crate::storage::{atomic, compressed, mapped};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+pub use location_cache::LocationCache;
+
 /// Distinguishes whether a source is a standalone IDE/CLI or a plugin inside another IDE.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExtractorKind {
@@ -87,7 +103,7 @@ pub struct SessionFile {
 
 /// Trait for all extractors.
 /// Extractors only find and copy files, DO NOT parse content in detail.
-pub trait Extractor: Sync {
+pub trait Extractor: Send + Sync {
     /// Source name (vscode-copilot, cursor, etc.)
     fn source_name(&self) -> &'static str;
 
@@ -111,6 +127,15 @@ pub trait Extractor: Sync {
     /// List all session files in a location.
     fn list_session_files(&self, location: &Path) -> Result<Vec<SessionFile>>;
 
+    /// List session files for ingest, without reading their contents.
+    ///
+    /// Titles and creation times may be left unset for [`enrich`] to fill in
+    /// from the vault copies, so ingest is not held up by parsing thousands of
+    /// sessions. Defaults to [`Extractor::list_session_files`].
+    fn list_session_files_fast(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        self.list_session_files(location)
+    }
+
     /// Count sessions in a location (fast, no metadata parsing).
     fn count_sessions(&self, location: &Path) -> Result<usize>;
 
@@ -140,14 +165,18 @@ pub trait Extractor: Sync {
             let src_modified = src_meta.modified()?;
             let dest_modified = dest_meta.modified()?;
 
-            // Copy if source is newer or size differs
-            src_modified > dest_modified || src_meta.len() != dest_meta.len()
+            // Copy if the size differs, or if the source is newer and its
+            // content differs (IDEs rewrite files without changing them)
+            src_meta.len() != dest_meta.len()
+                || (src_modified > dest_modified
+                    && mapped::blake3_file(&session.source_path)?
+                        != mapped::blake3_file(&dest_path)?)
         } else {
             true // File doesn't exist, need to copy
         };
 
         if should_copy {
-            std::fs::copy(&session.source_path, &dest_path)?;
+            copy_session_file(&session.source_path, &dest_path)?;
             Ok(Some(dest_path))
         } else {
             Ok(None) // File unchanged
@@ -155,21 +184,247 @@ pub trait Extractor: Sync {
     }
 }
 
+/// Glob of the temporary files older versions of [`copy_session_file`]
+/// wrote, which vault scans still skip.
+pub const INGEST_TEMP_GLOB: &str = "**/.*.ingest";
+
+/// Copy a session file into the vault.
+///
+/// The file is cloned as a reflink where the filesystem supports it (see
+/// [`atomic::copy`]). Hard links are not used: pulls and decryption rewrite
+/// vault files in place, which would write through into the IDE's own file.
+///
+/// The copy replaces `dest` atomically, so a failed copy keeps the previous
+/// version.
+pub fn copy_session_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    atomic::copy(src, dest)
+}
+
+/// Store a session file in the vault as `dest`, compressing it when `dest`
+/// is a `.zst` path (see [`compressed`]), and remove the other form of the
+/// file left by an earlier setting.
+pub fn store_session_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let other = if compressed::is_compressed(dest) {
+        compressed::compress(src, dest)?;
+        compressed::plain_path(dest)
+    } else {
+        copy_session_file(src, dest)?;
+        compressed::compressed_path(dest)
+    };
+    match std::fs::remove_file(other) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
 /// Create all extractors.
-/// Centralizes the extractor registry so CLI and Tauri don't duplicate the list.
+/// Centralizes the extractor registry so CLI and Tauri don't duplicate the list;
+/// [`ingest::ingest_sessions`] and the frontends' scans iterate over it.
 pub fn all_extractors() -> Vec<Box<dyn Extractor>> {
     vec![
         Box::new(vscode_copilot::VSCodeCopilotExtractor::new()),
         Box::new(cursor::CursorExtractor::new()),
         Box::new(cline::ClineExtractor::new()),
+        Box::new(roo_code::RooCodeExtractor::new()),
         Box::new(continue_dev::ContinueDevExtractor::new()),
         Box::new(jetbrains::JetBrainsExtractor::new()),
         Box::new(zed::ZedExtractor::new()),
         Box::new(antigravity::AntigravityExtractor::new()),
+        Box::new(windsurf::WindsurfExtractor::new()),
         Box::new(gemini_cli::GeminiCliExtractor::new()),
         Box::new(claude_code::ClaudeCodeExtractor::new()),
+        Box::new(claude_desktop::ClaudeDesktopExtractor::new()),
         Box::new(aider::AiderExtractor::new()),
         Box::new(codex::CodexExtractor::new()),
         Box::new(opencode::OpenCodeExtractor::new()),
     ]
 }
+
+/// Metadata of a session file from its path and size alone, for
+/// [`Extractor::list_session_files_fast`]. The id is the file stem, as the
+/// extractors listing file contents use for these sources; files too small
+/// to hold a session are skipped.
+pub fn path_metadata(
+    path: &Path,
+    source: &str,
+    workspace_name: Option<String>,
+) -> Option<SessionMetadata> {
+    let id = path.file_stem()?.to_str()?.to_string();
+    let file_size = std::fs::metadata(path).ok()?.len();
+    if id.is_empty() || file_size < 10 {
+        return None;
+    }
+    Some(SessionMetadata {
+        id,
+        source: source.to_string(),
+        title: None,
+        created_at: None,
+        vault_path: PathBuf::new(),
+        original_path: path.to_path_buf(),
+        file_size,
+        workspace_name,
+        ide_origin: None,
+    })
+}
+
+/// `sessionId` of a VS Code-style chat session file (Copilot, Cursor), the
+/// id [`Extractor::list_session_files`] gives it: from the `v` header on the
+/// first line of a JSONL file, or the top level of a JSON one, which is
+/// streamed without building the document. `None` when the file has none,
+/// in which case the file stem is the id.
+pub fn chat_session_id(path: &Path) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Header {
+        #[serde(rename = "sessionId")]
+        session_id: Option<String>,
+    }
+    #[derive(serde::Deserialize)]
+    struct JsonlHeader {
+        v: Header,
+    }
+
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+    let header = if path.extension().is_some_and(|ext| ext == "jsonl") {
+        let mut first_line = String::new();
+        std::io::BufRead::read_line(&mut reader, &mut first_line).ok()?;
+        serde_json::from_str::<JsonlHeader>(&first_line).ok()?.v
+    } else {
+        serde_json::from_reader::<_, Header>(reader).ok()?
+    };
+    header.session_id.filter(|id| !id.is_empty())
+}
+
+/// Storage locations of an extractor: the detected ones plus `extra_paths`.
+pub fn storage_locations(extractor: &dyn Extractor, settings: &SourceConfig) -> Vec<PathBuf> {
+    let mut locations = extractor.find_storage_locations().unwrap_or_default();
+    for path in &settings.extra_paths {
+        if !path.is_dir() {
+            tracing::warn!(
+                "{}: extra path {:?} is not a directory",
+                extractor.source_name(),
+                path
+            );
+        } else if !locations.contains(path) {
+            locations.push(path.clone());
+        }
+    }
+    locations
+}
+
+/// Session files of a source, filtered by its `[sources.<name>]` settings.
+/// Disabled sources yield nothing, and so do locations `cache` reports unchanged.
+pub fn collect_sessions(
+    extractor: &dyn Extractor,
+    settings: &SourceConfig,
+    cache: Option<&LocationCache>,
+) -> Result<Vec<SessionFile>> {
+    if !settings.enabled {
+        return Ok(Vec::new());
+    }
+    let exclude = crate::watcher::build_ignore_set(&settings.exclude)?;
+    let max_size = settings.max_file_size();
+
+    let sessions = storage_locations(extractor, settings)
+        .par_iter()
+        .filter(|location| !cache.is_some_and(|c| c.is_unchanged(location, settings)))
+        .filter_map(|location| extractor.list_session_files_fast(location).ok())
+        .flatten()
+        .filter(|session| {
+            !exclude.is_match(&session.source_path)
+                && max_size.is_none_or(|max| session.metadata.file_size <= max)
+        })
+        .collect();
+    Ok(sessions)
+}
+
+/// Sessions found for one source by [`scan_sources`].
+pub struct SourceScan {
+    pub source: &'static str,
+    pub sessions: Result<Vec<SessionFile>>,
+}
+
+/// Scan every enabled source concurrently, returning results in registry order.
+/// With `only`, sources not listed there are skipped.
+///
+/// Sources run as separate rayon tasks (as do the storage locations of each
+/// source), so one slow IDE directory no longer holds up the others.
+pub fn scan_sources(
+    config: &Config,
+    cache: Option<&LocationCache>,
+    only: Option<&[&str]>,
+) -> Vec<SourceScan> {
+    all_extractors()
+        .into_par_iter()
+        .filter_map(|extractor| {
+            let source = extractor.source_name();
+            if only.is_some_and(|only| !only.contains(&source)) {
+                return None;
+            }
+            let settings = config.source(source);
+            settings.enabled.then(|| SourceScan {
+                source,
+                sessions: collect_sessions(extractor.as_ref(), &settings, cache),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_copy_session_file_replaces_previous_copy() -> Result<()> {
+        let temp = TempDir::new()?;
+        let src = temp.path().join("session.jsonl");
+        let dest = temp.path().join("vault-session.jsonl");
+        std::fs::write(&src, "{\"a\": 1}\n")?;
+        copy_session_file(&src, &dest)?;
+        assert_eq!(std::fs::read(&dest)?, std::fs::read(&src)?);
+
+        // A new version replaces the copy without touching the source
+        std::fs::write(&src, "{\"a\": 1}\n{\"b\": 2}\n")?;
+        copy_session_file(&src, &dest)?;
+        assert_eq!(std::fs::read(&dest)?, std::fs::read(&src)?);
+        assert!(copy_session_file(&temp.path().join("missing"), &dest).is_err());
+        assert_eq!(std::fs::read(&dest)?, std::fs::read(&src)?);
+        assert_eq!(std::fs::read_dir(temp.path())?.count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_session_file_switches_form() -> Result<()> {
+        let temp = TempDir::new()?;
+        let src = temp.path().join("session.jsonl");
+        let dest = temp.path().join("vault-session.jsonl");
+        std::fs::write(&src, "{\"a\": 1}\n")?;
+        store_session_file(&src, &dest)?;
+
+        let packed = compressed::compressed_path(&dest);
+        store_session_file(&src, &packed)?;
+        assert!(packed.exists() && !dest.exists());
+        let plain = compressed::Plain::open(&packed, temp.path())?;
+        assert_eq!(std::fs::read(plain.path())?, std::fs::read(&src)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chat_session_id_reads_content() -> Result<()> {
+        let temp = TempDir::new()?;
+        let jsonl = temp.path().join("a.jsonl");
+        std::fs::write(
+            &jsonl,
+            "{\"kind\":0,\"v\":{\"sessionId\":\"s-1\"}}\n{\"kind\":1}\n",
+        )?;
+        let json = temp.path().join("b.json");
+        std::fs::write(&json, "{\"requests\":[{\"x\":1}],\"sessionId\":\"s-2\"}")?;
+        let bare = temp.path().join("c.json");
+        std::fs::write(&bare, "{\"requests\":[]}")?;
+
+        assert_eq!(chat_session_id(&jsonl).as_deref(), Some("s-1"));
+        assert_eq!(chat_session_id(&json).as_deref(), Some("s-2"));
+        assert_eq!(chat_session_id(&bare), None);
+        Ok(())
+    }
+}
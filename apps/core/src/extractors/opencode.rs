@@ -222,7 +222,7 @@ impl Extractor for OpenCodeExtractor {
         };
 
         if should_copy {
-            std::fs::copy(&session.source_path, &dest_path)?;
+            super::copy_session_file(&session.source_path, &dest_path)?;
             Ok(Some(dest_path))
         } else {
             Ok(None)
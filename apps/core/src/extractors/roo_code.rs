@@ -0,0 +1,221 @@
+//! Roo Code (Roo Cline) VS Code Extension Extractor
+//!
+//! Extracts task history from Roo Code, the Cline fork. Roo Code keeps its
+//! tasks under its own extension ID, separate from Cline's.
+//! ONLY COPY raw JSON files, DO NOT parse/transform content.
+//!
+//! Storage locations:
+//! - Windows: %APPDATA%/Code/User/globalStorage/rooveterinaryinc.roo-cline/tasks
+//! - macOS: ~/Library/Application Support/Code/User/globalStorage/rooveterinaryinc.roo-cline/tasks
+//! - Linux: ~/.config/Code/User/globalStorage/rooveterinaryinc.roo-cline/tasks
+
+use super::{Extractor, ExtractorKind, SessionFile, SessionMetadata};
+use crate::utils::wsl;
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Roo Code VS Code Extension Extractor.
+pub struct RooCodeExtractor {
+    /// Task directories that may exist
+    storage_paths: Vec<PathBuf>,
+}
+
+/// Extension ID of Roo Code.
+const ROO_CODE_EXTENSION_ID: &str = "rooveterinaryinc.roo-cline";
+
+/// Conversation file inside each task folder.
+const TASK_HISTORY_FILE: &str = "api_conversation_history.json";
+
+/// VS Code variants that may host Roo Code.
+const VSCODE_VARIANTS: &[&str] = &[
+    "Code",
+    "Code - Insiders",
+    "Cursor",
+    "Cursor - Insiders",
+    "Windsurf",
+    "VSCodium",
+];
+
+impl RooCodeExtractor {
+    /// Create new extractor with default paths per platform.
+    pub fn new() -> Self {
+        let mut storage_paths = Vec::new();
+
+        let add_paths = |base: &PathBuf, paths: &mut Vec<PathBuf>| {
+            for variant in VSCODE_VARIANTS {
+                let path = base.join(format!(
+                    "{}/User/globalStorage/{}/tasks",
+                    variant, ROO_CODE_EXTENSION_ID
+                ));
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        };
+
+        // Prefer reading from HOME env variable
+        if let Ok(home) = std::env::var("HOME") {
+            let home_config = PathBuf::from(home).join(".config");
+            add_paths(&home_config, &mut storage_paths);
+        }
+
+        // Fallback: Get path per platform via dirs crate (%APPDATA% on Windows)
+        if let Some(config_dir) = dirs::config_dir() {
+            add_paths(&config_dir, &mut storage_paths);
+        }
+
+        // Windows: Scan WSL for Roo Code installations
+        for variant in VSCODE_VARIANTS {
+            let subpath = format!(
+                ".config/{}/User/globalStorage/{}/tasks",
+                variant, ROO_CODE_EXTENSION_ID
+            );
+            for wsl_path in wsl::find_wsl_paths(&subpath) {
+                if !storage_paths.contains(&wsl_path) {
+                    storage_paths.push(wsl_path);
+                }
+            }
+        }
+
+        Self { storage_paths }
+    }
+
+    /// Title from the first user text of a task (truncated to 60 chars).
+    fn task_title(history: &Path) -> Option<String> {
+        let content = std::fs::read_to_string(history).ok()?;
+        let json: Value = serde_json::from_str(&content).ok()?;
+        let text = json
+            .as_array()?
+            .first()?
+            .get("content")?
+            .as_array()?
+            .first()?
+            .get("text")?
+            .as_str()?;
+        let truncated: String = text.chars().take(60).collect();
+        Some(if text.chars().count() > 60 {
+            format!("{}...", truncated)
+        } else {
+            truncated
+        })
+    }
+
+    /// Extract metadata from task folder.
+    fn extract_task_metadata(&self, task_dir: &Path) -> Option<SessionMetadata> {
+        let history = task_dir.join(TASK_HISTORY_FILE);
+        let task_id = task_dir.file_name()?.to_str()?.to_string();
+
+        // Total size of the task folder (history, UI messages, metadata)
+        let file_size = std::fs::read_dir(task_dir)
+            .ok()?
+            .flatten()
+            .filter_map(|e| std::fs::metadata(e.path()).ok())
+            .map(|m| m.len())
+            .sum();
+
+        let created_at = std::fs::metadata(task_dir)
+            .ok()
+            .and_then(|m| m.created().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| Utc.timestamp_opt(d.as_secs() as i64, 0).single());
+
+        Some(SessionMetadata {
+            id: task_id,
+            source: "roo-code".to_string(),
+            title: Self::task_title(&history),
+            created_at,
+            vault_path: PathBuf::new(),
+            original_path: history,
+            file_size,
+            workspace_name: None,
+            ide_origin: None,
+        })
+    }
+}
+
+impl Default for RooCodeExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extractor for RooCodeExtractor {
+    fn source_name(&self) -> &'static str {
+        "roo-code"
+    }
+
+    fn extractor_kind(&self) -> ExtractorKind {
+        ExtractorKind::Extension
+    }
+
+    fn supported_ides(&self) -> &'static [&'static str] {
+        &[
+            "VS Code",
+            "VS Code Insiders",
+            "Cursor",
+            "Windsurf",
+            "VSCodium",
+        ]
+    }
+
+    fn find_storage_locations(&self) -> Result<Vec<PathBuf>> {
+        let mut locations = Vec::new();
+
+        for storage_path in &self.storage_paths {
+            let Ok(entries) = std::fs::read_dir(storage_path) else {
+                continue;
+            };
+            // Each task is a subdirectory with its conversation history
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.join(TASK_HISTORY_FILE).is_file() {
+                    locations.push(path);
+                }
+            }
+        }
+
+        Ok(locations)
+    }
+
+    fn get_workspace_name(&self, _location: &Path) -> String {
+        "Roo Code Tasks".to_string()
+    }
+
+    fn list_session_files(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        // location is task folder
+        Ok(self
+            .extract_task_metadata(location)
+            .map(|metadata| SessionFile {
+                source_path: location.join(TASK_HISTORY_FILE),
+                metadata,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    fn count_sessions(&self, location: &Path) -> Result<usize> {
+        // Each location is a task, so count = 1
+        Ok(usize::from(location.join(TASK_HISTORY_FILE).exists()))
+    }
+
+    fn copy_to_vault(&self, session: &SessionFile, vault_dir: &Path) -> Result<Option<PathBuf>> {
+        // Every task has the same file name, so name the copy after the task ID
+        let source_dir = vault_dir.join(self.source_name());
+        std::fs::create_dir_all(&source_dir)?;
+        let dest_path = source_dir.join(format!("{}.json", session.metadata.id));
+
+        let should_copy = match (session.source_path.metadata(), dest_path.metadata()) {
+            (Ok(src), Ok(dest)) => src.modified()? > dest.modified()? || src.len() != dest.len(),
+            _ => true,
+        };
+
+        if should_copy {
+            super::copy_session_file(&session.source_path, &dest_path)?;
+            Ok(Some(dest_path))
+        } else {
+            Ok(None)
+        }
+    }
+}
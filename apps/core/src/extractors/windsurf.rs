@@ -0,0 +1,154 @@
+//! Windsurf Extractor
+//!
+//! Extracts Cascade chat history from the Windsurf IDE (Codeium).
+//! ONLY COPY raw files, DO NOT parse/transform content.
+//!
+//! Storage locations (same layout on every platform, under the user's home):
+//! - Linux/macOS: ~/.codeium/windsurf/cascade/{uuid}.pb
+//! - Windows: %USERPROFILE%\.codeium\windsurf\cascade\{uuid}.pb
+//! - Windsurf Next builds use ~/.codeium/windsurf-next/ instead
+//!
+//! On Windows, WSL homes are scanned as well.
+
+use super::{Extractor, SessionFile, SessionMetadata};
+use crate::utils::wsl;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Windsurf data directories relative to the home directory (stable, Next).
+const WINDSURF_DIRS: [&str; 2] = [".codeium/windsurf", ".codeium/windsurf-next"];
+/// Cascade conversations directory inside a Windsurf data directory.
+const CASCADE_DIR: &str = "cascade";
+
+/// Windsurf (Codeium IDE) Extractor.
+/// Cascade conversations are opaque .pb files, copied as-is.
+pub struct WindsurfExtractor {
+    /// Paths that may contain Windsurf data
+    storage_paths: Vec<PathBuf>,
+}
+
+impl WindsurfExtractor {
+    /// Create new extractor with default paths per platform.
+    pub fn new() -> Self {
+        let mut storage_paths = Vec::new();
+
+        for dir in WINDSURF_DIRS {
+            // Prefer reading from HOME env variable (for testing with HOME override)
+            if let Ok(home) = std::env::var("HOME") {
+                storage_paths.push(Path::new(&home).join(dir));
+            }
+
+            // Fallback: home directory via dirs crate (%USERPROFILE% on Windows)
+            if let Some(home) = dirs::home_dir() {
+                let path = home.join(dir);
+                if !storage_paths.contains(&path) {
+                    storage_paths.push(path);
+                }
+            }
+
+            // Windows: Scan WSL for Windsurf data (remote WSL sessions)
+            for wsl_path in wsl::find_wsl_paths(dir) {
+                if !storage_paths.contains(&wsl_path) {
+                    storage_paths.push(wsl_path);
+                }
+            }
+        }
+
+        Self { storage_paths }
+    }
+
+    /// Whether a directory holds at least one Cascade conversation.
+    fn has_conversations(dir: &Path) -> bool {
+        std::fs::read_dir(dir).is_ok_and(|entries| entries.flatten().any(|e| is_pb(&e.path())))
+    }
+
+    /// Extract metadata from a Cascade conversation (.pb file).
+    fn extract_conversation_metadata(&self, path: &Path) -> Option<SessionMetadata> {
+        // Get UUID from filename (e.g., 0b7d2f4e-9c1a-4f0e-8b3d-2a6c5e1f9d70.pb)
+        let session_id = path.file_stem()?.to_str()?.to_string();
+
+        let metadata = std::fs::metadata(path).ok()?;
+
+        // Use modified time as created_at (approximation)
+        let created_at = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| DateTime::<Utc>::from_timestamp(d.as_secs() as i64, 0));
+
+        Some(SessionMetadata {
+            id: session_id,
+            source: "windsurf".to_string(),
+            title: Some("Cascade Conversation".to_string()), // Protobuf has no readable title
+            created_at,
+            vault_path: PathBuf::new(),
+            original_path: path.to_path_buf(),
+            file_size: metadata.len(),
+            workspace_name: None, // Cascade history is global, not per workspace
+            ide_origin: None,
+        })
+    }
+}
+
+impl Default for WindsurfExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_pb(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "pb")
+}
+
+impl Extractor for WindsurfExtractor {
+    fn source_name(&self) -> &'static str {
+        "windsurf"
+    }
+
+    fn find_storage_locations(&self) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .storage_paths
+            .iter()
+            .map(|base_path| base_path.join(CASCADE_DIR))
+            .filter(|dir| dir.is_dir() && Self::has_conversations(dir))
+            .collect())
+    }
+
+    fn get_workspace_name(&self, _location: &Path) -> String {
+        // Cascade history is not tied to a specific workspace
+        "Global".to_string()
+    }
+
+    fn list_session_files(&self, location: &Path) -> Result<Vec<SessionFile>> {
+        let pb_files: Vec<PathBuf> = std::fs::read_dir(location)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| is_pb(p))
+            .collect();
+
+        let mut sessions: Vec<SessionFile> = pb_files
+            .par_iter()
+            .filter_map(|path| {
+                self.extract_conversation_metadata(path)
+                    .map(|metadata| SessionFile {
+                        source_path: path.clone(),
+                        metadata,
+                    })
+            })
+            .collect();
+
+        // Sort by creation time (newest first)
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
+
+        Ok(sessions)
+    }
+
+    fn count_sessions(&self, location: &Path) -> Result<usize> {
+        Ok(std::fs::read_dir(location)?
+            .flatten()
+            .filter(|e| is_pb(&e.path()))
+            .count())
+    }
+}
@@ -357,7 +357,7 @@ impl Extractor for ZedExtractor {
             sessions.extend(text_sessions);
         }
 
-        sessions.sort_by(|a, b| b.metadata.created_at.cmp(&a.metadata.created_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.metadata.created_at));
         Ok(sessions)
     }
 
@@ -414,7 +414,7 @@ impl Extractor for ZedExtractor {
                 true
             };
             if should_copy {
-                std::fs::copy(&session.source_path, &dest_path)?;
+                super::copy_session_file(&session.source_path, &dest_path)?;
                 Ok(Some(dest_path))
             } else {
                 Ok(None)
@@ -436,7 +436,7 @@ impl Extractor for ZedExtractor {
                 true
             };
             if should_copy {
-                std::fs::copy(&session.source_path, &dest_path)?;
+                super::copy_session_file(&session.source_path, &dest_path)?;
                 Ok(Some(dest_path))
             } else {
                 Ok(None)
@@ -0,0 +1,208 @@
+//! Localized user-facing strings of the backend: tray menu, notifications
+//! and command errors shown by the desktop app.
+//!
+//! Messages are looked up by key in a built-in table per [`Language`], chosen
+//! with `language` in the config (English by default). Keys missing from a
+//! translation fall back to English. `{name}` placeholders are filled by
+//! [`tf`]. Logs stay in English.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::RwLock;
+
+/// Language of user-facing messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// English
+    #[default]
+    En,
+    /// Vietnamese
+    Vi,
+}
+
+impl Language {
+    /// All supported languages.
+    pub const ALL: [Language; 2] = [Language::En, Language::Vi];
+
+    /// ISO 639-1 code (`en`, `vi`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Vi => "vi",
+        }
+    }
+
+    /// Language of a locale such as `vi-VN` or `vi_VN.UTF-8`, if supported.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale.split(['-', '_', '.']).next()?.to_lowercase();
+        Self::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+
+    fn messages(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::En => EN,
+            Language::Vi => VI,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Language used by [`t`] and [`tf`] in this process
+static LANGUAGE: RwLock<Language> = RwLock::new(Language::En);
+
+/// Select the language of messages (from `language` in the config).
+pub fn set_language(language: Language) {
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language;
+    }
+}
+
+/// Language of messages in this process.
+pub fn language() -> Language {
+    LANGUAGE.read().map(|lang| *lang).unwrap_or_default()
+}
+
+/// Message `key` in `language`, falling back to English, then to the key.
+pub fn t_in(language: Language, key: &'static str) -> &'static str {
+    let lookup = |messages: &'static [(&'static str, &'static str)]| {
+        messages.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    };
+    lookup(language.messages())
+        .or_else(|| lookup(EN))
+        .unwrap_or(key)
+}
+
+/// Message `key` in the current language.
+pub fn t(key: &'static str) -> &'static str {
+    t_in(language(), key)
+}
+
+/// Message `key` in the current language with `{name}` placeholders filled.
+pub fn tf(key: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter()
+        .fold(t(key).to_string(), |message, (name, value)| {
+            message.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+}
+
+/// English messages (every key must be here).
+const EN: &[(&str, &str)] = &[
+    // Tray menu
+    ("tray.toggle", "Show/Hide Window"),
+    ("tray.search", "Quick Search"),
+    ("tray.quit", "Exit"),
+    // Notifications
+    ("notify.digest_daily", "Daily digest: {start}"),
+    ("notify.digest_weekly", "Weekly digest: {start} to {end}"),
+    ("notify.digest_monthly", "Monthly digest: {month}"),
+    ("notify.digest_body", "{count} new conversations"),
+    // Command errors
+    (
+        "error.connect_cloud",
+        "Please connect to cloud storage first",
+    ),
+    ("error.not_authenticated", "Not authenticated"),
+    (
+        "error.vault_locked",
+        "Vault is locked. Enter your passphrase to sync.",
+    ),
+    (
+        "error.sync_in_progress",
+        "Sync in progress, try again later",
+    ),
+    ("error.invalid_config", "Invalid configuration: {issues}"),
+    ("error.unknown_source", "Unknown source: {source}"),
+    ("error.unknown_preset", "Unknown preset: {preset}"),
+    ("error.unknown_profile", "Unknown profile: {profile}"),
+    ("error.profile_env", "The profile is set by {var}"),
+    ("error.file_not_found", "File not found: {path}"),
+    ("error.vault_db", "Failed to open vault.db: {error}"),
+    ("error.vault_path_env", "vault_path is set by {var}"),
+    ("error.update_check", "Failed to check for updates: {error}"),
+    (
+        "error.interceptor_running",
+        "Interceptor is already running",
+    ),
+    ("error.encryption_enabled", "Encryption is already enabled"),
+    ("error.invalid_date", "Invalid date: {error}"),
+];
+
+/// Vietnamese messages
+const VI: &[(&str, &str)] = &[
+    ("tray.toggle", "Hiện/Ẩn cửa sổ"),
+    ("tray.search", "Tìm kiếm nhanh"),
+    ("tray.quit", "Thoát"),
+    ("notify.digest_daily", "Tổng kết ngày {start}"),
+    ("notify.digest_weekly", "Tổng kết tuần {start} đến {end}"),
+    ("notify.digest_monthly", "Tổng kết tháng {month}"),
+    ("notify.digest_body", "{count} cuộc hội thoại mới"),
+    (
+        "error.connect_cloud",
+        "Vui lòng kết nối lưu trữ đám mây trước",
+    ),
+    ("error.not_authenticated", "Chưa xác thực"),
+    (
+        "error.vault_locked",
+        "Vault đang bị khóa. Nhập passphrase để đồng bộ.",
+    ),
+    (
+        "error.sync_in_progress",
+        "Đang đồng bộ, vui lòng thử lại sau",
+    ),
+    ("error.invalid_config", "Cấu hình không hợp lệ: {issues}"),
+    ("error.unknown_source", "Nguồn không xác định: {source}"),
+    ("error.unknown_preset", "Preset không xác định: {preset}"),
+    ("error.unknown_profile", "Profile không xác định: {profile}"),
+    ("error.profile_env", "Profile đang được đặt bởi {var}"),
+    ("error.file_not_found", "Không tìm thấy file: {path}"),
+    ("error.vault_db", "Không mở được vault.db: {error}"),
+    ("error.vault_path_env", "vault_path đang được đặt bởi {var}"),
+    (
+        "error.update_check",
+        "Không kiểm tra được bản cập nhật: {error}",
+    ),
+    ("error.interceptor_running", "Interceptor đang chạy"),
+    ("error.encryption_enabled", "Mã hóa đã được bật"),
+    ("error.invalid_date", "Ngày không hợp lệ: {error}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translations_use_known_keys_and_placeholders() {
+        for (key, message) in VI {
+            let english = EN.iter().find(|(k, _)| k == key).map(|(_, v)| *v);
+            let english = english.unwrap_or_else(|| panic!("{} is not an English key", key));
+            let placeholders = |text: &str| {
+                let mut names: Vec<String> = text
+                    .split('{')
+                    .skip(1)
+                    .filter_map(|rest| rest.split_once('}').map(|(name, _)| name.to_string()))
+                    .collect();
+                names.sort();
+                names
+            };
+            assert_eq!(placeholders(message), placeholders(english), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_lookup_and_placeholders() {
+        assert_eq!(t_in(Language::Vi, "tray.quit"), "Thoát");
+        assert_eq!(t_in(Language::Vi, "no.such.key"), "no.such.key");
+        assert_eq!(
+            tf("error.unknown_source", &[("source", &"foo")]),
+            "Unknown source: foo"
+        );
+        assert_eq!(Language::from_locale("vi_VN.UTF-8"), Some(Language::Vi));
+        assert_eq!(Language::from_locale("fr-FR"), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! ChatGPT Export Importer
+//!
+//! Imports the official ChatGPT data export (Settings > Data controls >
+//! Export data). The export is a zip holding `conversations.json`, a JSON
+//! array of every conversation; each one is written to
+//! `sessions/chatgpt/{conversation_id}.json` unmodified and parsed by
+//! [`crate::parsers::chatgpt`].
+
+use super::ImportSummary;
+use crate::storage::{atomic, LocalDb, SessionEntry, VaultDb};
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde_json::Value;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Source name of imported ChatGPT conversations.
+pub const SOURCE: &str = "chatgpt";
+
+/// Conversations file inside the export.
+const CONVERSATIONS_FILE: &str = "conversations.json";
+
+/// Read `conversations.json` from an export zip, or from the file itself
+/// when it is not a zip (an extracted export).
+fn read_conversations(export: &Path) -> Result<Vec<Value>> {
+    let file =
+        std::fs::File::open(export).with_context(|| format!("Cannot open {}", export.display()))?;
+    let mut json = String::new();
+    match zip::ZipArchive::new(file) {
+        Ok(mut archive) => {
+            let name = archive
+                .file_names()
+                .find(|name| {
+                    *name == CONVERSATIONS_FILE
+                        || name.ends_with(&format!("/{}", CONVERSATIONS_FILE))
+                })
+                .map(String::from)
+                .with_context(|| format!("No {} in {}", CONVERSATIONS_FILE, export.display()))?;
+            archive.by_name(&name)?.read_to_string(&mut json)?;
+        }
+        Err(_) => {
+            json = std::fs::read_to_string(export)?;
+        }
+    }
+    serde_json::from_str(&json).context("conversations.json is not a JSON array of conversations")
+}
+
+/// Import a ChatGPT export into the vault. Conversations whose file in the
+/// vault is already identical are left alone, so importing a newer export
+/// only writes what changed.
+pub fn import_export(export: &Path, vault_dir: &Path) -> Result<ImportSummary> {
+    let conversations = read_conversations(export)?;
+    let source_dir = vault_dir.join("sessions").join(SOURCE);
+    std::fs::create_dir_all(&source_dir)?;
+
+    let mut summary = ImportSummary::default();
+    let mut entries = Vec::new();
+    for conv in &conversations {
+        let Some(id) = conv
+            .get("conversation_id")
+            .or_else(|| conv.get("id"))
+            .and_then(|v| v.as_str())
+        else {
+            continue;
+        };
+        let data = serde_json::to_vec(conv)?;
+        let path = source_dir.join(format!("{}.json", id));
+        if std::fs::read(&path).is_ok_and(|existing| existing == data) {
+            summary.unchanged += 1;
+            continue;
+        }
+        atomic::write(&path, &data)?;
+
+        let time = |key: &str| conv.get(key).and_then(|v| v.as_f64());
+        entries.push(SessionEntry {
+            id: id.to_string(),
+            source: SOURCE.to_string(),
+            mtime: time("update_time").or(time("create_time")).unwrap_or(0.0) as u64,
+            file_size: data.len() as u64,
+            title: conv.get("title").and_then(|v| v.as_str()).map(String::from),
+            workspace_name: None,
+            created_at: time("create_time")
+                .and_then(|t| DateTime::from_timestamp(t as i64, 0))
+                .map(|d| d.to_rfc3339()),
+            vault_path: format!("sessions/{}/{}.json", SOURCE, id),
+            original_path: export.to_string_lossy().to_string(),
+            content_hash: Some(blake3::hash(&data).to_hex().to_string()),
+        });
+    }
+
+    if !entries.is_empty() {
+        let mut db = VaultDb::open(vault_dir)?;
+        db.ingest_batch(&entries)?;
+        let written: Vec<PathBuf> = entries
+            .iter()
+            .map(|e| PathBuf::from(&e.vault_path))
+            .collect();
+        LocalDb::open(vault_dir)?.mark_dirty_files(&written)?;
+    }
+    summary.imported = entries.len();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{chatgpt::ChatGptParser, Parser, Role};
+    use serde_json::json;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn conversation() -> Value {
+        json!({
+            "id": "c1",
+            "conversation_id": "c1",
+            "title": "Borrow checker",
+            "create_time": 1_700_000_000.5,
+            "update_time": 1_700_000_100.0,
+            "current_node": "n3",
+            "mapping": {
+                "root": { "id": "root", "message": null, "parent": null, "children": ["n1"] },
+                "n1": {
+                    "id": "n1", "parent": "root", "children": ["n2", "n2b"],
+                    "message": {
+                        "author": { "role": "user" },
+                        "create_time": 1_700_000_000.5,
+                        "content": { "content_type": "text", "parts": ["Why does this not compile?"] }
+                    }
+                },
+                "n2b": {
+                    "id": "n2b", "parent": "n1", "children": [],
+                    "message": {
+                        "author": { "role": "assistant" },
+                        "content": { "content_type": "text", "parts": ["An abandoned branch"] }
+                    }
+                },
+                "n2": {
+                    "id": "n2", "parent": "n1", "children": ["n3"],
+                    "message": {
+                        "author": { "role": "system" },
+                        "metadata": { "is_visually_hidden_from_conversation": true },
+                        "content": { "content_type": "text", "parts": [""] }
+                    }
+                },
+                "n3": {
+                    "id": "n3", "parent": "n2", "children": [],
+                    "message": {
+                        "author": { "role": "assistant" },
+                        "metadata": { "model_slug": "gpt-4o" },
+                        "content": { "content_type": "text", "parts": ["The value was moved."] }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_import_export_and_parse() -> Result<()> {
+        let temp = TempDir::new()?;
+        let vault = temp.path().join("vault");
+        let export = temp.path().join("export.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&export)?);
+        zip.start_file(CONVERSATIONS_FILE, zip::write::SimpleFileOptions::default())?;
+        zip.write_all(serde_json::to_string(&json!([conversation()]))?.as_bytes())?;
+        zip.finish()?;
+
+        let summary = import_export(&export, &vault)?;
+        assert_eq!(summary.imported, 1);
+        let db = VaultDb::open(&vault)?;
+        assert_eq!(db.get_session_mtime("c1")?, Some(1_700_000_100));
+
+        // Importing the same export again writes nothing
+        let summary = import_export(&export, &vault)?;
+        assert_eq!((summary.imported, summary.unchanged), (0, 1));
+
+        // Only the branch of the current node is kept, hidden messages are not
+        let conv = ChatGptParser.parse(&vault.join("sessions/chatgpt/c1.json"))?;
+        assert_eq!(conv.title.as_deref(), Some("Borrow checker"));
+        assert_eq!(conv.messages.len(), 2);
+        assert_eq!(conv.messages[0].role, Role::User);
+        assert_eq!(conv.messages[1].content, "The value was moved.");
+        assert_eq!(conv.model.as_deref(), Some("gpt-4o"));
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+//! Manual Importer
+//!
+//! Imports a single chat the user saved by hand (pasted from a web UI) as a
+//! Markdown or OpenAI-messages JSON file. The file is copied unmodified to
+//! `sessions/manual/<id>.md|json`, with the title and origin given on import
+//! in a `<id>.meta.json` sidecar, and parsed by [`crate::parsers::manual`].
+//!
+//! The ID is derived from the file content, so importing the same file again
+//! only updates its title and origin (or does nothing when they are the same).
+
+use super::ImportSummary;
+use crate::parsers::manual::{self, ManualMeta, ManualParser, SOURCE};
+use crate::parsers::Parser;
+use crate::storage::{atomic, LocalDb, SessionEntry, VaultDb};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Import a chat file into the vault.
+///
+/// `origin` names where the chat came from (e.g. "claude.ai"); it is kept as
+/// an `origin:` tag of the conversation. The file must hold at least one
+/// message, so a typo'd path or an unrelated file is rejected up front.
+pub fn import_file(
+    path: &Path,
+    vault_dir: &Path,
+    title: Option<&str>,
+    origin: Option<&str>,
+) -> Result<ImportSummary> {
+    let data = std::fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let extension = match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => "json",
+        Some("md" | "markdown" | "txt") | None => "md",
+        Some(other) => bail!(
+            "Unsupported chat file '.{}' (use Markdown or OpenAI-messages JSON)",
+            other
+        ),
+    };
+    let content = String::from_utf8(data).context("Chat file is not UTF-8 text")?;
+    let messages = match extension {
+        "json" => manual::parse_json(&content)?,
+        _ => manual::parse_markdown(&content).0,
+    };
+    if messages.is_empty() {
+        bail!(
+            "No messages found in {} (mark turns with lines such as \"## User\" and \"## Assistant\")",
+            path.display()
+        );
+    }
+
+    let digest = Sha256::digest(content.as_bytes());
+    let id = format!(
+        "manual-{}",
+        digest[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    );
+    let source_dir = vault_dir.join("sessions").join(SOURCE);
+    std::fs::create_dir_all(&source_dir)?;
+    let raw_path = source_dir.join(format!("{}.{}", id, extension));
+
+    let modified: Option<DateTime<Utc>> = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::from);
+    let meta = ManualMeta {
+        title: title.map(String::from),
+        origin: origin.filter(|o| *o != SOURCE).map(String::from),
+        created_at: modified,
+    };
+    let meta_path = ManualMeta::path(&raw_path);
+    let previous: Option<ManualMeta> = std::fs::read(&meta_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok());
+    let unchanged = std::fs::read_to_string(&raw_path).is_ok_and(|existing| existing == content)
+        && previous
+            .as_ref()
+            .is_some_and(|p| p.title == meta.title && p.origin == meta.origin);
+    if unchanged {
+        return Ok(ImportSummary {
+            imported: 0,
+            unchanged: 1,
+        });
+    }
+    // Keep the date of the first import when the same chat is imported again
+    let meta = ManualMeta {
+        created_at: previous.and_then(|p| p.created_at).or(meta.created_at),
+        ..meta
+    };
+    atomic::write(&raw_path, &content)?;
+    atomic::write(&meta_path, serde_json::to_vec_pretty(&meta)?)?;
+
+    let title = match meta.title {
+        Some(title) => Some(title),
+        None => ManualParser.parse(&raw_path).ok().and_then(|c| c.title),
+    };
+    let vault_path = format!("sessions/{}/{}.{}", SOURCE, id, extension);
+    let meta_vault_path = format!("sessions/{}/{}.meta.json", SOURCE, id);
+    let mut db = VaultDb::open(vault_dir)?;
+    db.ingest_batch(&[SessionEntry {
+        id,
+        source: SOURCE.to_string(),
+        mtime: Utc::now().timestamp() as u64,
+        file_size: content.len() as u64,
+        title,
+        workspace_name: None,
+        created_at: meta.created_at.map(|t| t.to_rfc3339()),
+        vault_path: vault_path.clone(),
+        original_path: path.to_string_lossy().to_string(),
+        content_hash: Some(blake3::hash(content.as_bytes()).to_hex().to_string()),
+    }])?;
+    LocalDb::open(vault_dir)?
+        .mark_dirty_files(&[PathBuf::from(vault_path), PathBuf::from(meta_vault_path)])?;
+
+    Ok(ImportSummary {
+        imported: 1,
+        unchanged: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{parse_vault_source, Role};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_file_and_parse() -> Result<()> {
+        let temp = TempDir::new()?;
+        let vault = temp.path().join("vault");
+        let chat = temp.path().join("chat.md");
+        std::fs::write(
+            &chat,
+            "You said:\nWhat is a lifetime?\n\nClaude said:\nA scope.\n",
+        )?;
+
+        let summary = import_file(&chat, &vault, Some("Lifetimes"), Some("claude.ai"))?;
+        assert_eq!(summary.imported, 1);
+        let summary = import_file(&chat, &vault, Some("Lifetimes"), Some("claude.ai"))?;
+        assert_eq!((summary.imported, summary.unchanged), (0, 1));
+
+        let (conversations, errors) = parse_vault_source(&ManualParser, &vault.join("sessions"));
+        assert!(errors.is_empty());
+        assert_eq!(conversations.len(), 1);
+        let conv = &conversations[0];
+        assert_eq!(conv.title.as_deref(), Some("Lifetimes"));
+        assert_eq!(conv.tags, vec!["origin:claude.ai".to_string()]);
+        assert_eq!(conv.messages[1].role, Role::Assistant);
+
+        let db = VaultDb::open(&vault)?;
+        assert!(db.get_session_mtime(&conv.id)?.is_some());
+
+        let empty = temp.path().join("empty.md");
+        std::fs::write(&empty, "Just some notes\n")?;
+        assert!(import_file(&empty, &vault, None, None).is_err());
+        Ok(())
+    }
+}
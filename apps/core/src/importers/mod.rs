@@ -0,0 +1,23 @@
+//! Importers module - Bring in conversations from data exports.
+//!
+//! Unlike extractors, which copy what an IDE keeps on disk, importers read an
+//! archive the user downloaded from a chat service. Each conversation is
+//! written to `sessions/<source>/` as its own raw file (unmodified JSON) and
+//! recorded in vault.db, so it flows through parse, embed and search like
+//! any extracted session.
+//!
+//! ## Supported exports
+//! - `chatgpt`: ChatGPT data export (`conversations.json`, zipped or not)
+//! - `manual`: a single chat saved by hand (Markdown or OpenAI-messages JSON)
+
+pub mod chatgpt;
+pub mod manual;
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Conversations written (new or changed since the last import)
+    pub imported: usize,
+    /// Conversations already in the vault unchanged
+    pub unchanged: usize,
+}
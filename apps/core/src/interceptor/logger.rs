@@ -180,13 +180,15 @@ fn short_hash(s: &str) -> String {
     format!("{:06x}", hash & 0xFFFFFF)
 }
 
-/// Append a line to a file (create if not exists).
+/// Append a line to a file (create if not exists), replacing it atomically so
+/// parsers never see a half-written exchange.
 fn append_line(path: &std::path::Path, line: &str) -> std::io::Result<()> {
-    use std::io::Write;
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)?;
-    writeln!(file, "{}", line)?;
-    Ok(())
+    let mut content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    content.extend_from_slice(line.as_bytes());
+    content.push(b'\n');
+    crate::storage::atomic::write(path, content)
 }
@@ -3,23 +3,55 @@
 //! Core library for EchoVault - "Black Box" for AI chat history.
 //! Provides the following capabilities:
 //! - Extract chat sessions from various IDEs (VS Code Copilot, Cursor, Cline, Antigravity, etc.)
+//! - Import conversations from chat service data exports (ChatGPT)
 //! - Parse raw files into clean structured Markdown conversations
+//! - Export conversations as fine-tuning datasets (JSONL), with secrets redacted
+//! - Render conversations as PDF documents to share
 //! - Intercept API traffic via MITM proxy (feature-gated: `interceptor`)
-//! - Sync with Google Drive via Rclone
+//! - Sync with Google Drive via Rclone, optionally end-to-end encrypted
+//! - Redact secrets from what is pushed to chosen sync targets or exported
+//! - Keep secrets (passphrase, API keys) in the OS keyring
+//! - Record local metrics (sync durations, ingest counts, search latencies)
+//! - Estimate token usage and costs per model
+//! - Detect the same conversation captured by several sources
+//! - Tag conversations with their languages and topics
+//! - Write daily/weekly digests of new conversations
+//! - Localize user-facing messages of the desktop app (English, Vietnamese)
+//! - Report progress of, and cancel, long-running operations
+//! - Run pull, extract, parse, embed and push as one pipeline
+//! - Serve sessions, search and sync over a local REST API (feature-gated: `api`)
+//! - Send webhook notifications of sync and extraction events (feature-gated: `notifications`)
 //!
 //! Pipeline: Extract (raw copy) -> Parse (structured Markdown) -> Embed (semantic vectors) -> Search/MCP
 
+pub mod analytics;
+#[cfg(feature = "api")]
+pub mod api;
 pub mod config;
+pub mod crypto;
+pub mod dedupe;
+pub mod digest;
 #[cfg(feature = "embedding")]
 pub mod embedding;
 pub mod extractors;
+pub mod i18n;
+pub mod importers;
 #[cfg(feature = "interceptor")]
 pub mod interceptor;
 #[cfg(feature = "mcp")]
 pub mod mcp;
+pub mod metrics;
+#[cfg(feature = "notifications")]
+pub mod notifications;
 pub mod parsers;
+pub mod pipeline;
+pub mod progress;
+pub mod redact;
+pub mod render;
+pub mod secrets;
 pub mod storage;
 pub mod sync;
+pub mod tags;
 pub mod utils;
 pub mod vault;
 pub mod watcher;
@@ -28,7 +60,11 @@ pub mod watcher;
 pub use config::Config;
 pub use extractors::{all_extractors, Extractor, ExtractorKind};
 pub use parsers::{ParsedConversation, Parser};
+pub use progress::{CancellationToken, Operation, Progress};
 pub use storage::SessionIndex;
-pub use sync::{AuthStatus, PullResult, PushResult, RcloneProvider, SyncOptions, SyncProvider};
+pub use sync::{
+    AuthStatus, LocalFolderProvider, PullResult, PushResult, RcloneProvider, SyncOptions,
+    SyncProvider,
+};
 pub use vault::VaultMetadata;
 pub use watcher::FileWatcher;
@@ -3,11 +3,24 @@
 //! Phase 5 of the EchoVault pipeline:
 //! Exposes vault data via MCP tools for AI assistants.
 //!
-//! Tools (2-tool pattern, minimizing token usage):
-//! - `vault` - Unified tool: list, search, read, semantic_search
-//! - `help`  - On-demand documentation for the `vault` tool
+//! Tools (minimizing token usage):
+//! - `vault`    - Unified read-only tool: list, search, read, semantic_search
+//! - `search_conversations` - Semantic or keyword search with filters,
+//!   returning structured results
+//! - `annotate` - Set the note, rating or follow-up flag of a session
+//! - `annotate_session` - Set the tags and note of a session
+//! - `help`     - On-demand documentation for the tools
+//!
+//! Resources (browsing without tool calls):
+//! - `echovault://session/<id>` - Parsed Markdown of a session, listed page
+//!   by page through `resources/list`
+//! - `echovault://sessions?source=&workspace=&cursor=` - Page of sessions of
+//!   a source and/or workspace, linking their session resources
 //!
 //! Runs on stdio transport for integration with Claude Desktop, Copilot, etc.
+//! With the `mcp-http` feature, [`run_http_server`] serves the same server to
+//! network clients over streamable HTTP (responses streamed as SSE) at
+//! [`HTTP_PATH`]; every request must carry `Authorization: Bearer <token>`.
 
 use crate::config::Config;
 use rmcp::{
@@ -18,13 +31,26 @@ use rmcp::{
     transport::io::stdio,
 };
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 /// Type alias for MCP error data.
 type McpError = rmcp::model::ErrorData;
 
+/// URI prefix of session resources (`echovault://session/<id>`).
+const SESSION_URI: &str = "echovault://session/";
+
+/// URI of the filtered session listing resource.
+const SESSIONS_URI: &str = "echovault://sessions";
+
+/// Sessions per page of `resources/list` and of the listing resource.
+const RESOURCE_PAGE_SIZE: usize = 100;
+
+/// Path of the MCP endpoint of the HTTP transport.
+#[cfg(feature = "mcp-http")]
+pub const HTTP_PATH: &str = "/mcp";
+
 /// Lazily-loaded config singleton.
 static CONFIG: OnceLock<Config> = OnceLock::new();
 
@@ -55,6 +81,69 @@ struct VaultParams {
     limit: Option<usize>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SearchConversationsParams {
+    /// What to look for: natural language (semantic) or FTS5 syntax (keyword)
+    query: String,
+    /// "semantic", "keyword", or "auto" (default: semantic, falling back to keyword)
+    mode: Option<String>,
+    /// Only sessions of this source (e.g., "cursor")
+    source: Option<String>,
+    /// Only sessions whose workspace name contains this
+    workspace: Option<String>,
+    /// Only sessions started on or after this day (YYYY-MM-DD)
+    since: Option<String>,
+    /// Only sessions whose model name contains this (e.g., "claude")
+    model: Option<String>,
+    /// Maximum number of results (default 10)
+    limit: Option<usize>,
+}
+
+/// A conversation found by `search_conversations`.
+#[derive(Debug, Serialize)]
+struct ConversationHit {
+    session_id: String,
+    title: Option<String>,
+    source: String,
+    /// Matching passage
+    snippet: String,
+    /// Relevance (higher is better; comparable within one search)
+    score: f32,
+    /// "semantic" or "keyword"
+    matched_by: &'static str,
+}
+
+/// Filters of a `search_conversations` call.
+#[derive(Debug, Clone, Default)]
+struct ConversationFilter {
+    source: Option<String>,
+    workspace: Option<String>,
+    since: Option<chrono::NaiveDate>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AnnotateParams {
+    /// Session ID (from list/search output)
+    session_id: String,
+    /// Free-form note; replaces the existing one ("" removes it)
+    note: Option<String>,
+    /// Rating from 1 to 5 (0 clears it)
+    rating: Option<u8>,
+    /// Flag the session to come back to
+    follow_up: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AnnotateSessionParams {
+    /// Session ID (from list/search output)
+    id: String,
+    /// Tags; replace the existing ones ([] removes them)
+    tags: Option<Vec<String>>,
+    /// Free-form note; replaces the existing one ("" removes it)
+    note: Option<String>,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 struct HelpParams {
     /// Tool name to get help for (default: "vault")
@@ -80,10 +169,10 @@ Returns: `[source] title | ws: workspace | date: YYYY-MM-DD HH:MM | id: session_
 ### `search` - Full-text search (FTS5)
 | Param | Required | Default | Description |
 |-------|----------|---------|-------------|
-| query | yes      | -       | FTS5 query (titles + workspace names) |
+| query | yes      | -       | FTS5 query (titles + workspace names + notes) |
 | limit | no       | 20      | Max results |
 
-Returns: Matching sessions sorted by relevance.
+Returns: Matching sessions sorted by relevance, then sessions whose note matches.
 
 ### `read` - Read full session content
 | Param      | Required | Description |
@@ -91,7 +180,8 @@ Returns: Matching sessions sorted by relevance.
 | source     | yes      | Source name (from list/search output) |
 | session_id | yes      | Session ID (from list/search output) |
 
-Returns: Full Markdown with YAML frontmatter.
+Returns: Full Markdown with YAML frontmatter, followed by the session's
+annotation (note, rating, follow-up) if it has one.
 
 ### `semantic_search` - Hybrid search (vector + keyword)
 | Param | Required | Default | Description |
@@ -103,8 +193,8 @@ Returns: Ranked results with relevance score and content snippet.
 Requires embeddings to be generated first (Settings > Build Index).
 
 ## Sources
-copilot, cursor, cline, continue-dev, jetbrains, zed, antigravity,
-gemini-cli, claude-code, aider, codex, opencode
+copilot, cursor, cline, roo-code, continue-dev, jetbrains, zed, antigravity,
+windsurf, claude-desktop, gemini-cli, claude-code, aider, codex, opencode, chatgpt
 
 ## Workflow
 1. `vault(action="list")` to browse available sessions
@@ -113,6 +203,54 @@ gemini-cli, claude-code, aider, codex, opencode
 4. `vault(action="semantic_search", query="natural language")` for semantic match
 "#;
 
+const SEARCH_CONVERSATIONS_HELP: &str = r#"# EchoVault `search_conversations` Tool
+
+Recall past conversations. Returns JSON: `{"results": [{session_id, title,
+source, snippet, score, matched_by}]}`, best first.
+
+| Param     | Required | Default | Description |
+|-----------|----------|---------|-------------|
+| query     | yes      | -       | Natural language, or FTS5 syntax in keyword mode |
+| mode      | no       | auto    | semantic, keyword, or auto (semantic, then keyword if no embeddings) |
+| source    | no       | all     | Source name (e.g., "cursor") |
+| workspace | no       | all     | Part of the workspace name |
+| since     | no       | -       | YYYY-MM-DD, sessions started on or after it |
+| model     | no       | all     | Part of the model name (e.g., "claude") |
+| limit     | no       | 10      | Max results |
+
+Read a result with `vault(action="read", source=..., session_id=...)`.
+"#;
+
+const ANNOTATE_HELP: &str = r#"# EchoVault `annotate` Tool
+
+Set the note, rating or follow-up flag of a session. Fields left out are kept.
+
+| Param      | Required | Description |
+|------------|----------|-------------|
+| session_id | yes      | Session ID (from vault list/search output) |
+| note       | no       | Free-form note, replaces the existing one ("" removes it) |
+| rating     | no       | 1-5, or 0 to clear |
+| follow_up  | no       | true to flag the session to come back to |
+
+Returns: The resulting annotation. Notes are included in `vault` search, and in
+`semantic_search` once embeddings are rebuilt.
+"#;
+
+const ANNOTATE_SESSION_HELP: &str = r#"# EchoVault `annotate_session` Tool
+
+Curate a session with tags and a note. Fields left out are kept; the rating and
+follow-up flag (see `annotate`) are never changed.
+
+| Param | Required | Description |
+|-------|----------|-------------|
+| id    | yes      | Session ID (from vault list/search output) |
+| tags  | no       | Tags, replacing the existing ones ([] removes them) |
+| note  | no       | Free-form note, replaces the existing one ("" removes it) |
+
+Returns: The resulting annotation. It is stored in vault.db and written into the
+frontmatter of the parsed Markdown (`user_tags`, `note`).
+"#;
+
 // ============ TOOL IMPLEMENTATIONS ============
 
 #[tool_router]
@@ -185,6 +323,124 @@ impl EchoVaultServer {
         Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
+    #[tool(
+        name = "search_conversations",
+        description = "Search past AI conversations by meaning (semantic) or keywords, filtered by source, workspace, start date or model. Returns JSON results with session_id, title, source, snippet and score. Use `help` tool for full documentation.",
+        annotations(read_only_hint = true)
+    )]
+    async fn search_conversations(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<SearchConversationsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let p = params.0;
+        let vault_dir = self.vault_dir.clone();
+
+        let result =
+            tokio::task::spawn_blocking(move || -> Result<Vec<ConversationHit>, String> {
+                if p.query.trim().is_empty() {
+                    return Err("'query' is required".to_string());
+                }
+                let since = p
+                    .since
+                    .as_deref()
+                    .map(|d| d.parse::<chrono::NaiveDate>())
+                    .transpose()
+                    .map_err(|_| "'since' must be a date like 2025-01-31".to_string())?;
+                let filter = ConversationFilter {
+                    source: p.source,
+                    workspace: p.workspace,
+                    since,
+                    model: p.model,
+                };
+                let limit = p.limit.unwrap_or(10);
+                match p.mode.as_deref().unwrap_or("auto") {
+                    "semantic" => semantic_hits(&vault_dir, &p.query, &filter, limit),
+                    "keyword" => keyword_hits(&vault_dir, &p.query, &filter, limit),
+                    "auto" => match semantic_hits(&vault_dir, &p.query, &filter, limit) {
+                        Ok(hits) if !hits.is_empty() => Ok(hits),
+                        _ => keyword_hits(&vault_dir, &p.query, &filter, limit),
+                    },
+                    other => Err(format!(
+                        "Unknown mode '{}'. Valid modes: semantic, keyword, auto",
+                        other
+                    )),
+                }
+            })
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(match result {
+            Ok(hits) => CallToolResult::structured(serde_json::json!({ "results": hits })),
+            Err(e) => CallToolResult::error(vec![Content::text(format!("Error: {}", e))]),
+        })
+    }
+
+    #[tool(
+        name = "annotate",
+        description = "Set the note, rating (1-5) or follow-up flag of a session. Fields left out are kept. Use `help` tool for full documentation.",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn annotate(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AnnotateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let p = params.0;
+        let vault_dir = self.vault_dir.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let update = crate::storage::AnnotationUpdate {
+                note: p.note,
+                rating: p.rating,
+                follow_up: p.follow_up,
+                ..Default::default()
+            };
+            vault_annotate(&vault_dir, &p.session_id, &update)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let text = result.unwrap_or_else(|e| format!("Error: {}", e));
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "annotate_session",
+        description = "Set the tags and note of a session (stored in the vault and in its Markdown frontmatter). Fields left out are kept. Use `help` tool for full documentation.",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn annotate_session(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AnnotateSessionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let p = params.0;
+        let vault_dir = self.vault_dir.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            if p.tags.is_none() && p.note.is_none() {
+                return Err("Nothing to set; pass 'tags' or 'note'".to_string());
+            }
+            let update = crate::storage::AnnotationUpdate {
+                note: p.note,
+                tags: p.tags,
+                ..Default::default()
+            };
+            vault_annotate(&vault_dir, &p.id, &update)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let text = result.unwrap_or_else(|e| format!("Error: {}", e));
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
     #[tool(
         name = "help",
         description = "Get full documentation for EchoVault tools. Returns detailed usage, parameters, examples.",
@@ -197,8 +453,11 @@ impl EchoVaultServer {
         let tool_name = params.0.tool_name.unwrap_or_else(|| "vault".to_string());
         let doc = match tool_name.as_str() {
             "vault" => VAULT_HELP.to_string(),
+            "search_conversations" => SEARCH_CONVERSATIONS_HELP.to_string(),
+            "annotate" => ANNOTATE_HELP.to_string(),
+            "annotate_session" => ANNOTATE_SESSION_HELP.to_string(),
             "help" => "The `help` tool returns documentation for EchoVault tools.\n\nUsage: help(tool_name=\"vault\")".to_string(),
-            other => format!("Documentation not found for '{}'. Available: vault, help", other),
+            other => format!("Documentation not found for '{}'. Available: vault, search_conversations, annotate, annotate_session, help", other),
         };
         Ok(CallToolResult::success(vec![Content::text(doc)]))
     }
@@ -210,7 +469,7 @@ fn vault_list(vault_dir: &Path, source: Option<&str>, limit: usize) -> Result<St
     let index = crate::storage::SessionIndex::open(vault_dir).map_err(|e| e.to_string())?;
 
     let sessions = if let Some(src) = source {
-        index.filter_by_source(src, limit)
+        index.filter_by_source(src, limit, 0)
     } else {
         index.list(limit, 0)
     }
@@ -239,12 +498,19 @@ fn vault_list(vault_dir: &Path, source: Option<&str>, limit: usize) -> Result<St
 fn vault_search(vault_dir: &Path, query: &str, limit: usize) -> Result<String, String> {
     let index = crate::storage::SessionIndex::open(vault_dir).map_err(|e| e.to_string())?;
     let sessions = index.search(query, limit).map_err(|e| e.to_string())?;
+    let notes = crate::storage::VaultDb::open(vault_dir)
+        .and_then(|db| db.search_annotations(query, limit))
+        .map_err(|e| e.to_string())?;
 
-    if sessions.is_empty() {
+    if sessions.is_empty() && notes.is_empty() {
         return Ok(format!("No results for '{}'.", query));
     }
 
-    let mut output = format!("Search '{}': {} results\n\n", query, sessions.len());
+    let mut output = format!(
+        "Search '{}': {} results\n\n",
+        query,
+        sessions.len() + notes.len()
+    );
     for s in &sessions {
         let title = s.title.as_deref().unwrap_or("(untitled)");
         let ws = s.workspace_name.as_deref().unwrap_or("-");
@@ -253,37 +519,119 @@ fn vault_search(vault_dir: &Path, query: &str, limit: usize) -> Result<String, S
             s.source, title, ws, s.id
         ));
     }
+    if !notes.is_empty() {
+        output.push_str("\nMatching notes:\n");
+        for a in &notes {
+            let snippet: String = a.note.chars().take(200).collect();
+            output.push_str(&format!("- id: {} | note: {}\n", a.session_id, snippet));
+        }
+    }
     Ok(output)
 }
 
 fn vault_read(vault_dir: &Path, source: &str, session_id: &str) -> Result<String, String> {
+    Ok(read_parsed(vault_dir, source, session_id)?
+        .unwrap_or_else(|| format!("Session not found: {}/{}", source, session_id)))
+}
+
+/// Parsed Markdown of a session followed by its annotation, or `None` if the
+/// session has not been parsed.
+fn read_parsed(vault_dir: &Path, source: &str, session_id: &str) -> Result<Option<String>, String> {
     let parsed_path = vault_dir
         .join("parsed")
         .join(source)
         .join(format!("{}.md", session_id));
 
     if !parsed_path.exists() {
-        return Ok(format!("Session not found: {}/{}", source, session_id));
+        return Ok(None);
+    }
+
+    let mut content = std::fs::read_to_string(&parsed_path).map_err(|e| e.to_string())?;
+    let annotation =
+        crate::storage::VaultDb::open(vault_dir).and_then(|db| db.annotation(session_id));
+    if let Ok(Some(a)) = annotation {
+        content.push_str(&format!("\n\n---\n\n{}", format_annotation(&a)));
+    }
+    Ok(Some(content))
+}
+
+fn vault_annotate(
+    vault_dir: &Path,
+    session_id: &str,
+    update: &crate::storage::AnnotationUpdate,
+) -> Result<String, String> {
+    let db = crate::storage::VaultDb::open(vault_dir).map_err(|e| e.to_string())?;
+    let annotation = db.annotate(session_id, update).map_err(|e| e.to_string())?;
+    if let Err(e) = crate::parsers::markdown_writer::write_annotation(
+        vault_dir,
+        session_id,
+        annotation.as_ref(),
+    ) {
+        tracing::warn!(
+            "Failed to write annotation of {} to Markdown: {}",
+            session_id,
+            e
+        );
     }
+    match annotation {
+        Some(a) => Ok(format_annotation(&a)),
+        None => Ok(format!(
+            "Annotation of {} removed (nothing left).",
+            session_id
+        )),
+    }
+}
+
+fn format_annotation(a: &crate::storage::Annotation) -> String {
+    let rating = a
+        .rating
+        .map(|r| format!("{}/5", r))
+        .unwrap_or_else(|| "-".to_string());
+    let mut output = format!(
+        "Annotation | id: {} | rating: {} | follow-up: {}\n",
+        a.session_id,
+        rating,
+        if a.follow_up { "yes" } else { "no" }
+    );
+    if !a.tags.is_empty() {
+        output.push_str(&format!("Tags: {}\n", a.tags.join(", ")));
+    }
+    if !a.note.is_empty() {
+        output.push_str(&format!("Note: {}\n", a.note));
+    }
+    output
+}
 
-    std::fs::read_to_string(&parsed_path).map_err(|e| e.to_string())
+/// Embedding settings from the `[embedding]` config section.
+#[cfg(feature = "embedding")]
+fn embedding_config() -> crate::embedding::EmbeddingConfig {
+    let config = get_config();
+    crate::embedding::EmbeddingConfig {
+        backend: config.embedding.backend,
+        api_base: config.embedding.api_base.clone(),
+        api_key: config.embedding.resolved_api_key(),
+        model: config.embedding.model.clone(),
+        chunk_size: config.embedding.chunk_size,
+        chunk_overlap: config.embedding.chunk_overlap,
+        batch_size: config.embedding.batch_size,
+        concurrency: config.embedding.concurrency,
+        request_timeout_secs: config.embedding.request_timeout_secs,
+        max_retries: config.embedding.max_retries,
+        rerank: config.embedding.rerank.clone(),
+    }
 }
 
 fn vault_semantic_search(vault_dir: &Path, query: &str, limit: usize) -> Result<String, String> {
     #[cfg(feature = "embedding")]
     {
-        let config = get_config();
-        let embedding_config = crate::embedding::EmbeddingConfig {
-            api_base: config.embedding.api_base.clone(),
-            api_key: config.embedding.api_key.clone(),
-            model: config.embedding.model.clone(),
-            chunk_size: config.embedding.chunk_size,
-            chunk_overlap: config.embedding.chunk_overlap,
-            batch_size: config.embedding.batch_size,
-        };
-
-        let results = crate::embedding::search_similar(&embedding_config, vault_dir, query, limit)
-            .map_err(|e| e.to_string())?;
+        let results = crate::embedding::search_similar(
+            &embedding_config(),
+            vault_dir,
+            query,
+            limit,
+            &Default::default(),
+        )
+        .map_err(|e| e.to_string())?;
 
         if results.is_empty() {
             return Ok(format!(
@@ -315,6 +663,239 @@ fn vault_semantic_search(vault_dir: &Path, query: &str, limit: usize) -> Result<
     }
 }
 
+/// Semantic (hybrid) search results of `search_conversations`.
+fn semantic_hits(
+    vault_dir: &Path,
+    query: &str,
+    filter: &ConversationFilter,
+    limit: usize,
+) -> Result<Vec<ConversationHit>, String> {
+    #[cfg(feature = "embedding")]
+    {
+        let options = crate::embedding::SearchOptions {
+            filter: crate::embedding::SearchFilter {
+                source: filter.source.clone(),
+                workspace: filter.workspace.clone(),
+                since: filter.since,
+                model: filter.model.clone(),
+                only: None,
+            },
+            ..Default::default()
+        };
+        let results = crate::embedding::search_similar(
+            &embedding_config(),
+            vault_dir,
+            query,
+            limit,
+            &options,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(results
+            .into_iter()
+            .map(|r| ConversationHit {
+                session_id: r.session_id,
+                title: r.title,
+                source: r.source,
+                snippet: r.chunk_content.chars().take(300).collect(),
+                score: r.score,
+                matched_by: "semantic",
+            })
+            .collect())
+    }
+
+    #[cfg(not(feature = "embedding"))]
+    {
+        let _ = (vault_dir, query, filter, limit);
+        Err("Embedding feature not enabled. Use mode=\"keyword\"".to_string())
+    }
+}
+
+/// Keyword (full-text) search results of `search_conversations`. The index
+/// is built on first use.
+fn keyword_hits(
+    vault_dir: &Path,
+    query: &str,
+    filter: &ConversationFilter,
+    limit: usize,
+) -> Result<Vec<ConversationHit>, String> {
+    use crate::storage::fts::{HIGHLIGHT_END, HIGHLIGHT_START};
+
+    let mut index = crate::storage::FtsIndex::open(vault_dir).map_err(|e| e.to_string())?;
+    if index.is_empty().map_err(|e| e.to_string())? {
+        index
+            .rebuild(vault_dir, &crate::progress::Operation::default())
+            .map_err(|e| e.to_string())?;
+    }
+    let fts_filter = crate::storage::FtsFilter {
+        source: filter.source.clone(),
+        workspace: filter.workspace.clone(),
+        after: filter.since,
+        before: None,
+        model: filter.model.clone(),
+        exclude: crate::dedupe::duplicate_ids(vault_dir),
+        only: None,
+    };
+    let hits = index
+        .search(query, &fts_filter, limit)
+        .map_err(|e| format!("{:#}", e))?;
+    Ok(hits
+        .into_iter()
+        .map(|hit| ConversationHit {
+            session_id: hit.session_id,
+            title: hit.title,
+            source: hit.source,
+            snippet: hit
+                .snippet
+                .replace(HIGHLIGHT_START, "**")
+                .replace(HIGHLIGHT_END, "**"),
+            // BM25 rank is negative, closer to 0 is worse
+            score: (-hit.rank) as f32,
+            matched_by: "keyword",
+        })
+        .collect())
+}
+
+// ============ RESOURCES ============
+
+/// A page of session resources and the cursor of the next page.
+fn session_resources(
+    vault_dir: &Path,
+    source: Option<&str>,
+    workspace: Option<&str>,
+    offset: usize,
+) -> Result<(Vec<Resource>, Option<String>), String> {
+    let index = crate::storage::SessionIndex::open(vault_dir).map_err(|e| e.to_string())?;
+    let sessions = index
+        .list_filtered(source, workspace, RESOURCE_PAGE_SIZE, offset)
+        .map_err(|e| e.to_string())?;
+    let next_cursor =
+        (sessions.len() == RESOURCE_PAGE_SIZE).then(|| (offset + RESOURCE_PAGE_SIZE).to_string());
+
+    let resources = sessions
+        .into_iter()
+        .map(|s| {
+            let date = s
+                .created_at
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let mut raw = RawResource::new(format!("{}{}", SESSION_URI, s.id), s.id.clone());
+            raw.title = s.title;
+            raw.description = Some(format!(
+                "{} | ws: {} | date: {}",
+                s.source,
+                s.workspace_name.as_deref().unwrap_or("-"),
+                date
+            ));
+            raw.mime_type = Some("text/markdown".to_string());
+            raw.no_annotation()
+        })
+        .collect();
+    Ok((resources, next_cursor))
+}
+
+/// Content of a resource URI.
+fn read_resource_uri(vault_dir: &Path, uri: &str) -> Result<String, McpError> {
+    if let Some(session_id) = uri.strip_prefix(SESSION_URI) {
+        let index = crate::storage::SessionIndex::open(vault_dir)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let session = index
+            .get(session_id)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let content = match session {
+            Some(s) => read_parsed(vault_dir, &s.source, &s.id)
+                .map_err(|e| McpError::internal_error(e, None))?,
+            None => None,
+        };
+        return content.ok_or_else(|| {
+            McpError::resource_not_found(format!("Session not parsed: {}", session_id), None)
+        });
+    }
+
+    let query = match uri.strip_prefix(SESSIONS_URI) {
+        Some("") => "",
+        Some(rest) if rest.starts_with('?') => &rest[1..],
+        _ => {
+            return Err(McpError::resource_not_found(
+                format!("Unknown resource: {}", uri),
+                None,
+            ))
+        }
+    };
+    let (mut source, mut workspace, mut offset) = (None, None, 0);
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let value = decode_query_value(value);
+        match key {
+            "source" if !value.is_empty() => source = Some(value),
+            "workspace" if !value.is_empty() => workspace = Some(value),
+            "cursor" => offset = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+
+    let (resources, next_cursor) =
+        session_resources(vault_dir, source.as_deref(), workspace.as_deref(), offset)
+            .map_err(|e| McpError::internal_error(e, None))?;
+    if resources.is_empty() {
+        return Ok("No sessions found.".to_string());
+    }
+    let mut output = format!("{} sessions:\n\n", resources.len());
+    for r in &resources {
+        output.push_str(&format!(
+            "- [{}]({}) | {}\n",
+            r.title.as_deref().unwrap_or("(untitled)"),
+            r.uri,
+            r.description.as_deref().unwrap_or_default()
+        ));
+    }
+    if let Some(cursor) = next_cursor {
+        let mut next = format!("{}?cursor={}", SESSIONS_URI, cursor);
+        if let Some(s) = &source {
+            next.push_str(&format!("&source={}", encode_query_value(s)));
+        }
+        if let Some(w) = &workspace {
+            next.push_str(&format!("&workspace={}", encode_query_value(w)));
+        }
+        output.push_str(&format!("\nNext page: {}\n", next));
+    }
+    Ok(output)
+}
+
+/// Percent-encode a URI query value.
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decode `+` and `%XX` escapes of a URI query value.
+fn decode_query_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 // ============ SERVER HANDLER ============
 
 impl ServerHandler for EchoVaultServer {
@@ -323,6 +904,7 @@ impl ServerHandler for EchoVaultServer {
             protocol_version: ProtocolVersion::V_2025_03_26,
             capabilities: ServerCapabilities {
                 tools: Some(ToolsCapability { list_changed: None }),
+                resources: Some(ResourcesCapability::default()),
                 ..Default::default()
             },
             server_info: Implementation {
@@ -337,6 +919,11 @@ impl ServerHandler for EchoVaultServer {
                  vault(action=\"search\", query=\"...\") for FTS, \
                  vault(action=\"read\", source=\"...\", session_id=\"...\") for content, \
                  vault(action=\"semantic_search\", query=\"...\") for semantic search. \
+                 search_conversations(query=\"...\", source=..., since=...) for filtered search with JSON results. \
+                 annotate(session_id=\"...\", note=\"...\") to add notes, ratings and follow-up flags. \
+                 annotate_session(id=\"...\", tags=[...], note=\"...\") to tag sessions. \
+                 Sessions are also resources (echovault://session/<id>); \
+                 read echovault://sessions?source=...&workspace=... to list them by source or workspace. \
                  Call help() for full documentation."
                     .to_string(),
             ),
@@ -363,6 +950,90 @@ impl ServerHandler for EchoVaultServer {
             ..Default::default()
         }))
     }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        // The cursor is the offset of the page
+        let offset = request
+            .and_then(|r| r.cursor)
+            .map(|c| {
+                c.parse::<usize>()
+                    .map_err(|_| McpError::invalid_params(format!("Invalid cursor: {}", c), None))
+            })
+            .transpose()?
+            .unwrap_or(0);
+        let vault_dir = self.vault_dir.clone();
+
+        let (resources, next_cursor) =
+            tokio::task::spawn_blocking(move || session_resources(&vault_dir, None, None, offset))
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?
+                .map_err(|e| McpError::internal_error(e, None))?;
+
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor,
+            ..Default::default()
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        let template = |uri_template: String, name: &str, description: &str| {
+            RawResourceTemplate {
+                uri_template,
+                name: name.to_string(),
+                title: None,
+                description: Some(description.to_string()),
+                mime_type: Some("text/markdown".to_string()),
+                icons: None,
+            }
+            .no_annotation()
+        };
+        Ok(ListResourceTemplatesResult::with_all_items(vec![
+            template(
+                format!("{}{{session_id}}", SESSION_URI),
+                "session",
+                "Parsed Markdown of a session",
+            ),
+            template(
+                format!("{}{{?source,workspace,cursor}}", SESSIONS_URI),
+                "sessions",
+                "Sessions of a source and/or workspace, newest first, with a link to the next page",
+            ),
+        ]))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let vault_dir = self.vault_dir.clone();
+        let uri = request.uri;
+
+        let text = {
+            let uri = uri.clone();
+            tokio::task::spawn_blocking(move || read_resource_uri(&vault_dir, &uri))
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))??
+        };
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri,
+                mime_type: Some("text/markdown".to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
 }
 
 /// Run the MCP server on stdio transport.
@@ -385,3 +1056,72 @@ pub async fn run_server() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Run the MCP server over streamable HTTP on `addr` until `shutdown`
+/// completes. Requests without `Authorization: Bearer <token>` get 401.
+#[cfg(feature = "mcp-http")]
+pub async fn run_http_server(
+    addr: std::net::SocketAddr,
+    token: String,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    use crate::utils::http_auth;
+    use rmcp::transport::streamable_http_server::{
+        session::local::LocalSessionManager, StreamableHttpServerConfig, StreamableHttpService,
+    };
+
+    let vault_dir = get_config().vault_path.clone();
+    let config = StreamableHttpServerConfig::default();
+    let sessions = config.cancellation_token.clone();
+    let service = StreamableHttpService::new(
+        move || Ok(EchoVaultServer::new(vault_dir.clone())),
+        std::sync::Arc::new(LocalSessionManager::default()),
+        config,
+    );
+
+    let token = std::sync::Arc::new(token);
+    let app =
+        axum::Router::new()
+            .nest_service(HTTP_PATH, service)
+            .layer(axum::middleware::from_fn(
+                move |request: axum::extract::Request, next: axum::middleware::Next| {
+                    let token = token.clone();
+                    async move { http_auth::require_token(&token, request, next).await }
+                },
+            ));
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind MCP server to {}: {}", addr, e))?;
+    tracing::info!(
+        "[mcp] Listening on http://{}{}",
+        listener.local_addr()?,
+        HTTP_PATH
+    );
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            // Close the open SSE streams, or shutdown waits for them
+            sessions.cancel();
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("MCP server error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_query_value() {
+        assert_eq!(decode_query_value("my+project"), "my project");
+        assert_eq!(decode_query_value("a%2Fb%20c"), "a/b c");
+        assert_eq!(decode_query_value("100%"), "100%");
+        assert_eq!(decode_query_value("%zz"), "%zz");
+        assert_eq!(
+            decode_query_value(&encode_query_value("dự án & co")),
+            "dự án & co"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Local metrics.
+//!
+//! Sync durations, ingest counts and search latencies are recorded in the
+//! `metrics` table of local.db, which is never synced, and summarized by
+//! `echovault-cli stats`. Recording is controlled by `[metrics] enabled`.
+//!
+//! Nothing leaves the machine. `[metrics] telemetry` is an explicit opt-in
+//! that any future export must check through [`export_allowed`] first; no
+//! exporter exists today.
+
+use crate::config::Config;
+use crate::storage::local_db::LOCAL_DB;
+use crate::storage::LocalDb;
+use std::time::Instant;
+use tracing::debug;
+
+pub use crate::storage::MetricSummary;
+
+/// Duration of a full sync (pull, ingest, push), in milliseconds
+pub const SYNC_DURATION_MS: &str = "sync.duration_ms";
+/// Sessions copied into the vault by one ingest
+pub const INGEST_SESSIONS: &str = "ingest.sessions";
+/// Latency of a semantic search, in milliseconds
+pub const SEARCH_LATENCY_MS: &str = "search.latency_ms";
+
+/// How long samples are kept (90 days)
+const RETENTION_SECS: i64 = 90 * 24 * 60 * 60;
+
+/// Record a sample. Best-effort: failures are logged, never returned.
+pub fn record(config: &Config, name: &str, value: f64) {
+    if !config.metrics.enabled {
+        return;
+    }
+    let result = LocalDb::open(&config.vault_path)
+        .and_then(|db| db.record_metric(name, value, RETENTION_SECS));
+    if let Err(e) = result {
+        debug!("[metrics] Cannot record {}: {}", name, e);
+    }
+}
+
+/// Summaries of the metrics recorded on this machine.
+pub fn summaries(config: &Config) -> anyhow::Result<Vec<MetricSummary>> {
+    if !config.vault_path.join(LOCAL_DB).exists() {
+        return Ok(Vec::new());
+    }
+    LocalDb::open(&config.vault_path)?.metric_summaries()
+}
+
+/// Whether counters may ever be exported (requires the telemetry opt-in).
+pub fn export_allowed(config: &Config) -> bool {
+    config.metrics.enabled && config.metrics.telemetry
+}
+
+/// Measures an operation for a duration metric.
+pub struct Timer {
+    name: &'static str,
+    started: Instant,
+}
+
+impl Timer {
+    /// Start timing.
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            started: Instant::now(),
+        }
+    }
+
+    /// Record the elapsed time in milliseconds.
+    pub fn finish(self, config: &Config) {
+        let elapsed = self.started.elapsed().as_secs_f64() * 1000.0;
+        record(config, self.name, elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_metrics_are_local_and_opt_in() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let mut config = Config::with_vault_path(temp.path().to_path_buf());
+        assert!(!export_allowed(&config));
+
+        record(&config, INGEST_SESSIONS, 3.0);
+        record(&config, INGEST_SESSIONS, 5.0);
+        Timer::start(SEARCH_LATENCY_MS).finish(&config);
+
+        let summaries = summaries(&config)?;
+        assert_eq!(summaries.len(), 2);
+        let ingest = summaries
+            .iter()
+            .find(|s| s.name == INGEST_SESSIONS)
+            .unwrap();
+        assert_eq!(ingest.count, 2);
+        assert_eq!(ingest.average(), 4.0);
+        assert_eq!(ingest.max, 5.0);
+
+        // Disabled: nothing is recorded
+        config.metrics.enabled = false;
+        record(&config, INGEST_SESSIONS, 7.0);
+        config.metrics.enabled = true;
+        assert_eq!(summaries_count(&config, INGEST_SESSIONS), 2);
+
+        config.metrics.telemetry = true;
+        assert!(export_allowed(&config));
+        Ok(())
+    }
+
+    fn summaries_count(config: &Config, name: &str) -> u64 {
+        summaries(config)
+            .unwrap()
+            .into_iter()
+            .find(|s| s.name == name)
+            .map_or(0, |s| s.count)
+    }
+}
@@ -0,0 +1,242 @@
+//! Webhook notifications of sync and extraction events.
+//!
+//! Events are POSTed to the `[[notifications.webhooks]]` of the config, as
+//! Discord or Slack messages or as generic JSON:
+//!
+//! ```json
+//! {"event": "sync_failed", "message": "...", "timestamp": "...", "data": {...}}
+//! ```
+//!
+//! Rate-limited (429), failed (5xx) and timed out deliveries are retried with
+//! exponential backoff. A webhook that still fails is logged and skipped:
+//! notifications never fail the operation that raised them.
+
+use crate::config::{NotificationEvent, NotificationsConfig, WebhookFormat};
+use anyhow::{bail, Result};
+use serde_json::json;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Timeout of one delivery attempt.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before the first retry, doubled for each further one.
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+/// Longest delay between retries.
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Something worth telling the user about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A sync with `target` completed
+    SyncCompleted { target: String, files_pushed: usize },
+    /// A sync with `target` failed
+    SyncFailed { target: String, error: String },
+    /// Extraction copied `count` new or changed sessions into the vault
+    SessionsExtracted { count: usize },
+}
+
+impl Event {
+    /// Type of the event, as enabled in the config.
+    pub fn kind(&self) -> NotificationEvent {
+        match self {
+            Self::SyncCompleted { .. } => NotificationEvent::SyncCompleted,
+            Self::SyncFailed { .. } => NotificationEvent::SyncFailed,
+            Self::SessionsExtracted { .. } => NotificationEvent::SessionsExtracted,
+        }
+    }
+
+    /// One-line description for chat webhooks.
+    pub fn message(&self) -> String {
+        match self {
+            Self::SyncCompleted {
+                target,
+                files_pushed,
+            } => format!(
+                "EchoVault: sync with '{}' completed ({} files pushed)",
+                target, files_pushed
+            ),
+            Self::SyncFailed { target, error } => {
+                format!("EchoVault: sync with '{}' failed: {}", target, error)
+            }
+            Self::SessionsExtracted { count } => {
+                format!("EchoVault: {} new or changed sessions extracted", count)
+            }
+        }
+    }
+
+    /// Fields of the event for JSON webhooks.
+    fn data(&self) -> serde_json::Value {
+        match self {
+            Self::SyncCompleted {
+                target,
+                files_pushed,
+            } => json!({ "target": target, "files_pushed": files_pushed }),
+            Self::SyncFailed { target, error } => json!({ "target": target, "error": error }),
+            Self::SessionsExtracted { count } => json!({ "count": count }),
+        }
+    }
+}
+
+/// Request body of an event for a webhook of this format.
+pub fn payload(format: WebhookFormat, event: &Event) -> serde_json::Value {
+    match format {
+        WebhookFormat::Json => json!({
+            "event": event.kind(),
+            "message": event.message(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "data": event.data(),
+        }),
+        WebhookFormat::Discord => json!({ "content": event.message() }),
+        WebhookFormat::Slack => json!({ "text": event.message() }),
+    }
+}
+
+/// Send an event to the webhooks that want it, if its type is enabled.
+/// Returns how many webhooks received it.
+pub fn notify(config: &NotificationsConfig, event: &Event) -> usize {
+    let kind = event.kind();
+    if !config.is_enabled(kind) {
+        return 0;
+    }
+    let webhooks: Vec<_> = config.webhooks.iter().filter(|w| w.wants(kind)).collect();
+    if webhooks.is_empty() {
+        return 0;
+    }
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut delivered = 0;
+    for webhook in webhooks {
+        let body = payload(webhook.format, event);
+        match deliver(&agent, &webhook.url, &body, config.max_retries) {
+            Ok(()) => {
+                debug!("Sent {:?} notification to {}", kind, webhook.url);
+                delivered += 1;
+            }
+            Err(e) => warn!("Webhook {} failed: {:#}", webhook.url, e),
+        }
+    }
+    delivered
+}
+
+/// POST a payload, retrying rate-limited, failed and timed out requests.
+fn deliver(
+    agent: &ureq::Agent,
+    url: &str,
+    body: &serde_json::Value,
+    max_retries: u32,
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        let (error, retry_after) = match agent.post(url).send_json(body) {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                if (200..300).contains(&status) {
+                    return Ok(());
+                }
+                let error = anyhow::anyhow!("status {}", status);
+                if status != 429 && status < 500 {
+                    return Err(error);
+                }
+                (error, retry_after(&response))
+            }
+            Err(ureq::Error::Timeout(timeout)) => {
+                (anyhow::anyhow!("timed out ({})", timeout), None)
+            }
+            Err(ureq::Error::Io(e)) => (anyhow::anyhow!("connection failed: {}", e), None),
+            Err(other) => bail!("{}", other),
+        };
+
+        if attempt >= max_retries {
+            return Err(error.context(format!("Giving up after {} retries", attempt)));
+        }
+        let delay = retry_after.unwrap_or_else(|| backoff(attempt));
+        debug!(
+            "Webhook {}: {}, retrying in {:.1}s ({}/{})",
+            url,
+            error,
+            delay.as_secs_f64(),
+            attempt + 1,
+            max_retries
+        );
+        std::thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+/// Delay before retry number `attempt` (0-based): 1s, 2s, 4s, ... up to 30s.
+fn backoff(attempt: u32) -> Duration {
+    BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_MAX)
+}
+
+/// Delay requested by a `Retry-After: <seconds>` header.
+fn retry_after(response: &ureq::http::Response<ureq::Body>) -> Option<Duration> {
+    let secs: u64 = response
+        .headers()
+        .get("retry-after")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(BACKOFF_MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WebhookConfig;
+
+    #[test]
+    fn test_payloads() {
+        let event = Event::SyncFailed {
+            target: "default".to_string(),
+            error: "Push failed".to_string(),
+        };
+        let body = payload(WebhookFormat::Json, &event);
+        assert_eq!(body["event"], "sync_failed");
+        assert_eq!(body["data"]["error"], "Push failed");
+        assert_eq!(
+            payload(WebhookFormat::Discord, &event)["content"],
+            event.message()
+        );
+        assert_eq!(
+            payload(WebhookFormat::Slack, &event)["text"],
+            event.message()
+        );
+    }
+
+    #[test]
+    fn test_event_selection() {
+        let mut config = NotificationsConfig {
+            webhooks: vec![WebhookConfig {
+                url: "http://127.0.0.1:9/hook".to_string(),
+                format: WebhookFormat::Json,
+                events: vec![NotificationEvent::SyncFailed],
+            }],
+            ..Default::default()
+        };
+        // Not wanted by the only webhook: nothing is sent
+        let extracted = Event::SessionsExtracted { count: 3 };
+        assert_eq!(notify(&config, &extracted), 0);
+
+        // Disabled event type
+        config.sync_failed = false;
+        let failed = Event::SyncFailed {
+            target: "default".to_string(),
+            error: "boom".to_string(),
+        };
+        assert_eq!(notify(&config, &failed), 0);
+
+        assert_eq!(backoff(0), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(4));
+        assert_eq!(backoff(10), BACKOFF_MAX);
+    }
+}
@@ -165,6 +165,7 @@ impl Parser for AiderParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
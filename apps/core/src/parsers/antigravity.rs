@@ -154,6 +154,7 @@ impl AntigravityParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
@@ -223,6 +224,7 @@ impl AntigravityParser {
                 model: None,
             }],
             tags: vec!["artifact".to_string()],
+            usage: None,
         })
     }
 }
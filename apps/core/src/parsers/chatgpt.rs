@@ -0,0 +1,220 @@
+//! ChatGPT Parser
+//!
+//! Parses conversations imported from a ChatGPT data export (see
+//! [`crate::importers::chatgpt`]), one JSON object per file.
+//!
+//! Format: messages form a tree (edits and regenerations branch off), keyed
+//! by node ID; `current_node` is the leaf of the branch shown in ChatGPT:
+//! ```json
+//! {
+//!   "conversation_id": "...", "title": "...",
+//!   "create_time": 1700000000.0, "update_time": 1700000100.0,
+//!   "current_node": "n2",
+//!   "mapping": {
+//!     "n1": { "parent": null, "children": ["n2"], "message": {
+//!       "author": { "role": "user" },
+//!       "create_time": 1700000000.0,
+//!       "content": { "content_type": "text", "parts": ["..."] },
+//!       "metadata": { "model_slug": "gpt-4o" }
+//!     } }
+//!   }
+//! }
+//! ```
+
+use super::{ParsedConversation, ParsedMessage, Parser, Role};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// ChatGPT Parser
+pub struct ChatGptParser;
+
+impl ChatGptParser {
+    /// Messages on the branch ending at `current_node`, oldest first.
+    /// Without `current_node`, the most recent leaf is used.
+    fn branch<'a>(conv: &'a Value, mapping: &'a Map<String, Value>) -> Vec<&'a Value> {
+        let leaf = conv
+            .get("current_node")
+            .and_then(|n| n.as_str())
+            .or_else(|| {
+                mapping
+                    .iter()
+                    .filter(|(_, node)| {
+                        node.get("children")
+                            .and_then(|c| c.as_array())
+                            .is_none_or(|c| c.is_empty())
+                    })
+                    .max_by(|(_, a), (_, b)| {
+                        let time = |node: &Value| {
+                            node.pointer("/message/create_time")
+                                .and_then(|t| t.as_f64())
+                                .unwrap_or(0.0)
+                        };
+                        time(a).total_cmp(&time(b))
+                    })
+                    .map(|(id, _)| id.as_str())
+            });
+
+        let mut messages = Vec::new();
+        let mut seen = HashSet::new();
+        let mut node_id = leaf;
+        while let Some(id) = node_id {
+            // Guard against cycles in malformed exports
+            let Some(node) = mapping.get(id).filter(|_| seen.insert(id)) else {
+                break;
+            };
+            if let Some(message) = node.get("message").filter(|m| m.is_object()) {
+                messages.push(message);
+            }
+            node_id = node.get("parent").and_then(|p| p.as_str());
+        }
+        messages.reverse();
+        messages
+    }
+
+    /// Text of a message's content, or `None` for content not shown in the
+    /// transcript (reasoning, hidden context).
+    fn content_text(content: &Value) -> Option<String> {
+        let text = || content.get("text").and_then(|t| t.as_str());
+        let text = match content.get("content_type").and_then(|t| t.as_str())? {
+            "text" | "multimodal_text" => content
+                .get("parts")?
+                .as_array()?
+                .iter()
+                .filter_map(|part| match part {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Object(_) => match part.get("content_type").and_then(|t| t.as_str()) {
+                        Some("image_asset_pointer") => Some("*Image*".to_string()),
+                        Some("audio_transcription") => {
+                            part.get("text").and_then(|t| t.as_str()).map(String::from)
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            "code" => {
+                let language = content
+                    .get("language")
+                    .and_then(|l| l.as_str())
+                    .filter(|l| *l != "unknown")
+                    .unwrap_or("");
+                format!("```{}\n{}\n```", language, text()?)
+            }
+            "execution_output" => format!("```\n{}\n```", text()?),
+            "tether_quote" => {
+                let title = content.get("title").and_then(|t| t.as_str()).unwrap_or("");
+                format!("**{}**\n> {}", title, text()?.replace('\n', "\n> "))
+            }
+            "tether_browsing_display" => content.get("result")?.as_str()?.to_string(),
+            "thoughts" | "reasoning_recap" | "user_editable_context" | "model_editable_context" => {
+                return None
+            }
+            _ => text()?.to_string(),
+        };
+        Some(text)
+    }
+
+    fn parse_message(msg: &Value) -> Option<ParsedMessage> {
+        let hidden = msg
+            .pointer("/metadata/is_visually_hidden_from_conversation")
+            .and_then(|h| h.as_bool())
+            .unwrap_or(false);
+        if hidden {
+            return None;
+        }
+        let content = Self::content_text(msg.get("content")?)?;
+        if content.trim().is_empty() {
+            return None;
+        }
+
+        let role = match msg.pointer("/author/role").and_then(|r| r.as_str()) {
+            Some("user") => Role::User,
+            Some("assistant") => Role::Assistant,
+            Some("system") => Role::System,
+            Some("tool") => Role::Tool,
+            _ => Role::Info,
+        };
+        let tool_name = (role == Role::Tool)
+            .then(|| msg.pointer("/author/name").and_then(|n| n.as_str()))
+            .flatten()
+            .map(String::from);
+
+        Some(ParsedMessage {
+            role,
+            content,
+            timestamp: timestamp(msg.get("create_time")),
+            tool_name,
+            model: msg
+                .pointer("/metadata/model_slug")
+                .and_then(|m| m.as_str())
+                .map(String::from),
+        })
+    }
+}
+
+/// Time from Unix seconds with a fractional part.
+fn timestamp(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    let secs = value?.as_f64()?;
+    DateTime::from_timestamp(secs.trunc() as i64, (secs.fract() * 1e9) as u32)
+}
+
+impl Parser for ChatGptParser {
+    fn source_name(&self) -> &'static str {
+        "chatgpt"
+    }
+
+    fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
+        let content =
+            std::fs::read_to_string(raw_path).context("Cannot read ChatGPT conversation file")?;
+        let conv: Value =
+            serde_json::from_str(&content).context("Invalid JSON in ChatGPT conversation")?;
+        let mapping = conv
+            .get("mapping")
+            .and_then(|m| m.as_object())
+            .context("ChatGPT conversation has no message mapping")?;
+
+        let messages: Vec<ParsedMessage> = Self::branch(&conv, mapping)
+            .into_iter()
+            .filter_map(Self::parse_message)
+            .collect();
+
+        let id = conv
+            .get("conversation_id")
+            .or_else(|| conv.get("id"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| {
+                raw_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+        let model = conv
+            .get("default_model_slug")
+            .and_then(|m| m.as_str())
+            .map(String::from)
+            .or_else(|| messages.iter().rev().find_map(|m| m.model.clone()));
+
+        Ok(ParsedConversation {
+            id,
+            source: "chatgpt".to_string(),
+            title: conv.get("title").and_then(|t| t.as_str()).map(String::from),
+            workspace: None,
+            created_at: timestamp(conv.get("create_time")),
+            updated_at: timestamp(conv.get("update_time")),
+            model,
+            messages,
+            tags: Vec::new(),
+            usage: None,
+        })
+    }
+
+    fn can_parse(&self, raw_path: &Path) -> bool {
+        raw_path.extension().is_some_and(|ext| ext == "json")
+    }
+}
@@ -11,16 +11,27 @@
 //!
 //! Claude Code uses Anthropic API message format with multi-part content arrays.
 
+use super::json_lines::JsonLines;
 use super::{ParsedConversation, ParsedMessage, Parser, Role};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use serde_json::Value;
-use std::io::BufRead;
 use std::path::Path;
 
 /// Claude Code CLI Parser
 pub struct ClaudeCodeParser;
 
+/// Fields of a JSONL line used by the parser (others are skipped unparsed).
+#[derive(Deserialize)]
+struct Line {
+    role: Option<Value>,
+    timestamp: Option<Value>,
+    #[serde(rename = "createdAt")]
+    created_at: Option<Value>,
+    content: Option<Value>,
+}
+
 impl ClaudeCodeParser {
     /// Extract readable text from Claude Code content field.
     /// Content can be a string or Anthropic-style multi-part array.
@@ -139,8 +150,8 @@ impl Parser for ClaudeCodeParser {
     }
 
     fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
-        let file = std::fs::File::open(raw_path).context("Cannot open Claude Code JSONL file")?;
-        let reader = std::io::BufReader::new(file);
+        let lines =
+            JsonLines::<_, Line>::open(raw_path).context("Cannot open Claude Code JSONL file")?;
 
         let session_id = raw_path
             .file_stem()
@@ -165,29 +176,17 @@ impl Parser for ClaudeCodeParser {
         let mut first_timestamp: Option<DateTime<Utc>> = None;
         let mut last_timestamp: Option<DateTime<Utc>> = None;
 
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => continue,
-            };
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-
-            let obj: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-
-            let role_str = obj
-                .get("role")
+        for line in lines.map_while(Result::ok) {
+            let role_str = line
+                .role
+                .as_ref()
                 .and_then(|r| r.as_str())
                 .unwrap_or("unknown");
 
-            let timestamp = obj
-                .get("timestamp")
-                .or_else(|| obj.get("createdAt"))
+            let timestamp = line
+                .timestamp
+                .as_ref()
+                .or(line.created_at.as_ref())
                 .and_then(|v| v.as_str())
                 .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
                 .map(|dt| dt.with_timezone(&Utc));
@@ -199,11 +198,9 @@ impl Parser for ClaudeCodeParser {
                 last_timestamp = Some(ts);
             }
 
-            let content = obj.get("content");
-            if content.is_none() {
+            let Some(content) = &line.content else {
                 continue;
-            }
-            let content = content.unwrap();
+            };
 
             let (text, tool_calls) = Self::extract_content(content);
 
@@ -258,6 +255,7 @@ impl Parser for ClaudeCodeParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
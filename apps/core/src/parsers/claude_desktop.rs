@@ -0,0 +1,230 @@
+//! Claude Desktop Parser
+//!
+//! Parses the conversation responses cached by the Claude Desktop app (see
+//! [`crate::extractors::claude_desktop`]). A vault file is a Chromium simple
+//! cache entry: header, key (URL), then the response body up to an EOF record.
+//! The body is usually compressed (gzip, brotli or zstd) and holds the
+//! claude.ai conversation JSON:
+//! ```json
+//! {
+//!   "uuid": "...", "name": "...", "model": "...",
+//!   "created_at": "2025-01-01T00:00:00Z", "updated_at": "...",
+//!   "chat_messages": [
+//!     {
+//!       "sender": "human" | "assistant",
+//!       "text": "...",
+//!       "content": [
+//!         { "type": "text", "text": "..." },
+//!         { "type": "tool_use", "name": "...", "input": {...} },
+//!         { "type": "tool_result", "name": "...", "content": [{ "type": "text", "text": "..." }] }
+//!       ],
+//!       "attachments": [{ "file_name": "..." }],
+//!       "created_at": "..."
+//!     }
+//!   ]
+//! }
+//! ```
+//! Plain JSON files in that format are parsed as well.
+
+use super::{ParsedConversation, ParsedMessage, Parser, Role};
+use crate::extractors::claude_desktop::{SIMPLE_CACHE_HEADER_LEN, SIMPLE_CACHE_MAGIC};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+
+/// Magic number of the EOF record after each stream of a cache entry.
+const SIMPLE_CACHE_EOF_MAGIC: u64 = 0xf4fa6f45970d41d8;
+
+/// Claude Desktop Parser
+pub struct ClaudeDesktopParser;
+
+impl ClaudeDesktopParser {
+    /// Response body of a cache entry, or the data itself if it is not one.
+    fn body(data: &[u8]) -> Result<&[u8]> {
+        let is_entry =
+            data.len() >= SIMPLE_CACHE_HEADER_LEN && data[..8] == SIMPLE_CACHE_MAGIC.to_le_bytes();
+        if !is_entry {
+            return Ok(data);
+        }
+        let key_len = u32::from_le_bytes(data[12..16].try_into()?) as usize;
+        let start = SIMPLE_CACHE_HEADER_LEN + key_len;
+        let rest = data.get(start..).context("Truncated cache entry")?;
+        let end = rest
+            .windows(8)
+            .position(|w| w == SIMPLE_CACHE_EOF_MAGIC.to_le_bytes())
+            .context("Cache entry has no EOF record")?;
+        Ok(&rest[..end])
+    }
+
+    /// Decompress a response body by sniffing its encoding.
+    fn decode(body: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match body {
+            [b'{', ..] | [b'[', ..] => out.extend_from_slice(body),
+            [0x1f, 0x8b, ..] => {
+                flate2::read::GzDecoder::new(body)
+                    .read_to_end(&mut out)
+                    .context("Invalid gzip body")?;
+            }
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => out = zstd::decode_all(body)?,
+            _ => {
+                // Brotli has no magic number; fall back to it last
+                brotli_decompressor::Decompressor::new(body, 4096)
+                    .read_to_end(&mut out)
+                    .context("Unknown cache body encoding")?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Messages of one chat message (text, tool calls and results).
+    fn parse_message(msg: &Value) -> Vec<ParsedMessage> {
+        let role = match msg.get("sender").and_then(|s| s.as_str()) {
+            Some("human") => Role::User,
+            Some("assistant") => Role::Assistant,
+            _ => Role::Info,
+        };
+        let timestamp = parse_time(msg.get("created_at"));
+        let message = |role: Role, content: String, tool_name: Option<String>| ParsedMessage {
+            role,
+            content,
+            timestamp,
+            tool_name,
+            model: None,
+        };
+
+        let mut texts = Vec::new();
+        let mut tools = Vec::new();
+        for part in msg
+            .get("content")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let name = part.get("name").and_then(|n| n.as_str()).map(String::from);
+            match part.get("type").and_then(|t| t.as_str()) {
+                Some("text") => {
+                    if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                        texts.push(text.to_string());
+                    }
+                }
+                Some("tool_use") => {
+                    let input = part
+                        .get("input")
+                        .map(|i| serde_json::to_string_pretty(i).unwrap_or_default())
+                        .unwrap_or_default();
+                    let tool = name.as_deref().unwrap_or("unknown_tool");
+                    let content = if input.is_empty() || input == "{}" {
+                        format!("*Called tool: {}*", tool)
+                    } else {
+                        format!("*Called tool: {}*\n```json\n{}\n```", tool, input)
+                    };
+                    tools.push(message(Role::Tool, content, name));
+                }
+                Some("tool_result") => {
+                    let content = part
+                        .get("content")
+                        .and_then(|c| c.as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|c| c.get("text").and_then(|t| t.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if !content.trim().is_empty() {
+                        tools.push(message(Role::Tool, content, name));
+                    }
+                }
+                // Thinking blocks and other parts are not part of the transcript
+                _ => {}
+            }
+        }
+        // Older responses only have the flat `text` field
+        if texts.is_empty() {
+            if let Some(text) = msg.get("text").and_then(|t| t.as_str()) {
+                texts.push(text.to_string());
+            }
+        }
+        for attachment in msg
+            .get("attachments")
+            .and_then(|a| a.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if let Some(name) = attachment.get("file_name").and_then(|n| n.as_str()) {
+                texts.push(format!("*Attachment: {}*", name));
+            }
+        }
+
+        let mut messages = Vec::new();
+        let text = texts.join("\n\n");
+        if !text.trim().is_empty() {
+            messages.push(message(role, text, None));
+        }
+        messages.extend(tools);
+        messages
+    }
+}
+
+fn parse_time(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|v| v.as_str())
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|d| d.with_timezone(&Utc))
+}
+
+impl Parser for ClaudeDesktopParser {
+    fn source_name(&self) -> &'static str {
+        "claude-desktop"
+    }
+
+    fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
+        let data = std::fs::read(raw_path).context("Cannot read Claude Desktop cache entry")?;
+        let json = Self::decode(Self::body(&data)?)?;
+        let conv: Value =
+            serde_json::from_slice(&json).context("Invalid JSON in Claude Desktop conversation")?;
+
+        let Some(chat_messages) = conv.get("chat_messages").and_then(|m| m.as_array()) else {
+            bail!("Not a Claude conversation: {}", raw_path.display());
+        };
+        let messages: Vec<ParsedMessage> =
+            chat_messages.iter().flat_map(Self::parse_message).collect();
+
+        let id = conv
+            .get("uuid")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .or_else(|| {
+                raw_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+        let title = conv
+            .get("name")
+            .and_then(|n| n.as_str())
+            .filter(|n| !n.trim().is_empty())
+            .map(String::from);
+
+        Ok(ParsedConversation {
+            id,
+            source: "claude-desktop".to_string(),
+            title,
+            workspace: None,
+            created_at: parse_time(conv.get("created_at")),
+            updated_at: parse_time(conv.get("updated_at")),
+            model: conv.get("model").and_then(|m| m.as_str()).map(String::from),
+            messages,
+            tags: Vec::new(),
+            usage: None,
+        })
+    }
+
+    fn can_parse(&self, raw_path: &Path) -> bool {
+        // Copies are named after the conversation UUID, with no extension
+        // (or `.json` when ingested by file extension)
+        raw_path.extension().is_none_or(|ext| ext == "json")
+    }
+}
@@ -1,6 +1,7 @@
-//! Cline / Roo Code Parser
+//! Cline Parser
 //!
-//! Parses api_conversation_history.json files from Cline and Roo Code extensions.
+//! Parses api_conversation_history.json files from the Cline extension
+//! (also used by [`super::roo_code`] for the Roo Code fork).
 //!
 //! Format: JSON array of messages:
 //! ```json
@@ -23,7 +24,7 @@ use anyhow::{Context, Result};
 use serde_json::Value;
 use std::path::Path;
 
-/// Cline / Roo Code Parser
+/// Cline Parser
 pub struct ClineParser;
 
 impl ClineParser {
@@ -229,6 +230,7 @@ impl Parser for ClineParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
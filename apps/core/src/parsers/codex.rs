@@ -12,11 +12,11 @@
 //!
 //! Codex uses a streaming event format with type, role, and content fields.
 
+use super::json_lines::JsonLines;
 use super::{ParsedConversation, ParsedMessage, Parser, Role};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde_json::Value;
-use std::io::BufRead;
 use std::path::Path;
 
 /// OpenAI Codex CLI Parser
@@ -28,8 +28,8 @@ impl Parser for CodexParser {
     }
 
     fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
-        let file = std::fs::File::open(raw_path).context("Cannot open Codex JSONL file")?;
-        let reader = std::io::BufReader::new(file);
+        let lines =
+            JsonLines::<_, Value>::open(raw_path).context("Cannot open Codex JSONL file")?;
 
         let session_id = raw_path
             .file_stem()
@@ -41,21 +41,7 @@ impl Parser for CodexParser {
         let mut first_timestamp: Option<DateTime<Utc>> = None;
         let mut last_timestamp: Option<DateTime<Utc>> = None;
 
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => continue,
-            };
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-
-            let obj: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-
+        for obj in lines.map_while(Result::ok) {
             // Parse timestamp
             let timestamp = obj
                 .get("timestamp")
@@ -229,6 +215,7 @@ impl Parser for CodexParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
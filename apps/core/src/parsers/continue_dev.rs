@@ -268,6 +268,7 @@ impl Parser for ContinueDevParser {
             model,
             messages,
             tags,
+            usage: None,
         })
     }
 
@@ -0,0 +1,210 @@
+//! Fine-tuning datasets - Convert conversations into JSONL training records.
+//!
+//! Each conversation becomes one JSON line in one of two common layouts:
+//!
+//! - [`DatasetFormat::OpenAi`]: `{"messages": [{"role": "user", "content": ...}]}`
+//!   (OpenAI chat fine-tuning)
+//! - [`DatasetFormat::ShareGpt`]: `{"conversations": [{"from": "human", "value": ...}]}`
+//!   (Axolotl, LLaMA-Factory, ...)
+//!
+//! System, user and assistant turns are kept, as are tool calls and results
+//! unless stripped; informational messages (status, errors) are dropped.
+//! Consecutive messages of the same role are merged into one turn, and
+//! conversations without both a user and an assistant turn are skipped.
+
+use super::{ParsedConversation, Role};
+use crate::redact::redact_secrets;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::str::FromStr;
+
+/// Layout of the records of a dataset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    /// `messages` with `role`/`content` (OpenAI chat fine-tuning)
+    #[default]
+    OpenAi,
+    /// `conversations` with `from`/`value`
+    ShareGpt,
+}
+
+impl std::fmt::Display for DatasetFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpenAi => write!(f, "openai"),
+            Self::ShareGpt => write!(f, "sharegpt"),
+        }
+    }
+}
+
+impl FromStr for DatasetFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "openai" => Ok(Self::OpenAi),
+            "sharegpt" => Ok(Self::ShareGpt),
+            _ => Err(format!(
+                "unknown dataset format '{}' (expected openai or sharegpt)",
+                s
+            )),
+        }
+    }
+}
+
+/// What goes into the records.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DatasetOptions {
+    pub format: DatasetFormat,
+    /// Drop tool calls and results (kept as `tool`/`observation` turns
+    /// otherwise)
+    pub strip_tools: bool,
+    /// Mask credentials with [`redact_secrets`]
+    pub redact_secrets: bool,
+}
+
+/// A dataset record of a conversation, and how many secrets were masked in
+/// it. `None` if the conversation has no user/assistant exchange.
+pub fn to_record(
+    conv: &ParsedConversation,
+    options: &DatasetOptions,
+) -> Option<(serde_json::Value, usize)> {
+    let mut turns: Vec<(&Role, String)> = Vec::new();
+    let mut redacted = 0;
+    for message in &conv.messages {
+        let keep = match message.role {
+            Role::System | Role::User | Role::Assistant => true,
+            Role::Tool => !options.strip_tools,
+            Role::Info => false,
+        };
+        let content = message.content.trim();
+        if !keep || content.is_empty() {
+            continue;
+        }
+        let content = if options.redact_secrets {
+            let (content, count) = redact_secrets(content);
+            redacted += count;
+            content.into_owned()
+        } else {
+            content.to_string()
+        };
+        match turns.last_mut() {
+            Some((role, text)) if **role == message.role => {
+                text.push_str("\n\n");
+                text.push_str(&content);
+            }
+            _ => turns.push((&message.role, content)),
+        }
+    }
+
+    let has = |role: Role| turns.iter().any(|(r, _)| **r == role);
+    if !has(Role::User) || !has(Role::Assistant) {
+        return None;
+    }
+
+    let record = match options.format {
+        DatasetFormat::OpenAi => json!({
+            "messages": turns
+                .iter()
+                .map(|(role, content)| json!({ "role": openai_role(role), "content": content }))
+                .collect::<Vec<_>>(),
+        }),
+        DatasetFormat::ShareGpt => json!({
+            "conversations": turns
+                .iter()
+                .map(|(role, content)| json!({ "from": sharegpt_role(role), "value": content }))
+                .collect::<Vec<_>>(),
+        }),
+    };
+    Some((record, redacted))
+}
+
+fn openai_role(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool | Role::Info => "tool",
+    }
+}
+
+fn sharegpt_role(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "human",
+        Role::Assistant => "gpt",
+        Role::Tool | Role::Info => "observation",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ParsedMessage;
+
+    fn message(role: Role, content: &str) -> ParsedMessage {
+        ParsedMessage {
+            role,
+            content: content.to_string(),
+            timestamp: None,
+            tool_name: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_to_record() {
+        let conv = ParsedConversation {
+            id: "abc".to_string(),
+            source: "cursor".to_string(),
+            title: None,
+            workspace: None,
+            created_at: None,
+            updated_at: None,
+            model: None,
+            messages: vec![
+                message(Role::User, "Deploy with key sk-abcdefghijklmnopqrstuvwxyz"),
+                message(Role::Tool, "ls: done"),
+                message(Role::Info, "Context compacted"),
+                message(Role::Assistant, "Done."),
+                message(Role::Assistant, "Anything else?"),
+            ],
+            tags: vec![],
+            usage: None,
+        };
+
+        let options = DatasetOptions {
+            strip_tools: true,
+            redact_secrets: true,
+            ..Default::default()
+        };
+        let (record, redacted) = to_record(&conv, &options).unwrap();
+        assert_eq!(redacted, 1);
+        assert_eq!(
+            record,
+            json!({"messages": [
+                {"role": "user", "content": "Deploy with key [REDACTED:openai-key]"},
+                {"role": "assistant", "content": "Done.\n\nAnything else?"},
+            ]})
+        );
+
+        let options = DatasetOptions {
+            format: DatasetFormat::ShareGpt,
+            strip_tools: false,
+            redact_secrets: false,
+        };
+        let (record, _) = to_record(&conv, &options).unwrap();
+        let turns = record["conversations"].as_array().unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(
+            turns[1],
+            json!({"from": "observation", "value": "ls: done"})
+        );
+
+        let mut unanswered = conv.clone();
+        unanswered.messages.truncate(2);
+        assert!(to_record(&unanswered, &options).is_none());
+        assert_eq!("ShareGPT".parse(), Ok(DatasetFormat::ShareGpt));
+    }
+}
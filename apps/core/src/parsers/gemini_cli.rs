@@ -176,6 +176,7 @@ impl Parser for GeminiCliParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
@@ -124,6 +124,7 @@ impl JetBrainsParser {
                 model: messages.iter().rev().find_map(|m| m.model.clone()),
                 messages,
                 tags: Vec::new(),
+                usage: None,
             });
         }
 
@@ -298,6 +299,7 @@ impl JetBrainsParser {
                     model: messages.iter().rev().find_map(|m| m.model.clone()),
                     messages,
                     tags: Vec::new(),
+                    usage: None,
                 });
             }
 
@@ -417,6 +419,7 @@ impl Parser for JetBrainsParser {
                 model: None,
                 messages: Vec::new(),
                 tags: Vec::new(),
+                usage: None,
             });
         }
 
@@ -464,6 +467,7 @@ impl Parser for JetBrainsParser {
             model,
             messages: merged_messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
@@ -0,0 +1,170 @@
+//! Streaming JSONL reader shared by the line-oriented parsers.
+//!
+//! Lines are read into one reused byte buffer and deserialized straight from
+//! it, so a session costs a single buffer instead of one `String` per line.
+//! Deserializing into a typed struct also skips unused fields (large tool
+//! outputs, attachments) without building a `Value` tree for them.
+//!
+//! Sessions are never read whole, so files of hundreds of MB parse in
+//! constant memory. A single line larger than the message size limit
+//! ([`super::max_message_bytes`]) is skipped rather than buffered.
+
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Iterator over the JSON records of a JSONL stream.
+///
+/// Blank lines, lines that do not deserialize as `T` and lines over the size
+/// limit are skipped. A read error is yielded once and ends the iteration.
+pub struct JsonLines<R, T> {
+    reader: R,
+    buf: Vec<u8>,
+    max_line: usize,
+    done: bool,
+    _record: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> JsonLines<BufReader<File>, T> {
+    /// Open a JSONL file, with the configured message size limit.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)).max_line(super::max_message_bytes()))
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> JsonLines<R, T> {
+    /// Read records from any buffered reader, without a line size limit.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            max_line: usize::MAX,
+            done: false,
+            _record: PhantomData,
+        }
+    }
+
+    /// Skip lines longer than `limit` bytes.
+    pub fn max_line(mut self, limit: usize) -> Self {
+        self.max_line = limit;
+        self
+    }
+
+    /// Discard the rest of the current line without buffering it.
+    fn skip_line(&mut self) -> io::Result<usize> {
+        let mut skipped = 0;
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(skipped);
+            }
+            match available.iter().position(|&b| b == b'\n') {
+                Some(end) => {
+                    self.reader.consume(end + 1);
+                    return Ok(skipped + end + 1);
+                }
+                None => {
+                    let len = available.len();
+                    self.reader.consume(len);
+                    skipped += len;
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for JsonLines<R, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.buf.clear();
+            // One byte over the limit tells a full line from an oversized one
+            let limit = self.max_line.saturating_add(1) as u64;
+            let read = (&mut self.reader)
+                .take(limit)
+                .read_until(b'\n', &mut self.buf);
+            match read {
+                Ok(0) => self.done = true,
+                Ok(read) if read as u64 == limit && self.buf.last() != Some(&b'\n') => {
+                    // Release the oversized line rather than keep its capacity
+                    self.buf = Vec::new();
+                    match self.skip_line() {
+                        Ok(rest) => tracing::warn!(
+                            "Skipped a {} byte JSONL line (limit {} bytes)",
+                            read + rest,
+                            self.max_line
+                        ),
+                        Err(e) => {
+                            self.done = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+                Ok(_) => {
+                    let line = self.buf.trim_ascii();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    if let Ok(record) = serde_json::from_slice(line) {
+                        return Some(Ok(record));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Deserialize)]
+    struct Record {
+        kind: i64,
+    }
+
+    #[test]
+    fn test_json_lines_skip_blank_and_invalid() {
+        let input = "{\"kind\": 1, \"big\": [1, 2]}\n\n  not json\n{\"other\": 2}\r\n{\"kind\": 3}";
+        let kinds: Vec<i64> = JsonLines::<_, Record>::new(input.as_bytes())
+            .map(|r| r.unwrap().kind)
+            .collect();
+        assert_eq!(kinds, vec![1, 3]);
+
+        let values: Vec<Value> = JsonLines::new(input.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(values.len(), 3);
+    }
+
+    #[test]
+    fn test_json_lines_skip_oversized_lines() {
+        let big = format!("{{\"kind\": 2, \"text\": \"{}\"}}", "x".repeat(100));
+        let input = format!("{{\"kind\": 1}}\n{}\n{{\"kind\": 3}}\n", big);
+        let kinds: Vec<i64> =
+            JsonLines::<_, Record>::new(std::io::BufReader::with_capacity(16, input.as_bytes()))
+                .max_line(64)
+                .map(|r| r.unwrap().kind)
+                .collect();
+        assert_eq!(kinds, vec![1, 3]);
+
+        // A line of exactly the limit is kept
+        let exact = "{\"kind\": 4}";
+        let kinds: Vec<i64> = JsonLines::<_, Record>::new(format!("{}\n", exact).as_bytes())
+            .max_line(exact.len())
+            .map(|r| r.unwrap().kind)
+            .collect();
+        assert_eq!(kinds, vec![4]);
+    }
+}
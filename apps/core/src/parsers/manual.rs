@@ -0,0 +1,316 @@
+//! Manual Parser
+//!
+//! Parses chats the user pasted from a web UI and imported with
+//! `echovault-cli import file` (see [`crate::importers::manual`]). Each chat is
+//! stored unmodified as `sessions/manual/<id>.md` or `<id>.json`, next to a
+//! `<id>.meta.json` sidecar holding the title and origin given on import.
+//!
+//! Markdown chats mark each turn with a role line, as a heading or a label:
+//! ```markdown
+//! ## User
+//! How do I sort a vector?
+//!
+//! **Assistant:** Use `sort()`.
+//!
+//! ChatGPT said:
+//! ...
+//! ```
+//! Recognized roles: user/human/you/me, assistant/ai/model/bot/chatgpt/claude/
+//! gemini/copilot, system. YAML frontmatter is skipped (its `title` is used),
+//! as is text before the first role line. Role lines inside fenced code
+//! blocks are content.
+//!
+//! JSON chats use the OpenAI messages format, as an array or as
+//! `{"messages": [...]}`; `content` may be a string or a list of text parts.
+
+use super::{ParsedConversation, ParsedMessage, Parser, Role};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Source name of imported chats.
+pub const SOURCE: &str = "manual";
+
+/// Suffix of the sidecar of an imported chat.
+const META_SUFFIX: &str = ".meta.json";
+
+/// What the user said about an imported chat.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManualMeta {
+    /// Title given on import
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Where the chat came from (e.g. "claude.ai"), when not just "manual"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// Last modification of the imported file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl ManualMeta {
+    /// Path of the sidecar of a stored chat.
+    pub fn path(raw_path: &Path) -> PathBuf {
+        let stem = raw_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        raw_path.with_file_name(format!("{}{}", stem, META_SUFFIX))
+    }
+
+    fn read(raw_path: &Path) -> Self {
+        std::fs::read(Self::path(raw_path))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Manual Parser
+pub struct ManualParser;
+
+impl Parser for ManualParser {
+    fn source_name(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
+        let content = std::fs::read_to_string(raw_path)
+            .with_context(|| format!("Cannot read imported chat {}", raw_path.display()))?;
+        let (messages, title) = if raw_path.extension().is_some_and(|ext| ext == "json") {
+            (parse_json(&content)?, None)
+        } else {
+            parse_markdown(&content)
+        };
+        if messages.is_empty() {
+            bail!("No messages found in {}", raw_path.display());
+        }
+
+        let meta = ManualMeta::read(raw_path);
+        let id = raw_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let tags = meta
+            .origin
+            .iter()
+            .map(|origin| format!("origin:{}", origin))
+            .collect();
+        Ok(ParsedConversation {
+            id,
+            source: SOURCE.to_string(),
+            title: meta.title.or(title),
+            workspace: None,
+            created_at: meta.created_at,
+            updated_at: None,
+            model: None,
+            messages,
+            tags,
+            usage: None,
+        })
+    }
+
+    fn can_parse(&self, raw_path: &Path) -> bool {
+        let name = raw_path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        !name.ends_with(META_SUFFIX)
+            && raw_path
+                .extension()
+                .is_some_and(|ext| ext == "md" || ext == "json")
+    }
+}
+
+/// Messages of a Markdown chat, and the title from its frontmatter or first
+/// `# heading`.
+pub fn parse_markdown(content: &str) -> (Vec<ParsedMessage>, Option<String>) {
+    let mut lines = content.lines().peekable();
+    let mut title = None;
+
+    // Frontmatter (e.g. a conversation exported by EchoVault)
+    if lines.peek().is_some_and(|l| l.trim() == "---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line.trim() == "---" {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("title:") {
+                title = Some(value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+
+    let mut messages: Vec<ParsedMessage> = Vec::new();
+    let mut current: Option<(Role, String)> = None;
+    let mut in_code = false;
+    for line in lines {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if let Some((role, rest)) = role_line(line).filter(|_| !in_code) {
+            if let Some((role, text)) = current.take() {
+                push_message(&mut messages, role, &text);
+            }
+            current = Some((role, format!("{}\n", rest)));
+            continue;
+        }
+        match &mut current {
+            Some((_, text)) => {
+                text.push_str(line);
+                text.push('\n');
+            }
+            None => {
+                if let Some(heading) = line.strip_prefix("# ") {
+                    title.get_or_insert_with(|| heading.trim().to_string());
+                }
+            }
+        }
+    }
+    if let Some((role, text)) = current {
+        push_message(&mut messages, role, &text);
+    }
+    (messages, title.filter(|t| !t.is_empty()))
+}
+
+/// Role of a line starting a turn, and the text following the role label.
+fn role_line(line: &str) -> Option<(Role, &str)> {
+    let line = line.trim();
+    let heading = line.trim_start_matches('#');
+    let (label, rest) = if heading.len() < line.len() {
+        // "## User" (the whole heading is the label)
+        (heading.trim().trim_end_matches(':'), "")
+    } else if let Some(bold) = line.strip_prefix("**") {
+        // "**User:** text" or "**User**: text"
+        let (label, rest) = bold.split_once("**")?;
+        let rest = rest.trim_start();
+        match label.strip_suffix(':') {
+            Some(label) => (label, rest),
+            None => (label, rest.strip_prefix(':')?.trim_start()),
+        }
+    } else {
+        // "User: text", "ChatGPT said:"
+        let (label, rest) = line.split_once(':')?;
+        (label, rest.trim_start())
+    };
+
+    let label = label.trim().to_ascii_lowercase();
+    let label = label.strip_suffix(" said").unwrap_or(&label);
+    let role = match label {
+        "user" | "human" | "you" | "me" | "prompt" => Role::User,
+        "assistant" | "ai" | "model" | "bot" | "chatgpt" | "claude" | "gemini" | "copilot"
+        | "response" => Role::Assistant,
+        "system" => Role::System,
+        _ => return None,
+    };
+    Some((role, rest))
+}
+
+fn push_message(messages: &mut Vec<ParsedMessage>, role: Role, text: &str) {
+    let content = text.trim();
+    if content.is_empty() {
+        return;
+    }
+    messages.push(ParsedMessage {
+        role,
+        content: content.to_string(),
+        timestamp: None,
+        tool_name: None,
+        model: None,
+    });
+}
+
+/// Messages of a chat in the OpenAI messages format.
+pub fn parse_json(content: &str) -> Result<Vec<ParsedMessage>> {
+    let value: Value = serde_json::from_str(content).context("Invalid JSON chat")?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(map) => map
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .context("JSON chat has no \"messages\" array")?,
+        _ => bail!("JSON chat must be a messages array or {{\"messages\": [...]}}"),
+    };
+
+    let mut messages = Vec::new();
+    for item in items {
+        let role = match item.get("role").and_then(|r| r.as_str()) {
+            Some("user") => Role::User,
+            Some("assistant") => Role::Assistant,
+            Some("system") | Some("developer") => Role::System,
+            Some("tool") | Some("function") => Role::Tool,
+            _ => continue,
+        };
+        let text = match item.get("content") {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Array(parts)) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    Value::String(text) => Some(text.as_str()),
+                    part => part.get("text").and_then(|t| t.as_str()),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => continue,
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        messages.push(ParsedMessage {
+            role,
+            content: text.trim().to_string(),
+            timestamp: None,
+            tool_name: item.get("name").and_then(|n| n.as_str()).map(String::from),
+            model: None,
+        });
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_markdown() {
+        let content = "# Sorting help\n\nCopied from the web.\n\n## User\nHow do I sort?\n\n\
+                       **Assistant:** Use `sort()`:\n\n```rust\nv.sort();\n```\n\n\
+                       You said: Thanks\nChatGPT said:\nAnytime.\n";
+        let (messages, title) = parse_markdown(content);
+        assert_eq!(title.as_deref(), Some("Sorting help"));
+        let roles: Vec<&Role> = messages.iter().map(|m| &m.role).collect();
+        assert_eq!(
+            roles,
+            [&Role::User, &Role::Assistant, &Role::User, &Role::Assistant]
+        );
+        assert_eq!(
+            messages[1].content,
+            "Use `sort()`:\n\n```rust\nv.sort();\n```"
+        );
+        assert_eq!(messages[3].content, "Anytime.");
+
+        // Ordinary "Label:" lines are not turns
+        let (messages, _) = parse_markdown("User: Hi\nNote: this is text\n");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "Hi\nNote: this is text");
+    }
+
+    #[test]
+    fn test_parse_json() -> Result<()> {
+        let content = r#"{"messages": [
+            {"role": "developer", "content": "Be brief."},
+            {"role": "user", "content": [{"type": "text", "text": "Hi"}]},
+            {"role": "assistant", "content": "Hello!"},
+            {"role": "assistant", "content": null}
+        ]}"#;
+        let messages = parse_json(content)?;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].role, Role::System);
+        assert_eq!(messages[1].content, "Hi");
+        assert!(parse_json("42").is_err());
+        Ok(())
+    }
+}
@@ -22,11 +22,76 @@
 //!
 //! Here's how you can implement a REST API using Actix-web...
 //! ```
+//!
+//! Annotations of a session (see [`crate::storage::Annotation`]) are written
+//! into the frontmatter of its parsed file as `note`, `user_tags`, `rating` and
+//! `follow_up` whenever they change.
+//!
+//! Files written by the parse stage ([`write_parsed`]) also record the hash of
+//! the raw session they were parsed from as `source_hash`, so an edited raw
+//! session is parsed again while an unchanged one (even one parsed on another
+//! machine) is not.
+//!
+//! The [`MarkdownFlavor::Logseq`] flavor writes the same conversation as a
+//! Logseq page instead: `key:: value` page properties, one outline block per
+//! message, and a `date::` link to the journal page of the day it started.
 
 use super::{ParsedConversation, ParsedMessage, Role};
+use crate::storage::{atomic, Annotation};
 use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
 use std::fmt::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Markdown dialect of exported conversations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownFlavor {
+    /// Markdown with YAML frontmatter and a `## Role` section per message
+    #[default]
+    Standard,
+    /// Logseq page: page properties and an outline block per message
+    Logseq,
+}
+
+impl MarkdownFlavor {
+    /// Path of a conversation's file inside an export directory.
+    ///
+    /// Logseq pages go flat into `pages/`, so the export directory can be
+    /// opened (or merged) as a Logseq graph.
+    pub fn export_path(&self, conv: &ParsedConversation) -> PathBuf {
+        match self {
+            Self::Standard => PathBuf::from(&conv.source).join(format!("{}.md", conv.id)),
+            Self::Logseq => PathBuf::from("pages").join(format!("{}.md", conv.id)),
+        }
+    }
+}
+
+impl std::fmt::Display for MarkdownFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::Logseq => write!(f, "logseq"),
+        }
+    }
+}
+
+impl FromStr for MarkdownFlavor {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" | "markdown" => Ok(Self::Standard),
+            "logseq" => Ok(Self::Logseq),
+            _ => Err(format!(
+                "unknown Markdown flavor '{}' (expected standard or logseq)",
+                s
+            )),
+        }
+    }
+}
 
 /// Write a ParsedConversation to a Markdown file with YAML frontmatter.
 pub fn write_markdown(conversation: &ParsedConversation, output_path: &Path) -> Result<()> {
@@ -37,10 +102,68 @@ pub fn write_markdown(conversation: &ParsedConversation, output_path: &Path) ->
         std::fs::create_dir_all(parent)?;
     }
 
-    std::fs::write(output_path, content)?;
+    atomic::write(output_path, content)?;
+    Ok(())
+}
+
+/// Frontmatter key of the hash of the raw session a parsed file came from.
+const SOURCE_HASH_KEY: &str = "source_hash";
+
+/// Write the parsed Markdown of a conversation with the hash of its raw
+/// session as `source_hash`, keeping the annotation keys of the file it
+/// replaces.
+pub fn write_parsed(
+    conversation: &ParsedConversation,
+    output_path: &Path,
+    source_hash: &str,
+) -> Result<()> {
+    let content = render_markdown(conversation)?;
+    let previous = std::fs::read_to_string(output_path).unwrap_or_default();
+    let mut extra = format!("{}: {}\n", SOURCE_HASH_KEY, source_hash);
+    for line in frontmatter_lines(&previous) {
+        let key = line.split(':').next().unwrap_or_default();
+        if ANNOTATION_KEYS.contains(&key) {
+            extra.push_str(line);
+            extra.push('\n');
+        }
+    }
+    // Before the closing fence of the frontmatter
+    let end = content.find("\n---\n").map_or(0, |i| i + 1);
+    let mut content = content;
+    content.insert_str(end, &extra);
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic::write(output_path, content)?;
     Ok(())
 }
 
+/// `source_hash` recorded in a parsed Markdown file, read from its
+/// frontmatter only.
+pub fn parsed_source_hash(path: &Path) -> Option<String> {
+    use std::io::BufRead;
+    let file = std::fs::File::open(path).ok()?;
+    let mut lines = std::io::BufReader::new(file).lines().map_while(Result::ok);
+    if lines.next()? != "---" {
+        return None;
+    }
+    lines.take_while(|line| line != "---").find_map(|line| {
+        line.strip_prefix(SOURCE_HASH_KEY)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(|hash| hash.trim().to_string())
+    })
+}
+
+/// Lines of the frontmatter of Markdown content (none without frontmatter).
+fn frontmatter_lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.find("\n---\n").map(|end| &rest[..end]))
+        .unwrap_or_default()
+        .lines()
+}
+
 /// Render a ParsedConversation to a Markdown string.
 pub fn render_markdown(conversation: &ParsedConversation) -> Result<String> {
     let mut out = String::with_capacity(4096);
@@ -49,14 +172,58 @@ pub fn render_markdown(conversation: &ParsedConversation) -> Result<String> {
     write_frontmatter(&mut out, conversation)?;
 
     // Messages
+    write_messages(&mut out, conversation)?;
+
+    Ok(out)
+}
+
+/// Write a ParsedConversation to a Markdown file in the given flavor.
+pub fn write_markdown_as(
+    conversation: &ParsedConversation,
+    output_path: &Path,
+    flavor: MarkdownFlavor,
+) -> Result<()> {
+    let content = render_markdown_as(conversation, flavor)?;
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic::write(output_path, content)?;
+    Ok(())
+}
+
+/// Render a ParsedConversation to a Markdown string in the given flavor.
+pub fn render_markdown_as(
+    conversation: &ParsedConversation,
+    flavor: MarkdownFlavor,
+) -> Result<String> {
+    match flavor {
+        MarkdownFlavor::Standard => render_markdown(conversation),
+        MarkdownFlavor::Logseq => render_logseq(conversation),
+    }
+}
+
+/// Render only the YAML frontmatter block (with its `---` fences).
+pub fn render_frontmatter(conversation: &ParsedConversation) -> Result<String> {
+    let mut out = String::with_capacity(256);
+    write_frontmatter(&mut out, conversation)?;
+    Ok(out)
+}
+
+/// Render only the messages, one `## Role` section each.
+pub fn render_messages(conversation: &ParsedConversation) -> Result<String> {
+    let mut out = String::with_capacity(4096);
+    write_messages(&mut out, conversation)?;
+    Ok(out)
+}
+
+fn write_messages(out: &mut String, conversation: &ParsedConversation) -> Result<()> {
     for (i, msg) in conversation.messages.iter().enumerate() {
         if i > 0 {
             out.push('\n');
         }
-        write_message(&mut out, msg)?;
+        write_message(out, msg)?;
     }
-
-    Ok(out)
+    Ok(())
 }
 
 /// Write YAML frontmatter block.
@@ -158,8 +325,163 @@ fn write_content(out: &mut String, content: &str) {
     out.push_str("\n\n");
 }
 
+/// Render a conversation as a Logseq page.
+fn render_logseq(conv: &ParsedConversation) -> Result<String> {
+    let mut out = String::with_capacity(4096);
+
+    // Page properties (the first, bullet-less block)
+    if let Some(title) = &conv.title {
+        writeln!(out, "title:: {}", single_line(title))?;
+    }
+    writeln!(out, "source:: {}", conv.source)?;
+    if let Some(workspace) = &conv.workspace {
+        writeln!(out, "workspace:: {}", single_line(workspace))?;
+    }
+    if let Some(model) = &conv.model {
+        writeln!(out, "model:: {}", model)?;
+    }
+    if !conv.tags.is_empty() {
+        writeln!(out, "tags:: {}", conv.tags.join(", "))?;
+    }
+    if let Some(created_at) = &conv.created_at {
+        writeln!(out, "date:: [[{}]]", journal_title(created_at))?;
+    }
+    writeln!(out, "echovault-id:: {}", conv.id)?;
+    out.push('\n');
+
+    // One block per message, its content indented under the header line
+    for msg in &conv.messages {
+        write!(out, "- **{}**", role_label(msg))?;
+        if msg.role == Role::Assistant {
+            if let Some(model) = &msg.model {
+                write!(out, " ({})", model)?;
+            }
+        }
+        if let Some(ts) = &msg.timestamp {
+            write!(out, " {}", ts.format("%H:%M:%S"))?;
+        }
+        out.push('\n');
+
+        let content = msg.content.trim();
+        let content = if content.is_empty() {
+            "*(empty)*"
+        } else {
+            content
+        };
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                out.push('\n');
+            } else {
+                writeln!(out, "  {}", line)?;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Role header of a message (`Tool: <name>` for named tools).
+fn role_label(msg: &ParsedMessage) -> String {
+    match (&msg.role, &msg.tool_name) {
+        (Role::Tool, Some(name)) => format!("Tool: {}", name),
+        (Role::User, _) => "User".to_string(),
+        (Role::Assistant, _) => "Assistant".to_string(),
+        (Role::System, _) => "System".to_string(),
+        (Role::Tool, None) => "Tool".to_string(),
+        (Role::Info, _) => "Info".to_string(),
+    }
+}
+
+/// Title of Logseq's journal page for a day, in its default
+/// `MMM do, yyyy` format (e.g., "Jan 15th, 2024").
+fn journal_title(date: &DateTime<Utc>) -> String {
+    let day = date.day();
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{} {}{}, {}", date.format("%b"), day, suffix, date.year())
+}
+
+/// Property values must stay on one line.
+fn single_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 /// Escape special characters in YAML strings.
-fn escape_yaml_string(s: &str) -> String {
+/// Frontmatter keys holding the annotation of a session.
+const ANNOTATION_KEYS: &[&str] = &["note", "user_tags", "rating", "follow_up"];
+
+/// Replace the annotation keys in the frontmatter of a parsed Markdown file.
+/// Content without frontmatter is returned unchanged.
+pub fn apply_annotation(content: &str, annotation: Option<&Annotation>) -> String {
+    let Some(rest) = content.strip_prefix("---\n") else {
+        return content.to_string();
+    };
+    let Some(end) = rest.find("\n---\n") else {
+        return content.to_string();
+    };
+    let (frontmatter, body) = rest.split_at(end + 1);
+
+    let mut out = String::with_capacity(content.len() + 128);
+    out.push_str("---\n");
+    for line in frontmatter.lines() {
+        let key = line.split(':').next().unwrap_or_default();
+        if !ANNOTATION_KEYS.contains(&key) {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    if let Some(a) = annotation {
+        if !a.note.is_empty() {
+            let note = escape_yaml_string(&a.note).replace('\n', "\\n");
+            out.push_str(&format!("note: \"{}\"\n", note));
+        }
+        if !a.tags.is_empty() {
+            out.push_str(&format!("user_tags: [{}]\n", a.tags.join(", ")));
+        }
+        if let Some(rating) = a.rating {
+            out.push_str(&format!("rating: {}\n", rating));
+        }
+        if a.follow_up {
+            out.push_str("follow_up: true\n");
+        }
+    }
+    out.push_str(body);
+    out
+}
+
+/// Write the annotation of a session (`None` once removed) into its parsed
+/// Markdown files. Returns how many files were updated.
+pub fn write_annotation(
+    vault_dir: &Path,
+    session_id: &str,
+    annotation: Option<&Annotation>,
+) -> Result<usize> {
+    let parsed_dir = vault_dir.join("parsed");
+    let Ok(sources) = std::fs::read_dir(&parsed_dir) else {
+        return Ok(0);
+    };
+    let mut updated = 0;
+    for source in sources.flatten() {
+        let path = source.path().join(format!("{}.md", session_id));
+        if !path.is_file() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let annotated = apply_annotation(&content, annotation);
+        if annotated != content {
+            atomic::write(&path, annotated)?;
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+pub(crate) fn escape_yaml_string(s: &str) -> String {
     s.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
@@ -196,6 +518,7 @@ mod tests {
                 },
             ],
             tags: vec!["rust".to_string()],
+            usage: None,
         };
 
         let result = render_markdown(&conv).unwrap();
@@ -207,9 +530,118 @@ mod tests {
         assert!(result.contains("sort a vector"));
     }
 
+    #[test]
+    fn test_render_logseq_page() {
+        use chrono::TimeZone;
+
+        let conv = ParsedConversation {
+            id: "test-123".to_string(),
+            source: "cursor".to_string(),
+            title: Some("Sort\nvectors".to_string()),
+            workspace: None,
+            created_at: Some(Utc.with_ymd_and_hms(2024, 3, 22, 9, 0, 0).unwrap()),
+            updated_at: None,
+            model: None,
+            messages: vec![
+                ParsedMessage {
+                    role: Role::User,
+                    content: "How do I sort?\n\nIn place.".to_string(),
+                    timestamp: None,
+                    tool_name: None,
+                    model: None,
+                },
+                ParsedMessage {
+                    role: Role::Tool,
+                    content: String::new(),
+                    timestamp: None,
+                    tool_name: Some("read_file".to_string()),
+                    model: None,
+                },
+            ],
+            tags: vec!["rust".to_string()],
+            usage: None,
+        };
+
+        let page = render_markdown_as(&conv, MarkdownFlavor::Logseq).unwrap();
+        assert_eq!(
+            page,
+            "title:: Sort vectors\nsource:: cursor\ntags:: rust\n\
+             date:: [[Mar 22nd, 2024]]\nechovault-id:: test-123\n\n\
+             - **User**\n  How do I sort?\n\n  In place.\n\
+             - **Tool: read_file**\n  *(empty)*\n"
+        );
+        assert_eq!(
+            MarkdownFlavor::Logseq.export_path(&conv),
+            PathBuf::from("pages/test-123.md")
+        );
+        assert_eq!("LogSeq".parse(), Ok(MarkdownFlavor::Logseq));
+        assert!("org".parse::<MarkdownFlavor>().is_err());
+    }
+
     #[test]
     fn test_escape_yaml_string() {
         assert_eq!(escape_yaml_string("hello \"world\""), "hello \\\"world\\\"");
         assert_eq!(escape_yaml_string("path\\to\\file"), "path\\\\to\\\\file");
     }
+
+    #[test]
+    fn test_apply_annotation() {
+        let content = "---\nid: s1\nsource: cursor\nmessage_count: 2\n---\n\n## User\n\nnote: not frontmatter\n";
+        let annotation = Annotation {
+            session_id: "s1".to_string(),
+            note: "Two\nlines".to_string(),
+            tags: vec!["async".to_string(), "rust".to_string()],
+            rating: Some(4),
+            follow_up: false,
+            updated_at: 0,
+        };
+
+        let annotated = apply_annotation(content, Some(&annotation));
+        assert!(annotated.contains(
+            "message_count: 2\nnote: \"Two\\nlines\"\nuser_tags: [async, rust]\nrating: 4\n---\n"
+        ));
+        assert!(annotated.ends_with("## User\n\nnote: not frontmatter\n"));
+
+        // Applying again replaces the keys; removing the annotation drops them
+        assert_eq!(apply_annotation(&annotated, Some(&annotation)), annotated);
+        assert_eq!(apply_annotation(&annotated, None), content);
+        assert_eq!(apply_annotation("no frontmatter", None), "no frontmatter");
+    }
+
+    #[test]
+    fn test_write_parsed_records_hash_and_keeps_annotation() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let path = temp.path().join("cursor/s1.md");
+        let conv = ParsedConversation {
+            id: "s1".to_string(),
+            source: "cursor".to_string(),
+            title: None,
+            workspace: None,
+            created_at: None,
+            updated_at: None,
+            model: None,
+            messages: vec![ParsedMessage {
+                role: Role::User,
+                content: "Hello".to_string(),
+                timestamp: None,
+                tool_name: None,
+                model: None,
+            }],
+            tags: vec![],
+            usage: None,
+        };
+        write_parsed(&conv, &path, "aaa")?;
+        assert_eq!(parsed_source_hash(&path).as_deref(), Some("aaa"));
+
+        let content = std::fs::read_to_string(&path)?;
+        std::fs::write(
+            &path,
+            content.replace("source_hash", "rating: 5\nsource_hash"),
+        )?;
+        write_parsed(&conv, &path, "bbb")?;
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!(parsed_source_hash(&path).as_deref(), Some("bbb"));
+        assert!(content.contains("rating: 5\n---\n\n## User"));
+        Ok(())
+    }
 }
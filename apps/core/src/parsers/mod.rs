@@ -10,25 +10,63 @@
 
 pub mod aider;
 pub mod antigravity;
+pub mod chatgpt;
 pub mod claude_code;
+pub mod claude_desktop;
 pub mod cline;
 pub mod codex;
 pub mod continue_dev;
 pub mod cursor;
+pub mod dataset;
 pub mod gemini_cli;
 pub mod jetbrains;
+pub mod json_lines;
+pub mod manual;
 pub mod markdown_writer;
+pub mod obsidian;
 pub mod opencode;
+pub mod roo_code;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod vault_parse;
 pub mod vscode_copilot;
+pub mod windsurf;
 pub mod zed;
 
+use crate::extractors::INGEST_TEMP_GLOB;
+use crate::progress::Operation;
+use crate::storage::hash_cache::HashCache;
+use crate::storage::{atomic, compressed};
+use crate::utils::Walk;
+use crate::watcher::build_ignore_set;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Largest JSONL line (one message) the streaming parsers read, in bytes,
+/// unless `[parse] max_message_mb` says otherwise
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+static MAX_MESSAGE_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_MESSAGE_BYTES);
+
+/// Set the size limit of a single message of streamed (JSONL) sessions;
+/// larger lines are skipped. Applied from the config on load.
+pub fn set_max_message_bytes(limit: usize) {
+    MAX_MESSAGE_BYTES.store(limit.max(1), Ordering::Relaxed);
+}
+
+/// Current size limit of a single message of streamed sessions.
+pub fn max_message_bytes() -> usize {
+    MAX_MESSAGE_BYTES.load(Ordering::Relaxed)
+}
 
 /// Role of a message sender.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     /// Human user
@@ -57,6 +95,7 @@ impl std::fmt::Display for Role {
 
 /// A single message in a conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ParsedMessage {
     /// Role of the message sender
     pub role: Role,
@@ -70,8 +109,51 @@ pub struct ParsedMessage {
     pub model: Option<String>,
 }
 
+/// Tokens used by a conversation, as reported by its source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TokenCounts {
+    /// Prompt tokens
+    pub input_tokens: u64,
+    /// Completion tokens
+    pub output_tokens: u64,
+}
+
+impl TokenCounts {
+    /// Counts of a usage object: `input_tokens`/`output_tokens` (Anthropic,
+    /// Zed) or `prompt_tokens`/`completion_tokens` (OpenAI). `None` when it
+    /// holds neither or only zeros.
+    pub fn from_json(usage: &serde_json::Value) -> Option<Self> {
+        let count = |keys: [&str; 2]| {
+            keys.iter()
+                .find_map(|key| usage.get(key).and_then(serde_json::Value::as_u64))
+        };
+        let counts = Self {
+            input_tokens: count(["input_tokens", "prompt_tokens"]).unwrap_or(0),
+            output_tokens: count(["output_tokens", "completion_tokens"]).unwrap_or(0),
+        };
+        (counts.total() > 0).then_some(counts)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Sum of the counts recorded, or `None` when none is.
+    pub fn sum<'a>(counts: impl IntoIterator<Item = Option<&'a TokenCounts>>) -> Option<Self> {
+        counts.into_iter().flatten().fold(None, |sum, counts| {
+            let sum: TokenCounts = sum.unwrap_or_default();
+            Some(TokenCounts {
+                input_tokens: sum.input_tokens + counts.input_tokens,
+                output_tokens: sum.output_tokens + counts.output_tokens,
+            })
+        })
+    }
+}
+
 /// A fully parsed conversation with all messages.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ParsedConversation {
     /// Unique session ID
     pub id: String,
@@ -91,6 +173,9 @@ pub struct ParsedConversation {
     pub messages: Vec<ParsedMessage>,
     /// Tags for categorization (auto-extracted or user-defined)
     pub tags: Vec<String>,
+    /// Tokens used, when the source records them (summed over the replies)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenCounts>,
 }
 
 impl ParsedConversation {
@@ -112,6 +197,29 @@ impl ParsedConversation {
     }
 }
 
+/// Version of the canonical JSON format, bumped on incompatible changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A conversation in the canonical JSON format: the conversation's fields
+/// plus `format_version`, so consumers can tell which schema applies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ConversationExport {
+    /// Version of the format ([`FORMAT_VERSION`] when written)
+    pub format_version: u32,
+    #[serde(flatten)]
+    pub conversation: ParsedConversation,
+}
+
+impl ConversationExport {
+    pub fn new(conversation: ParsedConversation) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            conversation,
+        }
+    }
+}
+
 /// Trait for all parsers.
 /// A parser reads a raw file from the vault and produces a ParsedConversation.
 pub trait Parser: Sync {
@@ -126,60 +234,234 @@ pub trait Parser: Sync {
     fn can_parse(&self, raw_path: &Path) -> bool;
 }
 
+/// A parsed conversation and the raw file it was parsed from.
+pub struct ParsedFile {
+    pub path: PathBuf,
+    pub conversation: ParsedConversation,
+}
+
+/// Outcome of parsing the raw files of one source.
+#[derive(Default)]
+pub struct SourceParse {
+    /// Non-empty conversations, newest first
+    pub files: Vec<ParsedFile>,
+    /// Files that failed to parse
+    pub errors: Vec<(PathBuf, anyhow::Error)>,
+    /// Files skipped because `cache` reported them unchanged
+    pub skipped: usize,
+    /// Files yielding an empty conversation, whose earlier output is stale
+    pub empty: Vec<ParsedFile>,
+    /// Time spent parsing each file that was parsed
+    pub timings: Vec<(PathBuf, Duration)>,
+}
+
 /// Parse all raw files in a vault directory for a given source.
 /// Returns (successful_parses, errors).
 pub fn parse_vault_source(
     parser: &dyn Parser,
     vault_dir: &Path,
 ) -> (Vec<ParsedConversation>, Vec<(PathBuf, anyhow::Error)>) {
-    let source_dir = vault_dir.join(parser.source_name());
-    let mut conversations = Vec::new();
-    let mut errors = Vec::new();
+    let parsed =
+        parse_source_files(parser, vault_dir, None, &Operation::default()).unwrap_or_default();
+    let conversations = parsed.files.into_iter().map(|f| f.conversation).collect();
+    (conversations, parsed.errors)
+}
 
+/// Find a conversation by ID among the raw sessions in `sessions_dir`,
+/// parsing only the sessions of `source` when it is known.
+pub fn find_conversation(
+    sessions_dir: &Path,
+    id: &str,
+    source: Option<&str>,
+) -> Option<ParsedConversation> {
+    all_parsers()
+        .into_iter()
+        .filter(|parser| source.is_none_or(|s| s == parser.source_name()))
+        .find_map(|parser| {
+            let (conversations, _) = parse_vault_source(parser.as_ref(), sessions_dir);
+            conversations.into_iter().find(|conv| conv.id == id)
+        })
+}
+
+/// Parse one session of the vault by ID: its raw file as recorded in
+/// vault.db, or else (unrecorded, or stored under another name) the sessions
+/// of its source.
+pub fn load_session(vault_dir: &Path, id: &str) -> Result<Option<ParsedConversation>> {
+    let sessions_dir = vault_dir.join("sessions");
+    let Some(entry) = crate::storage::VaultDb::open(vault_dir)?.get_session(id)? else {
+        return Ok(find_conversation(&sessions_dir, id, None));
+    };
+    let raw = compressed::locate(&vault_dir.join(&entry.vault_path));
+    let parser = all_parsers()
+        .into_iter()
+        .find(|parser| parser.source_name() == entry.source);
+    if let Some(parser) =
+        parser.filter(|p| raw.is_file() && p.can_parse(&compressed::plain_path(&raw)))
+    {
+        match parse_raw_file(parser.as_ref(), &raw, &sessions_dir) {
+            Ok(conv) if conv.id == id => return Ok(Some(conv)),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("Cannot parse {:?} directly: {}", raw, e),
+        }
+    }
+    Ok(find_conversation(&sessions_dir, id, Some(&entry.source)))
+}
+
+/// File to open for one session of the vault by ID: its parsed Markdown, or
+/// else (not parsed yet) its raw file as recorded in vault.db, which may be
+/// compressed.
+pub fn session_file(vault_dir: &Path, id: &str) -> Result<Option<PathBuf>> {
+    let entry = crate::storage::VaultDb::open(vault_dir)?.get_session(id)?;
+    let parsed_dir = vault_dir.join("parsed");
+    let file_name = format!("{}.md", id);
+    let parsed = match &entry {
+        Some(entry) => Some(parsed_dir.join(&entry.source).join(&file_name)),
+        // Unrecorded (e.g. parsed on another machine): look in every source
+        None => std::fs::read_dir(&parsed_dir).ok().and_then(|dirs| {
+            dirs.flatten()
+                .map(|dir| dir.path().join(&file_name))
+                .find(|path| path.is_file())
+        }),
+    };
+    if let Some(parsed) = parsed.filter(|path| path.is_file()) {
+        return Ok(Some(parsed));
+    }
+    Ok(entry
+        .map(|entry| compressed::locate(&vault_dir.join(&entry.vault_path)))
+        .filter(|raw| raw.is_file()))
+}
+
+/// Files parsed together by [`parse_source_batches`]: enough to keep every
+/// core busy, few enough that a batch of conversations fits in memory.
+pub const PARSE_BATCH: usize = 256;
+
+/// Parse the raw files of a source, skipping those `cache` reports unchanged.
+///
+/// Files yielding an empty conversation are recorded in the cache right away;
+/// the caller records the others once it has processed them. Progress is
+/// reported per file, and a cancelled `op` stops the walk with
+/// [`Cancelled`](crate::progress::Cancelled).
+pub fn parse_source_files(
+    parser: &dyn Parser,
+    vault_dir: &Path,
+    cache: Option<&HashCache>,
+    op: &Operation,
+) -> Result<SourceParse> {
+    let mut result = SourceParse::default();
+    parse_source_batches(parser, vault_dir, cache, op, |batch| {
+        result.files.extend(batch.files);
+        result.errors.extend(batch.errors);
+        result.timings.extend(batch.timings);
+        result.skipped += batch.skipped;
+        Ok(())
+    })?;
+
+    // Sort by created_at (newest first)
+    result
+        .files
+        .sort_by_key(|file| std::cmp::Reverse(file.conversation.created_at));
+    Ok(result)
+}
+
+/// Parse the raw files of a source in parallel, handing each batch of
+/// [`PARSE_BATCH`] files to `handle` before parsing the next, so only one
+/// batch of conversations is held in memory.
+///
+/// Caching, progress and cancellation work as in [`parse_source_files`]; an
+/// error from `handle` stops the walk.
+pub fn parse_source_batches(
+    parser: &dyn Parser,
+    vault_dir: &Path,
+    cache: Option<&HashCache>,
+    op: &Operation,
+    mut handle: impl FnMut(SourceParse) -> Result<()>,
+) -> Result<()> {
+    let source_dir = vault_dir.join(parser.source_name());
     if !source_dir.exists() {
-        return (conversations, errors);
+        return Ok(());
     }
 
     // Walk the source directory for parseable files
-    let files = collect_files_recursive(&source_dir);
+    let files: Vec<PathBuf> = compressed::prefer_newer(collect_source_files(&source_dir))
+        .into_iter()
+        .filter(|path| parser.can_parse(&compressed::plain_path(path)))
+        .collect();
 
-    for file_path in files {
-        if !parser.can_parse(&file_path) {
-            continue;
-        }
+    op.start(parser.source_name(), files.len() as u64);
+    let result = files.chunks(PARSE_BATCH).try_for_each(|chunk| {
+        // Work stealing evens out batches mixing huge and tiny sessions;
+        // `None` marks a file skipped as unchanged
+        let outcomes: Vec<_> = chunk
+            .par_iter()
+            .filter_map(|file_path| {
+                if op.is_cancelled() {
+                    return None;
+                }
+                op.advance(1);
+                if cache.is_some_and(|c| c.is_unchanged(file_path)) {
+                    return Some((file_path, None));
+                }
+                let started = Instant::now();
+                let parsed = parse_raw_file(parser, file_path, vault_dir);
+                Some((file_path, Some((parsed, started.elapsed()))))
+            })
+            .collect();
+        op.check()?;
 
-        match parser.parse(&file_path) {
-            Ok(conv) => {
-                if !conv.is_empty() {
-                    conversations.push(conv);
+        let mut batch = SourceParse::default();
+        for (file_path, outcome) in outcomes {
+            let Some((parsed, elapsed)) = outcome else {
+                batch.skipped += 1;
+                continue;
+            };
+            batch.timings.push((file_path.clone(), elapsed));
+            match parsed {
+                Ok(conv) if !conv.is_empty() => batch.files.push(ParsedFile {
+                    path: file_path.clone(),
+                    conversation: conv,
+                }),
+                Ok(conv) => {
+                    if let Some(cache) = cache {
+                        cache.record(file_path);
+                    }
+                    batch.empty.push(ParsedFile {
+                        path: file_path.clone(),
+                        conversation: conv,
+                    });
                 }
-            }
-            Err(e) => {
-                errors.push((file_path, e));
+                Err(e) => batch.errors.push((file_path.clone(), e)),
             }
         }
-    }
-
-    // Sort by created_at (newest first)
-    conversations.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        handle(batch)
+    });
+    op.finish();
+    result
+}
 
-    (conversations, errors)
+/// Parse a raw session file under `sessions_dir`, decompressing it first
+/// when it is stored compressed, and tag the conversation (see
+/// [`crate::tags`]).
+pub fn parse_raw_file(
+    parser: &dyn Parser,
+    path: &Path,
+    sessions_dir: &Path,
+) -> Result<ParsedConversation> {
+    let plain = compressed::Plain::open(path, sessions_dir)?;
+    let mut conv = parser.parse(plain.path())?;
+    crate::tags::tag_conversation(&mut conv);
+    Ok(conv)
 }
 
-/// Recursively collect all files in a directory.
-fn collect_files_recursive(dir: &Path) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                files.extend(collect_files_recursive(&path));
-            } else if path.is_file() {
-                files.push(path);
-            }
-        }
+/// All files in a source directory, skipping chunk directories and files
+/// still being written.
+fn collect_source_files(dir: &Path) -> Vec<PathBuf> {
+    let mut walk = Walk::new(dir).prune(crate::storage::chunked::is_chunks_dir);
+    if let Ok(temp) =
+        build_ignore_set(&[INGEST_TEMP_GLOB.to_string(), atomic::TEMP_GLOB.to_string()])
+    {
+        walk = walk.exclude(temp);
     }
-    files
+    walk.files()
 }
 
 /// Create all parsers.
@@ -188,11 +470,16 @@ pub fn all_parsers() -> Vec<Box<dyn Parser>> {
         Box::new(vscode_copilot::VSCodeCopilotParser),
         Box::new(cursor::CursorParser),
         Box::new(cline::ClineParser),
+        Box::new(roo_code::RooCodeParser),
         Box::new(gemini_cli::GeminiCliParser),
         Box::new(claude_code::ClaudeCodeParser),
+        Box::new(claude_desktop::ClaudeDesktopParser),
+        Box::new(chatgpt::ChatGptParser),
+        Box::new(manual::ManualParser),
         Box::new(codex::CodexParser),
         Box::new(aider::AiderParser),
         Box::new(antigravity::AntigravityParser),
+        Box::new(windsurf::WindsurfParser),
         Box::new(continue_dev::ContinueDevParser),
         Box::new(opencode::OpenCodeParser),
         Box::new(zed::ZedParser),
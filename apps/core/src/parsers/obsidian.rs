@@ -0,0 +1,459 @@
+//! Obsidian mirror - Write parsed conversations as notes into an Obsidian vault.
+//!
+//! When `[obsidian] vault_path` is set, every conversation written to
+//! `parsed/` is also rendered through a note template and written to
+//! `<obsidian vault>/<folder>/<source>/<id>.md`, so conversations show up in
+//! the user's own notes (search, backlinks, graph) as they are parsed.
+//! `echovault-cli export obsidian` writes every conversation the same way.
+//!
+//! Each workspace gets an index note, `<folder>/workspaces/<workspace>.md`,
+//! listing its conversations; notes link back to it. Index entries are merged
+//! into the existing note, and notes or indexes that would not change are not
+//! rewritten, so re-exporting only touches what changed.
+//!
+//! Conversation tags become Obsidian tags in the frontmatter: characters
+//! Obsidian does not allow are replaced (`mode:agent` becomes `mode/agent`, a
+//! nested tag).
+//!
+//! Templates use TinyTemplate syntax (`{title}`, `{{ if model }}...{{ endif }}`,
+//! `{{ for m in messages }}...{{ endfor }}`). Values are inserted unescaped.
+//! Available fields:
+//!
+//! - `id`, `source`, `title`, `workspace`, `model`, `tags` (as Obsidian
+//!   tags), `message_count`
+//! - `created_at`, `updated_at` (RFC 3339) and `date` (`YYYY-MM-DD`, for
+//!   links to daily notes such as `[[{date}]]`)
+//! - `workspace_link` - link to the index note of the workspace
+//! - `frontmatter` - the YAML block of the `parsed/` Markdown, with Obsidian
+//!   tags
+//! - `body` - all messages rendered as `## Role` sections
+//! - `messages` - `role`, `tool_name`, `model`, `timestamp` and `content` of
+//!   each message
+
+use super::markdown_writer::{escape_yaml_string, render_frontmatter, render_messages};
+use super::ParsedConversation;
+use crate::config::Config;
+use crate::storage::atomic;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tinytemplate::TinyTemplate;
+
+/// Note template used when `[obsidian] template` is not set.
+pub const DEFAULT_TEMPLATE: &str = "{frontmatter}{{ if title }}# {title}
+
+{{ endif }}{{ if workspace_link }}Workspace: {workspace_link}
+
+{{ endif }}{body}";
+
+const TEMPLATE_NAME: &str = "note";
+
+/// Folder of the workspace index notes, inside the notes folder.
+const WORKSPACES_DIR: &str = "workspaces";
+
+/// Writes notes into the Obsidian vault configured in `[obsidian]`.
+pub struct ObsidianWriter {
+    /// Notes folder relative to the Obsidian vault (the root of links)
+    folder: String,
+    notes_dir: PathBuf,
+    template: String,
+}
+
+impl ObsidianWriter {
+    /// Writer for the configured Obsidian vault, or `None` when the mirror is
+    /// disabled. Fails if the custom template can't be read or compiled.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        match &config.obsidian.vault_path {
+            Some(vault) => Ok(Some(Self::for_vault(config, vault)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Writer for an Obsidian vault, with the folder and template of
+    /// `[obsidian]`.
+    pub fn for_vault(config: &Config, vault: &Path) -> Result<Self> {
+        let template = match &config.obsidian.template {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read Obsidian template {:?}", path))?,
+            None => DEFAULT_TEMPLATE.to_string(),
+        };
+        check_template(&template)?;
+        let folder = config.obsidian.folder.trim_matches('/').to_string();
+        Ok(Self {
+            notes_dir: vault.join(&folder),
+            folder,
+            template,
+        })
+    }
+
+    /// Path of the note of a conversation.
+    pub fn note_path(&self, conv: &ParsedConversation) -> PathBuf {
+        self.notes_dir
+            .join(&conv.source)
+            .join(format!("{}.md", conv.id))
+    }
+
+    /// Path of the index note of a workspace.
+    pub fn index_path(&self, workspace: &str) -> PathBuf {
+        self.notes_dir
+            .join(WORKSPACES_DIR)
+            .join(format!("{}.md", note_name(workspace)))
+    }
+
+    /// Render the note of a conversation.
+    pub fn render(&self, conv: &ParsedConversation) -> Result<String> {
+        let mut tt = TinyTemplate::new();
+        tt.set_default_formatter(&tinytemplate::format_unescaped);
+        tt.add_template(TEMPLATE_NAME, &self.template)?;
+        let workspace_link = conv.workspace.as_deref().map(|ws| {
+            format!(
+                "[[{}|{}]]",
+                self.link_target(&[WORKSPACES_DIR, &note_name(ws)]),
+                ws
+            )
+        });
+        let context = NoteContext::new(conv, workspace_link)?;
+        Ok(tt.render(TEMPLATE_NAME, &context)?)
+    }
+
+    /// Write the note of a conversation and add it to the index note of its
+    /// workspace. Returns false if the note already had this content, so
+    /// Obsidian doesn't see an unchanged note as edited.
+    pub fn write(&self, conv: &ParsedConversation) -> Result<bool> {
+        let content = self.render(conv)?;
+        let path = self.note_path(conv);
+        if let Some(workspace) = &conv.workspace {
+            self.add_to_index(workspace, std::slice::from_ref(conv))?;
+        }
+        if std::fs::read_to_string(&path).is_ok_and(|existing| existing == content) {
+            return Ok(false);
+        }
+        write_note(&path, &content)?;
+        Ok(true)
+    }
+
+    /// Merge conversations into the index note of a workspace. Entries already
+    /// listed are updated; the note is only written if it changes.
+    pub fn add_to_index(&self, workspace: &str, convs: &[ParsedConversation]) -> Result<bool> {
+        let path = self.index_path(workspace);
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut entries: BTreeMap<String, IndexEntry> = existing
+            .lines()
+            .filter_map(IndexEntry::parse)
+            .map(|entry| (entry.target.clone(), entry))
+            .collect();
+        for conv in convs {
+            let target = self.link_target(&[&conv.source, &conv.id]);
+            entries.insert(
+                target.clone(),
+                IndexEntry {
+                    date: conv.created_at.map(|t| t.format("%Y-%m-%d").to_string()),
+                    target,
+                    title: link_text(conv.title.as_deref().unwrap_or(&conv.id)),
+                },
+            );
+        }
+
+        let content = render_index(workspace, entries.into_values().collect());
+        if content == existing {
+            return Ok(false);
+        }
+        write_note(&path, &content)?;
+        Ok(true)
+    }
+
+    /// Link target (path from the vault root, without `.md`) of a note in
+    /// the notes folder.
+    fn link_target(&self, parts: &[&str]) -> String {
+        let mut target = self.folder.clone();
+        for part in parts {
+            if !target.is_empty() {
+                target.push('/');
+            }
+            target.push_str(part);
+        }
+        target
+    }
+}
+
+/// A conversation listed in a workspace index note:
+/// `- <date> [[<target>|<title>]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IndexEntry {
+    date: Option<String>,
+    target: String,
+    title: String,
+}
+
+impl IndexEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix("- ")?;
+        let (date, link) = match rest.split_once(" [[") {
+            Some((date, link)) => (Some(date.to_string()), link),
+            None => (None, rest.strip_prefix("[[")?),
+        };
+        let (target, title) = link.strip_suffix("]]")?.split_once('|')?;
+        Some(Self {
+            date,
+            target: target.to_string(),
+            title: title.to_string(),
+        })
+    }
+}
+
+/// Index note of a workspace, newest conversations first.
+fn render_index(workspace: &str, mut entries: Vec<IndexEntry>) -> String {
+    entries.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)));
+    let mut out = format!(
+        "---\ntype: echovault-workspace\nworkspace: \"{}\"\n---\n\n# {}\n\n",
+        escape_yaml_string(workspace),
+        workspace
+    );
+    for entry in &entries {
+        out.push_str("- ");
+        if let Some(date) = &entry.date {
+            out.push_str(date);
+            out.push(' ');
+        }
+        out.push_str(&format!("[[{}|{}]]\n", entry.target, entry.title));
+    }
+    out
+}
+
+/// File name of a note, without characters Obsidian links cannot contain.
+fn note_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '-',
+            c => c,
+        })
+        .collect();
+    match name.trim_matches(|c: char| c == '-' || c.is_whitespace()) {
+        "" => "untitled".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Text of a link, on one line and without the characters that end it.
+fn link_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(['[', ']', '|'], "")
+}
+
+/// Obsidian tag of a conversation tag, or `None` if nothing usable is left.
+/// Tags may only hold letters, digits, `_`, `-` and `/` (nesting), and not
+/// only digits.
+pub fn obsidian_tag(tag: &str) -> Option<String> {
+    let tag: String = tag
+        .trim()
+        .trim_start_matches('#')
+        .chars()
+        .map(|c| match c {
+            ':' | '.' => '/',
+            c if c.is_whitespace() => '-',
+            c => c,
+        })
+        .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+        .collect();
+    let tag = tag.trim_matches('/').to_string();
+    (!tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit())).then_some(tag)
+}
+
+/// Check that a note template compiles.
+pub fn check_template(template: &str) -> Result<()> {
+    let mut tt = TinyTemplate::new();
+    tt.add_template(TEMPLATE_NAME, template)
+        .context("Invalid Obsidian note template")?;
+    Ok(())
+}
+
+fn write_note(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic::write(path, content).with_context(|| format!("Failed to write note {:?}", path))
+}
+
+#[derive(Serialize)]
+struct NoteContext<'a> {
+    id: &'a str,
+    source: &'a str,
+    title: Option<&'a str>,
+    workspace: Option<&'a str>,
+    model: Option<&'a str>,
+    tags: Vec<String>,
+    message_count: usize,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    date: Option<String>,
+    workspace_link: Option<String>,
+    frontmatter: String,
+    body: String,
+    messages: Vec<NoteMessage<'a>>,
+}
+
+#[derive(Serialize)]
+struct NoteMessage<'a> {
+    role: String,
+    tool_name: Option<&'a str>,
+    model: Option<&'a str>,
+    timestamp: Option<String>,
+    content: &'a str,
+}
+
+impl<'a> NoteContext<'a> {
+    fn new(conv: &'a ParsedConversation, workspace_link: Option<String>) -> Result<Self> {
+        let messages = conv
+            .messages
+            .iter()
+            .map(|m| NoteMessage {
+                role: m.role.to_string(),
+                tool_name: m.tool_name.as_deref(),
+                model: m.model.as_deref(),
+                timestamp: m.timestamp.map(|t| t.to_rfc3339()),
+                content: m.content.trim(),
+            })
+            .collect();
+        let mut tags: Vec<String> = Vec::new();
+        for tag in conv.tags.iter().filter_map(|t| obsidian_tag(t)) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        // Same frontmatter as `parsed/`, with the tags Obsidian accepts
+        let frontmatter = render_frontmatter(conv)?
+            .split_inclusive('\n')
+            .filter_map(|line| match line.starts_with("tags: [") {
+                true if tags.is_empty() => None,
+                true => Some(format!("tags: [{}]\n", tags.join(", "))),
+                false => Some(line.to_string()),
+            })
+            .collect();
+        Ok(Self {
+            id: &conv.id,
+            source: &conv.source,
+            title: conv.title.as_deref(),
+            workspace: conv.workspace.as_deref(),
+            model: conv.model.as_deref(),
+            tags,
+            message_count: conv.messages.len(),
+            created_at: conv.created_at.map(|t| t.to_rfc3339()),
+            updated_at: conv.updated_at.map(|t| t.to_rfc3339()),
+            date: conv.created_at.map(|t| t.format("%Y-%m-%d").to_string()),
+            workspace_link,
+            frontmatter,
+            body: render_messages(conv)?,
+            messages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ParsedMessage, Role};
+    use chrono::{TimeZone, Utc};
+    use tempfile::TempDir;
+
+    fn conversation() -> ParsedConversation {
+        ParsedConversation {
+            id: "abc".to_string(),
+            source: "claude-code".to_string(),
+            title: Some("Sort <vectors> & slices".to_string()),
+            workspace: None,
+            created_at: Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap()),
+            updated_at: None,
+            model: None,
+            messages: vec![ParsedMessage {
+                role: Role::User,
+                content: "How do I sort?\n".to_string(),
+                timestamp: None,
+                tool_name: None,
+                model: None,
+            }],
+            tags: vec![],
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_mirror_renders_template_into_vault() -> Result<()> {
+        let temp = TempDir::new()?;
+        let template = temp.path().join("note.md");
+        std::fs::write(
+            &template,
+            "[[{date}]] {title}\n{{ for m in messages }}- {m.role}: {m.content}\n{{ endfor }}",
+        )?;
+        let mut config = Config::default();
+        config.obsidian.vault_path = Some(temp.path().join("notes"));
+        config.obsidian.template = Some(template);
+
+        let writer = ObsidianWriter::from_config(&config)?.expect("mirror enabled");
+        let conv = conversation();
+        assert!(writer.write(&conv)?);
+        assert!(!writer.write(&conv)?);
+
+        let path = temp.path().join("notes/EchoVault/claude-code/abc.md");
+        assert_eq!(writer.note_path(&conv), path);
+        assert_eq!(
+            std::fs::read_to_string(path)?,
+            "[[2024-01-15]] Sort <vectors> & slices\n- user: How do I sort?\n"
+        );
+
+        config.obsidian.template = None;
+        let default = ObsidianWriter::from_config(&config)?
+            .unwrap()
+            .render(&conv)?;
+        assert!(default.starts_with("---\nid: abc\n"));
+        assert!(default.contains("# Sort <vectors> & slices\n\n## User"));
+
+        assert!(check_template("{{ if title }}unclosed").is_err());
+        assert!(ObsidianWriter::from_config(&Config::default())?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_workspace_index_and_tags() -> Result<()> {
+        let temp = TempDir::new()?;
+        let mut config = Config::default();
+        config.obsidian.vault_path = Some(temp.path().to_path_buf());
+        let writer = ObsidianWriter::from_config(&config)?.unwrap();
+
+        let mut first = conversation();
+        first.workspace = Some("my/app".to_string());
+        first.tags = vec![
+            "mode:agent".to_string(),
+            "rust lang".to_string(),
+            "42".to_string(),
+        ];
+        assert!(writer.write(&first)?);
+        let note = std::fs::read_to_string(writer.note_path(&first))?;
+        assert!(note.contains("tags: [mode/agent, rust-lang]\n"));
+        assert!(note.contains("Workspace: [[EchoVault/workspaces/my-app|my/app]]\n"));
+
+        let mut second = conversation();
+        second.id = "def".to_string();
+        second.workspace = first.workspace.clone();
+        second.title = Some("Newer [draft]".to_string());
+        second.created_at = Some(Utc.with_ymd_and_hms(2024, 2, 1, 9, 0, 0).unwrap());
+        writer.write(&second)?;
+
+        let index = temp.path().join("EchoVault/workspaces/my-app.md");
+        assert_eq!(writer.index_path("my/app"), index);
+        let content = std::fs::read_to_string(&index)?;
+        assert!(content.ends_with(
+            "# my/app\n\n- 2024-02-01 [[EchoVault/claude-code/def|Newer draft]]\n\
+             - 2024-01-15 [[EchoVault/claude-code/abc|Sort <vectors> & slices]]\n"
+        ));
+        // Re-exporting listed conversations leaves the index untouched
+        assert!(!writer.add_to_index("my/app", &[first, second])?);
+
+        assert_eq!(obsidian_tag("#lang.rust"), Some("lang/rust".to_string()));
+        assert_eq!(obsidian_tag("2024"), None);
+        Ok(())
+    }
+}
@@ -14,7 +14,7 @@
 //! Message roles: "user", "assistant"
 //! The database stores timestamps as Unix epoch seconds.
 
-use super::{ParsedConversation, ParsedMessage, Parser, Role};
+use super::{ParsedConversation, ParsedMessage, Parser, Role, TokenCounts};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use std::path::Path;
@@ -215,6 +215,21 @@ impl Parser for OpenCodeParser {
                 })
             });
 
+            // Older databases may lack the token columns
+            let usage = db
+                .query_row(
+                    "SELECT prompt_tokens, completion_tokens FROM sessions WHERE id = ?",
+                    rusqlite::params![session_id],
+                    |row| {
+                        Ok(TokenCounts {
+                            input_tokens: row.get::<_, Option<i64>>(0)?.unwrap_or(0) as u64,
+                            output_tokens: row.get::<_, Option<i64>>(1)?.unwrap_or(0) as u64,
+                        })
+                    },
+                )
+                .ok()
+                .filter(|usage| usage.total() > 0);
+
             // Workspace from database filename
             let workspace = raw_path
                 .file_stem()
@@ -231,6 +246,7 @@ impl Parser for OpenCodeParser {
                 model,
                 messages,
                 tags: Vec::new(),
+                usage,
             });
         }
 
@@ -254,6 +270,7 @@ impl Parser for OpenCodeParser {
                 model: None,
                 messages: Vec::new(),
                 tags: Vec::new(),
+                usage: None,
             })
         } else {
             // Multiple sessions in one DB: merge into one ParsedConversation
@@ -294,6 +311,7 @@ impl Parser for OpenCodeParser {
                 model,
                 messages: merged_messages,
                 tags: Vec::new(),
+                usage: TokenCounts::sum(all_conversations.iter().map(|c| c.usage.as_ref())),
             })
         }
     }
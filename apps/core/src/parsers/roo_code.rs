@@ -0,0 +1,34 @@
+//! Roo Code Parser
+//!
+//! Roo Code is a Cline fork and keeps the same api_conversation_history.json
+//! format. This parser delegates to the Cline parser but tags output as
+//! "roo-code" source. Vault copies are named after the task ID.
+
+use super::{ParsedConversation, Parser};
+use anyhow::Result;
+use std::path::Path;
+
+/// Roo Code Parser (delegates to Cline parser format)
+pub struct RooCodeParser;
+
+impl Parser for RooCodeParser {
+    fn source_name(&self) -> &'static str {
+        "roo-code"
+    }
+
+    fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
+        let mut conv = super::cline::ClineParser.parse(raw_path)?;
+        conv.source = "roo-code".to_string();
+        // Vault copy is {task_id}.json; the original file sits in the task folder
+        if let Some(stem) = raw_path.file_stem().and_then(|s| s.to_str()) {
+            if stem != "api_conversation_history" {
+                conv.id = stem.to_string();
+            }
+        }
+        Ok(conv)
+    }
+
+    fn can_parse(&self, raw_path: &Path) -> bool {
+        raw_path.extension().is_some_and(|ext| ext == "json")
+    }
+}
@@ -0,0 +1,75 @@
+//! JSON Schema of the canonical conversation format.
+//!
+//! The schema of [`ConversationExport`] (and so of [`super::ParsedConversation`]
+//! and [`super::ParsedMessage`]) is generated from the Rust types and shipped
+//! as `schema/conversation.v{FORMAT_VERSION}.json`, so external tools can
+//! validate EchoVault data without depending on this crate. A test keeps the
+//! shipped file in sync with the types.
+
+use super::{ConversationExport, FORMAT_VERSION};
+
+/// JSON Schema of the canonical format, as shipped in `schema/`.
+pub fn conversation_schema() -> serde_json::Value {
+    let mut schema = schemars::schema_for!(ConversationExport);
+    schema.insert(
+        "title".to_string(),
+        format!("EchoVault conversation (format v{})", FORMAT_VERSION).into(),
+    );
+    schema.to_value()
+}
+
+/// File name of the shipped schema of the current format version.
+pub fn schema_file_name() -> String {
+    format!("conversation.v{}.json", FORMAT_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{ParsedConversation, ParsedMessage, Role};
+    use std::path::Path;
+
+    #[test]
+    fn test_shipped_schema_is_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("schema")
+            .join(schema_file_name());
+        let shipped: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            shipped,
+            conversation_schema(),
+            "{:?} is stale: regenerate it with `echovault-cli schema`",
+            path
+        );
+    }
+
+    #[test]
+    fn test_export_has_schema_fields() {
+        let export = ConversationExport::new(ParsedConversation {
+            id: "abc".to_string(),
+            source: "cursor".to_string(),
+            title: None,
+            workspace: None,
+            created_at: None,
+            updated_at: None,
+            model: None,
+            messages: vec![ParsedMessage {
+                role: Role::User,
+                content: "Hi".to_string(),
+                timestamp: None,
+                tool_name: None,
+                model: None,
+            }],
+            tags: vec![],
+            usage: None,
+        });
+        let value = serde_json::to_value(&export).unwrap();
+        let schema = conversation_schema();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in value.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "{} missing from schema", key);
+        }
+        assert_eq!(value["format_version"], FORMAT_VERSION);
+    }
+}
@@ -0,0 +1,304 @@
+//! Parse stage: write clean Markdown for the raw sessions of a vault.
+//!
+//! Shared by the CLI (`parse`, `watch --parse`) and the desktop app (parse
+//! and sync), so both skip, write, index and link sessions the same way.
+//! Each source's raw files are parsed in parallel batches (see
+//! [`super::parse_source_batches`]); a batch is written out before the next
+//! one is parsed.
+//!
+//! Parsed conversations are indexed for keyword search and their tags
+//! recorded in vault.db. Raw files whose hash changed are parsed again. Their
+//! Markdown is rewritten unless it already records the same `source_hash`
+//! (e.g. written by another machine), and removed when the session no longer
+//! holds any message.
+
+use super::markdown_writer::{self, parsed_source_hash};
+use super::obsidian::ObsidianWriter;
+use super::{all_parsers, parse_source_batches};
+use crate::config::{Config, EXPORT_DESTINATION};
+use crate::dedupe;
+use crate::progress::Operation;
+use crate::redact;
+use crate::storage::fts;
+use crate::storage::hash_cache::{self, HashCache};
+use crate::sync::delta;
+use crate::tags;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Slowest files kept in [`ParseResult::slowest`]
+const SLOWEST_FILES: usize = 5;
+
+/// Outcome of parsing one source.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceResult {
+    pub source: &'static str,
+    /// Conversations written
+    pub parsed: usize,
+    /// Files that failed to parse or write
+    pub errors: usize,
+    pub duration_ms: u64,
+}
+
+/// Time spent parsing one raw file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTiming {
+    pub path: PathBuf,
+    pub duration_ms: u64,
+}
+
+/// Outcome of [`parse_vault`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParseResult {
+    /// Conversations written
+    pub parsed: usize,
+    /// Files unchanged since they were last parsed
+    pub skipped: usize,
+    /// Stale Markdown removed (sessions edited down to no messages)
+    pub removed: usize,
+    /// Files that failed to parse or write
+    pub errors: usize,
+    /// Sessions linked as duplicates of another source's capture
+    pub duplicates: usize,
+    /// Obsidian notes written, when the Obsidian export is enabled
+    pub obsidian_notes: Option<usize>,
+    /// Per source, in registry order
+    pub sources: Vec<SourceResult>,
+    /// Files that took longest to parse, slowest first
+    pub slowest: Vec<FileTiming>,
+    pub duration_ms: u64,
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Whether parsed Markdown is up to date with its raw file: it records the
+/// raw file's hash, or (written before hashes were recorded) is newer.
+fn is_current(output_path: &Path, raw_path: &Path, source_hash: &str) -> bool {
+    match parsed_source_hash(output_path) {
+        Some(recorded) => recorded == source_hash,
+        None => {
+            let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            matches!(
+                (modified(output_path), modified(raw_path)),
+                (Some(output), Some(raw)) if output >= raw
+            )
+        }
+    }
+}
+
+/// Write Markdown (and Obsidian notes) for vault sessions changed since they
+/// were last parsed, index them for keyword search, then update duplicate
+/// links.
+///
+/// When `op` is cancelled, the Markdown written so far is kept and recorded
+/// for the next push, and [`Cancelled`](crate::progress::Cancelled) is
+/// returned.
+pub fn parse_vault(config: &Config, op: &Operation) -> Result<ParseResult> {
+    let started = Instant::now();
+    let vault_dir = &config.vault_path;
+    let sessions_dir = vault_dir.join("sessions");
+    let parsed_dir = vault_dir.join("parsed");
+    let obsidian = ObsidianWriter::from_config(config).unwrap_or_else(|e| {
+        tracing::warn!("Obsidian mirror disabled: {:#}", e);
+        None
+    });
+    let redact_export = config.redaction.applies_to(EXPORT_DESTINATION);
+    let mut result = ParseResult {
+        obsidian_notes: obsidian.as_ref().map(|_| 0),
+        ..Default::default()
+    };
+    if !sessions_dir.exists() {
+        return Ok(result);
+    }
+
+    let mut signatures = Vec::new();
+    // Markdown written, for the next delta push
+    let mut written = Vec::new();
+    let mut timings = Vec::new();
+    let cache = HashCache::load(vault_dir, hash_cache::PARSE);
+
+    let mut outcome = Ok(());
+    for parser in &all_parsers() {
+        if !sessions_dir.join(parser.source_name()).exists() {
+            continue;
+        }
+        let source_started = Instant::now();
+        let mut source = SourceResult {
+            source: parser.source_name(),
+            parsed: 0,
+            errors: 0,
+            duration_ms: 0,
+        };
+
+        outcome = parse_source_batches(parser.as_ref(), &sessions_dir, Some(&cache), op, |batch| {
+            result.skipped += batch.skipped;
+            source.errors += batch.errors.len();
+            for (path, err) in &batch.errors {
+                tracing::warn!("Error parsing {:?}: {}", path, err);
+            }
+            timings.extend(batch.timings);
+            signatures.extend(
+                batch
+                    .files
+                    .iter()
+                    .filter_map(|file| dedupe::signature(&file.conversation)),
+            );
+            fts::index_parsed(vault_dir, batch.files.iter().map(|file| &file.conversation));
+            tags::record(vault_dir, batch.files.iter().map(|file| &file.conversation));
+
+            for file in &batch.empty {
+                let conv = &file.conversation;
+                let output_path = parsed_dir
+                    .join(&conv.source)
+                    .join(format!("{}.md", conv.id));
+                if std::fs::remove_file(&output_path).is_ok() {
+                    result.removed += 1;
+                }
+            }
+
+            for file in &batch.files {
+                let conv = &file.conversation;
+                let output_path = parsed_dir
+                    .join(&conv.source)
+                    .join(format!("{}.md", conv.id));
+
+                if let (Some(obsidian), Some(notes)) = (&obsidian, result.obsidian_notes.as_mut()) {
+                    // The Obsidian vault is an export destination
+                    let redacted = redact_export.then(|| {
+                        let mut conv = conv.clone();
+                        redact::redact_conversation(&mut conv);
+                        conv
+                    });
+                    match obsidian.write(redacted.as_ref().unwrap_or(conv)) {
+                        Ok(written) => *notes += usize::from(written),
+                        Err(e) => {
+                            tracing::warn!("Error writing Obsidian note of {}: {}", conv.id, e)
+                        }
+                    }
+                }
+
+                // Skip if already parsed from the same raw content. Without a
+                // hash the Markdown could not tell an edit apart, so none is
+                // written and the file is parsed again next run.
+                let source_hash = match cache.hash(&file.path) {
+                    Ok(hash) => hash,
+                    Err(e) => {
+                        tracing::warn!("Cannot hash {:?}, not writing it: {}", file.path, e);
+                        source.errors += 1;
+                        continue;
+                    }
+                };
+                if output_path.exists() && is_current(&output_path, &file.path, &source_hash) {
+                    cache.record(&file.path);
+                    result.skipped += 1;
+                    continue;
+                }
+
+                match markdown_writer::write_parsed(conv, &output_path, &source_hash) {
+                    Ok(()) => {
+                        cache.record(&file.path);
+                        written.push(output_path);
+                        source.parsed += 1;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Error writing {:?}: {}", output_path, e);
+                        source.errors += 1;
+                    }
+                }
+            }
+            Ok(())
+        });
+
+        source.duration_ms = millis(source_started.elapsed());
+        result.parsed += source.parsed;
+        result.errors += source.errors;
+        result.sources.push(source);
+        if outcome.is_err() {
+            break;
+        }
+    }
+
+    // Keep the files written so far when interrupted
+    if let Err(e) = cache.save() {
+        tracing::warn!("Failed to save parse hashes: {}", e);
+    }
+    delta::mark_written(vault_dir, &written);
+    outcome?;
+
+    // Link captures of the same conversation by different sources
+    result.duplicates = match dedupe::update_duplicates(vault_dir, &signatures) {
+        Ok(count) => count,
+        Err(e) => {
+            tracing::warn!("Failed to update duplicate sessions: {}", e);
+            0
+        }
+    };
+
+    timings.sort_by_key(|(_, took)| std::cmp::Reverse(*took));
+    result.slowest = timings
+        .into_iter()
+        .take(SLOWEST_FILES)
+        .map(|(path, duration)| FileTiming {
+            path,
+            duration_ms: millis(duration),
+        })
+        .collect();
+    result.duration_ms = millis(started.elapsed());
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_vault_writes_and_skips() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = Config {
+            vault_path: temp.path().to_path_buf(),
+            ..Config::default()
+        };
+        let source_dir = temp.path().join("sessions/claude-code/-home-me-proj");
+        std::fs::create_dir_all(&source_dir)?;
+        for i in 0..3 {
+            std::fs::write(
+                source_dir.join(format!("s{}.jsonl", i)),
+                format!(
+                    "{{\"role\":\"user\",\"content\":\"Question {}\"}}\n\
+                     {{\"role\":\"assistant\",\"content\":\"Answer {}\"}}\n",
+                    i, i
+                ),
+            )?;
+        }
+
+        let result = parse_vault(&config, &Operation::default())?;
+        assert_eq!((result.parsed, result.errors), (3, 0));
+        assert_eq!(result.sources.len(), 1);
+        assert_eq!(result.slowest.len(), 3);
+        assert!(temp.path().join("parsed/claude-code/s0.md").exists());
+
+        let result = parse_vault(&config, &Operation::default())?;
+        assert_eq!((result.parsed, result.skipped), (0, 3));
+
+        // An edited session is parsed again; one left empty loses its Markdown
+        std::fs::write(
+            source_dir.join("s0.jsonl"),
+            "{\"role\":\"user\",\"content\":\"Edited question\"}\n",
+        )?;
+        std::fs::write(
+            source_dir.join("s1.jsonl"),
+            "{\"role\":\"system\",\"content\":\"Cleared\"}\n",
+        )?;
+        let result = parse_vault(&config, &Operation::default())?;
+        assert_eq!((result.parsed, result.removed, result.skipped), (1, 1, 1));
+        let parsed = temp.path().join("parsed/claude-code");
+        assert!(std::fs::read_to_string(parsed.join("s0.md"))?.contains("Edited question"));
+        assert!(!parsed.join("s1.md").exists());
+        Ok(())
+    }
+}
@@ -12,81 +12,111 @@
 //!    - kind=4: Confirmation
 //!    - kind=5: Follow-up
 
+use super::json_lines::JsonLines;
 use super::{ParsedConversation, ParsedMessage, Parser, Role};
+use crate::storage::mapped;
 use anyhow::{Context, Result};
 use chrono::{TimeZone, Utc};
+use serde::Deserialize;
 use serde_json::Value;
-use std::io::BufRead;
 use std::path::Path;
 
 /// VS Code Copilot Parser
 pub struct VSCodeCopilotParser;
 
+/// Fields of a legacy JSON session used by the parser (others are skipped unparsed).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSession {
+    session_id: Option<Value>,
+    custom_title: Option<Value>,
+    creation_date: Option<Value>,
+    #[serde(default)]
+    requests: Vec<JsonRequest>,
+}
+
+/// A request/response pair of a legacy JSON session.
+#[derive(Deserialize)]
+struct JsonRequest {
+    message: Option<Value>,
+    response: Option<Value>,
+}
+
+/// A line of a JSONL session: its kind and payload.
+#[derive(Deserialize)]
+struct JsonlLine {
+    kind: Option<Value>,
+    v: Option<Value>,
+}
+
 impl VSCodeCopilotParser {
-    /// Parse a legacy JSON format session file.
-    fn parse_json(&self, content: &str, file_stem: &str) -> Result<ParsedConversation> {
-        let json: Value =
-            serde_json::from_str(content).context("Invalid JSON in Copilot session")?;
+    /// Parse a legacy JSON format session file, mapped from the vault.
+    fn parse_json(&self, path: &Path, file_stem: &str) -> Result<ParsedConversation> {
+        let data = mapped::read(path).context("Cannot read JSON file")?;
+        let json: JsonSession =
+            serde_json::from_slice(&data).context("Invalid JSON in Copilot session")?;
 
         let session_id = json
-            .get("sessionId")
+            .session_id
+            .as_ref()
             .and_then(|v| v.as_str())
             .unwrap_or(file_stem)
             .to_string();
 
         let title = json
-            .get("customTitle")
+            .custom_title
+            .as_ref()
             .and_then(|v| v.as_str())
             .map(String::from);
 
         let created_at = json
-            .get("creationDate")
+            .creation_date
+            .as_ref()
             .and_then(|v| v.as_i64())
             .and_then(|ts| Utc.timestamp_millis_opt(ts).single());
 
         let mut messages = Vec::new();
 
         // Parse requests array
-        if let Some(requests) = json.get("requests").and_then(|v| v.as_array()) {
-            for request in requests {
-                // User message
-                if let Some(text) = request
-                    .get("message")
-                    .and_then(|m| m.get("text"))
-                    .and_then(|t| t.as_str())
-                {
+        for request in &json.requests {
+            // User message
+            if let Some(text) = request
+                .message
+                .as_ref()
+                .and_then(|m| m.get("text"))
+                .and_then(|t| t.as_str())
+            {
+                messages.push(ParsedMessage {
+                    role: Role::User,
+                    content: text.to_string(),
+                    timestamp: None,
+                    tool_name: None,
+                    model: None,
+                });
+            }
+
+            // Assistant response
+            if let Some(response) = &request.response {
+                let response_text = response
+                    .get("value")
+                    .and_then(|v| v.as_str())
+                    .or_else(|| response.get("message").and_then(|m| m.as_str()))
+                    .unwrap_or_default();
+
+                if !response_text.is_empty() {
+                    let model = response
+                        .get("model")
+                        .and_then(|v| v.as_str())
+                        .map(String::from);
+
                     messages.push(ParsedMessage {
-                        role: Role::User,
-                        content: text.to_string(),
+                        role: Role::Assistant,
+                        content: response_text.to_string(),
                         timestamp: None,
                         tool_name: None,
-                        model: None,
+                        model,
                     });
                 }
-
-                // Assistant response
-                if let Some(response) = request.get("response") {
-                    let response_text = response
-                        .get("value")
-                        .and_then(|v| v.as_str())
-                        .or_else(|| response.get("message").and_then(|m| m.as_str()))
-                        .unwrap_or_default();
-
-                    if !response_text.is_empty() {
-                        let model = response
-                            .get("model")
-                            .and_then(|v| v.as_str())
-                            .map(String::from);
-
-                        messages.push(ParsedMessage {
-                            role: Role::Assistant,
-                            content: response_text.to_string(),
-                            timestamp: None,
-                            tool_name: None,
-                            model,
-                        });
-                    }
-                }
             }
         }
 
@@ -107,37 +137,27 @@ impl VSCodeCopilotParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
     /// Parse a JSONL format session file.
     fn parse_jsonl(&self, path: &Path, file_stem: &str) -> Result<ParsedConversation> {
-        let file = std::fs::File::open(path).context("Cannot open JSONL file")?;
-        let reader = std::io::BufReader::new(file);
+        let lines = JsonLines::<_, JsonlLine>::open(path).context("Cannot open JSONL file")?;
 
         let mut session_id = file_stem.to_string();
         let mut title: Option<String> = None;
         let mut created_at = None;
         let mut messages = Vec::new();
 
-        for line in reader.lines() {
-            let line = line.context("Error reading JSONL line")?;
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-
-            let obj: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-
-            let kind = obj.get("kind").and_then(|v| v.as_i64()).unwrap_or(-1);
+        for line in lines {
+            let obj = line.context("Error reading JSONL line")?;
+            let kind = obj.kind.as_ref().and_then(|v| v.as_i64()).unwrap_or(-1);
 
             match kind {
                 0 => {
                     // Session header
-                    if let Some(v) = obj.get("v") {
+                    if let Some(v) = obj.v.as_ref() {
                         if let Some(id) = v.get("sessionId").and_then(|s| s.as_str()) {
                             session_id = id.to_string();
                         }
@@ -152,7 +172,7 @@ impl VSCodeCopilotParser {
                 }
                 1 => {
                     // User message
-                    if let Some(text) = obj.get("v").and_then(|v| v.as_str()) {
+                    if let Some(text) = obj.v.as_ref().and_then(|v| v.as_str()) {
                         if !text.trim().is_empty() {
                             messages.push(ParsedMessage {
                                 role: Role::User,
@@ -166,7 +186,7 @@ impl VSCodeCopilotParser {
                 }
                 2 => {
                     // Assistant response
-                    if let Some(text) = obj.get("v").and_then(|v| v.as_str()) {
+                    if let Some(text) = obj.v.as_ref().and_then(|v| v.as_str()) {
                         if !text.trim().is_empty() {
                             messages.push(ParsedMessage {
                                 role: Role::Assistant,
@@ -183,7 +203,7 @@ impl VSCodeCopilotParser {
                 }
                 4 => {
                     // Confirmation (tool approval) — add as info
-                    if let Some(v) = obj.get("v") {
+                    if let Some(v) = obj.v.as_ref() {
                         if let Some(text) = v.as_str() {
                             messages.push(ParsedMessage {
                                 role: Role::Info,
@@ -197,7 +217,7 @@ impl VSCodeCopilotParser {
                 }
                 5 => {
                     // Follow-up question — treat as assistant
-                    if let Some(text) = obj.get("v").and_then(|v| v.as_str()) {
+                    if let Some(text) = obj.v.as_ref().and_then(|v| v.as_str()) {
                         if !text.trim().is_empty() {
                             messages.push(ParsedMessage {
                                 role: Role::Assistant,
@@ -230,6 +250,7 @@ impl VSCodeCopilotParser {
             model: None,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 }
@@ -249,10 +270,7 @@ impl Parser for VSCodeCopilotParser {
 
         match ext {
             "jsonl" => self.parse_jsonl(raw_path, file_stem),
-            "json" => {
-                let content = std::fs::read_to_string(raw_path).context("Cannot read JSON file")?;
-                self.parse_json(&content, file_stem)
-            }
+            "json" => self.parse_json(raw_path, file_stem),
             _ => anyhow::bail!("Unsupported file extension: {}", ext),
         }
     }
@@ -0,0 +1,31 @@
+//! Windsurf Parser
+//!
+//! Windsurf Cascade conversations are protobuf files like Antigravity's (both
+//! come from the Codeium stack). This parser delegates to the Antigravity
+//! parser's best-effort text extraction but tags output as "windsurf" source.
+
+use super::{ParsedConversation, Parser};
+use anyhow::Result;
+use std::path::Path;
+
+/// Windsurf Parser (delegates to Antigravity .pb extraction)
+pub struct WindsurfParser;
+
+impl Parser for WindsurfParser {
+    fn source_name(&self) -> &'static str {
+        "windsurf"
+    }
+
+    fn parse(&self, raw_path: &Path) -> Result<ParsedConversation> {
+        let mut conv = super::antigravity::AntigravityParser.parse(raw_path)?;
+        conv.source = "windsurf".to_string();
+        if conv.title.as_deref() == Some("Antigravity Conversation") {
+            conv.title = Some("Cascade Conversation".to_string());
+        }
+        Ok(conv)
+    }
+
+    fn can_parse(&self, raw_path: &Path) -> bool {
+        raw_path.extension().is_some_and(|ext| ext == "pb")
+    }
+}
@@ -29,7 +29,7 @@
 //!   ```
 //!   Messages reference offsets into the `text` buffer.
 
-use super::{ParsedConversation, ParsedMessage, Parser, Role};
+use super::{ParsedConversation, ParsedMessage, Parser, Role, TokenCounts};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde_json::Value;
@@ -160,6 +160,9 @@ impl ZedParser {
                 model,
                 messages,
                 tags: Vec::new(),
+                usage: thread
+                    .get("cumulative_token_usage")
+                    .and_then(TokenCounts::from_json),
             });
         }
 
@@ -407,6 +410,7 @@ impl ZedParser {
             model,
             messages,
             tags: Vec::new(),
+            usage: None,
         })
     }
 
@@ -455,6 +459,7 @@ impl Parser for ZedParser {
                     model: None,
                     messages: Vec::new(),
                     tags: Vec::new(),
+                    usage: None,
                 });
             }
 
@@ -491,6 +496,7 @@ impl Parser for ZedParser {
                 model: None,
                 messages: merged_messages,
                 tags: Vec::new(),
+                usage: TokenCounts::sum(conversations.iter().map(|c| c.usage.as_ref())),
             })
         } else {
             // Text Threads: legacy JSON format
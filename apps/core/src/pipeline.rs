@@ -0,0 +1,289 @@
+//! Full pipeline: pull -> extract -> parse -> embed -> push in one run.
+//!
+//! Used by `echovault-cli sync --full-pipeline`, so a single scheduled
+//! command leaves the vault synced, parsed and searchable. Any stage can be
+//! skipped ([`PipelineOptions`]). A failed pull or embed is reported in
+//! [`PipelineSummary::warnings`] and the run goes on (the remote may be
+//! offline, the embedding API down); a failed extract, parse or push fails
+//! the run. The operation is checked between stages. Embedding follows the
+//! `[schedule]` deferral rules, since the pipeline usually runs unattended.
+
+use crate::config::{Config, DEFAULT_SYNC_TARGET};
+use crate::crypto::{staging, VaultKey};
+use crate::extractors::{enrich, ingest};
+use crate::parsers::vault_parse::{parse_vault, ParseResult};
+use crate::progress::{is_cancelled, Operation};
+use crate::redact;
+use crate::sync::{create_target_provider, delta, partition, SyncOptions};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Options of [`run`].
+#[derive(Debug, Clone)]
+pub struct PipelineOptions<'a> {
+    /// Sync target: [`DEFAULT_SYNC_TARGET`] or a `[sync.targets.<name>]` entry
+    pub target: String,
+    /// Key of an encrypted vault, which syncs through the staging directory
+    pub key: Option<&'a VaultKey>,
+    /// Push every file instead of only those changed since the last push
+    pub full_push: bool,
+    pub skip_pull: bool,
+    pub skip_extract: bool,
+    pub skip_parse: bool,
+    pub skip_embed: bool,
+    pub skip_push: bool,
+}
+
+impl Default for PipelineOptions<'_> {
+    /// Every stage, with the default sync target and no encryption.
+    fn default() -> Self {
+        Self {
+            target: DEFAULT_SYNC_TARGET.to_string(),
+            key: None,
+            full_push: false,
+            skip_pull: false,
+            skip_extract: false,
+            skip_parse: false,
+            skip_embed: false,
+            skip_push: false,
+        }
+    }
+}
+
+/// Outcome of the pull stage.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PullSummary {
+    pub new_files: usize,
+    pub updated_files: usize,
+    /// Files decrypted from the staging directory (encrypted vaults)
+    pub decrypted: usize,
+    /// Sessions from other machines recorded in vault.db
+    pub imported: usize,
+}
+
+/// Outcome of the extract stage.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExtractSummary {
+    /// Sessions found across sources
+    pub found: usize,
+    /// Sessions copied into the vault (new or changed)
+    pub copied: usize,
+    pub up_to_date: usize,
+    pub errors: usize,
+}
+
+/// Outcome of the embed stage.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EmbedSummary {
+    pub processed: usize,
+    pub chunks: usize,
+    /// Sessions already embedded
+    pub skipped: usize,
+    pub errors: usize,
+}
+
+/// Outcome of the push stage.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PushSummary {
+    /// Files encrypted into the staging directory (encrypted vaults)
+    pub encrypted: usize,
+    /// Secrets masked in the redacted copies (targets listed in
+    /// `[redaction] destinations`)
+    pub redacted: usize,
+    /// Files changed since the last push, or `None` for a full push
+    pub changed: Option<usize>,
+    pub pushed: usize,
+}
+
+/// Outcome of [`run`]. Stages skipped (or, for pull and embed, failed) are
+/// `None`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PipelineSummary {
+    pub pull: Option<PullSummary>,
+    pub extract: Option<ExtractSummary>,
+    pub parse: Option<ParseResult>,
+    pub embed: Option<EmbedSummary>,
+    pub push: Option<PushSummary>,
+    /// Stages that failed without failing the run
+    pub warnings: Vec<String>,
+    pub duration_ms: u64,
+}
+
+/// Run the stages of the pipeline not skipped by `options`, in order.
+///
+/// The sync target must be authenticated unless both pull and push are
+/// skipped. When `op` is cancelled, the stage in flight keeps what it wrote
+/// and [`Cancelled`](crate::progress::Cancelled) is returned.
+pub fn run(config: &Config, options: &PipelineOptions, op: &Operation) -> Result<PipelineSummary> {
+    let started = Instant::now();
+    let vault_dir = &config.vault_path;
+    let mut summary = PipelineSummary::default();
+
+    let provider = if options.skip_pull && options.skip_push {
+        None
+    } else {
+        let provider = create_target_provider(config, &options.target)?;
+        if !provider.is_authenticated() {
+            bail!(
+                "Sync target '{}' is not authenticated (run 'echovault-cli auth')",
+                options.target
+            );
+        }
+        Some(provider)
+    };
+    // Encrypted vaults sync through the staging directory
+    let sync_root = match options.key {
+        Some(_) => staging::staging_dir(vault_dir),
+        None => vault_dir.clone(),
+    };
+    // Sessions that workspace rules map to other targets go neither way
+    let mut sync_options = SyncOptions {
+        cancel: op.token().clone(),
+        exclude: partition::excluded(config, &options.target)?.paths,
+        skip_newer: options.key.is_some() || config.redaction.applies_to(&options.target),
+        ..SyncOptions::for_sync(&config.sync)
+    };
+
+    if let Some(provider) = provider.as_ref().filter(|_| !options.skip_pull) {
+        let mut pull = PullSummary::default();
+        match provider.pull(&sync_root, &sync_options) {
+            Ok(result) => {
+                pull.new_files = result.new_files;
+                pull.updated_files = result.updated_files;
+            }
+            Err(e) if is_cancelled(&e) => return Err(e),
+            Err(e) => summary.warnings.push(format!("Pull failed: {:#}", e)),
+        }
+        if let Some(key) = options.key {
+            pull.decrypted =
+                staging::open(vault_dir, key, config.encryption.allow_missing_manifest)?.decrypted;
+        }
+        pull.imported = ingest::import_vault_sessions(vault_dir)?;
+        summary.pull = Some(pull);
+    }
+
+    if !options.skip_extract {
+        op.check()?;
+        let report = ingest::ingest_sessions(vault_dir, config, op)?;
+        if report.needs_enrichment() {
+            if let Err(e) = enrich::enrich_metadata(vault_dir) {
+                tracing::warn!("Failed to enrich session metadata: {}", e);
+            }
+        }
+        summary.extract = Some(ExtractSummary {
+            found: report.found,
+            copied: report.copied,
+            up_to_date: report.up_to_date,
+            errors: report.errors.len(),
+        });
+    }
+
+    if !options.skip_parse {
+        op.check()?;
+        summary.parse = Some(parse_vault(config, op)?);
+    }
+
+    if !options.skip_embed {
+        op.check()?;
+        let deferred = crate::utils::power::defer_reason(&config.schedule);
+        if let Some(reason) = &deferred {
+            summary.warnings.push(format!("Embed deferred: {}", reason));
+        }
+        #[cfg(feature = "embedding")]
+        if deferred.is_none() {
+            let embedding = crate::embedding::EmbeddingConfig::from_config(config);
+            match crate::embedding::embed_vault(&embedding, vault_dir, op) {
+                Ok(result) => {
+                    summary.embed = Some(EmbedSummary {
+                        processed: result.sessions_processed,
+                        chunks: result.chunks_created,
+                        skipped: result.sessions_skipped,
+                        errors: result.errors.len(),
+                    })
+                }
+                Err(e) if is_cancelled(&e) => return Err(e),
+                Err(e) => summary.warnings.push(format!("Embed failed: {:#}", e)),
+            }
+        }
+        #[cfg(not(feature = "embedding"))]
+        summary
+            .warnings
+            .push("Embed skipped: built without the `embedding` feature".to_string());
+    }
+
+    if let Some(provider) = provider.as_ref().filter(|_| !options.skip_push) {
+        op.check()?;
+        let mut push = PushSummary::default();
+        if let Some(key) = options.key {
+            push.encrypted = staging::seal(vault_dir, key)?.encrypted;
+        }
+        // Extract may have added sessions of such workspaces
+        sync_options.exclude = partition::excluded(config, &options.target)?.paths;
+        let push_root =
+            match redact::vault::push_root(config, &options.target, options.key.is_some())? {
+                Some((root, staged)) => {
+                    push.redacted = staged.secrets;
+                    root
+                }
+                None => sync_root.clone(),
+            };
+        // With several targets, files pulled from one must reach the others,
+        // which only a full push (comparing the whole tree) guarantees
+        if !options.full_push && config.sync.targets.is_empty() {
+            sync_options.files = delta::push_files(vault_dir, &push_root, &options.target);
+        }
+        push.changed = sync_options.files.as_ref().map(Vec::len);
+        push.pushed = provider
+            .push(&push_root, &sync_options)
+            .context("Push failed")?
+            .files_pushed;
+        summary.push = Some(push);
+    }
+
+    summary.duration_ms = started.elapsed().as_millis().try_into().unwrap_or(u64::MAX);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_skips_stages() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = Config {
+            vault_path: temp.path().to_path_buf(),
+            ..Config::default()
+        };
+        let source_dir = temp.path().join("sessions/claude-code/-home-me-proj");
+        std::fs::create_dir_all(&source_dir)?;
+        std::fs::write(
+            source_dir.join("s0.jsonl"),
+            "{\"role\":\"user\",\"content\":\"Question\"}\n\
+             {\"role\":\"assistant\",\"content\":\"Answer\"}\n",
+        )?;
+
+        // Without pull and push, no sync target is needed
+        let options = PipelineOptions {
+            target: "missing".to_string(),
+            skip_pull: true,
+            skip_extract: true,
+            skip_embed: true,
+            skip_push: true,
+            ..Default::default()
+        };
+        let summary = run(&config, &options, &Operation::default())?;
+        assert!(summary.pull.is_none() && summary.extract.is_none() && summary.push.is_none());
+        assert_eq!(summary.parse.map(|p| p.parsed), Some(1));
+        assert!(summary.warnings.is_empty());
+
+        let options = PipelineOptions {
+            skip_pull: false,
+            ..options
+        };
+        assert!(run(&config, &options, &Operation::default()).is_err());
+        Ok(())
+    }
+}
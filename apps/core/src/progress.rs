@@ -0,0 +1,160 @@
+//! Progress reporting and cancellation of long-running operations.
+//!
+//! Ingest, parse, embed and sync run under an [`Operation`]: they report
+//! their progress to it and check its [`CancellationToken`] between units of
+//! work (a session copy, a raw file, an embedding window, an rclone
+//! transfer). A cancelled operation stops after the unit in flight and
+//! returns [`Cancelled`], so what it already wrote stays consistent. The CLI
+//! cancels on Ctrl+C and the desktop app from its UI, instead of killing the
+//! process mid-write.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Error returned by an operation that stopped because it was cancelled.
+#[derive(Debug, thiserror::Error)]
+#[error("Operation cancelled")]
+pub struct Cancelled;
+
+/// Whether an error (or its cause) is [`Cancelled`].
+pub fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Cancelled>().is_some()
+}
+
+/// Shared flag requesting that an operation stop. Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Return [`Cancelled`] if cancellation was requested.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The underlying flag, for signal handlers that set it.
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.0.clone()
+    }
+}
+
+/// Tokens are equal when they share a flag.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+/// Receives the progress of an operation, one stage at a time.
+pub trait Progress: Send + Sync {
+    /// A stage with `total` units of work started.
+    fn start(&self, _stage: &str, _total: u64) {}
+
+    /// `n` more units of the current stage are done.
+    fn advance(&self, _n: u64) {}
+
+    /// The current stage is over (complete or cancelled).
+    fn finish(&self) {}
+}
+
+/// Progress sink that ignores all updates.
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+/// Progress sink and cancellation token of a running operation.
+#[derive(Clone)]
+pub struct Operation {
+    cancel: CancellationToken,
+    progress: Arc<dyn Progress>,
+}
+
+impl Default for Operation {
+    /// An operation that reports nowhere and is never cancelled.
+    fn default() -> Self {
+        Self::new(CancellationToken::new(), Arc::new(NoProgress))
+    }
+}
+
+impl Operation {
+    pub fn new(cancel: CancellationToken, progress: Arc<dyn Progress>) -> Self {
+        Self { cancel, progress }
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Return [`Cancelled`] if cancellation was requested.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        self.cancel.check()
+    }
+
+    pub fn start(&self, stage: &str, total: u64) {
+        self.progress.start(stage, total);
+    }
+
+    pub fn advance(&self, n: u64) {
+        self.progress.advance(n);
+    }
+
+    pub fn finish(&self) {
+        self.progress.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+
+    #[derive(Default)]
+    struct Counter(AtomicU64);
+
+    impl Progress for Counter {
+        fn advance(&self, n: u64) {
+            self.0.fetch_add(n, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_cancel_is_shared_by_clones() {
+        let counter = Arc::new(Counter::default());
+        let op = Operation::new(CancellationToken::new(), counter.clone());
+        let token = op.token().clone();
+        assert_eq!(&token, op.token());
+        assert_ne!(token, CancellationToken::new());
+        op.advance(2);
+        op.advance(3);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 5);
+
+        assert!(op.check().is_ok());
+        token.cancel();
+        assert!(op.is_cancelled());
+        let err = anyhow::Error::from(op.check().unwrap_err()).context("Embedding failed");
+        assert!(is_cancelled(&err));
+        assert!(!is_cancelled(&anyhow::anyhow!("Rclone failed")));
+    }
+}
@@ -0,0 +1,171 @@
+//! Render module - Turn parsed conversations into documents to share.
+//!
+//! [`conversation_pdf`] lays out a conversation as a PDF for people who don't
+//! use the tools it came from: title and metadata, then each message under its
+//! role, with fenced code blocks set in a monospace font.
+
+pub mod pdf;
+
+use crate::parsers::{ParsedConversation, Role};
+use pdf::{Color, Font, PdfWriter, BLACK, GREY};
+
+const USER_COLOR: Color = (0.13, 0.35, 0.75);
+const ASSISTANT_COLOR: Color = (0.1, 0.5, 0.3);
+
+/// A run of message content.
+#[derive(Debug, PartialEq, Eq)]
+enum Block {
+    Text(String),
+    /// Body of a fenced code block (without the fences)
+    Code(String),
+}
+
+/// Render a conversation as a PDF document.
+pub fn conversation_pdf(conv: &ParsedConversation) -> Vec<u8> {
+    let title = conv.title.as_deref().unwrap_or(&conv.id);
+    let mut doc = PdfWriter::new(title);
+    doc.paragraph(title, Font::Bold, 18.0, BLACK);
+    doc.space(4.0);
+
+    let mut meta = vec![conv.source.clone()];
+    meta.extend(conv.workspace.clone());
+    meta.extend(conv.model.clone());
+    meta.extend(
+        conv.created_at
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()),
+    );
+    meta.push(format!("{} messages", conv.messages.len()));
+    doc.paragraph(&meta.join("  |  "), Font::Regular, 9.0, GREY);
+    if !conv.tags.is_empty() {
+        let tags = format!("Tags: {}", conv.tags.join(", "));
+        doc.paragraph(&tags, Font::Regular, 9.0, GREY);
+    }
+    doc.rule();
+
+    for message in &conv.messages {
+        let content = message.content.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let (label, color) = match message.role {
+            Role::User => ("User".to_string(), USER_COLOR),
+            Role::Assistant => ("Assistant".to_string(), ASSISTANT_COLOR),
+            Role::System => ("System".to_string(), GREY),
+            Role::Tool => match &message.tool_name {
+                Some(name) => (format!("Tool: {}", name), GREY),
+                None => ("Tool".to_string(), GREY),
+            },
+            Role::Info => ("Info".to_string(), GREY),
+        };
+        let header = match message.timestamp {
+            Some(t) => format!("{}  {}", label, t.format("%H:%M")),
+            None => label,
+        };
+        doc.paragraph(&header, Font::Bold, 11.0, color);
+        doc.space(2.0);
+
+        let body_font = match message.role {
+            Role::Tool | Role::Info => Font::Italic,
+            _ => Font::Regular,
+        };
+        for block in blocks(content) {
+            match block {
+                Block::Text(text) => doc.paragraph(&text, body_font, 10.0, BLACK),
+                Block::Code(code) => doc.preformatted(&code, 8.5),
+            }
+        }
+        doc.space(12.0);
+    }
+
+    doc.finish()
+}
+
+/// Split Markdown content into text and fenced code blocks. An unclosed fence
+/// runs to the end of the content.
+fn blocks(content: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut text = String::new();
+    let mut code: Option<String> = None;
+    for line in content.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut code, fence) {
+            (None, Some(_)) => {
+                if !text.trim().is_empty() {
+                    blocks.push(Block::Text(std::mem::take(&mut text)));
+                }
+                text.clear();
+                code = Some(String::new());
+            }
+            (Some(_), Some(_)) => {
+                if let Some(body) = code.take() {
+                    blocks.push(Block::Code(body));
+                }
+            }
+            (Some(body), None) => {
+                body.push_str(line);
+                body.push('\n');
+            }
+            (None, None) => {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+    }
+    if let Some(body) = code {
+        blocks.push(Block::Code(body));
+    }
+    if !text.trim().is_empty() {
+        blocks.push(Block::Text(text));
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ParsedMessage;
+
+    #[test]
+    fn test_blocks() {
+        let content = "Use this:\n```rust\nv.sort();\n```\nDone.";
+        assert_eq!(
+            blocks(content),
+            vec![
+                Block::Text("Use this:\n".to_string()),
+                Block::Code("v.sort();\n".to_string()),
+                Block::Text("Done.\n".to_string()),
+            ]
+        );
+        assert_eq!(
+            blocks("```\nunclosed"),
+            vec![Block::Code("unclosed\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_conversation_pdf() {
+        let conv = ParsedConversation {
+            id: "abc".to_string(),
+            source: "cursor".to_string(),
+            title: Some("Sort (vectors)".to_string()),
+            workspace: None,
+            created_at: None,
+            updated_at: None,
+            model: None,
+            messages: vec![ParsedMessage {
+                role: Role::User,
+                content: "How do I sort?".to_string(),
+                timestamp: None,
+                tool_name: None,
+                model: None,
+            }],
+            tags: vec![],
+            usage: None,
+        };
+        let pdf = conversation_pdf(&conv);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Title (Sort \\(vectors\\))"));
+        assert!(text.contains("/Count 1"));
+    }
+}
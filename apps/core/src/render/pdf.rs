@@ -0,0 +1,781 @@
+//! Minimal PDF writer: paginated, word-wrapped text on A4 pages.
+//!
+//! Text is set in DejaVu Sans and DejaVu Sans Mono, which are bundled and
+//! embedded (subset to the glyphs used), so Vietnamese, Greek, Cyrillic and
+//! common symbols render in every reader. Characters they lack, such as CJK,
+//! are looked up in fonts installed on the system (see [`FALLBACK_FONTS`]);
+//! only characters that no font covers are printed as `?`. Bold and italic
+//! are synthesized from the regular face (stroked and slanted).
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// A4, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+/// Spaces per tab in preformatted text
+const TAB_WIDTH: usize = 4;
+
+/// Slant of synthesized italics (horizontal shift per unit of height)
+const ITALIC_SKEW: f32 = 0.2;
+
+/// Outline width of synthesized bold, in 1/1000 of the font size
+const BOLD_STROKE: f32 = 30.0;
+
+/// RGB color, components in 0..=1.
+pub type Color = (f32, f32, f32);
+
+pub const BLACK: Color = (0.0, 0.0, 0.0);
+pub const GREY: Color = (0.4, 0.4, 0.4);
+
+/// Background of preformatted blocks
+const SHADE: Color = (0.94, 0.94, 0.94);
+
+const SANS_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const MONO_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+
+/// Indexes of the bundled fonts in [`PdfWriter::fonts`]
+const SANS: usize = 0;
+const MONO: usize = 1;
+
+/// System fonts tried, in order, for characters the bundled fonts lack:
+/// path and face index (for font collections).
+#[cfg(target_os = "linux")]
+pub const FALLBACK_FONTS: &[(&str, u32)] = &[
+    ("/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc", 0),
+    ("/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc", 0),
+    (
+        "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+        0,
+    ),
+    ("/usr/share/fonts/truetype/wqy/wqy-microhei.ttc", 0),
+    (
+        "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+        0,
+    ),
+    (
+        "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+        0,
+    ),
+];
+#[cfg(target_os = "macos")]
+pub const FALLBACK_FONTS: &[(&str, u32)] = &[
+    ("/System/Library/Fonts/Supplemental/Arial Unicode.ttf", 0),
+    ("/Library/Fonts/Arial Unicode.ttf", 0),
+    ("/System/Library/Fonts/Hiragino Sans GB.ttc", 0),
+    ("/System/Library/Fonts/AppleSDGothicNeo.ttc", 0),
+];
+#[cfg(windows)]
+pub const FALLBACK_FONTS: &[(&str, u32)] = &[
+    ("C:\\Windows\\Fonts\\msyh.ttc", 0),
+    ("C:\\Windows\\Fonts\\msgothic.ttc", 0),
+    ("C:\\Windows\\Fonts\\malgun.ttf", 0),
+    ("C:\\Windows\\Fonts\\seguisym.ttf", 0),
+];
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub const FALLBACK_FONTS: &[(&str, u32)] = &[];
+
+/// Text styles of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+    Italic,
+    Mono,
+}
+
+/// A character resolved to a glyph of one of the document's fonts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Glyph {
+    /// Index into [`PdfWriter::fonts`]
+    font: usize,
+    id: u16,
+    ch: char,
+    /// Advance, in 1/1000 of the font size
+    width: f32,
+}
+
+/// Total advance of `glyphs` at `size`, in points.
+fn advance(glyphs: &[Glyph], size: f32) -> f32 {
+    glyphs.iter().map(|g| g.width).sum::<f32>() * size / 1000.0
+}
+
+/// A TrueType/OpenType face embedded in the document.
+struct EmbeddedFont {
+    data: Cow<'static, [u8]>,
+    /// Face index within a font collection
+    index: u32,
+    /// Glyph id and advance (font units) of characters looked up so far
+    lookups: HashMap<char, Option<(u16, u16)>>,
+    /// Glyphs drawn, with the character each stands for
+    used: BTreeMap<u16, (char, u16)>,
+}
+
+impl EmbeddedFont {
+    fn parse(data: Cow<'static, [u8]>, index: u32) -> Option<Self> {
+        ttf_parser::Face::parse(&data, index).ok()?;
+        Some(Self {
+            data,
+            index,
+            lookups: HashMap::new(),
+            used: BTreeMap::new(),
+        })
+    }
+
+    fn load(path: &Path, index: u32) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        let font = Self::parse(Cow::Owned(data), index);
+        if font.is_none() {
+            debug!("[pdf] Cannot parse font {}", path.display());
+        }
+        font
+    }
+
+    fn face(&self) -> ttf_parser::Face<'_> {
+        ttf_parser::Face::parse(&self.data, self.index).expect("parsed when loaded")
+    }
+
+    /// Glyph of `ch` with its advance in 1/1000 of the font size, recording
+    /// it as used.
+    fn glyph(&mut self, ch: char) -> Option<(u16, f32)> {
+        let (id, advance) = match self.lookups.get(&ch) {
+            Some(lookup) => (*lookup)?,
+            None => {
+                let face = self.face();
+                let lookup = face
+                    .glyph_index(ch)
+                    .filter(|id| id.0 != 0)
+                    .map(|id| (id.0, face.glyph_hor_advance(id).unwrap_or(0)));
+                self.lookups.insert(ch, lookup);
+                lookup?
+            }
+        };
+        self.used.entry(id).or_insert((ch, advance));
+        Some((id, advance as f32 * 1000.0 / self.units_per_em()))
+    }
+
+    fn units_per_em(&self) -> f32 {
+        self.face().units_per_em() as f32
+    }
+
+    /// Objects of the font (Type0 font first), numbered from `first`.
+    fn objects(&self, first: usize) -> Vec<Vec<u8>> {
+        let face = self.face();
+        let scale = 1000.0 / face.units_per_em() as f32;
+        let cff = face.tables().cff.is_some();
+        let name = format!("{}+{}", self.subset_tag(), self.postscript_name());
+
+        let mut glyphs: Vec<u16> = vec![0];
+        glyphs.extend(self.used.keys());
+        let file = subsetter::subset(&self.data, self.index, subsetter::Profile::pdf(&glyphs))
+            .unwrap_or_else(|e| {
+                warn!(
+                    "[pdf] Cannot subset font {}, embedding all of it: {}",
+                    name, e
+                );
+                self.data.to_vec()
+            });
+
+        let widths: Vec<String> = self
+            .used
+            .iter()
+            .map(|(id, (_, advance))| format!("{} [{}]", id, (*advance as f32 * scale).round()))
+            .collect();
+        let bbox = face.global_bounding_box();
+        let mut flags = 4; // symbolic
+        if face.is_monospaced() {
+            flags |= 1;
+        }
+
+        let type0 = format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+             /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+            name,
+            first + 1,
+            first + 4
+        );
+        let cid_font = format!(
+            "<< /Type /Font /Subtype /{} /BaseFont /{} \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+             /FontDescriptor {} 0 R /W [{}]{} >>",
+            if cff { "CIDFontType0" } else { "CIDFontType2" },
+            name,
+            first + 2,
+            widths.join(" "),
+            if cff { "" } else { " /CIDToGIDMap /Identity" }
+        );
+        let descriptor = format!(
+            "<< /Type /FontDescriptor /FontName /{} /Flags {} /FontBBox [{} {} {} {}] \
+             /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /{} {} 0 R >>",
+            name,
+            flags,
+            (bbox.x_min as f32 * scale).round(),
+            (bbox.y_min as f32 * scale).round(),
+            (bbox.x_max as f32 * scale).round(),
+            (bbox.y_max as f32 * scale).round(),
+            (face.ascender() as f32 * scale).round(),
+            (face.descender() as f32 * scale).round(),
+            (face.capital_height().unwrap_or(face.ascender()) as f32 * scale).round(),
+            if cff { "FontFile3" } else { "FontFile2" },
+            first + 3
+        );
+        let file_entries = if cff {
+            "/Subtype /OpenType".to_string()
+        } else {
+            format!("/Length1 {}", file.len())
+        };
+
+        vec![
+            type0.into_bytes(),
+            cid_font.into_bytes(),
+            descriptor.into_bytes(),
+            stream(&file_entries, &file),
+            stream("", self.to_unicode().as_bytes()),
+        ]
+    }
+
+    /// CMap from glyph ids back to text, for copying and searching.
+    fn to_unicode(&self) -> String {
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        let used: Vec<_> = self.used.iter().collect();
+        // At most 100 entries per block
+        for block in used.chunks(100) {
+            let _ = writeln!(cmap, "{} beginbfchar", block.len());
+            for (id, (ch, _)) in block {
+                let utf16: String = ch
+                    .encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("{:04X}", unit))
+                    .collect();
+                let _ = writeln!(cmap, "<{:04X}> <{}>", id, utf16);
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        cmap
+    }
+
+    /// PostScript name of the face, reduced to characters allowed in a PDF name.
+    fn postscript_name(&self) -> String {
+        let name = self
+            .face()
+            .names()
+            .into_iter()
+            .filter(|n| n.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            // Only Unicode records decode
+            .find_map(|n| n.to_string())
+            .unwrap_or_default();
+        let name: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect();
+        if name.is_empty() {
+            "Font".to_string()
+        } else {
+            name
+        }
+    }
+
+    /// Six capital letters marking the font as a subset, derived from the
+    /// glyphs it keeps (so the same text gives the same file).
+    fn subset_tag(&self) -> String {
+        let mut hash: u32 = 0x811c_9dc5;
+        for id in self.used.keys() {
+            for byte in id.to_be_bytes() {
+                hash = (hash ^ byte as u32).wrapping_mul(0x0100_0193);
+            }
+        }
+        (0..6)
+            .map(|i| (b'A' + ((hash >> (i * 5)) % 26) as u8) as char)
+            .collect()
+    }
+}
+
+/// Lays out text top to bottom, starting a new page when one is full.
+pub struct PdfWriter {
+    title: String,
+    pages: Vec<String>,
+    /// Content stream of the page being filled
+    page: String,
+    /// Top of the free space on the page
+    y: f32,
+    /// Bundled fonts, then the system fallbacks loaded so far
+    fonts: Vec<EmbeddedFont>,
+    /// System fallbacks not tried yet
+    fallbacks: std::slice::Iter<'static, (&'static str, u32)>,
+}
+
+impl PdfWriter {
+    /// Empty document; `title` goes into the document properties.
+    pub fn new(title: &str) -> Self {
+        let bundled = |data| EmbeddedFont::parse(Cow::Borrowed(data), 0).expect("bundled font");
+        Self {
+            title: title.to_string(),
+            pages: Vec::new(),
+            page: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            fonts: vec![bundled(SANS_FONT), bundled(MONO_FONT)],
+            fallbacks: FALLBACK_FONTS.iter(),
+        }
+    }
+
+    /// Width available to text.
+    pub fn text_width() -> f32 {
+        PAGE_WIDTH - 2.0 * MARGIN
+    }
+
+    /// Text wrapped at word boundaries to the page width. Line breaks in
+    /// `text` are kept.
+    pub fn paragraph(&mut self, text: &str, font: Font, size: f32, color: Color) {
+        let leading = size * 1.4;
+        for line in text.lines() {
+            let glyphs = self.shape(line, font);
+            let wrapped = wrap(&glyphs, size, Self::text_width());
+            if wrapped.is_empty() {
+                self.space(leading);
+            }
+            for row in wrapped {
+                self.line(&row, font, size, leading, color, None);
+            }
+        }
+    }
+
+    /// Preformatted text in the monospace font on a shaded background:
+    /// lines are kept as they are, and only broken when too long.
+    pub fn preformatted(&mut self, text: &str, size: f32) {
+        let leading = size * 1.35;
+        let padding = 4.0;
+        let width = Self::text_width() - 2.0 * padding;
+
+        self.space(padding / 2.0);
+        for line in text.lines() {
+            let glyphs = self.shape(&line.replace('\t', &" ".repeat(TAB_WIDTH)), Font::Mono);
+            let rows = if glyphs.is_empty() {
+                vec![Vec::new()]
+            } else {
+                break_at(&glyphs, size, width)
+            };
+            for row in rows {
+                self.line(&row, Font::Mono, size, leading, BLACK, Some(padding));
+            }
+        }
+        self.space(padding / 2.0);
+    }
+
+    /// Vertical space, in points.
+    pub fn space(&mut self, points: f32) {
+        if self.y - points < MARGIN {
+            self.new_page();
+        } else {
+            self.y -= points;
+        }
+    }
+
+    /// Thin horizontal line across the text width.
+    pub fn rule(&mut self) {
+        self.space(6.0);
+        let _ = writeln!(
+            self.page,
+            "0.8 0.8 0.8 RG 0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+            MARGIN,
+            self.y,
+            PAGE_WIDTH - MARGIN,
+            self.y
+        );
+        self.space(10.0);
+    }
+
+    /// Finish the document: number the pages and serialize it.
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.page.is_empty() || self.pages.is_empty() {
+            self.pages.push(std::mem::take(&mut self.page));
+        }
+        let total = self.pages.len();
+        for i in 0..total {
+            let footer = self.shape(&format!("{} / {}", i + 1, total), Font::Regular);
+            let x = (PAGE_WIDTH - advance(&footer, 8.0)) / 2.0;
+            let mut content = std::mem::take(&mut self.pages[i]);
+            let _ = writeln!(
+                content,
+                "BT 0 Tr {} {} {} rg {:.2} {:.2} Td {} ET",
+                GREY.0,
+                GREY.1,
+                GREY.2,
+                x,
+                MARGIN / 2.0,
+                show_text(&footer, 8.0)
+            );
+            self.pages[i] = content;
+        }
+
+        // 1: catalog, 2: page tree, 3: document info, then fonts and pages
+        let mut objects: Vec<Vec<u8>> = vec![Vec::new(), Vec::new()];
+        let mut info = b"<< /Title ".to_vec();
+        info.extend(text_string(&self.title));
+        info.extend(b" /Producer (EchoVault) >>");
+        objects.push(info);
+
+        let mut fonts = Vec::new();
+        for (i, font) in self.fonts.iter().enumerate() {
+            if font.used.is_empty() {
+                continue;
+            }
+            fonts.push(format!("/F{} {} 0 R", i + 1, objects.len() + 1));
+            let first = objects.len() + 1;
+            objects.extend(font.objects(first));
+        }
+
+        let mut kids = Vec::with_capacity(total);
+        for content in &self.pages {
+            let id = objects.len() + 1;
+            kids.push(format!("{} 0 R", id));
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << {} >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    fonts.join(" "),
+                    id + 1
+                )
+                .into_bytes(),
+            );
+            objects.push(stream("", content.as_bytes()));
+        }
+        objects[0] = b"<< /Type /Catalog /Pages 2 0 R >>".to_vec();
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            total
+        )
+        .into_bytes();
+
+        // OpenType (CFF) font files need PDF 1.6
+        let cff = self
+            .fonts
+            .iter()
+            .any(|f| !f.used.is_empty() && f.face().tables().cff.is_some());
+        let version = if cff { "1.6" } else { "1.4" };
+        let mut out = format!("%PDF-{}\n", version).into_bytes();
+        out.extend(b"%\xE2\xE3\xCF\xD3\n");
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend(object);
+            out.extend(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        out
+    }
+
+    /// Glyphs of `text` in the fonts of `style`, falling back to the other
+    /// bundled font and then to system fonts; `?` where none has a glyph.
+    fn shape(&mut self, text: &str, style: Font) -> Vec<Glyph> {
+        text.chars()
+            .filter(|c| !c.is_control() || *c == '\t')
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .map(|ch| {
+                self.glyph(ch, style)
+                    .or_else(|| self.glyph('?', style))
+                    .expect("bundled fonts have '?'")
+            })
+            .collect()
+    }
+
+    fn glyph(&mut self, ch: char, style: Font) -> Option<Glyph> {
+        let preferred = match style {
+            Font::Mono => [MONO, SANS],
+            _ => [SANS, MONO],
+        };
+        let order: Vec<usize> = preferred.into_iter().chain(2..self.fonts.len()).collect();
+        for font in order {
+            if let Some((id, width)) = self.fonts[font].glyph(ch) {
+                return Some(Glyph {
+                    font,
+                    id,
+                    ch,
+                    width,
+                });
+            }
+        }
+        // Load system fonts one at a time, until one has the character
+        for (path, index) in self.fallbacks.by_ref() {
+            let Some(mut fallback) = EmbeddedFont::load(Path::new(path), *index) else {
+                continue;
+            };
+            debug!("[pdf] Using fallback font {}", path);
+            let found = fallback.glyph(ch);
+            self.fonts.push(fallback);
+            if let Some((id, width)) = found {
+                let font = self.fonts.len() - 1;
+                return Some(Glyph {
+                    font,
+                    id,
+                    ch,
+                    width,
+                });
+            }
+        }
+        None
+    }
+
+    /// One line of glyphs, optionally on a shaded background inset by
+    /// `shade` points.
+    fn line(
+        &mut self,
+        glyphs: &[Glyph],
+        style: Font,
+        size: f32,
+        leading: f32,
+        color: Color,
+        shade: Option<f32>,
+    ) {
+        if self.y - leading < MARGIN {
+            self.new_page();
+        }
+        self.y -= leading;
+        let mut x = MARGIN;
+        if let Some(padding) = shade {
+            let _ = writeln!(
+                self.page,
+                "{} {} {} rg {:.2} {:.2} {:.2} {:.2} re f",
+                SHADE.0,
+                SHADE.1,
+                SHADE.2,
+                MARGIN,
+                self.y,
+                Self::text_width(),
+                leading
+            );
+            x += padding;
+        }
+        if glyphs.is_empty() {
+            return;
+        }
+        let baseline = self.y + (leading - size) / 2.0 + size * 0.22;
+        let (r, g, b) = color;
+        let _ = write!(self.page, "BT {} {} {} rg ", r, g, b);
+        if style == Font::Bold {
+            let _ = write!(
+                self.page,
+                "2 Tr {} {} {} RG {:.2} w ",
+                r,
+                g,
+                b,
+                BOLD_STROKE * size / 1000.0
+            );
+        } else {
+            self.page.push_str("0 Tr ");
+        }
+        let skew = if style == Font::Italic {
+            ITALIC_SKEW
+        } else {
+            0.0
+        };
+        let _ = write!(self.page, "1 0 {} 1 {:.2} {:.2} Tm ", skew, x, baseline);
+        self.page.push_str(&show_text(glyphs, size));
+        self.page.push_str(" ET\n");
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.page));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+}
+
+/// Text-showing operators for `glyphs`: one `Tj` per run of the same font.
+fn show_text(glyphs: &[Glyph], size: f32) -> String {
+    let mut out = String::new();
+    for run in glyphs.chunk_by(|a, b| a.font == b.font) {
+        let _ = write!(out, "/F{} {} Tf <", run[0].font + 1, size);
+        for glyph in run {
+            let _ = write!(out, "{:04X}", glyph.id);
+        }
+        out.push_str("> Tj ");
+    }
+    out.pop();
+    out
+}
+
+/// Greedy word wrap; words wider than a line are broken.
+fn wrap(glyphs: &[Glyph], size: f32, width: f32) -> Vec<Vec<Glyph>> {
+    let space = glyphs.iter().find(|g| g.ch == ' ').copied();
+    let space_width = space.map_or(0.0, |g| advance(&[g], size));
+    let mut lines = Vec::new();
+    let mut line: Vec<Glyph> = Vec::new();
+    for word in glyphs.split(|g| g.ch == ' ').filter(|w| !w.is_empty()) {
+        let candidate_width = if line.is_empty() {
+            advance(word, size)
+        } else {
+            advance(&line, size) + space_width + advance(word, size)
+        };
+        if candidate_width <= width {
+            if let (false, Some(space)) = (line.is_empty(), space) {
+                line.push(space);
+            }
+            line.extend_from_slice(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Break words that don't fit on a line of their own
+        let mut rows = break_at(word, size, width);
+        line = rows.pop().unwrap_or_default();
+        lines.extend(rows);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Split `glyphs` into rows no wider than `width` (at least one glyph each).
+fn break_at(glyphs: &[Glyph], size: f32, width: f32) -> Vec<Vec<Glyph>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<Glyph> = Vec::new();
+    let mut row_width = 0.0;
+    for glyph in glyphs {
+        let glyph_width = glyph.width * size / 1000.0;
+        if !row.is_empty() && row_width + glyph_width > width {
+            rows.push(std::mem::take(&mut row));
+            row_width = 0.0;
+        }
+        row.push(*glyph);
+        row_width += glyph_width;
+    }
+    if !row.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+/// PDF text string: a literal for ASCII text (escaping delimiters), UTF-16BE
+/// with a byte order mark otherwise.
+fn text_string(text: &str) -> Vec<u8> {
+    if !text.chars().all(|c| (' '..='~').contains(&c)) {
+        let hex: String = text.encode_utf16().map(|u| format!("{:04X}", u)).collect();
+        return format!("<FEFF{}>", hex).into_bytes();
+    }
+    let mut out = Vec::with_capacity(text.len() + 2);
+    out.push(b'(');
+    for byte in text.bytes() {
+        if matches!(byte, b'(' | b')' | b'\\') {
+            out.push(b'\\');
+        }
+        out.push(byte);
+    }
+    out.push(b')');
+    out
+}
+
+/// Flate-compressed stream object, with extra dictionary `entries`.
+fn stream(entries: &str, content: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing into a Vec cannot fail
+    let _ = encoder.write_all(content);
+    let data = encoder.finish().unwrap_or_default();
+    let separator = if entries.is_empty() { "" } else { " " };
+    let mut out = format!(
+        "<< /Length {} /Filter /FlateDecode{}{} >>\nstream\n",
+        data.len(),
+        separator,
+        entries
+    )
+    .into_bytes();
+    out.extend(data);
+    out.extend(b"\nendstream");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_shape() {
+        let mut doc = PdfWriter::new("Title");
+        // DejaVu Sans Mono: 602/1000 em per character, ~6pt at 10pt
+        let glyphs = doc.shape("aaa bbb ccc", Font::Mono);
+        let text = |lines: Vec<Vec<Glyph>>| -> Vec<String> {
+            lines
+                .iter()
+                .map(|l| l.iter().map(|g| g.ch).collect())
+                .collect()
+        };
+        assert_eq!(text(wrap(&glyphs, 10.0, 40.0)), vec!["aaa", "bbb", "ccc"]);
+        assert_eq!(text(wrap(&glyphs, 10.0, 50.0)), vec!["aaa bbb", "ccc"]);
+        // A 10-character word breaks into 6 + 4
+        let glyphs = doc.shape("abcdefghij", Font::Mono);
+        assert_eq!(text(wrap(&glyphs, 10.0, 37.0)), vec!["abcdef", "ghij"]);
+
+        // Vietnamese and symbols come from the bundled fonts
+        let glyphs = doc.shape("Tiếng Việt – ✓", Font::Regular);
+        assert!(glyphs.iter().all(|g| g.font == SANS && g.ch != '?'));
+        // Unassigned code points have no glyph anywhere
+        let glyphs = doc.shape("\u{10fffd}", Font::Regular);
+        assert_eq!(glyphs[0].ch, '?');
+
+        assert_eq!(text_string("(a)\\"), b"(\\(a\\)\\\\)");
+        assert_eq!(text_string("é"), b"<FEFF00E9>");
+    }
+
+    #[test]
+    fn test_document_structure() {
+        let mut doc = PdfWriter::new("Title");
+        for i in 0..120 {
+            doc.paragraph(&format!("Line {}", i), Font::Regular, 10.0, BLACK);
+        }
+        doc.preformatted("fn main() {}\n", 9.0);
+        doc.paragraph("Xin chào thế giới", Font::Bold, 10.0, BLACK);
+        let pdf = doc.finish();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        // 120 lines of 14pt leading do not fit on one A4 page
+        assert!(text.contains("/Count 3"));
+        // Both bundled fonts are embedded, as subsets
+        assert_eq!(text.matches("/Subtype /Type0").count(), 2);
+        assert_eq!(text.matches("/FontFile2").count(), 2);
+        assert!(text.contains("+DejaVuSans /Encoding /Identity-H"));
+        assert!(text.contains("+DejaVuSansMono /Encoding /Identity-H"));
+        assert!(pdf.len() < 100_000);
+
+        // Every xref offset points at its object
+        let xref = text.rfind("\nxref\n").unwrap() + 1;
+        let entries = text[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "));
+        for (i, entry) in entries.enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+    }
+}
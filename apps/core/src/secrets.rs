@@ -0,0 +1,360 @@
+//! Secrets module - Store sensitive values in the OS keyring.
+//!
+//! Backends: Windows Credential Manager, macOS Keychain, Secret Service
+//! (GNOME Keyring / KWallet) on Linux.
+//!
+//! Stored entries (service "echovault"):
+//! - `vault-passphrase`: passphrase for end-to-end encryption
+//!   (`vault-passphrase:<profile>` when a profile is active)
+//! - `embedding-api-key`: API key of the embedding provider
+//! - `digest-api-key`: API key of the chat endpoint writing digest summaries
+//! - `provider-token:<provider>`: tokens of sync providers that manage their own
+//!   OAuth (Rclone keeps its tokens in rclone.conf and is not affected)
+//!
+//! When the keyring is unavailable (headless Linux without Secret Service,
+//! a locked session), secrets fall back to `secrets.enc` in the config
+//! directory, encrypted under a random key stored beside it in `secrets.key`.
+//! This is obfuscation, not protection: anyone who can read the config
+//! directory (the same user, root, a backup of the whole directory) can
+//! decrypt the file. It only keeps secrets out of echovault.toml, logs and
+//! copies that leave out the key file; the file permissions (0600) are the
+//! real access control, as for a plaintext file.
+//!
+//! Older configs kept the embedding API key in echovault.toml;
+//! [`migrate_default_config`] moves it here and rewrites the file without it.
+//!
+//! Secrets read back are [`SecretString`]s: cleared from memory on drop and
+//! printed as `[REDACTED]` by `Debug`.
+
+use crate::config::{default_config_dir, default_config_path, Config};
+use crate::crypto::{self, VaultKey, KEY_LEN};
+use crate::storage::atomic;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use anyhow::{bail, Context, Result};
+use keyring::Entry;
+pub use secrecy::{ExposeSecret, SecretString};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Keyring service name
+pub const SERVICE: &str = "echovault";
+
+/// Vault encryption passphrase
+pub const VAULT_PASSPHRASE: &str = "vault-passphrase";
+
+/// Keyring entry name for the passphrase of the active profile's vault.
+pub fn vault_passphrase_key() -> String {
+    match crate::config::active_profile() {
+        Some(profile) => format!("{}:{}", VAULT_PASSPHRASE, profile),
+        None => VAULT_PASSPHRASE.to_string(),
+    }
+}
+
+/// Embedding provider API key
+pub const EMBEDDING_API_KEY: &str = "embedding-api-key";
+
+/// API key of the digest chat endpoint
+pub const DIGEST_API_KEY: &str = "digest-api-key";
+
+/// Keyring entry name for a sync provider token.
+pub fn provider_token_key(provider: &str) -> String {
+    format!("provider-token:{}", provider)
+}
+
+fn entry(name: &str) -> Result<Entry> {
+    Entry::new(SERVICE, name).with_context(|| format!("Cannot open keyring entry '{}'", name))
+}
+
+/// Read a secret. Returns `None` if it is not stored.
+///
+/// Looks in the fallback file when the keyring has no such entry or cannot
+/// be reached.
+pub fn get(name: &str) -> Result<Option<SecretString>> {
+    match entry(name).and_then(|entry| Ok(entry.get_password()?)) {
+        Ok(value) => return Ok(Some(value.into())),
+        Err(e) => match e.downcast_ref::<keyring::Error>() {
+            Some(keyring::Error::NoEntry) => {}
+            _ => debug!("[secrets] Keyring unavailable for '{}': {:#}", name, e),
+        },
+    }
+    let store = FallbackStore::default_location();
+    if !store.exists() {
+        return Ok(None);
+    }
+    store
+        .get(name)
+        .with_context(|| format!("Cannot read '{}' from {}", name, store.path().display()))
+}
+
+/// Store (or replace) a secret, in the fallback file when the keyring
+/// cannot be reached.
+pub fn set(name: &str, value: &str) -> Result<()> {
+    let Err(e) = entry(name).and_then(|entry| Ok(entry.set_password(value)?)) else {
+        // A keyring entry supersedes an older fallback copy
+        return FallbackStore::default_location().delete(name);
+    };
+    let store = FallbackStore::default_location();
+    warn!(
+        "[secrets] Keyring unavailable ({:#}), storing '{}' in {} (obfuscated, not protected beyond its file permissions)",
+        e,
+        name,
+        store.path().display()
+    );
+    store.set(name, value).with_context(|| {
+        format!(
+            "Cannot write '{}' to keyring or {}",
+            name,
+            store.path().display()
+        )
+    })
+}
+
+/// Delete a secret from the keyring and the fallback file. Missing entries
+/// are not an error.
+pub fn delete(name: &str) -> Result<()> {
+    FallbackStore::default_location().delete(name)?;
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Cannot delete '{}' from keyring", name)),
+    }
+}
+
+/// Store a secret, or delete it when the value is empty/absent.
+pub fn set_or_delete(name: &str, value: Option<&str>) -> Result<()> {
+    match value.filter(|v| !v.is_empty()) {
+        Some(value) => set(name, value),
+        None => delete(name),
+    }
+}
+
+/// Read a secret, logging (not failing) when the keyring is unavailable.
+pub fn get_or_warn(name: &str) -> Option<SecretString> {
+    get(name).unwrap_or_else(|e| {
+        warn!("[secrets] {:#}", e);
+        None
+    })
+}
+
+/// Move plaintext secrets out of a config into the keyring.
+///
+/// Returns the names of moved secrets. Fields are cleared only after the
+/// keyring write succeeded; the caller saves the config to scrub the file.
+pub fn migrate_config(config: &mut Config) -> Result<Vec<&'static str>> {
+    let mut moved = Vec::new();
+
+    if let Some(api_key) = config.embedding.api_key.take() {
+        if !api_key.is_empty() {
+            if let Err(e) = set(EMBEDDING_API_KEY, &api_key) {
+                config.embedding.api_key = Some(api_key);
+                return Err(e);
+            }
+        }
+        moved.push(EMBEDDING_API_KEY);
+    }
+
+    Ok(moved)
+}
+
+/// Migrate secrets of the default config file and rewrite it without them.
+pub fn migrate_default_config() -> Result<Vec<&'static str>> {
+    let path = default_config_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut config = Config::load(&path)?;
+    let moved = migrate_config(&mut config)?;
+    if !moved.is_empty() {
+        config.save(&path)?;
+        info!(
+            "[secrets] Moved {} from {} to the OS keyring",
+            moved.join(", "),
+            path.display()
+        );
+    }
+    Ok(moved)
+}
+
+/// Secrets file used when the keyring is unavailable.
+///
+/// Obfuscated only: the key sits next to the file (see the module docs).
+struct FallbackStore {
+    dir: PathBuf,
+}
+
+impl FallbackStore {
+    /// Encrypted name → value map
+    const FILE: &'static str = "secrets.enc";
+    /// Random key of [`Self::FILE`]
+    const KEY_FILE: &'static str = "secrets.key";
+
+    fn default_location() -> Self {
+        Self::new(&default_config_dir())
+    }
+
+    fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(Self::FILE)
+    }
+
+    fn exists(&self) -> bool {
+        self.path().exists()
+    }
+
+    /// Key of the file, created on first write.
+    fn key(&self, create: bool) -> Result<VaultKey> {
+        let path = self.dir.join(Self::KEY_FILE);
+        match fs::read(&path) {
+            Ok(bytes) => {
+                let Ok(bytes) = <[u8; KEY_LEN]>::try_from(bytes.as_slice()) else {
+                    bail!("{} is not a {}-byte key", path.display(), KEY_LEN);
+                };
+                Ok(VaultKey::from_raw(&bytes))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                let mut bytes = [0u8; KEY_LEN];
+                OsRng.fill_bytes(&mut bytes);
+                write_private(&path, &bytes)?;
+                Ok(VaultKey::from_raw(&bytes))
+            }
+            Err(e) => Err(e).with_context(|| format!("Cannot read {}", path.display())),
+        }
+    }
+
+    fn load(&self, key: &VaultKey) -> Result<BTreeMap<String, String>> {
+        match fs::read(self.path()) {
+            Ok(blob) => Ok(serde_json::from_slice(&crypto::decrypt(key, &blob)?)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, key: &VaultKey, secrets: &BTreeMap<String, String>) -> Result<()> {
+        if secrets.is_empty() {
+            return match fs::remove_file(self.path()) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+        let blob = crypto::encrypt(key, &serde_json::to_vec(secrets)?)?;
+        write_private(&self.path(), &blob)
+    }
+
+    fn get(&self, name: &str) -> Result<Option<SecretString>> {
+        let secrets = self.load(&self.key(false)?)?;
+        Ok(secrets.get(name).map(|value| value.clone().into()))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        let key = self.key(true)?;
+        let mut secrets = self.load(&key)?;
+        secrets.insert(name.to_string(), value.to_string());
+        self.save(&key, &secrets)
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        if !self.exists() {
+            return Ok(());
+        }
+        let key = self.key(false)?;
+        let mut secrets = self.load(&key)?;
+        if secrets.remove(name).is_some() {
+            self.save(&key, &secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a file readable only by the current user, replacing it atomically.
+///
+/// On Unix the temporary file is created with mode 0600, so the secret is
+/// never readable by others, not even between the write and a chmod.
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = atomic::temp_path(path);
+    // A stale temporary file would keep its old mode
+    let _ = fs::remove_file(&tmp);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options.open(&tmp).and_then(|mut file| file.write_all(data));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("Failed to write {}", path.display()));
+    }
+    atomic::persist(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_config_scrubs_api_key() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        let mut config = Config::default();
+        config.embedding.api_key = Some("sk-test".to_string());
+
+        let moved = migrate_config(&mut config).unwrap();
+        assert_eq!(moved, vec![EMBEDDING_API_KEY]);
+        assert!(config.embedding.api_key.is_none());
+
+        let toml = toml::to_string_pretty(&config).unwrap();
+        assert!(!toml.contains("sk-test"));
+
+        // Nothing left to migrate
+        assert!(migrate_config(&mut config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fallback_store() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let store = FallbackStore::new(temp.path());
+        assert!(store.delete(EMBEDDING_API_KEY).is_ok());
+
+        store.set(EMBEDDING_API_KEY, "sk-test")?;
+        store.set(DIGEST_API_KEY, "sk-digest")?;
+        let value = store.get(EMBEDDING_API_KEY)?.unwrap();
+        assert_eq!(value.expose_secret(), "sk-test");
+        assert!(!String::from_utf8_lossy(&fs::read(store.path())?).contains("sk-test"));
+
+        // The file is unreadable without its key
+        fs::remove_file(temp.path().join(FallbackStore::KEY_FILE))?;
+        assert!(store.get(EMBEDDING_API_KEY).is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_mode() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::TempDir::new()?;
+        let path = temp.path().join("secrets.enc");
+        fs::write(&path, "old")?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644))?;
+
+        write_private(&path, b"new")?;
+        assert_eq!(fs::read(&path)?, b"new");
+        assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        assert!(!atomic::temp_path(&path).exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Atomic vault writes.
+//!
+//! A crash or a full disk in the middle of a plain `fs::write` or `fs::copy`
+//! leaves a truncated file behind, which parsers later choke on. [`write`] and
+//! [`copy`] write a hidden `.<name>.tmp` sibling instead, flush it to disk and
+//! rename it over the target, so readers see either the old file or the new
+//! one. On Unix the parent directory is synced too, making the rename durable.
+//!
+//! Vault scans skip leftover temporary files ([`TEMP_GLOB`]).
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Glob of the temporary files written next to their target.
+pub const TEMP_GLOB: &str = "**/.*.tmp";
+
+/// Temporary sibling `path` is written to before being renamed over it.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+/// Write `data` to `path`, replacing it atomically.
+pub fn write(path: &Path, data: impl AsRef<[u8]>) -> io::Result<()> {
+    let tmp = temp_path(path);
+    if let Err(e) = fs::write(&tmp, data) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    persist(&tmp, path)
+}
+
+/// Copy `src` to `dest`, replacing it atomically.
+///
+/// On filesystems with copy-on-write clones (Btrfs, XFS, APFS, ReFS) the file
+/// is cloned as a reflink, which takes no time or extra space; elsewhere it is
+/// copied.
+pub fn copy(src: &Path, dest: &Path) -> io::Result<()> {
+    let tmp = temp_path(dest);
+    let _ = fs::remove_file(&tmp);
+    if let Err(e) = reflink_copy::reflink_or_copy(src, &tmp) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    persist(&tmp, dest)
+}
+
+/// Flush a fully written temporary file to disk and rename it over `path`,
+/// removing it on failure. For content streamed to [`temp_path`] rather than
+/// held in memory.
+pub fn persist(tmp: &Path, path: &Path) -> io::Result<()> {
+    let result = File::options()
+        .write(true)
+        .open(tmp)
+        .and_then(|file| file.sync_all())
+        .and_then(|_| fs::rename(tmp, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(tmp);
+        return Err(e);
+    }
+    if let Some(dir) = path.parent() {
+        sync_dir(dir)?;
+    }
+    Ok(())
+}
+
+/// Flush a directory's entries (the rename) to disk.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// Windows has no directory handles to flush; renames are journaled by NTFS.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_and_copy_replace_target() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("session.json");
+        write(&path, "{\"v\": 1}")?;
+        write(&path, "{\"v\": 2}")?;
+        assert_eq!(fs::read_to_string(&path)?, "{\"v\": 2}");
+        assert!(!temp_path(&path).exists());
+
+        let dest = temp.path().join("copy.json");
+        copy(&path, &dest)?;
+        assert_eq!(fs::read_to_string(&dest)?, "{\"v\": 2}");
+
+        // A failed write keeps the previous version and no temporary file
+        assert!(copy(&temp.path().join("missing.json"), &dest).is_err());
+        assert_eq!(fs::read_to_string(&dest)?, "{\"v\": 2}");
+        assert!(!temp_path(&dest).exists());
+
+        let exclude = crate::watcher::build_ignore_set(&[TEMP_GLOB.to_string()])?;
+        assert!(exclude.is_match("cursor/.session.json.tmp"));
+        assert!(!exclude.is_match("cursor/session.json"));
+        Ok(())
+    }
+}
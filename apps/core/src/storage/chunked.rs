@@ -0,0 +1,415 @@
+//! Chunked storage - compress, encrypt and split files too large for one object.
+//!
+//! Sync providers often cap object size (around 100 MB) and some session
+//! files exceed it (e.g. Cursor databases). A chunked file is stored as a
+//! directory named after the file:
+//! ```text
+//! state.vscdb.chunks/
+//! ├── index.json[.enc]    # Original size + SHA-256 and the part list
+//! ├── part-00000[.enc]    # zstd stream, split every CHUNK_SIZE bytes
+//! └── part-00001[.enc]
+//! ```
+//! With a vault key the index and every part are ordinary encrypted blobs,
+//! so the staging manifest and key rotation handle them like any other
+//! `.enc` file. Reassembly checks the size and SHA-256 of the result.
+
+use crate::crypto::staging::ENCRYPTED_EXT;
+use crate::crypto::{self, VaultKey};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Extension of chunk directories (`<file>.chunks`)
+pub const CHUNKS_EXT: &str = "chunks";
+
+/// Maximum size of one part; files larger than this are chunked
+pub const CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+const INDEX_FILE: &str = "index.json";
+const INDEX_VERSION: u32 = 1;
+const COMPRESSION: &str = "zstd";
+const READ_BUFFER: usize = 1024 * 1024;
+const TMP_SUFFIX: &str = ".chunks.tmp";
+
+/// Description of a chunked file, stored as `index.json` in its directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkIndex {
+    /// Index format version
+    pub version: u32,
+    /// Size of the original file in bytes
+    pub size: u64,
+    /// Hex SHA-256 of the original file
+    pub sha256: String,
+    /// Compression of the joined parts
+    pub compression: String,
+    /// Part file names, in order
+    pub parts: Vec<String>,
+}
+
+/// `a/state.vscdb` -> `a/state.vscdb.chunks`
+pub fn chunks_dir(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(CHUNKS_EXT);
+    PathBuf::from(name)
+}
+
+/// `a/state.vscdb.chunks` -> `a/state.vscdb`
+pub fn original_path(dir: &Path) -> Option<PathBuf> {
+    is_chunks_dir(dir).then(|| dir.with_extension(""))
+}
+
+/// Whether a path is a chunk directory.
+pub fn is_chunks_dir(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(CHUNKS_EXT) && path.is_dir()
+}
+
+/// Whether a file belongs to a chunk directory.
+pub fn is_chunk_part(path: &Path) -> bool {
+    path.parent().is_some_and(is_chunks_dir)
+}
+
+/// Whether a file was left by an interrupted [`write`].
+pub fn is_partial(path: &Path) -> bool {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|name| name.to_string_lossy().ends_with(TMP_SUFFIX))
+}
+
+/// Whether a file of this size must be chunked.
+pub fn needs_chunking(size: u64) -> bool {
+    size > CHUNK_SIZE as u64
+}
+
+/// The index file of a chunk directory (plain or encrypted).
+pub fn index_path(dir: &Path, encrypted: bool) -> PathBuf {
+    dir.join(file_name(INDEX_FILE, encrypted))
+}
+
+fn file_name(name: &str, encrypted: bool) -> String {
+    if encrypted {
+        format!("{}.{}", name, ENCRYPTED_EXT)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Splits a byte stream into part files, encrypting each one with the key.
+struct PartWriter<'a> {
+    dir: &'a Path,
+    key: Option<&'a VaultKey>,
+    part_size: usize,
+    buffer: Vec<u8>,
+    parts: Vec<String>,
+}
+
+impl PartWriter<'_> {
+    fn write_part(&mut self, data: &[u8]) -> io::Result<()> {
+        let name = file_name(&format!("part-{:05}", self.parts.len()), self.key.is_some());
+        let path = self.dir.join(&name);
+        match self.key {
+            Some(key) => fs::write(&path, crypto::encrypt(key, data).map_err(io::Error::other)?)?,
+            None => fs::write(&path, data)?,
+        }
+        self.parts.push(name);
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<Vec<String>> {
+        if !self.buffer.is_empty() || self.parts.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            self.write_part(&rest)?;
+        }
+        Ok(self.parts)
+    }
+}
+
+impl Write for PartWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() >= self.part_size {
+            let rest = self.buffer.split_off(self.part_size);
+            let part = std::mem::replace(&mut self.buffer, rest);
+            self.write_part(&part)?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write a stream as a chunk directory, replacing any previous one.
+///
+/// Parts are written to a temporary directory first, so an interrupted write
+/// leaves the previous version intact.
+pub fn write(reader: impl Read, dir: &Path, key: Option<&VaultKey>) -> Result<ChunkIndex> {
+    write_parts(reader, dir, key, CHUNK_SIZE)
+}
+
+fn write_parts(
+    mut reader: impl Read,
+    dir: &Path,
+    key: Option<&VaultKey>,
+    part_size: usize,
+) -> Result<ChunkIndex> {
+    let mut tmp = dir.with_extension("").into_os_string();
+    tmp.push(TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp);
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp)?;
+    }
+    fs::create_dir_all(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+
+    let writer = PartWriter {
+        dir: &tmp,
+        key,
+        part_size,
+        buffer: Vec::new(),
+        parts: Vec::new(),
+    };
+    let mut encoder = zstd::stream::Encoder::new(writer, 0)?;
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buf = vec![0u8; READ_BUFFER];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        encoder.write_all(&buf[..n])?;
+        size += n as u64;
+    }
+    let parts = encoder.finish()?.finish()?;
+
+    let index = ChunkIndex {
+        version: INDEX_VERSION,
+        size,
+        sha256: format!("{:x}", hasher.finalize()),
+        compression: COMPRESSION.to_string(),
+        parts,
+    };
+    let json = serde_json::to_vec_pretty(&index)?;
+    let index_file = index_path(&tmp, key.is_some());
+    match key {
+        Some(key) => fs::write(&index_file, crypto::encrypt(key, &json)?)?,
+        None => fs::write(&index_file, json)?,
+    }
+
+    if dir.exists() {
+        fs::remove_dir_all(dir).with_context(|| format!("Failed to replace {:?}", dir))?;
+    }
+    fs::rename(&tmp, dir).with_context(|| format!("Failed to write {:?}", dir))?;
+    debug!(
+        "[chunked] Wrote {} bytes as {} parts into {:?}",
+        index.size,
+        index.parts.len(),
+        dir
+    );
+    Ok(index)
+}
+
+/// Chunk a file into `dir`.
+pub fn write_file(src: &Path, dir: &Path, key: Option<&VaultKey>) -> Result<ChunkIndex> {
+    let file = fs::File::open(src).with_context(|| format!("Failed to read {:?}", src))?;
+    write(io::BufReader::new(file), dir, key)
+}
+
+/// Read the index of a chunk directory.
+pub fn load_index(dir: &Path, key: Option<&VaultKey>) -> Result<ChunkIndex> {
+    let path = index_path(dir, key.is_some());
+    let data = read_blob(&path, key)?;
+    let index: ChunkIndex =
+        serde_json::from_slice(&data).with_context(|| format!("Invalid chunk index {:?}", path))?;
+    if index.compression != COMPRESSION {
+        bail!(
+            "Unsupported chunk compression '{}' in {:?}",
+            index.compression,
+            dir
+        );
+    }
+    Ok(index)
+}
+
+fn read_blob(path: &Path, key: Option<&VaultKey>) -> Result<Vec<u8>> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    match key {
+        Some(key) => {
+            crypto::decrypt(key, &data).with_context(|| format!("Cannot decrypt {:?}", path))
+        }
+        None => Ok(data),
+    }
+}
+
+/// Joins part files back into one stream, decrypting them one at a time.
+struct PartReader<'a> {
+    dir: &'a Path,
+    key: Option<&'a VaultKey>,
+    parts: std::vec::IntoIter<String>,
+    current: Cursor<Vec<u8>>,
+}
+
+impl Read for PartReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.current.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            let Some(name) = self.parts.next() else {
+                return Ok(0);
+            };
+            let data = read_blob(&self.dir.join(name), self.key).map_err(io::Error::other)?;
+            self.current = Cursor::new(data);
+        }
+    }
+}
+
+/// Decode a chunk directory into a writer, checking size and hash.
+fn decode(dir: &Path, key: Option<&VaultKey>, out: &mut impl Write) -> Result<ChunkIndex> {
+    let index = load_index(dir, key)?;
+    let reader = PartReader {
+        dir,
+        key,
+        parts: index.parts.clone().into_iter(),
+        current: Cursor::new(Vec::new()),
+    };
+    let mut decoder = zstd::stream::Decoder::new(reader)?;
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buf = vec![0u8; READ_BUFFER];
+    loop {
+        let n = decoder
+            .read(&mut buf)
+            .with_context(|| format!("Cannot decompress {:?}", dir))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        out.write_all(&buf[..n])?;
+        size += n as u64;
+    }
+
+    if size != index.size || format!("{:x}", hasher.finalize()) != index.sha256 {
+        bail!(
+            "{:?} does not match its index (missing or corrupted part)",
+            dir
+        );
+    }
+    Ok(index)
+}
+
+/// Reassemble a chunk directory into memory.
+pub fn read(dir: &Path, key: Option<&VaultKey>) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    decode(dir, key, &mut out)?;
+    Ok(out)
+}
+
+/// Reassemble a chunk directory into `dst` (written atomically).
+pub fn reassemble(dir: &Path, dst: &Path, key: Option<&VaultKey>) -> Result<u64> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = dst.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let file = fs::File::create(&tmp).with_context(|| format!("Failed to write {:?}", tmp))?;
+    let mut out = io::BufWriter::new(file);
+    let result = decode(dir, key, &mut out).and_then(|index| {
+        out.flush()?;
+        Ok(index)
+    });
+    drop(out);
+    let index = match result {
+        Ok(index) => index,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+    fs::rename(&tmp, dst).with_context(|| format!("Failed to write {:?}", dst))?;
+    Ok(index.size)
+}
+
+/// All chunk directories under `root`.
+pub fn find_chunk_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if is_chunks_dir(&path) {
+                dirs.push(path);
+            } else if path.is_dir() {
+                stack.push(path);
+            }
+        }
+    }
+    dirs.sort();
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::tests::test_params;
+    use crate::crypto::EncryptionMeta;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_chunked_roundtrip() {
+        let (_, key) = EncryptionMeta::create_with_params("pw", test_params()).unwrap();
+        let temp = TempDir::new().unwrap();
+
+        // Incompressible data spanning several (small) parts
+        let mut data = Vec::new();
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        while data.len() < 10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            data.extend_from_slice(&state.to_le_bytes());
+        }
+        let src = temp.path().join("state.vscdb");
+        fs::write(&src, &data).unwrap();
+
+        let dir = chunks_dir(&src);
+        let file = fs::File::open(&src).unwrap();
+        let index = write_parts(file, &dir, Some(&key), 4096).unwrap();
+        assert_eq!(index.parts.len(), 3);
+        assert!(index_path(&dir, true).exists());
+        assert!(is_chunk_part(&dir.join(&index.parts[0])));
+
+        let dst = temp.path().join("restored.vscdb");
+        assert_eq!(
+            reassemble(&dir, &dst, Some(&key)).unwrap(),
+            data.len() as u64
+        );
+        assert!(fs::read(&dst).unwrap() == data);
+
+        // A truncated part is detected
+        let part = dir.join(&index.parts[1]);
+        let blob = fs::read(&part).unwrap();
+        fs::write(&part, &blob[..blob.len() / 2]).unwrap();
+        assert!(read(&dir, Some(&key)).is_err());
+
+        // Without a key the parts are only compressed
+        let text = temp.path().join("sessions/big.json");
+        write(&b"{\"messages\":[]}"[..], &chunks_dir(&text), None).unwrap();
+        assert!(index_path(&chunks_dir(&text), false).exists());
+        assert_eq!(
+            read(&chunks_dir(&text), None).unwrap(),
+            b"{\"messages\":[]}"
+        );
+    }
+}
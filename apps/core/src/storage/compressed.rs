@@ -0,0 +1,293 @@
+//! Transparent zstd compression of raw session files.
+//!
+//! With `[extractors] compress = true`, ingest stores session copies as
+//! `sessions/<source>/<file>.zst` (e.g. `abc.jsonl.zst`), which shrinks the
+//! JSON-heavy Cursor and Antigravity sessions several times over. Parsers
+//! only see plain files: [`Plain::open`] decompresses a compressed file into
+//! a temporary directory, under the same relative path and with the same
+//! modification time and companion files, so parsers that pick files by
+//! extension, open them as SQLite or read their directory names work
+//! unchanged.
+//!
+//! `echovault-cli compress` converts an existing vault ([`compress_sessions`]).
+//! A vault synced between machines with different settings can hold both
+//! forms of a file; scans use the newer one ([`prefer_newer`]).
+
+use super::atomic;
+use crate::progress::Operation;
+use crate::utils::Walk;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Extension appended to compressed session files
+pub const EXT: &str = "zst";
+
+/// zstd level: most of the gain of higher levels, still fast on large files
+const LEVEL: i32 = 9;
+
+/// Sources never compressed: imported chats keep sidecar files named after
+/// the plain file.
+const UNCOMPRESSED_SOURCES: &[&str] = &[crate::parsers::manual::SOURCE];
+
+/// Whether a file is a compressed session file.
+pub fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == EXT)
+}
+
+/// `a/abc.jsonl` -> `a/abc.jsonl.zst`
+pub fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(EXT);
+    PathBuf::from(name)
+}
+
+/// `a/abc.jsonl.zst` -> `a/abc.jsonl` (other paths are returned as-is)
+pub fn plain_path(path: &Path) -> PathBuf {
+    if is_compressed(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// The form of a session file that exists: `path` itself, or else its
+/// compressed or plain counterpart.
+pub fn locate(path: &Path) -> PathBuf {
+    if path.exists() {
+        return path.to_path_buf();
+    }
+    let other = if is_compressed(path) {
+        plain_path(path)
+    } else {
+        compressed_path(path)
+    };
+    if other.exists() {
+        other
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Whether session files of a source are stored compressed.
+pub fn applies_to(source: &str) -> bool {
+    !UNCOMPRESSED_SOURCES.contains(&source)
+}
+
+/// Compress `src` into `dest`, replacing it atomically.
+pub fn compress(src: &Path, dest: &Path) -> io::Result<()> {
+    let tmp = atomic::temp_path(dest);
+    let written = File::open(src)
+        .and_then(|input| zstd::stream::copy_encode(input, File::create(&tmp)?, LEVEL));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    atomic::persist(&tmp, dest)
+}
+
+/// Decompress `src` into `dest`, keeping its modification time.
+pub fn decompress(src: &Path, dest: &Path) -> io::Result<()> {
+    let output = File::create(dest)?;
+    zstd::stream::copy_decode(File::open(src)?, &output)?;
+    if let Ok(modified) = fs::metadata(src).and_then(|m| m.modified()) {
+        output.set_modified(modified)?;
+    }
+    Ok(())
+}
+
+/// A session file as a plain file: the file itself, or a decompressed copy
+/// removed on drop.
+pub struct Plain {
+    path: PathBuf,
+    _dir: Option<TempDir>,
+}
+
+impl Plain {
+    /// Plain form of `path`, a file under `root` (the sessions directory).
+    pub fn open(path: &Path, root: &Path) -> io::Result<Self> {
+        if !is_compressed(path) {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                _dir: None,
+            });
+        }
+        let dir = tempfile::Builder::new().prefix("echovault-").tempdir()?;
+        let relative = plain_path(path.strip_prefix(root).unwrap_or(path));
+        let plain = dir.path().join(&relative);
+        let plain_dir = plain.parent().unwrap_or(dir.path());
+        fs::create_dir_all(plain_dir)?;
+        decompress(path, &plain)?;
+
+        // Companion files (`<stem>.*`, e.g. `abc.md.metadata.json`)
+        let stem = plain
+            .file_stem()
+            .map(|s| format!("{}.", s.to_string_lossy()))
+            .unwrap_or_default();
+        for entry in path
+            .parent()
+            .map(fs::read_dir)
+            .transpose()?
+            .into_iter()
+            .flatten()
+        {
+            let sibling = entry?.path();
+            let name = plain_path(&sibling)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if sibling == path || !name.starts_with(&stem) || !sibling.is_file() {
+                continue;
+            }
+            if is_compressed(&sibling) {
+                decompress(&sibling, &plain_dir.join(&name))?;
+            } else {
+                fs::copy(&sibling, plain_dir.join(&name))?;
+            }
+        }
+
+        Ok(Self {
+            path: plain,
+            _dir: Some(dir),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Keep one form of each session file held both plain and compressed: the
+/// most recently modified one.
+pub fn prefer_newer(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut by_plain: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut order = Vec::new();
+    for file in files {
+        let key = plain_path(&file);
+        match by_plain.get_mut(&key) {
+            Some(kept) => {
+                if modified(&file) > modified(kept) {
+                    *kept = file;
+                }
+            }
+            None => {
+                order.push(key.clone());
+                by_plain.insert(key, file);
+            }
+        }
+    }
+    order
+        .into_iter()
+        .filter_map(|key| by_plain.remove(&key))
+        .collect()
+}
+
+/// Outcome of [`compress_sessions`].
+#[derive(Debug, Default)]
+pub struct CompressStats {
+    /// Files compressed
+    pub files: usize,
+    /// Their size before and after compression
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Written files, relative to the vault (for the next delta push)
+    pub written: Vec<PathBuf>,
+}
+
+/// Compress the plain session files of a vault in place. A cancelled `op`
+/// stops between files; the stats cover those already compressed.
+pub fn compress_sessions(vault_dir: &Path, op: &Operation) -> Result<CompressStats> {
+    let sessions_dir = vault_dir.join("sessions");
+    let mut stats = CompressStats::default();
+    if !sessions_dir.is_dir() {
+        return Ok(stats);
+    }
+
+    let files: Vec<PathBuf> = Walk::new(&sessions_dir)
+        .prune(super::chunked::is_chunks_dir)
+        .files()
+        .into_iter()
+        .filter(|path| {
+            let source = path
+                .strip_prefix(&sessions_dir)
+                .ok()
+                .and_then(|p| p.components().next())
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .unwrap_or_default();
+            let hidden = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            applies_to(&source) && !is_compressed(path) && !hidden
+        })
+        .collect();
+
+    op.start("compress", files.len() as u64);
+    for path in files {
+        if op.is_cancelled() {
+            break;
+        }
+        op.advance(1);
+        let dest = compressed_path(&path);
+        let before = fs::metadata(&path)?.len();
+        compress(&path, &dest)?;
+        fs::remove_file(&path)?;
+        stats.files += 1;
+        stats.bytes_before += before;
+        stats.bytes_after += fs::metadata(&dest)?.len();
+        stats
+            .written
+            .push(dest.strip_prefix(vault_dir).unwrap_or(&dest).to_path_buf());
+    }
+    op.finish();
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_compress_round_trip_and_newer_form() -> Result<()> {
+        let temp = TempDir::new()?;
+        let vault = temp.path();
+        let session = vault.join("sessions/cursor/abc.jsonl");
+        let manual = vault.join("sessions/manual/chat.md");
+        fs::create_dir_all(session.parent().unwrap())?;
+        fs::create_dir_all(manual.parent().unwrap())?;
+        let content = "{\"role\": \"user\", \"content\": \"hello\"}\n".repeat(100);
+        fs::write(&session, &content)?;
+        fs::write(&manual, "## User\nHi\n")?;
+
+        let stats = compress_sessions(vault, &Operation::default())?;
+        assert_eq!(stats.files, 1);
+        assert!(stats.bytes_after < stats.bytes_before);
+        assert!(!session.exists() && manual.exists());
+        let compressed = compressed_path(&session);
+        assert_eq!(locate(&session), compressed);
+
+        let plain = Plain::open(&compressed, &vault.join("sessions"))?;
+        assert!(plain.path().ends_with("cursor/abc.jsonl"));
+        assert_eq!(fs::read_to_string(plain.path())?, content);
+        let temp_copy = plain.path().to_path_buf();
+        drop(plain);
+        assert!(!temp_copy.exists());
+
+        // A plain copy pulled from another machine wins when it is newer
+        fs::write(&session, "newer")?;
+        File::options()
+            .write(true)
+            .open(&compressed)?
+            .set_modified(SystemTime::now() - Duration::from_secs(60))?;
+        assert_eq!(
+            prefer_newer(vec![compressed.clone(), session.clone()]),
+            vec![session]
+        );
+        Ok(())
+    }
+}
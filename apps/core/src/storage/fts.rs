@@ -0,0 +1,371 @@
+//! Full-text index of parsed conversations, for keyword search without an
+//! embedding API.
+//!
+//! `parse` indexes every conversation it parses into `<vault>/fts.db`, an
+//! SQLite FTS5 table holding the title and message text plus the metadata
+//! used as filters (source, workspace, creation time, model). The index is a
+//! per-machine cache: it is never synced, and [`FtsIndex::rebuild`] recreates
+//! it from the raw sessions.
+
+use crate::parsers::{all_parsers, parse_source_files, ParsedConversation, Role};
+use crate::progress::Operation;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, params_from_iter, Connection};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Index database in the vault root (excluded from sync)
+pub const FTS_DB: &str = "fts.db";
+
+/// Start of a matched term in [`FtsHit::snippet`]
+pub const HIGHLIGHT_START: &str = "\u{2}";
+/// End of a matched term in [`FtsHit::snippet`]
+pub const HIGHLIGHT_END: &str = "\u{3}";
+
+/// Words of context in a snippet
+const SNIPPET_TOKENS: i32 = 16;
+
+/// Filters applied to a full-text search.
+#[derive(Debug, Clone, Default)]
+pub struct FtsFilter {
+    /// Only this source (e.g. "cursor")
+    pub source: Option<String>,
+    /// Only workspaces whose name contains this (case-insensitive)
+    pub workspace: Option<String>,
+    /// Only conversations started on or after this day
+    pub after: Option<NaiveDate>,
+    /// Only conversations started before this day
+    pub before: Option<NaiveDate>,
+    /// Only models whose name contains this (case-insensitive)
+    pub model: Option<String>,
+    /// Leave out these sessions (e.g. duplicates of another capture)
+    pub exclude: HashSet<String>,
+    /// Only these sessions (e.g. those holding a tag)
+    pub only: Option<HashSet<String>>,
+}
+
+/// A conversation matching a full-text query.
+#[derive(Debug, Clone)]
+pub struct FtsHit {
+    pub session_id: String,
+    pub source: String,
+    pub title: Option<String>,
+    pub workspace: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Matching passage, terms wrapped in [`HIGHLIGHT_START`] / [`HIGHLIGHT_END`]
+    pub snippet: String,
+    /// BM25 rank (lower is better)
+    pub rank: f64,
+}
+
+/// SQLite FTS5 index of parsed conversations.
+pub struct FtsIndex {
+    conn: Connection,
+}
+
+impl FtsIndex {
+    /// Open or create the index of a vault.
+    pub fn open(vault_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(vault_dir)?;
+        let path = vault_dir.join(FTS_DB);
+        let conn = Connection::open(&path)
+            .with_context(|| format!("Cannot open full-text index: {}", path.display()))?;
+        let index = Self { conn };
+        index.init_schema()?;
+        Ok(index)
+    }
+
+    /// Open an index in memory (for testing)
+    #[cfg(test)]
+    fn open_in_memory() -> Result<Self> {
+        let index = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        index.init_schema()?;
+        Ok(index)
+    }
+
+    fn init_schema(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+            -- Metadata columns are stored but not tokenized
+            CREATE VIRTUAL TABLE IF NOT EXISTS documents USING fts5(
+                session_id UNINDEXED,
+                source UNINDEXED,
+                workspace UNINDEXED,
+                created_at UNINDEXED,
+                model UNINDEXED,
+                title,
+                content,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );",
+        )?;
+        Ok(())
+    }
+
+    /// Number of indexed conversations.
+    pub fn len(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Whether nothing is indexed yet.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Index conversations, replacing earlier versions of them.
+    pub fn index<'a>(
+        &mut self,
+        conversations: impl IntoIterator<Item = &'a ParsedConversation>,
+    ) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut delete = tx.prepare("DELETE FROM documents WHERE session_id = ?1")?;
+            let mut insert = tx.prepare(
+                "INSERT INTO documents
+                 (session_id, source, workspace, created_at, model, title, content)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for conv in conversations {
+                delete.execute([&conv.id])?;
+                insert.execute(params![
+                    conv.id,
+                    conv.source,
+                    conv.workspace,
+                    conv.created_at.map(|d| d.to_rfc3339()),
+                    conv.model,
+                    conv.title,
+                    searchable_text(conv),
+                ])?;
+                count += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Drop the index and re-index every raw session of the vault. An
+    /// interrupted rebuild leaves the index empty, so it is rebuilt again.
+    pub fn rebuild(&mut self, vault_dir: &Path, op: &Operation) -> Result<usize> {
+        self.conn.execute("DELETE FROM documents", [])?;
+        let sessions_dir = vault_dir.join("sessions");
+        let mut count = 0;
+        for parser in &all_parsers() {
+            let indexed = parse_source_files(parser.as_ref(), &sessions_dir, None, op)
+                .and_then(|parsed| self.index(parsed.files.iter().map(|file| &file.conversation)));
+            match indexed {
+                Ok(indexed) => count += indexed,
+                Err(e) => {
+                    self.conn.execute("DELETE FROM documents", [])?;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Conversations matching an FTS5 query, best matches first.
+    pub fn search(&self, query: &str, filter: &FtsFilter, limit: usize) -> Result<Vec<FtsHit>> {
+        let mut sql = format!(
+            "SELECT session_id, source, title, workspace, created_at,
+                    snippet(documents, 6, '{}', '{}', '…', {}), rank
+             FROM documents WHERE documents MATCH ?",
+            HIGHLIGHT_START, HIGHLIGHT_END, SNIPPET_TOKENS
+        );
+        let mut args: Vec<String> = vec![query.to_string()];
+        if let Some(source) = &filter.source {
+            sql.push_str(" AND source = ?");
+            args.push(source.clone());
+        }
+        if let Some(workspace) = &filter.workspace {
+            sql.push_str(" AND instr(lower(workspace), lower(?)) > 0");
+            args.push(workspace.clone());
+        }
+        // created_at is RFC 3339 in UTC, so days compare as strings
+        if let Some(after) = filter.after {
+            sql.push_str(" AND created_at >= ?");
+            args.push(after.to_string());
+        }
+        if let Some(before) = filter.before {
+            sql.push_str(" AND created_at < ?");
+            args.push(before.to_string());
+        }
+        if let Some(model) = &filter.model {
+            sql.push_str(" AND instr(lower(model), lower(?)) > 0");
+            args.push(model.clone());
+        }
+        if !filter.exclude.is_empty() {
+            // One JSON array parameter, however many sessions are excluded
+            sql.push_str(" AND session_id NOT IN (SELECT value FROM json_each(?))");
+            args.push(serde_json::to_string(&filter.exclude)?);
+        }
+        if let Some(only) = &filter.only {
+            sql.push_str(" AND session_id IN (SELECT value FROM json_each(?))");
+            args.push(serde_json::to_string(only)?);
+        }
+        sql.push_str(&format!(" ORDER BY rank LIMIT {}", limit));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let hits = stmt
+            .query_map(params_from_iter(args), |row| {
+                let created_at: Option<String> = row.get(4)?;
+                Ok(FtsHit {
+                    session_id: row.get(0)?,
+                    source: row.get(1)?,
+                    title: row.get(2)?,
+                    workspace: row.get(3)?,
+                    created_at: created_at
+                        .and_then(|d| DateTime::parse_from_rfc3339(&d).ok())
+                        .map(|d| d.with_timezone(&Utc)),
+                    snippet: row.get(5)?,
+                    rank: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .with_context(|| format!("Invalid search query: {}", query))?;
+        Ok(hits)
+    }
+}
+
+/// Index conversations just parsed into a vault whose index exists.
+///
+/// A missing index is left to be built in full by the first search, which
+/// a partial one would hide. Failures are only logged: the conversations are
+/// indexed again when they next change, or by a rebuild.
+pub fn index_parsed<'a>(
+    vault_dir: &Path,
+    conversations: impl IntoIterator<Item = &'a ParsedConversation>,
+) {
+    if !vault_dir.join(FTS_DB).exists() {
+        return;
+    }
+    if let Err(e) = FtsIndex::open(vault_dir).and_then(|mut index| index.index(conversations)) {
+        tracing::warn!("Failed to update the full-text index: {}", e);
+    }
+}
+
+/// Text of the messages worth searching (not system prompts or status lines).
+fn searchable_text(conv: &ParsedConversation) -> String {
+    conv.messages
+        .iter()
+        .filter(|m| matches!(m.role, Role::User | Role::Assistant | Role::Tool))
+        .map(|m| m.content.trim())
+        .filter(|content| !content.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ParsedMessage;
+    use chrono::TimeZone;
+
+    fn conversation(
+        id: &str,
+        source: &str,
+        workspace: &str,
+        day: u32,
+        text: &str,
+    ) -> ParsedConversation {
+        let message = |role, content: &str| ParsedMessage {
+            role,
+            content: content.to_string(),
+            timestamp: None,
+            tool_name: None,
+            model: None,
+        };
+        ParsedConversation {
+            id: id.to_string(),
+            source: source.to_string(),
+            title: Some(format!("Session {}", id)),
+            workspace: Some(workspace.to_string()),
+            created_at: Some(Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap()),
+            updated_at: None,
+            model: Some(format!("{}-model", source)),
+            messages: vec![
+                message(Role::System, "You are a helpful assistant"),
+                message(Role::User, text),
+                message(Role::Assistant, "Here is how"),
+            ],
+            tags: Vec::new(),
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_search_with_filters() -> Result<()> {
+        let mut index = FtsIndex::open_in_memory()?;
+        let a = conversation(
+            "a",
+            "cursor",
+            "EchoVault",
+            5,
+            "Fix the borrow checker error",
+        );
+        let b = conversation("b", "cline", "website", 20, "Borrow a layout from the docs");
+        index.index([&a, &b])?;
+        assert_eq!(index.len()?, 2);
+
+        let hits = index.search("borrow", &FtsFilter::default(), 10)?;
+        assert_eq!(hits.len(), 2);
+        let highlighted = format!("{}borrow{}", HIGHLIGHT_START, HIGHLIGHT_END);
+        assert!(hits
+            .iter()
+            .all(|hit| hit.snippet.to_lowercase().contains(&highlighted)));
+
+        // System prompts are not indexed
+        assert!(index
+            .search("helpful", &FtsFilter::default(), 10)?
+            .is_empty());
+
+        let filter = FtsFilter {
+            workspace: Some("echo".to_string()),
+            ..FtsFilter::default()
+        };
+        let hits = index.search("borrow", &filter, 10)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "a");
+
+        let filter = FtsFilter {
+            source: Some("cline".to_string()),
+            after: NaiveDate::from_ymd_opt(2025, 1, 10),
+            before: NaiveDate::from_ymd_opt(2025, 2, 1),
+            ..FtsFilter::default()
+        };
+        let hits = index.search("borrow", &filter, 10)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "b");
+
+        let filter = FtsFilter {
+            model: Some("CURSOR".to_string()),
+            ..FtsFilter::default()
+        };
+        let hits = index.search("borrow", &filter, 10)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "a");
+
+        let filter = FtsFilter {
+            exclude: HashSet::from(["a".to_string()]),
+            ..FtsFilter::default()
+        };
+        let hits = index.search("borrow", &filter, 10)?;
+        assert!(hits.iter().all(|hit| hit.session_id != "a"));
+
+        // Re-indexing replaces the earlier version
+        let a = conversation("a", "cursor", "EchoVault", 5, "Now about lifetimes");
+        index.index([&a])?;
+        assert_eq!(index.len()?, 2);
+        assert_eq!(index.search("borrow", &FtsFilter::default(), 10)?.len(), 1);
+
+        assert!(index
+            .search("\"unbalanced", &FtsFilter::default(), 10)
+            .is_err());
+        Ok(())
+    }
+}
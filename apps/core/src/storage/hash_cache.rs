@@ -0,0 +1,138 @@
+//! Per-stage content hashes of raw session files.
+//!
+//! `parse` and `embed` re-read every raw file in the vault on each run. Each
+//! stage instead records the hashes of the files it has processed in the
+//! `file_hashes` table of local.db, and skips files whose hash is unchanged
+//! on the next run. Paths are stored relative to the vault.
+//!
+//! The hash is the BLAKE3 digest ingest stores as `sessions.content_hash`,
+//! and parse writes it as the `source_hash` of the Markdown it produces.
+
+use super::local_db::{LocalDb, LOCAL_DB};
+use super::mapped;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Stage name of `parse` (raw file -> Markdown)
+pub const PARSE: &str = "parse";
+/// Stage name of `embed` (raw file -> chunks + embeddings)
+pub const EMBED: &str = "embed";
+
+/// Hashes recorded by a stage, and those of files it processed this run.
+pub struct HashCache {
+    vault_dir: PathBuf,
+    stage: &'static str,
+    known: HashMap<String, String>,
+    pending: Mutex<HashMap<String, String>>,
+    done: Mutex<Vec<(String, String)>>,
+}
+
+impl HashCache {
+    /// Load the hashes a stage recorded.
+    pub fn load(vault_dir: &Path, stage: &'static str) -> Self {
+        let known = if vault_dir.join(LOCAL_DB).exists() {
+            LocalDb::open(vault_dir)
+                .and_then(|db| db.file_hashes(stage))
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        Self {
+            vault_dir: vault_dir.to_path_buf(),
+            stage,
+            known,
+            pending: Mutex::new(HashMap::new()),
+            done: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn key(&self, path: &Path) -> String {
+        path.strip_prefix(&self.vault_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Whether a file's content is unchanged since the stage last processed it.
+    ///
+    /// Changed files are remembered so [`HashCache::record`] can mark them
+    /// processed once the stage has succeeded.
+    pub fn is_unchanged(&self, path: &Path) -> bool {
+        let Ok(hash) = mapped::blake3_file(path) else {
+            return false;
+        };
+        let key = self.key(path);
+        if self.known.get(&key) == Some(&hash) {
+            return true;
+        }
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(key, hash);
+        }
+        false
+    }
+
+    /// Hash of a file: the one computed by [`HashCache::is_unchanged`] for a
+    /// changed file, or else freshly computed.
+    pub fn hash(&self, path: &Path) -> io::Result<String> {
+        let pending = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|p| p.get(&self.key(path)).cloned());
+        pending.map_or_else(|| mapped::blake3_file(path), Ok)
+    }
+
+    /// Mark a changed file as processed by this stage.
+    pub fn record(&self, path: &Path) {
+        let key = self.key(path);
+        let hash = self.pending.lock().ok().and_then(|mut p| p.remove(&key));
+        if let (Some(hash), Ok(mut done)) = (hash, self.done.lock()) {
+            done.push((key, hash));
+        }
+    }
+
+    /// Store the hashes of the files processed this run.
+    pub fn save(&self) -> Result<()> {
+        let done = self.done.lock().map(|d| d.clone()).unwrap_or_default();
+        if done.is_empty() {
+            return Ok(());
+        }
+        LocalDb::open(&self.vault_dir)?.save_file_hashes(self.stage, &done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_only_recorded_files_are_skipped() -> Result<()> {
+        let vault = TempDir::new()?;
+        let dir = vault.path().join("sessions/codex");
+        fs::create_dir_all(&dir)?;
+        let done = dir.join("a.jsonl");
+        let failed = dir.join("b.jsonl");
+        fs::write(&done, "{}")?;
+        fs::write(&failed, "{}")?;
+
+        let cache = HashCache::load(vault.path(), PARSE);
+        assert!(!cache.is_unchanged(&done));
+        assert!(!cache.is_unchanged(&failed));
+        cache.record(&done);
+        cache.save()?;
+
+        let cache = HashCache::load(vault.path(), PARSE);
+        assert!(cache.is_unchanged(&done));
+        assert!(!cache.is_unchanged(&failed));
+        // Stages are independent, and edits are detected
+        assert!(!HashCache::load(vault.path(), EMBED).is_unchanged(&done));
+        fs::write(&done, "{\"edited\": true}")?;
+        assert!(!cache.is_unchanged(&done));
+        Ok(())
+    }
+}
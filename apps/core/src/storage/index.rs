@@ -253,16 +253,21 @@ impl SessionIndex {
     }
 
     /// Filter sessions by source (vscode-copilot, cursor, etc.)
-    pub fn filter_by_source(&self, source: &str, limit: usize) -> Result<Vec<SessionMetadata>> {
+    pub fn filter_by_source(
+        &self,
+        source: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SessionMetadata>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, source, title, created_at, vault_path, original_path, file_size, workspace_name
              FROM sessions
              WHERE source = ?1
              ORDER BY created_at DESC
-             LIMIT ?2",
+             LIMIT ?2 OFFSET ?3",
         )?;
 
-        let rows = stmt.query_map(params![source, limit as i64], |row| {
+        let rows = stmt.query_map(params![source, limit as i64, offset as i64], |row| {
             Ok(SessionMetadataRow {
                 id: row.get(0)?,
                 source: row.get(1)?,
@@ -318,6 +323,46 @@ impl SessionIndex {
         Ok(sessions)
     }
 
+    /// Sessions of a source and/or workspace, newest first (with pagination)
+    pub fn list_filtered(
+        &self,
+        source: Option<&str>,
+        workspace: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SessionMetadata>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, source, title, created_at, vault_path, original_path, file_size, workspace_name
+             FROM sessions
+             WHERE (?1 IS NULL OR source = ?1) AND (?2 IS NULL OR workspace_name = ?2)
+             ORDER BY created_at DESC
+             LIMIT ?3 OFFSET ?4",
+        )?;
+
+        let rows = stmt.query_map(
+            params![source, workspace, limit as i64, offset as i64],
+            |row| {
+                Ok(SessionMetadataRow {
+                    id: row.get(0)?,
+                    source: row.get(1)?,
+                    title: row.get(2)?,
+                    created_at: row.get(3)?,
+                    vault_path: row.get(4)?,
+                    original_path: row.get(5)?,
+                    file_size: row.get(6)?,
+                    workspace_name: row.get(7)?,
+                })
+            },
+        )?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?.into());
+        }
+
+        Ok(sessions)
+    }
+
     /// Get session by ID
     pub fn get(&self, id: &str) -> Result<Option<SessionMetadata>> {
         let mut stmt = self.conn.prepare(
@@ -505,6 +550,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_list_filtered() -> Result<()> {
+        let mut index = SessionIndex::open_in_memory()?;
+        let mut sessions: Vec<SessionMetadata> = (1..=4)
+            .map(|i| create_test_metadata(&format!("s{}", i)))
+            .collect();
+        sessions[0].source = "cursor".to_string();
+        sessions[1].workspace_name = Some("other".to_string());
+        index.upsert_batch(&sessions)?;
+
+        assert_eq!(index.list_filtered(None, None, 10, 0)?.len(), 4);
+        assert_eq!(index.list_filtered(Some("cursor"), None, 10, 0)?.len(), 1);
+        assert_eq!(index.list_filtered(None, Some("other"), 10, 0)?.len(), 1);
+        assert_eq!(
+            index
+                .list_filtered(Some("vscode-copilot"), Some("test-project"), 10, 0)?
+                .len(),
+            2
+        );
+        assert_eq!(index.list_filtered(None, None, 3, 2)?.len(), 2);
+
+        Ok(())
+    }
+
     #[test]
     fn test_search() -> Result<()> {
         let mut index = SessionIndex::open_in_memory()?;
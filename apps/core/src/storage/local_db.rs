@@ -0,0 +1,349 @@
+//! LocalDb - SQLite database for state of this machine only.
+//!
+//! vault.db is pulled from the sync target and replaces the local copy, so
+//! anything that describes one machine (its metrics, the files its parse and
+//! embed stages have processed, what it has pushed to each sync target)
+//! cannot live there: a pull would overwrite it with another machine's copy. `<vault>/local.db` holds that state instead. Like the full-text index it is never synced,
+//! and losing it only costs history.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Database in the vault root (excluded from sync)
+pub const LOCAL_DB: &str = "local.db";
+
+/// Days dirty file marks are kept for delta pushes. A sync target not
+/// pushed to for longer gets a full push.
+pub const DIRTY_RETENTION_DAYS: i64 = 30;
+
+/// SQLite database of machine-local vault state.
+pub struct LocalDb {
+    conn: Connection,
+}
+
+impl LocalDb {
+    /// Open or create the local database of a vault.
+    pub fn open(vault_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(vault_dir)?;
+        let path = vault_dir.join(LOCAL_DB);
+        let conn = Connection::open(&path)
+            .with_context(|| format!("Cannot open local database: {}", path.display()))?;
+        let db = Self { conn };
+        db.init_schema()?;
+        Ok(db)
+    }
+
+    /// Open a database in memory (for testing)
+    #[cfg(test)]
+    fn open_in_memory() -> Result<Self> {
+        let db = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        db.init_schema()?;
+        Ok(db)
+    }
+
+    fn init_schema(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+            PRAGMA busy_timeout = 30000;
+
+            -- Metrics samples
+            CREATE TABLE IF NOT EXISTS metrics (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                value REAL NOT NULL DEFAULT 0,
+                recorded_at INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS idx_metrics_name ON metrics(name);
+
+            -- Hashes of the raw files each stage processed: parsed Markdown
+            -- is synced, but the full-text index and ANN graph built with it
+            -- are not, so every machine runs the stages itself
+            CREATE TABLE IF NOT EXISTS file_hashes (
+                stage TEXT NOT NULL,
+                path TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                updated_at INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (stage, path)
+            );
+
+            -- Outcome of the last sync with each sync target
+            CREATE TABLE IF NOT EXISTS remote_sync_state (
+                remote TEXT PRIMARY KEY NOT NULL,
+                last_attempt INTEGER NOT NULL DEFAULT 0,
+                last_success INTEGER,
+                files_pushed INTEGER NOT NULL DEFAULT 0,
+                error TEXT,
+                pushed_from INTEGER
+            );
+
+            -- Vault files written here, for delta pushes
+            CREATE TABLE IF NOT EXISTS dirty_files (
+                path TEXT PRIMARY KEY NOT NULL,
+                marked_at INTEGER NOT NULL DEFAULT 0
+            );",
+        )?;
+        Ok(())
+    }
+
+    /// Record a metric sample, dropping samples older than `keep_secs`.
+    pub fn record_metric(&self, name: &str, value: f64, keep_secs: i64) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.conn.execute(
+            "INSERT INTO metrics (name, value, recorded_at) VALUES (?1, ?2, ?3)",
+            params![name, value, now],
+        )?;
+        self.conn.execute(
+            "DELETE FROM metrics WHERE recorded_at < ?1",
+            params![now - keep_secs],
+        )?;
+        Ok(())
+    }
+
+    /// Raw file hashes a stage recorded, by vault-relative path.
+    pub fn file_hashes(&self, stage: &str) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, hash FROM file_hashes WHERE stage = ?1")?;
+        let rows = stmt.query_map(params![stage], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Store raw file hashes processed by a stage.
+    pub fn save_file_hashes(&mut self, stage: &str, hashes: &[(String, String)]) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO file_hashes (stage, path, hash, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(stage, path) DO UPDATE SET
+                    hash = excluded.hash, updated_at = excluded.updated_at",
+            )?;
+            for (path, hash) in hashes {
+                stmt.execute(params![stage, path, hash, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record the outcome of a sync with a sync target: the files pushed, or
+    /// the error it failed with. `pushed_from` is when the sync started (unix
+    /// milliseconds): files marked dirty since then are left for the next
+    /// delta push.
+    pub fn record_remote_sync(
+        &self,
+        remote: &str,
+        pushed_from: i64,
+        outcome: Result<usize, &str>,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        match outcome {
+            Ok(files_pushed) => self.conn.execute(
+                "INSERT INTO remote_sync_state
+                     (remote, last_attempt, last_success, files_pushed, error, pushed_from)
+                 VALUES (?1, ?2, ?2, ?3, NULL, ?4)
+                 ON CONFLICT(remote) DO UPDATE SET
+                     last_attempt = excluded.last_attempt,
+                     last_success = excluded.last_success,
+                     files_pushed = excluded.files_pushed,
+                     error = NULL,
+                     pushed_from = excluded.pushed_from",
+                params![remote, now, files_pushed as i64, pushed_from],
+            )?,
+            // Keep the last success, so the state shows how long it has failed
+            Err(error) => self.conn.execute(
+                "INSERT INTO remote_sync_state (remote, last_attempt, error)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(remote) DO UPDATE SET
+                     last_attempt = excluded.last_attempt,
+                     error = excluded.error",
+                params![remote, now, error],
+            )?,
+        };
+        Ok(())
+    }
+
+    /// Last sync with each sync target, by remote name.
+    pub fn remote_sync_states(&self) -> Result<Vec<RemoteSyncState>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT remote, last_attempt, last_success, files_pushed, error, pushed_from
+             FROM remote_sync_state
+             ORDER BY remote",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RemoteSyncState {
+                remote: row.get(0)?,
+                last_attempt: row.get(1)?,
+                last_success: row.get(2)?,
+                files_pushed: row.get::<_, i64>(3)? as usize,
+                error: row.get(4)?,
+                pushed_from: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Mark vault files (paths relative to the vault) as written, so the next
+    /// delta push sends them. Marks older than [`DIRTY_RETENTION_DAYS`] are
+    /// dropped.
+    pub fn mark_dirty_files(&mut self, paths: &[PathBuf]) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO dirty_files (path, marked_at) VALUES (?1, ?2)
+                 ON CONFLICT(path) DO UPDATE SET marked_at = excluded.marked_at",
+            )?;
+            for path in paths {
+                // Forward slashes, as rclone expects in a file list
+                stmt.execute(params![path.to_string_lossy().replace('\\', "/"), now])?;
+            }
+        }
+        tx.execute(
+            "DELETE FROM dirty_files WHERE marked_at < ?1",
+            params![now - DIRTY_RETENTION_DAYS * 86_400_000],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Vault files marked dirty at or after `since` (unix milliseconds).
+    pub fn dirty_files_since(&self, since: i64) -> Result<Vec<PathBuf>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path FROM dirty_files WHERE marked_at >= ?1 ORDER BY path")?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(PathBuf::from(row.get::<_, String>(0)?))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Summaries of the recorded metrics, by name.
+    pub fn metric_summaries(&self) -> Result<Vec<MetricSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, COUNT(*), SUM(value), MIN(value), MAX(value), MAX(recorded_at)
+             FROM metrics
+             GROUP BY name ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(MetricSummary {
+                name: row.get(0)?,
+                count: row.get::<_, i64>(1)? as u64,
+                total: row.get(2)?,
+                min: row.get(3)?,
+                max: row.get(4)?,
+                last_at: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// Last sync with a sync target, from this machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoteSyncState {
+    /// Sync target name (`default` or a `[sync.targets.<name>]` key)
+    pub remote: String,
+    /// Unix timestamp (seconds) of the last sync attempt
+    pub last_attempt: i64,
+    /// Unix timestamp (seconds) of the last successful sync
+    pub last_success: Option<i64>,
+    /// Files pushed by the last successful sync
+    pub files_pushed: usize,
+    /// Why the last attempt failed (`None` when it succeeded)
+    pub error: Option<String>,
+    /// When the last successful sync started (unix milliseconds)
+    pub pushed_from: Option<i64>,
+}
+
+/// Aggregate of one metric recorded on this machine.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricSummary {
+    pub name: String,
+    pub count: u64,
+    pub total: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Unix timestamp (seconds) of the latest sample
+    pub last_at: i64,
+}
+
+impl MetricSummary {
+    /// Mean of the recorded samples.
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total / self.count as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_old_metric_samples_are_dropped() -> Result<()> {
+        let db = LocalDb::open_in_memory()?;
+        db.conn.execute(
+            "INSERT INTO metrics (name, value, recorded_at) VALUES ('sync.duration_ms', 9, 0)",
+            [],
+        )?;
+        db.record_metric("sync.duration_ms", 2.0, 3600)?;
+        db.record_metric("sync.duration_ms", 4.0, 3600)?;
+
+        let summaries = db.metric_summaries()?;
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].count, 2);
+        assert_eq!(summaries[0].average(), 3.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remote_sync_state() -> Result<()> {
+        let db = LocalDb::open_in_memory()?;
+        db.record_remote_sync("default", 1_000, Ok(3))?;
+        db.record_remote_sync("nas", 2_000, Ok(5))?;
+        db.record_remote_sync("nas", 3_000, Err("drive not mounted"))?;
+
+        let states = db.remote_sync_states()?;
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].remote, "default");
+        assert_eq!(states[0].error, None);
+        // A failure keeps the last success and its file count
+        assert_eq!(states[1].error.as_deref(), Some("drive not mounted"));
+        assert!(states[1].last_success.is_some());
+        assert_eq!(states[1].files_pushed, 5);
+        assert_eq!(states[1].pushed_from, Some(2_000));
+
+        db.record_remote_sync("nas", 4_000, Ok(0))?;
+        assert_eq!(db.remote_sync_states()?[1].error, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dirty_files() -> Result<()> {
+        let mut db = LocalDb::open_in_memory()?;
+        let before = chrono::Utc::now().timestamp_millis();
+        db.mark_dirty_files(&[
+            PathBuf::from("sessions/cursor/a.json"),
+            PathBuf::from("parsed/cursor/a.md"),
+        ])?;
+        assert_eq!(
+            db.dirty_files_since(before)?,
+            vec![
+                PathBuf::from("parsed/cursor/a.md"),
+                PathBuf::from("sessions/cursor/a.json")
+            ]
+        );
+        assert!(db.dirty_files_since(before + 3_600_000)?.is_empty());
+        Ok(())
+    }
+}
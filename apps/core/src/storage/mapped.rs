@@ -0,0 +1,102 @@
+//! Memory-mapped file reads.
+//!
+//! Large files (Cursor `state.vscdb`, Antigravity `.pb` conversations,
+//! database snapshots) are mapped instead of read into a heap buffer, so
+//! hashing and encrypting them does not allocate their full size. Small files
+//! are read normally, where a mapping costs more than it saves.
+//!
+//! A mapped file truncated by another process while it is read faults the
+//! reader. [`read`] hands out mappings that live as long as the caller keeps
+//! them, so only use it on files EchoVault owns (the vault, staging,
+//! snapshots). [`blake3_file`] maps a file only while hashing it, which
+//! ingest uses on the IDEs' own session files: they are replaced or appended
+//! to, not truncated in place.
+
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Files at least this large are mapped rather than read
+pub const MAP_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// Contents of a file, mapped or read into memory.
+pub enum FileBytes {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileBytes::Mapped(map) => map,
+            FileBytes::Owned(data) => data,
+        }
+    }
+}
+
+impl AsRef<[u8]> for FileBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Read a file, mapping it when it is large.
+pub fn read(path: &Path) -> io::Result<FileBytes> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() < MAP_THRESHOLD {
+        return fs::read(path).map(FileBytes::Owned);
+    }
+    // SAFETY: only vault-owned files are mapped (see module docs), and the
+    // mapping is read-only and dropped before the file is replaced.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(FileBytes::Mapped(map))
+}
+
+/// Hex BLAKE3 of a file's contents, mapping it when it is large.
+pub fn blake3_file(path: &Path) -> io::Result<String> {
+    let file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    if file.metadata()?.len() < MAP_THRESHOLD {
+        hasher.update_reader(file)?;
+    } else {
+        // SAFETY: the mapping is read-only and dropped once hashed (see
+        // module docs for files other processes own).
+        let map = unsafe { Mmap::map(&file)? };
+        hasher.update(&map);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_small_files_are_read_large_files_mapped() -> io::Result<()> {
+        let temp = TempDir::new()?;
+        let small = temp.path().join("small.json");
+        let large = temp.path().join("state.vscdb");
+        fs::write(&small, b"{}")?;
+        let content = vec![7u8; MAP_THRESHOLD as usize + 1];
+        fs::write(&large, &content)?;
+
+        assert!(matches!(read(&small)?, FileBytes::Owned(_)));
+        let mapped = read(&large)?;
+        assert!(matches!(mapped, FileBytes::Mapped(_)));
+        assert_eq!(&*mapped, content.as_slice());
+        assert_eq!(
+            blake3_file(&large)?,
+            blake3::hash(&content).to_hex().to_string()
+        );
+        assert_eq!(
+            blake3_file(&small)?,
+            blake3::hash(b"{}").to_hex().to_string()
+        );
+        Ok(())
+    }
+}
@@ -2,20 +2,42 @@
 //!
 //! This module contains:
 //! - SQLite index for fast session search and filtering
+//! - Full-text (FTS5) index of parsed conversation content
+//! - Chunked (compressed, encrypted, split) storage for files too large to upload whole
 //! - VaultDb for multi-machine sync with conflict resolution
+//! - LocalDb for state of this machine only (never synced)
 //! - SyncManager for cr-sqlite CRDT sync support
 //! - Utilities for vault directory management
+//! - Vault relocation (move a vault and its databases to a new path)
+//! - Memory-mapped reads of large vault files
+//! - Per-stage content hashes so parse/embed skip unchanged raw files
+//! - Atomic (temp file + rename) writes of vault files
+//! - Optional zstd compression of raw session files
 
+pub mod atomic;
+pub mod chunked;
+pub mod compressed;
+pub mod fts;
+pub mod hash_cache;
 pub mod index;
+pub mod local_db;
+pub mod mapped;
+pub mod relocate;
 pub mod sync_manager;
 pub mod vault_db;
 
 #[cfg(feature = "ci-sync-test")]
 pub mod sync_test;
 
+pub use fts::{FtsFilter, FtsHit, FtsIndex};
 pub use index::SessionIndex;
+pub use local_db::{LocalDb, MetricSummary, RemoteSyncState, DIRTY_RETENTION_DAYS};
 pub use sync_manager::{
     apply_remote_changes, deserialize_changeset, get_db_version, get_last_synced_version,
     get_local_changes, serialize_changeset, set_last_synced_version, Changeset, CrdtChange,
 };
-pub use vault_db::{BatchResult, SessionEntry, UpsertResult, VaultDb};
+pub use vault_db::{
+    Annotation, AnnotationUpdate, BatchResult, ConversationStats, DayCount, DuplicateLink,
+    EnrichedMetadata, ModelCount, NewSession, PendingChange, RoleCounts, SessionEntry,
+    SessionSignature, SessionUsage, SyncLogEntry, UpsertResult, VaultDb, WorkspaceActivity,
+};
@@ -0,0 +1,342 @@
+//! Vault relocation.
+//!
+//! Moves a whole vault directory (session files, `vault.db`, `embeddings.db`,
+//! encrypted staging) to a new location. On the same filesystem this is a
+//! single rename. Across filesystems the vault is copied into a hidden sibling
+//! of the destination, renamed into place once complete and only then removed
+//! from the old location, so an interrupted move leaves the old vault intact.
+//! Absolute session paths stored in `vault.db` are rewritten to the new location.
+
+use super::VaultDb;
+use crate::crypto::staging::ENCRYPTED_DBS;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Progress of a vault move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RelocateProgress {
+    pub files_done: usize,
+    pub total_files: usize,
+    pub bytes_done: u64,
+    pub total_bytes: u64,
+}
+
+/// Outcome of a vault move.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelocateReport {
+    /// Files moved
+    pub files: usize,
+    /// Bytes moved
+    pub bytes: u64,
+    /// Copied across filesystems instead of renamed
+    pub copied: bool,
+    /// Session paths rewritten in vault.db
+    pub rewritten_paths: usize,
+    /// Old vault that could not be removed after copying
+    pub leftover: Option<PathBuf>,
+}
+
+/// Files (relative paths and sizes) and directories of a vault.
+struct Tree {
+    dirs: Vec<PathBuf>,
+    files: Vec<(PathBuf, u64)>,
+}
+
+impl Tree {
+    fn scan(root: &Path) -> Result<Self> {
+        let mut tree = Self {
+            dirs: Vec::new(),
+            files: Vec::new(),
+        };
+        let mut pending = vec![PathBuf::new()];
+        while let Some(relative) = pending.pop() {
+            for entry in fs::read_dir(root.join(&relative))? {
+                let entry = entry?;
+                let path = relative.join(entry.file_name());
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    tree.dirs.push(path.clone());
+                    pending.push(path);
+                } else {
+                    tree.files.push((path, entry.metadata()?.len()));
+                }
+            }
+        }
+        Ok(tree)
+    }
+
+    fn total_bytes(&self) -> u64 {
+        self.files.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Absolute form of a path, resolving symlinks in the part that exists.
+fn resolve(path: &Path) -> Result<PathBuf> {
+    let path = std::path::absolute(path)?;
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        let Some(parent) = existing.parent() else {
+            return Ok(path);
+        };
+        rest.push(existing.file_name().unwrap_or_default().to_owned());
+        existing = parent;
+    }
+    let mut resolved = fs::canonicalize(existing)?;
+    resolved.extend(rest.iter().rev());
+    Ok(resolved)
+}
+
+/// Fold SQLite write-ahead logs into the databases before they are moved.
+fn checkpoint(vault_dir: &Path) {
+    for name in ENCRYPTED_DBS {
+        let path = vault_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        let result = rusqlite::Connection::open(&path)
+            .and_then(|conn| conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(())));
+        if let Err(e) = result {
+            warn!("[relocate] Cannot checkpoint {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Rewrite session paths in the vault.db inside `vault_dir`.
+fn rewrite_paths(vault_dir: &Path, old: &[PathBuf], new: &Path) -> Result<usize> {
+    if !vault_dir.join("vault.db").exists() {
+        return Ok(0);
+    }
+    let db = VaultDb::open(vault_dir)?;
+    let mut rewritten = 0;
+    for old in old {
+        rewritten += db.rewrite_vault_paths(old, new)?;
+    }
+    Ok(rewritten)
+}
+
+/// Copy a vault tree into `dest`, checking every file's size.
+fn copy_tree(
+    from: &Path,
+    dest: &Path,
+    tree: &Tree,
+    on_progress: &mut impl FnMut(RelocateProgress),
+) -> Result<()> {
+    let mut progress = RelocateProgress {
+        total_files: tree.files.len(),
+        total_bytes: tree.total_bytes(),
+        ..Default::default()
+    };
+    fs::create_dir_all(dest)?;
+    for dir in &tree.dirs {
+        fs::create_dir_all(dest.join(dir))?;
+    }
+    for (relative, size) in &tree.files {
+        let copied = fs::copy(from.join(relative), dest.join(relative))
+            .with_context(|| format!("Failed to copy {}", relative.display()))?;
+        if copied != *size {
+            bail!(
+                "{} changed while moving the vault (stop the watcher and desktop app first)",
+                relative.display()
+            );
+        }
+        progress.files_done += 1;
+        progress.bytes_done += copied;
+        on_progress(progress);
+    }
+    Ok(())
+}
+
+/// Move a vault directory to `to`, calling `on_progress` as files are moved.
+///
+/// `to` must not exist or be an empty directory. Stop anything writing to the
+/// vault (the `watch` command, the desktop app) before moving it.
+pub fn relocate_vault(
+    from: &Path,
+    to: &Path,
+    mut on_progress: impl FnMut(RelocateProgress),
+) -> Result<RelocateReport> {
+    if !from.is_dir() {
+        bail!("Vault not found: {}", from.display());
+    }
+    let source = resolve(from)?;
+    let dest = resolve(to)?;
+    if source == dest {
+        bail!("The vault is already at {}", dest.display());
+    }
+    if dest.starts_with(&source) || source.starts_with(&dest) {
+        bail!(
+            "Cannot move the vault from {} into {}",
+            source.display(),
+            dest.display()
+        );
+    }
+    if dest.exists() {
+        if !dest.is_dir() || fs::read_dir(&dest)?.next().is_some() {
+            bail!("{} already exists and is not empty", dest.display());
+        }
+        fs::remove_dir(&dest)?;
+    }
+    let parent = dest
+        .parent()
+        .with_context(|| format!("Invalid destination: {}", dest.display()))?;
+    fs::create_dir_all(parent)?;
+
+    // Paths stored in vault.db use the configured (maybe non-canonical) form
+    let mut old_paths = vec![std::path::absolute(from)?];
+    if !old_paths.contains(&source) {
+        old_paths.push(source.clone());
+    }
+
+    checkpoint(&source);
+    let tree = Tree::scan(&source)?;
+    let mut report = RelocateReport {
+        files: tree.files.len(),
+        bytes: tree.total_bytes(),
+        ..Default::default()
+    };
+
+    match fs::rename(&source, &dest) {
+        Ok(()) => {
+            on_progress(RelocateProgress {
+                files_done: report.files,
+                total_files: report.files,
+                bytes_done: report.bytes,
+                total_bytes: report.bytes,
+            });
+            match rewrite_paths(&dest, &old_paths, &dest) {
+                Ok(rewritten) => report.rewritten_paths = rewritten,
+                Err(e) => {
+                    // Put the vault back so config and databases still agree
+                    fs::rename(&dest, &source).with_context(|| {
+                        format!("Vault left at {} after: {:#}", dest.display(), e)
+                    })?;
+                    return Err(e.context("Failed to update vault.db"));
+                }
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            report.copied = true;
+            report.rewritten_paths =
+                move_by_copy(&source, &dest, &tree, &old_paths, &mut on_progress)?;
+            if let Err(e) = fs::remove_dir_all(&source) {
+                warn!("[relocate] Cannot remove old vault {:?}: {}", source, e);
+                report.leftover = Some(source);
+            }
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to move vault to {}", dest.display()))
+        }
+    }
+    Ok(report)
+}
+
+/// Copy the vault next to `dest`, fix its database and rename it into place.
+fn move_by_copy(
+    source: &Path,
+    dest: &Path,
+    tree: &Tree,
+    old_paths: &[PathBuf],
+    on_progress: &mut impl FnMut(RelocateProgress),
+) -> Result<usize> {
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    let staging = dest.with_file_name(format!(".{}.relocating", name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let result = copy_tree(source, &staging, tree, on_progress)
+        .and_then(|()| rewrite_paths(&staging, old_paths, dest))
+        .and_then(|rewritten| {
+            fs::rename(&staging, dest)?;
+            Ok(rewritten)
+        });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::SessionEntry;
+    use tempfile::TempDir;
+
+    fn create_vault(dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir.join("sessions/cursor"))?;
+        fs::write(dir.join("sessions/cursor/s1.json"), "{}")?;
+        let db = VaultDb::open(dir)?;
+        db.upsert_session(&SessionEntry {
+            id: "s1".to_string(),
+            source: "cursor".to_string(),
+            mtime: 1,
+            file_size: 2,
+            title: None,
+            workspace_name: None,
+            created_at: None,
+            vault_path: dir
+                .join("sessions/cursor/s1.json")
+                .to_string_lossy()
+                .to_string(),
+            original_path: "/original/s1.json".to_string(),
+            content_hash: None,
+        })?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_relocate_vault() -> Result<()> {
+        let temp = TempDir::new()?;
+        let root = fs::canonicalize(temp.path())?;
+        let old = root.join("old/vault");
+        create_vault(&old)?;
+
+        let new = root.join("new/vault");
+        let mut last = RelocateProgress::default();
+        let report = relocate_vault(&old, &new, |p| last = p)?;
+        assert!(!old.exists());
+        assert!(new.join("sessions/cursor/s1.json").exists());
+        assert_eq!(report.rewritten_paths, 1);
+        assert_eq!(last.files_done, last.total_files);
+
+        let sessions = VaultDb::open(&new)?.get_all_sessions()?;
+        assert_eq!(
+            PathBuf::from(&sessions[0].vault_path),
+            new.join("sessions/cursor/s1.json")
+        );
+
+        // Not into itself or onto a non-empty directory
+        assert!(relocate_vault(&new, &new.join("inner"), |_| {}).is_err());
+        fs::create_dir_all(&old)?;
+        fs::write(old.join("keep.txt"), "x")?;
+        assert!(relocate_vault(&new, &old, |_| {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_by_copy() -> Result<()> {
+        let temp = TempDir::new()?;
+        let root = fs::canonicalize(temp.path())?;
+        let old = root.join("old");
+        create_vault(&old)?;
+        let new = root.join("new");
+
+        let tree = Tree::scan(&old)?;
+        let mut calls = 0;
+        let rewritten = move_by_copy(&old, &new, &tree, std::slice::from_ref(&old), &mut |_| {
+            calls += 1
+        })?;
+        assert_eq!(rewritten, 1);
+        assert_eq!(calls, tree.files.len());
+        assert!(new.join("sessions/cursor/s1.json").exists());
+        assert!(!root.join(".new.relocating").exists());
+        // The source is only removed by relocate_vault
+        assert!(old.join("vault.db").exists());
+        Ok(())
+    }
+}
@@ -21,6 +21,7 @@ fn create_test_session(id: &str, source: &str, mtime: u64) -> SessionEntry {
         created_at: Some("2024-12-26T10:00:00Z".to_string()),
         vault_path: format!("/vault/{}/{}.json", source, id),
         original_path: format!("/original/{}.json", id),
+        content_hash: None,
     }
 }
 
@@ -6,6 +6,8 @@
 
 use anyhow::{Context, Result};
 use rusqlite::{params, Connection, LoadExtensionGuard, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tracing::info;
 
@@ -102,6 +104,14 @@ fn get_machine_id() -> String {
     format!("{}-{}", hostname, random_suffix)
 }
 
+/// Stable host identifier for machine-local data that must survive restarts
+/// (unlike `machine_id()`, which gets a new random suffix per process).
+fn host_id() -> String {
+    hostname::get()
+        .map(|h| h.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
 /// Lazy-initialized machine ID (generated once per process).
 fn machine_id() -> &'static str {
     use std::sync::OnceLock;
@@ -132,7 +142,7 @@ pub enum UpsertResult {
 }
 
 /// A session entry for the vault database.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SessionEntry {
     pub id: String,
     pub source: String,
@@ -143,6 +153,8 @@ pub struct SessionEntry {
     pub created_at: Option<String>,
     pub vault_path: String,
     pub original_path: String,
+    /// Hex BLAKE3 of the raw session file, if known
+    pub content_hash: Option<String>,
 }
 
 impl VaultDb {
@@ -236,7 +248,8 @@ impl VaultDb {
                 workspace_name TEXT,
                 created_at TEXT,
                 vault_path TEXT NOT NULL DEFAULT '',
-                original_path TEXT NOT NULL DEFAULT ''
+                original_path TEXT NOT NULL DEFAULT '',
+                content_hash TEXT
             );
 
             CREATE INDEX IF NOT EXISTS idx_sessions_source ON sessions(source);
@@ -257,9 +270,126 @@ impl VaultDb {
                 last_synced_db_version INTEGER NOT NULL DEFAULT 0
             );
             INSERT OR IGNORE INTO sync_state (id, last_synced_db_version) VALUES (1, 0);
+
+            -- Crash-safe queue of detected-but-not-yet-ingested file changes
+            -- (machine-local: rows are keyed by host and never replayed elsewhere)
+            CREATE TABLE IF NOT EXISTS pending_changes (
+                host TEXT NOT NULL DEFAULT '',
+                path TEXT NOT NULL,
+                source TEXT NOT NULL DEFAULT '',
+                detected_at INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (host, path)
+            );
+
+            -- Metrics are recorded in local.db, which is never synced
+            DROP TABLE IF EXISTS metrics;
+
+            -- Storage location fingerprints of the last successful ingest (machine-local)
+            CREATE TABLE IF NOT EXISTS location_cache (
+                host TEXT NOT NULL DEFAULT '',
+                path TEXT NOT NULL,
+                fingerprint TEXT NOT NULL,
+                updated_at INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (host, path)
+            );
+
+            -- Models and message counts of sessions, filled in by enrichment from
+            -- the session files (stale once mtime changes)
+            CREATE TABLE IF NOT EXISTS session_stats (
+                session_id TEXT PRIMARY KEY NOT NULL,
+                mtime INTEGER NOT NULL DEFAULT 0,
+                model TEXT,
+                message_count INTEGER NOT NULL DEFAULT 0
+            );
+
+            -- Messages per role and model and text size of parsed conversations,
+            -- cached for `stats` (local and stale once mtime changes, like session_stats)
+            CREATE TABLE IF NOT EXISTS conversation_stats (
+                session_id TEXT PRIMARY KEY NOT NULL,
+                mtime INTEGER NOT NULL DEFAULT 0,
+                user_messages INTEGER NOT NULL DEFAULT 0,
+                assistant_messages INTEGER NOT NULL DEFAULT 0,
+                system_messages INTEGER NOT NULL DEFAULT 0,
+                tool_messages INTEGER NOT NULL DEFAULT 0,
+                info_messages INTEGER NOT NULL DEFAULT 0,
+                characters INTEGER NOT NULL DEFAULT 0,
+                -- JSON object: model -> assistant messages
+                models TEXT NOT NULL DEFAULT '{}'
+            );
+
+            -- Tokens of parsed conversations per day and model, reported by the
+            -- source or estimated from text length; cached with conversation_stats
+            CREATE TABLE IF NOT EXISTS usage (
+                session_id TEXT NOT NULL,
+                source TEXT NOT NULL DEFAULT '',
+                date TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                estimated INTEGER NOT NULL DEFAULT 1,
+                PRIMARY KEY (session_id, date, model)
+            );
+
+            -- MinHash signatures of parsed conversations (content-derived, like
+            -- session_stats) and the sessions linked as duplicates of another
+            CREATE TABLE IF NOT EXISTS session_signatures (
+                session_id TEXT PRIMARY KEY NOT NULL,
+                source TEXT NOT NULL DEFAULT '',
+                shingles INTEGER NOT NULL DEFAULT 0,
+                minhash BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS duplicates (
+                session_id TEXT PRIMARY KEY NOT NULL,
+                canonical_id TEXT NOT NULL,
+                similarity REAL NOT NULL DEFAULT 0
+            );
+
+            -- Notes, ratings (1-5) and follow-up flags the user added to sessions,
+            -- with a full-text index of the notes
+            CREATE TABLE IF NOT EXISTS annotations (
+                session_id TEXT PRIMARY KEY NOT NULL,
+                note TEXT NOT NULL DEFAULT '',
+                rating INTEGER,
+                follow_up INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS annotations_fts USING fts5(
+                note,
+                content='annotations',
+                content_rowid='rowid'
+            );
+            CREATE TRIGGER IF NOT EXISTS annotations_ai AFTER INSERT ON annotations BEGIN
+                INSERT INTO annotations_fts(rowid, note) VALUES (new.rowid, new.note);
+            END;
+            CREATE TRIGGER IF NOT EXISTS annotations_ad AFTER DELETE ON annotations BEGIN
+                INSERT INTO annotations_fts(annotations_fts, rowid, note)
+                VALUES ('delete', old.rowid, old.note);
+            END;
+            CREATE TRIGGER IF NOT EXISTS annotations_au AFTER UPDATE ON annotations BEGIN
+                INSERT INTO annotations_fts(annotations_fts, rowid, note)
+                VALUES ('delete', old.rowid, old.note);
+                INSERT INTO annotations_fts(rowid, note) VALUES (new.rowid, new.note);
+            END;
+            -- Tags of sessions, one row per tag: extracted from the parsed
+            -- conversation (added = 0) or added by the user (added = 1)
+            CREATE TABLE IF NOT EXISTS tags (
+                session_id TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                added INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (session_id, tag, added)
+            );
+
+            -- Sync state, dirty files and stage hashes are recorded in
+            -- local.db, which is never synced
+            DROP TABLE IF EXISTS remote_sync_state;
+            DROP TABLE IF EXISTS dirty_files;
+            DROP TABLE IF EXISTS file_hashes;
         ",
         )?;
 
+        self.add_content_hash_column()?;
+        self.merge_tag_tables()?;
+
         // Upgrade sessions table to CRR if cr-sqlite is loaded
         if self.is_crsqlite_loaded() {
             info!("[VaultDb] Upgrading sessions table to CRR...");
@@ -273,6 +403,68 @@ impl VaultDb {
         Ok(())
     }
 
+    /// Add `sessions.content_hash` to vaults created before it existed. A
+    /// table already upgraded to CRR is altered through cr-sqlite, which keeps
+    /// its change tracking in step.
+    fn add_content_hash_column(&self) -> Result<()> {
+        if self
+            .conn
+            .prepare("SELECT content_hash FROM sessions LIMIT 0")
+            .is_ok()
+        {
+            return Ok(());
+        }
+        let crr = self.is_crsqlite_loaded()
+            && self
+                .conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE name = 'sessions__crsql_clock'",
+                    [],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+        if crr {
+            self.conn
+                .query_row("SELECT crsql_begin_alter('sessions')", [], |_| Ok(()))?;
+        }
+        self.conn
+            .execute("ALTER TABLE sessions ADD COLUMN content_hash TEXT", [])?;
+        if crr {
+            self.conn
+                .query_row("SELECT crsql_commit_alter('sessions')", [], |_| Ok(()))?;
+        }
+        info!("[VaultDb] Added content_hash column to sessions");
+        Ok(())
+    }
+
+    /// Move tags of vaults that kept user tags (`annotation_tags`) and
+    /// extracted tags (`session_tags`) apart into the one `tags` table.
+    fn merge_tag_tables(&self) -> Result<()> {
+        for (table, added) in [("annotation_tags", true), ("session_tags", false)] {
+            let exists = self
+                .conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    params![table],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            if !exists {
+                continue;
+            }
+            self.conn.execute_batch(&format!(
+                "INSERT OR IGNORE INTO tags (session_id, tag, added)
+                    SELECT session_id, tag, {} FROM {};
+                 DROP TABLE {};",
+                added as i32, table, table
+            ))?;
+            info!("[VaultDb] Merged {} into tags", table);
+        }
+        Ok(())
+    }
+
     /// Get the current machine ID.
     pub fn get_machine_id(&self) -> &str {
         machine_id()
@@ -284,192 +476,583 @@ impl VaultDb {
         &self.conn
     }
 
-    /// Upsert a session with conflict resolution (keep newest by mtime).
+    /// Upsert a session with conflict resolution (see [`upsert_one`]).
     pub fn upsert_session(&self, session: &SessionEntry) -> Result<UpsertResult> {
-        // Check if session exists
-        let existing: Option<(i64, String)> = self
+        upsert_one(&self.conn, session, chrono::Utc::now().timestamp())
+    }
+
+    /// Upsert multiple sessions in a transaction.
+    pub fn upsert_batch(&mut self, sessions: &[SessionEntry]) -> Result<BatchResult> {
+        let tx = self.conn.transaction()?;
+        let result = upsert_all(&tx, sessions)?;
+        tx.commit()?;
+        Ok(result)
+    }
+
+    /// Upsert the sessions of an ingest and log it, in one transaction.
+    pub fn ingest_batch(&mut self, sessions: &[SessionEntry]) -> Result<BatchResult> {
+        let tx = self.conn.transaction()?;
+        let result = upsert_all(&tx, sessions)?;
+        insert_sync_log(&tx, "ingest", Some(&format!("{} sessions", sessions.len())))?;
+        tx.commit()?;
+        Ok(result)
+    }
+
+    /// Get all sessions from the database.
+    pub fn get_all_sessions(&self) -> Result<Vec<SessionEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, source, mtime, file_size, title, workspace_name,
+                    created_at, vault_path, original_path, content_hash
+             FROM sessions
+             ORDER BY mtime DESC",
+        )?;
+
+        let rows = stmt.query_map([], session_from_row)?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?);
+        }
+
+        Ok(sessions)
+    }
+
+    /// A session by ID.
+    pub fn get_session(&self, id: &str) -> Result<Option<SessionEntry>> {
+        Ok(self
             .conn
             .query_row(
-                "SELECT mtime, machine_id FROM sessions WHERE id = ?1",
-                params![session.id],
-                |row| Ok((row.get(0)?, row.get(1)?)),
+                "SELECT id, source, mtime, file_size, title, workspace_name,
+                        created_at, vault_path, original_path, content_hash
+                 FROM sessions
+                 WHERE id = ?1",
+                params![id],
+                session_from_row,
             )
-            .optional()?;
+            .optional()?)
+    }
 
-        let now = chrono::Utc::now().timestamp();
+    /// Sessions not enriched yet, or changed since they were.
+    pub fn sessions_missing_metadata(&self) -> Result<Vec<SessionEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.source, s.mtime, s.file_size, s.title, s.workspace_name,
+                    s.created_at, s.vault_path, s.original_path, s.content_hash
+             FROM sessions s LEFT JOIN session_stats t ON t.session_id = s.id
+             WHERE s.created_at IS NULL OR t.mtime IS NULL OR t.mtime != s.mtime",
+        )?;
+        let rows = stmt.query_map([], session_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 
-        match existing {
-            Some((existing_mtime, _existing_machine)) => {
-                if session.mtime as i64 > existing_mtime {
-                    // New version is newer, update
-                    self.conn.execute(
-                        "UPDATE sessions SET
-                            source = ?2, machine_id = ?3, mtime = ?4,
-                            file_size = ?5, last_synced = ?6, title = ?7,
-                            workspace_name = ?8, created_at = ?9,
-                            vault_path = ?10, original_path = ?11
-                         WHERE id = ?1",
-                        params![
-                            session.id,
-                            session.source,
-                            machine_id(),
-                            session.mtime as i64,
-                            session.file_size as i64,
-                            now,
-                            session.title,
-                            session.workspace_name,
-                            session.created_at,
-                            session.vault_path,
-                            session.original_path
-                        ],
-                    )?;
-                    Ok(UpsertResult::Updated)
-                } else if (session.mtime as i64) < existing_mtime {
-                    // Existing is newer, skip
-                    Ok(UpsertResult::Skipped {
-                        reason: "Remote version is newer".into(),
-                    })
-                } else {
-                    // Same mtime, no change
-                    Ok(UpsertResult::NoChange)
-                }
+    /// Store enriched metadata: titles and creation times fill in missing
+    /// values only, models and message counts replace the previous ones.
+    pub fn set_session_metadata(&mut self, metadata: &[EnrichedMetadata]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut updated = 0;
+        {
+            let mut sessions = tx.prepare(
+                "UPDATE sessions SET
+                    title = COALESCE(title, ?2),
+                    created_at = COALESCE(created_at, ?3)
+                 WHERE id = ?1",
+            )?;
+            let mut stats = tx.prepare(
+                "INSERT INTO session_stats (session_id, mtime, model, message_count)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(session_id) DO UPDATE SET
+                    mtime = excluded.mtime, model = excluded.model,
+                    message_count = excluded.message_count",
+            )?;
+            for m in metadata {
+                updated += sessions.execute(params![m.id, m.title, m.created_at])?;
+                stats.execute(params![
+                    m.id,
+                    m.mtime as i64,
+                    m.model,
+                    m.message_count as i64
+                ])?;
             }
-            None => {
-                // Insert new session
-                self.conn.execute(
-                    "INSERT INTO sessions
-                        (id, source, machine_id, mtime, file_size, last_synced,
-                         title, workspace_name, created_at, vault_path, original_path)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-                    params![
-                        session.id,
-                        session.source,
-                        machine_id(),
-                        session.mtime as i64,
-                        session.file_size as i64,
-                        now,
-                        session.title,
-                        session.workspace_name,
-                        session.created_at,
-                        session.vault_path,
-                        session.original_path
-                    ],
-                )?;
-                Ok(UpsertResult::Inserted)
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Sessions without conversation stats, or changed since they were
+    /// computed.
+    pub fn sessions_missing_conversation_stats(&self) -> Result<Vec<SessionEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.source, s.mtime, s.file_size, s.title, s.workspace_name,
+                    s.created_at, s.vault_path, s.original_path, s.content_hash
+             FROM sessions s LEFT JOIN conversation_stats c ON c.session_id = s.id
+             WHERE c.mtime IS NULL OR c.mtime != s.mtime",
+        )?;
+        let rows = stmt.query_map([], session_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Store conversation stats and token usage, replacing the previous ones
+    /// of the same sessions.
+    pub fn set_conversation_stats(
+        &mut self,
+        stats: &[ConversationStats],
+        usage: &[SessionUsage],
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut clear = tx.prepare("DELETE FROM usage WHERE session_id = ?1")?;
+            for s in stats {
+                clear.execute(params![s.session_id])?;
+            }
+            let mut insert = tx.prepare(
+                "INSERT INTO usage
+                    (session_id, source, date, model, input_tokens, output_tokens, estimated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT(session_id, date, model) DO UPDATE SET
+                    input_tokens = input_tokens + excluded.input_tokens,
+                    output_tokens = output_tokens + excluded.output_tokens,
+                    estimated = estimated OR excluded.estimated",
+            )?;
+            for u in usage {
+                insert.execute(params![
+                    u.session_id,
+                    u.source,
+                    u.date,
+                    u.model,
+                    u.input_tokens as i64,
+                    u.output_tokens as i64,
+                    u.estimated,
+                ])?;
+            }
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO conversation_stats
+                    (session_id, mtime, user_messages, assistant_messages, system_messages,
+                     tool_messages, info_messages, characters, models)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for s in stats {
+                stmt.execute(params![
+                    s.session_id,
+                    s.mtime as i64,
+                    s.messages.user as i64,
+                    s.messages.assistant as i64,
+                    s.messages.system as i64,
+                    s.messages.tool as i64,
+                    s.messages.info as i64,
+                    s.characters as i64,
+                    serde_json::to_string(&s.models)?,
+                ])?;
             }
         }
+        tx.commit()?;
+        Ok(())
     }
 
-    /// Upsert multiple sessions in a transaction.
-    pub fn upsert_batch(&mut self, sessions: &[SessionEntry]) -> Result<BatchResult> {
+    /// Token usage of every session but duplicates, oldest first.
+    pub fn session_usage(&self) -> Result<Vec<SessionUsage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, source, date, model, input_tokens, output_tokens, estimated
+             FROM usage WHERE session_id NOT IN (SELECT session_id FROM duplicates)
+             ORDER BY date, session_id, model",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SessionUsage {
+                session_id: row.get(0)?,
+                source: row.get(1)?,
+                date: row.get(2)?,
+                model: row.get(3)?,
+                input_tokens: row.get::<_, i64>(4)? as u64,
+                output_tokens: row.get::<_, i64>(5)? as u64,
+                estimated: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Every session but duplicates, with its conversation stats when they
+    /// have been computed.
+    pub fn sessions_with_conversation_stats(
+        &self,
+    ) -> Result<Vec<(SessionEntry, Option<ConversationStats>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.source, s.mtime, s.file_size, s.title, s.workspace_name,
+                    s.created_at, s.vault_path, s.original_path, s.content_hash,
+                    c.mtime, c.user_messages, c.assistant_messages, c.system_messages,
+                    c.tool_messages, c.info_messages, c.characters, c.models
+             FROM sessions s LEFT JOIN conversation_stats c ON c.session_id = s.id
+             WHERE s.id NOT IN (SELECT session_id FROM duplicates)",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let session = session_from_row(row)?;
+            let Some(mtime) = row.get::<_, Option<i64>>(10)? else {
+                return Ok((session, None));
+            };
+            let models: String = row.get(17)?;
+            let stats = ConversationStats {
+                session_id: session.id.clone(),
+                mtime: mtime as u64,
+                messages: RoleCounts {
+                    user: row.get::<_, i64>(11)? as u64,
+                    assistant: row.get::<_, i64>(12)? as u64,
+                    system: row.get::<_, i64>(13)? as u64,
+                    tool: row.get::<_, i64>(14)? as u64,
+                    info: row.get::<_, i64>(15)? as u64,
+                },
+                characters: row.get::<_, i64>(16)? as u64,
+                models: serde_json::from_str(&models).unwrap_or_default(),
+            };
+            Ok((session, Some(stats)))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Store MinHash signatures of parsed conversations.
+    pub fn save_signatures(&mut self, signatures: &[SessionSignature]) -> Result<()> {
         let tx = self.conn.transaction()?;
-        let now = chrono::Utc::now().timestamp();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO session_signatures (session_id, source, shingles, minhash)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(session_id) DO UPDATE SET
+                    source = excluded.source, shingles = excluded.shingles,
+                    minhash = excluded.minhash",
+            )?;
+            for s in signatures {
+                let minhash: Vec<u8> = s.minhash.iter().flat_map(|h| h.to_le_bytes()).collect();
+                stmt.execute(params![s.session_id, s.source, s.shingles as i64, minhash])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
 
-        let mut inserted = 0;
-        let mut updated = 0;
-        let mut skipped = 0;
+    /// All stored MinHash signatures.
+    pub fn signatures(&self) -> Result<Vec<SessionSignature>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT session_id, source, shingles, minhash FROM session_signatures")?;
+        let rows = stmt.query_map([], |row| {
+            let minhash: Vec<u8> = row.get(3)?;
+            Ok(SessionSignature {
+                session_id: row.get(0)?,
+                source: row.get(1)?,
+                shingles: row.get::<_, i64>(2)? as u64,
+                minhash: minhash
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 
-        for session in sessions {
-            let existing: Option<i64> = tx
-                .query_row(
-                    "SELECT mtime FROM sessions WHERE id = ?1",
-                    params![session.id],
-                    |row| row.get(0),
-                )
-                .optional()?;
-
-            match existing {
-                Some(existing_mtime) => {
-                    if session.mtime as i64 > existing_mtime {
-                        tx.execute(
-                            "UPDATE sessions SET
-                                source = ?2, machine_id = ?3, mtime = ?4,
-                                file_size = ?5, last_synced = ?6, title = ?7,
-                                workspace_name = ?8, created_at = ?9,
-                                vault_path = ?10, original_path = ?11
-                             WHERE id = ?1",
-                            params![
-                                session.id,
-                                session.source,
-                                machine_id(),
-                                session.mtime as i64,
-                                session.file_size as i64,
-                                now,
-                                session.title,
-                                session.workspace_name,
-                                session.created_at,
-                                session.vault_path,
-                                session.original_path
-                            ],
-                        )?;
-                        updated += 1;
-                    } else {
-                        skipped += 1;
-                    }
-                }
-                None => {
-                    tx.execute(
-                        "INSERT INTO sessions
-                            (id, source, machine_id, mtime, file_size, last_synced,
-                             title, workspace_name, created_at, vault_path, original_path)
-                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-                        params![
-                            session.id,
-                            session.source,
-                            machine_id(),
-                            session.mtime as i64,
-                            session.file_size as i64,
-                            now,
-                            session.title,
-                            session.workspace_name,
-                            session.created_at,
-                            session.vault_path,
-                            session.original_path
-                        ],
-                    )?;
-                    inserted += 1;
-                }
+    /// Replace all duplicate links.
+    pub fn replace_duplicates(&mut self, links: &[DuplicateLink]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM duplicates", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO duplicates (session_id, canonical_id, similarity)
+                 VALUES (?1, ?2, ?3)",
+            )?;
+            for link in links {
+                stmt.execute(params![link.session_id, link.canonical_id, link.similarity])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sessions linked as duplicates of another, most similar first.
+    pub fn duplicates(&self) -> Result<Vec<DuplicateLink>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, canonical_id, similarity FROM duplicates
+             ORDER BY similarity DESC, session_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(DuplicateLink {
+                session_id: row.get(0)?,
+                canonical_id: row.get(1)?,
+                similarity: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// IDs of sessions to hide because another session holds the same conversation.
+    pub fn duplicate_ids(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare("SELECT session_id FROM duplicates")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Annotation of a session, if it has one.
+    pub fn annotation(&self, session_id: &str) -> Result<Option<Annotation>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("{} WHERE session_id = ?1", SELECT_ANNOTATIONS),
+                params![session_id],
+                annotation_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Apply an update to the annotation of a session. Returns the resulting
+    /// annotation, or `None` if it was left empty and removed.
+    pub fn annotate(
+        &self,
+        session_id: &str,
+        update: &AnnotationUpdate,
+    ) -> Result<Option<Annotation>> {
+        let mut annotation = self
+            .annotation(session_id)?
+            .unwrap_or_else(|| Annotation::new(session_id));
+        if let Some(note) = &update.note {
+            annotation.note = note.trim().to_string();
+        }
+        match update.rating {
+            Some(0) => annotation.rating = None,
+            Some(rating @ 1..=5) => annotation.rating = Some(rating),
+            Some(rating) => anyhow::bail!("Rating must be 1-5 (0 clears it), got {}", rating),
+            None => {}
+        }
+        if let Some(follow_up) = update.follow_up {
+            annotation.follow_up = follow_up;
+        }
+        if let Some(tags) = &update.tags {
+            annotation.tags = normalize_tags(tags);
+        }
+
+        if annotation.is_empty() {
+            self.delete_annotation(session_id)?;
+            return Ok(None);
+        }
+        annotation.updated_at = chrono::Utc::now().timestamp_millis();
+        self.conn.execute(
+            "INSERT INTO annotations (session_id, note, rating, follow_up, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(session_id) DO UPDATE SET
+                note = excluded.note, rating = excluded.rating,
+                follow_up = excluded.follow_up, updated_at = excluded.updated_at",
+            params![
+                annotation.session_id,
+                annotation.note,
+                annotation.rating,
+                annotation.follow_up,
+                annotation.updated_at
+            ],
+        )?;
+        if update.tags.is_some() {
+            self.conn.execute(
+                "DELETE FROM tags WHERE session_id = ?1 AND added = 1",
+                params![session_id],
+            )?;
+            let mut stmt = self
+                .conn
+                .prepare("INSERT INTO tags (session_id, tag, added) VALUES (?1, ?2, 1)")?;
+            for tag in &annotation.tags {
+                stmt.execute(params![session_id, tag])?;
             }
         }
+        Ok(Some(annotation))
+    }
 
+    /// Remove the annotation of a session. Returns false if it had none.
+    pub fn delete_annotation(&self, session_id: &str) -> Result<bool> {
+        self.conn.execute(
+            "DELETE FROM tags WHERE session_id = ?1 AND added = 1",
+            params![session_id],
+        )?;
+        let deleted = self.conn.execute(
+            "DELETE FROM annotations WHERE session_id = ?1",
+            params![session_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// All annotations (only those flagged for follow-up if `follow_up_only`),
+    /// most recently updated first.
+    pub fn annotations(&self, follow_up_only: bool) -> Result<Vec<Annotation>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE follow_up = 1 OR ?1 = 0 ORDER BY updated_at DESC",
+            SELECT_ANNOTATIONS
+        ))?;
+        let rows = stmt.query_map(params![follow_up_only], annotation_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Annotations whose note matches an FTS5 query, best match first.
+    pub fn search_annotations(&self, query: &str, limit: usize) -> Result<Vec<Annotation>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.session_id, a.note, a.rating, a.follow_up, a.updated_at,
+                (SELECT group_concat(tag, char(31)) FROM tags t
+                 WHERE t.session_id = a.session_id AND t.added = 1)
+             FROM annotations a
+             JOIN annotations_fts fts ON a.rowid = fts.rowid
+             WHERE annotations_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![query, limit as i64], annotation_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Store the extracted tags of sessions, replacing their previous ones.
+    pub fn set_session_tags(&mut self, tags: &[(String, Vec<String>)]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut clear = tx.prepare("DELETE FROM tags WHERE session_id = ?1 AND added = 0")?;
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO tags (session_id, tag, added) VALUES (?1, ?2, 0)",
+            )?;
+            for (session_id, session_tags) in tags {
+                clear.execute(params![session_id])?;
+                for tag in normalize_tags(session_tags) {
+                    insert.execute(params![session_id, tag])?;
+                }
+            }
+        }
         tx.commit()?;
+        Ok(())
+    }
 
-        Ok(BatchResult {
-            inserted,
-            updated,
-            skipped,
-        })
+    /// Tags extracted from a session, sorted (user tags are in its
+    /// [`Annotation`]).
+    pub fn session_tags(&self, session_id: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM tags WHERE session_id = ?1 AND added = 0 ORDER BY tag")?;
+        let rows = stmt.query_map(params![session_id], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
-    /// Get all sessions from the database.
-    pub fn get_all_sessions(&self) -> Result<Vec<SessionEntry>> {
+    /// Every tag, extracted or added by the user, with the number of sessions
+    /// holding it, most used first.
+    pub fn tag_counts(&self) -> Result<Vec<(String, u64)>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, source, mtime, file_size, title, workspace_name,
-                    created_at, vault_path, original_path
-             FROM sessions
-             ORDER BY mtime DESC",
+            "SELECT tag, COUNT(DISTINCT session_id) FROM tags
+             GROUP BY tag ORDER BY 2 DESC, tag",
         )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 
-        let rows = stmt.query_map([], |row| {
-            Ok(SessionEntry {
-                id: row.get(0)?,
-                source: row.get(1)?,
-                mtime: row.get::<_, i64>(2)? as u64,
-                file_size: row.get::<_, i64>(3)? as u64,
-                title: row.get(4)?,
-                workspace_name: row.get(5)?,
-                created_at: row.get(6)?,
-                vault_path: row.get(7)?,
-                original_path: row.get(8)?,
+    /// Sessions holding every tag in `tags`, extracted or added by the user
+    /// (case-insensitive).
+    pub fn sessions_with_tags(&self, tags: &[String]) -> Result<HashSet<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT session_id FROM tags WHERE lower(tag) = lower(?1)")?;
+        let mut sessions: Option<HashSet<String>> = None;
+        for tag in tags {
+            let tagged = stmt
+                .query_map(params![tag.trim()], |row| row.get(0))?
+                .collect::<rusqlite::Result<HashSet<String>>>()?;
+            sessions = Some(match sessions {
+                Some(sessions) => sessions.intersection(&tagged).cloned().collect(),
+                None => tagged,
+            });
+        }
+        Ok(sessions.unwrap_or_default())
+    }
+
+    /// Sessions created per day since `since` (`YYYY-MM-DD`), oldest first.
+    pub fn sessions_per_day(&self, since: &str) -> Result<Vec<DayCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT substr(created_at, 1, 10) AS day, COUNT(*)
+             FROM sessions WHERE created_at >= ?1 AND id NOT IN (SELECT session_id FROM duplicates)
+             GROUP BY day ORDER BY day",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(DayCount {
+                date: row.get(0)?,
+                count: row.get::<_, i64>(1)? as u64,
             })
         })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 
-        let mut sessions = Vec::new();
-        for row in rows {
-            sessions.push(row?);
+    /// Sessions and messages per model, for sessions created since `since`,
+    /// most messages first. Sessions not enriched yet are left out.
+    pub fn messages_per_model(&self, since: &str) -> Result<Vec<ModelCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT COALESCE(t.model, 'unknown') AS model, COUNT(*), SUM(t.message_count)
+             FROM sessions s JOIN session_stats t ON t.session_id = s.id
+             WHERE s.created_at >= ?1 AND s.id NOT IN (SELECT session_id FROM duplicates)
+             GROUP BY model ORDER BY 3 DESC, model",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(ModelCount {
+                model: row.get(0)?,
+                sessions: row.get::<_, i64>(1)? as u64,
+                messages: row.get::<_, i64>(2)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// The `limit` workspaces with the most sessions created since `since`,
+    /// with their sessions per week (weeks start on Monday).
+    pub fn workspace_activity(&self, since: &str, limit: usize) -> Result<Vec<WorkspaceActivity>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT workspace_name, COUNT(*), MAX(created_at)
+             FROM sessions
+             WHERE created_at >= ?1 AND workspace_name IS NOT NULL
+               AND id NOT IN (SELECT session_id FROM duplicates)
+             GROUP BY workspace_name ORDER BY 2 DESC, workspace_name
+             LIMIT ?2",
+        )?;
+        let mut workspaces = stmt
+            .query_map(params![since, limit as i64], |row| {
+                Ok(WorkspaceActivity {
+                    workspace: row.get(0)?,
+                    sessions: row.get::<_, i64>(1)? as u64,
+                    last_active: row.get(2)?,
+                    weekly: Vec::new(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut weekly = self.conn.prepare(
+            "SELECT date(substr(created_at, 1, 10), '-6 days', 'weekday 1') AS week, COUNT(*)
+             FROM sessions WHERE created_at >= ?1 AND workspace_name = ?2
+               AND id NOT IN (SELECT session_id FROM duplicates)
+             GROUP BY week ORDER BY week",
+        )?;
+        for activity in &mut workspaces {
+            let rows = weekly.query_map(params![since, activity.workspace], |row| {
+                Ok(DayCount {
+                    date: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as u64,
+                })
+            })?;
+            activity.weekly = rows.collect::<rusqlite::Result<_>>()?;
         }
+        Ok(workspaces)
+    }
 
-        Ok(sessions)
+    /// Sessions created from `since` up to (not including) `until`
+    /// (`YYYY-MM-DD`), most messages first. Duplicates are left out.
+    pub fn sessions_created_between(&self, since: &str, until: &str) -> Result<Vec<NewSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.source, s.title, s.workspace_name, s.created_at,
+                    COALESCE(t.message_count, 0)
+             FROM sessions s LEFT JOIN session_stats t ON t.session_id = s.id
+             WHERE s.created_at >= ?1 AND s.created_at < ?2
+               AND s.id NOT IN (SELECT session_id FROM duplicates)
+             ORDER BY 6 DESC, s.created_at DESC",
+        )?;
+        let rows = stmt.query_map(params![since, until], |row| {
+            Ok(NewSession {
+                id: row.get(0)?,
+                source: row.get(1)?,
+                title: row.get(2)?,
+                workspace_name: row.get(3)?,
+                created_at: row.get(4)?,
+                message_count: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
     /// Check if a session exists and get its mtime.
@@ -486,15 +1069,159 @@ impl VaultDb {
         Ok(mtime.map(|m| m as u64))
     }
 
+    /// Content hash recorded for a session, if any.
+    pub fn get_session_hash(&self, id: &str) -> Result<Option<String>> {
+        let hash: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT content_hash FROM sessions WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(hash.flatten())
+    }
+
+    /// Point stored vault file paths at a moved vault (`old` -> `new` prefix).
+    pub fn rewrite_vault_paths(&self, old: &Path, new: &Path) -> Result<usize> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET vault_path = ?2 || substr(vault_path, length(?1) + 1)
+             WHERE vault_path = ?1
+                OR substr(vault_path, 1, length(?1) + 1) IN (?1 || '/', ?1 || '\\')",
+            params![old.to_string_lossy(), new.to_string_lossy()],
+        )?;
+        Ok(updated)
+    }
+
+    /// Storage location fingerprints recorded on this host, by path.
+    pub fn location_fingerprints(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, fingerprint FROM location_cache WHERE host = ?1")?;
+        let rows = stmt.query_map(params![host_id()], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut fingerprints = HashMap::new();
+        for row in rows {
+            let (path, fingerprint) = row?;
+            fingerprints.insert(path, fingerprint);
+        }
+        Ok(fingerprints)
+    }
+
+    /// Store storage location fingerprints for this host.
+    pub fn save_location_fingerprints(&mut self, fingerprints: &[(String, String)]) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO location_cache (host, path, fingerprint, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(host, path) DO UPDATE SET
+                    fingerprint = excluded.fingerprint, updated_at = excluded.updated_at",
+            )?;
+            let host = host_id();
+            for (path, fingerprint) in fingerprints {
+                stmt.execute(params![host, path, fingerprint, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Log a sync action.
     pub fn log_sync(&self, action: &str, details: Option<&str>) -> Result<()> {
-        let now = chrono::Utc::now().timestamp();
-        self.conn.execute(
-            "INSERT INTO sync_log (machine_id, timestamp, action, details)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![machine_id(), now, action, details],
+        insert_sync_log(&self.conn, action, details)
+    }
+
+    /// Latest entries of the sync log (ingests and syncs of every machine),
+    /// newest first.
+    pub fn recent_sync_log(&self, limit: usize) -> Result<Vec<SyncLogEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, action, details
+             FROM sync_log
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?1",
         )?;
-        Ok(())
+
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(SyncLogEntry {
+                timestamp: row.get(0)?,
+                action: row.get(1)?,
+                details: row.get(2)?,
+            })
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+
+    /// Persist detected file changes so they survive a crash before ingest.
+    ///
+    /// Re-queuing an already pending path just refreshes its timestamp.
+    pub fn enqueue_pending_changes(&mut self, source: &str, paths: &[PathBuf]) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO pending_changes (host, path, source, detected_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(host, path) DO UPDATE SET detected_at = excluded.detected_at",
+            )?;
+            let host = host_id();
+            for path in paths {
+                stmt.execute(params![host, path.to_string_lossy(), source, now])?;
+            }
+        }
+        tx.commit()?;
+        Ok(paths.len())
+    }
+
+    /// Get pending changes recorded on this host, oldest first.
+    pub fn pending_changes(&self) -> Result<Vec<PendingChange>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, source, detected_at FROM pending_changes
+             WHERE host = ?1
+             ORDER BY detected_at ASC",
+        )?;
+
+        let rows = stmt.query_map(params![host_id()], |row| {
+            Ok(PendingChange {
+                path: PathBuf::from(row.get::<_, String>(0)?),
+                source: row.get(1)?,
+                detected_at: row.get(2)?,
+            })
+        })?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            changes.push(row?);
+        }
+        Ok(changes)
+    }
+
+    /// Remove pending changes detected at or before `until` (unix milliseconds).
+    ///
+    /// Called after a successful ingest; changes that arrived during the ingest
+    /// stay queued for the next run.
+    pub fn clear_pending_changes(&self, until: i64) -> Result<usize> {
+        let removed = self.conn.execute(
+            "DELETE FROM pending_changes WHERE host = ?1 AND detected_at <= ?2",
+            params![host_id(), until],
+        )?;
+        Ok(removed)
+    }
+
+    /// Remove pending changes of one source detected at or before `until`,
+    /// after an ingest limited to that source.
+    pub fn clear_source_pending_changes(&self, source: &str, until: i64) -> Result<usize> {
+        let removed = self.conn.execute(
+            "DELETE FROM pending_changes WHERE host = ?1 AND source = ?2 AND detected_at <= ?3",
+            params![host_id(), source, until],
+        )?;
+        Ok(removed)
     }
 
     /// Get total count of sessions.
@@ -516,6 +1243,371 @@ impl VaultDb {
     }
 }
 
+/// A detected file change waiting to be ingested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingChange {
+    pub path: PathBuf,
+    pub source: String,
+    /// Unix timestamp (milliseconds) when the change was detected
+    pub detected_at: i64,
+}
+
+/// Entry of the sync log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncLogEntry {
+    /// Unix timestamp (seconds)
+    pub timestamp: i64,
+    /// What happened (`ingest`, `sync`)
+    pub action: String,
+    pub details: Option<String>,
+}
+
+/// Metadata of a session read from its file by enrichment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnrichedMetadata {
+    pub id: String,
+    /// Modification time of the session when it was read
+    pub mtime: u64,
+    pub title: Option<String>,
+    pub created_at: Option<String>,
+    pub model: Option<String>,
+    /// User and assistant messages
+    pub message_count: u64,
+}
+
+/// Messages of a conversation (or of many) by role.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RoleCounts {
+    pub user: u64,
+    pub assistant: u64,
+    pub system: u64,
+    pub tool: u64,
+    pub info: u64,
+}
+
+impl RoleCounts {
+    /// Messages of every role.
+    pub fn total(&self) -> u64 {
+        self.user + self.assistant + self.system + self.tool + self.info
+    }
+
+    /// Add the counts of `other`.
+    pub fn add(&mut self, other: &RoleCounts) {
+        self.user += other.user;
+        self.assistant += other.assistant;
+        self.system += other.system;
+        self.tool += other.tool;
+        self.info += other.info;
+    }
+}
+
+/// Message counts and text size of a parsed conversation (see
+/// [`crate::analytics::vault_stats`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversationStats {
+    pub session_id: String,
+    /// Modification time of the session when it was read
+    pub mtime: u64,
+    pub messages: RoleCounts,
+    /// Characters of all messages
+    pub characters: u64,
+    /// Assistant messages per model
+    pub models: BTreeMap<String, u64>,
+}
+
+/// Tokens a session used with one model on one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub source: String,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated from text length rather than reported by the source
+    pub estimated: bool,
+}
+
+/// MinHash signature of a parsed conversation (see [`crate::dedupe`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSignature {
+    pub session_id: String,
+    pub source: String,
+    /// Distinct word shingles of the conversation
+    pub shingles: u64,
+    pub minhash: Vec<u32>,
+}
+
+/// A session holding the same conversation as `canonical_id`, the capture
+/// that is kept.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateLink {
+    pub session_id: String,
+    pub canonical_id: String,
+    /// Estimated Jaccard similarity of the two conversations
+    pub similarity: f64,
+}
+
+/// Note, tags, rating and follow-up flag the user added to a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Annotation {
+    pub session_id: String,
+    /// Free-form note (empty if none)
+    pub note: String,
+    /// Tags, sorted and without duplicates
+    pub tags: Vec<String>,
+    /// Rating from 1 to 5
+    pub rating: Option<u8>,
+    /// Flagged to come back to
+    pub follow_up: bool,
+    /// Unix timestamp (milliseconds) of the last change
+    pub updated_at: i64,
+}
+
+impl Annotation {
+    fn new(session_id: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            note: String::new(),
+            tags: Vec::new(),
+            rating: None,
+            follow_up: false,
+            updated_at: 0,
+        }
+    }
+
+    /// True if there is nothing left to store.
+    pub fn is_empty(&self) -> bool {
+        self.note.is_empty() && self.tags.is_empty() && self.rating.is_none() && !self.follow_up
+    }
+}
+
+/// Changes to the annotation of a session; `None` fields are left as they are.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct AnnotationUpdate {
+    /// New note (an empty note removes it)
+    pub note: Option<String>,
+    /// New tags, replacing the existing ones (empty removes them)
+    pub tags: Option<Vec<String>>,
+    /// New rating from 1 to 5, or 0 to clear it
+    pub rating: Option<u8>,
+    pub follow_up: Option<bool>,
+}
+
+const SELECT_ANNOTATIONS: &str = "SELECT session_id, note, rating, follow_up, updated_at,
+        (SELECT group_concat(tag, char(31)) FROM tags t
+         WHERE t.session_id = annotations.session_id AND t.added = 1)
+     FROM annotations";
+
+/// Trimmed, non-empty tags, sorted and without duplicates.
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn annotation_from_row(row: &rusqlite::Row) -> rusqlite::Result<Annotation> {
+    Ok(Annotation {
+        session_id: row.get(0)?,
+        note: row.get(1)?,
+        rating: row.get(2)?,
+        follow_up: row.get(3)?,
+        updated_at: row.get(4)?,
+        tags: row
+            .get::<_, Option<String>>(5)?
+            .map(|tags| {
+                let mut tags: Vec<String> = tags.split('\u{1f}').map(str::to_string).collect();
+                tags.sort();
+                tags
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// Count for one day (or the first day of a week).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayCount {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub count: u64,
+}
+
+/// Sessions and messages of one model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelCount {
+    pub model: String,
+    pub sessions: u64,
+    pub messages: u64,
+}
+
+/// Sessions of one workspace, in total and per week.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceActivity {
+    pub workspace: String,
+    pub sessions: u64,
+    /// Creation time of the newest session
+    pub last_active: Option<String>,
+    pub weekly: Vec<DayCount>,
+}
+
+/// Session created in a period, for digests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewSession {
+    pub id: String,
+    pub source: String,
+    pub title: Option<String>,
+    pub workspace_name: Option<String>,
+    pub created_at: Option<String>,
+    /// User and assistant messages (0 if not enriched yet)
+    pub message_count: u64,
+}
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<SessionEntry> {
+    Ok(SessionEntry {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        mtime: row.get::<_, i64>(2)? as u64,
+        file_size: row.get::<_, i64>(3)? as u64,
+        title: row.get(4)?,
+        workspace_name: row.get(5)?,
+        created_at: row.get(6)?,
+        vault_path: row.get(7)?,
+        original_path: row.get(8)?,
+        content_hash: row.get(9)?,
+    })
+}
+
+/// Upsert a session, keeping the newest version by mtime.
+///
+/// Content hashes take precedence when both versions have one: the same
+/// content is no change even with a newer mtime (an IDE rewriting a file
+/// as-is, or another machine's clock running ahead), which only moves the
+/// recorded mtime forward; different content with the same mtime is an update.
+fn upsert_one(conn: &Connection, session: &SessionEntry, now: i64) -> Result<UpsertResult> {
+    let existing: Option<(i64, Option<String>)> = conn
+        .query_row(
+            "SELECT mtime, content_hash FROM sessions WHERE id = ?1",
+            params![session.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let mtime = session.mtime as i64;
+
+    let Some((existing_mtime, existing_hash)) = existing else {
+        conn.execute(
+            "INSERT INTO sessions
+                (id, source, machine_id, mtime, file_size, last_synced,
+                 title, workspace_name, created_at, vault_path, original_path, content_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                session.id,
+                session.source,
+                machine_id(),
+                mtime,
+                session.file_size as i64,
+                now,
+                session.title,
+                session.workspace_name,
+                session.created_at,
+                session.vault_path,
+                session.original_path,
+                session.content_hash
+            ],
+        )?;
+        return Ok(UpsertResult::Inserted);
+    };
+
+    let same_content = match (&session.content_hash, &existing_hash) {
+        (Some(new), Some(old)) => Some(new == old),
+        _ => None,
+    };
+    let newer = match same_content {
+        Some(true) => {
+            if mtime > existing_mtime {
+                conn.execute(
+                    "UPDATE sessions SET mtime = ?2 WHERE id = ?1",
+                    params![session.id, mtime],
+                )?;
+            }
+            return Ok(UpsertResult::NoChange);
+        }
+        Some(false) => mtime >= existing_mtime,
+        None => mtime > existing_mtime,
+    };
+    if !newer {
+        return Ok(if mtime < existing_mtime {
+            UpsertResult::Skipped {
+                reason: "Remote version is newer".into(),
+            }
+        } else {
+            UpsertResult::NoChange
+        });
+    }
+
+    conn.execute(
+        "UPDATE sessions SET
+            source = ?2, machine_id = ?3, mtime = ?4,
+            file_size = ?5, last_synced = ?6, title = ?7,
+            workspace_name = ?8, created_at = ?9,
+            vault_path = ?10, original_path = ?11,
+            content_hash = COALESCE(?12, content_hash)
+         WHERE id = ?1",
+        params![
+            session.id,
+            session.source,
+            machine_id(),
+            mtime,
+            session.file_size as i64,
+            now,
+            session.title,
+            session.workspace_name,
+            session.created_at,
+            session.vault_path,
+            session.original_path,
+            session.content_hash
+        ],
+    )?;
+    Ok(UpsertResult::Updated)
+}
+
+/// Upsert sessions with an open transaction.
+fn upsert_all(tx: &Connection, sessions: &[SessionEntry]) -> Result<BatchResult> {
+    let now = chrono::Utc::now().timestamp();
+
+    let mut inserted = 0;
+    let mut updated = 0;
+    let mut skipped = 0;
+    for session in sessions {
+        match upsert_one(tx, session, now)? {
+            UpsertResult::Inserted => inserted += 1,
+            UpsertResult::Updated => updated += 1,
+            UpsertResult::NoChange | UpsertResult::Skipped { .. } => skipped += 1,
+        }
+    }
+
+    Ok(BatchResult {
+        inserted,
+        updated,
+        skipped,
+    })
+}
+
+fn insert_sync_log(conn: &Connection, action: &str, details: Option<&str>) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO sync_log (machine_id, timestamp, action, details)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![machine_id(), now, action, details],
+    )?;
+    Ok(())
+}
+
 /// Result of a batch upsert operation.
 #[derive(Debug, Clone)]
 pub struct BatchResult {
@@ -539,6 +1631,7 @@ mod tests {
             created_at: Some("2024-12-24T10:00:00Z".to_string()),
             vault_path: format!("/vault/vscode-copilot/{}.json", id),
             original_path: format!("/original/{}.json", id),
+            content_hash: None,
         }
     }
 
@@ -594,6 +1687,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_content_hash_decides_changes() -> Result<()> {
+        let db = VaultDb::open_in_memory()?;
+        let hashed = |mtime: u64, hash: &str| SessionEntry {
+            content_hash: Some(hash.to_string()),
+            ..create_test_session("s1", mtime)
+        };
+        db.upsert_session(&hashed(1000, "aaa"))?;
+
+        // Rewritten as-is: no change, but the newer mtime is recorded
+        assert_eq!(
+            db.upsert_session(&hashed(2000, "aaa"))?,
+            UpsertResult::NoChange
+        );
+        assert_eq!(db.get_session_mtime("s1")?, Some(2000));
+
+        // New content within the same mtime is still an update
+        assert_eq!(
+            db.upsert_session(&hashed(2000, "bbb"))?,
+            UpsertResult::Updated
+        );
+        assert_eq!(db.get_session_hash("s1")?.as_deref(), Some("bbb"));
+
+        // Unhashed versions fall back to mtime and keep the recorded hash
+        assert_eq!(
+            db.upsert_session(&create_test_session("s1", 3000))?,
+            UpsertResult::Updated
+        );
+        assert_eq!(db.get_session_hash("s1")?.as_deref(), Some("bbb"));
+        Ok(())
+    }
+
     #[test]
     fn test_batch_upsert() -> Result<()> {
         let mut db = VaultDb::open_in_memory()?;
@@ -613,6 +1738,28 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_ingest_batch_logs_sync() -> Result<()> {
+        let mut db = VaultDb::open_in_memory()?;
+        db.upsert_session(&create_test_session("s1", 2000))?;
+
+        let sessions = vec![
+            create_test_session("s1", 1000),
+            create_test_session("s2", 2000),
+        ];
+        let result = db.ingest_batch(&sessions)?;
+        assert_eq!(result.inserted, 1);
+        assert_eq!(result.skipped, 1);
+
+        let details: String = db.conn.query_row(
+            "SELECT details FROM sync_log WHERE action = 'ingest'",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(details, "2 sessions");
+        Ok(())
+    }
+
     #[test]
     fn test_get_all_sessions() -> Result<()> {
         let mut db = VaultDb::open_in_memory()?;
@@ -626,10 +1773,147 @@ mod tests {
 
         let all = db.get_all_sessions()?;
         assert_eq!(all.len(), 2);
+        assert_eq!(db.get_session("s1")?.map(|s| s.mtime), Some(1000));
+        assert!(db.get_session("missing")?.is_none());
         // Should be ordered by mtime DESC
         assert_eq!(all[0].id, "s2");
         assert_eq!(all[1].id, "s1");
 
         Ok(())
     }
+
+    #[test]
+    fn test_pending_changes_queue() -> Result<()> {
+        let mut db = VaultDb::open_in_memory()?;
+
+        let paths = vec![PathBuf::from("/a/1.json"), PathBuf::from("/a/2.json")];
+        db.enqueue_pending_changes("claude-code", &paths)?;
+        // Re-queuing is idempotent
+        db.enqueue_pending_changes("claude-code", &paths[..1])?;
+
+        let pending = db.pending_changes()?;
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].source, "claude-code");
+
+        let now = chrono::Utc::now().timestamp_millis();
+        assert_eq!(db.clear_pending_changes(now - 3_600_000)?, 0);
+        assert_eq!(db.clear_source_pending_changes("codex", now)?, 0);
+        assert_eq!(db.clear_pending_changes(now)?, 2);
+        assert!(db.pending_changes()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotations() -> Result<()> {
+        let mut db = VaultDb::open_in_memory()?;
+        let note = AnnotationUpdate {
+            note: Some("Fix for the flaky tokio shutdown test".to_string()),
+            rating: Some(4),
+            ..Default::default()
+        };
+        db.annotate("s1", &note)?;
+        db.annotate(
+            "s2",
+            &AnnotationUpdate {
+                follow_up: Some(true),
+                ..Default::default()
+            },
+        )?;
+
+        // Fields left out of an update are kept
+        let flagged = AnnotationUpdate {
+            follow_up: Some(true),
+            ..Default::default()
+        };
+        let s1 = db.annotate("s1", &flagged)?.unwrap();
+        assert_eq!(s1.rating, Some(4));
+        assert!(s1.note.starts_with("Fix for"));
+        assert_eq!(db.annotations(true)?.len(), 2);
+
+        let found = db.search_annotations("tokio", 10)?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].session_id, "s1");
+
+        let invalid = AnnotationUpdate {
+            rating: Some(6),
+            ..Default::default()
+        };
+        assert!(db.annotate("s1", &invalid).is_err());
+
+        // Clearing every field removes the annotation, and its note from search
+        let cleared = AnnotationUpdate {
+            note: Some(String::new()),
+            rating: Some(0),
+            follow_up: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(db.annotate("s1", &cleared)?, None);
+        assert_eq!(db.annotation("s1")?, None);
+        assert!(db.search_annotations("tokio", 10)?.is_empty());
+
+        // Tags are replaced as a whole, trimmed and deduplicated
+        let tagged = AnnotationUpdate {
+            tags: Some(vec![
+                " rust ".to_string(),
+                "async".to_string(),
+                "rust".to_string(),
+            ]),
+            ..Default::default()
+        };
+        db.annotate("s2", &tagged)?;
+        assert_eq!(db.annotation("s2")?.unwrap().tags, vec!["async", "rust"]);
+
+        // Extracted tags are kept apart; filters match both kinds
+        db.set_session_tags(&[
+            (
+                "s1".to_string(),
+                vec!["rust".to_string(), "test".to_string()],
+            ),
+            ("s2".to_string(), vec!["test".to_string()]),
+        ])?;
+        assert_eq!(db.session_tags("s2")?, vec!["test"]);
+        let rust_tests = db.sessions_with_tags(&["Rust".to_string(), "test".to_string()])?;
+        assert_eq!(rust_tests.len(), 2);
+        let async_tests = db.sessions_with_tags(&["async".to_string(), "test".to_string()])?;
+        assert_eq!(async_tests, HashSet::from(["s2".to_string()]));
+        assert_eq!(db.tag_counts()?[0], ("rust".to_string(), 2));
+        let untagged = AnnotationUpdate {
+            tags: Some(Vec::new()),
+            follow_up: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(db.annotate("s2", &untagged)?, None);
+        db.annotate("s2", &flagged)?;
+        assert!(db.annotation("s2")?.unwrap().tags.is_empty());
+        assert!(db.delete_annotation("s2")?);
+        assert!(db.annotations(false)?.is_empty());
+        assert_eq!(db.session_tags("s2")?, vec!["test"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_tag_tables() -> Result<()> {
+        let db = VaultDb::open_in_memory()?;
+        db.conn.execute_batch(
+            "CREATE TABLE annotation_tags (session_id TEXT, tag TEXT);
+             INSERT INTO annotation_tags VALUES ('s1', 'rust');
+             CREATE TABLE session_tags (session_id TEXT, tag TEXT);
+             INSERT INTO session_tags VALUES ('s1', 'rust'), ('s1', 'test');",
+        )?;
+        db.merge_tag_tables()?;
+
+        assert_eq!(db.session_tags("s1")?, vec!["rust", "test"]);
+        assert_eq!(
+            db.tag_counts()?,
+            vec![("rust".to_string(), 1), ("test".to_string(), 1)]
+        );
+        let tables: i64 = db.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name IN ('annotation_tags', 'session_tags')",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(tables, 0);
+        Ok(())
+    }
 }
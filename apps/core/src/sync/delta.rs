@@ -0,0 +1,168 @@
+//! Delta push: send only the vault files written since the last push to a
+//! sync target, instead of comparing the whole tree with the remote.
+//!
+//! Ingest, import and parse mark the session files and Markdown they write
+//! as dirty ([`mark_written`]). A delta push sends those files plus
+//! everything outside the tracked `sessions/` and `parsed/` trees (databases
+//! and metadata: a handful of files).
+//!
+//! Dirty marks and the last push to each target ([`record_sync`]) describe
+//! this machine only, so they live in local.db rather than the synced
+//! vault.db, where a pull would replace them with another machine's. A target
+//! this machine has no successful push to in the last [`DIRTY_RETENTION_DAYS`]
+//! days (or no record of at all, e.g. a fresh local.db) gets a full push.
+
+use crate::crypto::staging::{ENCRYPTED_DIRS, ENCRYPTED_EXT, STAGING_DIR};
+use crate::redact::vault::REDACTED_DIR;
+use crate::storage::{chunked, LocalDb, VaultDb, DIRTY_RETENTION_DAYS};
+use crate::utils::Walk;
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Mark files written into the vault (absolute, or relative to it) as dirty.
+/// Failures are only logged: the files then reach the remote with the next
+/// full push.
+pub fn mark_written(vault_dir: &Path, paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+    let relative: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| path.strip_prefix(vault_dir).unwrap_or(path).to_path_buf())
+        .collect();
+    if let Err(e) = LocalDb::open(vault_dir).and_then(|mut db| db.mark_dirty_files(&relative)) {
+        tracing::warn!("Failed to mark {} files for delta push: {}", paths.len(), e);
+    }
+}
+
+/// Record the outcome of a sync with `target` started at `pushed_from` (unix
+/// milliseconds): the state of the target in local.db, for `status` and the
+/// next delta push, and an entry in the sync log of vault.db.
+pub fn record_sync(
+    vault_dir: &Path,
+    target: &str,
+    pushed_from: i64,
+    outcome: Result<usize, &str>,
+) -> Result<()> {
+    LocalDb::open(vault_dir)?.record_remote_sync(target, pushed_from, outcome)?;
+    let details = match outcome {
+        Ok(files_pushed) => format!("{}: {} files pushed", target, files_pushed),
+        Err(error) => format!("{}: failed: {}", target, error),
+    };
+    VaultDb::open(vault_dir)?.log_sync("sync", Some(&details))
+}
+
+/// Files to push to `target` from `sync_root` (the vault, its encrypted
+/// staging directory or its redacted copies), relative to it, or `None` when
+/// the target needs a full push.
+pub fn delta_files(sync_root: &Path, db: &LocalDb, target: &str) -> Result<Option<Vec<PathBuf>>> {
+    let cutoff = chrono::Utc::now().timestamp_millis() - DIRTY_RETENTION_DAYS * 86_400_000;
+    let since = db
+        .remote_sync_states()?
+        .into_iter()
+        .find(|state| state.remote == target)
+        .and_then(|state| state.pushed_from)
+        .filter(|since| *since >= cutoff);
+    let Some(since) = since else {
+        return Ok(None);
+    };
+
+    let mut files = BTreeSet::new();
+    let mut add_tree = |dir: &Path| -> Result<()> {
+        for file in Walk::new(dir).files() {
+            files.insert(file.strip_prefix(sync_root)?.to_path_buf());
+        }
+        Ok(())
+    };
+
+    // Everything outside the tracked trees
+    for entry in fs::read_dir(sync_root)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let tracked = ENCRYPTED_DIRS.iter().any(|dir| name == *dir)
+            || name == STAGING_DIR
+            || name == REDACTED_DIR;
+        if !tracked && !super::is_not_synced(Path::new(&name)) {
+            add_tree(&entry.path())?;
+        }
+    }
+
+    // Tracked files written since the last push, plain or sealed, whole or chunked
+    for path in db.dirty_files_since(since)? {
+        let mut sealed = path.clone().into_os_string();
+        sealed.push(".");
+        sealed.push(ENCRYPTED_EXT);
+        for stored in [path, PathBuf::from(sealed)] {
+            add_tree(&sync_root.join(&stored))?;
+            add_tree(&sync_root.join(chunked::chunks_dir(&stored)))?;
+        }
+    }
+
+    Ok(Some(files.into_iter().collect()))
+}
+
+/// [`delta_files`] of a push from `sync_root`, falling back to a full push
+/// (`None`) when local.db cannot tell what changed.
+pub fn push_files(vault_dir: &Path, sync_root: &Path, target: &str) -> Option<Vec<PathBuf>> {
+    LocalDb::open(vault_dir)
+        .and_then(|db| delta_files(sync_root, &db, target))
+        .unwrap_or_else(|e| {
+            tracing::warn!("Cannot list changed files, pushing everything: {}", e);
+            None
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_delta_files() -> Result<()> {
+        let temp = TempDir::new()?;
+        let vault = temp.path();
+        for file in [
+            "sessions/cursor/a.json",
+            "sessions/cursor/b.json",
+            "parsed/cursor/a.md",
+            "digests/2025-01.md",
+            ".encrypted/sessions/cursor/a.json.enc",
+        ] {
+            fs::create_dir_all(vault.join(file).parent().unwrap())?;
+            fs::write(vault.join(file), file)?;
+        }
+        VaultDb::open(vault)?;
+        let db = LocalDb::open(vault)?;
+
+        // Never pushed: full push
+        assert_eq!(delta_files(vault, &db, "nas")?, None);
+
+        record_sync(vault, "nas", chrono::Utc::now().timestamp_millis(), Ok(4))?;
+        let log = VaultDb::open(vault)?.recent_sync_log(1)?;
+        assert_eq!(log[0].details.as_deref(), Some("nas: 4 files pushed"));
+        mark_written(
+            vault,
+            &[
+                vault.join("sessions/cursor/a.json"),
+                PathBuf::from("parsed/cursor/a.md"),
+                PathBuf::from("sessions/cursor/removed.json"),
+            ],
+        );
+        let files = delta_files(vault, &db, "nas")?.unwrap();
+        assert!(files.contains(&PathBuf::from("sessions/cursor/a.json")));
+        assert!(files.contains(&PathBuf::from("parsed/cursor/a.md")));
+        assert!(files.contains(&PathBuf::from("digests/2025-01.md")));
+        assert!(files.contains(&PathBuf::from("vault.db")));
+        assert!(!files.contains(&PathBuf::from("sessions/cursor/b.json")));
+        assert!(!files.iter().any(|f| f.starts_with(STAGING_DIR)));
+        assert!(!files.contains(&PathBuf::from("local.db")));
+
+        // The staging directory has the sealed copies
+        let staging = vault.join(STAGING_DIR);
+        let files = delta_files(&staging, &db, "nas")?.unwrap();
+        assert_eq!(files, vec![PathBuf::from("sessions/cursor/a.json.enc")]);
+        Ok(())
+    }
+}
@@ -0,0 +1,494 @@
+//! Local Folder Provider - Sync vault with another directory.
+//!
+//! Mirrors the vault to a path such as a NAS mount, an external drive or a
+//! folder shared by another sync tool, for multi-machine sync without any
+//! cloud account. Semantics follow [`super::rclone`] (`rclone copy`):
+//! - pull/push copy files missing or newer on the other side, never delete;
+//!   a newer file with the same content (BLAKE3) is not copied, and a file
+//!   newer on the destination is never replaced (always `skip_newer`)
+//! - SQLite WAL files, the encrypted and redacted staging directories and the
+//!   key rotation journal are not transferred, nor the files of
+//!   [`SyncOptions::exclude`]
+//! - each file is written to a temporary file and renamed into place, so a
+//!   cancelled or interrupted transfer leaves no partial files
+//! - `transfers` files are copied in parallel, within the bandwidth limit
+
+use super::provider::{
+    AuthStatus, PullResult, PushResult, SyncOptions, SyncProvider, TransferStats,
+};
+use crate::config::SyncConfig;
+use crate::storage::mapped;
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::info;
+use walkdir::WalkDir;
+
+/// Modification times closer than this are considered equal: FAT and exFAT
+/// drives, common for external disks, store them with 2 seconds precision.
+const MODIFY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Suffix of the temporary file a transfer writes before renaming it.
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Local folder sync provider
+pub struct LocalFolderProvider {
+    /// Directory the vault is mirrored to
+    target: PathBuf,
+}
+
+/// Buffer size of copies under a bandwidth limit.
+const THROTTLED_BUFFER: usize = 64 * 1024;
+
+/// Bandwidth limit shared by the parallel copies of a transfer.
+struct Throttle {
+    bytes_per_sec: u64,
+    /// Start of the transfer and bytes copied since
+    copied: Mutex<(Instant, u64)>,
+}
+
+impl Throttle {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            copied: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Account for `bytes` copied, sleeping while ahead of the limit.
+    fn consume(&self, bytes: u64) {
+        let wait = {
+            let Ok(mut copied) = self.copied.lock() else {
+                return;
+            };
+            copied.1 += bytes;
+            let due = Duration::from_secs_f64(copied.1 as f64 / self.bytes_per_sec as f64);
+            due.saturating_sub(copied.0.elapsed())
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Copy a file in small writes paced by the limit.
+    fn copy(&self, src: &Path, dest: &Path) -> std::io::Result<()> {
+        let mut reader = File::open(src)?;
+        let mut writer = File::create(dest)?;
+        let mut buffer = vec![0; THROTTLED_BUFFER];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                return Ok(());
+            }
+            writer.write_all(&buffer[..read])?;
+            self.consume(read as u64);
+        }
+    }
+}
+
+/// A file to copy and whether it already exists at the destination.
+struct Transfer {
+    relative: PathBuf,
+    size: u64,
+    exists: bool,
+}
+
+impl LocalFolderProvider {
+    pub fn new(target: impl Into<PathBuf>) -> Self {
+        Self {
+            target: target.into(),
+        }
+    }
+
+    /// Provider syncing with `<local_path>/<folder_name>` of sync settings.
+    /// Without a `local_path` it is never authenticated.
+    pub fn for_sync(sync: &SyncConfig) -> Self {
+        let target = sync
+            .local_path
+            .as_ref()
+            .map(|path| path.join(&sync.folder_name))
+            .unwrap_or_default();
+        Self::new(target)
+    }
+
+    /// Directory the vault is mirrored to.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Whether a file is never transferred (path relative to the synced
+    /// root): [`super::NOT_SYNCED`] and copies still being written.
+    fn is_excluded(relative: &Path) -> bool {
+        super::is_not_synced(relative)
+            || relative
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with(PARTIAL_SUFFIX))
+    }
+
+    /// Files of `from` missing in `to`, or newer than their copy there, and
+    /// not excluded by `options`. With `files`, only those (relative to
+    /// `from`) are considered.
+    fn plan(
+        from: &Path,
+        to: &Path,
+        files: Option<&[PathBuf]>,
+        options: &SyncOptions,
+    ) -> Result<Vec<Transfer>> {
+        let excluded =
+            |relative: &Path| Self::is_excluded(relative) || options.is_excluded(relative);
+        if !from.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let candidates: Vec<PathBuf> = match files {
+            Some(files) => files
+                .iter()
+                .filter(|relative| !excluded(relative))
+                .cloned()
+                .collect(),
+            None => {
+                let mut candidates = Vec::new();
+                let entries = WalkDir::new(from)
+                    .into_iter()
+                    // Skip excluded directories without descending into them
+                    .filter_entry(|e| !e.path().strip_prefix(from).is_ok_and(excluded));
+                for entry in entries {
+                    let entry = entry?;
+                    if entry.file_type().is_file() {
+                        candidates.push(entry.path().strip_prefix(from)?.to_path_buf());
+                    }
+                }
+                candidates
+            }
+        };
+
+        let mut transfers = Vec::new();
+        for relative in candidates {
+            // Listed files may have been removed since they were marked
+            let Ok(src) = fs::metadata(from.join(&relative)) else {
+                continue;
+            };
+            if !src.is_file() {
+                continue;
+            }
+            let exists = match fs::metadata(to.join(&relative)) {
+                Ok(dest) => {
+                    if !Self::should_update(&src, &dest)
+                        || (src.len() == dest.len()
+                            && Self::same_content(&from.join(&relative), &to.join(&relative)))
+                    {
+                        continue;
+                    }
+                    true
+                }
+                Err(_) => false,
+            };
+            transfers.push(Transfer {
+                relative,
+                size: src.len(),
+                exists,
+            });
+        }
+        Ok(transfers)
+    }
+
+    /// `--update` rule: replace a file only with a newer one; with equal
+    /// modification times, only when the sizes differ.
+    fn should_update(src: &fs::Metadata, dest: &fs::Metadata) -> bool {
+        let (Ok(src_time), Ok(dest_time)) = (src.modified(), dest.modified()) else {
+            return src.len() != dest.len();
+        };
+        match src_time.duration_since(dest_time) {
+            Ok(newer_by) if newer_by > MODIFY_WINDOW => true,
+            Ok(_) => src.len() != dest.len(),
+            Err(e) if e.duration() <= MODIFY_WINDOW => src.len() != dest.len(),
+            Err(_) => false,
+        }
+    }
+
+    /// Whether two files of the same size hold the same bytes (by BLAKE3), so
+    /// a newer copy rewritten as-is is not transferred again.
+    fn same_content(a: &Path, b: &Path) -> bool {
+        match (mapped::blake3_file(a), mapped::blake3_file(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Copy a file through a temporary file, keeping its modification time so
+    /// the other side sees it as up to date.
+    fn copy_file(
+        src: &Path,
+        dest: &Path,
+        modified: SystemTime,
+        throttle: Option<&Throttle>,
+    ) -> Result<()> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let name = dest.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = dest.with_file_name(format!(".{}{}", name, PARTIAL_SUFFIX));
+        let copied = match throttle {
+            Some(throttle) => throttle.copy(src, &tmp),
+            None => fs::copy(src, &tmp).map(|_| ()),
+        };
+        let copied = copied
+            .and_then(|_| File::options().write(true).open(&tmp))
+            .and_then(|file| file.set_modified(modified))
+            .and_then(|_| fs::rename(&tmp, dest));
+        if copied.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        copied.with_context(|| format!("Cannot copy {} to {}", src.display(), dest.display()))
+    }
+
+    /// Copy files planned from `from` to `to`, `options.transfers` at a
+    /// time (one by default) within its bandwidth limit, reporting progress
+    /// and stopping between files once cancelled. Returns the stats and the
+    /// number of files that replaced an existing copy.
+    fn transfer(
+        from: &Path,
+        to: &Path,
+        files: Option<&[PathBuf]>,
+        options: &SyncOptions,
+    ) -> Result<(TransferStats, usize)> {
+        let transfers = Self::plan(from, to, files, options)?;
+        let stats = TransferStats {
+            files_total: transfers.len() as u64,
+            bytes_total: transfers.iter().map(|t| t.size).sum(),
+            ..Default::default()
+        };
+        options.progress.report(stats);
+
+        let throttle = options.bwlimit_kib.map(|kib| Throttle::new(kib * 1024));
+        let done = Mutex::new((stats, 0));
+        let copy = |transfer: &Transfer| -> Result<()> {
+            options.cancel.check()?;
+            let src = from.join(&transfer.relative);
+            let modified = fs::metadata(&src)?.modified()?;
+            Self::copy_file(
+                &src,
+                &to.join(&transfer.relative),
+                modified,
+                throttle.as_ref(),
+            )?;
+            if let Ok(mut done) = done.lock() {
+                done.0.files_done += 1;
+                done.0.bytes_done += transfer.size;
+                done.1 += usize::from(transfer.exists);
+                options.progress.report(done.0);
+            }
+            Ok(())
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.transfers.unwrap_or(1).max(1))
+            .build()?;
+        pool.install(|| transfers.par_iter().try_for_each(copy))?;
+
+        Ok(done.into_inner().unwrap_or_default())
+    }
+}
+
+impl SyncProvider for LocalFolderProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.target.is_dir()
+    }
+
+    fn auth_status(&self) -> AuthStatus {
+        if self.is_authenticated() {
+            AuthStatus::Authenticated
+        } else {
+            AuthStatus::NotAuthenticated
+        }
+    }
+
+    /// Create the target directory. Its parent must exist, so an unmounted
+    /// drive is reported instead of creating the folder on the local disk.
+    fn start_auth(&mut self) -> Result<AuthStatus> {
+        if self.target.is_dir() {
+            return Ok(AuthStatus::Authenticated);
+        }
+        match self.target.parent() {
+            Some(parent) if parent.is_dir() => {
+                fs::create_dir(&self.target)?;
+                Ok(AuthStatus::Authenticated)
+            }
+            _ => Ok(AuthStatus::Error(format!(
+                "{} is not available (is the drive mounted?)",
+                self.target.display()
+            ))),
+        }
+    }
+
+    fn complete_auth(&mut self) -> Result<AuthStatus> {
+        Ok(self.auth_status())
+    }
+
+    fn pull(&self, vault_dir: &Path, options: &SyncOptions) -> Result<PullResult> {
+        info!(
+            "[Local] Pulling from {} to {}...",
+            self.target.display(),
+            vault_dir.display()
+        );
+        let (stats, updated) = Self::transfer(&self.target, vault_dir, None, options)?;
+        let files = stats.files_done as usize;
+
+        Ok(PullResult {
+            has_changes: files > 0,
+            new_files: files - updated,
+            updated_files: updated,
+        })
+    }
+
+    fn push(&self, vault_dir: &Path, options: &SyncOptions) -> Result<PushResult> {
+        if !self.is_authenticated() {
+            bail!(
+                "Sync folder {} is not available (is the drive mounted?)",
+                self.target.display()
+            );
+        }
+        info!(
+            "[Local] Pushing from {} to {}...",
+            vault_dir.display(),
+            self.target.display()
+        );
+        let (stats, _) =
+            Self::transfer(vault_dir, &self.target, options.files.as_deref(), options)?;
+
+        Ok(PushResult {
+            success: true,
+            files_pushed: stats.files_done as usize,
+            message: Some(format!("Synced to {}", self.target.display())),
+        })
+    }
+
+    fn has_local_changes(&self, vault_dir: &Path) -> Result<bool> {
+        Ok(!Self::plan(vault_dir, &self.target, None, &SyncOptions::default())?.is_empty())
+    }
+
+    fn has_remote_changes(&self, vault_dir: &Path) -> Result<bool> {
+        Ok(!Self::plan(&self.target, vault_dir, None, &SyncOptions::default())?.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str, modified: SystemTime) -> Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, content)?;
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(modified)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_throttle_paces_copies() {
+        let throttle = Throttle::new(10_000);
+        let started = Instant::now();
+        throttle.consume(2_000);
+        assert!(started.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_push_and_pull_newer_files() -> Result<()> {
+        let temp = TempDir::new()?;
+        let vault = temp.path().join("vault");
+        let target = temp.path().join("nas/EchoVault");
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        let now = SystemTime::now();
+
+        write(&vault.join("sessions/cursor/a.json"), "a", old)?;
+        write(&vault.join("vault.db-wal"), "wal", old)?;
+        write(&vault.join(".encrypted/sessions/a.age"), "sealed", old)?;
+
+        // The target folder is created under an existing parent only
+        let mut provider = LocalFolderProvider::new(&target);
+        assert_eq!(provider.auth_status(), AuthStatus::NotAuthenticated);
+        assert!(matches!(provider.start_auth()?, AuthStatus::Error(_)));
+        fs::create_dir_all(temp.path().join("nas"))?;
+        assert_eq!(provider.start_auth()?, AuthStatus::Authenticated);
+
+        assert!(provider.has_local_changes(&vault)?);
+        // In parallel, within a bandwidth limit
+        let options = SyncOptions {
+            bwlimit_kib: Some(1024),
+            ..SyncOptions::for_sync(&SyncConfig::default())
+        };
+        let pushed = provider.push(&vault, &options)?;
+        assert_eq!(pushed.files_pushed, 1);
+        assert!(target.join("sessions/cursor/a.json").exists());
+        assert!(!target.join("vault.db-wal").exists());
+        assert!(!target.join(".encrypted").exists());
+        assert!(!provider.has_local_changes(&vault)?);
+
+        // Another machine updates a session and adds one
+        write(&target.join("sessions/cursor/a.json"), "a2", now)?;
+        write(&target.join("sessions/zed/b.json"), "b", now)?;
+        assert!(provider.has_remote_changes(&vault)?);
+        let pulled = provider.pull(&vault, &SyncOptions::default())?;
+        assert_eq!((pulled.new_files, pulled.updated_files), (1, 1));
+        assert_eq!(
+            fs::read_to_string(vault.join("sessions/cursor/a.json"))?,
+            "a2"
+        );
+
+        // An older copy never replaces a newer file
+        write(&target.join("sessions/zed/b.json"), "stale", old)?;
+        let pulled = provider.pull(&vault, &SyncOptions::default())?;
+        assert!(!pulled.has_changes);
+        assert_eq!(fs::read_to_string(vault.join("sessions/zed/b.json"))?, "b");
+
+        // A file rewritten as-is is newer but not changed
+        let later = now + Duration::from_secs(3600);
+        write(&vault.join("sessions/cursor/a.json"), "a2", later)?;
+        let planned = LocalFolderProvider::plan(&vault, &target, None, &SyncOptions::default())?;
+        assert!(planned
+            .iter()
+            .all(|t| t.relative != Path::new("sessions/cursor/a.json")));
+
+        // A delta push only sends the listed files
+        write(&vault.join("sessions/cursor/c.json"), "c", now)?;
+        write(&vault.join("sessions/cursor/d.json"), "d", now)?;
+        let options = SyncOptions {
+            files: Some(vec![
+                PathBuf::from("sessions/cursor/c.json"),
+                PathBuf::from("sessions/cursor/gone.json"),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(provider.push(&vault, &options)?.files_pushed, 1);
+        assert!(target.join("sessions/cursor/c.json").exists());
+        assert!(!target.join("sessions/cursor/d.json").exists());
+
+        // Excluded files and directories go neither way (the newer b.json
+        // still replaces the stale copy left on the target above)
+        write(&target.join("sessions/work/e.json"), "e", now)?;
+        let options = SyncOptions {
+            exclude: HashSet::from([
+                PathBuf::from("sessions/cursor/d.json"),
+                PathBuf::from("sessions/work"),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(provider.push(&vault, &options)?.files_pushed, 1);
+        assert_eq!(fs::read_to_string(target.join("sessions/zed/b.json"))?, "b");
+        assert!(!target.join("sessions/cursor/d.json").exists());
+        assert!(!provider.pull(&vault, &options)?.has_changes);
+        assert!(!vault.join("sessions/work").exists());
+        Ok(())
+    }
+}
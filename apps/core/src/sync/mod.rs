@@ -3,9 +3,91 @@
 //! This module contains:
 //! - SyncProvider trait for abstraction
 //! - Rclone provider (supports 40+ cloud services)
+//! - Local folder provider (NAS mount, external drive)
+//! - Delta push file lists built from files marked dirty in vault.db
+//! - Per-workspace partitioning of what each target receives
+//!
+//! `[sync] provider` picks the backend; [`create_provider`] builds it, so the
+//! CLI and desktop app never name a provider themselves.
 
+pub mod delta;
+pub mod local;
+pub mod partition;
 pub mod provider;
 pub mod rclone;
 
-pub use provider::{AuthStatus, PullResult, PushResult, SyncOptions, SyncProvider};
+pub use local::LocalFolderProvider;
+pub use provider::{
+    AuthStatus, PullResult, PushResult, SyncOptions, SyncProvider, TransferProgress, TransferStats,
+};
 pub use rclone::RcloneProvider;
+
+use crate::config::{Config, SyncProviderKind, DEFAULT_SYNC_TARGET};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Vault files no provider transfers, as rclone filters relative to the
+/// synced root (`/` anchors at the root, other patterns match at any depth):
+/// SQLite WAL files (temporary, they cause conflicts), the encrypted and
+/// redacted staging directories (each pushed as its own root), the key
+/// rotation journal, and the per-machine full-text index, ANN graph and
+/// local database.
+pub const NOT_SYNCED: &[&str] = &[
+    "*.db-wal",
+    "*.db-shm",
+    "/.encrypted/**",
+    "/.redacted/**",
+    "/.key-rotation.json",
+    "/fts.db",
+    "/embeddings.hnsw*",
+    "/local.db",
+];
+
+static NOT_SYNCED_GLOBS: LazyLock<GlobSet> = LazyLock::new(|| {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in NOT_SYNCED {
+        let glob = pattern.strip_prefix('/').unwrap_or(pattern);
+        builder.add(Glob::new(glob).expect("valid NOT_SYNCED glob"));
+    }
+    builder.build().expect("valid NOT_SYNCED globs")
+});
+
+/// Whether a file (relative to the synced root) matches [`NOT_SYNCED`].
+pub fn is_not_synced(relative: &Path) -> bool {
+    NOT_SYNCED_GLOBS.is_match(relative)
+}
+
+/// Create the sync provider selected by `[sync] provider`.
+///
+/// The `local` provider syncs with `<local_path>/<folder_name>`; without a
+/// `local_path` it is never authenticated ([`Config::validate`] reports it),
+/// and neither is an unknown provider.
+pub fn create_provider(config: &Config) -> Box<dyn SyncProvider> {
+    match config.sync.provider {
+        SyncProviderKind::Rclone => Box::new(RcloneProvider::for_config(config)),
+        SyncProviderKind::Local => Box::new(LocalFolderProvider::for_sync(&config.sync)),
+        SyncProviderKind::Unknown => Box::new(LocalFolderProvider::new(PathBuf::new())),
+    }
+}
+
+/// Create the provider of a sync target: [`DEFAULT_SYNC_TARGET`] (same as
+/// [`create_provider`]) or a `[sync.targets.<name>]` entry.
+pub fn create_target_provider(config: &Config, name: &str) -> Result<Box<dyn SyncProvider>> {
+    if name == DEFAULT_SYNC_TARGET {
+        return Ok(create_provider(config));
+    }
+    let sync = config.sync.target(name).with_context(|| {
+        format!(
+            "Unknown sync target '{}' (configured: {})",
+            name,
+            config.sync.target_names().join(", ")
+        )
+    })?;
+    Ok(match sync.provider {
+        SyncProviderKind::Rclone => Box::new(RcloneProvider::for_sync(&sync)),
+        SyncProviderKind::Local => Box::new(LocalFolderProvider::for_sync(&sync)),
+        SyncProviderKind::Unknown => Box::new(LocalFolderProvider::new(PathBuf::new())),
+    })
+}
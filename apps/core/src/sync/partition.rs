@@ -0,0 +1,272 @@
+//! Per-workspace partitioning of sync targets (`[[sync.workspaces]]`).
+//!
+//! A session whose workspace matches a rule syncs only with the targets of
+//! the first such rule; other sessions, with or without a workspace, sync
+//! with every target. [`excluded`] lists what a target must not hold: the
+//! session files and Markdown of sessions mapped elsewhere, plus the files
+//! describing every session ([`SHARED`]). Sync hands it to the provider as
+//! [`SyncOptions::exclude`](super::SyncOptions::exclude), so those files are
+//! neither pushed to that target nor pulled from it.
+//!
+//! Paths cover every form a file is synced in: plain or zstd-compressed,
+//! encrypted (`.enc`) and split into chunks.
+
+use crate::config::{Config, SyncConfig};
+use crate::crypto::staging::ENCRYPTED_EXT;
+use crate::digest::DIGESTS_DIR;
+use crate::storage::{chunked, compressed, SessionEntry, VaultDb};
+use crate::utils::Walk;
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Vault files describing every session (titles, annotations, embedded
+/// text, digests), kept from targets that do not receive every session
+pub const SHARED: &[&str] = &["vault.db", "index.db", "embeddings.db", DIGESTS_DIR];
+
+/// Matcher of a `[[sync.workspaces]]` workspace glob (case-insensitive).
+pub fn workspace_glob(pattern: &str) -> Result<GlobMatcher> {
+    Ok(GlobBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .with_context(|| format!("Invalid workspace glob: {}", pattern))?
+        .compile_matcher())
+}
+
+/// Compiled `[[sync.workspaces]]` rules.
+pub struct WorkspaceRules(Vec<(GlobMatcher, Vec<String>)>);
+
+impl WorkspaceRules {
+    pub fn new(sync: &SyncConfig) -> Result<Self> {
+        sync.workspaces
+            .iter()
+            .map(|rule| Ok((workspace_glob(&rule.workspace)?, rule.targets.clone())))
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    /// Targets of the sessions of `workspace`, or `None` for every target.
+    pub fn targets(&self, workspace: Option<&str>) -> Option<&[String]> {
+        let workspace = workspace?;
+        self.0
+            .iter()
+            .find(|(glob, _)| glob.is_match(workspace))
+            .map(|(_, targets)| targets.as_slice())
+    }
+
+    /// Whether the sessions of `workspace` sync with `target`.
+    pub fn syncs_with(&self, workspace: Option<&str>, target: &str) -> bool {
+        self.targets(workspace)
+            .is_none_or(|targets| targets.iter().any(|t| t == target))
+    }
+}
+
+/// What workspace rules keep from a sync target.
+#[derive(Debug, Clone, Default)]
+pub struct Excluded {
+    /// Sessions of workspaces mapped to other targets
+    pub sessions: usize,
+    /// Their files and the [`SHARED`] files, relative to the synced root
+    pub paths: HashSet<PathBuf>,
+}
+
+/// Files kept from `target` by `[[sync.workspaces]]` rules (none without
+/// rules).
+pub fn excluded(config: &Config, target: &str) -> Result<Excluded> {
+    let mut excluded = Excluded::default();
+    if config.sync.workspaces.is_empty() {
+        return Ok(excluded);
+    }
+    let rules = WorkspaceRules::new(&config.sync)?;
+    let vault_dir = &config.vault_path;
+    for session in VaultDb::open(vault_dir)?.get_all_sessions()? {
+        if !rules.syncs_with(session.workspace_name.as_deref(), target) {
+            excluded.sessions += 1;
+            excluded.paths.extend(session_paths(vault_dir, &session));
+        }
+    }
+    if excluded.sessions > 0 {
+        for name in SHARED {
+            excluded.paths.extend(synced_forms(Path::new(name)));
+        }
+    }
+    Ok(excluded)
+}
+
+/// Sessions of the workspaces whose name contains `workspace`
+/// (case-insensitive, as `list --workspace`).
+pub fn workspace_sessions(vault_dir: &Path, workspace: &str) -> Result<Vec<SessionEntry>> {
+    let workspace = workspace.to_lowercase();
+    Ok(VaultDb::open(vault_dir)?
+        .get_all_sessions()?
+        .into_iter()
+        .filter(|s| {
+            s.workspace_name
+                .as_ref()
+                .is_some_and(|name| name.to_lowercase().contains(&workspace))
+        })
+        .collect())
+}
+
+/// Targets syncing any of `sessions`, [`DEFAULT_SYNC_TARGET`] first.
+///
+/// [`DEFAULT_SYNC_TARGET`]: crate::config::DEFAULT_SYNC_TARGET
+pub fn targets_of(config: &Config, sessions: &[SessionEntry]) -> Result<Vec<String>> {
+    let rules = WorkspaceRules::new(&config.sync)?;
+    Ok(config
+        .sync
+        .target_names()
+        .into_iter()
+        .filter(|target| {
+            sessions
+                .iter()
+                .any(|s| rules.syncs_with(s.workspace_name.as_deref(), target))
+        })
+        .map(String::from)
+        .collect())
+}
+
+/// Files of `sessions` in `sync_root` (the vault, its encrypted staging
+/// directory or its redacted copies), relative to it: the file list of a
+/// push of only those sessions.
+pub fn session_files(
+    vault_dir: &Path,
+    sync_root: &Path,
+    sessions: &[SessionEntry],
+) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in sessions.iter().flat_map(|s| session_paths(vault_dir, s)) {
+        let full = sync_root.join(&path);
+        if full.is_file() {
+            files.push(path);
+        } else if full.is_dir() {
+            files.extend(
+                Walk::new(&full)
+                    .files()
+                    .into_iter()
+                    .filter_map(|file| Some(file.strip_prefix(sync_root).ok()?.to_path_buf())),
+            );
+        }
+    }
+    files
+}
+
+/// Raw file and Markdown of a session, relative to the vault, in every form
+/// they are synced in.
+fn session_paths(vault_dir: &Path, session: &SessionEntry) -> Vec<PathBuf> {
+    let stored = Path::new(&session.vault_path);
+    // Recorded with the vault path of the machine that ingested it
+    let raw = match stored.strip_prefix(vault_dir) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => Path::new("sessions")
+            .join(&session.source)
+            .join(stored.file_name().unwrap_or_default()),
+    };
+    let plain = compressed::plain_path(&raw);
+    let parsed = Path::new("parsed")
+        .join(&session.source)
+        .join(format!("{}.md", session.id));
+    [compressed::compressed_path(&plain), plain, parsed]
+        .iter()
+        .flat_map(|path| synced_forms(path))
+        .collect()
+}
+
+/// A vault file as synced: itself and encrypted, each whole or split into
+/// chunks.
+fn synced_forms(path: &Path) -> [PathBuf; 4] {
+    let mut sealed = path.as_os_str().to_os_string();
+    sealed.push(".");
+    sealed.push(ENCRYPTED_EXT);
+    let sealed = PathBuf::from(sealed);
+    [
+        chunked::chunks_dir(path),
+        chunked::chunks_dir(&sealed),
+        path.to_path_buf(),
+        sealed,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkspaceRule;
+    use tempfile::TempDir;
+
+    fn session(vault: &Path, id: &str, workspace: Option<&str>) -> SessionEntry {
+        SessionEntry {
+            id: id.to_string(),
+            source: "cursor".to_string(),
+            mtime: 0,
+            file_size: 0,
+            title: None,
+            workspace_name: workspace.map(str::to_string),
+            created_at: None,
+            vault_path: vault
+                .join(format!("sessions/cursor/{}.json.zst", id))
+                .to_string_lossy()
+                .to_string(),
+            original_path: String::new(),
+            content_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_workspace_partition() -> Result<()> {
+        let temp = TempDir::new()?;
+        let mut config = Config::with_vault_path(temp.path().to_path_buf());
+        let vault = config.vault_path.clone();
+        config.sync.workspaces = vec![WorkspaceRule {
+            workspace: "Acme-*".to_string(),
+            targets: vec!["work".to_string()],
+        }];
+        let sessions = [
+            session(&vault, "a", Some("acme-api")),
+            session(&vault, "b", Some("blog")),
+            session(&vault, "c", None),
+        ];
+        VaultDb::open(&vault)?.upsert_batch(&sessions)?;
+
+        // Work sessions never reach the default target, nor the databases
+        let default = excluded(&config, "default")?;
+        assert_eq!(default.sessions, 1);
+        for path in [
+            "sessions/cursor/a.json.zst",
+            "sessions/cursor/a.json.enc",
+            "parsed/cursor/a.md",
+            "vault.db",
+            "embeddings.db.enc",
+        ] {
+            assert!(default.paths.contains(Path::new(path)), "{}", path);
+        }
+        assert!(!default.paths.contains(Path::new("parsed/cursor/b.md")));
+        // The work target gets every session
+        assert_eq!(excluded(&config, "work")?.sessions, 0);
+
+        let acme = workspace_sessions(&vault, "ACME")?;
+        assert_eq!(acme.len(), 1);
+        assert_eq!(targets_of(&config, &acme)?, Vec::<String>::new());
+        config
+            .sync
+            .targets
+            .insert("work".to_string(), toml::from_str("provider = \"local\"")?);
+        assert_eq!(targets_of(&config, &acme)?, vec!["work"]);
+        assert_eq!(targets_of(&config, &sessions)?, vec!["default", "work"]);
+
+        std::fs::create_dir_all(vault.join("parsed/cursor/a.md.chunks"))?;
+        std::fs::write(vault.join("parsed/cursor/a.md.chunks/0"), "")?;
+        std::fs::create_dir_all(vault.join("sessions/cursor"))?;
+        std::fs::write(vault.join("sessions/cursor/a.json.zst"), "")?;
+        let mut files = session_files(&vault, &vault, &acme);
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("parsed/cursor/a.md.chunks/0"),
+                PathBuf::from("sessions/cursor/a.json.zst"),
+            ]
+        );
+        Ok(())
+    }
+}
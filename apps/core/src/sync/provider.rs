@@ -2,9 +2,51 @@
 //!
 //! This trait provides an interface for syncing with Google Drive via Rclone.
 
+use crate::config::SyncConfig;
+use crate::progress::CancellationToken;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Progress of a pull or push while it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferStats {
+    /// Files transferred so far
+    pub files_done: u64,
+    /// Files to transfer (grows while the provider is still listing)
+    pub files_total: u64,
+    /// Bytes transferred so far
+    pub bytes_done: u64,
+    /// Bytes to transfer
+    pub bytes_total: u64,
+}
+
+/// Receives [`TransferStats`] updates of a transfer. Does nothing by default.
+#[derive(Clone, Default)]
+pub struct TransferProgress(Option<Arc<dyn Fn(TransferStats) + Send + Sync>>);
+
+impl TransferProgress {
+    pub fn new(report: impl Fn(TransferStats) + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(report)))
+    }
+
+    pub fn report(&self, stats: TransferStats) {
+        if let Some(report) = &self.0 {
+            report(stats);
+        }
+    }
+}
+
+impl std::fmt::Debug for TransferProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TransferProgress")
+            .field(&self.0.is_some())
+            .finish()
+    }
+}
 
 /// Options for sync operations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +55,53 @@ pub struct SyncOptions {
     pub encrypt: bool,
     /// Whether to compress data before sync
     pub compress: bool,
+    /// Cancels a transfer in progress
+    #[serde(skip)]
+    pub cancel: CancellationToken,
+    /// Receives transfer progress
+    #[serde(skip)]
+    pub progress: TransferProgress,
+    /// Push only these files (relative to the synced root) instead of
+    /// comparing the whole tree; `None` pushes everything
+    #[serde(skip)]
+    pub files: Option<Vec<PathBuf>>,
+    /// Files and directories (relative to the synced root) neither pushed
+    /// nor pulled, besides those no provider transfers: the sessions kept
+    /// from the target by workspace rules (see [`super::partition`])
+    #[serde(skip)]
+    pub exclude: HashSet<PathBuf>,
+    /// Files transferred in parallel (`None`: the provider's own setting)
+    pub transfers: Option<usize>,
+    /// Bandwidth limit in KiB/s (`None`: unlimited)
+    pub bwlimit_kib: Option<u64>,
+    /// How long a transfer may stall before it fails (`None`: the
+    /// provider's default). Local folder copies are left to the OS.
+    pub timeout: Option<Duration>,
+    /// Never replace a file that is newer on the destination. Set for the
+    /// encrypted staging directory, so a machine still holding files under
+    /// a rotated-away key cannot overwrite the re-encrypted ones, and for
+    /// targets given redacted copies, so a pull never brings a copy back
+    /// over its original.
+    #[serde(skip)]
+    pub skip_newer: bool,
+}
+
+impl SyncOptions {
+    /// Options with the transfer tuning of sync settings.
+    pub fn for_sync(sync: &SyncConfig) -> Self {
+        Self {
+            transfers: Some(sync.transfers.max(1)),
+            bwlimit_kib: sync.bwlimit_kib,
+            timeout: sync.timeout_secs.map(Duration::from_secs),
+            ..Default::default()
+        }
+    }
+
+    /// Whether `exclude` holds a file (relative to the synced root) or one
+    /// of its directories.
+    pub fn is_excluded(&self, relative: &Path) -> bool {
+        !self.exclude.is_empty() && relative.ancestors().any(|dir| self.exclude.contains(dir))
+    }
 }
 
 impl Default for SyncOptions {
@@ -20,6 +109,14 @@ impl Default for SyncOptions {
         Self {
             encrypt: true,
             compress: true,
+            cancel: CancellationToken::new(),
+            progress: TransferProgress::default(),
+            files: None,
+            exclude: HashSet::new(),
+            transfers: None,
+            bwlimit_kib: None,
+            timeout: None,
+            skip_newer: false,
         }
     }
 }
@@ -7,10 +7,18 @@
 //! - Rclone comes with built-in OAuth credentials for Google Drive
 //! - Bundled into app, no separate installation needed
 
-use super::provider::{AuthStatus, PullResult, PushResult, SyncOptions, SyncProvider};
+use super::provider::{
+    AuthStatus, PullResult, PushResult, SyncOptions, SyncProvider, TransferProgress, TransferStats,
+};
+use super::NOT_SYNCED;
+use crate::config::{Config, SyncConfig};
+use crate::progress::Cancelled;
 use anyhow::{bail, Context, Result};
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use tracing::info;
 
 #[cfg(windows)]
@@ -26,6 +34,10 @@ const DEFAULT_REMOTE_NAME: &str = "echovault-gdrive";
 /// Remote path on cloud storage
 const DEFAULT_REMOTE_PATH: &str = "EchoVault";
 
+/// Parallel transfers and checks on Google Drive when no config is given
+const DEFAULT_TRANSFERS: usize = 8;
+const DEFAULT_CHECKERS: usize = 16;
+
 /// Rclone sync provider
 pub struct RcloneProvider {
     /// Path to rclone binary
@@ -36,6 +48,12 @@ pub struct RcloneProvider {
     remote_path: String,
     /// Whether remote is configured
     is_configured: bool,
+    /// Whether the remote is a Google Drive remote
+    drive: bool,
+    /// Files transferred in parallel by push/pull on Google Drive
+    transfers: usize,
+    /// Files compared with the remote in parallel by push/pull on Google Drive
+    checkers: usize,
 }
 
 impl RcloneProvider {
@@ -48,6 +66,9 @@ impl RcloneProvider {
             remote_name: DEFAULT_REMOTE_NAME.to_string(),
             remote_path: DEFAULT_REMOTE_PATH.to_string(),
             is_configured: false,
+            drive: false,
+            transfers: DEFAULT_TRANSFERS,
+            checkers: DEFAULT_CHECKERS,
         };
 
         // Try to find existing Google Drive remote
@@ -59,6 +80,7 @@ impl RcloneProvider {
             // Check if default remote exists
             provider.is_configured = provider.check_remote_exists().unwrap_or(false);
         }
+        provider.drive = provider.is_drive_remote();
 
         provider
     }
@@ -91,9 +113,59 @@ impl RcloneProvider {
             remote_name: remote_name.to_string(),
             remote_path: remote_path.to_string(),
             is_configured: false,
+            drive: false,
+            transfers: DEFAULT_TRANSFERS,
+            checkers: DEFAULT_CHECKERS,
         };
 
         provider.is_configured = provider.check_remote_exists().unwrap_or(false);
+        provider.drive = provider.is_drive_remote();
+        provider
+    }
+
+    /// Create provider for a loaded config.
+    ///
+    /// The `[sync]` folder is always used, and so is its remote when rclone
+    /// has it; otherwise an existing Google Drive remote is auto-detected.
+    /// A remote named by the active profile or an `ECHOVAULT_*` override is
+    /// never replaced by another one: the provider stays unauthenticated, so
+    /// pulls and pushes fail until `auth` creates it. Parallelism of
+    /// transfers and checks on Google Drive comes from `[sync]` as well.
+    pub fn for_config(config: &Config) -> Self {
+        let pinned = config.profile_name().is_some()
+            || config
+                .env_overrides
+                .iter()
+                .any(|o| o.key == "sync.remote_name");
+        let mut provider = match config.sync.remote_name.as_deref() {
+            Some(remote) => {
+                let named = Self::with_remote(remote, DEFAULT_REMOTE_PATH);
+                if pinned || named.is_configured {
+                    named
+                } else {
+                    Self::new()
+                }
+            }
+            None => Self::new(),
+        };
+        if !config.sync.folder_name.is_empty() {
+            provider.remote_path = config.sync.folder_name.clone();
+        }
+        provider.transfers = config.sync.transfers.max(1);
+        provider.checkers = config.sync.checkers.max(1);
+        provider
+    }
+
+    /// Create provider for the remote and folder of sync settings, such as
+    /// a `[sync.targets.<name>]` entry. Unlike [`Self::for_config`], the
+    /// remote is never auto-detected.
+    pub fn for_sync(sync: &SyncConfig) -> Self {
+        let mut provider = Self::with_remote(
+            sync.remote_name.as_deref().unwrap_or(DEFAULT_REMOTE_NAME),
+            &sync.folder_name,
+        );
+        provider.transfers = sync.transfers.max(1);
+        provider.checkers = sync.checkers.max(1);
         provider
     }
 
@@ -168,6 +240,92 @@ impl RcloneProvider {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Run rclone command like [`Self::run_rclone`], killing it once
+    /// `options.cancel` is triggered. rclone writes each transfer to a
+    /// temporary file and renames it into place, so a killed transfer leaves
+    /// no partial files. Transfer stats in its JSON log (`--use-json-log`)
+    /// are reported to `options.progress`; the last ones are returned.
+    fn run_rclone_cancellable(
+        &self,
+        args: &[&str],
+        options: &SyncOptions,
+    ) -> Result<TransferStats> {
+        let cancel = &options.cancel;
+        let mut cmd = Command::new(&self.rclone_path);
+        cmd.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        // On Windows, prevent console window from appearing
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NO_WINDOW);
+
+        let mut child = cmd.spawn().context("Cannot execute rclone")?;
+        // Drain both pipes while waiting, so a full pipe cannot stall rclone
+        let stdout = child.stdout.take().map(read_to_string_thread);
+        let log = child
+            .stderr
+            .take()
+            .map(|pipe| read_log_thread(pipe, options.progress.clone()));
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if cancel.is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Cancelled.into());
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+
+        if let Some(handle) = stdout {
+            let _ = handle.join();
+        }
+        let (messages, stats) = log.and_then(|h| h.join().ok()).unwrap_or_default();
+        if !status.success() {
+            bail!("Rclone failed: {}", messages);
+        }
+
+        Ok(stats)
+    }
+
+    /// Transfer tuning of a pull or push:
+    /// - `--transfers`: move several files at once (the options' transfers,
+    ///   or the provider's on Google Drive)
+    /// - on Google Drive, whose API handles one file per request:
+    ///   `--checkers` to compare several files at once, and `--fast-list`
+    ///   to list the remote in a few batched calls (unless the options list
+    ///   the files to push)
+    /// - `--bwlimit`: bandwidth limit in KiB/s
+    /// - `--timeout`: how long a transfer may stall before it fails
+    /// - `--update`: never replace a newer file on the destination
+    ///   (the options' `skip_newer`)
+    fn tuning_args(&self, options: &SyncOptions) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(transfers) = options.transfers.or(self.drive.then_some(self.transfers)) {
+            args.extend(["--transfers".to_string(), transfers.max(1).to_string()]);
+        }
+        if self.drive {
+            args.extend(["--checkers".to_string(), self.checkers.to_string()]);
+            if options.files.is_none() {
+                args.push("--fast-list".to_string());
+            }
+        }
+        if let Some(limit) = options.bwlimit_kib {
+            args.extend(["--bwlimit".to_string(), format!("{}K", limit)]);
+        }
+        if let Some(timeout) = options.timeout {
+            args.extend([
+                "--timeout".to_string(),
+                format!("{}s", timeout.as_secs().max(1)),
+            ]);
+        }
+        if options.skip_newer {
+            args.push("--update".to_string());
+        }
+        args
+    }
+
     /// Run rclone command with direct output (for interactive commands).
     fn run_rclone_interactive(&self, args: &[&str]) -> Result<()> {
         let mut cmd = Command::new(&self.rclone_path);
@@ -200,6 +358,18 @@ impl RcloneProvider {
         Ok(remotes)
     }
 
+    /// Whether the remote is configured as a Google Drive remote (type
+    /// `drive` in `rclone listremotes --long`).
+    fn is_drive_remote(&self) -> bool {
+        let Ok(output) = self.run_rclone(&["listremotes", "--long"]) else {
+            return false;
+        };
+        output.lines().any(|line| {
+            line.split_once(':')
+                .is_some_and(|(name, kind)| name == self.remote_name && kind.trim() == "drive")
+        })
+    }
+
     /// Check if remote exists.
     pub fn check_remote_exists(&self) -> Result<bool> {
         let remotes = self.list_remotes()?;
@@ -312,7 +482,7 @@ impl SyncProvider for RcloneProvider {
         }
     }
 
-    fn pull(&self, vault_dir: &Path, _options: &SyncOptions) -> Result<PullResult> {
+    fn pull(&self, vault_dir: &Path, options: &SyncOptions) -> Result<PullResult> {
         if !self.is_configured {
             bail!("Remote not configured. Please run start_auth first.");
         }
@@ -325,21 +495,38 @@ impl SyncProvider for RcloneProvider {
         // rclone copy remote:path local_path
         // Use 'copy' instead of 'sync' to prevent deleting local files
         // that don't exist on remote (important for bidirectional sync)
-        // Exclude SQLite WAL files as they are temporary and cause conflicts
-        let output = self.run_rclone(&[
+        // Exclude the files no provider transfers (NOT_SYNCED) and the files
+        // kept from this target by workspace rules (--exclude-from).
+        // Parallelism, listing, bandwidth limit and timeout: see tuning_args
+        let tuning = self.tuning_args(options);
+        let exclude_file = write_exclude_list(options)?;
+        let exclude_path = exclude_file
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string());
+        let mut args: Vec<&str> = vec![
             "copy",
             &remote_url,
             &local_path,
-            "--exclude",
-            "*.db-wal",
-            "--exclude",
-            "*.db-shm",
             "--verbose",
             "--stats-one-line",
-        ])?;
+            "--stats",
+            "1s",
+            "--use-json-log",
+        ];
+        for pattern in NOT_SYNCED {
+            args.extend(["--exclude", pattern]);
+        }
+        if let Some(exclude) = &exclude_path {
+            args.extend(["--exclude-from", exclude.as_str()]);
+        }
+        args.extend(tuning.iter().map(String::as_str));
+        let stats = self.run_rclone_cancellable(&args, options);
+        if let Some(path) = &exclude_file {
+            let _ = std::fs::remove_file(path);
+        }
+        let stats = stats?;
 
-        // Parse output to count files (simplified)
-        let new_files = output.matches("Transferred:").count();
+        let new_files = stats.files_done as usize;
 
         Ok(PullResult {
             has_changes: new_files > 0,
@@ -348,7 +535,7 @@ impl SyncProvider for RcloneProvider {
         })
     }
 
-    fn push(&self, vault_dir: &Path, _options: &SyncOptions) -> Result<PushResult> {
+    fn push(&self, vault_dir: &Path, options: &SyncOptions) -> Result<PushResult> {
         if !self.is_configured {
             bail!("Remote not configured. Please run start_auth first.");
         }
@@ -358,24 +545,70 @@ impl SyncProvider for RcloneProvider {
 
         info!("[Rclone] Pushing from {} to {}...", local_path, remote_url);
 
+        // Files kept from this target are left out of a delta push's list
+        let files: Option<Vec<PathBuf>> = options.files.as_ref().map(|files| {
+            files
+                .iter()
+                .filter(|file| !options.is_excluded(file))
+                .cloned()
+                .collect()
+        });
+        // A delta push with nothing changed has nothing to do
+        if files.as_ref().is_some_and(|files| files.is_empty()) {
+            return Ok(PushResult {
+                success: true,
+                files_pushed: 0,
+                message: Some("Nothing to push".to_string()),
+            });
+        }
+        let list_file = files.as_deref().map(write_file_list).transpose()?;
+        let list_path = list_file
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string());
+        let exclude_file = match &files {
+            Some(_) => None,
+            None => write_exclude_list(options)?,
+        };
+        let exclude_path = exclude_file
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string());
+
         // rclone copy local_path remote:path
         // Use 'copy' instead of 'sync' to prevent deleting remote files
         // that don't exist locally (important for bidirectional sync)
-        // Exclude SQLite WAL files as they are temporary and cause conflicts
-        let output = self.run_rclone(&[
+        // Exclude the files no provider transfers (NOT_SYNCED) and the files
+        // kept from this target by workspace rules (--exclude-from).
+        // --files-from-raw/--no-traverse (delta push): only the listed files,
+        // each checked on the remote instead of listing the whole tree
+        // Parallelism, listing, bandwidth limit and timeout: see tuning_args
+        let tuning = self.tuning_args(options);
+        let mut args: Vec<&str> = vec![
             "copy",
             &local_path,
             &remote_url,
-            "--exclude",
-            "*.db-wal",
-            "--exclude",
-            "*.db-shm",
             "--verbose",
             "--stats-one-line",
-        ])?;
+            "--stats",
+            "1s",
+            "--use-json-log",
+        ];
+        for pattern in NOT_SYNCED {
+            args.extend(["--exclude", pattern]);
+        }
+        if let Some(list) = &list_path {
+            args.extend(["--files-from-raw", list.as_str(), "--no-traverse"]);
+        }
+        if let Some(exclude) = &exclude_path {
+            args.extend(["--exclude-from", exclude.as_str()]);
+        }
+        args.extend(tuning.iter().map(String::as_str));
+        let stats = self.run_rclone_cancellable(&args, options);
+        for path in list_file.iter().chain(&exclude_file) {
+            let _ = std::fs::remove_file(path);
+        }
+        let stats = stats?;
 
-        // Parse output to count files (simplified)
-        let files_pushed = output.matches("Transferred:").count();
+        let files_pushed = stats.files_done as usize;
 
         Ok(PushResult {
             success: true,
@@ -434,6 +667,96 @@ impl SyncProvider for RcloneProvider {
     }
 }
 
+/// Write the files of a delta push to a temporary list for `--files-from-raw`.
+fn write_file_list(files: &[PathBuf]) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("echovault-push-{}.txt", std::process::id()));
+    let list: Vec<String> = files
+        .iter()
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect();
+    std::fs::write(&path, list.join("\n")).context("Cannot write the push file list")?;
+    Ok(path)
+}
+
+/// Write the files of `options.exclude` as filter rules for
+/// `--exclude-from`, each anchored at the root as a file and as a
+/// directory; `None` when there are none.
+fn write_exclude_list(options: &SyncOptions) -> Result<Option<PathBuf>> {
+    if options.exclude.is_empty() {
+        return Ok(None);
+    }
+    let path = std::env::temp_dir().join(format!("echovault-exclude-{}.txt", std::process::id()));
+    let mut rules = String::new();
+    for file in &options.exclude {
+        let file = escape_filter(&file.to_string_lossy().replace('\\', "/"));
+        rules.push_str(&format!("/{0}\n/{0}/**\n", file));
+    }
+    std::fs::write(&path, rules).context("Cannot write the sync exclude list")?;
+    Ok(Some(path))
+}
+
+/// Escape the characters of a path that rclone filter patterns give a
+/// meaning to.
+fn escape_filter(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if "\\*?[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Read rclone's JSON log on its own thread, reporting the stats of each
+/// stats line as they arrive. Returns the log messages, for error reports,
+/// and the last stats.
+fn read_log_thread(
+    pipe: impl Read + Send + 'static,
+    progress: TransferProgress,
+) -> JoinHandle<(String, TransferStats)> {
+    thread::spawn(move || {
+        let mut messages = String::new();
+        let mut last = TransferStats::default();
+        for line in BufReader::new(pipe).lines().map_while(|line| line.ok()) {
+            let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+                messages.push_str(&line);
+                messages.push('\n');
+                continue;
+            };
+            if let Some(stats) = entry.get("stats") {
+                last = transfer_stats(stats);
+                progress.report(last);
+            }
+            if let Some(msg) = entry.get("msg").and_then(|m| m.as_str()) {
+                messages.push_str(msg.trim_end());
+                messages.push('\n');
+            }
+        }
+        (messages, last)
+    })
+}
+
+/// Transfer stats from the `stats` object of an rclone JSON log line.
+fn transfer_stats(stats: &serde_json::Value) -> TransferStats {
+    let field = |key: &str| stats.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    TransferStats {
+        files_done: field("transfers"),
+        files_total: field("totalTransfers"),
+        bytes_done: field("bytes"),
+        bytes_total: field("totalBytes"),
+    }
+}
+
+/// Read a child's output pipe to the end on its own thread.
+fn read_to_string_thread(mut pipe: impl Read + Send + 'static) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -461,4 +784,105 @@ mod tests {
         let provider = RcloneProvider::with_remote(DEFAULT_REMOTE_NAME, DEFAULT_REMOTE_PATH);
         assert_eq!(provider.get_remote_url(), "echovault-gdrive:EchoVault");
     }
+
+    #[test]
+    fn test_for_config_sets_parallelism() {
+        let mut config = Config::default();
+        config.sync.transfers = 3;
+        config.sync.checkers = 0;
+        let provider = RcloneProvider::for_config(&config);
+        assert_eq!(provider.transfers, 3);
+        assert_eq!(provider.checkers, 1);
+    }
+
+    #[test]
+    fn test_for_config_honors_env_override() -> Result<()> {
+        let mut config = Config::default();
+        config.apply_overrides([
+            ("ECHOVAULT_REMOTE".to_string(), "no-such-remote".to_string()),
+            ("ECHOVAULT_REMOTE_FOLDER".to_string(), "Work".to_string()),
+        ])?;
+        let provider = RcloneProvider::for_config(&config);
+        assert_eq!(provider.remote_name(), "no-such-remote");
+        assert_eq!(provider.remote_path(), "Work");
+        assert!(!provider.is_authenticated());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuning_args() {
+        let mut provider = RcloneProvider::with_remote(DEFAULT_REMOTE_NAME, DEFAULT_REMOTE_PATH);
+        provider.drive = true;
+        assert_eq!(
+            provider.tuning_args(&SyncOptions::default()),
+            ["--transfers", "8", "--checkers", "16", "--fast-list"]
+        );
+
+        let sync = SyncConfig {
+            transfers: 2,
+            bwlimit_kib: Some(512),
+            timeout_secs: Some(30),
+            ..Default::default()
+        };
+        let mut options = SyncOptions::for_sync(&sync);
+        options.files = Some(Vec::new());
+        assert_eq!(
+            provider.tuning_args(&options),
+            [
+                "--transfers",
+                "2",
+                "--checkers",
+                "16",
+                "--bwlimit",
+                "512K",
+                "--timeout",
+                "30s"
+            ]
+        );
+
+        // Other remotes keep rclone's defaults unless told otherwise
+        provider.drive = false;
+        let options = SyncOptions {
+            skip_newer: true,
+            ..Default::default()
+        };
+        assert_eq!(provider.tuning_args(&options), ["--update"]);
+        assert_eq!(
+            provider.tuning_args(&SyncOptions::for_sync(&sync))[..2],
+            ["--transfers", "2"]
+        );
+    }
+
+    #[test]
+    fn test_escape_filter() {
+        assert_eq!(
+            escape_filter("sessions/zed/[draft] {1}*.json"),
+            "sessions/zed/\\[draft\\] \\{1\\}\\*.json"
+        );
+    }
+
+    #[test]
+    fn test_read_log_reports_stats() {
+        let log = concat!(
+            r#"{"level":"info","msg":"Copied (new)","object":"a.json"}"#,
+            "\n",
+            r#"{"level":"info","msg":"stats","stats":{"bytes":512,"totalBytes":1024,"transfers":1,"totalTransfers":2}}"#,
+            "\n",
+            "plain line\n",
+        );
+        let reported = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        let progress = TransferProgress::new(move |stats| sink.lock().unwrap().push(stats));
+
+        let (messages, last) = read_log_thread(log.as_bytes(), progress).join().unwrap();
+        let expected = TransferStats {
+            files_done: 1,
+            files_total: 2,
+            bytes_done: 512,
+            bytes_total: 1024,
+        };
+        assert_eq!(last, expected);
+        assert_eq!(*reported.lock().unwrap(), vec![expected]);
+        assert_eq!(messages, "Copied (new)\nstats\nplain line\n");
+    }
 }
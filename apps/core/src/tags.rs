@@ -0,0 +1,186 @@
+//! Automatic tags of conversations.
+//!
+//! [`extract_tags`] tags a conversation with the languages of its fenced code
+//! blocks and of the source files it mentions, and with `error`, `refactor`
+//! or `test` when its user messages are about them. Every parse adds them to
+//! [`ParsedConversation::tags`] (see [`crate::parsers::parse_raw_file`]); the
+//! parse stage records them in vault.db ([`record`]), where `list`, `search`
+//! and `grep` filter by them together with the tags users add to sessions.
+
+use crate::parsers::{ParsedConversation, Role};
+use crate::storage::VaultDb;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Info string of a fenced code block (```rust, ~~~ python)
+static FENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^[ \t]*(?:```|~~~)[ \t]*([A-Za-z0-9_+#.-]+)").expect("valid fence pattern")
+});
+
+/// File name with an extension (main.rs, src/app.tsx)
+static FILE_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9_-]+\.([A-Za-z0-9+]{1,6})\b").expect("valid file name pattern")
+});
+
+/// Topics of user messages, by tag
+static TOPICS: LazyLock<Vec<(&'static str, Regex)>> = LazyLock::new(|| {
+    [
+        (
+            "error",
+            r"(?i)\b(?:errors?|exceptions?|traceback|panic(?:ked|s)?|stack ?trace|segfault|crash(?:es|ed)?)\b",
+        ),
+        (
+            "refactor",
+            r"(?i)\b(?:refactor\w*|restructur\w*|clean(?:ing)? ?up)\b",
+        ),
+        (
+            "test",
+            r"(?i)\b(?:tests?|testing|unit tests?|test cases?|pytest|jest|vitest)\b",
+        ),
+    ]
+    .into_iter()
+    .map(|(tag, pattern)| (tag, Regex::new(pattern).expect("valid topic pattern")))
+    .collect()
+});
+
+/// Tag of a code block language or file extension.
+fn language(name: &str) -> Option<&'static str> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "rs" | "rust" => "rust",
+        "py" | "python" | "python3" => "python",
+        "js" | "mjs" | "cjs" | "jsx" | "javascript" => "javascript",
+        "ts" | "mts" | "tsx" | "typescript" => "typescript",
+        "go" | "golang" => "go",
+        "java" => "java",
+        "kt" | "kts" | "kotlin" => "kotlin",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "cxx" | "hpp" | "c++" => "cpp",
+        "cs" | "csharp" | "c#" => "csharp",
+        "rb" | "ruby" => "ruby",
+        "php" => "php",
+        "swift" => "swift",
+        "scala" => "scala",
+        "dart" => "dart",
+        "lua" => "lua",
+        "ex" | "exs" | "elixir" => "elixir",
+        "hs" | "haskell" => "haskell",
+        "sh" | "bash" | "zsh" | "shell" | "console" | "ps1" | "powershell" => "shell",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" | "scss" | "sass" => "css",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "dockerfile" | "docker" => "docker",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "json" | "jsonc" => "json",
+        _ => return None,
+    })
+}
+
+/// Languages tagged from code blocks only: config and data files are
+/// mentioned in passing too often to tag from their names
+const DATA_LANGUAGES: &[&str] = &["yaml", "toml", "json"];
+
+/// Tags of a conversation, sorted.
+pub fn extract_tags(conv: &ParsedConversation) -> Vec<String> {
+    let mut tags = BTreeSet::new();
+    for message in &conv.messages {
+        let text = &message.content;
+        for fence in FENCE.captures_iter(text) {
+            tags.extend(language(&fence[1]));
+        }
+        if matches!(message.role, Role::User | Role::Assistant) {
+            for file in FILE_NAME.captures_iter(text) {
+                tags.extend(language(&file[1]).filter(|tag| !DATA_LANGUAGES.contains(tag)));
+            }
+        }
+        if message.role == Role::User {
+            for (tag, pattern) in TOPICS.iter() {
+                if pattern.is_match(text) {
+                    tags.insert(*tag);
+                }
+            }
+        }
+    }
+    tags.into_iter().map(str::to_string).collect()
+}
+
+/// Add the extracted tags to those the parser set.
+pub fn tag_conversation(conv: &mut ParsedConversation) {
+    let mut tags = extract_tags(conv);
+    tags.append(&mut conv.tags);
+    tags.sort();
+    tags.dedup();
+    conv.tags = tags;
+}
+
+/// Record the tags of conversations just parsed, replacing those recorded
+/// before. Failures are only logged: the tags are recorded again when the
+/// conversations next change.
+pub fn record<'a>(
+    vault_dir: &Path,
+    conversations: impl IntoIterator<Item = &'a ParsedConversation>,
+) {
+    let tags: Vec<_> = conversations
+        .into_iter()
+        .map(|conv| (conv.id.clone(), conv.tags.clone()))
+        .collect();
+    if tags.is_empty() {
+        return;
+    }
+    if let Err(e) = VaultDb::open(vault_dir).and_then(|mut db| db.set_session_tags(&tags)) {
+        tracing::warn!("Failed to record session tags: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ParsedMessage;
+
+    fn message(role: Role, content: &str) -> ParsedMessage {
+        ParsedMessage {
+            role,
+            content: content.to_string(),
+            timestamp: None,
+            tool_name: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        let mut conv = ParsedConversation {
+            id: "abc".to_string(),
+            source: "cursor".to_string(),
+            title: None,
+            workspace: None,
+            created_at: None,
+            updated_at: None,
+            model: None,
+            messages: vec![
+                message(
+                    Role::User,
+                    "cargo test fails with a panic in src/main.rs, see Cargo.toml",
+                ),
+                message(
+                    Role::Assistant,
+                    "Try this:\n```python\nprint(1)\n```\nand e.g. example.com",
+                ),
+                message(Role::Tool, "app.tsx written"),
+            ],
+            tags: vec!["imported".to_string()],
+            usage: None,
+        };
+        assert_eq!(extract_tags(&conv), vec!["error", "python", "rust", "test"]);
+
+        tag_conversation(&mut conv);
+        assert_eq!(
+            conv.tags,
+            vec!["error", "imported", "python", "rust", "test"]
+        );
+    }
+}
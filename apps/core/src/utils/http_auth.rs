@@ -0,0 +1,104 @@
+//! Bearer token and Host checks shared by the HTTP servers (MCP, REST API).
+
+use axum::extract::Request;
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+#[cfg(feature = "api")]
+use std::net::IpAddr;
+
+/// Random bearer token (256 bits, hex).
+pub fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Reject requests whose bearer token is not `token`.
+pub(crate) async fn require_token(token: &str, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| tokens_match(given.trim(), token));
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Missing or invalid bearer token",
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+/// Reject requests whose `Host` is not localhost, so a web page cannot reach
+/// the server through a DNS name rebound to 127.0.0.1.
+#[cfg(feature = "api")]
+pub(crate) async fn require_loopback_host(request: Request, next: Next) -> Response {
+    let loopback = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(is_loopback_host);
+    if !loopback {
+        return (StatusCode::FORBIDDEN, "Host must be localhost").into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether a `Host` header value (with or without port) names this machine.
+#[cfg(feature = "api")]
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        // IPv6 literal: "[::1]" or "[::1]:18081"
+        Some(rest) => match rest.split_once(']') {
+            Some((name, _)) => name,
+            None => return false,
+        },
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Compare tokens in time independent of where they differ.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_match() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(tokens_match(&token, &token));
+        assert!(!tokens_match(&token[1..], &token));
+        assert!(!tokens_match(&generate_token(), &token));
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn test_is_loopback_host() {
+        assert!(is_loopback_host("localhost"));
+        assert!(is_loopback_host("LOCALHOST:18081"));
+        assert!(is_loopback_host("127.0.0.1:18081"));
+        assert!(is_loopback_host("[::1]:18081"));
+        assert!(is_loopback_host("[::1]"));
+        assert!(!is_loopback_host("evil.example:18081"));
+        assert!(!is_loopback_host("192.168.1.2"));
+        assert!(!is_loopback_host("localhost.evil.example"));
+        assert!(!is_loopback_host("[::1"));
+    }
+}
@@ -1,7 +1,12 @@
 //! Utility functions for EchoVault.
 
 pub mod browser;
+#[cfg(any(feature = "mcp-http", feature = "api"))]
+pub mod http_auth;
+pub mod power;
+pub mod walk;
 pub mod wsl;
 
 pub use browser::open_browser;
+pub use walk::Walk;
 pub use wsl::find_wsl_paths;
@@ -0,0 +1,211 @@
+//! Power and network state detection.
+//!
+//! Used by background scheduling (periodic sync, watcher) to defer heavy
+//! work while running on battery or on a metered connection.
+//! Detection is best-effort: `None` means the state could not be determined.
+
+use crate::config::ScheduleConfig;
+use std::fmt;
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+use std::process::Command;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+/// Windows flag to prevent console window from appearing
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Snapshot of power and network state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PowerStatus {
+    /// Running on battery (discharging)
+    pub on_battery: Option<bool>,
+    /// Active connection is metered
+    pub metered: Option<bool>,
+}
+
+/// Why background work was deferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeferReason {
+    OnBattery,
+    MeteredConnection,
+}
+
+impl fmt::Display for DeferReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OnBattery => write!(f, "running on battery"),
+            Self::MeteredConnection => write!(f, "metered connection"),
+        }
+    }
+}
+
+/// Detect current power and network state.
+pub fn current_status() -> PowerStatus {
+    PowerStatus {
+        on_battery: detect_on_battery(),
+        metered: detect_metered(),
+    }
+}
+
+/// Check whether heavy background work should be deferred.
+///
+/// Returns `None` when work may run now (including when state is unknown).
+pub fn defer_reason(config: &ScheduleConfig) -> Option<DeferReason> {
+    if !config.defer_on_battery && !config.defer_on_metered {
+        return None;
+    }
+
+    let status = current_status();
+    if config.defer_on_battery && status.on_battery == Some(true) {
+        return Some(DeferReason::OnBattery);
+    }
+    if config.defer_on_metered && status.metered == Some(true) {
+        return Some(DeferReason::MeteredConnection);
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn detect_on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+
+    let mut on_battery = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return Some(false);
+                }
+            }
+            "Battery" => {
+                let status = std::fs::read_to_string(path.join("status")).unwrap_or_default();
+                if status.trim() == "Discharging" {
+                    on_battery = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(on_battery)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_on_battery() -> Option<bool> {
+    let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    parse_pmset(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+fn detect_on_battery() -> Option<bool> {
+    let mut cmd = Command::new("powershell");
+    cmd.args([
+        "-NoProfile",
+        "-Command",
+        "(Get-CimInstance Win32_Battery).BatteryStatus",
+    ]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let output = cmd.output().ok()?;
+    let status = String::from_utf8_lossy(&output.stdout);
+    let status = status.trim();
+    if status.is_empty() {
+        // No battery (desktop)
+        return Some(false);
+    }
+    // BatteryStatus 1 = discharging
+    Some(status == "1")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect_on_battery() -> Option<bool> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn detect_metered() -> Option<bool> {
+    // NetworkManager exposes a global Metered property (NMMetered enum)
+    let output = Command::new("busctl")
+        .args([
+            "--system",
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_metered() -> Option<bool> {
+    // Not detectable without platform APIs
+    None
+}
+
+/// Parse `pmset -g batt` output.
+#[cfg(any(target_os = "macos", test))]
+fn parse_pmset(output: &str) -> Option<bool> {
+    if output.contains("'Battery Power'") {
+        Some(true)
+    } else if output.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Parse busctl output for NetworkManager's Metered property (e.g., "u 3").
+///
+/// Values: 0 unknown, 1 yes, 2 no, 3 guess-yes, 4 guess-no.
+#[cfg(any(target_os = "linux", test))]
+fn parse_nm_metered(output: &str) -> Option<bool> {
+    let value = output.trim().strip_prefix("u ")?.trim();
+    match value {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pmset() {
+        assert_eq!(
+            parse_pmset("Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1)\t85%"),
+            Some(true)
+        );
+        assert_eq!(parse_pmset("Now drawing from 'AC Power'"), Some(false));
+        assert_eq!(parse_pmset(""), None);
+    }
+
+    #[test]
+    fn test_parse_nm_metered() {
+        assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 3"), Some(true));
+        assert_eq!(parse_nm_metered("u 4"), Some(false));
+        assert_eq!(parse_nm_metered("u 0"), None);
+        assert_eq!(parse_nm_metered("garbage"), None);
+    }
+
+    #[test]
+    fn test_defer_reason_disabled() {
+        let config = ScheduleConfig {
+            defer_on_battery: false,
+            defer_on_metered: false,
+        };
+        assert_eq!(defer_reason(&config), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Shared directory walker.
+//!
+//! Vault scans (parse, embed, encryption staging) and extractor scans go
+//! through [`Walk`] instead of hand-rolled recursion. Symlinked directories
+//! are followed as before, but a link back to an ancestor is reported and
+//! skipped instead of recursing forever, and scans can be bounded by depth
+//! and pruned by exclude globs.
+
+use globset::GlobSet;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use walkdir::{DirEntry, WalkDir};
+
+/// Walk of the regular files under a directory.
+pub struct Walk {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    exclude: Option<GlobSet>,
+    prune: Option<fn(&Path) -> bool>,
+}
+
+impl Walk {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            max_depth: None,
+            exclude: None,
+            prune: None,
+        }
+    }
+
+    /// Only descend `depth` levels (files directly in the root are at depth 1).
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Skip files and directories whose path relative to the root matches.
+    pub fn exclude(mut self, globs: GlobSet) -> Self {
+        self.exclude = Some(globs);
+        self
+    }
+
+    /// Skip entries (and the contents of directories) for which `prune` is true.
+    pub fn prune(mut self, prune: fn(&Path) -> bool) -> Self {
+        self.prune = Some(prune);
+        self
+    }
+
+    fn is_skipped(&self, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return false;
+        }
+        let path = entry.path();
+        self.prune.is_some_and(|prune| prune(path))
+            || self
+                .exclude
+                .as_ref()
+                .is_some_and(|globs| globs.is_match(path.strip_prefix(&self.root).unwrap_or(path)))
+    }
+
+    fn entries(&self) -> impl Iterator<Item = walkdir::Result<DirEntry>> + '_ {
+        let mut walker = WalkDir::new(&self.root).follow_links(true);
+        if let Some(depth) = self.max_depth {
+            walker = walker.max_depth(depth);
+        }
+        walker
+            .into_iter()
+            .filter_entry(|entry| !self.is_skipped(entry))
+    }
+
+    /// Regular files under the root, in directory order (empty if missing).
+    ///
+    /// Unreadable directories and symlink loops are logged and skipped.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in self.entries() {
+            match entry {
+                Ok(entry) if entry.file_type().is_file() => files.push(entry.into_path()),
+                Ok(_) => {}
+                Err(e) if e.loop_ancestor().is_some() => {
+                    warn!("[walk] Skipping symlink loop at {:?}", e.path());
+                }
+                Err(e) if is_not_found(&e) => {}
+                Err(e) => warn!("[walk] {}", e),
+            }
+        }
+        files
+    }
+
+    /// Like [`Walk::files`], but failing on the first unreadable entry or
+    /// symlink loop, for callers that must not miss a file.
+    pub fn try_files(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in self.entries() {
+            match entry {
+                Ok(entry) if entry.file_type().is_file() => files.push(entry.into_path()),
+                Ok(_) => {}
+                Err(e) if e.depth() == 0 && is_not_found(&e) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// The root is missing, or an entry vanished during the walk.
+fn is_not_found(err: &walkdir::Error) -> bool {
+    err.io_error()
+        .is_some_and(|io| io.kind() == std::io::ErrorKind::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher::build_ignore_set;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_walk_limits_depth_and_skips_excluded() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        fs::create_dir_all(root.join("2024/01/15"))?;
+        fs::write(root.join("top.jsonl"), "{}")?;
+        fs::write(root.join("2024/01/15/deep.jsonl"), "{}")?;
+        fs::write(root.join("2024/.deep.jsonl.ingest"), "{}")?;
+
+        let mut all = Walk::new(root).files();
+        all.sort();
+        assert_eq!(all.len(), 3);
+
+        let shallow = Walk::new(root).max_depth(2).files();
+        assert_eq!(shallow.len(), 2);
+
+        let exclude = build_ignore_set(&["**/.*.ingest".to_string()])?;
+        let kept = Walk::new(root).exclude(exclude).files();
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|p| p.extension().unwrap() == "jsonl"));
+
+        let pruned = Walk::new(root).prune(|p| p.ends_with("2024")).files();
+        assert_eq!(pruned, vec![root.join("top.jsonl")]);
+
+        assert!(Walk::new(&root.join("missing")).files().is_empty());
+        assert!(Walk::new(&root.join("missing")).try_files()?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_survives_symlink_loops() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let root = temp.path();
+        fs::create_dir_all(root.join("a"))?;
+        fs::write(root.join("a/session.json"), "{}")?;
+        std::os::unix::fs::symlink(root, root.join("a/loop"))?;
+
+        assert_eq!(Walk::new(root).files(), vec![root.join("a/session.json")]);
+        assert!(Walk::new(root).try_files().is_err());
+        Ok(())
+    }
+}
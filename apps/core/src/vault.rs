@@ -2,6 +2,8 @@
 //!
 //! This module manages vault metadata and operations.
 
+use crate::crypto::EncryptionMeta;
+use crate::storage::atomic;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -14,6 +16,9 @@ pub struct VaultMetadata {
     pub version: u32,
     /// Vault creation timestamp
     pub created_at: String,
+    /// End-to-end encryption settings (absent = sync in plaintext)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionMeta>,
 }
 
 impl VaultMetadata {
@@ -22,8 +27,9 @@ impl VaultMetadata {
         use chrono::Utc;
 
         Self {
-            version: 2, // Version 2: simplified, no encryption
+            version: 2,
             created_at: Utc::now().to_rfc3339(),
+            encryption: None,
         }
     }
 
@@ -42,10 +48,27 @@ impl VaultMetadata {
         let path = vault_dir.join("vault.json");
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize vault metadata")?;
-        fs::write(&path, content).context(format!("Failed to write vault.json to {:?}", path))?;
+        // Write then rename so a crash never leaves a truncated vault.json
+        atomic::write(&path, content).context(format!("Failed to replace {:?}", path))?;
         Ok(())
     }
 
+    /// Load metadata, creating vault.json if it does not exist yet.
+    pub fn load_or_create(vault_dir: &Path) -> Result<Self> {
+        if Self::exists(vault_dir) {
+            return Self::load(vault_dir);
+        }
+        fs::create_dir_all(vault_dir)?;
+        let metadata = Self::new();
+        metadata.save(vault_dir)?;
+        Ok(metadata)
+    }
+
+    /// Whether synced data is end-to-end encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
     /// Check if vault.json exists.
     pub fn exists(vault_dir: &Path) -> bool {
         vault_dir.join("vault.json").exists()
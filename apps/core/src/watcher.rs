@@ -2,32 +2,99 @@
 //!
 //! This module provides event-driven file system monitoring.
 //! Replaces polling with native OS notifications for reduced RAM and CPU usage.
+//!
+//! Capture can be paused in-process (`FileWatcher::pause`, or a
+//! `FileWatcher::pause_handle` from another thread) or across
+//! processes via a marker file in the config directory (`set_capture_paused`),
+//! so the CLI can pause the desktop app's watchers and vice versa.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
-use std::sync::mpsc::{channel, Receiver};
-use std::time::Duration;
+/// Upper bound for a debounce window, as a multiple of the quiet period.
+/// Sources that write continuously would otherwise never settle.
+const MAX_DEBOUNCE_FACTOR: u32 = 10;
+
+/// Marker file name (in the config directory) for cross-process capture pause.
+const CAPTURE_PAUSED_FILE: &str = "capture.paused";
 
 /// File system event watcher.
 pub struct FileWatcher {
     watcher: RecommendedWatcher,
     rx: Receiver<Result<Event, notify::Error>>,
+    paused: PauseHandle,
+}
+
+/// Cloneable handle to pause/resume a `FileWatcher` from another thread.
+#[derive(Debug, Clone)]
+pub struct PauseHandle(Arc<AtomicBool>);
+
+impl PauseHandle {
+    /// Drop incoming events until resumed.
+    pub fn pause(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Resume delivering events.
+    pub fn resume(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether the watcher is paused.
+    pub fn is_paused(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
 }
 
 impl FileWatcher {
     /// Create a new file watcher.
     pub fn new() -> Result<Self> {
         let (tx, rx) = channel();
+        let paused = PauseHandle(Arc::new(AtomicBool::new(false)));
+        let paused_flag = paused.clone();
 
         let watcher = RecommendedWatcher::new(
             move |res| {
-                let _ = tx.send(res);
+                // Events that happen while paused are discarded, not queued
+                if !paused_flag.is_paused() {
+                    let _ = tx.send(res);
+                }
             },
             Config::default().with_poll_interval(Duration::from_secs(2)),
         )?;
 
-        Ok(Self { watcher, rx })
+        Ok(Self {
+            watcher,
+            rx,
+            paused,
+        })
+    }
+
+    /// Drop incoming events until resumed.
+    pub fn pause(&self) {
+        self.paused.pause();
+    }
+
+    /// Resume delivering events.
+    pub fn resume(&self) {
+        self.paused.resume();
+    }
+
+    /// Whether the watcher is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_paused()
+    }
+
+    /// Get a handle that can pause/resume this watcher from another thread.
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.paused.clone()
     }
 
     /// Start watching a directory recursively.
@@ -65,6 +132,117 @@ impl FileWatcher {
             _ => None,
         }
     }
+
+    /// Wait for changes and return them as one debounced batch (blocking).
+    ///
+    /// Blocks until the first create/modify/remove event, then keeps collecting
+    /// until no new event arrives for `quiet` (capped at `MAX_DEBOUNCE_FACTOR * quiet`).
+    /// Access events are ignored. Returns `None` once the watcher is shut down.
+    pub fn next_debounced(&self, quiet: Duration) -> Option<Vec<PathBuf>> {
+        let mut paths = BTreeSet::new();
+
+        // Wait for the first relevant event
+        loop {
+            match self.rx.recv() {
+                Ok(Ok(event)) if is_change(&event) => {
+                    paths.extend(event.paths);
+                    break;
+                }
+                Ok(_) => continue,
+                Err(_) => return None,
+            }
+        }
+
+        Some(self.collect_until_quiet(paths, quiet))
+    }
+
+    /// Like `next_debounced`, but gives up waiting for the first event after `timeout`.
+    ///
+    /// Returns `Some(vec![])` on timeout so callers can check shutdown flags
+    /// between waits.
+    pub fn next_debounced_timeout(
+        &self,
+        quiet: Duration,
+        timeout: Duration,
+    ) -> Option<Vec<PathBuf>> {
+        let mut paths = BTreeSet::new();
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.rx.recv_timeout(remaining) {
+                Ok(Ok(event)) if is_change(&event) => {
+                    paths.extend(event.paths);
+                    break;
+                }
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => return Some(Vec::new()),
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+
+        Some(self.collect_until_quiet(paths, quiet))
+    }
+
+    /// Keep collecting changed paths until no event arrives for `quiet`.
+    fn collect_until_quiet(&self, mut paths: BTreeSet<PathBuf>, quiet: Duration) -> Vec<PathBuf> {
+        let deadline = Instant::now() + quiet * MAX_DEBOUNCE_FACTOR;
+        while Instant::now() < deadline {
+            match self.rx.recv_timeout(quiet) {
+                Ok(Ok(event)) if is_change(&event) => paths.extend(event.paths),
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        paths.into_iter().collect()
+    }
+}
+
+/// Path of the cross-process capture pause marker.
+pub fn capture_paused_marker() -> PathBuf {
+    crate::config::default_config_dir().join(CAPTURE_PAUSED_FILE)
+}
+
+/// Whether capture is paused across processes (marker file present).
+pub fn is_capture_paused() -> bool {
+    capture_paused_marker().exists()
+}
+
+/// Persist the cross-process capture pause state.
+pub fn set_capture_paused(paused: bool) -> Result<()> {
+    let marker = capture_paused_marker();
+    if paused {
+        if let Some(parent) = marker.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&marker, chrono::Utc::now().to_rfc3339())
+            .with_context(|| format!("Cannot write {}", marker.display()))?;
+    } else if marker.exists() {
+        std::fs::remove_file(&marker)
+            .with_context(|| format!("Cannot remove {}", marker.display()))?;
+    }
+    Ok(())
+}
+
+/// Compile ignore glob patterns into a matcher.
+pub fn build_ignore_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).with_context(|| format!("Invalid ignore glob: {}", pattern))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Whether an event represents a content change worth reacting to.
+fn is_change(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    )
 }
 
 impl Default for FileWatcher {
@@ -77,4 +255,39 @@ impl Default for FileWatcher {
         Self::new().expect("Failed to create FileWatcher - check OS support for file watching")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_ignore_set() -> Result<()> {
+        let set = build_ignore_set(&["**/state.vscdb".to_string(), "**/*.tmp".to_string()])?;
+        assert!(set.is_match("/home/u/.config/Cursor/User/workspaceStorage/abc/state.vscdb"));
+        assert!(set.is_match("/tmp/session.tmp"));
+        assert!(!set.is_match("/home/u/.config/Cursor/chatSessions/abc.json"));
+
+        assert!(build_ignore_set(&["[".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pause_resume() -> Result<()> {
+        let watcher = FileWatcher::new()?;
+        let handle = watcher.pause_handle();
+        assert!(!handle.is_paused());
+
+        handle.pause();
+        assert!(watcher.pause_handle().is_paused());
+
+        watcher.pause_handle().resume();
+        assert!(!handle.is_paused());
+
+        watcher.pause();
+        assert!(watcher.is_paused() && handle.is_paused());
+        watcher.resume();
+        assert!(!watcher.is_paused());
+        Ok(())
+    }
+}
 // Trigger
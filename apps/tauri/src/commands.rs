@@ -1,10 +1,20 @@
 //! Tauri commands - API giữa frontend và backend
 //!
 //! Các commands này được gọi từ frontend qua IPC.
-//! Simplified version - only Rclone provider, no encryption.
-
+//! Sync provider theo `[sync] provider` (rclone hoặc thư mục local), mã hóa end-to-end tùy chọn.
+
+use echovault_core::config::{SyncProviderKind, DEFAULT_SYNC_TARGET};
+use echovault_core::crypto::{staging, EncryptionMeta, VaultKey};
+use echovault_core::dedupe;
+use echovault_core::i18n::{t, tf, Language};
+use echovault_core::parsers::vault_parse::{parse_vault, ParseResult};
+use echovault_core::secrets::{ExposeSecret, SecretString};
+use echovault_core::storage::compressed;
+use echovault_core::sync::{
+    create_provider, create_target_provider, delta, partition, TransferProgress,
+};
 use echovault_core::{
-    AuthStatus, Config, RcloneProvider, SyncOptions, SyncProvider, VaultMetadata,
+    AuthStatus, CancellationToken, Config, Operation, SyncOptions, SyncProvider, VaultMetadata,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
@@ -18,16 +28,21 @@ use std::os::windows::process::CommandExt;
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-/// State chứa RcloneProvider
+/// State chứa sync provider (chọn theo config) và key mã hóa đã mở khóa
 #[derive(Clone)]
 pub struct AppState {
-    pub provider: Arc<Mutex<RcloneProvider>>,
+    pub provider: Arc<Mutex<Box<dyn SyncProvider>>>,
+    /// Vault key (chỉ giữ trong bộ nhớ, None = chưa mở khóa)
+    pub vault_key: Arc<Mutex<Option<VaultKey>>>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            provider: Arc::new(Mutex::new(RcloneProvider::new())),
+            provider: Arc::new(Mutex::new(create_provider(
+                &Config::load_default().unwrap_or_default(),
+            ))),
+            vault_key: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -93,6 +108,38 @@ pub struct AppConfig {
     pub folder_name: String,
 }
 
+/// Cài đặt một nguồn ([sources.<name>]) cho Settings UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSettings {
+    pub name: String,
+    pub enabled: bool,
+    pub extra_paths: Vec<String>,
+    pub exclude: Vec<String>,
+    pub max_file_size_mb: Option<u64>,
+}
+
+/// Tinh chỉnh truyền tải khi sync ([sync]) cho Settings UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub transfers: usize,
+    pub checkers: usize,
+    /// Giới hạn băng thông (KiB/s), None = không giới hạn
+    pub bwlimit_kib: Option<u64>,
+    /// Thời gian tối đa một lần truyền bị treo (giây), None = mặc định của rclone
+    pub timeout_secs: Option<u64>,
+}
+
+/// Các profile cho Settings UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfilesInfo {
+    /// Profile đang dùng, None = echovault.toml
+    pub active: Option<String>,
+    /// Profile mặc định trong profiles.toml
+    pub default: Option<String>,
+    /// Tên tất cả profile (profiles.toml và [profiles.<name>])
+    pub profiles: Vec<String>,
+}
+
 /// Setup request từ frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupRequest {
@@ -124,16 +171,18 @@ pub async fn complete_setup(
     let mut config = Config::load_default().map_err(|e| e.to_string())?;
     let vault_path = config.vault_path.clone();
 
-    // Check if Rclone remote is configured
+    // Check if the sync provider is connected (rclone remote, mounted folder)
     let provider = state.provider.lock().map_err(|e| e.to_string())?;
-    if !provider.check_remote_exists().unwrap_or(false) {
-        return Err("Please connect to cloud storage first".to_string());
+    if !provider.is_authenticated() {
+        return Err(t("error.connect_cloud").to_string());
     }
     drop(provider);
 
     // Update config
     config.setup_complete = true;
-    config.sync.remote_name = Some("echovault".to_string());
+    if config.sync.provider == SyncProviderKind::Rclone {
+        config.sync.remote_name = Some("echovault".to_string());
+    }
     config.sync.folder_name = request.folder_name;
 
     config
@@ -174,6 +223,186 @@ pub async fn get_config() -> Result<AppConfig, String> {
     })
 }
 
+/// Kiểm tra config, trả về các vấn đề (lỗi trước, cảnh báo sau)
+#[tauri::command]
+pub async fn validate_config() -> Result<Vec<echovault_core::config::ConfigIssue>, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    Ok(config.validate())
+}
+
+/// Ngôn ngữ đang dùng cho tray, thông báo và lỗi (`en`, `vi`)
+#[tauri::command]
+pub async fn get_language() -> Result<Language, String> {
+    Ok(echovault_core::i18n::language())
+}
+
+/// Đặt ngôn ngữ (`null`: theo locale hệ thống), lưu vào config và
+/// cập nhật nhãn tray ngay
+#[tauri::command]
+pub async fn set_language(
+    app: tauri::AppHandle,
+    language: Option<Language>,
+) -> Result<Language, String> {
+    let mut config = Config::load_default().map_err(|e| e.to_string())?;
+    config.language = language;
+    config.save_default().map_err(|e| e.to_string())?;
+
+    let effective = config.language();
+    echovault_core::i18n::set_language(effective);
+    if let Some(tray) = app.tray_by_id(crate::TRAY_ID) {
+        let menu = crate::tray_menu(&app).map_err(|e| e.to_string())?;
+        tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+    }
+    Ok(effective)
+}
+
+/// Lấy cài đặt của tất cả nguồn
+#[tauri::command]
+pub async fn get_source_settings() -> Result<Vec<SourceSettings>, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    Ok(echovault_core::all_extractors()
+        .iter()
+        .map(|extractor| {
+            let name = extractor.source_name();
+            let settings = config.source(name);
+            SourceSettings {
+                name: name.to_string(),
+                enabled: settings.enabled,
+                extra_paths: settings
+                    .extra_paths
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect(),
+                exclude: settings.exclude,
+                max_file_size_mb: settings.max_file_size_mb,
+            }
+        })
+        .collect())
+}
+
+/// Lưu cài đặt của một nguồn
+#[tauri::command]
+pub async fn set_source_settings(settings: SourceSettings) -> Result<(), String> {
+    use echovault_core::config::{default_config_path, SourceConfig};
+
+    if !echovault_core::all_extractors()
+        .iter()
+        .any(|e| e.source_name() == settings.name)
+    {
+        return Err(tf("error.unknown_source", &[("source", &settings.name)]));
+    }
+    echovault_core::watcher::build_ignore_set(&settings.exclude).map_err(|e| e.to_string())?;
+
+    let mut config = Config::load_default().map_err(|e| e.to_string())?;
+    let source = SourceConfig {
+        enabled: settings.enabled,
+        extra_paths: settings
+            .extra_paths
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(std::path::PathBuf::from)
+            .collect(),
+        exclude: settings.exclude,
+        max_file_size_mb: settings.max_file_size_mb,
+    };
+
+    config.set_source(&settings.name, source);
+    config
+        .save(&default_config_path())
+        .map_err(|e| e.to_string())
+}
+
+/// Lấy cài đặt truyền tải khi sync
+#[tauri::command]
+pub async fn get_sync_settings() -> Result<SyncSettings, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    Ok(SyncSettings {
+        transfers: config.sync.transfers,
+        checkers: config.sync.checkers,
+        bwlimit_kib: config.sync.bwlimit_kib,
+        timeout_secs: config.sync.timeout_secs,
+    })
+}
+
+/// Lưu cài đặt truyền tải khi sync (áp dụng từ lần sync tiếp theo)
+#[tauri::command]
+pub async fn set_sync_settings(settings: SyncSettings) -> Result<(), String> {
+    use echovault_core::config::default_config_path;
+
+    let mut config = Config::load_default().map_err(|e| e.to_string())?;
+    config.sync.transfers = settings.transfers;
+    config.sync.checkers = settings.checkers;
+    config.sync.bwlimit_kib = settings.bwlimit_kib;
+    config.sync.timeout_secs = settings.timeout_secs;
+
+    let errors: Vec<String> = config
+        .validate()
+        .into_iter()
+        .filter(|issue| issue.is_error() && issue.key.starts_with("sync."))
+        .map(|issue| issue.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return Err(tf(
+            "error.invalid_config",
+            &[("issues", &errors.join("; "))],
+        ));
+    }
+    config
+        .save(&default_config_path())
+        .map_err(|e| e.to_string())
+}
+
+/// Lấy danh sách profile và profile đang dùng
+#[tauri::command]
+pub async fn get_profiles() -> Result<ProfilesInfo, String> {
+    use echovault_core::config::{active_profile, main_config_path, ProfilesIndex};
+
+    let index = ProfilesIndex::load_default().map_err(|e| e.to_string())?;
+    let mut profiles: Vec<String> = index.profiles.keys().cloned().collect();
+    let main = main_config_path();
+    if main.exists() {
+        let config = Config::load(&main).map_err(|e| e.to_string())?;
+        profiles.extend(config.profiles.into_keys());
+    }
+    profiles.sort();
+    profiles.dedup();
+    Ok(ProfilesInfo {
+        active: active_profile(),
+        default: index.default,
+        profiles,
+    })
+}
+
+/// Chọn profile mặc định (None = echovault.toml), lưu vào profiles.toml rồi
+/// khởi động lại app với vault của profile đó
+#[tauri::command]
+pub async fn set_profile(app: tauri::AppHandle, profile: Option<String>) -> Result<(), String> {
+    use echovault_core::config::{ProfilesIndex, PROFILE_ENV};
+    use std::sync::atomic::Ordering;
+
+    // ECHOVAULT_PROFILE luôn được ưu tiên hơn profiles.toml
+    if std::env::var(PROFILE_ENV).is_ok_and(|v| !v.is_empty()) {
+        return Err(tf("error.profile_env", &[("var", PROFILE_ENV)]));
+    }
+    if let Some(name) = &profile {
+        if !get_profiles().await?.profiles.contains(name) {
+            return Err(tf("error.unknown_profile", &[("profile", name)]));
+        }
+    }
+    if SYNC_IN_PROGRESS.load(Ordering::SeqCst) {
+        return Err(t("error.sync_in_progress").to_string());
+    }
+
+    let mut index = ProfilesIndex::load_default().map_err(|e| e.to_string())?;
+    index.default = profile;
+    index.save_default().map_err(|e| e.to_string())?;
+    info!("[set_profile] Default profile: {:?}", index.default);
+
+    // Watcher và state đang giữ vault của profile cũ
+    app.restart()
+}
+
 // ============ AUTH COMMANDS ============
 
 /// Lấy trạng thái auth hiện tại
@@ -204,62 +433,44 @@ pub async fn complete_auth(state: State<'_, AppState>) -> Result<AuthStatusRespo
 /// Scan tất cả sessions có sẵn (local + synced từ vault)
 #[tauri::command]
 pub async fn scan_sessions() -> Result<ScanResult, String> {
-    use echovault_core::extractors::{
-        aider::AiderExtractor, antigravity::AntigravityExtractor, claude_code::ClaudeCodeExtractor,
-        cline::ClineExtractor, codex::CodexExtractor, continue_dev::ContinueDevExtractor,
-        cursor::CursorExtractor, gemini_cli::GeminiCliExtractor, jetbrains::JetBrainsExtractor,
-        opencode::OpenCodeExtractor, vscode_copilot::VSCodeCopilotExtractor, zed::ZedExtractor,
-        Extractor,
-    };
     use std::collections::HashSet;
 
     let sessions = tokio::task::spawn_blocking(move || {
         let mut all_sessions = Vec::new();
-        let mut seen_ids: HashSet<String> = HashSet::new();
-
-        // Helper macro to scan an extractor
-        macro_rules! scan_extractor {
-            ($extractor:expr) => {
-                if let Ok(locations) = $extractor.find_storage_locations() {
-                    for location in locations {
-                        if let Ok(files) = $extractor.list_session_files(&location) {
-                            for file in files {
-                                let id = file.metadata.id.clone();
-                                if seen_ids.insert(id) {
-                                    all_sessions.push(SessionInfo {
-                                        id: file.metadata.id,
-                                        source: file.metadata.source,
-                                        title: file.metadata.title,
-                                        workspace_name: file.metadata.workspace_name,
-                                        created_at: file
-                                            .metadata
-                                            .created_at
-                                            .map(|d| d.to_rfc3339()),
-                                        file_size: file.metadata.file_size,
-                                        path: file.source_path.to_string_lossy().to_string(),
-                                    });
-                                }
-                            }
-                        }
+        // Session trùng với session của source khác chỉ hiện một lần
+        let mut seen_ids: HashSet<String> = Config::load_default()
+            .map(|config| dedupe::duplicate_ids(&config.vault_path))
+            .unwrap_or_default();
+
+        // Scan every source in the registry, except those disabled in config
+        let config = Config::load_default().unwrap_or_default();
+        for extractor in echovault_core::all_extractors() {
+            if !config.source(extractor.source_name()).enabled {
+                continue;
+            }
+            let Ok(locations) = extractor.find_storage_locations() else {
+                continue;
+            };
+            for location in locations {
+                let Ok(files) = extractor.list_session_files(&location) else {
+                    continue;
+                };
+                for file in files {
+                    if seen_ids.insert(file.metadata.id.clone()) {
+                        all_sessions.push(SessionInfo {
+                            id: file.metadata.id,
+                            source: file.metadata.source,
+                            title: file.metadata.title,
+                            workspace_name: file.metadata.workspace_name,
+                            created_at: file.metadata.created_at.map(|d| d.to_rfc3339()),
+                            file_size: file.metadata.file_size,
+                            path: file.source_path.to_string_lossy().to_string(),
+                        });
                     }
                 }
-            };
+            }
         }
 
-        // Scan all supported extractors
-        scan_extractor!(VSCodeCopilotExtractor::new());
-        scan_extractor!(CursorExtractor::new());
-        scan_extractor!(ClineExtractor::new());
-        scan_extractor!(AntigravityExtractor::new());
-        scan_extractor!(GeminiCliExtractor::new());
-        scan_extractor!(ClaudeCodeExtractor::new());
-        scan_extractor!(AiderExtractor::new());
-        scan_extractor!(CodexExtractor::new());
-        scan_extractor!(ContinueDevExtractor::new());
-        scan_extractor!(OpenCodeExtractor::new());
-        scan_extractor!(ZedExtractor::new());
-        scan_extractor!(JetBrainsExtractor::new());
-
         // 5. Read sessions from vault.db (synced from other machines)
         // Use inner scope to ensure VaultDb connection is dropped before returning
         if let Ok(config) = Config::load_default() {
@@ -354,7 +565,7 @@ fn find_vault_session_info(
             vec![format!("{}.md", clean_name), file_name.to_string()]
         } else {
             // Normal session - try both .json and .jsonl extensions
-            let extension = if source == "antigravity" {
+            let extension = if source == "antigravity" || source == "windsurf" {
                 "pb"
             } else {
                 "json" // Will try jsonl as fallback below
@@ -414,7 +625,7 @@ fn find_vault_session_info(
 
 /// Import sessions from vault/sessions folder into vault.db
 /// This is called after pull to import sessions from other machines
-fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
+pub(crate) fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
     use echovault_core::storage::{SessionEntry, VaultDb};
     use std::fs;
 
@@ -463,7 +674,9 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
             // Scan session files in this source directory (supports .json, .jsonl, .pb, .md)
             if let Ok(files) = fs::read_dir(&source_dir) {
                 for file in files.filter_map(|f| f.ok()) {
-                    let file_path = file.path();
+                    // Compressed files are read through a plain copy
+                    let stored_path = file.path();
+                    let file_path = compressed::plain_path(&stored_path);
 
                     // Check for supported extensions
                     let extension = file_path.extension().and_then(|e| e.to_str());
@@ -486,7 +699,7 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
                     }
 
                     // Get file metadata
-                    let metadata = match fs::metadata(&file_path) {
+                    let metadata = match fs::metadata(&stored_path) {
                         Ok(m) => m,
                         Err(_) => continue,
                     };
@@ -506,6 +719,13 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
                     }
 
                     // Extract metadata based on file type
+                    let plain = match compressed::Plain::open(&stored_path, &sessions_dir) {
+                        Ok(plain) => plain,
+                        Err(_) => continue,
+                    };
+                    let file_path = plain.path();
+                    let file_size = fs::metadata(file_path).map_or(metadata.len(), |m| m.len());
+
                     let (title, workspace_name, created_at) = if extension == Some("json")
                         || extension == Some("jsonl")
                     {
@@ -514,7 +734,7 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
                             Some("jsonl") => {
                                 // JSONL: read first line, parse v.customTitle/v.creationDate
                                 use std::io::BufRead;
-                                match std::fs::File::open(&file_path) {
+                                match std::fs::File::open(file_path) {
                                     Ok(file) => {
                                         let reader = std::io::BufReader::new(file);
                                         if let Some(Ok(first_line)) = reader.lines().next() {
@@ -557,7 +777,7 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
                             }
                             _ => {
                                 // Standard JSON
-                                match fs::read_to_string(&file_path) {
+                                match fs::read_to_string(file_path) {
                                     Ok(content) => {
                                         if let Ok(json) =
                                             serde_json::from_str::<serde_json::Value>(&content)
@@ -591,19 +811,26 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
                         (None, None, None)
                     };
 
-                    let ext = extension.unwrap_or("json");
-                    let vault_path = format!("sessions/{}/{}.{}", source_name, session_id, ext);
+                    let vault_path = format!(
+                        "sessions/{}/{}",
+                        source_name,
+                        stored_path
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                    );
 
                     sessions_to_import.push(SessionEntry {
                         id: session_id,
                         source: source_name.clone(),
                         mtime: file_mtime,
-                        file_size: metadata.len(),
+                        file_size,
                         title,
                         workspace_name,
                         created_at,
                         vault_path,
-                        original_path: file_path.to_string_lossy().to_string(),
+                        original_path: stored_path.to_string_lossy().to_string(),
+                        content_hash: echovault_core::storage::mapped::blake3_file(file_path).ok(),
                     });
                 }
             }
@@ -632,273 +859,311 @@ fn import_vault_sessions(vault_dir: &std::path::Path) -> Result<usize, String> {
 }
 
 /// Ingest sessions từ local extractors vào vault
-fn ingest_sessions(vault_dir: &std::path::Path) -> Result<bool, String> {
-    use echovault_core::extractors::{
-        aider::AiderExtractor, antigravity::AntigravityExtractor, claude_code::ClaudeCodeExtractor,
-        cline::ClineExtractor, codex::CodexExtractor, continue_dev::ContinueDevExtractor,
-        cursor::CursorExtractor, gemini_cli::GeminiCliExtractor, jetbrains::JetBrainsExtractor,
-        opencode::OpenCodeExtractor, vscode_copilot::VSCodeCopilotExtractor, zed::ZedExtractor,
-        Extractor,
-    };
-    use rayon::prelude::*;
-
-    use parking_lot::Mutex;
-    use std::fs;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-
+///
+/// `only`: chỉ quét các nguồn này (ví dụ nguồn mà watcher vừa thấy thay đổi).
+/// `op` hủy giữa chừng: sessions đã copy vẫn được ghi vào vault.db, phần còn lại
+/// sẽ được ingest lần sau.
+pub(crate) fn ingest_sessions(
+    vault_dir: &std::path::Path,
+    only: Option<&[&str]>,
+    op: &Operation,
+) -> Result<bool, String> {
     // Configure thread pool: use num_cpus - 2 (minimum 1)
     let num_threads = std::cmp::max(1, num_cpus::get().saturating_sub(2));
     info!(
         "[ingest_sessions] Using {} threads for parallel processing",
         num_threads
     );
-
-    // Build custom thread pool
     let pool = rayon::ThreadPoolBuilder::new()
         .num_threads(num_threads)
         .build()
         .map_err(|e| format!("Failed to build thread pool: {}", e))?;
 
-    info!("[ingest_sessions] Starting scan...");
+    // Cài đặt từng nguồn trong config ([sources.<name>], enabled_sources)
+    let config = echovault_core::Config::load_default().unwrap_or_default();
 
-    let mut sessions = Vec::new();
+    info!("[ingest_sessions] Starting scan...");
+    let report = pool
+        .install(|| match only {
+            Some(sources) => {
+                echovault_core::extractors::ingest::ingest_sources(vault_dir, &config, sources, op)
+            }
+            None => echovault_core::extractors::ingest::ingest_sessions(vault_dir, &config, op),
+        })
+        .map_err(|e| e.to_string())?;
+    for (source, found) in &report.sources {
+        match found {
+            Ok(count) => info!("[ingest_sessions] {}: {} files", source, count),
+            Err(e) => warn!("[ingest_sessions] {}: {}", source, e),
+        }
+    }
 
-    // Helper macro to scan an extractor into sessions vec
-    macro_rules! ingest_extractor {
-        ($extractor:expr, $name:literal) => {
-            if let Ok(locations) = $extractor.find_storage_locations() {
-                info!("[ingest_sessions] {}: {} locations", $name, locations.len());
-                for location in &locations {
-                    if let Ok(files) = $extractor.list_session_files(location) {
-                        info!(
-                            "[ingest_sessions] {} {:?}: {} files",
-                            $name,
-                            location.file_name().unwrap_or_default(),
-                            files.len()
-                        );
-                        sessions.extend(files);
-                    }
-                }
+    // Điền title/created_at (bỏ qua khi liệt kê) ở background, từ bản copy trong vault
+    if report.needs_enrichment() {
+        let vault_dir = vault_dir.to_path_buf();
+        std::thread::spawn(move || {
+            match echovault_core::extractors::enrich::enrich_metadata(&vault_dir) {
+                Ok(count) => info!("[ingest_sessions] Enriched metadata of {} sessions", count),
+                Err(e) => warn!("[ingest_sessions] Failed to enrich metadata: {}", e),
             }
-        };
+        });
     }
 
-    // Scan all extractors
-    ingest_extractor!(VSCodeCopilotExtractor::new(), "VS Code Copilot");
-    ingest_extractor!(CursorExtractor::new(), "Cursor");
-    ingest_extractor!(ClineExtractor::new(), "Cline");
-    ingest_extractor!(AntigravityExtractor::new(), "Antigravity");
-    ingest_extractor!(GeminiCliExtractor::new(), "Gemini CLI");
-    ingest_extractor!(ClaudeCodeExtractor::new(), "Claude Code");
-    ingest_extractor!(AiderExtractor::new(), "Aider");
-    ingest_extractor!(CodexExtractor::new(), "Codex");
-    ingest_extractor!(ContinueDevExtractor::new(), "Continue.dev");
-    ingest_extractor!(OpenCodeExtractor::new(), "OpenCode");
-    ingest_extractor!(ZedExtractor::new(), "Zed");
-    ingest_extractor!(JetBrainsExtractor::new(), "JetBrains AI");
-
-    let total_sessions = sessions.len();
     info!(
-        "[ingest_sessions] Total sessions to check: {}",
-        total_sessions
+        "[ingest_sessions] Complete: {} processed, {} skipped, {} errors",
+        report.copied,
+        report.up_to_date,
+        report.errors.len()
     );
+    if report.copied > 0 {
+        send_notification(echovault_core::notifications::Event::SessionsExtracted {
+            count: report.copied,
+        });
+    }
+    Ok(report.copied > 0)
+}
 
-    // 2. Open VaultDb for deduplication (with retry for concurrent access)
-    info!("[ingest_sessions] Opening VaultDb at {:?}...", vault_dir);
-    let vault_db = {
-        let mut attempts = 0;
-        let max_attempts = 3;
-        loop {
-            attempts += 1;
-            match echovault_core::storage::VaultDb::open(vault_dir) {
-                Ok(db) => {
-                    info!("[ingest_sessions] VaultDb opened successfully");
-                    break db;
-                }
-                Err(e) => {
-                    if attempts >= max_attempts {
-                        error!(
-                            "[ingest_sessions] Failed to open vault.db after {} attempts: {}",
-                            max_attempts, e
-                        );
-                        return Err(format!("Failed to open vault.db: {}", e));
-                    }
-                    warn!(
-                        "[ingest_sessions] VaultDb open attempt {} failed: {}, retrying...",
-                        attempts, e
-                    );
-                    std::thread::sleep(std::time::Duration::from_millis(500));
-                }
-            }
+/// Lock để prevent concurrent sync/ingest từ cùng instance
+/// (dùng chung giữa sync_vault và auto-extract watcher)
+pub(crate) static SYNC_IN_PROGRESS: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// Token hủy của các thao tác dài đang chạy (sync, ingest, parse, embed)
+static RUNNING_OPERATIONS: Mutex<Vec<CancellationToken>> = Mutex::new(Vec::new());
+
+/// Thao tác dài có thể hủy từ UI qua `cancel_operations`; bỏ đăng ký khi drop
+pub(crate) struct RunningOperation(Operation);
+
+impl RunningOperation {
+    pub(crate) fn start() -> Self {
+        let op = Operation::default();
+        if let Ok(mut running) = RUNNING_OPERATIONS.lock() {
+            running.push(op.token().clone());
         }
-    };
+        Self(op)
+    }
+}
 
-    let sessions_dir = vault_dir.join("sessions");
-    fs::create_dir_all(&sessions_dir).map_err(|e| e.to_string())?;
+impl std::ops::Deref for RunningOperation {
+    type Target = Operation;
 
-    // 3. Filter sessions that need processing
-    let sessions_to_process: Vec<_> = sessions
-        .into_iter()
-        .filter_map(|session| {
-            let source_path = &session.metadata.original_path;
-            let file_size = session.metadata.file_size;
-
-            // Skip if source file doesn't exist
-            let metadata = match fs::metadata(source_path) {
-                Ok(m) => m,
-                Err(_) => return None,
-            };
+    fn deref(&self) -> &Operation {
+        &self.0
+    }
+}
 
-            let mtime = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
-
-            // Check against vault.db
-            let should_process = match vault_db.get_session_mtime(&session.metadata.id) {
-                Ok(Some(cached_mtime)) => mtime > cached_mtime,
-                Ok(None) => true,
-                Err(_) => true, // Process if we can't check
-            };
+impl Drop for RunningOperation {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING_OPERATIONS.lock() {
+            running.retain(|token| token != self.0.token());
+        }
+    }
+}
 
-            if should_process {
-                Some((session, mtime, file_size))
-            } else {
-                None
-            }
-        })
-        .collect();
+/// Hủy các thao tác dài đang chạy. Mỗi thao tác dừng sau bước hiện tại và giữ
+/// nguyên những gì đã ghi. Trả về số thao tác bị hủy.
+#[tauri::command]
+pub fn cancel_operations() -> usize {
+    let running = RUNNING_OPERATIONS
+        .lock()
+        .map(|running| running.clone())
+        .unwrap_or_default();
+    for token in &running {
+        token.cancel();
+    }
+    running.len()
+}
 
-    let to_process = sessions_to_process.len();
-    let skipped = total_sessions - to_process;
-    info!(
-        "[ingest_sessions] To process: {}, Already up-to-date: {}",
-        to_process, skipped
-    );
+/// Thời điểm (unix secs) lần cuối sync/ingest của chính app nhả lock.
+/// Vault watcher dùng để bỏ qua thay đổi do chính app ghi vào vault.
+pub(crate) static LAST_LOCAL_WRITE: std::sync::atomic::AtomicU64 =
+    std::sync::atomic::AtomicU64::new(0);
+
+/// Nhả sync lock và ghi nhận thời điểm ghi vault cục bộ
+pub(crate) fn release_sync_lock() {
+    use std::sync::atomic::Ordering;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    LAST_LOCAL_WRITE.store(now, Ordering::SeqCst);
+    SYNC_IN_PROGRESS.store(false, Ordering::SeqCst);
+}
 
-    if to_process == 0 {
-        info!("[ingest_sessions] Nothing to process, complete");
-        return Ok(false);
-    }
-
-    // 4. Process sessions in parallel
-    let processed = AtomicUsize::new(0);
-    let errors = Mutex::new(Vec::<String>::new());
-    let new_entries = Mutex::new(Vec::<echovault_core::storage::SessionEntry>::new());
-
-    pool.install(|| {
-        sessions_to_process
-            .par_iter()
-            .for_each(|(session, mtime, file_size)| {
-                let source_path = &session.metadata.original_path;
-                let dest_dir = sessions_dir.join(&session.metadata.source);
-
-                // Create dest dir
-                if let Err(e) = fs::create_dir_all(&dest_dir) {
-                    errors
-                        .lock()
-                        .push(format!("Failed to create dir {:?}: {}", dest_dir, e));
-                    return;
-                }
+/// Giai đoạn hiện tại của sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncPhase {
+    Idle,
+    Pull,
+    Import,
+    Ingest,
+    Parse,
+    Push,
+}
 
-                let current = processed.fetch_add(1, Ordering::Relaxed) + 1;
-                if current.is_multiple_of(10) || current == to_process {
-                    info!(
-                        "[ingest_sessions] Progress: {}/{} ({}%)",
-                        current,
-                        to_process,
-                        current * 100 / to_process
-                    );
-                }
+/// Tiến độ sync, gửi qua event `sync-progress` và trả về bởi `get_sync_state`.
+/// Pull/push đếm file và byte đã truyền; ingest/parse chỉ đếm file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncState {
+    pub phase: SyncPhase,
+    pub files_done: u64,
+    pub files_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
 
-                // Prepare destination path
-                let extension = source_path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("json");
+impl SyncState {
+    const IDLE: SyncState = SyncState {
+        phase: SyncPhase::Idle,
+        files_done: 0,
+        files_total: 0,
+        bytes_done: 0,
+        bytes_total: 0,
+    };
 
-                // Smart filename handling
-                let dest_path = if session.metadata.id.ends_with(extension) {
-                    dest_dir.join(&session.metadata.id)
-                } else {
-                    dest_dir.join(format!("{}.{}", session.metadata.id, extension))
-                };
+    fn phase(phase: SyncPhase) -> Self {
+        Self {
+            phase,
+            ..Self::IDLE
+        }
+    }
+}
 
-                // Ensure parent directory exists (for IDs with slashes)
-                if let Some(parent) = dest_path.parent() {
-                    if !parent.exists() {
-                        let _ = fs::create_dir_all(parent);
-                    }
-                }
+static SYNC_STATE: Mutex<SyncState> = Mutex::new(SyncState::IDLE);
 
-                if let Err(e) = fs::copy(source_path, &dest_path) {
-                    errors
-                        .lock()
-                        .push(format!("Failed to copy {}: {}", session.metadata.id, e));
-                    return;
-                }
+/// Ghi nhận tiến độ sync và gửi event `sync-progress` tới frontend
+fn report_sync_state(app: &tauri::AppHandle, state: SyncState) {
+    use tauri::Emitter;
 
-                // Record for vault.db update
-                new_entries
-                    .lock()
-                    .push(echovault_core::storage::SessionEntry {
-                        id: session.metadata.id.clone(),
-                        source: session.metadata.source.clone(),
-                        mtime: *mtime,
-                        file_size: *file_size,
-                        title: session.metadata.title.clone(),
-                        workspace_name: session.metadata.workspace_name.clone(),
-                        created_at: session.metadata.created_at.map(|d| d.to_rfc3339()),
-                        vault_path: dest_path.to_string_lossy().to_string(),
-                        original_path: source_path.to_string_lossy().to_string(),
-                    });
-            });
-    });
+    if let Ok(mut current) = SYNC_STATE.lock() {
+        *current = state.clone();
+    }
+    let _ = app.emit("sync-progress", state);
+}
 
-    // Check for errors
-    let errs = errors.into_inner();
-    if !errs.is_empty() {
-        info!("[ingest_sessions] {} errors occurred:", errs.len());
-        for e in &errs {
-            warn!("{}", e);
-        }
-        // Continue anyway, just log errors
+/// Giai đoạn và tiến độ của sync đang chạy (`idle` khi không có sync)
+#[tauri::command]
+pub fn get_sync_state() -> SyncState {
+    SYNC_STATE
+        .lock()
+        .map(|state| state.clone())
+        .unwrap_or(SyncState::IDLE)
+}
+
+/// Khoảng cách tối thiểu giữa hai event tiến độ của ingest/parse
+const SYNC_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Nhận tiến độ ingest/parse (đơn vị: file) và gửi thành event `sync-progress`
+struct SyncProgress {
+    app: tauri::AppHandle,
+    phase: SyncPhase,
+    done: std::sync::atomic::AtomicU64,
+    total: std::sync::atomic::AtomicU64,
+    last_report: Mutex<std::time::Instant>,
+}
+
+impl SyncProgress {
+    fn operation(app: &tauri::AppHandle, phase: SyncPhase, op: &Operation) -> Operation {
+        report_sync_state(app, SyncState::phase(phase));
+        let progress = Self {
+            app: app.clone(),
+            phase,
+            done: Default::default(),
+            total: Default::default(),
+            last_report: Mutex::new(std::time::Instant::now()),
+        };
+        Operation::new(op.token().clone(), Arc::new(progress))
     }
 
-    // 5. Update vault.db
-    let entries = new_entries.into_inner();
-    if !entries.is_empty() {
-        for entry in &entries {
-            if let Err(e) = vault_db.upsert_session(entry) {
-                warn!("[ingest_sessions] Failed to upsert {}: {}", entry.id, e);
-            }
-        }
-        if let Err(e) = vault_db.log_sync("ingest", Some(&format!("{} sessions", entries.len()))) {
-            warn!("[ingest_sessions] Failed to log sync: {}", e);
+    fn report(&self, force: bool) {
+        use std::sync::atomic::Ordering;
+
+        let Ok(mut last_report) = self.last_report.lock() else {
+            return;
+        };
+        if !force && last_report.elapsed() < SYNC_PROGRESS_INTERVAL {
+            return;
         }
-        info!(
-            "[ingest_sessions] vault.db updated with {} entries",
-            entries.len()
+        *last_report = std::time::Instant::now();
+        report_sync_state(
+            &self.app,
+            SyncState {
+                files_done: self.done.load(Ordering::Relaxed),
+                files_total: self.total.load(Ordering::Relaxed),
+                ..SyncState::phase(self.phase)
+            },
         );
     }
+}
 
-    info!(
-        "[ingest_sessions] Complete: {} processed, {} skipped",
-        processed.load(Ordering::Relaxed),
-        skipped
-    );
-    Ok(true)
+impl echovault_core::progress::Progress for SyncProgress {
+    // Parse chạy từng nguồn một: cộng dồn tổng số file của các nguồn
+    fn start(&self, _stage: &str, total: u64) {
+        self.total
+            .fetch_add(total, std::sync::atomic::Ordering::Relaxed);
+        self.report(true);
+    }
+
+    fn advance(&self, n: u64) {
+        self.done.fetch_add(n, std::sync::atomic::Ordering::Relaxed);
+        self.report(false);
+    }
+
+    fn finish(&self) {
+        self.report(true);
+    }
+}
+
+/// Tiến độ pull/push của provider, gửi thành event `sync-progress`
+fn transfer_progress(app: &tauri::AppHandle, phase: SyncPhase) -> TransferProgress {
+    report_sync_state(app, SyncState::phase(phase));
+    let app = app.clone();
+    TransferProgress::new(move |stats| {
+        report_sync_state(
+            &app,
+            SyncState {
+                phase,
+                files_done: stats.files_done,
+                files_total: stats.files_total,
+                bytes_done: stats.bytes_done,
+                bytes_total: stats.bytes_total,
+            },
+        )
+    })
 }
 
 /// Sync vault với cloud (Pull -> Ingest -> Push)
+///
+/// Tiến độ từng giai đoạn được gửi qua event `sync-progress` (xem [`SyncState`]).
+///
+/// `background = true` cho sync định kỳ: bị hoãn khi chạy pin hoặc mạng metered
+/// (theo `[schedule]` trong config). Sync thủ công luôn chạy. Sync nền cũng
+/// ghi các digest đã đến hạn (theo `[digest]`) trước khi push, và bỏ qua bước
+/// ingest khi capture đang tạm dừng.
 #[tauri::command]
-pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
-    use std::sync::atomic::{AtomicBool, Ordering};
-
-    // Local sync lock để prevent concurrent sync từ cùng instance
-    static SYNC_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+pub async fn sync_vault(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    background: Option<bool>,
+) -> Result<String, String> {
+    use std::sync::atomic::Ordering;
+
+    if background.unwrap_or(false) {
+        let schedule = Config::load_default()
+            .map(|c| c.schedule)
+            .unwrap_or_default();
+        let reason = tokio::task::spawn_blocking(move || {
+            echovault_core::utils::power::defer_reason(&schedule)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        if let Some(reason) = reason {
+            info!("[sync_vault] Background sync deferred: {}", reason);
+            return Ok(format!("Sync deferred: {}", reason));
+        }
+    }
 
     // Try to acquire lock
     if SYNC_IN_PROGRESS
@@ -910,17 +1175,35 @@ pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
     }
 
     // Ensure lock is released when function returns
-    struct SyncLockGuard;
+    struct SyncLockGuard(tauri::AppHandle);
     impl Drop for SyncLockGuard {
         fn drop(&mut self) {
-            SYNC_IN_PROGRESS.store(false, Ordering::SeqCst);
+            release_sync_lock();
+            report_sync_state(&self.0, SyncState::IDLE);
             info!("[sync_vault] Sync lock released");
         }
     }
-    let _lock_guard = SyncLockGuard;
+    let _lock_guard = SyncLockGuard(app.clone());
+    let op = RunningOperation::start();
 
     info!("[sync_vault] Starting (lock acquired)...");
 
+    // Báo lỗi config ngay thay vì thất bại giữa chừng
+    if let Ok(config) = Config::load_default() {
+        let errors: Vec<String> = config
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .map(|issue| issue.to_string())
+            .collect();
+        if !errors.is_empty() {
+            return Err(tf(
+                "error.invalid_config",
+                &[("issues", &errors.join("; "))],
+            ));
+        }
+    }
+
     // Check auth status
     {
         let provider = state.provider.lock().map_err(|e| {
@@ -933,7 +1216,7 @@ pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
         );
         if !provider.is_authenticated() {
             info!("[sync_vault] Not authenticated, returning error");
-            return Err("Not authenticated".to_string());
+            return Err(t("error.not_authenticated").to_string());
         }
     }
 
@@ -945,12 +1228,41 @@ pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
     })?;
     let vault_dir = config.vault_path.clone();
     info!("[sync_vault] vault_dir: {:?}", vault_dir);
+    let timer = echovault_core::metrics::Timer::start(echovault_core::metrics::SYNC_DURATION_MS);
+
+    // Encrypted vaults sync through the staging directory
+    let encrypted = VaultMetadata::load(&vault_dir)
+        .map(|meta| meta.is_encrypted())
+        .unwrap_or(false);
+    let key = if encrypted {
+        let key = state.vault_key.lock().map_err(|e| e.to_string())?.clone();
+        if key.is_none() {
+            return Err(t("error.vault_locked").to_string());
+        }
+        key
+    } else {
+        None
+    };
+    let sync_root = if key.is_some() {
+        staging::staging_dir(&vault_dir)
+    } else {
+        vault_dir.clone()
+    };
 
     // 1. Pull from Remote (get changes from other machines first)
     info!("[sync_vault] Pulling from remote...");
-    let vault_dir_for_pull = vault_dir.clone();
+    let vault_dir_for_pull = sync_root.clone();
     let provider_for_pull = state.provider.clone();
-    let options_for_pull = SyncOptions::default();
+    // Session của workspace được gán cho target khác không được pull/push
+    let options_for_pull = SyncOptions {
+        cancel: op.token().clone(),
+        progress: transfer_progress(&app, SyncPhase::Pull),
+        exclude: partition::excluded(&config, DEFAULT_SYNC_TARGET)
+            .map_err(|e| e.to_string())?
+            .paths,
+        skip_newer: key.is_some() || config.redaction.applies_to(DEFAULT_SYNC_TARGET),
+        ..SyncOptions::for_sync(&config.sync)
+    };
 
     let pull_result = tokio::task::spawn_blocking(move || {
         let provider = provider_for_pull.lock().map_err(|e| e.to_string())?;
@@ -973,9 +1285,23 @@ pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
             warn!("[sync_vault] Pull failed (continuing anyway): {}", e);
         }
     }
+    op.check().map_err(|e| e.to_string())?;
+
+    if let Some(key) = key.clone() {
+        let vault_dir_for_open = vault_dir.clone();
+        let allow_missing_manifest = config.encryption.allow_missing_manifest;
+        let opened = tokio::task::spawn_blocking(move || {
+            staging::open(&vault_dir_for_open, &key, allow_missing_manifest)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+        info!("[sync_vault] Decrypted {} files", opened.decrypted);
+    }
 
     // 2. Import sessions from vault/sessions folder (pulled from other machines)
     info!("[sync_vault] Importing vault sessions...");
+    report_sync_state(&app, SyncState::phase(SyncPhase::Import));
     let vault_dir_for_import = vault_dir.clone();
     let import_result =
         tokio::task::spawn_blocking(move || import_vault_sessions(&vault_dir_for_import))
@@ -987,62 +1313,86 @@ pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
     );
 
     // 3. Ingest Sessions (local extractors -> vault)
-    info!("[sync_vault] Ingesting sessions...");
-    let vault_dir_for_ingest = vault_dir.clone();
-    let ingest_result = tokio::task::spawn_blocking(move || ingest_sessions(&vault_dir_for_ingest))
+    // Capture đang tạm dừng: sync định kỳ không được ingest thay cho watcher
+    if background.unwrap_or(false) && crate::watcher::is_paused() {
+        info!("[sync_vault] Capture paused, skipping ingest");
+    } else {
+        info!("[sync_vault] Ingesting sessions...");
+        let vault_dir_for_ingest = vault_dir.clone();
+        let op_for_ingest = SyncProgress::operation(&app, SyncPhase::Ingest, &op);
+        let ingest_result = tokio::task::spawn_blocking(move || {
+            let started = chrono::Utc::now().timestamp_millis();
+            let result = ingest_sessions(&vault_dir_for_ingest, None, &op_for_ingest)?;
+            // Full ingest covers everything the watchers queued before it started
+            if let Err(e) = echovault_core::storage::VaultDb::open(&vault_dir_for_ingest)
+                .and_then(|db| db.clear_pending_changes(started))
+            {
+                warn!("[sync_vault] Failed to clear pending changes: {}", e);
+            }
+            Ok::<bool, String>(result)
+        })
         .await
         .map_err(|e| e.to_string())??;
-    info!("[sync_vault] Ingest complete: changes={}", ingest_result);
+        info!("[sync_vault] Ingest complete: changes={}", ingest_result);
+    }
 
     // 3.5 Parse raw sessions to Markdown (non-blocking, best-effort)
     info!("[sync_vault] Parsing sessions...");
-    let vault_dir_for_parse = vault_dir.clone();
+    let op_for_parse = SyncProgress::operation(&app, SyncPhase::Parse, &op);
+    let config_for_parse = config.clone();
     let parse_result = tokio::task::spawn_blocking(move || {
-        use echovault_core::parsers::{all_parsers, markdown_writer, parse_vault_source};
-
-        let parsers = all_parsers();
-        let sessions_dir = vault_dir_for_parse.join("sessions");
-        let parsed_dir = vault_dir_for_parse.join("parsed");
-        let mut parsed_count = 0usize;
-
-        if !sessions_dir.exists() {
-            return parsed_count;
-        }
-
-        for parser in &parsers {
-            let (conversations, _errors) = parse_vault_source(parser.as_ref(), &sessions_dir);
-
-            for conv in &conversations {
-                let output_path = parsed_dir
-                    .join(&conv.source)
-                    .join(format!("{}.md", conv.id));
-
-                if output_path.exists() {
-                    continue;
-                }
-
-                if markdown_writer::write_markdown(conv, &output_path).is_ok() {
-                    parsed_count += 1;
-                }
+        // Bị hủy: giữ các file đã ghi, dừng parse
+        match parse_vault(&config_for_parse, &op_for_parse) {
+            Ok(result) => result.parsed,
+            Err(e) => {
+                warn!("[sync_vault] Parse stopped: {:#}", e);
+                0
             }
         }
-
-        parsed_count
     })
     .await
     .unwrap_or(0);
     info!("[sync_vault] Parse complete: {} new", parse_result);
+    op.check().map_err(|e| e.to_string())?;
+
+    if background.unwrap_or(false) {
+        write_due_digests(&app, &config).await;
+    }
 
-    // 4. Push to Remote
+    // 4. Push to Remote: chỉ các file đã thay đổi kể từ lần push trước. Khi có
+    // nhiều target, file pull từ target này phải tới các target khác, nên push
+    // so sánh toàn bộ cây thư mục
     info!("[sync_vault] Pushing to remote...");
-    let options = SyncOptions::default();
-    let vault_dir_clone = vault_dir.clone();
+    let delta_push = config.sync.targets.is_empty();
+    let mut options = SyncOptions {
+        cancel: op.token().clone(),
+        progress: transfer_progress(&app, SyncPhase::Push),
+        skip_newer: key.is_some() || config.redaction.applies_to(DEFAULT_SYNC_TARGET),
+        ..SyncOptions::for_sync(&config.sync)
+    };
+    let vault_dir_for_seal = vault_dir.clone();
+    let vault_dir_clone = sync_root.clone();
+    let config_for_push = config.clone();
+    let encrypted = key.is_some();
     let provider_clone = state.provider.clone();
+    let push_started = chrono::Utc::now().timestamp_millis();
 
-    let result = tokio::task::spawn_blocking(move || {
+    let push_result = tokio::task::spawn_blocking(move || {
+        if let Some(key) = &key {
+            let sealed = staging::seal(&vault_dir_for_seal, key).map_err(|e| e.to_string())?;
+            info!("[sync_vault] Encrypted {} changed files", sealed.encrypted);
+        }
+        options.exclude = partition::excluded(&config_for_push, DEFAULT_SYNC_TARGET)
+            .map_err(|e| e.to_string())?
+            .paths;
+        let push_root = redacted_push_root(&config_for_push, DEFAULT_SYNC_TARGET, encrypted)?
+            .unwrap_or(vault_dir_clone);
+        if delta_push {
+            options.files = delta::push_files(&vault_dir_for_seal, &push_root, DEFAULT_SYNC_TARGET);
+        }
         let provider = provider_clone.lock().map_err(|e| e.to_string())?;
         info!("[sync_vault] Calling provider.push...");
-        provider.push(&vault_dir_clone, &options).map_err(|e| {
+        provider.push(&push_root, &options).map_err(|e| {
             error!("[sync_vault] Push failed: {}", e);
             e.to_string()
         })
@@ -1051,22 +1401,157 @@ pub async fn sync_vault(state: State<'_, AppState>) -> Result<String, String> {
     .map_err(|e| {
         error!("[sync_vault] spawn_blocking failed: {}", e);
         e.to_string()
-    })??;
+    })?;
+    if !op.is_cancelled() {
+        record_remote_sync(
+            &vault_dir,
+            DEFAULT_SYNC_TARGET,
+            push_started,
+            push_result
+                .as_ref()
+                .map(|r| r.files_pushed)
+                .map_err(String::as_str),
+        );
+    }
+    let result = push_result?;
 
     info!(
         "[sync_vault] Push complete: files_pushed={}",
         result.files_pushed
     );
-    Ok(format!("Synced {} files", result.files_pushed))
-}
-
-// ============ UTILITY COMMANDS ============
 
-/// Mở URL trong browser
-#[tauri::command]
-pub async fn open_url(url: String) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
+    // 5. Push tới các target phụ ([sync.targets.<name>]); lỗi chỉ được ghi lại
+    let extra_targets: Vec<String> = config
+        .sync
+        .target_names()
+        .into_iter()
+        .filter(|name| *name != DEFAULT_SYNC_TARGET)
+        .map(String::from)
+        .collect();
+    if !extra_targets.is_empty() {
+        let config_for_targets = config.clone();
+        let vault_dir_for_targets = vault_dir.clone();
+        let options = SyncOptions {
+            cancel: op.token().clone(),
+            progress: transfer_progress(&app, SyncPhase::Push),
+            skip_newer: encrypted,
+            ..SyncOptions::for_sync(&config.sync)
+        };
+        tokio::task::spawn_blocking(move || {
+            for name in &extra_targets {
+                if options.cancel.is_cancelled() {
+                    break;
+                }
+                info!("[sync_vault] Pushing to target '{}'...", name);
+                let push_started = chrono::Utc::now().timestamp_millis();
+                let pushed = create_target_provider(&config_for_targets, name)
+                    .and_then(|provider| {
+                        if !provider.is_authenticated() {
+                            anyhow::bail!("Not authenticated");
+                        }
+                        let push_root = redacted_push_root(&config_for_targets, name, encrypted)
+                            .map_err(anyhow::Error::msg)?
+                            .unwrap_or_else(|| sync_root.clone());
+                        let options = SyncOptions {
+                            exclude: partition::excluded(&config_for_targets, name)?.paths,
+                            ..options.clone()
+                        };
+                        provider.push(&push_root, &options)
+                    })
+                    .map(|r| r.files_pushed)
+                    .map_err(|e| format!("{:#}", e));
+                if let Err(e) = &pushed {
+                    warn!("[sync_vault] Push to target '{}' failed: {}", name, e);
+                }
+                if !options.cancel.is_cancelled() {
+                    record_remote_sync(
+                        &vault_dir_for_targets,
+                        name,
+                        push_started,
+                        pushed.as_ref().copied().map_err(String::as_str),
+                    );
+                }
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    timer.finish(&config);
+    Ok(format!("Synced {} files", result.files_pushed))
+}
+
+/// Ghi kết quả sync với một target vào local.db (hiển thị ở `echovault-cli status`,
+/// và là mốc của lần delta push tiếp theo) và nhật ký sync của vault.db
+fn record_remote_sync(
+    vault_dir: &std::path::Path,
+    target: &str,
+    pushed_from: i64,
+    outcome: Result<usize, &str>,
+) {
+    if let Err(e) = delta::record_sync(vault_dir, target, pushed_from, outcome) {
+        warn!(
+            "[sync_vault] Failed to record sync state of {}: {}",
+            target, e
+        );
+    }
+    let target = target.to_string();
+    send_notification(match outcome {
+        Ok(files_pushed) => echovault_core::notifications::Event::SyncCompleted {
+            target,
+            files_pushed,
+        },
+        Err(error) => echovault_core::notifications::Event::SyncFailed {
+            target,
+            error: error.to_string(),
+        },
+    });
+}
+
+/// Thư mục push tới `target`: bản đã che secret khi `[redaction]` liệt kê
+/// target (không áp dụng cho vault mã hóa), `None` để push từ sync root
+fn redacted_push_root(
+    config: &Config,
+    target: &str,
+    encrypted: bool,
+) -> Result<Option<std::path::PathBuf>, String> {
+    let staged = echovault_core::redact::vault::push_root(config, target, encrypted)
+        .map_err(|e| format!("{:#}", e))?;
+    Ok(staged.map(|(root, stats)| {
+        info!(
+            "[sync_vault] Redacted {} secrets in {} changed files for '{}'",
+            stats.secrets, stats.redacted, target
+        );
+        if stats.unredacted > 0 {
+            warn!(
+                "[sync_vault] {} binary files pushed unredacted to '{}'",
+                stats.unredacted, target
+            );
+        }
+        root
+    }))
+}
+
+/// Gửi thông báo webhook (`[notifications]`) ở thread riêng, để retry không
+/// chặn sync
+fn send_notification(event: echovault_core::notifications::Event) {
+    let Ok(config) = Config::load_default() else {
+        return;
+    };
+    if config.notifications.webhooks.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        echovault_core::notifications::notify(&config.notifications, &event);
+    });
+}
+
+// ============ UTILITY COMMANDS ============
+
+/// Mở URL trong browser
+#[tauri::command]
+pub async fn open_url(url: String) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
         let mut cmd = std::process::Command::new("cmd");
         cmd.args(["/C", "start", "", &url]);
         cmd.creation_flags(CREATE_NO_WINDOW);
@@ -1100,7 +1585,7 @@ pub async fn read_file_content(path: String) -> Result<String, String> {
     let path = std::path::Path::new(&path);
 
     if !path.exists() {
-        return Err(format!("File not found: {}", path.display()));
+        return Err(tf("error.file_not_found", &[("path", &path.display())]));
     }
 
     // Giới hạn 50MB
@@ -1175,17 +1660,8 @@ pub async fn set_autostart(app: tauri::AppHandle, enabled: bool) -> Result<(), S
 /// Lấy export path từ config
 #[tauri::command]
 pub async fn get_export_path() -> Result<String, String> {
-    use echovault_core::config::default_vault_path;
-
     let config = Config::load_default().map_err(|e| e.to_string())?;
-    let export_path = config.export_path.unwrap_or_else(|| {
-        // Default export path is sibling of vault: data_dir/../exports
-        default_vault_path()
-            .parent()
-            .map(|p| p.join("exports"))
-            .unwrap_or_else(|| std::path::PathBuf::from("./exports"))
-    });
-    Ok(export_path.to_string_lossy().to_string())
+    Ok(config.export_dir().to_string_lossy().to_string())
 }
 
 /// Cập nhật export path
@@ -1200,6 +1676,73 @@ pub async fn set_export_path(path: String) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
+/// Di chuyển vault (file + database) sang thư mục mới, cập nhật config rồi khởi động lại app.
+///
+/// Tiến độ được gửi qua event `vault-relocate-progress`.
+#[tauri::command]
+pub async fn relocate_vault(app: tauri::AppHandle, new_path: String) -> Result<(), String> {
+    use echovault_core::config::default_config_path;
+    use std::sync::atomic::Ordering;
+    use tauri::Emitter;
+
+    let mut config = Config::load_default().map_err(|e| e.to_string())?;
+    if let Some(env) = config.env_overrides.iter().find(|o| o.key == "vault_path") {
+        return Err(tf("error.vault_path_env", &[("var", &env.var)]));
+    }
+    let new_path = std::path::absolute(&new_path).map_err(|e| e.to_string())?;
+    let old_path = config.vault_path.clone();
+
+    // Không cho sync/ingest ghi vào vault trong lúc di chuyển
+    if SYNC_IN_PROGRESS
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err(t("error.sync_in_progress").to_string());
+    }
+    let was_paused = crate::watcher::is_paused();
+    if !was_paused {
+        let _ = crate::watcher::set_paused(true);
+    }
+
+    let target = new_path.clone();
+    let emitter = app.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        if !old_path.is_dir() {
+            return Ok(None);
+        }
+        echovault_core::storage::relocate::relocate_vault(&old_path, &target, |progress| {
+            let _ = emitter.emit("vault-relocate-progress", progress);
+        })
+        .map(Some)
+    })
+    .await;
+
+    if !was_paused {
+        let _ = crate::watcher::set_paused(false);
+    }
+    release_sync_lock();
+    let report = result
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{:#}", e))?;
+    if let Some(report) = report {
+        info!(
+            "[relocate_vault] Moved {} files to {:?} (copied: {}, paths updated: {})",
+            report.files, new_path, report.copied, report.rewritten_paths
+        );
+        if let Some(leftover) = report.leftover {
+            warn!("[relocate_vault] Old vault left at {:?}", leftover);
+        }
+    }
+
+    config.vault_path = new_path;
+    config
+        .save(&default_config_path())
+        .map_err(|e| e.to_string())?;
+
+    // Watcher và state đang giữ đường dẫn cũ
+    app.restart()
+}
+
 /// Mở thư mục data trong file explorer
 #[tauri::command]
 pub async fn open_data_folder() -> Result<(), String> {
@@ -1258,6 +1801,41 @@ fn open_folder_in_explorer(path: &std::path::Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Helper: Mở folder chứa file trong file explorer và chọn file đó
+/// (Linux không có cách chung để chọn file, chỉ mở folder)
+fn reveal_in_explorer(path: &std::path::Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut cmd = std::process::Command::new("explorer");
+        // explorer cần "/select,<path>" là một argument duy nhất
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        cmd.arg(select);
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd.spawn().map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let folder = path.parent().unwrap_or(path);
+        std::process::Command::new("xdg-open")
+            .arg(folder)
+            .spawn()
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg("-R")
+            .arg(path)
+            .spawn()
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
 /// Response cho update check
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateCheckResult {
@@ -1268,111 +1846,28 @@ pub struct UpdateCheckResult {
 
 // ============ PARSE COMMANDS ============
 
-/// Kết quả parse sessions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ParseResult {
-    pub parsed: usize,
-    pub errors: usize,
-    pub skipped: usize,
-}
-
 /// Parse tất cả raw sessions trong vault thành clean Markdown.
 /// Output: vault/parsed/<source>/<session_id>.md
 #[tauri::command]
 pub async fn parse_sessions() -> Result<ParseResult, String> {
     let config = Config::load_default().map_err(|e| e.to_string())?;
-    let vault_dir = config.vault_path.clone();
-    let sessions_dir = vault_dir.join("sessions");
-
-    if !sessions_dir.exists() {
-        return Ok(ParseResult {
-            parsed: 0,
-            errors: 0,
-            skipped: 0,
-        });
-    }
-
-    let result = tokio::task::spawn_blocking(move || {
-        use echovault_core::parsers::{all_parsers, markdown_writer};
-
-        let parsers = all_parsers();
-        let parsed_dir = vault_dir.join("parsed");
-
-        let mut total_parsed = 0usize;
-        let mut total_errors = 0usize;
-        let mut total_skipped = 0usize;
-
-        for parser in &parsers {
-            let source_dir = sessions_dir.join(parser.source_name());
-            if !source_dir.exists() {
-                continue;
-            }
-
-            let (conversations, errors) =
-                echovault_core::parsers::parse_vault_source(parser.as_ref(), &sessions_dir);
-
-            total_errors += errors.len();
-            for (path, err) in &errors {
-                warn!("[parse_sessions] Error parsing {:?}: {}", path, err);
-            }
-
-            for conv in &conversations {
-                // Output path: parsed/<source>/<id>.md
-                let output_path = parsed_dir
-                    .join(&conv.source)
-                    .join(format!("{}.md", conv.id));
-
-                // Skip if already parsed and source hasn't changed
-                if output_path.exists() {
-                    let source_path = sessions_dir
-                        .join(&conv.source)
-                        .join(format!("{}.json", conv.id));
-                    let source_jsonl = sessions_dir
-                        .join(&conv.source)
-                        .join(format!("{}.jsonl", conv.id));
-
-                    let source_mtime = std::fs::metadata(&source_path)
-                        .or_else(|_| std::fs::metadata(&source_jsonl))
-                        .ok()
-                        .and_then(|m| m.modified().ok());
-                    let parsed_mtime = std::fs::metadata(&output_path)
-                        .ok()
-                        .and_then(|m| m.modified().ok());
-
-                    if let (Some(src_t), Some(dst_t)) = (source_mtime, parsed_mtime) {
-                        if dst_t >= src_t {
-                            total_skipped += 1;
-                            continue;
-                        }
-                    }
-                }
-
-                match markdown_writer::write_markdown(conv, &output_path) {
-                    Ok(()) => {
-                        total_parsed += 1;
-                    }
-                    Err(e) => {
-                        warn!("[parse_sessions] Error writing {:?}: {}", output_path, e);
-                        total_errors += 1;
-                    }
-                }
-            }
-        }
+    let op = RunningOperation::start();
+    let op_for_parse = op.clone();
+    let result = tokio::task::spawn_blocking(move || parse_vault(&config, &op_for_parse))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{:#}", e))?;
 
+    info!(
+        "[parse_sessions] Complete: {} parsed, {} errors, {} skipped in {} ms",
+        result.parsed, result.errors, result.skipped, result.duration_ms
+    );
+    if let Some(slowest) = result.slowest.first() {
         info!(
-            "[parse_sessions] Complete: {} parsed, {} errors, {} skipped",
-            total_parsed, total_errors, total_skipped
+            "[parse_sessions] Slowest: {:?} ({} ms)",
+            slowest.path, slowest.duration_ms
         );
-
-        ParseResult {
-            parsed: total_parsed,
-            errors: total_errors,
-            skipped: total_skipped,
-        }
-    })
-    .await
-    .map_err(|e| e.to_string())?;
-
+    }
     Ok(result)
 }
 
@@ -1397,6 +1892,24 @@ pub async fn read_parsed_session(source: String, session_id: String) -> Result<S
         .map_err(|e| format!("Failed to read parsed session: {}", e))
 }
 
+/// Hiện file của một session (parsed Markdown, hoặc raw file nếu chưa parse)
+/// trong file explorer
+#[tauri::command]
+pub async fn open_session(session_id: String) -> Result<(), String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    let path = tokio::task::spawn_blocking(move || {
+        echovault_core::parsers::session_file(&config.vault_path, &session_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Session not found: {}", session_id))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    info!("[open_session] Revealing {}", path.display());
+    reveal_in_explorer(&path)
+}
+
 /// Kiểm tra update thủ công
 #[tauri::command]
 pub async fn check_update_manual(app: tauri::AppHandle) -> Result<UpdateCheckResult, String> {
@@ -1415,7 +1928,7 @@ pub async fn check_update_manual(app: tauri::AppHandle) -> Result<UpdateCheckRes
             current_version: env!("CARGO_PKG_VERSION").to_string(),
             new_version: None,
         }),
-        Err(e) => Err(format!("Failed to check for updates: {}", e)),
+        Err(e) => Err(tf("error.update_check", &[("error", &e)])),
     }
 }
 
@@ -1500,7 +2013,7 @@ pub async fn start_interceptor(
         let guard = state.handle.lock().unwrap();
         if let Some(ref h) = *guard {
             if matches!(h.state(), InterceptorState::Running { .. }) {
-                return Err("Interceptor is already running".to_string());
+                return Err(t("error.interceptor_running").to_string());
             }
         }
     }
@@ -1650,15 +2163,26 @@ pub async fn save_embedding_config(request: SaveEmbeddingConfigRequest) -> Resul
         "ollama" => EmbeddingPreset::Ollama,
         "openai" => EmbeddingPreset::OpenAI,
         "custom" => EmbeddingPreset::Custom,
-        other => return Err(format!("Unknown preset: {}", other)),
+        other => return Err(tf("error.unknown_preset", &[("preset", &other)])),
     };
 
     let mut config = Config::load_default().map_err(|e| e.to_string())?;
     config.embedding.preset = preset;
     config.embedding.api_base = request.api_base;
-    config.embedding.api_key = request.api_key;
     config.embedding.model = request.model;
 
+    // API key lưu trong OS keyring; chỉ ghi vào config nếu keyring không khả dụng
+    match echovault_core::secrets::set_or_delete(
+        echovault_core::secrets::EMBEDDING_API_KEY,
+        request.api_key.as_deref(),
+    ) {
+        Ok(()) => config.embedding.api_key = None,
+        Err(e) => {
+            warn!("[save_embedding_config] Keyring unavailable: {:#}", e);
+            config.embedding.api_key = request.api_key;
+        }
+    }
+
     config
         .save(&default_config_path())
         .map_err(|e| e.to_string())?;
@@ -1681,17 +2205,25 @@ pub struct ProviderStatusResponse {
 /// Test embedding connection với config hiện tại
 #[tauri::command]
 pub async fn test_embedding_connection() -> Result<ProviderStatusResponse, String> {
-    use echovault_core::embedding::provider::{EmbeddingProvider, ProviderStatus};
+    use echovault_core::embedding::provider::ProviderStatus;
+    use echovault_core::embedding::{new_embedder, EmbeddingConfig};
 
     let config = Config::load_default().map_err(|e| e.to_string())?;
 
     let result = tokio::task::spawn_blocking(move || {
-        let provider = EmbeddingProvider::new(
-            &config.embedding.api_base,
-            config.embedding.api_key.as_deref(),
-            &config.embedding.model,
-        );
-        provider.check_provider_status()
+        let embedding_config = EmbeddingConfig {
+            backend: config.embedding.backend,
+            api_base: config.embedding.api_base.clone(),
+            api_key: config.embedding.resolved_api_key(),
+            model: config.embedding.model.clone(),
+            ..EmbeddingConfig::default()
+        };
+        match new_embedder(&embedding_config) {
+            Ok(provider) => provider.check_provider_status(),
+            Err(e) => ProviderStatus::Unavailable {
+                reason: format!("{:#}", e),
+            },
+        }
     })
     .await
     .map_err(|e| e.to_string())?;
@@ -1749,7 +2281,10 @@ pub async fn get_embedding_config() -> Result<serde_json::Value, String> {
     Ok(serde_json::json!({
         "preset": preset,
         "api_base": config.embedding.api_base,
-        "api_key": config.embedding.api_key,
+        "api_key": config
+            .embedding
+            .resolved_api_key()
+            .map(|key| key.expose_secret().to_string()),
         "model": config.embedding.model,
     }))
 }
@@ -1759,17 +2294,25 @@ pub async fn get_embedding_config() -> Result<serde_json::Value, String> {
 pub async fn embed_sessions() -> Result<EmbedResponse, String> {
     let config = Config::load_default().map_err(|e| e.to_string())?;
     let vault_dir = config.vault_path.clone();
+    let api_key = config.embedding.resolved_api_key();
     let embedding_config = echovault_core::embedding::EmbeddingConfig {
+        backend: config.embedding.backend,
         api_base: config.embedding.api_base,
-        api_key: config.embedding.api_key,
+        api_key,
         model: config.embedding.model,
         chunk_size: config.embedding.chunk_size,
         chunk_overlap: config.embedding.chunk_overlap,
         batch_size: config.embedding.batch_size,
+        concurrency: config.embedding.concurrency,
+        request_timeout_secs: config.embedding.request_timeout_secs,
+        max_retries: config.embedding.max_retries,
+        rerank: config.embedding.rerank.clone(),
     };
 
+    let op = RunningOperation::start();
+    let op_for_embed = op.clone();
     let result = tokio::task::spawn_blocking(move || {
-        echovault_core::embedding::embed_vault(&embedding_config, &vault_dir)
+        echovault_core::embedding::embed_vault(&embedding_config, &vault_dir, &op_for_embed)
     })
     .await
     .map_err(|e| e.to_string())?
@@ -1801,22 +2344,36 @@ pub async fn search_semantic(
 ) -> Result<Vec<SearchResultResponse>, String> {
     let config = Config::load_default().map_err(|e| e.to_string())?;
     let vault_dir = config.vault_path.clone();
+    let api_key = config.embedding.resolved_api_key();
     let embedding_config = echovault_core::embedding::EmbeddingConfig {
-        api_base: config.embedding.api_base,
-        api_key: config.embedding.api_key,
-        model: config.embedding.model,
+        backend: config.embedding.backend,
+        api_base: config.embedding.api_base.clone(),
+        api_key,
+        model: config.embedding.model.clone(),
         chunk_size: config.embedding.chunk_size,
         chunk_overlap: config.embedding.chunk_overlap,
         batch_size: config.embedding.batch_size,
+        concurrency: config.embedding.concurrency,
+        request_timeout_secs: config.embedding.request_timeout_secs,
+        max_retries: config.embedding.max_retries,
+        rerank: config.embedding.rerank.clone(),
     };
     let limit = limit.unwrap_or(10);
 
+    let timer = echovault_core::metrics::Timer::start(echovault_core::metrics::SEARCH_LATENCY_MS);
     let results = tokio::task::spawn_blocking(move || {
-        echovault_core::embedding::search_similar(&embedding_config, &vault_dir, &query, limit)
+        echovault_core::embedding::search_similar(
+            &embedding_config,
+            &vault_dir,
+            &query,
+            limit,
+            &Default::default(),
+        )
     })
     .await
     .map_err(|e| e.to_string())?
     .map_err(|e| e.to_string())?;
+    timer.finish(&config);
 
     Ok(results
         .into_iter()
@@ -1848,3 +2405,677 @@ pub async fn embedding_stats() -> Result<serde_json::Value, String> {
         "dimension": stats.dimension,
     }))
 }
+
+// ============ ANALYTICS COMMANDS ============
+
+/// Báo cáo token và chi phí theo tool và model, nhóm theo ngày, tuần hoặc
+/// tháng (cho dashboard)
+#[tauri::command]
+pub async fn get_cost_report(
+    period: echovault_core::analytics::Period,
+) -> Result<echovault_core::analytics::CostReport, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    // Parse các sessions mới hoặc đã đổi nên chạy ngoài async runtime
+    tokio::task::spawn_blocking(move || {
+        echovault_core::analytics::vault_cost_report(&config, period)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Số liệu cho màn hình biểu đồ: sessions theo ngày, messages theo model,
+/// workspaces hoạt động nhiều nhất trong `days` ngày gần đây
+#[tauri::command]
+pub async fn get_usage_dashboard(
+    days: u32,
+) -> Result<echovault_core::analytics::UsageDashboard, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        echovault_core::analytics::usage_dashboard(&config.vault_path, days)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Thống kê toàn vault: sessions theo source/workspace/tháng, messages theo
+/// role, models dùng nhiều nhất và những ngày bận rộn nhất
+#[tauri::command]
+pub async fn get_stats() -> Result<echovault_core::analytics::VaultStats, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    // Parse các sessions mới hoặc đã đổi nên chạy ngoài async runtime
+    tokio::task::spawn_blocking(move || echovault_core::analytics::vault_stats(&config.vault_path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+// ============ ANNOTATION COMMANDS ============
+
+/// Ghi chú, rating và cờ follow-up của một session (`null` nếu chưa có)
+#[tauri::command]
+pub async fn get_annotation(
+    session_id: String,
+) -> Result<Option<echovault_core::storage::Annotation>, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = echovault_core::storage::VaultDb::open(&config.vault_path)
+            .map_err(|e| e.to_string())?;
+        db.annotation(&session_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Cập nhật annotation của session; field bỏ trống giữ nguyên.
+/// Trả về `null` khi annotation trống và đã bị xóa
+#[tauri::command]
+pub async fn annotate_session(
+    session_id: String,
+    update: echovault_core::storage::AnnotationUpdate,
+) -> Result<Option<echovault_core::storage::Annotation>, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = echovault_core::storage::VaultDb::open(&config.vault_path)
+            .map_err(|e| e.to_string())?;
+        let annotation = db
+            .annotate(&session_id, &update)
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = echovault_core::parsers::markdown_writer::write_annotation(
+            &config.vault_path,
+            &session_id,
+            annotation.as_ref(),
+        ) {
+            warn!(
+                "[annotate_session] Failed to write annotation of {} to Markdown: {}",
+                session_id, e
+            );
+        }
+        Ok(annotation)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Danh sách annotations, mới sửa nhất trước (chỉ các session cần follow-up
+/// nếu `follow_up_only`)
+#[tauri::command]
+pub async fn list_annotations(
+    follow_up_only: bool,
+) -> Result<Vec<echovault_core::storage::Annotation>, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = echovault_core::storage::VaultDb::open(&config.vault_path)
+            .map_err(|e| e.to_string())?;
+        db.annotations(follow_up_only).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ============ DIGEST COMMANDS ============
+
+/// Ghi digest của ngày (hoặc tuần nếu `weekly`) chứa `date` (YYYY-MM-DD,
+/// mặc định: kỳ gần nhất đã kết thúc) vào vault
+#[tauri::command]
+pub async fn generate_digest(
+    weekly: bool,
+    date: Option<String>,
+) -> Result<echovault_core::digest::Digest, String> {
+    use echovault_core::analytics::Period;
+    use echovault_core::digest;
+
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let period = if weekly { Period::Week } else { Period::Day };
+    let date = match date {
+        Some(date) => date
+            .parse::<chrono::NaiveDate>()
+            .map_err(|e| tf("error.invalid_date", &[("error", &e)]))?,
+        None => digest::last_complete(period, chrono::Utc::now().date_naive()),
+    };
+
+    // Summary gọi chat API (blocking)
+    tokio::task::spawn_blocking(move || {
+        digest::generate(&config, period, date, true)
+            .map(|(digest, _)| digest)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Ghi các digest đã đến hạn và thông báo (nếu `[digest] notify`)
+async fn write_due_digests(app: &tauri::AppHandle, config: &Config) {
+    use echovault_core::analytics::Period;
+    use tauri_plugin_notification::NotificationExt;
+
+    let config_for_digest = config.clone();
+    let written = tokio::task::spawn_blocking(move || {
+        echovault_core::digest::generate_due(&config_for_digest)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result.map_err(|e| e.to_string()));
+
+    match written {
+        Ok(written) => {
+            for (digest, path) in written {
+                info!("[digest] {} -> {}", digest.title(), path.display());
+                if !config.digest.notify {
+                    continue;
+                }
+                let start = digest.start.to_string();
+                let end = digest.end.to_string();
+                let title = match digest.period {
+                    Period::Day => tf("notify.digest_daily", &[("start", &start)]),
+                    Period::Week => tf("notify.digest_weekly", &[("start", &start), ("end", &end)]),
+                    Period::Month => tf(
+                        "notify.digest_monthly",
+                        &[("month", &digest.start.format("%Y-%m"))],
+                    ),
+                };
+                let body = tf("notify.digest_body", &[("count", &digest.total)]);
+                if let Err(e) = app.notification().builder().title(title).body(body).show() {
+                    warn!("[digest] Cannot show notification: {}", e);
+                }
+            }
+        }
+        Err(e) => warn!("[digest] Failed to write due digests: {}", e),
+    }
+}
+
+// ============ WATCHER COMMANDS ============
+
+/// Tạm dừng capture (watchers bỏ qua mọi thay đổi cho đến khi resume)
+#[tauri::command]
+pub async fn pause_watcher() -> Result<(), String> {
+    crate::watcher::set_paused(true).map_err(|e| e.to_string())
+}
+
+/// Tiếp tục capture
+#[tauri::command]
+pub async fn resume_watcher() -> Result<(), String> {
+    crate::watcher::set_paused(false).map_err(|e| e.to_string())
+}
+
+/// Trạng thái watcher: `{ paused: bool }`
+#[tauri::command]
+pub async fn watcher_status() -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({ "paused": crate::watcher::is_paused() }))
+}
+
+// ============ QUICK SEARCH COMMANDS ============
+
+/// Label của cửa sổ quick-search (tạo trong lib.rs)
+pub const QUICK_SEARCH_WINDOW: &str = "quick-search";
+
+/// Một kết quả quick-search
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickSearchResult {
+    pub session_id: String,
+    pub source: String,
+    pub title: Option<String>,
+    pub workspace_name: Option<String>,
+    pub created_at: Option<String>,
+    pub vault_path: String,
+}
+
+/// Tìm nhanh sessions theo title/workspace/source trong vault.db.
+/// Không gọi embedding provider nên đủ nhanh để gọi theo từng phím gõ.
+#[tauri::command]
+pub async fn quick_search(
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickSearchResult>, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let vault_dir = config.vault_path.clone();
+    let limit = limit.unwrap_or(20);
+
+    tokio::task::spawn_blocking(move || {
+        let db = echovault_core::storage::VaultDb::open(&vault_dir).map_err(|e| e.to_string())?;
+        let entries = db.get_all_sessions().map_err(|e| e.to_string())?;
+        let duplicates = db.duplicate_ids().unwrap_or_default();
+
+        let needle = query.trim().to_lowercase();
+        let results = entries
+            .into_iter()
+            .filter(|entry| !duplicates.contains(&entry.id))
+            .filter(|entry| {
+                needle.is_empty()
+                    || entry.id.to_lowercase().contains(&needle)
+                    || entry.source.to_lowercase().contains(&needle)
+                    || entry
+                        .title
+                        .as_deref()
+                        .is_some_and(|t| t.to_lowercase().contains(&needle))
+                    || entry
+                        .workspace_name
+                        .as_deref()
+                        .is_some_and(|w| w.to_lowercase().contains(&needle))
+            })
+            .take(limit)
+            .map(|entry| QuickSearchResult {
+                session_id: entry.id,
+                source: entry.source,
+                title: entry.title,
+                workspace_name: entry.workspace_name,
+                created_at: entry.created_at,
+                vault_path: entry.vault_path,
+            })
+            .collect();
+
+        Ok(results)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Hiện cửa sổ quick-search và focus vào ô tìm kiếm
+#[tauri::command]
+pub async fn show_quick_search(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+
+    let window = app
+        .get_webview_window(QUICK_SEARCH_WINDOW)
+        .ok_or("Quick search window not found")?;
+    window.center().map_err(|e| e.to_string())?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Ẩn cửa sổ quick-search (Esc hoặc sau khi chọn kết quả)
+#[tauri::command]
+pub async fn hide_quick_search(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+
+    if let Some(window) = app.get_webview_window(QUICK_SEARCH_WINDOW) {
+        window.hide().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// ============ ENCRYPTION COMMANDS ============
+
+/// Trạng thái mã hóa: `{ enabled, unlocked, algorithm, kdf, recipients, has_recovery, created_at }`
+#[tauri::command]
+pub async fn get_encryption_status(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let encryption = VaultMetadata::load(&config.vault_path)
+        .ok()
+        .and_then(|meta| meta.encryption);
+    let unlocked = state.vault_key.lock().map_err(|e| e.to_string())?.is_some();
+
+    Ok(match encryption {
+        Some(meta) => serde_json::json!({
+            "enabled": true,
+            "unlocked": unlocked,
+            "algorithm": meta.algorithm,
+            "kdf": meta.kdf,
+            "recipients": meta.recipients,
+            "has_recovery": meta.recovery.is_some(),
+            "created_at": meta.created_at,
+        }),
+        None => serde_json::json!({ "enabled": false, "unlocked": false }),
+    })
+}
+
+/// Bật mã hóa end-to-end với passphrase mới.
+/// Nếu remote đã được mã hóa (hoặc đổi key) bởi máy khác thì dùng lại key đó (passphrase phải khớp).
+#[tauri::command]
+pub async fn setup_encryption(
+    state: State<'_, AppState>,
+    passphrase: SecretString,
+) -> Result<String, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let vault_dir = config.vault_path.clone();
+    let provider = state.provider.clone();
+    let options = SyncOptions::for_sync(&config.sync);
+    let candidate = passphrase.clone();
+
+    let (key, joined, is_age) = tokio::task::spawn_blocking(move || {
+        let mut metadata = VaultMetadata::load_or_create(&vault_dir).map_err(|e| e.to_string())?;
+
+        // Kéo remote vào staging để phát hiện vault đã mã hóa (hoặc đã đổi key) từ máy khác
+        {
+            let provider = provider.lock().map_err(|e| e.to_string())?;
+            if provider.is_authenticated() {
+                if let Err(e) = provider.pull(&staging::staging_dir(&vault_dir), &options) {
+                    warn!("[setup_encryption] Remote check failed: {}", e);
+                }
+            }
+        }
+
+        let remote = staging::remote_meta(&vault_dir)
+            .filter(|remote| metadata.encryption.as_ref() != Some(remote));
+        if metadata.is_encrypted() && remote.is_none() {
+            return Err(t("error.encryption_enabled").to_string());
+        }
+
+        let (meta, key, joined) = match remote {
+            Some(remote) if remote.is_age() => {
+                let key = unlock_age(&config, &remote)?;
+                (remote, key, true)
+            }
+            Some(remote) => {
+                let key = remote
+                    .unlock(candidate.expose_secret())
+                    .map_err(|e| e.to_string())?;
+                (remote, key, true)
+            }
+            None => {
+                let (meta, key) =
+                    EncryptionMeta::create(candidate.expose_secret()).map_err(|e| e.to_string())?;
+                (meta, key, false)
+            }
+        };
+
+        let is_age = meta.is_age();
+        metadata.encryption = Some(meta);
+        metadata.save(&vault_dir).map_err(|e| e.to_string())?;
+        staging::open(&vault_dir, &key, config.encryption.allow_missing_manifest)
+            .map_err(|e| e.to_string())?;
+        Ok::<_, String>((key, joined, is_age))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    *state.vault_key.lock().map_err(|e| e.to_string())? = Some(key);
+    if !is_age {
+        remember_passphrase(&passphrase);
+    }
+    info!(
+        "[setup_encryption] Encryption enabled (joined remote: {})",
+        joined
+    );
+
+    Ok(if joined {
+        "Joined existing encrypted vault".to_string()
+    } else {
+        "Encryption enabled".to_string()
+    })
+}
+
+/// Bật mã hóa age: mã hóa tới identity của máy này (tự tạo nếu chưa có) và các recipient thêm.
+/// Trả về public key của máy này.
+#[tauri::command]
+pub async fn setup_age_encryption(
+    state: State<'_, AppState>,
+    recipients: Vec<String>,
+) -> Result<String, String> {
+    use echovault_core::crypto::age_backend;
+
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    let (key, public_key) = tokio::task::spawn_blocking(move || {
+        let vault_dir = &config.vault_path;
+        let mut metadata = VaultMetadata::load_or_create(vault_dir).map_err(|e| e.to_string())?;
+        if metadata.is_encrypted() {
+            return Err(t("error.encryption_enabled").to_string());
+        }
+
+        let identity_path = config.encryption.identity_path();
+        let identity = if identity_path.exists() {
+            age_backend::load_identity(&identity_path).map_err(|e| e.to_string())?
+        } else {
+            let (identity, _) = age_backend::generate_identity();
+            age_backend::save_identity(&identity_path, identity.expose_secret())
+                .map_err(|e| e.to_string())?;
+            identity
+        };
+
+        let mut all = age_backend::recipients_of(identity.expose_secret());
+        let public_key = all.first().cloned().unwrap_or_default();
+        for recipient in recipients.into_iter().filter(|r| !r.trim().is_empty()) {
+            if !all.contains(&recipient) {
+                all.push(recipient);
+            }
+        }
+
+        let meta = EncryptionMeta::create_age(all).map_err(|e| format!("{:#}", e))?;
+        let key = meta
+            .unlock_with_identity(identity.expose_secret())
+            .map_err(|e| e.to_string())?;
+        metadata.encryption = Some(meta);
+        metadata.save(vault_dir).map_err(|e| e.to_string())?;
+        Ok::<_, String>((key, public_key))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    *state.vault_key.lock().map_err(|e| e.to_string())? = Some(key);
+    info!("[setup_age_encryption] age encryption enabled");
+    Ok(public_key)
+}
+
+/// Mở khóa vault age bằng identity file trong config
+fn unlock_age(config: &Config, meta: &EncryptionMeta) -> Result<VaultKey, String> {
+    let identity =
+        echovault_core::crypto::age_backend::load_identity(&config.encryption.identity_path())
+            .map_err(|e| format!("{:#}", e))?;
+    meta.unlock_with_identity(identity.expose_secret())
+        .map_err(|e| e.to_string())
+}
+
+/// Kiểm tra passphrase có đúng không (không mở khóa vault)
+#[tauri::command]
+pub async fn verify_passphrase(passphrase: SecretString) -> Result<bool, String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let meta = VaultMetadata::load(&config.vault_path)
+        .map_err(|e| e.to_string())?
+        .encryption
+        .ok_or("Encryption is not enabled")?;
+
+    tokio::task::spawn_blocking(move || meta.verify(passphrase.expose_secret()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Mở khóa vault: derive key và giữ trong bộ nhớ cho các lần sync.
+/// Vault age dùng identity file (bỏ qua passphrase).
+#[tauri::command]
+pub async fn unlock_vault(
+    state: State<'_, AppState>,
+    passphrase: SecretString,
+) -> Result<(), String> {
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let meta = VaultMetadata::load(&config.vault_path)
+        .map_err(|e| e.to_string())?
+        .encryption
+        .ok_or("Encryption is not enabled")?;
+
+    if meta.is_age() {
+        let key = unlock_age(&config, &meta)?;
+        *state.vault_key.lock().map_err(|e| e.to_string())? = Some(key);
+        info!("[unlock_vault] Vault unlocked with age identity");
+        return Ok(());
+    }
+
+    let candidate = passphrase.clone();
+    let key = tokio::task::spawn_blocking(move || meta.unlock(candidate.expose_secret()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    *state.vault_key.lock().map_err(|e| e.to_string())? = Some(key);
+    remember_passphrase(&passphrase);
+    info!("[unlock_vault] Vault unlocked");
+    Ok(())
+}
+
+/// Đổi passphrase: mã hóa lại toàn bộ vault bằng key mới (tiếp tục nếu lần trước bị gián đoạn)
+#[tauri::command]
+pub async fn rotate_encryption_key(
+    state: State<'_, AppState>,
+    old_passphrase: SecretString,
+    new_passphrase: SecretString,
+) -> Result<String, String> {
+    use std::sync::atomic::Ordering;
+
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let vault_dir = config.vault_path.clone();
+    let candidate = new_passphrase.clone();
+
+    // Không cho sync chạy song song trong lúc đổi key
+    if SYNC_IN_PROGRESS
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err(t("error.sync_in_progress").to_string());
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        echovault_core::crypto::rotate::rotate_key(
+            &vault_dir,
+            old_passphrase.expose_secret(),
+            candidate.expose_secret(),
+        )
+    })
+    .await;
+    release_sync_lock();
+    let (key, stats) = result
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{:#}", e))?;
+
+    *state.vault_key.lock().map_err(|e| e.to_string())? = Some(key);
+    remember_passphrase(&new_passphrase);
+    info!(
+        "[rotate_encryption_key] Rotated {} files (resumed: {})",
+        stats.rotated, stats.resumed
+    );
+
+    Ok(format!(
+        "Re-encrypted {} files",
+        stats.rotated + stats.skipped
+    ))
+}
+
+/// Tạo recovery code mới (thay thế code cũ). Cần vault đã mở khóa.
+/// Trả về danh sách từ - chỉ hiển thị một lần.
+#[tauri::command]
+pub async fn generate_recovery_code(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let key = state
+        .vault_key
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("Vault is locked")?;
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut metadata = VaultMetadata::load(&config.vault_path).map_err(|e| e.to_string())?;
+        let meta = metadata
+            .encryption
+            .as_mut()
+            .ok_or("Encryption is not enabled")?;
+        let code = meta.add_recovery(&key).map_err(|e| e.to_string())?;
+        metadata
+            .save(&config.vault_path)
+            .map_err(|e| e.to_string())?;
+        info!("[generate_recovery_code] New recovery code generated");
+        Ok::<Vec<String>, String>(code.words().into_iter().map(String::from).collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Khôi phục vault bằng recovery code và đặt passphrase mới (mã hóa lại toàn bộ vault)
+#[tauri::command]
+pub async fn recover_vault(
+    state: State<'_, AppState>,
+    recovery_code: SecretString,
+    new_passphrase: SecretString,
+) -> Result<String, String> {
+    use echovault_core::crypto::recovery::RecoveryCode;
+    use std::sync::atomic::Ordering;
+
+    let config = Config::load_default().map_err(|e| e.to_string())?;
+    let vault_dir = config.vault_path.clone();
+    let candidate = new_passphrase.clone();
+
+    if SYNC_IN_PROGRESS
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err(t("error.sync_in_progress").to_string());
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        let meta = VaultMetadata::load(&vault_dir)?
+            .encryption
+            .ok_or_else(|| anyhow::anyhow!("Encryption is not enabled"))?;
+        let code = RecoveryCode::parse(recovery_code.expose_secret())?;
+        let key = meta.unlock_with_recovery(&code)?;
+        echovault_core::crypto::rotate::rotate_with_key(&vault_dir, &key, candidate.expose_secret())
+    })
+    .await;
+    release_sync_lock();
+    let (key, stats) = result
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{:#}", e))?;
+
+    *state.vault_key.lock().map_err(|e| e.to_string())? = Some(key);
+    remember_passphrase(&new_passphrase);
+    info!(
+        "[recover_vault] Recovered, {} files re-encrypted",
+        stats.rotated
+    );
+
+    Ok(format!(
+        "Vault recovered, {} files re-encrypted",
+        stats.rotated + stats.skipped
+    ))
+}
+
+/// Lưu passphrase vào OS keyring để tự mở khóa ở lần khởi động sau (best-effort)
+fn remember_passphrase(passphrase: &SecretString) {
+    use echovault_core::secrets;
+    if let Err(e) = secrets::set(&secrets::vault_passphrase_key(), passphrase.expose_secret()) {
+        warn!("[encryption] Cannot store passphrase in keyring: {:#}", e);
+    }
+}
+
+/// Tự mở khóa vault khi khởi động bằng passphrase trong OS keyring (hoặc identity file với age)
+pub fn spawn_auto_unlock(vault_key: Arc<Mutex<Option<VaultKey>>>) {
+    std::thread::spawn(move || {
+        use echovault_core::secrets;
+
+        let Ok(config) = Config::load_default() else {
+            return;
+        };
+        let Some(meta) = VaultMetadata::load(&config.vault_path)
+            .ok()
+            .and_then(|metadata| metadata.encryption)
+        else {
+            return;
+        };
+
+        let unlocked = if meta.is_age() {
+            unlock_age(&config, &meta)
+        } else {
+            let Some(passphrase) = secrets::get_or_warn(&secrets::vault_passphrase_key()) else {
+                return;
+            };
+            meta.unlock(passphrase.expose_secret())
+                .map_err(|e| e.to_string())
+        };
+
+        match unlocked {
+            Ok(key) => {
+                if let Ok(mut slot) = vault_key.lock() {
+                    *slot = Some(key);
+                    info!("[encryption] Vault unlocked from keyring");
+                }
+            }
+            Err(e) => warn!("[encryption] Auto-unlock failed: {}", e),
+        }
+    });
+}
@@ -5,17 +5,26 @@
 //! - System tray with menu
 //! - Periodic background sync
 //! - Notifications when sync completes
+//! - Daily/weekly digests of new conversations after background syncs
 //! - Autostart on login
 //! - Auto-update on startup
+//! - Auto-extract when source session files change
+//! - Re-import when the vault is changed externally
+//! - Quick-search palette window (hidden until summoned, hides on blur)
+//! - Optional end-to-end encryption of synced data (passphrase unlock)
+//! - Secrets (passphrase, API keys) stored in the OS keyring
+//! - Tray labels, notifications and errors in the configured language
 
+use echovault_core::i18n::t;
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager,
+    AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder,
 };
 use tauri_plugin_updater::UpdaterExt;
 
 mod commands;
+mod watcher;
 
 /// Check for updates on app startup.
 /// If an update is available, prompt the user and install if accepted.
@@ -81,19 +90,50 @@ async fn check_for_updates(app: AppHandle) {
     }
 }
 
+/// Create the quick-search palette window.
+/// A small undecorated window that stays hidden until opened from the tray
+/// (or via `show_quick_search`) and hides itself again when it loses focus.
+/// The frontend renders the palette instead of the main UI based on the `window` query param.
+fn setup_quick_search_window(app: &tauri::App) -> tauri::Result<()> {
+    WebviewWindowBuilder::new(
+        app,
+        commands::QUICK_SEARCH_WINDOW,
+        WebviewUrl::App("index.html?window=quick-search".into()),
+    )
+    .title("EchoVault Search")
+    .inner_size(560.0, 380.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .visible(false)
+    .build()?;
+
+    Ok(())
+}
+
+/// ID of the tray icon (unique to avoid collision with other Tauri apps on Linux)
+pub(crate) const TRAY_ID: &str = "com.n24q02m.echovault";
+
+/// Tray menu with labels in the current language.
+pub(crate) fn tray_menu<R: Runtime, M: Manager<R>>(app: &M) -> tauri::Result<Menu<R>> {
+    // Toggle item "Show/Hide Window" - action depends on current visibility
+    let toggle = MenuItem::with_id(app, "toggle", t("tray.toggle"), true, None::<&str>)?;
+    let search = MenuItem::with_id(app, "search", t("tray.search"), true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", t("tray.quit"), true, None::<&str>)?;
+
+    Menu::with_items(app, &[&toggle, &search, &quit])
+}
+
 /// Setup system tray with menu.
 /// Uses a dynamic toggle item that changes between Show/Hide based on window state.
 /// On Linux, click events are not supported (AppIndicator protocol limitation),
 /// so the menu-based toggle is the primary way to show/hide the window.
 fn setup_tray(app: &tauri::App) -> tauri::Result<()> {
-    // Toggle item with label "Show/Hide Window" - action depends on current visibility
-    let toggle = MenuItem::with_id(app, "toggle", "Show/Hide Window", true, None::<&str>)?;
-    let quit = MenuItem::with_id(app, "quit", "Exit", true, None::<&str>)?;
-
-    let menu = Menu::with_items(app, &[&toggle, &quit])?;
+    let menu = tray_menu(app)?;
 
-    // Use unique ID to avoid collision with other Tauri apps on Linux
-    let _tray = TrayIconBuilder::with_id("com.n24q02m.echovault")
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
         .tooltip("EchoVault")
         .icon(app.default_window_icon().unwrap().clone())
@@ -109,6 +149,13 @@ fn setup_tray(app: &tauri::App) -> tauri::Result<()> {
                     }
                 }
             }
+            "search" => {
+                if let Some(window) = app.get_webview_window(commands::QUICK_SEARCH_WINDOW) {
+                    let _ = window.center();
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
             "quit" => {
                 app.exit(0);
             }
@@ -138,17 +185,31 @@ fn setup_tray(app: &tauri::App) -> tauri::Result<()> {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize tracing subscriber for structured logging
+    // (log_level in config or ECHOVAULT_LOG_LEVEL, default info)
+    let loaded = echovault_core::Config::load_default();
+    let log_level = loaded
+        .as_ref()
+        .ok()
+        .and_then(|c| c.log_level.clone())
+        .filter(|level| level.parse::<tracing::Level>().is_ok())
+        .unwrap_or_else(|| "info".to_string());
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("echovault=info".parse().unwrap())
-                .add_directive("echovault_core=info".parse().unwrap())
-                .add_directive("echovault_lib=info".parse().unwrap()),
+                .add_directive(format!("echovault={}", log_level).parse().unwrap())
+                .add_directive(format!("echovault_core={}", log_level).parse().unwrap())
+                .add_directive(format!("echovault_lib={}", log_level).parse().unwrap()),
         )
         .with_target(true)
         .init();
 
     tracing::info!("EchoVault starting...");
+    if let Ok(config) = &loaded {
+        for issue in config.validate() {
+            tracing::warn!("Config: {}", issue);
+        }
+        echovault_core::i18n::set_language(config.language());
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -162,6 +223,23 @@ pub fn run() {
         .manage(commands::InterceptorAppState::default())
         .setup(|app| {
             setup_tray(app)?;
+            setup_quick_search_window(app)?;
+
+            // Move plaintext secrets from echovault.toml into the OS keyring
+            if let Err(e) = echovault_core::secrets::migrate_default_config() {
+                tracing::warn!("Secrets migration failed: {:#}", e);
+            }
+            // Unlock an encrypted vault with the passphrase remembered in the keyring
+            commands::spawn_auto_unlock(app.state::<commands::AppState>().vault_key.clone());
+
+            // Ingest new/changed sessions as soon as source files settle
+            watcher::spawn_auto_extract(app.handle().clone());
+            // Ingest changes detected right before the last shutdown/crash
+            watcher::spawn_pending_replay(app.handle().clone());
+            // Re-import vault.db when another process changes the vault
+            watcher::spawn_vault_watcher(app.handle().clone());
+            // Follow pause/resume requests from the CLI
+            watcher::spawn_pause_monitor();
 
             // Spawn background task to check for updates
             let handle = app.handle().clone();
@@ -171,34 +249,54 @@ pub fn run() {
 
             Ok(())
         })
-        .on_window_event(|window, event| {
+        .on_window_event(|window, event| match event {
             // Minimize to tray instead of closing completely
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+            tauri::WindowEvent::CloseRequested { api, .. } => {
                 // Hide window instead of closing
                 let _ = window.hide();
                 api.prevent_close();
             }
+            // Quick-search palette behaves like a popup: dismiss when focus moves elsewhere
+            tauri::WindowEvent::Focused(false)
+                if window.label() == commands::QUICK_SEARCH_WINDOW =>
+            {
+                let _ = window.hide();
+            }
+            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             commands::check_setup_complete,
             commands::complete_setup,
             commands::get_config,
+            commands::validate_config,
+            commands::get_language,
+            commands::set_language,
+            commands::get_source_settings,
+            commands::set_source_settings,
+            commands::get_sync_settings,
+            commands::set_sync_settings,
+            commands::get_profiles,
+            commands::set_profile,
             commands::get_auth_status,
             commands::start_auth,
             commands::complete_auth,
             commands::scan_sessions,
             commands::sync_vault,
+            commands::get_sync_state,
+            commands::cancel_operations,
             commands::open_url,
             commands::read_file_content,
             // Parse commands
             commands::parse_sessions,
             commands::read_parsed_session,
+            commands::open_session,
             // Settings commands
             commands::get_app_info,
             commands::get_autostart_status,
             commands::set_autostart,
             commands::get_export_path,
             commands::set_export_path,
+            commands::relocate_vault,
             commands::open_data_folder,
             commands::open_logs_folder,
             commands::check_update_manual,
@@ -216,6 +314,33 @@ pub fn run() {
             commands::test_embedding_connection,
             commands::check_ollama,
             commands::get_embedding_config,
+            // Quick search commands
+            commands::quick_search,
+            commands::show_quick_search,
+            commands::hide_quick_search,
+            // Analytics commands
+            commands::get_cost_report,
+            commands::get_usage_dashboard,
+            commands::get_stats,
+            // Digest commands
+            commands::generate_digest,
+            // Annotation commands
+            commands::get_annotation,
+            commands::annotate_session,
+            commands::list_annotations,
+            // Watcher commands
+            commands::pause_watcher,
+            commands::resume_watcher,
+            commands::watcher_status,
+            // Encryption commands
+            commands::get_encryption_status,
+            commands::setup_encryption,
+            commands::setup_age_encryption,
+            commands::verify_passphrase,
+            commands::unlock_vault,
+            commands::rotate_encryption_key,
+            commands::generate_recovery_code,
+            commands::recover_vault,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,443 @@
+//! Background file watchers
+//!
+//! Auto-extract: watches extractor storage locations and runs an incremental
+//! ingest of the source that changed after a debounce window, instead of
+//! waiting for the next scheduled sync.
+//! Each enabled source gets its own watcher thread so debounce and ignore
+//! rules from the `[watcher]` config section apply per source.
+//!
+//! Vault watcher: watches `vault/sessions/` for changes made by another process
+//! (rclone bisync, Syncthing, manual edits) and re-imports them into vault.db.
+//!
+//! Events emitted to the frontend:
+//! - `session-detected`: source files changed (after debounce and ignore rules)
+//! - `ingest-complete`: the incremental ingest triggered by those changes finished
+//! - `vault-changed`: external vault changes were re-imported
+//!
+//! Capture can be paused without quitting the app. The pause marker in the
+//! config directory is the source of truth (shared with `echovault-cli pause`);
+//! a monitor thread mirrors it onto every watcher's `PauseHandle`.
+
+use echovault_core::extractors::storage_locations;
+use echovault_core::storage::VaultDb;
+use echovault_core::utils::power::defer_reason;
+use echovault_core::watcher::{
+    build_ignore_set, is_capture_paused, set_capture_paused, PauseHandle,
+};
+use echovault_core::{all_extractors, Config, Extractor, FileWatcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+use crate::commands::{
+    import_vault_sessions, ingest_sessions, release_sync_lock, RunningOperation, LAST_LOCAL_WRITE,
+    SYNC_IN_PROGRESS,
+};
+
+/// How often the pause monitor re-checks the shared pause marker
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Pause handles of all running watchers
+static PAUSE_HANDLES: Mutex<Vec<PauseHandle>> = Mutex::new(Vec::new());
+
+/// Register a watcher so it follows the global pause state.
+fn register_pause_handle(handle: PauseHandle) {
+    if is_capture_paused() {
+        handle.pause();
+    }
+    if let Ok(mut handles) = PAUSE_HANDLES.lock() {
+        handles.push(handle);
+    }
+}
+
+/// Apply a pause state to every running watcher.
+fn apply_pause_state(paused: bool) {
+    if let Ok(handles) = PAUSE_HANDLES.lock() {
+        for handle in handles.iter() {
+            if paused {
+                handle.pause();
+            } else {
+                handle.resume();
+            }
+        }
+    }
+}
+
+/// Pause or resume capture for all watchers (persisted across processes).
+pub fn set_paused(paused: bool) -> anyhow::Result<()> {
+    set_capture_paused(paused)?;
+    apply_pause_state(paused);
+    info!(
+        "[watcher] Capture {}",
+        if paused { "paused" } else { "resumed" }
+    );
+    Ok(())
+}
+
+/// Whether capture is currently paused.
+pub fn is_paused() -> bool {
+    is_capture_paused()
+}
+
+/// Spawn a thread that mirrors the shared pause marker onto all watchers,
+/// so `echovault-cli pause/resume` takes effect in the running app.
+pub fn spawn_pause_monitor() {
+    let spawned = std::thread::Builder::new()
+        .name("watcher-pause-monitor".into())
+        .spawn(|| {
+            let mut last = is_capture_paused();
+            apply_pause_state(last);
+            loop {
+                std::thread::sleep(PAUSE_POLL_INTERVAL);
+                let paused = is_capture_paused();
+                if paused != last {
+                    apply_pause_state(paused);
+                    last = paused;
+                }
+            }
+        });
+
+    if let Err(e) = spawned {
+        warn!("[watcher] Failed to spawn pause monitor: {}", e);
+    }
+}
+
+/// Payload of the `session-detected` event
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDetectedEvent {
+    pub source: String,
+    pub paths: Vec<String>,
+}
+
+/// Payload of the `ingest-complete` event
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestCompleteEvent {
+    pub source: String,
+    pub has_changes: bool,
+    pub error: Option<String>,
+}
+
+/// Payload of the `vault-changed` event
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultChangedEvent {
+    pub imported: usize,
+}
+
+/// Spawn one auto-extract watcher thread per enabled source.
+pub fn spawn_auto_extract(app: AppHandle) {
+    let config = match Config::load_default() {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("[auto_extract] Cannot load config, watcher disabled: {}", e);
+            return;
+        }
+    };
+
+    if !config.watcher.enabled {
+        info!("[auto_extract] Watcher disabled in config");
+        return;
+    }
+
+    for extractor in all_extractors() {
+        let source = extractor.source_name();
+        let settings = config.source(source);
+        if !settings.enabled || !config.watcher.is_source_enabled(source) {
+            info!("[auto_extract] {}: disabled in config", source);
+            continue;
+        }
+
+        let debounce = config.watcher.debounce_for(source);
+        let ignore = config.watcher.ignore_for(source).to_vec();
+        let app = app.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name(format!("auto-extract-{}", source))
+            .spawn(move || {
+                let locations = storage_locations(extractor.as_ref(), &settings);
+                if let Err(e) =
+                    run_source_watcher(&app, extractor.as_ref(), &locations, debounce, &ignore)
+                {
+                    warn!(
+                        "[auto_extract] {}: watcher stopped: {}",
+                        extractor.source_name(),
+                        e
+                    );
+                }
+            });
+
+        if let Err(e) = spawned {
+            warn!("[auto_extract] {}: failed to spawn thread: {}", source, e);
+        }
+    }
+}
+
+fn run_source_watcher(
+    app: &AppHandle,
+    extractor: &dyn Extractor,
+    locations: &[PathBuf],
+    debounce: Duration,
+    ignore: &[String],
+) -> anyhow::Result<()> {
+    let source = extractor.source_name();
+    let ignore_set = build_ignore_set(ignore)?;
+
+    let mut watcher = FileWatcher::new()?;
+    register_pause_handle(watcher.pause_handle());
+    let mut watched = 0usize;
+    for location in locations {
+        match watcher.watch(location) {
+            Ok(()) => watched += 1,
+            Err(e) => warn!(
+                "[auto_extract] {}: cannot watch {:?}: {}",
+                source, location, e
+            ),
+        }
+    }
+
+    if watched == 0 {
+        return Ok(());
+    }
+    info!(
+        "[auto_extract] {}: watching {} locations (debounce {:?})",
+        source, watched, debounce
+    );
+
+    while let Some(mut changed) = watcher.next_debounced(debounce) {
+        changed.retain(|path| !ignore_set.is_match(path));
+        if changed.is_empty() {
+            continue;
+        }
+
+        let _ = app.emit(
+            "session-detected",
+            SessionDetectedEvent {
+                source: source.to_string(),
+                paths: changed
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect(),
+            },
+        );
+
+        let config = match Config::load_default() {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("[auto_extract] {}: cannot load config: {}", source, e);
+                continue;
+            }
+        };
+
+        // Persist first so the batch survives a crash/shutdown before ingest
+        enqueue_pending(&config.vault_path, source, &changed);
+
+        // Defer on battery / metered network; the queued changes are replayed later
+        if let Some(reason) = defer_reason(&config.schedule) {
+            info!("[auto_extract] {}: ingest deferred: {}", source, reason);
+            continue;
+        }
+
+        info!(
+            "[auto_extract] {}: {} paths changed, running incremental ingest",
+            source,
+            changed.len()
+        );
+        let result = locked_ingest(&config.vault_path, source);
+
+        match &result {
+            Ok(has_changes) => info!(
+                "[auto_extract] {}: ingest complete: changes={}",
+                source, has_changes
+            ),
+            Err(e) => warn!("[auto_extract] {}: ingest failed: {}", source, e),
+        }
+
+        let _ = app.emit(
+            "ingest-complete",
+            IngestCompleteEvent {
+                source: source.to_string(),
+                has_changes: result.as_ref().is_ok_and(|changed| *changed),
+                error: result.err(),
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Record changed paths in the crash-safe pending queue (best-effort).
+fn enqueue_pending(vault_dir: &Path, source: &str, paths: &[PathBuf]) {
+    let result =
+        VaultDb::open(vault_dir).and_then(|mut db| db.enqueue_pending_changes(source, paths));
+    if let Err(e) = result {
+        warn!(
+            "[auto_extract] {}: cannot queue pending changes: {}",
+            source, e
+        );
+    }
+}
+
+/// Run an incremental ingest of `source` while holding the sync lock.
+///
+/// Waits for a running sync/ingest instead of skipping, since it may have
+/// scanned before the change landed. On success, clears the queued changes
+/// of the source detected before the ingest started.
+fn locked_ingest(vault_dir: &Path, source: &str) -> Result<bool, String> {
+    while SYNC_IN_PROGRESS
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        std::thread::sleep(Duration::from_secs(1));
+    }
+
+    let started = chrono::Utc::now().timestamp_millis();
+    let result = ingest_sessions(vault_dir, Some(&[source]), &RunningOperation::start());
+    if result.is_ok() {
+        let cleared = VaultDb::open(vault_dir)
+            .and_then(|db| db.clear_source_pending_changes(source, started));
+        if let Err(e) = cleared {
+            warn!("[auto_extract] Cannot clear pending changes: {}", e);
+        }
+    }
+    release_sync_lock();
+
+    result
+}
+
+/// Replay changes queued before the last shutdown/crash (runs on a background thread).
+pub fn spawn_pending_replay(app: AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("pending-replay".into())
+        .spawn(move || {
+            let Ok(config) = Config::load_default() else {
+                return;
+            };
+            let pending = VaultDb::open(&config.vault_path)
+                .and_then(|db| db.pending_changes())
+                .unwrap_or_default();
+            if pending.is_empty() {
+                return;
+            }
+
+            info!(
+                "[auto_extract] Replaying {} pending changes from previous run",
+                pending.len()
+            );
+            // One ingest and one event per source, like the source watchers
+            let sources: BTreeSet<&str> = pending.iter().map(|c| c.source.as_str()).collect();
+            for source in sources {
+                let result = locked_ingest(&config.vault_path, source);
+                let _ = app.emit(
+                    "ingest-complete",
+                    IngestCompleteEvent {
+                        source: source.to_string(),
+                        has_changes: result.as_ref().is_ok_and(|changed| *changed),
+                        error: result.err(),
+                    },
+                );
+            }
+        });
+
+    if let Err(e) = spawned {
+        warn!("[auto_extract] Failed to spawn pending replay: {}", e);
+    }
+}
+
+/// Spawn the vault watcher on a background thread.
+pub fn spawn_vault_watcher(app: AppHandle) {
+    let config = match Config::load_default() {
+        Ok(config) => config,
+        Err(e) => {
+            warn!(
+                "[vault_watcher] Cannot load config, watcher disabled: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    if !config.watcher.enabled {
+        return;
+    }
+
+    let vault_dir = config.vault_path.clone();
+    let debounce = Duration::from_secs(config.watcher.debounce_secs);
+
+    let spawned = std::thread::Builder::new()
+        .name("vault-watcher".into())
+        .spawn(move || {
+            if let Err(e) = run_vault_watcher(&app, vault_dir, debounce) {
+                warn!("[vault_watcher] Watcher stopped: {}", e);
+            }
+        });
+
+    if let Err(e) = spawned {
+        warn!("[vault_watcher] Failed to spawn thread: {}", e);
+    }
+}
+
+fn run_vault_watcher(
+    app: &AppHandle,
+    vault_dir: PathBuf,
+    debounce: Duration,
+) -> anyhow::Result<()> {
+    let sessions_dir = vault_dir.join("sessions");
+    std::fs::create_dir_all(&sessions_dir)?;
+
+    let mut watcher = FileWatcher::new()?;
+    register_pause_handle(watcher.pause_handle());
+    watcher.watch(&sessions_dir)?;
+    info!("[vault_watcher] Watching {:?}", sessions_dir);
+
+    while let Some(changed) = watcher.next_debounced(debounce) {
+        if changed.is_empty() || is_own_write(debounce) {
+            continue;
+        }
+
+        if SYNC_IN_PROGRESS
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            // The running sync imports vault sessions itself
+            continue;
+        }
+
+        info!(
+            "[vault_watcher] {} external changes, re-importing vault.db",
+            changed.len()
+        );
+        let result = import_vault_sessions(&vault_dir);
+        release_sync_lock();
+
+        match result {
+            Ok(imported) => {
+                info!("[vault_watcher] Imported {} sessions", imported);
+                let _ = app.emit("vault-changed", VaultChangedEvent { imported });
+            }
+            Err(e) => warn!("[vault_watcher] Import failed: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a change batch was most likely caused by this app's own sync/ingest.
+///
+/// A batch is returned `debounce` after its last event, so writes made while
+/// holding the sync lock show up shortly after the lock is released.
+fn is_own_write(debounce: Duration) -> bool {
+    if SYNC_IN_PROGRESS.load(Ordering::SeqCst) {
+        return true;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let last_write = LAST_LOCAL_WRITE.load(Ordering::SeqCst);
+    now.saturating_sub(last_write) <= debounce.as_secs() + 2
+}
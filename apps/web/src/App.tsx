@@ -2,6 +2,11 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useEffect, useRef, useState } from "react";
 import { Toaster, toast } from "sonner";
+import { EncryptionSettings } from "./EncryptionSettings";
+import { SourceSettings } from "./SourceSettings";
+import { SyncSettings } from "./SyncSettings";
+import { UsageDashboard } from "./UsageDashboard";
+import { VaultLocation } from "./VaultLocation";
 import { TextEditor } from "./TextEditor";
 
 // Types
@@ -45,6 +50,57 @@ interface UpdateCheckResult {
   new_version: string | null;
 }
 
+interface SessionDetectedEvent {
+  source: string;
+  paths: string[];
+}
+
+interface IngestCompleteEvent {
+  source: string;
+  has_changes: boolean;
+  error: string | null;
+}
+
+type SyncPhase = "idle" | "pull" | "import" | "ingest" | "parse" | "push";
+
+interface SyncState {
+  phase: SyncPhase;
+  files_done: number;
+  files_total: number;
+  bytes_done: number;
+  bytes_total: number;
+}
+
+const SYNC_PHASE_LABELS: Record<SyncPhase, string> = {
+  idle: "Syncing",
+  pull: "Pulling",
+  import: "Importing",
+  ingest: "Capturing",
+  parse: "Parsing",
+  push: "Pushing",
+};
+
+const SOURCE_LABELS: Record<string, string> = {
+  "vscode-copilot": "VS Code Copilot",
+  cursor: "Cursor",
+  cline: "Cline",
+  "roo-code": "Roo Code",
+  "continue-dev": "Continue.dev",
+  jetbrains: "JetBrains AI",
+  zed: "Zed",
+  antigravity: "Antigravity",
+  windsurf: "Windsurf",
+  "gemini-cli": "Gemini CLI",
+  "claude-code": "Claude Code",
+  "claude-desktop": "Claude Desktop",
+  aider: "Aider",
+  codex: "Codex",
+  opencode: "OpenCode",
+  chatgpt: "ChatGPT",
+};
+
+const sourceLabel = (source: string): string => SOURCE_LABELS[source] ?? source;
+
 // Views
 type View = "setup" | "main";
 
@@ -278,6 +334,13 @@ const PRESET_DEFAULTS: Record<EmbeddingPreset, { api_base: string; model: string
   custom: { api_base: "http://localhost:8000/v1", model: "nomic-embed-text" },
 };
 
+interface ConfigIssue {
+  level: "warning" | "error";
+  key: string;
+  message: string;
+  hint: string;
+}
+
 function SettingsOverlay({ onClose }: { onClose: () => void }) {
   const [appInfo, setAppInfo] = useState<AppInfo | null>(null);
   const [autoLaunch, setAutoLaunch] = useState(false);
@@ -296,6 +359,13 @@ function SettingsOverlay({ onClose }: { onClose: () => void }) {
   const [isSavingConfig, setIsSavingConfig] = useState(false);
   const [configDirty, setConfigDirty] = useState(false);
   const [ollamaAvailable, setOllamaAvailable] = useState<boolean | null>(null);
+  const [configIssues, setConfigIssues] = useState<ConfigIssue[]>([]);
+
+  useEffect(() => {
+    invoke<ConfigIssue[]>("validate_config")
+      .then(setConfigIssues)
+      .catch((err) => toast.error(`Failed to check config: ${String(err)}`));
+  }, []);
 
   useEffect(() => {
     const loadSettings = async () => {
@@ -517,6 +587,35 @@ function SettingsOverlay({ onClose }: { onClose: () => void }) {
               </div>
             </div>
 
+            {configIssues.length > 0 && (
+              <div className="mb-4">
+                <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+                  Configuration Problems
+                </h3>
+                <ul className="space-y-2 rounded-lg bg-[var(--bg-card)] p-3 text-xs">
+                  {configIssues.map((issue) => (
+                    <li key={`${issue.key}: ${issue.message}`}>
+                      <span
+                        className={issue.level === "error" ? "text-red-400" : "text-yellow-400"}
+                      >
+                        {issue.key}
+                      </span>
+                      : {issue.message}
+                      <p className="text-[var(--text-secondary)]">{issue.hint}</p>
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            )}
+
+            <EncryptionSettings />
+
+            <SourceSettings />
+
+            <SyncSettings />
+
+            <VaultLocation />
+
             {/* Embedding Provider Section */}
             <div className="mb-4">
               <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
@@ -686,7 +785,7 @@ function SettingsOverlay({ onClose }: { onClose: () => void }) {
 // ==================== MAIN APP ====================
 
 // Tabs
-type MainTab = "sessions" | "search";
+type MainTab = "sessions" | "search" | "stats";
 
 // Search result type matching Tauri SearchResultResponse
 interface SearchResult {
@@ -732,6 +831,7 @@ function MainApp() {
   const [isScanning, setIsScanning] = useState(false);
   const [expandedSources, setExpandedSources] = useState<Set<string>>(new Set());
   const [isSyncing, setIsSyncing] = useState(false);
+  const [syncState, setSyncState] = useState<SyncState | null>(null);
   // syncError is kept to be able to display toast notification in the future
   const [, setSyncError] = useState<string | null>(null);
   const [visibleCounts, setVisibleCounts] = useState<Record<string, number>>({});
@@ -790,12 +890,13 @@ function MainApp() {
     }
   };
 
-  const handleSync = async () => {
+  // background: scheduled sync, may be deferred on battery / metered network
+  const handleSync = async (background = false) => {
     if (isSyncing) return;
     setIsSyncing(true);
     setSyncError(null);
     try {
-      await invoke<string>("sync_vault");
+      await invoke<string>("sync_vault", { background });
       await loadSessions();
     } catch (err) {
       setSyncError(String(err));
@@ -804,6 +905,15 @@ function MainApp() {
     }
   };
 
+  // Stops running sync/parse/embed after their current step
+  const handleCancel = async () => {
+    try {
+      await invoke<number>("cancel_operations");
+    } catch (err) {
+      toast.error(`Failed to cancel: ${String(err)}`);
+    }
+  };
+
   const handleOpenFile = (session: SessionInfo) => {
     setViewingSession(session);
   };
@@ -902,9 +1012,9 @@ function MainApp() {
 
   useEffect(() => {
     // Initial sync after 10s (give app time to fully load)
-    const timeout = setTimeout(() => syncRef.current(), 10000);
+    const timeout = setTimeout(() => syncRef.current(true), 10000);
     // Periodic sync every 5 minutes (300000ms) to reduce RAM/CPU usage
-    const interval = setInterval(() => syncRef.current(), 300000);
+    const interval = setInterval(() => syncRef.current(true), 300000);
 
     // Listen for tray menu event
     const unlisten = listen("trigger-sync", () => syncRef.current());
@@ -916,6 +1026,43 @@ function MainApp() {
     };
   }, []);
 
+  // Watcher events: toast when sessions are captured in real time
+  const loadSessionsRef = useRef(loadSessions);
+  loadSessionsRef.current = loadSessions;
+
+  useEffect(() => {
+    const unlistenDetected = listen<SessionDetectedEvent>("session-detected", (event) => {
+      console.info(`Detected ${event.payload.paths.length} changes in ${event.payload.source}`);
+    });
+    const unlistenIngest = listen<IngestCompleteEvent>("ingest-complete", (event) => {
+      const { source, has_changes, error } = event.payload;
+      if (error) {
+        toast.error(`Capture failed for ${sourceLabel(source)}: ${error}`);
+      } else if (has_changes) {
+        toast.success(`New ${sourceLabel(source)} session captured`);
+        loadSessionsRef.current();
+      }
+    });
+
+    // Vault changed by another process (rclone bisync, Syncthing, manual edits)
+    const unlistenVault = listen("vault-changed", () => loadSessionsRef.current());
+
+    // Live progress of the running sync (pull, ingest, push, ...)
+    invoke<SyncState>("get_sync_state")
+      .then(setSyncState)
+      .catch(() => {});
+    const unlistenSync = listen<SyncState>("sync-progress", (event) =>
+      setSyncState(event.payload),
+    );
+
+    return () => {
+      unlistenDetected.then((fn) => fn());
+      unlistenIngest.then((fn) => fn());
+      unlistenVault.then((fn) => fn());
+      unlistenSync.then((fn) => fn());
+    };
+  }, []);
+
   return (
     <div className="flex h-full flex-col">
       {/* Header */}
@@ -930,7 +1077,22 @@ function MainApp() {
               {isSyncing ? (
                 <>
                   <div className="h-3 w-3 animate-spin rounded-full border-2 border-[var(--accent)] border-t-transparent" />
-                  <span className="text-[var(--text-secondary)]">Syncing...</span>
+                  <span className="text-[var(--text-secondary)]">
+                    {SYNC_PHASE_LABELS[syncState?.phase ?? "idle"]}
+                    {syncState && syncState.files_total > 0
+                      ? ` ${syncState.files_done}/${syncState.files_total}`
+                      : "..."}
+                    {syncState && syncState.bytes_total > 0
+                      ? ` (${formatFileSize(syncState.bytes_done)} / ${formatFileSize(syncState.bytes_total)})`
+                      : ""}
+                  </span>
+                  <button
+                    type="button"
+                    onClick={handleCancel}
+                    className="text-xs text-[var(--text-secondary)] underline hover:text-[var(--text-primary)]"
+                  >
+                    Cancel
+                  </button>
                 </>
               ) : (
                 <>
@@ -993,6 +1155,17 @@ function MainApp() {
           >
             Search
           </button>
+          <button
+            type="button"
+            onClick={() => setActiveTab("stats")}
+            className={`border-b-2 px-4 py-2 text-sm font-medium transition-colors ${
+              activeTab === "stats"
+                ? "border-[var(--accent)] text-[var(--accent)]"
+                : "border-transparent text-[var(--text-secondary)] hover:text-white"
+            }`}
+          >
+            Stats
+          </button>
         </div>
       </div>
 
@@ -1174,6 +1347,15 @@ function MainApp() {
                   "Build Index"
                 )}
               </button>
+              {isEmbedding && (
+                <button
+                  type="button"
+                  onClick={handleCancel}
+                  className="text-xs text-[var(--text-secondary)] underline hover:text-[var(--text-primary)]"
+                >
+                  Cancel
+                </button>
+              )}
             </div>
 
             {/* Search Results */}
@@ -1231,6 +1413,9 @@ function MainApp() {
             )}
           </div>
         )}
+
+        {/* ===== Stats Tab ===== */}
+        {activeTab === "stats" && <UsageDashboard />}
       </div>
 
       {/* TextEditor Overlay */}
@@ -0,0 +1,340 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+
+interface EncryptionStatus {
+  enabled: boolean;
+  unlocked: boolean;
+  algorithm?: string;
+  kdf?: string;
+  recipients?: string[];
+  has_recovery?: boolean;
+  created_at?: string;
+}
+
+const INPUT_CLASS =
+  "w-full rounded-md border border-[var(--border)] bg-[var(--bg-primary)] px-2.5 py-1.5 text-xs focus:border-[var(--accent)] focus:outline-none";
+
+// ==================== ENCRYPTION SETTINGS ====================
+// End-to-end encryption: setup, unlock (key held in memory), verify and key rotation
+export function EncryptionSettings() {
+  const [status, setStatus] = useState<EncryptionStatus | null>(null);
+  const [passphrase, setPassphrase] = useState("");
+  const [confirm, setConfirm] = useState("");
+  const [newPassphrase, setNewPassphrase] = useState("");
+  const [useAge, setUseAge] = useState(false);
+  const [recipients, setRecipients] = useState("");
+  const [recoveryWords, setRecoveryWords] = useState<string[] | null>(null);
+  const [recoveryCode, setRecoveryCode] = useState("");
+  const [isRecovering, setIsRecovering] = useState(false);
+  const [isBusy, setIsBusy] = useState(false);
+
+  const loadStatus = async () => {
+    try {
+      setStatus(await invoke<EncryptionStatus>("get_encryption_status"));
+    } catch (err) {
+      toast.error(`Failed to load encryption status: ${String(err)}`);
+    }
+  };
+
+  useEffect(() => {
+    loadStatus();
+  }, []);
+
+  const run = async (action: () => Promise<void>) => {
+    setIsBusy(true);
+    try {
+      await action();
+      setPassphrase("");
+      setConfirm("");
+      setNewPassphrase("");
+      setRecipients("");
+      setRecoveryCode("");
+      await loadStatus();
+    } catch (err) {
+      toast.error(String(err));
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  const handleSetup = () =>
+    run(async () => {
+      if (passphrase !== confirm) {
+        throw new Error("Passphrases do not match");
+      }
+      const message = await invoke<string>("setup_encryption", { passphrase });
+      toast.success(message);
+      if (message === "Encryption enabled") {
+        setRecoveryWords(await invoke<string[]>("generate_recovery_code"));
+      }
+    });
+
+  const handleSetupAge = () =>
+    run(async () => {
+      const publicKey = await invoke<string>("setup_age_encryption", {
+        recipients: recipients
+          .split(/[\s,]+/)
+          .map((r) => r.trim())
+          .filter(Boolean),
+      });
+      toast.success(`age encryption enabled. This machine's key: ${publicKey}`);
+    });
+
+  const handleGenerateRecovery = () =>
+    run(async () => {
+      setRecoveryWords(await invoke<string[]>("generate_recovery_code"));
+    });
+
+  const handleRecover = () =>
+    run(async () => {
+      if (newPassphrase !== confirm) {
+        throw new Error("New passphrases do not match");
+      }
+      const message = await invoke<string>("recover_vault", {
+        recoveryCode,
+        newPassphrase,
+      });
+      setIsRecovering(false);
+      toast.success(`${message}. Sync to upload the re-encrypted vault.`);
+    });
+
+  const handleUnlock = () =>
+    run(async () => {
+      await invoke("unlock_vault", { passphrase });
+      toast.success("Vault unlocked");
+    });
+
+  const handleVerify = () =>
+    run(async () => {
+      const ok = await invoke<boolean>("verify_passphrase", { passphrase });
+      if (ok) {
+        toast.success("Passphrase is correct");
+      } else {
+        toast.error("Incorrect passphrase");
+      }
+    });
+
+  const handleRotate = () =>
+    run(async () => {
+      if (newPassphrase !== confirm) {
+        throw new Error("New passphrases do not match");
+      }
+      const message = await invoke<string>("rotate_encryption_key", {
+        oldPassphrase: passphrase,
+        newPassphrase,
+      });
+      toast.success(`${message}. Sync to upload the re-encrypted vault.`);
+    });
+
+  if (!status) {
+    return null;
+  }
+
+  const isAge = status.algorithm === "age";
+  const ageSetup = !status.enabled && useAge;
+
+  if (recoveryWords) {
+    return (
+      <div className="mb-4">
+        <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+          Recovery Code
+        </h3>
+        <div className="space-y-2 rounded-lg bg-[var(--bg-card)] p-3">
+          <p className="text-xs text-[var(--text-secondary)]">
+            Write these words down and keep them offline. They unlock the vault if you forget the
+            passphrase, and are shown only once.
+          </p>
+          <ol className="grid list-decimal grid-cols-3 gap-1 pl-5 font-mono text-xs">
+            {recoveryWords.map((word, i) => (
+              // biome-ignore lint/suspicious/noArrayIndexKey: words can repeat
+              <li key={i}>{word}</li>
+            ))}
+          </ol>
+          <button
+            type="button"
+            onClick={() => setRecoveryWords(null)}
+            className="w-full rounded-md bg-[var(--accent)] py-1.5 text-xs font-medium text-white"
+          >
+            I have written it down
+          </button>
+        </div>
+      </div>
+    );
+  }
+
+  if (isRecovering) {
+    return (
+      <div className="mb-4">
+        <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+          Recover Vault
+        </h3>
+        <div className="space-y-2 rounded-lg bg-[var(--bg-card)] p-3">
+          <textarea
+            value={recoveryCode}
+            onChange={(e) => setRecoveryCode(e.target.value)}
+            placeholder="Recovery code (24 words)"
+            rows={3}
+            className={INPUT_CLASS}
+          />
+          <input
+            type="password"
+            value={newPassphrase}
+            onChange={(e) => setNewPassphrase(e.target.value)}
+            placeholder="New passphrase"
+            className={INPUT_CLASS}
+          />
+          <input
+            type="password"
+            value={confirm}
+            onChange={(e) => setConfirm(e.target.value)}
+            placeholder="Confirm new passphrase"
+            className={INPUT_CLASS}
+          />
+          <div className="flex gap-2">
+            <button
+              type="button"
+              onClick={() => setIsRecovering(false)}
+              className="flex-1 rounded-md border border-[var(--border)] py-1.5 text-xs"
+            >
+              Cancel
+            </button>
+            <button
+              type="button"
+              disabled={isBusy || !recoveryCode || !newPassphrase}
+              onClick={handleRecover}
+              className="flex-1 rounded-md bg-[var(--accent)] py-1.5 text-xs font-medium text-white disabled:opacity-50"
+            >
+              {isBusy ? "Working..." : "Recover"}
+            </button>
+          </div>
+        </div>
+      </div>
+    );
+  }
+
+  return (
+    <div className="mb-4">
+      <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+        Encryption
+      </h3>
+      <div className="space-y-2 rounded-lg bg-[var(--bg-card)] p-3">
+        <p className="text-xs text-[var(--text-secondary)]">
+          {!status.enabled
+            ? "Synced data is not encrypted."
+            : isAge
+              ? `Enabled (age, ${status.recipients?.length ?? 0} recipients) - ${
+                  status.unlocked ? "unlocked" : "locked. Check your identity file."
+                }`
+              : status.unlocked
+                ? `Enabled (${status.algorithm}, ${status.kdf}) - unlocked`
+                : "Enabled - locked. Enter your passphrase to sync."}
+        </p>
+        {!status.enabled && (
+          <label className="flex items-center gap-2 text-xs text-[var(--text-secondary)]">
+            <input
+              type="checkbox"
+              checked={useAge}
+              onChange={(e) => setUseAge(e.target.checked)}
+            />
+            Use age keys instead of a passphrase
+          </label>
+        )}
+        {ageSetup && (
+          <input
+            type="text"
+            value={recipients}
+            onChange={(e) => setRecipients(e.target.value)}
+            placeholder="Extra recipients (age1..., optional)"
+            className={INPUT_CLASS}
+          />
+        )}
+        {!isAge && !ageSetup && (
+          <input
+            type="password"
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            placeholder="Passphrase"
+            className={INPUT_CLASS}
+          />
+        )}
+        {!status.enabled && !useAge && (
+          <input
+            type="password"
+            value={confirm}
+            onChange={(e) => setConfirm(e.target.value)}
+            placeholder="Confirm passphrase"
+            className={INPUT_CLASS}
+          />
+        )}
+        {status.unlocked && !isAge && (
+          <>
+            <input
+              type="password"
+              value={newPassphrase}
+              onChange={(e) => setNewPassphrase(e.target.value)}
+              placeholder="New passphrase (to change it)"
+              className={INPUT_CLASS}
+            />
+            {newPassphrase && (
+              <input
+                type="password"
+                value={confirm}
+                onChange={(e) => setConfirm(e.target.value)}
+                placeholder="Confirm new passphrase"
+                className={INPUT_CLASS}
+              />
+            )}
+          </>
+        )}
+        {!(isAge && status.unlocked) && (
+          <button
+            type="button"
+            disabled={isBusy || (!passphrase && !ageSetup && !isAge)}
+            onClick={
+              ageSetup
+                ? handleSetupAge
+                : !status.enabled
+                  ? handleSetup
+                  : !status.unlocked
+                    ? handleUnlock
+                    : newPassphrase
+                      ? handleRotate
+                      : handleVerify
+            }
+            className="w-full rounded-md bg-[var(--accent)] py-1.5 text-xs font-medium text-white disabled:opacity-50"
+          >
+            {isBusy
+              ? "Working..."
+              : !status.enabled
+                ? "Enable Encryption"
+                : !status.unlocked
+                  ? "Unlock"
+                  : newPassphrase
+                    ? "Change Passphrase"
+                    : "Verify Passphrase"}
+          </button>
+        )}
+        {status.enabled && !isAge && status.unlocked && (
+          <button
+            type="button"
+            disabled={isBusy}
+            onClick={handleGenerateRecovery}
+            className="w-full rounded-md border border-[var(--border)] py-1.5 text-xs disabled:opacity-50"
+          >
+            {status.has_recovery ? "Replace Recovery Code" : "Generate Recovery Code"}
+          </button>
+        )}
+        {status.enabled && !isAge && !status.unlocked && status.has_recovery && (
+          <button
+            type="button"
+            onClick={() => setIsRecovering(true)}
+            className="w-full text-xs text-[var(--text-secondary)] underline"
+          >
+            Forgot passphrase? Use recovery code
+          </button>
+        )}
+      </div>
+    </div>
+  );
+}
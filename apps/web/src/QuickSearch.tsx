@@ -0,0 +1,106 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useRef, useState } from "react";
+
+interface QuickSearchResult {
+  session_id: string;
+  source: string;
+  title: string | null;
+  workspace_name: string | null;
+  created_at: string | null;
+  vault_path: string;
+}
+
+// ==================== QUICK SEARCH PALETTE ====================
+// Rendered in the separate "quick-search" window (see apps/tauri/src/lib.rs)
+export function QuickSearch() {
+  const [query, setQuery] = useState("");
+  const [results, setResults] = useState<QuickSearchResult[]>([]);
+  const [selected, setSelected] = useState(0);
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  // Focus input whenever the window is shown again
+  useEffect(() => {
+    const focus = () => inputRef.current?.focus();
+    focus();
+    window.addEventListener("focus", focus);
+    return () => window.removeEventListener("focus", focus);
+  }, []);
+
+  // Debounced search as the user types
+  useEffect(() => {
+    const timer = setTimeout(async () => {
+      try {
+        const found = await invoke<QuickSearchResult[]>("quick_search", {
+          query,
+          limit: 20,
+        });
+        setResults(found);
+        setSelected(0);
+      } catch (err) {
+        console.error("Quick search failed:", err);
+        setResults([]);
+      }
+    }, 150);
+    return () => clearTimeout(timer);
+  }, [query]);
+
+  const hide = () => invoke("hide_quick_search").catch(console.error);
+
+  const openResult = async (result: QuickSearchResult) => {
+    try {
+      await invoke("open_url", { url: result.vault_path });
+    } catch (err) {
+      console.error("Failed to open session:", err);
+    }
+    hide();
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === "Escape") {
+      hide();
+    } else if (e.key === "ArrowDown") {
+      e.preventDefault();
+      setSelected((i) => Math.min(i + 1, results.length - 1));
+    } else if (e.key === "ArrowUp") {
+      e.preventDefault();
+      setSelected((i) => Math.max(i - 1, 0));
+    } else if (e.key === "Enter" && results[selected]) {
+      openResult(results[selected]);
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col overflow-hidden rounded-xl bg-[var(--bg-primary)] text-[var(--text-primary)]">
+      <input
+        ref={inputRef}
+        value={query}
+        onChange={(e) => setQuery(e.target.value)}
+        onKeyDown={handleKeyDown}
+        placeholder="Search sessions..."
+        className="w-full border-b border-[var(--border)] bg-transparent px-4 py-3 text-sm outline-none"
+      />
+      <div className="flex-1 overflow-y-auto p-2">
+        {results.map((result, index) => (
+          <button
+            key={`${result.source}-${result.session_id}`}
+            type="button"
+            onMouseEnter={() => setSelected(index)}
+            onClick={() => openResult(result)}
+            className={`w-full rounded-lg px-3 py-2 text-left ${
+              index === selected ? "bg-[var(--accent)]/20" : ""
+            }`}
+          >
+            <p className="truncate text-sm font-medium">{result.title || result.session_id}</p>
+            <p className="truncate text-xs text-[var(--text-secondary)]">
+              {result.source}
+              {result.workspace_name ? ` · ${result.workspace_name}` : ""}
+            </p>
+          </button>
+        ))}
+        {results.length === 0 && (
+          <p className="py-8 text-center text-xs text-[var(--text-secondary)]">No sessions found</p>
+        )}
+      </div>
+    </div>
+  );
+}
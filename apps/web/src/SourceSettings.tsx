@@ -0,0 +1,142 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+
+interface SourceSetting {
+  name: string;
+  enabled: boolean;
+  extra_paths: string[];
+  exclude: string[];
+  max_file_size_mb: number | null;
+}
+
+const INPUT_CLASS =
+  "w-full rounded-md border border-[var(--border)] bg-[var(--bg-primary)] px-2.5 py-1.5 text-xs focus:border-[var(--accent)] focus:outline-none";
+
+const splitLines = (value: string) =>
+  value
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean);
+
+// ==================== SOURCE SETTINGS ====================
+// Per-source settings ([sources.<name>] in echovault.toml): enabled flag,
+// extra storage paths, exclude globs and max file size
+export function SourceSettings() {
+  const [sources, setSources] = useState<SourceSetting[]>([]);
+  const [editing, setEditing] = useState<SourceSetting | null>(null);
+  const [isBusy, setIsBusy] = useState(false);
+
+  const loadSources = async () => {
+    try {
+      setSources(await invoke<SourceSetting[]>("get_source_settings"));
+    } catch (err) {
+      toast.error(`Failed to load sources: ${String(err)}`);
+    }
+  };
+
+  useEffect(() => {
+    loadSources();
+  }, []);
+
+  const save = async (settings: SourceSetting) => {
+    setIsBusy(true);
+    try {
+      await invoke("set_source_settings", { settings });
+      await loadSources();
+      setEditing(null);
+    } catch (err) {
+      toast.error(String(err));
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  if (sources.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className="mb-4">
+      <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">Sources</h3>
+      <div className="space-y-1.5 rounded-lg bg-[var(--bg-card)] p-3">
+        {sources.map((source) => (
+          <div key={source.name}>
+            <div className="flex items-center justify-between text-xs">
+              <label className="flex items-center gap-2">
+                <input
+                  type="checkbox"
+                  checked={source.enabled}
+                  disabled={isBusy}
+                  onChange={(e) => save({ ...source, enabled: e.target.checked })}
+                />
+                {source.name}
+              </label>
+              <button
+                type="button"
+                onClick={() => setEditing(editing?.name === source.name ? null : source)}
+                className="text-[var(--text-secondary)] hover:text-white"
+              >
+                {editing?.name === source.name ? "Close" : "Edit"}
+              </button>
+            </div>
+
+            {editing?.name === source.name && (
+              <div className="mt-2 space-y-2">
+                <label className="block text-xs text-[var(--text-secondary)]">
+                  Extra paths (one per line)
+                  <textarea
+                    rows={2}
+                    value={editing.extra_paths.join("\n")}
+                    onChange={(e) => setEditing({ ...editing, extra_paths: e.target.value.split("\n") })}
+                    className={INPUT_CLASS}
+                  />
+                </label>
+                <label className="block text-xs text-[var(--text-secondary)]">
+                  Exclude globs (one per line)
+                  <textarea
+                    rows={2}
+                    value={editing.exclude.join("\n")}
+                    onChange={(e) => setEditing({ ...editing, exclude: e.target.value.split("\n") })}
+                    placeholder="**/scratch-*"
+                    className={INPUT_CLASS}
+                  />
+                </label>
+                <label className="block text-xs text-[var(--text-secondary)]">
+                  Max file size (MB)
+                  <input
+                    type="number"
+                    min={1}
+                    value={editing.max_file_size_mb ?? ""}
+                    onChange={(e) =>
+                      setEditing({
+                        ...editing,
+                        max_file_size_mb: e.target.value ? Number(e.target.value) : null,
+                      })
+                    }
+                    placeholder="No limit"
+                    className={INPUT_CLASS}
+                  />
+                </label>
+                <button
+                  type="button"
+                  disabled={isBusy}
+                  onClick={() =>
+                    save({
+                      ...editing,
+                      extra_paths: splitLines(editing.extra_paths.join("\n")),
+                      exclude: splitLines(editing.exclude.join("\n")),
+                    })
+                  }
+                  className="w-full rounded-md bg-[var(--accent)] py-1.5 text-xs font-medium text-white disabled:opacity-50"
+                >
+                  {isBusy ? "Saving..." : "Save"}
+                </button>
+              </div>
+            )}
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
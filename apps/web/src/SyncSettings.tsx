@@ -0,0 +1,106 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+
+interface SyncSetting {
+  transfers: number;
+  checkers: number;
+  bwlimit_kib: number | null;
+  timeout_secs: number | null;
+}
+
+const INPUT_CLASS =
+  "w-full rounded-md border border-[var(--border)] bg-[var(--bg-primary)] px-2.5 py-1.5 text-xs focus:border-[var(--accent)] focus:outline-none";
+
+const optionalNumber = (value: string) => (value ? Number(value) : null);
+
+// ==================== SYNC SETTINGS ====================
+// Transfer tuning ([sync] in echovault.toml): parallel transfers and checks,
+// bandwidth limit and stall timeout, used from the next sync
+export function SyncSettings() {
+  const [settings, setSettings] = useState<SyncSetting | null>(null);
+  const [isBusy, setIsBusy] = useState(false);
+
+  useEffect(() => {
+    invoke<SyncSetting>("get_sync_settings")
+      .then(setSettings)
+      .catch((err) => toast.error(`Failed to load sync settings: ${String(err)}`));
+  }, []);
+
+  const save = async () => {
+    if (!settings) return;
+    setIsBusy(true);
+    try {
+      await invoke("set_sync_settings", { settings });
+      toast.success("Sync settings saved");
+    } catch (err) {
+      toast.error(String(err));
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  if (!settings) {
+    return null;
+  }
+
+  return (
+    <div className="mb-4">
+      <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">Sync Transfers</h3>
+      <div className="space-y-2 rounded-lg bg-[var(--bg-card)] p-3">
+        <div className="grid grid-cols-2 gap-2">
+          <label className="block text-xs text-[var(--text-secondary)]">
+            Parallel transfers
+            <input
+              type="number"
+              min={1}
+              value={settings.transfers}
+              onChange={(e) => setSettings({ ...settings, transfers: Number(e.target.value) })}
+              className={INPUT_CLASS}
+            />
+          </label>
+          <label className="block text-xs text-[var(--text-secondary)]">
+            Parallel checks
+            <input
+              type="number"
+              min={1}
+              value={settings.checkers}
+              onChange={(e) => setSettings({ ...settings, checkers: Number(e.target.value) })}
+              className={INPUT_CLASS}
+            />
+          </label>
+          <label className="block text-xs text-[var(--text-secondary)]">
+            Bandwidth limit (KiB/s)
+            <input
+              type="number"
+              min={1}
+              value={settings.bwlimit_kib ?? ""}
+              onChange={(e) => setSettings({ ...settings, bwlimit_kib: optionalNumber(e.target.value) })}
+              placeholder="No limit"
+              className={INPUT_CLASS}
+            />
+          </label>
+          <label className="block text-xs text-[var(--text-secondary)]">
+            Stall timeout (s)
+            <input
+              type="number"
+              min={1}
+              value={settings.timeout_secs ?? ""}
+              onChange={(e) => setSettings({ ...settings, timeout_secs: optionalNumber(e.target.value) })}
+              placeholder="300"
+              className={INPUT_CLASS}
+            />
+          </label>
+        </div>
+        <button
+          type="button"
+          disabled={isBusy}
+          onClick={save}
+          className="w-full rounded-md bg-[var(--accent)] py-1.5 text-xs font-medium text-white disabled:opacity-50"
+        >
+          {isBusy ? "Saving..." : "Save"}
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,154 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+
+interface DayCount {
+  date: string;
+  count: number;
+}
+
+interface ModelCount {
+  model: string;
+  sessions: number;
+  messages: number;
+}
+
+interface WorkspaceActivity {
+  workspace: string;
+  sessions: number;
+  last_active: string | null;
+  weekly: DayCount[];
+}
+
+interface Dashboard {
+  since: string;
+  sessions_per_day: DayCount[];
+  messages_per_model: ModelCount[];
+  top_workspaces: WorkspaceActivity[];
+}
+
+const RANGES = [7, 30, 90];
+
+// Horizontal bar whose width is `value` relative to `max`
+function Bar({
+  label,
+  value,
+  max,
+  detail,
+}: {
+  label: string;
+  value: number;
+  max: number;
+  detail: string;
+}) {
+  return (
+    <div className="flex items-center gap-2 text-xs">
+      <span className="w-32 shrink-0 truncate text-[var(--text-secondary)]" title={label}>
+        {label}
+      </span>
+      <div className="h-2 flex-1 rounded-full bg-[var(--bg-primary)]">
+        <div
+          className="h-2 rounded-full bg-[var(--accent)]"
+          style={{ width: `${max > 0 ? (value / max) * 100 : 0}%` }}
+        />
+      </div>
+      <span className="w-20 shrink-0 text-right text-[var(--text-secondary)]">{detail}</span>
+    </div>
+  );
+}
+
+// ==================== USAGE DASHBOARD ====================
+// Sessions per day, messages per model and most active workspaces (from vault.db)
+export function UsageDashboard() {
+  const [days, setDays] = useState(30);
+  const [data, setData] = useState<Dashboard | null>(null);
+
+  useEffect(() => {
+    invoke<Dashboard>("get_usage_dashboard", { days })
+      .then(setData)
+      .catch((err) => toast.error(`Failed to load stats: ${String(err)}`));
+  }, [days]);
+
+  if (!data) {
+    return (
+      <div className="flex items-center justify-center py-8">
+        <div className="h-8 w-8 animate-spin rounded-full border-2 border-[var(--accent)] border-t-transparent" />
+      </div>
+    );
+  }
+
+  const maxDay = Math.max(0, ...data.sessions_per_day.map((d) => d.count));
+  const maxMessages = Math.max(0, ...data.messages_per_model.map((m) => m.messages));
+  const maxSessions = Math.max(0, ...data.top_workspaces.map((w) => w.sessions));
+
+  return (
+    <div className="space-y-4">
+      <div className="flex gap-2">
+        {RANGES.map((range) => (
+          <button
+            key={range}
+            type="button"
+            onClick={() => setDays(range)}
+            className={`rounded-md px-3 py-1 text-xs ${
+              days === range
+                ? "bg-[var(--accent)] text-white"
+                : "bg-[var(--bg-card)] text-[var(--text-secondary)] hover:text-white"
+            }`}
+          >
+            {range} days
+          </button>
+        ))}
+      </div>
+
+      <section className="rounded-lg bg-[var(--bg-card)] p-3">
+        <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+          Sessions per day
+        </h3>
+        {data.sessions_per_day.length === 0 ? (
+          <p className="text-xs text-[var(--text-secondary)]">No sessions since {data.since}</p>
+        ) : (
+          <div className="flex h-24 items-end gap-0.5">
+            {data.sessions_per_day.map((d) => (
+              <div
+                key={d.date}
+                title={`${d.date}: ${d.count}`}
+                className="flex-1 rounded-t bg-[var(--accent)]"
+                style={{ height: `${maxDay > 0 ? (d.count / maxDay) * 100 : 0}%` }}
+              />
+            ))}
+          </div>
+        )}
+      </section>
+
+      <section className="space-y-1.5 rounded-lg bg-[var(--bg-card)] p-3">
+        <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+          Messages per model
+        </h3>
+        {data.messages_per_model.map((m) => (
+          <Bar
+            key={m.model}
+            label={m.model}
+            value={m.messages}
+            max={maxMessages}
+            detail={`${m.messages} msgs`}
+          />
+        ))}
+      </section>
+
+      <section className="space-y-1.5 rounded-lg bg-[var(--bg-card)] p-3">
+        <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+          Most active workspaces
+        </h3>
+        {data.top_workspaces.map((w) => (
+          <Bar
+            key={w.workspace}
+            label={w.workspace}
+            value={w.sessions}
+            max={maxSessions}
+            detail={`${w.sessions} sessions`}
+          />
+        ))}
+      </section>
+    </div>
+  );
+}
@@ -0,0 +1,94 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+
+interface RelocateProgress {
+  files_done: number;
+  total_files: number;
+  bytes_done: number;
+  total_bytes: number;
+}
+
+const INPUT_CLASS =
+  "w-full rounded-md border border-[var(--border)] bg-[var(--bg-primary)] px-2.5 py-1.5 text-xs focus:border-[var(--accent)] focus:outline-none";
+
+// ==================== VAULT LOCATION ====================
+// Move the vault (session files + databases) to another folder; the app restarts afterwards
+export function VaultLocation() {
+  const [currentPath, setCurrentPath] = useState("");
+  const [newPath, setNewPath] = useState("");
+  const [progress, setProgress] = useState<RelocateProgress | null>(null);
+  const [isMoving, setIsMoving] = useState(false);
+
+  useEffect(() => {
+    invoke<{ vault_path: string }>("get_config")
+      .then((config) => {
+        setCurrentPath(config.vault_path);
+        setNewPath(config.vault_path);
+      })
+      .catch(() => {});
+
+    const unlisten = listen<RelocateProgress>("vault-relocate-progress", (event) =>
+      setProgress(event.payload),
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleMove = async () => {
+    setIsMoving(true);
+    setProgress(null);
+    try {
+      // Resolves only on failure: the app restarts once the vault has moved
+      await invoke("relocate_vault", { newPath: newPath.trim() });
+    } catch (err) {
+      toast.error(`Failed to move vault: ${String(err)}`);
+    } finally {
+      setIsMoving(false);
+    }
+  };
+
+  const percent =
+    progress && progress.total_bytes > 0
+      ? Math.round((progress.bytes_done / progress.total_bytes) * 100)
+      : 0;
+
+  return (
+    <div className="mb-4">
+      <h3 className="mb-2 text-xs font-medium uppercase text-[var(--text-secondary)]">
+        Vault Location
+      </h3>
+      <div className="space-y-2 rounded-lg bg-[var(--bg-card)] p-3">
+        <input
+          type="text"
+          value={newPath}
+          disabled={isMoving}
+          onChange={(e) => setNewPath(e.target.value)}
+          className={INPUT_CLASS}
+        />
+        {isMoving && progress && (
+          <div className="h-1.5 w-full rounded-full bg-[var(--bg-primary)]">
+            <div
+              className="h-1.5 rounded-full bg-[var(--accent)] transition-all"
+              style={{ width: `${percent}%` }}
+            />
+          </div>
+        )}
+        <button
+          type="button"
+          disabled={isMoving || !newPath.trim() || newPath.trim() === currentPath}
+          onClick={handleMove}
+          className="w-full rounded-md bg-[var(--accent)] py-1.5 text-xs font-medium text-white disabled:opacity-50"
+        >
+          {isMoving
+            ? progress
+              ? `Moving... ${progress.files_done}/${progress.total_files} files`
+              : "Moving..."
+            : "Move Vault"}
+        </button>
+      </div>
+    </div>
+  );
+}
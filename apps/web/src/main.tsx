@@ -1,10 +1,12 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import { QuickSearch } from "./QuickSearch";
 import "./index.css";
 
+// The quick-search window loads the same bundle with ?window=quick-search
+const isQuickSearch = new URLSearchParams(window.location.search).get("window") === "quick-search";
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
-  <React.StrictMode>
-    <App />
-  </React.StrictMode>
+  <React.StrictMode>{isQuickSearch ? <QuickSearch /> : <App />}</React.StrictMode>
 );